@@ -0,0 +1,14 @@
+//! Feed arbitrary output to the Claude log normalizer, which must never panic: every input
+//! yields either a conversation or an error.
+//!
+//! Run with `cargo fuzz run normalize_logs` from `backend/`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use vibe_kanban::{executor::Executor, executors::ClaudeExecutor};
+
+fuzz_target!(|data: &[u8]| {
+    let logs = String::from_utf8_lossy(data);
+    let _ = ClaudeExecutor::new().normalize_logs(&logs, "/tmp");
+});
@@ -0,0 +1,4 @@
+PRAGMA foreign_keys = ON;
+
+-- Add base_branch column to projects table; new worktrees are created from this branch
+ALTER TABLE projects ADD COLUMN base_branch TEXT NOT NULL DEFAULT 'main';
@@ -0,0 +1,14 @@
+PRAGMA foreign_keys = ON;
+
+-- Labels copied onto new tasks that are created without any, stored as a JSON array
+ALTER TABLE projects ADD COLUMN default_labels TEXT NOT NULL DEFAULT '[]';
+
+CREATE TABLE task_labels (
+    task_id    BLOB NOT NULL,
+    label      TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (task_id, label),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_labels_label ON task_labels(label);
@@ -0,0 +1,15 @@
+PRAGMA foreign_keys = ON;
+
+-- Files an executor wrote to its --output-dir, recorded once the run completes
+CREATE TABLE task_artifacts (
+    id                   BLOB PRIMARY KEY,
+    execution_process_id BLOB NOT NULL,
+    filename             TEXT NOT NULL,
+    size_bytes           INTEGER NOT NULL,
+    mime_type            TEXT NOT NULL,
+    stored_path          TEXT NOT NULL,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_artifacts_execution_process_id ON task_artifacts(execution_process_id);
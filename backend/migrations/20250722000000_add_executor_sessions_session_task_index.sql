@@ -0,0 +1,5 @@
+PRAGMA foreign_keys = ON;
+
+-- Speeds up finding every task attempt that ran with a (possibly shared) session ID
+CREATE INDEX idx_executor_sessions_session_id_task_attempt_id
+    ON executor_sessions(session_id, task_attempt_id);
@@ -0,0 +1,14 @@
+PRAGMA foreign_keys = ON;
+
+-- Periodic executor health probe results, trimmed to the last 24 hours per executor
+CREATE TABLE executor_health_history (
+    id            BLOB PRIMARY KEY,
+    executor_name TEXT NOT NULL,
+    healthy       BOOLEAN NOT NULL,
+    error         TEXT,
+    latency_ms    INTEGER NOT NULL,
+    timestamp     TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_executor_health_history_executor_name_timestamp
+    ON executor_health_history(executor_name, timestamp);
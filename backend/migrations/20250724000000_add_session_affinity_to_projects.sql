@@ -0,0 +1,6 @@
+PRAGMA foreign_keys = ON;
+
+-- How the project's tasks share Claude sessions, stored as JSON (see SessionAffinity)
+ALTER TABLE projects ADD COLUMN session_affinity TEXT NOT NULL DEFAULT '"none"';
+-- Session continued by tasks covered by the affinity, set after the first one completes
+ALTER TABLE projects ADD COLUMN pinned_session_id TEXT;
@@ -0,0 +1,16 @@
+PRAGMA foreign_keys = ON;
+
+-- In-app notifications about task progress, pushed to connected clients over WebSocket
+CREATE TABLE notifications (
+    id         BLOB PRIMARY KEY,
+    user_id    TEXT NOT NULL,
+    task_id    BLOB,
+    type       TEXT NOT NULL
+                  CHECK (type IN ('task_completed','task_failed','status_changed')),
+    message    TEXT NOT NULL,
+    read_at    TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_notifications_user_id_created_at ON notifications(user_id, created_at);
@@ -0,0 +1,4 @@
+PRAGMA foreign_keys = ON;
+
+-- Heuristic estimate of how much work a task is, derived from its title and description
+ALTER TABLE tasks ADD COLUMN complexity_score REAL NOT NULL DEFAULT 0;
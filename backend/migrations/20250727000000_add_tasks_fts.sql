@@ -0,0 +1,29 @@
+PRAGMA foreign_keys = ON;
+
+-- Full-text index over task titles and descriptions, used to find similar tasks
+CREATE VIRTUAL TABLE tasks_fts USING fts5(
+    task_id UNINDEXED,
+    title,
+    description
+);
+
+INSERT INTO tasks_fts (task_id, title, description)
+SELECT id, title, COALESCE(description, '') FROM tasks;
+
+CREATE TRIGGER tasks_fts_insert AFTER INSERT ON tasks
+BEGIN
+    INSERT INTO tasks_fts (task_id, title, description)
+    VALUES (new.id, new.title, COALESCE(new.description, ''));
+END;
+
+CREATE TRIGGER tasks_fts_update AFTER UPDATE OF title, description ON tasks
+BEGIN
+    UPDATE tasks_fts
+    SET title = new.title, description = COALESCE(new.description, '')
+    WHERE task_id = new.id;
+END;
+
+CREATE TRIGGER tasks_fts_delete AFTER DELETE ON tasks
+BEGIN
+    DELETE FROM tasks_fts WHERE task_id = old.id;
+END;
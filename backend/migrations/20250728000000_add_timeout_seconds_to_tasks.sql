@@ -0,0 +1,4 @@
+PRAGMA foreign_keys = ON;
+
+-- Seconds a task's executor may run before its process group is killed; NULL means no limit
+ALTER TABLE tasks ADD COLUMN timeout_seconds INTEGER;
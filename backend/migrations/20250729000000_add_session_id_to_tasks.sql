@@ -0,0 +1,4 @@
+PRAGMA foreign_keys = ON;
+
+-- Session of the task's latest coding agent run, so follow-ups can resume it
+ALTER TABLE tasks ADD COLUMN session_id TEXT;
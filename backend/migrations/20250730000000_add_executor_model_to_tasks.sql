@@ -0,0 +1,4 @@
+PRAGMA foreign_keys = ON;
+
+-- Model the task's Claude runs use, e.g. a cheaper one for routine work; NULL keeps the CLI default
+ALTER TABLE tasks ADD COLUMN executor_model TEXT;
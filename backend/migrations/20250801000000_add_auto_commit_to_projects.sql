@@ -0,0 +1,5 @@
+PRAGMA foreign_keys = ON;
+
+-- Commit a task's changes after each successful run; the message template may use {title} and {task_id}
+ALTER TABLE projects ADD COLUMN auto_commit BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE projects ADD COLUMN git_commit_template TEXT;
@@ -0,0 +1,15 @@
+PRAGMA foreign_keys = ON;
+
+-- Per-project Claude CLI settings that replace the globally detected command for the
+-- executor named by executor_type, e.g. 'claude'
+CREATE TABLE project_executor_config (
+    project_id    BLOB PRIMARY KEY,
+    executor_type TEXT NOT NULL,
+    binary_path   TEXT NOT NULL,
+    model         TEXT,
+    extra_flags   TEXT,
+    use_plan_mode BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
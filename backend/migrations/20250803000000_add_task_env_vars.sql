@@ -0,0 +1,13 @@
+PRAGMA foreign_keys = ON;
+
+-- Environment variables set on a task's executor processes; value holds the AES-256-GCM
+-- sealed value (nonce followed by ciphertext), never the plain text
+CREATE TABLE task_env_vars (
+    task_id    BLOB NOT NULL,
+    key        TEXT NOT NULL,
+    value      BLOB NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (task_id, key),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
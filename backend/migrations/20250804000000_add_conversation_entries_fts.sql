@@ -0,0 +1,12 @@
+PRAGMA foreign_keys = ON;
+
+-- Full-text index over the normalized conversation entries of coding agent runs. Runs are
+-- indexed when they finish; runs finished before this migration are indexed at startup,
+-- since normalizing their logs needs the executors
+CREATE VIRTUAL TABLE conversation_entries_fts USING fts5(
+    task_id UNINDEXED,
+    run_id UNINDEXED,
+    entry_type UNINDEXED,
+    content,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
@@ -0,0 +1,7 @@
+PRAGMA foreign_keys = ON;
+
+-- Tasks with a higher priority (1-100) take free executor slots first. A project's
+-- priority_boost_after_seconds raises a waiting task's priority by 1 per period, so
+-- low-priority tasks are not starved; NULL disables the boost
+ALTER TABLE tasks ADD COLUMN priority SMALLINT NOT NULL DEFAULT 50 CHECK (priority BETWEEN 1 AND 100);
+ALTER TABLE projects ADD COLUMN priority_boost_after_seconds INTEGER;
@@ -0,0 +1,7 @@
+PRAGMA foreign_keys = ON;
+
+-- Sub-tasks point at the task they were split out of; deleting the parent keeps them as
+-- top-level tasks
+ALTER TABLE tasks ADD COLUMN parent_task_id BLOB REFERENCES tasks(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_tasks_parent_task_id ON tasks(parent_task_id);
@@ -0,0 +1,6 @@
+PRAGMA foreign_keys = ON;
+
+-- Executor model for tasks created from the template, and the names of the environment
+-- variables (a JSON array) they are created with, left empty for the user to fill in
+ALTER TABLE task_templates ADD COLUMN executor_model TEXT;
+ALTER TABLE task_templates ADD COLUMN env_var_keys TEXT NOT NULL DEFAULT '[]';
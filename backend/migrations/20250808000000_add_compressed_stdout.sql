@@ -0,0 +1,6 @@
+PRAGMA foreign_keys = ON;
+
+-- Output of finished processes is moved here as a zstd frame and stdout is cleared; stdout
+-- only holds output while the process runs. Rows written before this column existed are
+-- compressed in batches of 100 when the server starts, since SQL cannot produce zstd.
+ALTER TABLE execution_processes ADD COLUMN stdout_zstd BLOB;
@@ -0,0 +1,5 @@
+PRAGMA foreign_keys = ON;
+
+-- Standing instructions given to the coding agent ahead of every task of the project
+ALTER TABLE projects ADD COLUMN system_prompt TEXT;
+ALTER TABLE projects ADD COLUMN prompt_prefix TEXT;
@@ -0,0 +1,6 @@
+PRAGMA foreign_keys = ON;
+
+-- Deleted tasks are archived first and only removed for good 30 days later
+ALTER TABLE tasks ADD COLUMN archived_at TEXT;
+
+CREATE INDEX idx_tasks_archived_at ON tasks(archived_at);
@@ -0,0 +1,15 @@
+PRAGMA foreign_keys = ON;
+
+-- Who changed which task or project and how; entries outlive the entities they describe
+CREATE TABLE audit_log (
+    id          BLOB PRIMARY KEY,
+    entity_type TEXT NOT NULL,
+    entity_id   BLOB NOT NULL,
+    action      TEXT NOT NULL,
+    actor_id    TEXT NOT NULL,
+    before_json TEXT,
+    after_json  TEXT,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_audit_log_entity_id_created_at ON audit_log(entity_id, created_at);
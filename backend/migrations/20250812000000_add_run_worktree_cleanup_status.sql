@@ -0,0 +1,5 @@
+PRAGMA foreign_keys = ON;
+
+-- Outcome of removing the worktree of a finished coding agent run; NULL until it is tried
+ALTER TABLE execution_processes ADD COLUMN cleanup_status TEXT
+    CHECK (cleanup_status IN ('success','not_found','error'));
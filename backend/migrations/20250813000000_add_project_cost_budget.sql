@@ -0,0 +1,6 @@
+PRAGMA foreign_keys = ON;
+
+-- Monthly spend cap for a project; NULL means unlimited
+ALTER TABLE projects ADD COLUMN monthly_cost_limit_usd REAL;
+-- Cost reported by the project's runs since the budget was last reset
+ALTER TABLE projects ADD COLUMN monthly_cost_usd_used REAL NOT NULL DEFAULT 0.0;
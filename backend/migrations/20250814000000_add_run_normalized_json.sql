@@ -0,0 +1,4 @@
+PRAGMA foreign_keys = ON;
+
+-- NormalizedConversation of a run, stored when its log is reparsed
+ALTER TABLE execution_processes ADD COLUMN normalized_json TEXT;
@@ -0,0 +1,17 @@
+PRAGMA foreign_keys = ON;
+
+-- One row per tool use in a finished run, so tool statistics do not need to parse logs
+CREATE TABLE tool_use_events (
+    id             BLOB PRIMARY KEY,
+    run_id         BLOB NOT NULL,
+    task_id        BLOB NOT NULL,
+    tool_name      TEXT NOT NULL,
+    action_type    TEXT NOT NULL,
+    content_length INTEGER NOT NULL,
+    created_at     TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (run_id) REFERENCES execution_processes(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_tool_use_events_task_id_tool_name ON tool_use_events(task_id, tool_name);
+CREATE INDEX idx_tool_use_events_created_at ON tool_use_events(created_at);
@@ -0,0 +1,42 @@
+PRAGMA foreign_keys = ON;
+
+-- Labels become project-scoped rows with a color, and task_labels links tasks to them
+CREATE TABLE labels (
+    id         BLOB PRIMARY KEY,
+    project_id BLOB NOT NULL,
+    name       TEXT NOT NULL,
+    color_hex  TEXT NOT NULL DEFAULT '#6b7280',
+    created_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    UNIQUE (project_id, name),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- Every label already given to a task becomes a label of the task's project
+INSERT INTO labels (id, project_id, name)
+SELECT randomblob(16), project_id, label
+FROM (
+    SELECT DISTINCT t.project_id, tl.label
+    FROM task_labels tl
+    JOIN tasks t ON t.id = tl.task_id
+);
+
+CREATE TABLE task_labels_new (
+    task_id    BLOB NOT NULL,
+    label_id   BLOB NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (task_id, label_id),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (label_id) REFERENCES labels(id) ON DELETE CASCADE
+);
+
+INSERT INTO task_labels_new (task_id, label_id, created_at)
+SELECT tl.task_id, l.id, tl.created_at
+FROM task_labels tl
+JOIN tasks t ON t.id = tl.task_id
+JOIN labels l ON l.project_id = t.project_id AND l.name = tl.label;
+
+DROP TABLE task_labels;
+
+ALTER TABLE task_labels_new RENAME TO task_labels;
+
+CREATE INDEX idx_task_labels_label_id ON task_labels(label_id);
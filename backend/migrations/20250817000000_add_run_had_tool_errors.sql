@@ -0,0 +1,7 @@
+PRAGMA foreign_keys = ON;
+
+-- Whether the run's normalized conversation has a failed tool use, set when the run finishes
+ALTER TABLE execution_processes ADD COLUMN had_tool_errors BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX idx_execution_processes_had_tool_errors
+    ON execution_processes(had_tool_errors) WHERE had_tool_errors;
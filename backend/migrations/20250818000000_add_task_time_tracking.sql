@@ -0,0 +1,6 @@
+PRAGMA foreign_keys = ON;
+
+-- Timing of the task's latest coding agent run; the duration is stored for fast aggregation
+ALTER TABLE tasks ADD COLUMN started_at TEXT;
+ALTER TABLE tasks ADD COLUMN completed_at TEXT;
+ALTER TABLE tasks ADD COLUMN total_execution_ms INTEGER;
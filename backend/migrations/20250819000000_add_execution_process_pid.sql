@@ -0,0 +1,8 @@
+PRAGMA foreign_keys = ON;
+
+-- PID of the process group leader, so running runs can be signalled by id
+ALTER TABLE execution_processes ADD COLUMN pid INTEGER;
+
+-- Start time of the process recorded in pid, in clock ticks since boot, so a process that
+-- reused the PID is never signalled in its place
+ALTER TABLE execution_processes ADD COLUMN pid_start_time INTEGER;
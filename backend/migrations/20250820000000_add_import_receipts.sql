@@ -0,0 +1,12 @@
+PRAGMA foreign_keys = ON;
+
+-- One row per imported project archive, so importing the same archive again returns the
+-- project created the first time; deleting that project allows importing it once more
+CREATE TABLE import_receipts (
+    original_project_id BLOB NOT NULL,
+    schema_version      INTEGER NOT NULL,
+    project_id          BLOB NOT NULL,
+    created_at          TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (original_project_id, schema_version),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
@@ -0,0 +1,15 @@
+PRAGMA foreign_keys = ON;
+
+-- API keys a project gives an executor instead of the server's own; key_encrypted holds the
+-- AES-256-GCM sealed key (its nonce followed by the ciphertext), key_hint its last characters
+CREATE TABLE project_api_keys (
+    id            BLOB PRIMARY KEY,
+    project_id    BLOB NOT NULL,
+    executor_type TEXT NOT NULL,
+    key_encrypted BLOB NOT NULL,
+    key_hint      TEXT NOT NULL,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    UNIQUE (project_id, executor_type),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
@@ -0,0 +1,4 @@
+PRAGMA foreign_keys = ON;
+
+-- How often the supervisor re-spawned a crashed run under the same id
+ALTER TABLE execution_processes ADD COLUMN restart_count INTEGER NOT NULL DEFAULT 0;
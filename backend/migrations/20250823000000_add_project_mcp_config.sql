@@ -0,0 +1,4 @@
+PRAGMA foreign_keys = ON;
+
+-- Claude CLI `--mcp-config` document written into the worktree of each run
+ALTER TABLE projects ADD COLUMN mcp_config_json TEXT;
@@ -0,0 +1,7 @@
+PRAGMA foreign_keys = ON;
+
+-- Summarize each finished coding agent run with a second, cheaper Claude call
+ALTER TABLE projects ADD COLUMN auto_summarize BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- One-paragraph summary of a finished run, NULL until it has been summarized
+ALTER TABLE execution_processes ADD COLUMN summary TEXT;
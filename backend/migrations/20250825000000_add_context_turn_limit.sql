@@ -0,0 +1,8 @@
+PRAGMA foreign_keys = ON;
+
+-- Start a fresh Claude session from a summary once a conversation has more turns than
+-- this, instead of resuming it; NULL never does
+ALTER TABLE projects ADD COLUMN max_context_turns INTEGER;
+
+-- Set on runs that replaced a session whose history had grown past that limit
+ALTER TABLE execution_processes ADD COLUMN was_context_truncated BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,5 @@
+PRAGMA foreign_keys = ON;
+
+-- Domains Claude runs may fetch from, as JSON arrays; NULL allows every domain not denied
+ALTER TABLE project_executor_config ADD COLUMN web_allowlist TEXT;
+ALTER TABLE project_executor_config ADD COLUMN web_denylist TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,4 @@
+PRAGMA foreign_keys = ON;
+
+-- How many output lines of the run were too large to store as they were
+ALTER TABLE execution_processes ADD COLUMN truncated_line_count INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+PRAGMA foreign_keys = ON;
+
+-- How Claude runs of the project receive their prompt on stdin: plain_text or structured_json
+ALTER TABLE project_executor_config ADD COLUMN stdin_format TEXT NOT NULL DEFAULT 'plain_text';
@@ -0,0 +1,4 @@
+PRAGMA foreign_keys = ON;
+
+-- Whether Claude runs of the project that time out resume their session instead of failing
+ALTER TABLE project_executor_config ADD COLUMN auto_resume_on_timeout BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,4 @@
+PRAGMA foreign_keys = ON;
+
+-- JSON object mapping the most used base commands of a finished run to how often they ran
+ALTER TABLE execution_processes ADD COLUMN command_counts TEXT;
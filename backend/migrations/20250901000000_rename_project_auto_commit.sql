@@ -0,0 +1,4 @@
+PRAGMA foreign_keys = ON;
+
+-- Every finished run is committed; the flag only picks git_commit_template as the message of successful runs
+ALTER TABLE projects RENAME COLUMN auto_commit TO use_commit_template;
@@ -0,0 +1,397 @@
+//! Per-project API keys for executors
+//!
+//! A project can give an executor its own API key instead of the one in the server's
+//! environment, e.g. to bill each team of a shared instance separately. Keys are sealed with
+//! [`SecretKey`] before they are stored, bound to their project and executor so a sealed
+//! key copied to another row does not open; only the last characters of long keys are kept
+//! in plain text as a hint.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::{
+    models::task::Task,
+    utils::encryption::{EncryptionError, SecretKey},
+};
+
+/// How many trailing characters of a key are kept as its hint
+const KEY_HINT_CHARS: usize = 4;
+
+/// Keys shorter than this are fully masked in their hint, which would give away too much of
+/// them otherwise
+const MIN_HINTED_KEY_CHARS: usize = 4 * KEY_HINT_CHARS;
+
+/// Environment variable an executor reads its API key from, `None` for executors that
+/// cannot be given one. The opencode executors and the Claude Code router pick their
+/// provider from their own configuration, so there is no single variable to set for them;
+/// codestral is checked for the server's `MISTRAL_API_KEY` before any run starts.
+pub fn api_key_env_var(executor_type: &str) -> Option<&'static str> {
+    match executor_type {
+        "claude" => Some("ANTHROPIC_API_KEY"),
+        "amp" => Some("AMP_API_KEY"),
+        "gemini" => Some("GEMINI_API_KEY"),
+        // aider runs Claude Sonnet when it finds an Anthropic key
+        "aider" => Some("ANTHROPIC_API_KEY"),
+        _ => None,
+    }
+}
+
+/// Pass a project's key to `command` in the variable `executor_type` reads it from,
+/// overriding the one inherited from the server
+pub fn apply_api_key_env(
+    command: &mut tokio::process::Command,
+    executor_type: &str,
+    api_key: Option<&str>,
+) {
+    if let (Some(var), Some(api_key)) = (api_key_env_var(executor_type), api_key) {
+        command.env(var, api_key);
+    }
+}
+
+/// A stored key, without the key itself
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ProjectApiKey {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    /// Executor the key is given to, e.g. `claude`
+    pub executor_type: String,
+    /// Last characters of the key, e.g. `…a1b2`, or `…****` for keys too short to show any
+    pub key_hint: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Deliberately not `Debug`, so the key cannot end up in logs
+#[derive(Deserialize, TS)]
+#[ts(export)]
+pub struct SetProjectApiKey {
+    pub api_key: String,
+}
+
+#[derive(Debug)]
+pub enum ApiKeyError {
+    Database(sqlx::Error),
+    Encryption(EncryptionError),
+    /// The executor does not read an API key from its environment
+    UnsupportedExecutor(String),
+    EmptyKey,
+}
+
+impl std::fmt::Display for ApiKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiKeyError::Database(e) => write!(f, "Database error: {}", e),
+            ApiKeyError::Encryption(e) => write!(f, "Encryption error: {}", e),
+            ApiKeyError::UnsupportedExecutor(executor_type) => {
+                write!(f, "Executor '{}' does not take an API key", executor_type)
+            }
+            ApiKeyError::EmptyKey => write!(f, "API key must not be empty"),
+        }
+    }
+}
+
+impl std::error::Error for ApiKeyError {}
+
+impl From<sqlx::Error> for ApiKeyError {
+    fn from(err: sqlx::Error) -> Self {
+        ApiKeyError::Database(err)
+    }
+}
+
+impl From<EncryptionError> for ApiKeyError {
+    fn from(err: EncryptionError) -> Self {
+        ApiKeyError::Encryption(err)
+    }
+}
+
+fn key_hint(api_key: &str) -> String {
+    let chars: Vec<char> = api_key.chars().collect();
+    if chars.len() < MIN_HINTED_KEY_CHARS {
+        return format!("…{}", "*".repeat(KEY_HINT_CHARS));
+    }
+    let hint: String = chars[chars.len() - KEY_HINT_CHARS..].iter().collect();
+    format!("…{}", hint)
+}
+
+/// Associated data binding a sealed key to its row
+fn key_aad(project_id: Uuid, executor_type: &str) -> Vec<u8> {
+    format!("project_api_keys:{}:{}", project_id, executor_type).into_bytes()
+}
+
+/// Encrypted storage of the `project_api_keys` table
+pub struct ProjectApiKeyStore;
+
+impl ProjectApiKeyStore {
+    /// Store the project's key for an executor, replacing the previous one. The key is
+    /// encrypted with the key derived from `VIBE_ENCRYPTION_KEY`.
+    pub async fn set(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        executor_type: &str,
+        api_key: &str,
+    ) -> Result<ProjectApiKey, ApiKeyError> {
+        if api_key_env_var(executor_type).is_none() {
+            return Err(ApiKeyError::UnsupportedExecutor(executor_type.to_string()));
+        }
+        let api_key = api_key.trim();
+        if api_key.is_empty() {
+            return Err(ApiKeyError::EmptyKey);
+        }
+
+        let id = Uuid::new_v4();
+        let key_encrypted = SecretKey::from_env()?
+            .encrypt_with_aad(api_key.as_bytes(), &key_aad(project_id, executor_type));
+        let hint = key_hint(api_key);
+        let stored = sqlx::query_as!(
+            ProjectApiKey,
+            r#"INSERT INTO project_api_keys (id, project_id, executor_type, key_encrypted, key_hint)
+               VALUES ($1, $2, $3, $4, $5)
+               ON CONFLICT(project_id, executor_type) DO UPDATE SET
+                   key_encrypted = excluded.key_encrypted,
+                   key_hint = excluded.key_hint,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", executor_type, key_hint, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            executor_type,
+            key_encrypted,
+            hint
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(stored)
+    }
+
+    /// The project's decrypted key for an executor, `None` when it has none
+    pub async fn get(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        executor_type: &str,
+    ) -> Result<Option<String>, ApiKeyError> {
+        let sealed = sqlx::query_scalar!(
+            "SELECT key_encrypted FROM project_api_keys WHERE project_id = $1 AND executor_type = $2",
+            project_id,
+            executor_type
+        )
+        .fetch_optional(pool)
+        .await?;
+        // Projects without keys work even when no encryption key is configured
+        let Some(sealed) = sealed else {
+            return Ok(None);
+        };
+
+        let api_key = String::from_utf8(
+            SecretKey::from_env()?
+                .decrypt_with_aad(&sealed, &key_aad(project_id, executor_type))?,
+        )
+        .map_err(|_| EncryptionError::Decrypt)?;
+        Ok(Some(api_key))
+    }
+
+    /// Load the key the project of `task_id` gives `executor_type`. A run whose key cannot
+    /// be loaded starts with the server's credentials.
+    pub async fn get_for_task(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        executor_type: &str,
+    ) -> Option<String> {
+        let project_id = match Task::find_by_id(pool, task_id).await {
+            Ok(Some(task)) => task.project_id,
+            Ok(None) => return None,
+            Err(e) => {
+                tracing::warn!("Failed to load the project of task {}: {}", task_id, e);
+                return None;
+            }
+        };
+        Self::get(pool, project_id, executor_type)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to load API key of project {}: {}", project_id, e);
+                None
+            })
+    }
+
+    /// The project's stored keys, by executor
+    pub async fn list(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<ProjectApiKey>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectApiKey,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", executor_type, key_hint, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_api_keys
+               WHERE project_id = $1
+               ORDER BY executor_type ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn delete(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        executor_type: &str,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM project_api_keys WHERE project_id = $1 AND executor_type = $2",
+            project_id,
+            executor_type
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_fixtures::{create_project, create_task, memory_pool},
+        utils::encryption::ENCRYPTION_KEY_ENV,
+    };
+
+    async fn setup() -> (SqlitePool, Uuid) {
+        std::env::set_var(ENCRYPTION_KEY_ENV, "test secret");
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        (pool, project_id)
+    }
+
+    #[test]
+    fn test_executors_read_their_key_from_their_own_variable() {
+        assert_eq!(api_key_env_var("claude"), Some("ANTHROPIC_API_KEY"));
+        assert_eq!(api_key_env_var("amp"), Some("AMP_API_KEY"));
+        assert_eq!(api_key_env_var("gemini"), Some("GEMINI_API_KEY"));
+        assert_eq!(api_key_env_var("aider"), Some("ANTHROPIC_API_KEY"));
+        assert_eq!(api_key_env_var("mistral"), None);
+        assert_eq!(api_key_env_var("sst-opencode"), None);
+        assert_eq!(api_key_env_var("echo"), None);
+    }
+
+    #[test]
+    fn test_api_key_is_passed_in_the_executor_variable() {
+        use std::ffi::OsStr;
+
+        let mut command = tokio::process::Command::new("true");
+        apply_api_key_env(&mut command, "gemini", Some("gemini-key"));
+        apply_api_key_env(&mut command, "echo", Some("ignored"));
+        apply_api_key_env(&mut command, "amp", None);
+
+        let envs: Vec<_> = command.as_std().get_envs().collect();
+        assert_eq!(
+            envs,
+            vec![(OsStr::new("GEMINI_API_KEY"), Some(OsStr::new("gemini-key")))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_task_runs_get_their_project_key() {
+        let (pool, project_id) = setup().await;
+        let task_id = create_task(&pool, project_id, "t").await;
+        ProjectApiKeyStore::set(&pool, project_id, "amp", "amp-test-key-c3d4")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            ProjectApiKeyStore::get_for_task(&pool, task_id, "amp")
+                .await
+                .as_deref(),
+            Some("amp-test-key-c3d4")
+        );
+        assert!(ProjectApiKeyStore::get_for_task(&pool, task_id, "gemini")
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_key_is_stored_encrypted_and_round_trips() {
+        let (pool, project_id) = setup().await;
+
+        let stored = ProjectApiKeyStore::set(&pool, project_id, "claude", "sk-ant-test-a1b2")
+            .await
+            .unwrap();
+        assert_eq!(stored.key_hint, "…a1b2");
+
+        let sealed: Vec<u8> = sqlx::query_scalar("SELECT key_encrypted FROM project_api_keys")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(!String::from_utf8_lossy(&sealed).contains("sk-ant"));
+        assert_eq!(
+            ProjectApiKeyStore::get(&pool, project_id, "claude")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("sk-ant-test-a1b2")
+        );
+        assert!(ProjectApiKeyStore::get(&pool, Uuid::new_v4(), "claude")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tampered_key_fails_to_decrypt() {
+        let (pool, project_id) = setup().await;
+        ProjectApiKeyStore::set(&pool, project_id, "claude", "sk-ant-test-a1b2")
+            .await
+            .unwrap();
+
+        let mut sealed: Vec<u8> = sqlx::query_scalar("SELECT key_encrypted FROM project_api_keys")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        *sealed.last_mut().unwrap() ^= 1;
+        sqlx::query("UPDATE project_api_keys SET key_encrypted = $1")
+            .bind(sealed)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            ProjectApiKeyStore::get(&pool, project_id, "claude").await,
+            Err(ApiKeyError::Encryption(EncryptionError::Decrypt))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_short_key_hint_is_fully_masked() {
+        let (pool, project_id) = setup().await;
+
+        let stored = ProjectApiKeyStore::set(&pool, project_id, "claude", "abcd")
+            .await
+            .unwrap();
+        assert_eq!(stored.key_hint, "…****");
+    }
+
+    #[tokio::test]
+    async fn test_key_copied_to_another_row_fails_to_decrypt() {
+        let (pool, project_id) = setup().await;
+        ProjectApiKeyStore::set(&pool, project_id, "claude", "sk-ant-test-a1b2")
+            .await
+            .unwrap();
+        ProjectApiKeyStore::set(&pool, project_id, "amp", "amp-test-key-c3d4")
+            .await
+            .unwrap();
+
+        sqlx::query(
+            "UPDATE project_api_keys SET key_encrypted = \
+             (SELECT key_encrypted FROM project_api_keys WHERE executor_type = 'claude') \
+             WHERE executor_type = 'amp'",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        assert!(matches!(
+            ProjectApiKeyStore::get(&pool, project_id, "amp").await,
+            Err(ApiKeyError::Encryption(EncryptionError::Decrypt))
+        ));
+    }
+}
@@ -5,7 +5,14 @@ use nix::{sys::signal::Signal, unistd::Pid};
 use tokio::sync::{Mutex, RwLock as TokioRwLock};
 use uuid::Uuid;
 
-use crate::services::{generate_user_id, AnalyticsConfig, AnalyticsService};
+use crate::{
+    db::multi_tenant::MultiTenantPool,
+    executor::{RunOutputChannels, RunProgressChannels},
+    executor_pool::ExecutorPool,
+    models::notification::{Notification, NotificationType},
+    services::{generate_user_id, AnalyticsConfig, AnalyticsService, NotificationService},
+    telemetry::RunTrace,
+};
 
 #[derive(Debug)]
 pub enum ExecutionType {
@@ -17,17 +24,31 @@ pub enum ExecutionType {
 #[derive(Debug)]
 pub struct RunningExecution {
     pub task_attempt_id: Uuid,
+    /// Database holding the execution's records
+    pub pool: sqlx::SqlitePool,
     pub _execution_type: ExecutionType,
     pub child: command_group::AsyncGroupChild,
+    /// Executor pool slot held while the process runs, freed when the entry is removed
+    pub _permit: Option<crate::executor_pool::ExecutorPermit>,
 }
 
 #[derive(Debug, Clone)]
 pub struct AppState {
     running_executions: Arc<Mutex<HashMap<Uuid, RunningExecution>>>,
     pub db_pool: sqlx::SqlitePool,
+    /// Per-project databases, when projects are stored apart; `db_pool` is then the meta
+    /// database
+    tenants: Option<Arc<MultiTenantPool>>,
     config: Arc<tokio::sync::RwLock<crate::models::config::Config>>,
     pub analytics: Arc<TokioRwLock<AnalyticsService>>,
     user_id: String,
+    /// One concurrency-limited pool per project, created on first use
+    executor_pools: Arc<std::sync::Mutex<HashMap<Uuid, ExecutorPool>>>,
+    max_concurrent_executors: usize,
+    run_output_channels: RunOutputChannels,
+    run_progress_channels: RunProgressChannels,
+    /// Spans of the traced runs, from their spawn until their completion is handled
+    run_traces: Arc<std::sync::Mutex<HashMap<Uuid, RunTrace>>>,
 }
 
 impl AppState {
@@ -47,9 +68,15 @@ impl AppState {
         Self {
             running_executions: Arc::new(Mutex::new(HashMap::new())),
             db_pool,
+            tenants: None,
             config,
             analytics,
             user_id: generate_user_id(),
+            executor_pools: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            max_concurrent_executors: ExecutorPool::max_concurrent_from_env(),
+            run_output_channels: RunOutputChannels::default(),
+            run_progress_channels: RunProgressChannels::default(),
+            run_traces: Arc::new(std::sync::Mutex::new(HashMap::new())),
         }
     }
 
@@ -79,7 +106,11 @@ impl AppState {
             .any(|exec| exec.task_attempt_id == attempt_id)
     }
 
-    pub async fn get_running_executions_for_monitor(&self) -> Vec<(Uuid, Uuid, bool, Option<i64>)> {
+    /// Take the executions that have exited, with their attempt ID, database, success and
+    /// exit code
+    pub async fn get_running_executions_for_monitor(
+        &self,
+    ) -> Vec<(Uuid, Uuid, sqlx::SqlitePool, bool, Option<i64>)> {
         let mut executions = self.running_executions.lock().await;
         let mut completed_executions = Vec::new();
 
@@ -91,6 +122,7 @@ impl AppState {
                     completed_executions.push((
                         *execution_id,
                         running_exec.task_attempt_id,
+                        running_exec.pool.clone(),
                         success,
                         exit_code,
                     ));
@@ -103,6 +135,7 @@ impl AppState {
                     completed_executions.push((
                         *execution_id,
                         running_exec.task_attempt_id,
+                        running_exec.pool.clone(),
                         false,
                         None,
                     ));
@@ -111,8 +144,12 @@ impl AppState {
         }
 
         // Remove completed executions from the map
-        for (execution_id, _, _, _) in &completed_executions {
+        let mut run_traces = self.run_traces.lock().unwrap();
+        for (execution_id, _, _, _, exit_code) in &completed_executions {
             executions.remove(execution_id);
+            if let Some(trace) = run_traces.get_mut(execution_id) {
+                trace.record_exit(*exit_code);
+            }
         }
 
         completed_executions
@@ -154,9 +191,117 @@ impl AppState {
 
         // only NOW remove it
         executions.remove(&execution_id);
+        self.take_run_trace(execution_id);
         Ok(true)
     }
 
+    /// Stop an execution by sending SIGTERM to its process group, then SIGKILL if it is still
+    /// running after `grace`. Returns `false` when the execution was not running.
+    pub async fn terminate_running_execution(
+        &self,
+        execution_id: Uuid,
+        grace: Duration,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        // Taken out of the map so the execution monitor does not handle its exit as a
+        // completion, and so the lock is not held while waiting for the process
+        let Some(mut exec) = self.running_executions.lock().await.remove(&execution_id) else {
+            return Ok(false);
+        };
+
+        if let Err(e) = terminate_child(&mut exec.child, grace).await {
+            // Put back so a failed cancel can be retried
+            self.running_executions
+                .lock()
+                .await
+                .insert(execution_id, exec);
+            return Err(e);
+        }
+        self.take_run_trace(execution_id);
+        Ok(true)
+    }
+
+    /// Store each project's data in its own database, see [`MultiTenantPool`]
+    pub fn with_tenants(mut self, tenants: Arc<MultiTenantPool>) -> Self {
+        self.tenants = Some(tenants);
+        self
+    }
+
+    /// Pool of the database holding the project's tasks
+    pub async fn project_pool(&self, project_id: Uuid) -> Result<sqlx::SqlitePool, sqlx::Error> {
+        match &self.tenants {
+            Some(tenants) => tenants.pool(project_id).await,
+            None => Ok(self.db_pool.clone()),
+        }
+    }
+
+    /// Pools of every database holding tasks, one per project when projects are stored apart
+    pub async fn task_pools(&self) -> Result<Vec<sqlx::SqlitePool>, sqlx::Error> {
+        match &self.tenants {
+            Some(tenants) => tenants.pools().await,
+            None => Ok(vec![self.db_pool.clone()]),
+        }
+    }
+
+    /// The database holding a record that requests only know by ID, found by asking each
+    /// task database whether it `contains` it. Without separate project databases this is
+    /// always the single database, so callers still look the record up themselves.
+    pub async fn find_task_pool<F, Fut>(
+        &self,
+        contains: F,
+    ) -> Result<Option<sqlx::SqlitePool>, sqlx::Error>
+    where
+        F: Fn(sqlx::SqlitePool) -> Fut,
+        Fut: std::future::Future<Output = Result<bool, sqlx::Error>>,
+    {
+        let Some(tenants) = &self.tenants else {
+            return Ok(Some(self.db_pool.clone()));
+        };
+        for pool in tenants.pools().await? {
+            if contains(pool.clone()).await? {
+                return Ok(Some(pool));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Delete the database of a deleted project, when projects are stored apart
+    pub async fn remove_project_database(&self, project_id: Uuid) -> std::io::Result<()> {
+        match &self.tenants {
+            Some(tenants) => tenants.remove(project_id).await,
+            None => Ok(()),
+        }
+    }
+
+    /// The pool limiting how many executors run at once for a project
+    pub fn executor_pool(&self, project_id: Uuid) -> ExecutorPool {
+        let mut pools = self.executor_pools.lock().unwrap();
+        pools
+            .entry(project_id)
+            .or_insert_with(|| ExecutorPool::new(self.max_concurrent_executors))
+            .clone()
+    }
+
+    /// Keep the spans of a run until its completion is handled
+    pub fn add_run_trace(&self, execution_id: Uuid, trace: RunTrace) {
+        self.run_traces.lock().unwrap().insert(execution_id, trace);
+    }
+
+    /// Take the spans of a finished run; dropping them ends its trace
+    pub fn take_run_trace(&self, execution_id: Uuid) -> Option<RunTrace> {
+        self.run_traces.lock().unwrap().remove(&execution_id)
+    }
+
+    /// Live stdout of the running processes, keyed by execution process ID
+    pub fn run_output_channels(&self) -> &RunOutputChannels {
+        &self.run_output_channels
+    }
+
+    /// Entries reported by the executors of the running processes, keyed by execution
+    /// process ID
+    pub fn run_progress_channels(&self) -> &RunProgressChannels {
+        &self.run_progress_channels
+    }
+
     // Config getters
     pub async fn get_sound_alerts_enabled(&self) -> bool {
         let config = self.config.read().await;
@@ -190,6 +335,25 @@ impl AppState {
         }
     }
 
+    pub fn user_id(&self) -> &str {
+        &self.user_id
+    }
+
+    /// Record an in-app notification for the local user in `pool`, the database of the task
+    /// it is about, and push it to their connected clients
+    pub async fn send_notification(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Option<Uuid>,
+        notification_type: NotificationType,
+        message: &str,
+    ) {
+        match Notification::create(pool, &self.user_id, task_id, notification_type, message).await {
+            Ok(notification) => NotificationService::send(&self.user_id, notification),
+            Err(e) => tracing::error!("Failed to record notification: {}", e),
+        }
+    }
+
     pub async fn update_sentry_scope(&self) {
         let config = self.get_config().read().await;
         let username = config.github.username.clone();
@@ -215,3 +379,24 @@ impl AppState {
         });
     }
 }
+
+/// Send SIGTERM to the child's process group, then kill the group if it is still running
+/// after `grace`
+async fn terminate_child(
+    child: &mut command_group::AsyncGroupChild,
+    grace: Duration,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        use nix::sys::signal::killpg;
+
+        killpg(Pid::from_raw(pid as i32), Signal::SIGTERM)?;
+    }
+
+    if tokio::time::timeout(grace, child.wait()).await.is_err() {
+        // command_group kills the whole group
+        child.kill().await?;
+        child.wait().await?;
+    }
+    Ok(())
+}
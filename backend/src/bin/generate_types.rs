@@ -9,11 +9,14 @@ export const EXECUTOR_TYPES: string[] = [
     "echo",
     "claude",
     "claude-plan",
+    "claude-plan-then-execute",
     "amp",
     "gemini",
     "charm-opencode",
     "claude-code-router",
-    "sst-opencode"
+    "sst-opencode",
+    "aider",
+    "mistral"
 ];
 
 export const EDITOR_TYPES: EditorType[] = [
@@ -29,11 +32,14 @@ export const EXECUTOR_LABELS: Record<string, string> = {
     "echo": "Echo (Test Mode)",
     "claude": "Claude",
     "claude-plan": "Claude Plan",
+    "claude-plan-then-execute": "Claude Plan then Execute",
     "amp": "Amp",
     "gemini": "Gemini",
     "charm-opencode": "Charm Opencode",
     "claude-code-router": "Claude Code Router",
-    "sst-opencode": "SST Opencode"
+    "sst-opencode": "SST Opencode",
+    "aider": "Aider",
+    "mistral": "Mistral Codestral"
 };
 
 export const EDITOR_LABELS: Record<string, string> = {
@@ -88,28 +94,95 @@ fn generate_types_content() -> String {
         vibe_kanban::routes::config::ConfigConstants::decl(),
         vibe_kanban::executor::ExecutorConfig::decl(),
         vibe_kanban::executor::ExecutorConstants::decl(),
+        vibe_kanban::executor::ValidationReport::decl(),
+        vibe_kanban::executors::claude::ClaudeBinary::decl(),
+        vibe_kanban::executors::claude::StdinFormat::decl(),
+        vibe_kanban::executors::claude::ToolResult::decl(),
+        vibe_kanban::executor_supervisor::RestartPolicy::decl(),
         vibe_kanban::models::project::CreateProject::decl(),
         vibe_kanban::models::project::Project::decl(),
         vibe_kanban::models::project::ProjectWithBranch::decl(),
         vibe_kanban::models::project::UpdateProject::decl(),
+        vibe_kanban::models::project::SessionAffinity::decl(),
         vibe_kanban::models::project::SearchResult::decl(),
         vibe_kanban::models::project::SearchMatchType::decl(),
         vibe_kanban::models::project::GitBranch::decl(),
+        vibe_kanban::models::project::ProjectGitStatus::decl(),
         vibe_kanban::models::project::CreateBranch::decl(),
+        vibe_kanban::models::project::SetProjectBaseBranch::decl(),
+        vibe_kanban::models::project::SetProjectSprint::decl(),
+        vibe_kanban::models::project::ProjectPrompts::decl(),
+        vibe_kanban::models::project::UpdateProjectPrompts::decl(),
+        vibe_kanban::models::project::ProjectMcpConfig::decl(),
+        vibe_kanban::models::project::ProjectBudget::decl(),
+        vibe_kanban::models::project::SetProjectBudget::decl(),
+        vibe_kanban::models::project_executor_config::ProjectExecutorConfig::decl(),
+        vibe_kanban::models::project_executor_config::UpdateProjectExecutorConfig::decl(),
+        vibe_kanban::api_key_store::ProjectApiKey::decl(),
+        vibe_kanban::api_key_store::SetProjectApiKey::decl(),
+        vibe_kanban::models::project::WorktreeGcReason::decl(),
+        vibe_kanban::models::project::WorktreeGcEntry::decl(),
+        vibe_kanban::models::project_archive::ProjectArchive::decl(),
+        vibe_kanban::models::project_archive::ArchivedProject::decl(),
+        vibe_kanban::models::project_archive::ArchivedTask::decl(),
+        vibe_kanban::models::project_archive::ArchivedTaskAttempt::decl(),
+        vibe_kanban::models::project_archive::ArchivedTaskRun::decl(),
+        vibe_kanban::models::project_archive::ArchivedLabel::decl(),
+        vibe_kanban::models::project_archive::ArchivedTaskLabel::decl(),
+        vibe_kanban::models::project_archive::ArchivedTaskDependency::decl(),
+        vibe_kanban::models::project_archive::ArchivedTaskEnvVar::decl(),
+        vibe_kanban::models::project_archive::ArchivedExecutorConfig::decl(),
+        vibe_kanban::models::project_archive::ArchivedWebhook::decl(),
+        vibe_kanban::models::project_archive::ImportedProject::decl(),
         vibe_kanban::models::task::CreateTask::decl(),
         vibe_kanban::models::task::CreateTaskAndStart::decl(),
+        vibe_kanban::models::task::CreateTaskBatchItem::decl(),
         vibe_kanban::models::task::TaskStatus::decl(),
         vibe_kanban::models::task::Task::decl(),
         vibe_kanban::models::task::TaskWithAttemptStatus::decl(),
         vibe_kanban::models::task::UpdateTask::decl(),
+        vibe_kanban::models::task::TaskDependency::decl(),
+        vibe_kanban::models::task::AddTaskDependency::decl(),
+        vibe_kanban::models::task::SetTaskEnvVar::decl(),
+        vibe_kanban::models::task::TaskGraphNode::decl(),
+        vibe_kanban::models::task::TaskGraphEdge::decl(),
+        vibe_kanban::models::task::TaskGraph::decl(),
+        vibe_kanban::models::task::SimilarCompletion::decl(),
+        vibe_kanban::models::task::TaskEstimate::decl(),
+        vibe_kanban::models::task::TaskSubtasks::decl(),
+        vibe_kanban::models::task::TaskTimeTracking::decl(),
+        vibe_kanban::models::task::TaskTimingStats::decl(),
+        vibe_kanban::models::label::Label::decl(),
+        vibe_kanban::models::label::TaskLabel::decl(),
+        vibe_kanban::models::label::CreateLabel::decl(),
+        vibe_kanban::models::label::AttachLabel::decl(),
+        vibe_kanban::models::task_artifact::TaskArtifact::decl(),
+        vibe_kanban::models::task_run::TaskRun::decl(),
+        vibe_kanban::models::task_run::WorktreeCleanupStatus::decl(),
+        vibe_kanban::models::task_run::CancelledRuns::decl(),
+        vibe_kanban::models::conversation_index::ConversationSearchResult::decl(),
+        vibe_kanban::models::tool_use_event::ToolUseStat::decl(),
+        vibe_kanban::models::executor_health::HealthCheckResult::decl(),
+        vibe_kanban::services::system_health::SystemCheckStatus::decl(),
+        vibe_kanban::services::system_health::SystemCheck::decl(),
+        vibe_kanban::services::system_health::SystemHealthReport::decl(),
+        vibe_kanban::models::notification::NotificationType::decl(),
+        vibe_kanban::models::notification::Notification::decl(),
+        vibe_kanban::models::audit_log::AuditLogEntry::decl(),
         vibe_kanban::models::task_template::TaskTemplate::decl(),
         vibe_kanban::models::task_template::CreateTaskTemplate::decl(),
         vibe_kanban::models::task_template::UpdateTaskTemplate::decl(),
+        vibe_kanban::models::task_template::InstantiateTaskTemplate::decl(),
+        vibe_kanban::models::webhook::Webhook::decl(),
+        vibe_kanban::models::webhook::CreateWebhook::decl(),
+        vibe_kanban::models::webhook::WebhookDeliveryStatus::decl(),
+        vibe_kanban::models::webhook::WebhookDelivery::decl(),
         vibe_kanban::models::task_attempt::TaskAttemptStatus::decl(),
         vibe_kanban::models::task_attempt::TaskAttempt::decl(),
         vibe_kanban::models::task_attempt::CreateTaskAttempt::decl(),
         vibe_kanban::models::task_attempt::UpdateTaskAttempt::decl(),
         vibe_kanban::models::task_attempt::CreateFollowUpAttempt::decl(),
+        vibe_kanban::models::task_attempt::ResumeSession::decl(),
         vibe_kanban::routes::filesystem::DirectoryEntry::decl(),
         vibe_kanban::routes::filesystem::DirectoryListResponse::decl(),
         vibe_kanban::routes::auth::DeviceStartResponse::decl(),
@@ -131,9 +204,16 @@ fn generate_types_content() -> String {
         vibe_kanban::models::executor_session::CreateExecutorSession::decl(),
         vibe_kanban::models::executor_session::UpdateExecutorSession::decl(),
         vibe_kanban::executor::NormalizedConversation::decl(),
+        vibe_kanban::executor::ExecutionStats::decl(),
+        vibe_kanban::executor::ConversationMetadata::decl(),
         vibe_kanban::executor::NormalizedEntry::decl(),
         vibe_kanban::executor::NormalizedEntryType::decl(),
         vibe_kanban::executor::ActionType::decl(),
+        vibe_kanban::conversation_diff::ConversationDiff::decl(),
+        vibe_kanban::diff_parser::FileDiff::decl(),
+        vibe_kanban::diff_parser::DiffHunk::decl(),
+        vibe_kanban::diff_parser::DiffLineKind::decl(),
+        vibe_kanban::diff_parser::DiffLine::decl(),
     ];
 
     let body = decls
@@ -1,9 +1,13 @@
 use std::str::FromStr;
 
 use rmcp::{transport::stdio, ServiceExt};
-use sqlx::{sqlite::SqliteConnectOptions, SqlitePool};
+use sqlx::sqlite::SqliteConnectOptions;
 use tracing_subscriber::{prelude::*, EnvFilter};
-use vibe_kanban::{mcp::task_server::TaskServer, sentry_layer, utils::asset_dir};
+use vibe_kanban::{
+    mcp::task_server::TaskServer,
+    sentry_layer,
+    utils::{asset_dir, database::connect_pool},
+};
 
 fn main() -> anyhow::Result<()> {
     let environment = if cfg!(debug_assertions) {
@@ -42,7 +46,7 @@ fn main() -> anyhow::Result<()> {
             );
 
             let options = SqliteConnectOptions::from_str(&database_url)?.create_if_missing(false);
-            let pool = SqlitePool::connect_with(options).await?;
+            let pool = connect_pool(options).await?;
 
             let service = TaskServer::new(pool)
                 .serve(stdio())
@@ -0,0 +1,233 @@
+//! Compare the conversations of two runs of a task, e.g. to see which tool calls a re-run
+//! added or dropped compared to the attempt it fixed.
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::executor::{NormalizedConversation, NormalizedEntry};
+
+/// How much of an entry's content is compared; longer tool output often differs only in
+/// details such as timings
+const COMPARED_CONTENT_BYTES: usize = 200;
+
+/// Entries of the second conversation missing from the first and the other way round
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ConversationDiff {
+    /// Entries only in the newer conversation, in its order
+    pub added: Vec<NormalizedEntry>,
+    /// Entries only in the older conversation, in its order
+    pub removed: Vec<NormalizedEntry>,
+    /// Entries found in both
+    pub unchanged_count: usize,
+}
+
+/// One step of the edit script turning the older entries into the newer ones
+enum Edit {
+    Keep,
+    /// Insert the newer entry at this index
+    Insert(usize),
+    /// Delete the older entry at this index
+    Delete(usize),
+}
+
+/// Entries are equal when they are of the same kind, ignoring e.g. tool names, and the
+/// start of their content matches
+fn same_entry(a: &NormalizedEntry, b: &NormalizedEntry) -> bool {
+    std::mem::discriminant(&a.entry_type) == std::mem::discriminant(&b.entry_type)
+        && content_prefix(&a.content) == content_prefix(&b.content)
+}
+
+fn content_prefix(content: &str) -> &[u8] {
+    let bytes = content.as_bytes();
+    &bytes[..bytes.len().min(COMPARED_CONTENT_BYTES)]
+}
+
+/// The entries `b` added to and removed from `a`, based on a shortest edit script
+pub fn diff(a: &NormalizedConversation, b: &NormalizedConversation) -> ConversationDiff {
+    let mut diff = ConversationDiff {
+        added: Vec::new(),
+        removed: Vec::new(),
+        unchanged_count: 0,
+    };
+    for edit in shortest_edit_script(&a.entries, &b.entries) {
+        match edit {
+            Edit::Keep => diff.unchanged_count += 1,
+            Edit::Insert(index) => diff.added.push(b.entries[index].clone()),
+            Edit::Delete(index) => diff.removed.push(a.entries[index].clone()),
+        }
+    }
+    diff
+}
+
+/// Myers' O(ND) algorithm: find the furthest reaching path on each diagonal `k = x - y` for
+/// an increasing number of edits `d`, then walk the recorded frontiers back from the end
+fn shortest_edit_script(a: &[NormalizedEntry], b: &[NormalizedEntry]) -> Vec<Edit> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = n + m;
+    // Diagonal `k` is stored at `k + offset`; the backtrack peeks one diagonal beyond `d`
+    let offset = max + 1;
+    let mut frontier = vec![0isize; 2 * max as usize + 3];
+    let mut trace = Vec::new();
+
+    'search: for d in 0..=max {
+        trace.push(frontier.clone());
+        for k in (-d..=d).step_by(2) {
+            let index = (k + offset) as usize;
+            // Step down (insert) from diagonal k + 1 or right (delete) from k - 1
+            let mut x = if k == -d || (k != d && frontier[index - 1] < frontier[index + 1]) {
+                frontier[index + 1]
+            } else {
+                frontier[index - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && same_entry(&a[x as usize], &b[y as usize]) {
+                x += 1;
+                y += 1;
+            }
+            frontier[index] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, frontier) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let index = (k + offset) as usize;
+        let previous_k = if k == -d || (k != d && frontier[index - 1] < frontier[index + 1]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let previous_x = frontier[(previous_k + offset) as usize];
+        let previous_y = previous_x - previous_k;
+
+        while x > previous_x && y > previous_y {
+            edits.push(Edit::Keep);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            if x == previous_x {
+                edits.push(Edit::Insert(previous_y as usize));
+            } else {
+                edits.push(Edit::Delete(previous_x as usize));
+            }
+        }
+        x = previous_x;
+        y = previous_y;
+    }
+    edits.reverse();
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{ActionType, NormalizedEntryType};
+
+    fn entry(entry_type: NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+            repeat_count: 1,
+        }
+    }
+
+    fn bash(command: &str) -> NormalizedEntry {
+        entry(
+            NormalizedEntryType::ToolUse {
+                tool_name: "Bash".to_string(),
+                action_type: ActionType::CommandRun {
+                    command: command.to_string(),
+                },
+            },
+            &format!("`{}`", command),
+        )
+    }
+
+    fn read(path: &str) -> NormalizedEntry {
+        entry(
+            NormalizedEntryType::ToolUse {
+                tool_name: "Read".to_string(),
+                action_type: ActionType::FileRead {
+                    path: path.to_string(),
+                },
+            },
+            &format!("`{}`", path),
+        )
+    }
+
+    fn conversation(entries: Vec<NormalizedEntry>) -> NormalizedConversation {
+        NormalizedConversation {
+            entries,
+            session_id: None,
+            executor_type: "claude".to_string(),
+            prompt: None,
+            summary: None,
+            stats: None,
+            metadata: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_diff_finds_added_bash_call() {
+        let first = conversation(vec![
+            entry(NormalizedEntryType::UserMessage, "Fix the build"),
+            read("src/main.rs"),
+            bash("cargo build"),
+        ]);
+        let second = conversation(vec![
+            entry(NormalizedEntryType::UserMessage, "Fix the build"),
+            read("src/main.rs"),
+            bash("cargo fmt"),
+            bash("cargo build"),
+        ]);
+
+        let diff = diff(&first, &second);
+        assert_eq!(diff.added, vec![bash("cargo fmt")]);
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.unchanged_count, 3);
+    }
+
+    #[test]
+    fn test_diff_finds_removed_tool_use() {
+        let first = conversation(vec![
+            read("README.md"),
+            read("src/lib.rs"),
+            entry(NormalizedEntryType::AssistantMessage, "Done."),
+        ]);
+        let second = conversation(vec![
+            read("src/lib.rs"),
+            entry(NormalizedEntryType::AssistantMessage, "Done."),
+        ]);
+
+        let diff = diff(&first, &second);
+        assert!(diff.added.is_empty());
+        assert_eq!(diff.removed, vec![read("README.md")]);
+        assert_eq!(diff.unchanged_count, 2);
+    }
+
+    #[test]
+    fn test_diff_of_identical_runs_is_empty() {
+        let entries = vec![
+            entry(NormalizedEntryType::UserMessage, "Add a test"),
+            bash("cargo test"),
+            entry(NormalizedEntryType::AssistantMessage, &"x".repeat(500)),
+        ];
+        let mut rerun = entries.clone();
+        // Only the first bytes of the content are compared
+        rerun[2].content.push_str(" (took 3s)");
+
+        let diff = diff(&conversation(entries), &conversation(rerun));
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.unchanged_count, 3);
+    }
+}
@@ -0,0 +1,294 @@
+//! Render a [`NormalizedConversation`] as a self-contained Markdown document, so a task's
+//! conversation can be pasted into docs or shared.
+
+use crate::executor::{ActionType, NormalizedConversation, NormalizedEntry, NormalizedEntryType};
+
+/// Render the conversation as Markdown.
+///
+/// The session id and executor type go into a YAML front-matter block. Assistant messages
+/// become paragraphs, user messages blockquotes and tool uses fenced code blocks tagged with
+/// the tool name under a bold action header. System messages and other executor internals
+/// become HTML comments, so they stay in the source but not in the rendered output. Entries
+/// standing for several identical ones are followed by `(×N)`.
+pub fn to_markdown(conv: &NormalizedConversation) -> String {
+    let mut blocks = vec![front_matter(conv)];
+    blocks.extend(conv.entries.iter().filter_map(render_entry));
+
+    let mut markdown = blocks.join("\n\n");
+    markdown.push('\n');
+    markdown
+}
+
+fn front_matter(conv: &NormalizedConversation) -> String {
+    format!(
+        "---\nsession_id: {}\nexecutor_type: {}\n---",
+        conv.session_id
+            .as_deref()
+            .map(yaml_string)
+            .unwrap_or_else(|| "null".to_string()),
+        yaml_string(&conv.executor_type)
+    )
+}
+
+/// Quote a YAML scalar; a JSON string is a valid double-quoted YAML string
+fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+fn render_entry(entry: &NormalizedEntry) -> Option<String> {
+    let repeats = repeat_marker(entry.repeat_count);
+    // Code blocks keep their indentation; everything else is trimmed to its text
+    if let NormalizedEntryType::ToolUse {
+        tool_name,
+        action_type,
+    } = &entry.entry_type
+    {
+        return Some(tool_use(tool_name, action_type, &entry.content, &repeats));
+    }
+
+    let content = normalize_lines(&entry.content);
+    if content.is_empty() {
+        return None;
+    }
+    let block = match &entry.entry_type {
+        NormalizedEntryType::AssistantMessage => content,
+        NormalizedEntryType::UserMessage => blockquote(&content),
+        NormalizedEntryType::ErrorMessage | NormalizedEntryType::ToolError { .. } => {
+            format!("**Error:** {}", content)
+        }
+        NormalizedEntryType::SystemMessage
+        | NormalizedEntryType::ToolResult { .. }
+        | NormalizedEntryType::Thinking
+        | NormalizedEntryType::DebugTrace
+        | NormalizedEntryType::Timing => html_comment(&content),
+        NormalizedEntryType::ToolUse { .. } => unreachable!("handled above"),
+    };
+    Some(format!("{}{}", block, repeats))
+}
+
+/// ` (×N)` for an entry standing for `N` identical ones, empty for a single one
+fn repeat_marker(repeat_count: u32) -> String {
+    if repeat_count > 1 {
+        format!(" (×{})", repeat_count)
+    } else {
+        String::new()
+    }
+}
+
+/// Strip trailing whitespace from each line and surrounding whitespace from the text
+fn normalize_lines(text: &str) -> String {
+    text.lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+fn blockquote(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.is_empty() {
+                ">".to_string()
+            } else {
+                format!("> {}", line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn html_comment(text: &str) -> String {
+    // `-->` would end the comment early
+    format!("<!--\n{}\n-->", text.replace("-->", "-- >"))
+}
+
+/// `repeats` goes after the header, as text after the closing fence would not end the block
+fn tool_use(tool_name: &str, action_type: &ActionType, content: &str, repeats: &str) -> String {
+    let header = format!("**{}**{}", action_header(action_type), repeats);
+    let code = content.trim_end_matches(['\n', '\r']);
+    let code = code.trim_start_matches(['\n', '\r']);
+    if code.trim().is_empty() {
+        return header;
+    }
+
+    // A fence ends at the first run of backticks at least as long as the opening one
+    let fence = "`".repeat(longest_backtick_run(code).max(2) + 1);
+    let language: String = tool_name
+        .chars()
+        .filter(|c| *c != '`')
+        .map(|c| if c.is_whitespace() { '-' } else { c })
+        .collect();
+    format!("{}\n\n{}{}\n{}\n{}", header, fence, language, code, fence)
+}
+
+fn action_header(action_type: &ActionType) -> String {
+    match action_type {
+        ActionType::FileRead { path } => format!("Read {}", inline_code(path)),
+        ActionType::FileWrite { path } => format!("Write {}", inline_code(path)),
+        ActionType::FileDelete { path } => format!("Delete {}", inline_code(path)),
+        ActionType::FileMove { from, to } => {
+            format!("Move {} → {}", inline_code(from), inline_code(to))
+        }
+        ActionType::CommandRun { command } => format!("Run {}", inline_code(command)),
+        ActionType::Search { query } => format!("Search {}", inline_code(query)),
+        ActionType::WebFetch { url } => format!("Fetch {}", inline_code(url)),
+        ActionType::TaskCreate { .. } => "Create task".to_string(),
+        ActionType::PlanPresentation { .. } => "Present plan".to_string(),
+        ActionType::ComputerUse { action, .. } => format!("Computer {}", inline_code(action)),
+        ActionType::Other { .. } => "Tool use".to_string(),
+    }
+}
+
+/// Wrap `text` in a code span delimited by more backticks than it contains
+fn inline_code(text: &str) -> String {
+    let text = text.replace('\n', " ");
+    let ticks = "`".repeat(longest_backtick_run(&text) + 1);
+    if text.starts_with('`') || text.ends_with('`') {
+        format!("{} {} {}", ticks, text, ticks)
+    } else {
+        format!("{}{}{}", ticks, text, ticks)
+    }
+}
+
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(entry_type: NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+            repeat_count: 1,
+        }
+    }
+
+    fn conversation(entries: Vec<NormalizedEntry>) -> NormalizedConversation {
+        NormalizedConversation {
+            entries,
+            session_id: Some("sess-1".to_string()),
+            executor_type: "claude".to_string(),
+            prompt: None,
+            summary: None,
+            stats: None,
+            metadata: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_to_markdown_renders_each_entry_type() {
+        let conv = conversation(vec![
+            entry(NormalizedEntryType::UserMessage, "Fix the bug\n\nplease"),
+            entry(NormalizedEntryType::SystemMessage, "model: sonnet"),
+            entry(NormalizedEntryType::AssistantMessage, "Looking at it."),
+            entry(
+                NormalizedEntryType::ToolUse {
+                    tool_name: "bash".to_string(),
+                    action_type: ActionType::CommandRun {
+                        command: "cargo test".to_string(),
+                    },
+                },
+                "cargo test",
+            ),
+        ]);
+
+        assert_eq!(
+            to_markdown(&conv),
+            "---\nsession_id: \"sess-1\"\nexecutor_type: \"claude\"\n---\n\n\
+             > Fix the bug\n>\n> please\n\n\
+             <!--\nmodel: sonnet\n-->\n\n\
+             Looking at it.\n\n\
+             **Run `cargo test`**\n\n```bash\ncargo test\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_to_markdown_is_whitespace_stable() {
+        let code = "fn main() {\n    println!(\"hi\");\n\n}";
+        let conv = conversation(vec![
+            entry(NormalizedEntryType::AssistantMessage, "\n  Done.  \n\n"),
+            entry(
+                NormalizedEntryType::ToolUse {
+                    tool_name: "write".to_string(),
+                    action_type: ActionType::FileWrite {
+                        path: "src/main.rs".to_string(),
+                    },
+                },
+                &format!("\n{}\n\n", code),
+            ),
+            entry(NormalizedEntryType::UserMessage, "   "),
+        ]);
+
+        let markdown = to_markdown(&conv);
+        // Indentation and blank lines inside code survive unchanged
+        assert!(markdown.contains(&format!("```write\n{}\n```", code)));
+        assert!(markdown.ends_with("```\n"));
+        assert!(!markdown.contains("\n\n\n"));
+        for line in markdown.lines().filter(|line| !code.contains(*line)) {
+            assert_eq!(line, line.trim_end(), "trailing whitespace in {:?}", line);
+        }
+        assert_eq!(markdown, to_markdown(&conv));
+
+        // Surrounding whitespace of an entry does not change the output
+        let mut trimmed = conv.clone();
+        trimmed.entries[0].content = "Done.".to_string();
+        trimmed.entries[1].content = code.to_string();
+        assert_eq!(markdown, to_markdown(&trimmed));
+    }
+
+    #[test]
+    fn test_to_markdown_escapes_code_fences() {
+        let content = "Example:\n```rust\nlet x = 1;\n```";
+        let conv = conversation(vec![entry(
+            NormalizedEntryType::ToolUse {
+                tool_name: "read".to_string(),
+                action_type: ActionType::FileRead {
+                    path: "README`s.md".to_string(),
+                },
+            },
+            content,
+        )]);
+
+        let markdown = to_markdown(&conv);
+        assert!(markdown.contains(&format!(
+            "**Read ``README`s.md``**\n\n````read\n{}\n````",
+            content
+        )));
+    }
+
+    #[test]
+    fn test_to_markdown_marks_repeated_entries() {
+        let mut retried = entry(
+            NormalizedEntryType::ToolUse {
+                tool_name: "bash".to_string(),
+                action_type: ActionType::CommandRun {
+                    command: "npm install".to_string(),
+                },
+            },
+            "npm install",
+        );
+        retried.repeat_count = 3;
+        let mut error = entry(NormalizedEntryType::ErrorMessage, "network unreachable");
+        error.repeat_count = 2;
+        let conv = conversation(vec![retried, error]);
+
+        let markdown = to_markdown(&conv);
+        assert!(markdown.contains("**Run `npm install`** (×3)\n\n```bash\nnpm install\n```"));
+        assert!(markdown.ends_with("**Error:** network unreachable (×2)\n"));
+    }
+
+    #[test]
+    fn test_to_markdown_keeps_comments_closed() {
+        let conv = conversation(vec![entry(NormalizedEntryType::SystemMessage, "a --> b")]);
+
+        let markdown = to_markdown(&conv);
+        assert!(markdown.contains("<!--\na -- > b\n-->"));
+        assert_eq!(markdown.matches("-->").count(), 1);
+    }
+}
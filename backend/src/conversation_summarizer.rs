@@ -0,0 +1,200 @@
+//! One-paragraph summaries of finished runs, written by a cheaper Claude model so long
+//! conversations can be skimmed.
+
+use crate::{
+    executor::{ExecutorError, NormalizedConversation, NormalizedEntryType},
+    executors::ClaudeExecutor,
+};
+
+/// Model the summaries are written with
+pub const SUMMARY_MODEL: &str = "claude-haiku-4-20250514";
+
+const SUMMARY_PROMPT: &str =
+    "Summarize what this AI agent did in one paragraph, focusing on files changed and outcomes";
+
+/// Tokens of assistant messages sent along with the prompt
+const MAX_TRANSCRIPT_TOKENS: usize = 4000;
+
+/// Characters per token, for estimating the length of a transcript
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Summarizes conversations with a single-turn Claude call
+pub struct ConversationSummarizer {
+    executor: ClaudeExecutor,
+}
+
+impl Default for ConversationSummarizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConversationSummarizer {
+    pub fn new() -> Self {
+        Self {
+            executor: ClaudeExecutor::new_with_model(SUMMARY_MODEL),
+        }
+    }
+
+    /// Summarize with another executor, e.g. one running a mock CLI
+    #[cfg(test)]
+    pub fn with_executor(executor: ClaudeExecutor) -> Self {
+        Self { executor }
+    }
+
+    /// Write a summary of the conversation into its `summary` and return it. Conversations
+    /// without assistant messages are left alone.
+    pub async fn summarize(
+        &self,
+        conversation: &mut NormalizedConversation,
+        working_dir: &str,
+    ) -> Result<Option<String>, ExecutorError> {
+        let Some(transcript) = transcript(conversation) else {
+            return Ok(None);
+        };
+
+        let prompt = format!("{}:\n\n{}", SUMMARY_PROMPT, transcript);
+        let summary = self
+            .executor
+            .run_single_turn(&prompt, working_dir)
+            .await?
+            .trim()
+            .to_string();
+        conversation.summary = Some(summary.clone());
+        Ok(Some(summary))
+    }
+}
+
+/// The conversation's assistant messages, one per paragraph. Long transcripts keep their
+/// end, where the outcome of the run is described.
+fn transcript(conversation: &NormalizedConversation) -> Option<String> {
+    let messages: Vec<&str> = conversation
+        .entries
+        .iter()
+        .filter(|entry| matches!(entry.entry_type, NormalizedEntryType::AssistantMessage))
+        .map(|entry| entry.content.trim())
+        .filter(|content| !content.is_empty())
+        .collect();
+    if messages.is_empty() {
+        return None;
+    }
+
+    let transcript = messages.join("\n\n");
+    let max_chars = MAX_TRANSCRIPT_TOKENS * CHARS_PER_TOKEN;
+    let char_count = transcript.chars().count();
+    if char_count <= max_chars {
+        return Some(transcript);
+    }
+    Some(transcript.chars().skip(char_count - max_chars).collect())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::executor::NormalizedEntry;
+
+    fn entry(entry_type: NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+            repeat_count: 1,
+        }
+    }
+
+    fn conversation(entries: Vec<NormalizedEntry>) -> NormalizedConversation {
+        NormalizedConversation {
+            entries,
+            session_id: None,
+            executor_type: "claude".to_string(),
+            prompt: None,
+            summary: None,
+            stats: None,
+            metadata: Default::default(),
+        }
+    }
+
+    /// An executor whose "CLI" records its last argument and prints canned stream-json
+    fn mock_executor(dir: &std::path::Path) -> ClaudeExecutor {
+        let script = dir.join("mock-claude.sh");
+        std::fs::write(
+            &script,
+            r#"for last; do :; done
+printf '%s' "$last" > "$(dirname "$0")/prompt.txt"
+echo '{"type":"system","subtype":"init","session_id":"s-1","model":"claude-haiku-4-20250514"}'
+echo '{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Summary follows"}]}}'
+echo '{"type":"result","subtype":"success","is_error":false,"num_turns":1,"result":"Fixed the failing parser test by editing src/parser.rs; all tests pass."}'
+"#,
+        )
+        .unwrap();
+        ClaudeExecutor::with_command("Claude".to_string(), format!("sh {}", script.display()))
+    }
+
+    #[tokio::test]
+    async fn test_summary_is_populated_from_cli_result() {
+        let dir = tempfile::tempdir().unwrap();
+        let summarizer = ConversationSummarizer::with_executor(mock_executor(dir.path()));
+        let mut conversation = conversation(vec![
+            entry(NormalizedEntryType::UserMessage, "Fix the parser test"),
+            entry(
+                NormalizedEntryType::AssistantMessage,
+                "The test fails on \"quoted\" input.",
+            ),
+            entry(
+                NormalizedEntryType::AssistantMessage,
+                "Fixed it in src/parser.rs.",
+            ),
+        ]);
+
+        let summary = summarizer
+            .summarize(&mut conversation, dir.path().to_str().unwrap())
+            .await
+            .unwrap();
+
+        let expected = "Fixed the failing parser test by editing src/parser.rs; all tests pass.";
+        assert_eq!(summary.as_deref(), Some(expected));
+        assert_eq!(conversation.summary.as_deref(), Some(expected));
+        // The prompt is passed as one argument and carries only the assistant messages
+        let prompt = std::fs::read_to_string(dir.path().join("prompt.txt")).unwrap();
+        assert!(prompt.starts_with(SUMMARY_PROMPT));
+        assert!(
+            prompt.ends_with("The test fails on \"quoted\" input.\n\nFixed it in src/parser.rs.")
+        );
+        assert!(!prompt.contains("Fix the parser test"));
+    }
+
+    #[tokio::test]
+    async fn test_conversation_without_assistant_messages_is_not_summarized() {
+        let dir = tempfile::tempdir().unwrap();
+        let summarizer = ConversationSummarizer::with_executor(mock_executor(dir.path()));
+        let mut conversation = conversation(vec![entry(
+            NormalizedEntryType::UserMessage,
+            "Fix the parser test",
+        )]);
+
+        let summary = summarizer
+            .summarize(&mut conversation, dir.path().to_str().unwrap())
+            .await
+            .unwrap();
+
+        assert!(summary.is_none());
+        assert!(conversation.summary.is_none());
+        assert!(!dir.path().join("prompt.txt").exists());
+    }
+
+    #[test]
+    fn test_transcript_keeps_the_end_of_long_conversations() {
+        let conversation = conversation(vec![
+            entry(NormalizedEntryType::AssistantMessage, &"a".repeat(20_000)),
+            entry(NormalizedEntryType::AssistantMessage, "Done."),
+        ]);
+
+        let transcript = transcript(&conversation).unwrap();
+        assert_eq!(
+            transcript.chars().count(),
+            MAX_TRANSCRIPT_TOKENS * CHARS_PER_TOKEN
+        );
+        assert!(transcript.ends_with("\n\nDone."));
+    }
+}
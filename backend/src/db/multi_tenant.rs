@@ -0,0 +1,309 @@
+//! One SQLite database per project, for hosted instances that keep each team's data apart
+//!
+//! The `projects` table lives in the meta database, `meta.db`. Each project's tasks live in
+//! `<data_dir>/<project_id>.db`, which is created and migrated the first time the project is
+//! accessed. Project data is only ever read from the meta database: every connection to a
+//! project database attaches `meta.db` and shadows its own `projects` table with a view of
+//! the meta one. The project database's own table holds a single row with nothing but the
+//! project's ID, which the foreign keys of its tasks point at.
+
+use std::path::{Path, PathBuf};
+
+use dashmap::DashMap;
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    SqlitePool,
+};
+use uuid::Uuid;
+
+use crate::{
+    models::project::Project,
+    utils::database::{connect_pool, connect_pool_with},
+};
+
+/// Environment variable that, when set to `1` or `true`, stores each project in its own
+/// database file
+pub const MULTI_TENANT_ENV: &str = "VIBE_MULTI_TENANT";
+
+/// File name of the database holding the projects when they are stored apart
+pub const META_DB: &str = "meta.db";
+
+/// The pools of the project databases, opened on first use
+#[derive(Debug)]
+pub struct MultiTenantPool {
+    meta: SqlitePool,
+    data_dir: PathBuf,
+    pools: DashMap<Uuid, SqlitePool>,
+    /// Held while a project database is created or removed, so concurrent first accesses do
+    /// not run its migrations twice
+    opening: tokio::sync::Mutex<()>,
+}
+
+impl MultiTenantPool {
+    /// Project databases go to `data_dir`, next to the meta database `<data_dir>/meta.db`
+    pub fn new(meta: SqlitePool, data_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            meta,
+            data_dir: data_dir.into(),
+            pools: DashMap::new(),
+            opening: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Whether [`MULTI_TENANT_ENV`] asks for one database per project
+    pub fn enabled_from_env() -> bool {
+        std::env::var(MULTI_TENANT_ENV)
+            .map(|value| matches!(value.trim(), "1" | "true"))
+            .unwrap_or(false)
+    }
+
+    /// File the project's data is stored in
+    pub fn database_path(&self, project_id: Uuid) -> PathBuf {
+        self.data_dir.join(format!("{}.db", project_id))
+    }
+
+    /// Pool of the project's database, creating and migrating the database on first access.
+    /// Fails with [`sqlx::Error::RowNotFound`] for projects missing from the meta database.
+    pub async fn pool(&self, project_id: Uuid) -> Result<SqlitePool, sqlx::Error> {
+        if let Some(pool) = self.pools.get(&project_id) {
+            return Ok(pool.clone());
+        }
+
+        let _opening = self.opening.lock().await;
+        if let Some(pool) = self.pools.get(&project_id) {
+            return Ok(pool.clone());
+        }
+        if !Project::exists(&self.meta, project_id).await? {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        let path = self.database_path(project_id);
+        // Migrated before the meta view hides the database's own projects table
+        let setup = open(&path).await?;
+        sqlx::query("INSERT OR IGNORE INTO projects (id, name) VALUES ($1, '')")
+            .bind(project_id)
+            .execute(&setup)
+            .await?;
+        setup.close().await;
+        let pool = open_with_meta(&path, &self.data_dir.join(META_DB)).await?;
+
+        tracing::info!("Opened database of project {}", project_id);
+        self.pools.insert(project_id, pool.clone());
+        Ok(pool)
+    }
+
+    /// Pools of every project database that exists, opening the ones not accessed yet.
+    /// Projects whose database was never created hold no tasks and are skipped, so listing
+    /// does not create a file for each of them.
+    pub async fn pools(&self) -> Result<Vec<SqlitePool>, sqlx::Error> {
+        let mut pools = Vec::new();
+        for project in Project::find_all(&self.meta).await? {
+            if !self.pools.contains_key(&project.id)
+                && !tokio::fs::try_exists(self.database_path(project.id)).await?
+            {
+                continue;
+            }
+            match self.pool(project.id).await {
+                Ok(pool) => pools.push(pool),
+                // Deleted since it was listed
+                Err(sqlx::Error::RowNotFound) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(pools)
+    }
+
+    /// Close the project's database and delete its files, once the project is deleted
+    pub async fn remove(&self, project_id: Uuid) -> std::io::Result<()> {
+        let _opening = self.opening.lock().await;
+        if let Some((_, pool)) = self.pools.remove(&project_id) {
+            pool.close().await;
+        }
+
+        let path = self.database_path(project_id);
+        for suffix in ["", "-wal", "-shm"] {
+            let mut file = path.clone().into_os_string();
+            file.push(suffix);
+            match tokio::fs::remove_file(&file).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+        tracing::info!("Removed database of project {}", project_id);
+        Ok(())
+    }
+}
+
+/// Open the database at `path`, creating it if needed, and bring its schema up to date
+pub async fn open(path: &Path) -> Result<SqlitePool, sqlx::Error> {
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(true);
+    let pool = connect_pool(options).await?;
+    sqlx::migrate!("./migrations").run(&pool).await?;
+    Ok(pool)
+}
+
+/// Open the migrated project database at `path` with the projects of the meta database at
+/// `meta_path` in place of its own
+async fn open_with_meta(path: &Path, meta_path: &Path) -> Result<SqlitePool, sqlx::Error> {
+    let meta_path = meta_path.to_string_lossy().into_owned();
+    let pool_options = SqlitePoolOptions::new().after_connect(move |connection, _| {
+        let meta_path = meta_path.clone();
+        Box::pin(async move {
+            sqlx::query("ATTACH DATABASE $1 AS meta")
+                .bind(meta_path)
+                .execute(&mut *connection)
+                .await?;
+            // Temporary objects come first in name resolution, while foreign keys keep
+            // pointing at the table in the project database
+            sqlx::query("CREATE TEMP VIEW projects AS SELECT * FROM meta.projects")
+                .execute(&mut *connection)
+                .await?;
+            Ok(())
+        })
+    });
+    connect_pool_with(SqliteConnectOptions::new().filename(path), pool_options).await
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::test_fixtures::{create_project, create_task};
+
+    async fn task_titles(path: &Path) -> Vec<String> {
+        let pool = open(path).await.unwrap();
+        sqlx::query_scalar("SELECT title FROM tasks")
+            .fetch_all(&pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_projects_store_tasks_in_separate_files() {
+        let dir = TempDir::new().unwrap();
+        let meta = open(&dir.path().join(META_DB)).await.unwrap();
+        let alpha = create_project(&meta).await;
+        let beta = create_project(&meta).await;
+        let tenants = MultiTenantPool::new(meta.clone(), dir.path());
+
+        let alpha_pool = tenants.pool(alpha).await.unwrap();
+        create_task(&alpha_pool, alpha, "Alpha task").await;
+        create_task(&tenants.pool(beta).await.unwrap(), beta, "Beta task").await;
+
+        assert_eq!(
+            task_titles(&tenants.database_path(alpha)).await,
+            vec!["Alpha task"]
+        );
+        assert_eq!(
+            task_titles(&tenants.database_path(beta)).await,
+            vec!["Beta task"]
+        );
+        assert!(task_titles(&dir.path().join(META_DB)).await.is_empty());
+        // Later accesses reuse the cached pool
+        let cached = tenants.pool(alpha).await.unwrap();
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks")
+            .fetch_one(&cached)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_project_gets_no_database() {
+        let dir = TempDir::new().unwrap();
+        let meta = open(&dir.path().join(META_DB)).await.unwrap();
+        let tenants = MultiTenantPool::new(meta, dir.path());
+        let project_id = Uuid::new_v4();
+
+        assert!(matches!(
+            tenants.pool(project_id).await,
+            Err(sqlx::Error::RowNotFound)
+        ));
+        assert!(!tenants.database_path(project_id).exists());
+    }
+
+    #[tokio::test]
+    async fn test_project_data_is_read_from_meta() {
+        let dir = TempDir::new().unwrap();
+        let meta = open(&dir.path().join(META_DB)).await.unwrap();
+        let project_id = create_project(&meta).await;
+        let tenants = MultiTenantPool::new(meta.clone(), dir.path());
+        let pool = tenants.pool(project_id).await.unwrap();
+
+        Project::set_prompts(
+            &meta,
+            project_id,
+            &crate::models::project::ProjectPrompts {
+                system_prompt: Some("Be brief".to_string()),
+                prompt_prefix: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let project = Project::find_by_id(&pool, project_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(project.name, "project");
+        let prompts = Project::find_prompts(&pool, project_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(prompts.system_prompt.as_deref(), Some("Be brief"));
+        // Projects are only written through the meta database
+        assert!(
+            Project::set_pinned_session_id(&pool, project_id, Some("session"))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pools_cover_every_existing_database() {
+        let dir = TempDir::new().unwrap();
+        let meta = open(&dir.path().join(META_DB)).await.unwrap();
+        let alpha = create_project(&meta).await;
+        let beta = create_project(&meta).await;
+        let tenants = MultiTenantPool::new(meta.clone(), dir.path());
+        create_task(&tenants.pool(alpha).await.unwrap(), alpha, "Alpha task").await;
+        // As after a restart, with no project database opened yet
+        let tenants = MultiTenantPool::new(meta, dir.path());
+
+        let mut titles = Vec::new();
+        for pool in tenants.pools().await.unwrap() {
+            titles.extend(
+                sqlx::query_scalar::<_, String>("SELECT title FROM tasks")
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap(),
+            );
+        }
+        assert_eq!(titles, vec!["Alpha task"]);
+        assert!(!tenants.database_path(beta).exists());
+    }
+
+    #[tokio::test]
+    async fn test_remove_deletes_the_database_file() {
+        let dir = TempDir::new().unwrap();
+        let meta = open(&dir.path().join(META_DB)).await.unwrap();
+        let project_id = create_project(&meta).await;
+        let tenants = MultiTenantPool::new(meta.clone(), dir.path());
+        create_task(&tenants.pool(project_id).await.unwrap(), project_id, "Task").await;
+        let path = tenants.database_path(project_id);
+        assert!(path.exists());
+
+        Project::delete(&meta, project_id).await.unwrap();
+        tenants.remove(project_id).await.unwrap();
+
+        assert!(!path.exists());
+        assert!(matches!(
+            tenants.pool(project_id).await,
+            Err(sqlx::Error::RowNotFound)
+        ));
+    }
+}
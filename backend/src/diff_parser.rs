@@ -0,0 +1,256 @@
+//! Parse the unified diff printed by `git diff` into files, hunks and lines, e.g. to show the
+//! changes of a run like a pull request diff.
+
+use serde::Serialize;
+use ts_rs::TS;
+
+/// Changes to one file
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+#[ts(export, rename = "UnifiedFileDiff")]
+pub struct FileDiff {
+    /// Path after the change; the old path for deleted files
+    pub path: String,
+    pub hunks: Vec<DiffHunk>,
+}
+
+/// A changed region of a file. Binary files get a single hunk without lines.
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+#[ts(export)]
+pub struct DiffHunk {
+    pub old_start: u32,
+    pub new_start: u32,
+    pub old_count: u32,
+    pub new_count: u32,
+    pub lines: Vec<DiffLine>,
+    pub is_binary: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum DiffLineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+/// One line of a hunk, without its `+`/`-`/` ` prefix
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+#[ts(export)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub content: String,
+}
+
+/// Parse `git diff` output. Headers other than paths, e.g. modes and indexes, are skipped.
+pub fn parse(diff: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+
+    for line in diff.lines() {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            files.push(FileDiff {
+                path: header_path(header),
+                hunks: Vec::new(),
+            });
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+
+        if let Some(hunk) = line.strip_prefix("@@ ").and_then(parse_hunk_header) {
+            file.hunks.push(hunk);
+        } else if let Some(hunk) = file.hunks.last_mut().filter(|hunk| !hunk.is_binary) {
+            let (kind, content) = match line.split_at_checked(1) {
+                Some(("+", content)) => (DiffLineKind::Added, content),
+                Some(("-", content)) => (DiffLineKind::Removed, content),
+                Some((" ", content)) => (DiffLineKind::Context, content),
+                // Blank context lines lose their space in some outputs
+                None => (DiffLineKind::Context, ""),
+                // "\ No newline at end of file"
+                _ => continue,
+            };
+            hunk.lines.push(DiffLine {
+                kind,
+                content: content.to_string(),
+            });
+        } else if let Some(path) = line.strip_prefix("+++ b/") {
+            file.path = path.to_string();
+        } else if let Some(path) = line.strip_prefix("rename to ") {
+            file.path = path.to_string();
+        } else if line.starts_with("Binary files ") || line == "GIT binary patch" {
+            file.hunks.push(DiffHunk {
+                old_start: 0,
+                new_start: 0,
+                old_count: 0,
+                new_count: 0,
+                lines: Vec::new(),
+                is_binary: true,
+            });
+        }
+    }
+    files
+}
+
+/// The new path of a `diff --git a/<old> b/<new>` header
+fn header_path(header: &str) -> String {
+    match header.rfind(" b/") {
+        Some(index) => header[index + 3..].to_string(),
+        None => header.to_string(),
+    }
+}
+
+/// Parse `-<old_start>[,<old_count>] +<new_start>[,<new_count>] @@`; counts default to 1
+fn parse_hunk_header(header: &str) -> Option<DiffHunk> {
+    let mut ranges = header.split_whitespace();
+    let (old_start, old_count) = parse_range(ranges.next()?.strip_prefix('-')?)?;
+    let (new_start, new_count) = parse_range(ranges.next()?.strip_prefix('+')?)?;
+    Some(DiffHunk {
+        old_start,
+        new_start,
+        old_count,
+        new_count,
+        lines: Vec::new(),
+        is_binary: false,
+    })
+}
+
+fn parse_range(range: &str) -> Option<(u32, u32)> {
+    match range.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(kind: DiffLineKind, content: &str) -> DiffLine {
+        DiffLine {
+            kind,
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_multi_hunk_diff() {
+        let diff = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 3b18e51..a9c2f1e 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,4 @@
+ use std::io;
++use std::fs;
+
+ fn main() {
+@@ -20,2 +21,2 @@ fn helper() {
+-    old();
++    new();
+ }
+\\ No newline at end of file
+diff --git a/README.md b/README.md
+new file mode 100644
+--- /dev/null
++++ b/README.md
+@@ -0,0 +1 @@
++# Title
+";
+
+        let files = parse(diff);
+        assert_eq!(files.len(), 2);
+
+        let lib = &files[0];
+        assert_eq!(lib.path, "src/lib.rs");
+        assert_eq!(lib.hunks.len(), 2);
+        assert_eq!(
+            (
+                lib.hunks[0].old_start,
+                lib.hunks[0].old_count,
+                lib.hunks[0].new_start,
+                lib.hunks[0].new_count
+            ),
+            (1, 3, 1, 4)
+        );
+        assert_eq!(
+            lib.hunks[0].lines,
+            vec![
+                line(DiffLineKind::Context, "use std::io;"),
+                line(DiffLineKind::Added, "use std::fs;"),
+                line(DiffLineKind::Context, ""),
+                line(DiffLineKind::Context, "fn main() {"),
+            ]
+        );
+        assert_eq!((lib.hunks[1].old_start, lib.hunks[1].new_start), (20, 21));
+        assert_eq!(
+            lib.hunks[1].lines,
+            vec![
+                line(DiffLineKind::Removed, "    old();"),
+                line(DiffLineKind::Added, "    new();"),
+                line(DiffLineKind::Context, "}"),
+            ]
+        );
+
+        let readme = &files[1];
+        assert_eq!(readme.path, "README.md");
+        assert_eq!(
+            (readme.hunks[0].old_count, readme.hunks[0].new_count),
+            (0, 1)
+        );
+        assert_eq!(
+            readme.hunks[0].lines,
+            vec![line(DiffLineKind::Added, "# Title")]
+        );
+    }
+
+    #[test]
+    fn test_parse_binary_file() {
+        let diff = "\
+diff --git a/assets/logo.png b/assets/logo.png
+new file mode 100644
+index 0000000..4c2f6a1
+Binary files /dev/null and b/assets/logo.png differ
+";
+
+        let files = parse(diff);
+        assert_eq!(
+            files,
+            vec![FileDiff {
+                path: "assets/logo.png".to_string(),
+                hunks: vec![DiffHunk {
+                    old_start: 0,
+                    new_start: 0,
+                    old_count: 0,
+                    new_count: 0,
+                    lines: vec![],
+                    is_binary: true,
+                }],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_deleted_file_keeps_old_path() {
+        let diff = "\
+diff --git a/old.txt b/old.txt
+deleted file mode 100644
+--- a/old.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-gone
+";
+
+        let files = parse(diff);
+        assert_eq!(files[0].path, "old.txt");
+        assert_eq!(
+            files[0].hunks[0].lines,
+            vec![line(DiffLineKind::Removed, "gone")]
+        );
+    }
+
+    #[test]
+    fn test_parse_empty_diff() {
+        assert!(parse("").is_empty());
+    }
+}
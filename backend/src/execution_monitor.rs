@@ -1,17 +1,37 @@
+use std::path::Path;
+
 use git2::Repository;
 use uuid::Uuid;
 
 use crate::{
     app_state::AppState,
+    conversation_summarizer::ConversationSummarizer,
+    executor::NormalizedConversation,
+    executor_events::ExecutorExited,
+    executor_supervisor::ExecutorSupervisor,
     models::{
+        conversation_index::ConversationIndex,
         execution_process::{ExecutionProcess, ExecutionProcessStatus, ExecutionProcessType},
+        notification::NotificationType,
+        project::{Project, WorktreeGcEntry, WorktreeGcReason},
         task::{Task, TaskStatus},
-        task_attempt::TaskAttempt,
+        task_artifact::TaskArtifact,
+        task_attempt::{FollowupRequest, TaskAttempt},
+        task_run::TaskRun,
+        tool_use_event::ToolUseEvent,
+    },
+    services::{
+        git_service,
+        webhook_service::{TASK_COMPLETED_EVENT, TASK_FAILED_EVENT},
+        NotificationConfig, NotificationService, ProcessService, WebhookService,
     },
-    services::{NotificationConfig, NotificationService, ProcessService},
+    telemetry::RunTrace,
     utils::worktree_manager::WorktreeManager,
 };
 
+/// How many runs `reparse_all_runs` loads at a time
+const REPARSE_BATCH_SIZE: i64 = 20;
+
 /// Delegation context structure
 #[derive(Debug, serde::Deserialize)]
 struct DelegationContext {
@@ -53,7 +73,11 @@ fn parse_delegation_context(args_json: &str) -> Option<DelegationContext> {
 }
 
 /// Handle delegation after setup completion
-async fn handle_setup_delegation(app_state: &AppState, delegation_context: DelegationContext) {
+async fn handle_setup_delegation(
+    pool: &sqlx::SqlitePool,
+    app_state: &AppState,
+    delegation_context: DelegationContext,
+) {
     let params = &delegation_context.operation_params;
     let task_id = params.task_id;
     let project_id = params.project_id;
@@ -68,43 +92,22 @@ async fn handle_setup_delegation(app_state: &AppState, delegation_context: Deleg
     let result = match delegation_context.delegate_to.as_str() {
         "dev_server" => {
             ProcessService::start_dev_server_direct(
-                &app_state.db_pool,
-                app_state,
-                attempt_id,
-                task_id,
-                project_id,
+                pool, app_state, attempt_id, task_id, project_id,
             )
             .await
         }
         "coding_agent" => {
-            ProcessService::start_coding_agent(
-                &app_state.db_pool,
-                app_state,
-                attempt_id,
-                task_id,
-                project_id,
-            )
-            .await
+            ProcessService::start_coding_agent(pool, app_state, attempt_id, task_id, project_id)
+                .await
         }
-        "followup" => {
-            let prompt = params
-                .additional
-                .as_ref()
-                .and_then(|a| a.get("prompt"))
-                .and_then(|p| p.as_str())
-                .unwrap_or("");
-
-            ProcessService::start_followup_execution_direct(
-                &app_state.db_pool,
-                app_state,
-                attempt_id,
-                task_id,
-                project_id,
-                prompt,
+        "followup" => match FollowupRequest::from_operation_params(params.additional.as_ref()) {
+            Ok(request) => ProcessService::start_followup_execution_direct(
+                pool, app_state, attempt_id, task_id, project_id, request,
             )
             .await
-            .map(|_| ())
-        }
+            .map(|_| ()),
+            Err(e) => Err(e),
+        },
         _ => {
             tracing::error!(
                 "Unknown delegation target: {}",
@@ -128,69 +131,69 @@ async fn handle_setup_delegation(app_state: &AppState, delegation_context: Deleg
     }
 }
 
-/// Commit any unstaged changes in the worktree after execution completion
-async fn commit_execution_changes(
-    worktree_path: &str,
-    attempt_id: Uuid,
+/// Message of the commit that ends a run: the project's commit template for successful runs
+/// of projects with `use_commit_template`, the run's summary otherwise
+async fn run_commit_message(
+    pool: &sqlx::SqlitePool,
+    task_attempt: &TaskAttempt,
+    success: bool,
     summary: Option<&str>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    // Run git operations in a blocking task since git2 is synchronous
-    let worktree_path = worktree_path.to_string();
-    let summary = summary.map(|s| s.to_string());
-    tokio::task::spawn_blocking(move || {
-        let worktree_repo = Repository::open(&worktree_path)?;
-
-        // Check if there are any changes to commit
-        let status = worktree_repo.statuses(None)?;
-        let has_changes = status.iter().any(|entry| {
-            let flags = entry.status();
-            flags.contains(git2::Status::INDEX_NEW)
-                || flags.contains(git2::Status::INDEX_MODIFIED)
-                || flags.contains(git2::Status::INDEX_DELETED)
-                || flags.contains(git2::Status::WT_NEW)
-                || flags.contains(git2::Status::WT_MODIFIED)
-                || flags.contains(git2::Status::WT_DELETED)
-        });
-
-        if !has_changes {
-            return Ok::<(), Box<dyn std::error::Error + Send + Sync>>(());
+) -> String {
+    let fallback = || {
+        summary
+            .map(|summary| summary.to_string())
+            .unwrap_or_else(|| format!("Task attempt {} - Final changes", task_attempt.id))
+    };
+    if !success {
+        return fallback();
+    }
+    let Ok(Some(task)) = Task::find_by_id(pool, task_attempt.task_id).await else {
+        return fallback();
+    };
+    match Project::find_by_id(pool, task.project_id).await {
+        Ok(Some(project)) if project.use_commit_template => {
+            let template = project
+                .git_commit_template
+                .as_deref()
+                .unwrap_or(git_service::DEFAULT_COMMIT_TEMPLATE);
+            git_service::render_commit_message(template, &task.title, task.id)
         }
+        Ok(_) => fallback(),
+        Err(e) => {
+            tracing::warn!(
+                "Failed to load project {} for the commit message: {}",
+                task.project_id,
+                e
+            );
+            fallback()
+        }
+    }
+}
 
-        // Get the current signature for commits
-        let signature = worktree_repo.signature()?;
-
-        // Get the current HEAD commit
-        let head = worktree_repo.head()?;
-        let parent_commit = head.peel_to_commit()?;
-
-        // Stage all changes
-        let mut worktree_index = worktree_repo.index()?;
-        worktree_index.add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)?;
-        worktree_index.write()?;
-
-        let tree_id = worktree_index.write_tree()?;
-        let tree = worktree_repo.find_tree(tree_id)?;
-
-        // Create commit for the changes
-        let commit_message = if let Some(ref summary_msg) = summary {
-            summary_msg.clone()
-        } else {
-            format!("Task attempt {} - Final changes", attempt_id)
-        };
-        worktree_repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            &commit_message,
-            &tree,
-            &[&parent_commit],
-        )?;
-
-        Ok(())
-    })
-    .await??;
-
-    Ok(())
+/// Commit everything a finished coding agent run left in its worktree, successful or not,
+/// so the branch can be merged. Failures are only logged and never fail the run.
+pub(crate) async fn commit_run_changes(
+    pool: &sqlx::SqlitePool,
+    task_attempt: &TaskAttempt,
+    success: bool,
+    summary: Option<&str>,
+) {
+    let message = run_commit_message(pool, task_attempt, success, summary).await;
+    match git_service::commit_all(Path::new(&task_attempt.worktree_path), &message).await {
+        Ok(true) => tracing::info!(
+            "Committed execution changes for attempt {}",
+            task_attempt.id
+        ),
+        Ok(false) => tracing::debug!(
+            "No execution changes to commit for attempt {}",
+            task_attempt.id
+        ),
+        Err(e) => tracing::error!(
+            "Failed to commit execution changes for attempt {}: {}",
+            task_attempt.id,
+            e
+        ),
+    }
 }
 
 /// Check if worktree has uncommitted changes and warn if so
@@ -363,8 +366,9 @@ async fn check_externally_deleted_worktrees(pool: &sqlx::SqlitePool) {
     }
 }
 
-/// Find and delete orphaned worktrees that don't correspond to any task attempts
-async fn cleanup_orphaned_worktrees(pool: &sqlx::SqlitePool) {
+/// Find and delete orphaned worktrees that don't correspond to any task attempt in any of
+/// the task databases
+async fn cleanup_orphaned_worktrees(pools: &[sqlx::SqlitePool]) {
     // Check if orphan cleanup is disabled via environment variable
     if std::env::var("DISABLE_WORKTREE_ORPHAN_CLEANUP").is_ok() {
         tracing::debug!("Orphan worktree cleanup is disabled via DISABLE_WORKTREE_ORPHAN_CLEANUP environment variable");
@@ -416,24 +420,31 @@ async fn cleanup_orphaned_worktrees(pool: &sqlx::SqlitePool) {
         let worktree_path_str = path.to_string_lossy().to_string();
         checked_count += 1;
 
-        // Check if this worktree path exists in the database
-        let exists_in_db = match sqlx::query!(
-            "SELECT COUNT(*) as count FROM task_attempts WHERE worktree_path = ?",
-            worktree_path_str
-        )
-        .fetch_one(pool)
-        .await
-        {
-            Ok(row) => row.count > 0,
-            Err(e) => {
-                tracing::error!(
-                    "Failed to check database for worktree path {}: {}",
-                    worktree_path_str,
-                    e
-                );
-                continue;
+        // Check if this worktree path exists in any of the databases
+        let mut exists_in_db = false;
+        let mut lookup_failed = false;
+        for pool in pools {
+            match sqlx::query!(
+                "SELECT COUNT(*) as count FROM task_attempts WHERE worktree_path = ?",
+                worktree_path_str
+            )
+            .fetch_one(pool)
+            .await
+            {
+                Ok(row) => exists_in_db |= row.count > 0,
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to check database for worktree path {}: {}",
+                        worktree_path_str,
+                        e
+                    );
+                    lookup_failed = true;
+                }
             }
-        };
+        }
+        if lookup_failed {
+            continue;
+        }
 
         if !exists_in_db {
             // This is an orphaned worktree - delete it
@@ -511,6 +522,90 @@ async fn cleanup_orphaned_worktree_directory(
     Ok(())
 }
 
+/// Find the project's linked worktrees that no in-progress task needs and, unless `dry_run`,
+/// remove them and mark their attempts' worktrees as deleted
+pub async fn gc_project_worktrees(
+    app_state: &AppState,
+    project: &Project,
+    dry_run: bool,
+) -> Result<Vec<WorktreeGcEntry>, git2::Error> {
+    let pool = &app_state
+        .project_pool(project.id)
+        .await
+        .map_err(|e| git2::Error::from_str(&format!("Failed to open project database: {}", e)))?;
+    let worktrees = WorktreeManager::list_linked_worktrees(&project.git_repo_path).await?;
+    let mut entries = Vec::new();
+
+    for worktree in worktrees {
+        let path = worktree.to_string_lossy().to_string();
+        let attempts = TaskAttempt::find_by_worktree_path_with_task_status(pool, project.id, &path)
+            .await
+            .map_err(|e| git2::Error::from_str(&format!("Failed to look up {}: {}", path, e)))?;
+
+        if attempts
+            .iter()
+            .any(|(_, status)| *status == TaskStatus::InProgress)
+        {
+            continue;
+        }
+        let mut running = false;
+        for (attempt_id, _) in &attempts {
+            running |= app_state.has_running_execution(*attempt_id).await;
+        }
+        if running {
+            continue;
+        }
+
+        let reason = if attempts.is_empty() {
+            WorktreeGcReason::NoRecord
+        } else {
+            WorktreeGcReason::TaskNotInProgress
+        };
+        let attempt_ids: Vec<Uuid> = attempts.into_iter().map(|(id, _)| id).collect();
+        let mut entry = WorktreeGcEntry {
+            path,
+            attempt_ids,
+            reason,
+            removed: false,
+            error: None,
+        };
+
+        if !dry_run {
+            check_uncommitted_changes(&entry.path);
+            match WorktreeManager::cleanup_worktree(&worktree, Some(&project.git_repo_path)).await {
+                Ok(()) => {
+                    entry.removed = true;
+                    tracing::info!(
+                        "Worktree GC removed {} from project {} ({:?}, attempts {:?})",
+                        entry.path,
+                        project.id,
+                        entry.reason,
+                        entry.attempt_ids
+                    );
+                    for attempt_id in &entry.attempt_ids {
+                        if let Err(e) = TaskAttempt::mark_worktree_deleted(pool, *attempt_id).await
+                        {
+                            tracing::error!(
+                                "Failed to mark worktree as deleted in database for attempt {}: {}",
+                                attempt_id,
+                                e
+                            );
+                        }
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Worktree GC failed to remove {}: {}", entry.path, e);
+                    entry.error = Some(e.to_string());
+                }
+            }
+        }
+
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
 pub async fn execution_monitor(app_state: AppState) {
     let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(5));
     let mut cleanup_interval = tokio::time::interval(tokio::time::Duration::from_secs(1800)); // 30 minutes
@@ -520,9 +615,30 @@ pub async fn execution_monitor(app_state: AppState) {
             _ = interval.tick() => {
                 // Check for completed processes FIRST to avoid race conditions
                 let completed_executions = app_state.get_running_executions_for_monitor().await;
+                let supervisor = ExecutorSupervisor::new(
+                    app_state.get_config().read().await.restart_policy.clone(),
+                );
 
                 // Handle completed executions
-                for (execution_process_id, task_attempt_id, success, exit_code) in completed_executions {
+                for (execution_process_id, task_attempt_id, pool, success, exit_code) in completed_executions {
+                    // A crashed run that is resumed keeps running under the same id
+                    if !success
+                        && supervisor
+                            .restart_if_crashed(
+                                &pool,
+                                &app_state,
+                                execution_process_id,
+                                task_attempt_id,
+                                exit_code,
+                            )
+                            .await
+                    {
+                        continue;
+                    }
+
+                    // Ends the run's trace once its completion is handled
+                    let run_trace = app_state.take_run_trace(execution_process_id);
+
                     let status_text = if success {
                         "completed successfully"
                     } else {
@@ -549,7 +665,7 @@ pub async fn execution_monitor(app_state: AppState) {
                     };
 
                     if let Err(e) = ExecutionProcess::update_completion(
-                        &app_state.db_pool,
+                        &pool,
                         execution_process_id,
                         execution_status,
                         exit_code,
@@ -565,11 +681,12 @@ pub async fn execution_monitor(app_state: AppState) {
 
                     // Get the execution process to determine next steps
                     if let Ok(Some(execution_process)) =
-                        ExecutionProcess::find_by_id(&app_state.db_pool, execution_process_id).await
+                        ExecutionProcess::find_by_id(&pool, execution_process_id).await
                     {
                         match execution_process.process_type {
                             ExecutionProcessType::SetupScript => {
                                 handle_setup_completion(
+                                    &pool,
                                     &app_state,
                                     task_attempt_id,
                                     execution_process,
@@ -579,18 +696,19 @@ pub async fn execution_monitor(app_state: AppState) {
                             }
                             ExecutionProcessType::CodingAgent => {
                                 handle_coding_agent_completion(
+                                    &pool,
                                     &app_state,
                                     task_attempt_id,
-                                    execution_process_id,
                                     execution_process,
                                     success,
                                     exit_code,
+                                    run_trace.as_ref(),
                                 )
                                 .await;
                             }
                             ExecutionProcessType::DevServer => {
                                 handle_dev_server_completion(
-                                    &app_state,
+                                    &pool,
                                     task_attempt_id,
                                     execution_process_id,
                                     execution_process,
@@ -606,138 +724,189 @@ pub async fn execution_monitor(app_state: AppState) {
                             execution_process_id
                         );
                     }
+
+                    // The output is final now, so it can be stored compressed
+                    if let Err(e) =
+                        ExecutionProcess::compress_stdout(&pool, execution_process_id).await
+                    {
+                        tracing::error!(
+                            "Failed to compress stdout of execution process {}: {}",
+                            execution_process_id,
+                            e
+                        );
+                    }
                 }
 
                 // Check for orphaned execution processes AFTER handling completions
                 // Add a small delay to ensure completed processes are properly handled first
                 tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
 
-                let running_processes = match ExecutionProcess::find_running(&app_state.db_pool).await {
-                    Ok(processes) => processes,
+                let task_pools = match app_state.task_pools().await {
+                    Ok(task_pools) => task_pools,
+                    Err(e) => {
+                        tracing::error!("Failed to open task databases: {}", e);
+                        continue;
+                    }
+                };
+                for pool in &task_pools {
+                    fail_orphaned_processes(pool, &app_state).await;
+                }
+            }
+            _ = cleanup_interval.tick() => {
+                tracing::info!("Starting periodic worktree cleanup...");
+
+                let task_pools = match app_state.task_pools().await {
+                    Ok(task_pools) => task_pools,
                     Err(e) => {
-                        tracing::error!("Failed to query running execution processes: {}", e);
+                        tracing::error!("Failed to open task databases: {}", e);
                         continue;
                     }
                 };
 
-                for process in running_processes {
-                    // Check if this process is not actually running in the app state
-                    if !app_state.has_running_execution(process.task_attempt_id).await {
-                        // Additional check: if the process was recently updated, skip it to prevent race conditions
-                        let now = chrono::Utc::now();
-                        let time_since_update = now - process.updated_at;
-                        if time_since_update.num_seconds() < 10 {
-                            // Process was updated within last 10 seconds, likely just completed
-                            tracing::debug!(
-                                "Skipping recently updated orphaned process {} (updated {} seconds ago)",
-                                process.id,
-                                time_since_update.num_seconds()
-                            );
-                            continue;
-                        }
+                // First, defensively check for externally deleted worktrees
+                for pool in &task_pools {
+                    check_externally_deleted_worktrees(pool).await;
+                }
 
-                        // This is truly an orphaned execution process - mark it as failed
-                        tracing::info!(
-                            "Found orphaned execution process {} for task attempt {}",
-                            process.id,
-                            process.task_attempt_id
-                        );
-                        // This is truly an orphaned execution process - mark it as failed
-                        tracing::info!(
-                            "Found orphaned execution process {} for task attempt {}",
-                            process.id,
-                            process.task_attempt_id
-                        );
+                // Then, find and delete orphaned worktrees that don't belong to any task
+                cleanup_orphaned_worktrees(&task_pools).await;
+
+                // Then, proceed with normal expired worktree cleanup
+                for pool in &task_pools {
+                    cleanup_expired_worktrees(pool).await;
+                }
+            }
+        }
+    }
+}
+
+/// Mark running execution processes that no longer run in this server as failed
+async fn fail_orphaned_processes(pool: &sqlx::SqlitePool, app_state: &AppState) {
+    let running_processes = match ExecutionProcess::find_running(pool).await {
+        Ok(processes) => processes,
+        Err(e) => {
+            tracing::error!("Failed to query running execution processes: {}", e);
+            return;
+        }
+    };
+
+    for process in running_processes {
+        // Check if this process is not actually running in the app state
+        if !app_state
+            .has_running_execution(process.task_attempt_id)
+            .await
+        {
+            // Additional check: if the process was recently updated, skip it to prevent race conditions
+            let now = chrono::Utc::now();
+            let time_since_update = now - process.updated_at;
+            if time_since_update.num_seconds() < 10 {
+                // Process was updated within last 10 seconds, likely just completed
+                tracing::debug!(
+                    "Skipping recently updated orphaned process {} (updated {} seconds ago)",
+                    process.id,
+                    time_since_update.num_seconds()
+                );
+                continue;
+            }
+
+            // This is truly an orphaned execution process - mark it as failed
+            tracing::info!(
+                "Found orphaned execution process {} for task attempt {}",
+                process.id,
+                process.task_attempt_id
+            );
+
+            // Update the execution process status first
+            if let Err(e) = ExecutionProcess::update_completion(
+                pool,
+                process.id,
+                ExecutionProcessStatus::Failed,
+                None, // No exit code for orphaned processes
+            )
+            .await
+            {
+                tracing::error!(
+                    "Failed to update orphaned execution process {} status: {}",
+                    process.id,
+                    e
+                );
+                continue;
+            }
 
-                        // Update the execution process status first
-                        if let Err(e) = ExecutionProcess::update_completion(
-                            &app_state.db_pool,
-                            process.id,
-                            ExecutionProcessStatus::Failed,
-                            None, // No exit code for orphaned processes
+            tracing::info!("Marked orphaned execution process {} as failed", process.id);
+
+            // Update task status to InReview for coding agent and setup script failures
+            if matches!(
+                process.process_type,
+                ExecutionProcessType::CodingAgent | ExecutionProcessType::SetupScript
+            ) {
+                if let Ok(Some(task_attempt)) =
+                    TaskAttempt::find_by_id(pool, process.task_attempt_id).await
+                {
+                    if let Ok(Some(task)) = Task::find_by_id(pool, task_attempt.task_id).await {
+                        if let Err(e) = Task::update_status(
+                            pool,
+                            task.id,
+                            task.project_id,
+                            TaskStatus::InReview,
                         )
                         .await
                         {
                             tracing::error!(
-                                "Failed to update orphaned execution process {} status: {}",
-                                process.id,
+                                "Failed to update task status to InReview for orphaned attempt: {}",
                                 e
                             );
-                            continue;
-                        }
-
-                        // Process marked as failed
-
-                        tracing::info!("Marked orphaned execution process {} as failed", process.id);
-
-                        // Update task status to InReview for coding agent and setup script failures
-                        if matches!(
-                            process.process_type,
-                            ExecutionProcessType::CodingAgent | ExecutionProcessType::SetupScript
-                        ) {
-                            if let Ok(Some(task_attempt)) =
-                                TaskAttempt::find_by_id(&app_state.db_pool, process.task_attempt_id).await
-                            {
-                                if let Ok(Some(task)) =
-                                    Task::find_by_id(&app_state.db_pool, task_attempt.task_id).await
-                                {
-                                    if let Err(e) = Task::update_status(
-                                        &app_state.db_pool,
-                                        task.id,
-                                        task.project_id,
-                                        TaskStatus::InReview,
-                                    )
-                                    .await
-                                    {
-                                        tracing::error!("Failed to update task status to InReview for orphaned attempt: {}", e);
-                                    }
-                                }
-                            }
                         }
                     }
                 }
             }
-            _ = cleanup_interval.tick() => {
-                tracing::info!("Starting periodic worktree cleanup...");
-
-                // First, defensively check for externally deleted worktrees
-                check_externally_deleted_worktrees(&app_state.db_pool).await;
-
-                // Then, find and delete orphaned worktrees that don't belong to any task
-                cleanup_orphaned_worktrees(&app_state.db_pool).await;
+        }
+    }
+}
 
-                // Then, proceed with normal expired worktree cleanup
-                match TaskAttempt::find_expired_for_cleanup(&app_state.db_pool).await {
-                    Ok(expired_attempts) => {
-                        if expired_attempts.is_empty() {
-                            tracing::debug!("No expired worktrees found");
+/// Delete the worktrees of attempts whose retention expired
+async fn cleanup_expired_worktrees(pool: &sqlx::SqlitePool) {
+    match TaskAttempt::find_expired_for_cleanup(pool).await {
+        Ok(expired_attempts) => {
+            if expired_attempts.is_empty() {
+                tracing::debug!("No expired worktrees found");
+            } else {
+                tracing::info!(
+                    "Found {} expired worktrees to clean up",
+                    expired_attempts.len()
+                );
+                for (attempt_id, worktree_path, git_repo_path) in expired_attempts {
+                    if let Err(e) =
+                        delete_worktree(&worktree_path, &git_repo_path, attempt_id).await
+                    {
+                        tracing::error!("Failed to cleanup expired worktree {}: {}", attempt_id, e);
+                    } else {
+                        // Mark worktree as deleted in database after successful cleanup
+                        if let Err(e) = TaskAttempt::mark_worktree_deleted(pool, attempt_id).await {
+                            tracing::error!(
+                                "Failed to mark worktree as deleted in database for attempt {}: {}",
+                                attempt_id,
+                                e
+                            );
                         } else {
-                            tracing::info!("Found {} expired worktrees to clean up", expired_attempts.len());
-                            for (attempt_id, worktree_path, git_repo_path) in expired_attempts {
-                                if let Err(e) = delete_worktree(&worktree_path, &git_repo_path, attempt_id).await {
-                                    tracing::error!("Failed to cleanup expired worktree {}: {}", attempt_id, e);
-                                } else {
-                                    // Mark worktree as deleted in database after successful cleanup
-                                    if let Err(e) = crate::models::task_attempt::TaskAttempt::mark_worktree_deleted(&app_state.db_pool, attempt_id).await {
-                                        tracing::error!("Failed to mark worktree as deleted in database for attempt {}: {}", attempt_id, e);
-                                    } else {
-                                        tracing::info!("Successfully marked worktree as deleted for attempt {}", attempt_id);
-                                    }
-                                }
-                            }
+                            tracing::info!(
+                                "Successfully marked worktree as deleted for attempt {}",
+                                attempt_id
+                            );
                         }
                     }
-                    Err(e) => {
-                        tracing::error!("Failed to query expired task attempts: {}", e);
-                    }
                 }
             }
         }
+        Err(e) => {
+            tracing::error!("Failed to query expired task attempts: {}", e);
+        }
     }
 }
 
 /// Handle setup script completion
 async fn handle_setup_completion(
+    pool: &sqlx::SqlitePool,
     app_state: &AppState,
     task_attempt_id: Uuid,
     execution_process: ExecutionProcess,
@@ -745,8 +914,7 @@ async fn handle_setup_completion(
 ) {
     if success {
         // Mark setup as completed in database
-        if let Err(e) = TaskAttempt::mark_setup_completed(&app_state.db_pool, task_attempt_id).await
-        {
+        if let Err(e) = TaskAttempt::mark_setup_completed(pool, task_attempt_id).await {
             tracing::error!(
                 "Failed to mark setup as completed for attempt {}: {}",
                 task_attempt_id,
@@ -765,18 +933,14 @@ async fn handle_setup_completion(
 
         if let Some(delegation_context) = delegation_result {
             // Delegate to the original operation
-            handle_setup_delegation(app_state, delegation_context).await;
+            handle_setup_delegation(pool, app_state, delegation_context).await;
         } else {
             // Fallback to original behavior - start coding agent
-            if let Ok(Some(task_attempt)) =
-                TaskAttempt::find_by_id(&app_state.db_pool, task_attempt_id).await
-            {
-                if let Ok(Some(task)) =
-                    Task::find_by_id(&app_state.db_pool, task_attempt.task_id).await
-                {
+            if let Ok(Some(task_attempt)) = TaskAttempt::find_by_id(pool, task_attempt_id).await {
+                if let Ok(Some(task)) = Task::find_by_id(pool, task_attempt.task_id).await {
                     // Start the coding agent
                     if let Err(e) = ProcessService::start_coding_agent(
-                        &app_state.db_pool,
+                        pool,
                         app_state,
                         task_attempt_id,
                         task.id,
@@ -796,18 +960,10 @@ async fn handle_setup_completion(
         // Setup failed, update task status
 
         // Update task status to InReview since setup failed
-        if let Ok(Some(task_attempt)) =
-            TaskAttempt::find_by_id(&app_state.db_pool, task_attempt_id).await
-        {
-            if let Ok(Some(task)) = Task::find_by_id(&app_state.db_pool, task_attempt.task_id).await
-            {
-                if let Err(e) = Task::update_status(
-                    &app_state.db_pool,
-                    task.id,
-                    task.project_id,
-                    TaskStatus::InReview,
-                )
-                .await
+        if let Ok(Some(task_attempt)) = TaskAttempt::find_by_id(pool, task_attempt_id).await {
+            if let Ok(Some(task)) = Task::find_by_id(pool, task_attempt.task_id).await {
+                if let Err(e) =
+                    Task::update_status(pool, task.id, task.project_id, TaskStatus::InReview).await
                 {
                     tracing::error!(
                         "Failed to update task status to InReview after setup failure: {}",
@@ -819,84 +975,597 @@ async fn handle_setup_completion(
     }
 }
 
-/// Handle coding agent completion
-async fn handle_coding_agent_completion(
-    app_state: &AppState,
+/// Pin the session of the first successful run covered by the project's session affinity,
+/// so later tasks of the project continue it. `meta_pool` is the database holding the project.
+async fn pin_project_session(
+    pool: &sqlx::SqlitePool,
+    meta_pool: &sqlx::SqlitePool,
     task_attempt_id: Uuid,
     execution_process_id: Uuid,
-    execution_process: ExecutionProcess,
-    success: bool,
-    exit_code: Option<i64>,
-) {
-    // Extract and store assistant message from execution logs
-    let summary = if let Some(stdout) = &execution_process.stdout {
-        if let Some(assistant_message) = crate::executor::parse_assistant_message_from_logs(stdout)
-        {
-            if let Err(e) = crate::models::executor_session::ExecutorSession::update_summary(
-                &app_state.db_pool,
-                execution_process_id,
-                &assistant_message,
-            )
-            .await
-            {
-                tracing::error!(
-                    "Failed to update summary for execution process {}: {}",
-                    execution_process_id,
-                    e
-                );
-                None
-            } else {
-                tracing::info!(
-                    "Successfully stored summary for execution process {}",
-                    execution_process_id
-                );
-                Some(assistant_message)
-            }
-        } else {
-            None
-        }
-    } else {
-        None
+) -> Result<(), sqlx::Error> {
+    let Some(task_attempt) = TaskAttempt::find_by_id(pool, task_attempt_id).await? else {
+        return Ok(());
     };
+    let Some(task) = Task::find_by_id(pool, task_attempt.task_id).await? else {
+        return Ok(());
+    };
+    let Some(project) = Project::find_by_id(pool, task.project_id).await? else {
+        return Ok(());
+    };
+    if project.pinned_session_id.is_some()
+        || !project
+            .session_affinity
+            .applies_to(&Task::labels(pool, task.id).await?)
+    {
+        return Ok(());
+    }
 
-    // Send notifications if enabled
-    let sound_enabled = app_state.get_sound_alerts_enabled().await;
-    let push_enabled = app_state.get_push_notifications_enabled().await;
-
-    if sound_enabled || push_enabled {
-        let sound_file = app_state.get_sound_file().await;
-        let notification_config = NotificationConfig {
-            sound_enabled,
-            push_enabled,
-        };
+    let session_id =
+        crate::models::executor_session::ExecutorSession::find_by_execution_process_id(
+            pool,
+            execution_process_id,
+        )
+        .await?
+        .and_then(|session| session.session_id);
+    if let Some(session_id) = session_id {
+        Project::set_pinned_session_id(meta_pool, project.id, Some(&session_id)).await?;
+        tracing::info!(
+            "Pinned session {} for tasks of project {}",
+            session_id,
+            project.id
+        );
+    }
 
-        let notification_service = NotificationService::new(notification_config);
+    Ok(())
+}
 
-        // Get task attempt and task details for richer notification
-        let (notification_title, notification_message) = if let Ok(Some(task_attempt)) =
-            TaskAttempt::find_by_id(&app_state.db_pool, task_attempt_id).await
+/// Normalize the stored output of a finished run with the executor that produced it
+fn normalize_run(execution_process: &ExecutionProcess) -> Option<NormalizedConversation> {
+    let stdout = execution_process.stdout.as_deref()?;
+    let config = execution_process
+        .executor_type
+        .as_deref()?
+        .parse::<crate::executor::ExecutorConfig>()
+        .ok()?;
+    config
+        .create_executor()
+        .normalize_logs(stdout, &execution_process.working_directory)
+        .ok()
+}
+
+/// Store the session id found in the run's normalized logs on its task, so follow-ups can
+/// resume it from the task row
+async fn persist_task_session(
+    pool: &sqlx::SqlitePool,
+    task_attempt_id: Uuid,
+    conversation: Option<&NormalizedConversation>,
+) -> Result<(), sqlx::Error> {
+    let Some(session_id) = conversation.and_then(|c| c.session_id.as_deref()) else {
+        return Ok(());
+    };
+    let Some(task_attempt) = TaskAttempt::find_by_id(pool, task_attempt_id).await? else {
+        return Ok(());
+    };
+
+    Task::set_session_id(pool, task_attempt.task_id, session_id).await
+}
+
+/// Add the run's normalized entries to the conversation search index
+async fn index_run_conversation(
+    pool: &sqlx::SqlitePool,
+    task_attempt_id: Uuid,
+    execution_process_id: Uuid,
+    conversation: Option<&NormalizedConversation>,
+) -> Result<(), sqlx::Error> {
+    let Some(conversation) = conversation else {
+        return Ok(());
+    };
+    let Some(task_attempt) = TaskAttempt::find_by_id(pool, task_attempt_id).await? else {
+        return Ok(());
+    };
+
+    ConversationIndex::index_run(
+        pool,
+        task_attempt.task_id,
+        execution_process_id,
+        &conversation.entries,
+    )
+    .await
+}
+
+/// Record each tool use in the run's normalized entries for the tool statistics
+async fn record_run_tool_use(
+    pool: &sqlx::SqlitePool,
+    task_attempt_id: Uuid,
+    execution_process_id: Uuid,
+    conversation: Option<&NormalizedConversation>,
+) -> Result<(), sqlx::Error> {
+    let Some(conversation) = conversation else {
+        return Ok(());
+    };
+    let Some(task_attempt) = TaskAttempt::find_by_id(pool, task_attempt_id).await? else {
+        return Ok(());
+    };
+
+    ToolUseEvent::record_run(
+        pool,
+        task_attempt.task_id,
+        execution_process_id,
+        &conversation.entries,
+    )
+    .await
+}
+
+/// Record on the run's task that its latest run ended
+async fn mark_task_completed(
+    pool: &sqlx::SqlitePool,
+    task_attempt_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    let Some(task_attempt) = TaskAttempt::find_by_id(pool, task_attempt_id).await? else {
+        return Ok(());
+    };
+
+    Task::mark_completed(pool, task_attempt.task_id).await
+}
+
+/// Add the cost the executor reported for a run to its project's spend, kept in `meta_pool`
+async fn charge_run_cost(
+    pool: &sqlx::SqlitePool,
+    meta_pool: &sqlx::SqlitePool,
+    task_attempt_id: Uuid,
+    conversation: Option<&NormalizedConversation>,
+) -> Result<(), sqlx::Error> {
+    let Some(cost_usd) = conversation
+        .and_then(|conversation| conversation.stats.as_ref())
+        .and_then(|stats| stats.total_cost_usd)
+        .filter(|cost_usd| *cost_usd > 0.0)
+    else {
+        return Ok(());
+    };
+    let Some(task_attempt) = TaskAttempt::find_by_id(pool, task_attempt_id).await? else {
+        return Ok(());
+    };
+    let Some(task) = Task::find_by_id(pool, task_attempt.task_id).await? else {
+        return Ok(());
+    };
+
+    Project::add_cost(meta_pool, task.project_id, cost_usd).await
+}
+
+/// Summarize a finished run when its project has `auto_summarize` set. The extra Claude
+/// call runs in the background so it does not hold up the monitor.
+async fn summarize_run(
+    pool: &sqlx::SqlitePool,
+    task_attempt_id: Uuid,
+    run_id: Uuid,
+    conversation: Option<&NormalizedConversation>,
+    working_dir: &str,
+) -> Result<(), sqlx::Error> {
+    let Some(conversation) = conversation else {
+        return Ok(());
+    };
+    let Some(task_attempt) = TaskAttempt::find_by_id(pool, task_attempt_id).await? else {
+        return Ok(());
+    };
+    let Some(task) = Task::find_by_id(pool, task_attempt.task_id).await? else {
+        return Ok(());
+    };
+    let Some(project) = Project::find_by_id(pool, task.project_id).await? else {
+        return Ok(());
+    };
+    if !project.auto_summarize {
+        return Ok(());
+    }
+
+    let pool = pool.clone();
+    let mut conversation = conversation.clone();
+    let working_dir = working_dir.to_string();
+    tokio::spawn(async move {
+        match ConversationSummarizer::new()
+            .summarize(&mut conversation, &working_dir)
+            .await
+        {
+            Ok(Some(summary)) => {
+                if let Err(e) = TaskRun::set_summary(&pool, run_id, &summary).await {
+                    tracing::error!(
+                        "Failed to store summary of execution process {}: {}",
+                        run_id,
+                        e
+                    );
+                }
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to summarize execution process {}: {}", run_id, e),
+        }
+    });
+    Ok(())
+}
+
+/// Normalize a run's stored log again with the current parsing, without running anything,
+/// and keep the result. Returns `None` when the run does not exist or its executor cannot
+/// parse the log.
+pub async fn reparse_run(
+    pool: &sqlx::SqlitePool,
+    run_id: Uuid,
+) -> Result<Option<NormalizedConversation>, sqlx::Error> {
+    let Some(execution_process) = ExecutionProcess::find_by_id(pool, run_id).await? else {
+        return Ok(None);
+    };
+    let Some(conversation) = normalize_run(&execution_process) else {
+        return Ok(None);
+    };
+
+    let normalized_json =
+        serde_json::to_string(&conversation).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+    TaskRun::set_normalized_json(pool, run_id, &normalized_json).await?;
+    TaskRun::set_had_tool_errors(pool, run_id, conversation.has_errors()).await?;
+    TaskRun::set_command_counts(pool, run_id, &conversation.bash_command_counts()).await?;
+    Ok(Some(conversation))
+}
+
+/// Reparse every coding agent run, `REPARSE_BATCH_SIZE` at a time, e.g. after an executor's
+/// parsing improved. Returns how many runs were reparsed.
+pub async fn reparse_all_runs(pool: &sqlx::SqlitePool) -> Result<usize, sqlx::Error> {
+    let mut after_rowid = 0;
+    let mut reparsed = 0;
+    loop {
+        let runs = TaskRun::find_ids_after(pool, after_rowid, REPARSE_BATCH_SIZE).await?;
+        let Some(&(last_rowid, _)) = runs.last() else {
+            return Ok(reparsed);
+        };
+        after_rowid = last_rowid;
+
+        for (_, run_id) in runs {
+            match reparse_run(pool, run_id).await {
+                Ok(Some(_)) => reparsed += 1,
+                Ok(None) => {}
+                Err(e) => tracing::error!("Failed to reparse execution process {}: {}", run_id, e),
+            }
+        }
+    }
+}
+
+/// Index the conversations of runs that finished before the search index existed
+pub async fn backfill_conversation_index(pool: &sqlx::SqlitePool) {
+    let run_ids = match ConversationIndex::find_unindexed_run_ids(pool).await {
+        Ok(run_ids) => run_ids,
+        Err(e) => {
+            tracing::error!(
+                "Failed to find runs missing from the conversation index: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    for run_id in run_ids {
+        let execution_process = match ExecutionProcess::find_by_id(pool, run_id).await {
+            Ok(Some(execution_process)) => execution_process,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::error!("Failed to load execution process {}: {}", run_id, e);
+                continue;
+            }
+        };
+        let conversation = normalize_run(&execution_process);
+        if let Err(e) = index_run_conversation(
+            pool,
+            execution_process.task_attempt_id,
+            run_id,
+            conversation.as_ref(),
+        )
+        .await
+        {
+            tracing::error!(
+                "Failed to index conversation of execution process {}: {}",
+                run_id,
+                e
+            );
+        }
+    }
+}
+
+/// Compress the logs of processes that finished before logs were stored compressed
+pub async fn compress_stored_logs(pool: &sqlx::SqlitePool) {
+    match ExecutionProcess::compress_finished_stdout(pool).await {
+        Ok(0) => {}
+        Ok(count) => tracing::info!(
+            "Compressed the stored logs of {} execution processes",
+            count
+        ),
+        Err(e) => tracing::error!("Failed to compress stored execution process logs: {}", e),
+    }
+}
+
+/// Purge archived tasks of every task database once an hour, for as long as the server runs
+pub async fn archived_task_purger(app_state: AppState) {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600));
+    loop {
+        interval.tick().await;
+        match app_state.task_pools().await {
+            Ok(task_pools) => {
+                for pool in &task_pools {
+                    purge_archived_tasks(pool).await;
+                }
+            }
+            Err(e) => tracing::error!("Failed to open task databases: {}", e),
+        }
+    }
+}
+
+/// Delete tasks archived more than `ARCHIVED_TASK_RETENTION_DAYS` ago, together with their
+/// worktrees, and return how many were deleted
+pub async fn purge_archived_tasks(pool: &sqlx::SqlitePool) -> usize {
+    let expired = match Task::find_expired_archived(pool).await {
+        Ok(expired) => expired,
+        Err(e) => {
+            tracing::error!("Failed to find expired archived tasks: {}", e);
+            return 0;
+        }
+    };
+
+    let mut purged = 0;
+    for (task_id, project_id) in expired {
+        if let Err(e) = cleanup_task_worktrees(pool, task_id).await {
+            tracing::error!("Failed to cleanup worktrees for task {}: {}", task_id, e);
+            // Continue with deletion even if cleanup fails
+        }
+        match Task::delete(pool, task_id, project_id).await {
+            Ok(_) => purged += 1,
+            Err(e) => tracing::error!("Failed to purge archived task {}: {}", task_id, e),
+        }
+    }
+    if purged > 0 {
+        tracing::info!("Purged {} archived tasks", purged);
+    }
+    purged
+}
+
+/// Handle coding agent completion
+async fn handle_coding_agent_completion(
+    pool: &sqlx::SqlitePool,
+    app_state: &AppState,
+    task_attempt_id: Uuid,
+    execution_process: ExecutionProcess,
+    success: bool,
+    exit_code: Option<i64>,
+    run_trace: Option<&RunTrace>,
+) {
+    let execution_process_id = execution_process.id;
+    if let Err(e) = mark_task_completed(pool, task_attempt_id).await {
+        tracing::error!(
+            "Failed to record the end of execution process {} on its task: {}",
+            execution_process_id,
+            e
+        );
+    }
+
+    if let Err(e) = crate::mcp::client_config::remove(
+        &execution_process.working_directory,
+        execution_process_id,
+    )
+    .await
+    {
+        tracing::warn!(
+            "Failed to remove MCP config of execution process {}: {}",
+            execution_process_id,
+            e
+        );
+    }
+
+    // Record files the run wrote to its --output-dir
+    if let Some(output_dir) =
+        crate::executors::claude::take_output_dir(&execution_process.working_directory)
+    {
+        match TaskArtifact::collect_from_dir(pool, execution_process_id, &output_dir).await {
+            Ok(artifacts) => tracing::info!(
+                "Collected {} artifacts for execution process {}",
+                artifacts.len(),
+                execution_process_id
+            ),
+            Err(e) => tracing::error!(
+                "Failed to collect artifacts for execution process {}: {}",
+                execution_process_id,
+                e
+            ),
+        }
+    }
+
+    let conversation = match run_trace {
+        Some(run_trace) => run_trace
+            .normalize_logs_span()
+            .in_scope(|| normalize_run(&execution_process)),
+        None => normalize_run(&execution_process),
+    };
+    if let Err(e) = persist_task_session(pool, task_attempt_id, conversation.as_ref()).await {
+        tracing::error!(
+            "Failed to store session of execution process {} on its task: {}",
+            execution_process_id,
+            e
+        );
+    }
+
+    if let Err(e) = index_run_conversation(
+        pool,
+        task_attempt_id,
+        execution_process_id,
+        conversation.as_ref(),
+    )
+    .await
+    {
+        tracing::error!(
+            "Failed to index conversation of execution process {}: {}",
+            execution_process_id,
+            e
+        );
+    }
+
+    if let Err(e) = record_run_tool_use(
+        pool,
+        task_attempt_id,
+        execution_process_id,
+        conversation.as_ref(),
+    )
+    .await
+    {
+        tracing::error!(
+            "Failed to record tool uses of execution process {}: {}",
+            execution_process_id,
+            e
+        );
+    }
+
+    if let Err(e) = charge_run_cost(
+        pool,
+        &app_state.db_pool,
+        task_attempt_id,
+        conversation.as_ref(),
+    )
+    .await
+    {
+        tracing::error!(
+            "Failed to charge cost of execution process {} to its project: {}",
+            execution_process_id,
+            e
+        );
+    }
+
+    if let Some(conversation) = &conversation {
+        if let Err(e) =
+            TaskRun::set_had_tool_errors(pool, execution_process_id, conversation.has_errors())
+                .await
         {
-            if let Ok(Some(task)) = Task::find_by_id(&app_state.db_pool, task_attempt.task_id).await
+            tracing::error!(
+                "Failed to record tool errors of execution process {}: {}",
+                execution_process_id,
+                e
+            );
+        }
+        if let Err(e) = TaskRun::set_command_counts(
+            pool,
+            execution_process_id,
+            &conversation.bash_command_counts(),
+        )
+        .await
+        {
+            tracing::error!(
+                "Failed to record command counts of execution process {}: {}",
+                execution_process_id,
+                e
+            );
+        }
+    }
+
+    if let Err(e) = summarize_run(
+        pool,
+        task_attempt_id,
+        execution_process_id,
+        conversation.as_ref(),
+        &execution_process.working_directory,
+    )
+    .await
+    {
+        tracing::error!(
+            "Failed to start summary of execution process {}: {}",
+            execution_process_id,
+            e
+        );
+    }
+
+    if success {
+        if let Err(e) = pin_project_session(
+            pool,
+            &app_state.db_pool,
+            task_attempt_id,
+            execution_process_id,
+        )
+        .await
+        {
+            tracing::error!(
+                "Failed to pin session of execution process {}: {}",
+                execution_process_id,
+                e
+            );
+        }
+    }
+
+    // Extract and store assistant message from execution logs
+    let summary = if let Some(stdout) = &execution_process.stdout {
+        if let Some(assistant_message) = crate::executor::parse_assistant_message_from_logs(stdout)
+        {
+            if let Err(e) = crate::models::executor_session::ExecutorSession::update_summary(
+                pool,
+                execution_process_id,
+                &assistant_message,
+            )
+            .await
             {
-                let title = format!("Task Complete: {}", task.title);
-                let message = if success {
-                    format!(
-                        "✅ '{}' completed successfully\nBranch: {}\nExecutor: {}",
-                        task.title,
-                        task_attempt.branch,
-                        task_attempt.executor.as_deref().unwrap_or("default")
-                    )
+                tracing::error!(
+                    "Failed to update summary for execution process {}: {}",
+                    execution_process_id,
+                    e
+                );
+                None
+            } else {
+                tracing::info!(
+                    "Successfully stored summary for execution process {}",
+                    execution_process_id
+                );
+                Some(assistant_message)
+            }
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
+    // Send notifications if enabled
+    let sound_enabled = app_state.get_sound_alerts_enabled().await;
+    let push_enabled = app_state.get_push_notifications_enabled().await;
+
+    if sound_enabled || push_enabled {
+        let sound_file = app_state.get_sound_file().await;
+        let notification_config = NotificationConfig {
+            sound_enabled,
+            push_enabled,
+        };
+
+        let notification_service = NotificationService::new(notification_config);
+
+        // Get task attempt and task details for richer notification
+        let (notification_title, notification_message) =
+            if let Ok(Some(task_attempt)) = TaskAttempt::find_by_id(pool, task_attempt_id).await {
+                if let Ok(Some(task)) = Task::find_by_id(pool, task_attempt.task_id).await {
+                    let title = format!("Task Complete: {}", task.title);
+                    let message = if success {
+                        format!(
+                            "✅ '{}' completed successfully\nBranch: {}\nExecutor: {}",
+                            task.title,
+                            task_attempt.branch,
+                            task_attempt.executor.as_deref().unwrap_or("default")
+                        )
+                    } else {
+                        format!(
+                            "❌ '{}' execution failed\nBranch: {}\nExecutor: {}",
+                            task.title,
+                            task_attempt.branch,
+                            task_attempt.executor.as_deref().unwrap_or("default")
+                        )
+                    };
+                    (title, message)
                 } else {
-                    format!(
-                        "❌ '{}' execution failed\nBranch: {}\nExecutor: {}",
-                        task.title,
-                        task_attempt.branch,
-                        task_attempt.executor.as_deref().unwrap_or("default")
-                    )
-                };
-                (title, message)
+                    // Fallback if task not found
+                    let title = "Task Complete";
+                    let message = if success {
+                        "Task execution completed successfully"
+                    } else {
+                        "Task execution failed"
+                    };
+                    (title.to_string(), message.to_string())
+                }
             } else {
-                // Fallback if task not found
+                // Fallback if task attempt not found
                 let title = "Task Complete";
                 let message = if success {
                     "Task execution completed successfully"
@@ -904,17 +1573,7 @@ async fn handle_coding_agent_completion(
                     "Task execution failed"
                 };
                 (title.to_string(), message.to_string())
-            }
-        } else {
-            // Fallback if task attempt not found
-            let title = "Task Complete";
-            let message = if success {
-                "Task execution completed successfully"
-            } else {
-                "Task execution failed"
             };
-            (title.to_string(), message.to_string())
-        };
 
         notification_service
             .notify(&notification_title, &notification_message, &sound_file)
@@ -922,28 +1581,18 @@ async fn handle_coding_agent_completion(
     }
 
     // Get task attempt to access worktree path for committing changes
-    if let Ok(Some(task_attempt)) =
-        TaskAttempt::find_by_id(&app_state.db_pool, task_attempt_id).await
-    {
-        // Commit any unstaged changes after execution completion
-        if let Err(e) = commit_execution_changes(
-            &task_attempt.worktree_path,
-            task_attempt_id,
-            summary.as_deref(),
-        )
-        .await
-        {
-            tracing::error!(
-                "Failed to commit execution changes for attempt {}: {}",
-                task_attempt_id,
-                e
-            );
-        } else {
-            tracing::info!(
-                "Successfully committed execution changes for attempt {}",
-                task_attempt_id
-            );
+    if let Ok(Some(task_attempt)) = TaskAttempt::find_by_id(pool, task_attempt_id).await {
+        let finished_at = execution_process
+            .completed_at
+            .unwrap_or_else(chrono::Utc::now);
+        ExecutorExited {
+            task_id: task_attempt.task_id,
+            exit_code,
+            duration_ms: (finished_at - execution_process.started_at).num_milliseconds(),
         }
+        .emit();
+
+        commit_run_changes(pool, &task_attempt, success, summary.as_deref()).await;
 
         // Coding agent execution completed
         tracing::info!(
@@ -952,7 +1601,7 @@ async fn handle_coding_agent_completion(
         );
 
         // Get task to access task_id and project_id for status update
-        if let Ok(Some(task)) = Task::find_by_id(&app_state.db_pool, task_attempt.task_id).await {
+        if let Ok(Some(task)) = Task::find_by_id(pool, task_attempt.task_id).await {
             app_state
                 .track_analytics_event(
                     "task_attempt_finished",
@@ -967,19 +1616,54 @@ async fn handle_coding_agent_completion(
                 .await;
 
             // Update task status to InReview
-            if let Err(e) = Task::update_status(
-                &app_state.db_pool,
-                task.id,
-                task.project_id,
-                TaskStatus::InReview,
-            )
-            .await
+            if let Err(e) =
+                Task::update_status(pool, task.id, task.project_id, TaskStatus::InReview).await
             {
                 tracing::error!(
                     "Failed to update task status to InReview for completed attempt: {}",
                     e
                 );
             }
+
+            // The run's changes are committed, so the worktree can go once retention passes
+            WorktreeManager::release(task.project_id, &task_attempt.branch).await;
+
+            let (notification_type, message) = if success {
+                (
+                    NotificationType::TaskCompleted,
+                    format!("'{}' completed successfully", task.title),
+                )
+            } else {
+                (
+                    NotificationType::TaskFailed,
+                    format!("'{}' execution failed", task.title),
+                )
+            };
+            app_state
+                .send_notification(pool, Some(task.id), notification_type, &message)
+                .await;
+
+            let event = if success {
+                TASK_COMPLETED_EVENT
+            } else {
+                TASK_FAILED_EVENT
+            };
+            WebhookService::dispatch(
+                pool,
+                task.project_id,
+                event,
+                serde_json::json!({
+                    "task": {
+                        "id": task.id,
+                        "title": task.title,
+                    },
+                    "attempt_id": task_attempt_id,
+                    "execution_process_id": execution_process_id,
+                    "exit_code": exit_code,
+                    "stats": conversation.as_ref().and_then(|c| c.stats.as_ref()),
+                }),
+            )
+            .await;
         }
     } else {
         tracing::error!(
@@ -991,7 +1675,7 @@ async fn handle_coding_agent_completion(
 
 /// Handle dev server completion (future functionality)
 async fn handle_dev_server_completion(
-    app_state: &AppState,
+    pool: &sqlx::SqlitePool,
     task_attempt_id: Uuid,
     execution_process_id: Uuid,
     _execution_process: ExecutionProcess,
@@ -1017,13 +1701,9 @@ async fn handle_dev_server_completion(
         ExecutionProcessStatus::Failed
     };
 
-    if let Err(e) = ExecutionProcess::update_completion(
-        &app_state.db_pool,
-        execution_process_id,
-        process_status,
-        exit_code,
-    )
-    .await
+    if let Err(e) =
+        ExecutionProcess::update_completion(pool, execution_process_id, process_status, exit_code)
+            .await
     {
         tracing::error!(
             "Failed to update dev server execution process status: {}",
@@ -1031,3 +1711,192 @@ async fn handle_dev_server_completion(
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use sqlx::SqlitePool;
+
+    use super::*;
+    use crate::test_fixtures::{create_attempt, create_project, create_task, memory_pool};
+
+    const CLAUDE_LOG: &str = r#"{"type":"system","subtype":"init","session_id":"3f1c2a","cwd":"/tmp/test-worktree"}
+{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Reading the module"},{"type":"tool_use","id":"toolu_01","name":"Read","input":{"file_path":"/tmp/test-worktree/src/lib.rs"}}]},"session_id":"3f1c2a"}
+{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_01","content":"pub fn add() {}"}]},"session_id":"3f1c2a"}
+{"type":"result","subtype":"success","is_error":false,"duration_ms":6059,"num_turns":2,"result":"Done","total_cost_usd":0.0421,"usage":{"input_tokens":1200,"output_tokens":350}}"#;
+
+    /// A database holding one attempt with `runs` finished Claude runs
+    async fn setup(runs: usize) -> (SqlitePool, Vec<Uuid>) {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let task_id = create_task(&pool, project_id, "task").await;
+        let attempt_id = create_attempt(&pool, task_id, "/tmp/test-worktree").await;
+
+        let mut run_ids = Vec::new();
+        for _ in 0..runs {
+            let run_id = Uuid::new_v4();
+            sqlx::query(
+                "INSERT INTO execution_processes (id, task_attempt_id, process_type, executor_type, status, command, working_directory, stdout)
+                 VALUES ($1, $2, 'codingagent', 'claude', 'completed', 'claude', '/tmp/test-worktree', $3)",
+            )
+            .bind(run_id)
+            .bind(attempt_id)
+            .bind(CLAUDE_LOG)
+            .execute(&pool)
+            .await
+            .unwrap();
+            run_ids.push(run_id);
+        }
+        (pool, run_ids)
+    }
+
+    #[tokio::test]
+    async fn test_reparse_matches_original_parse() {
+        let (pool, run_ids) = setup(1).await;
+        let run_id = run_ids[0];
+        let original = normalize_run(
+            &ExecutionProcess::find_by_id(&pool, run_id)
+                .await
+                .unwrap()
+                .unwrap(),
+        )
+        .unwrap();
+        assert!(!original.entries.is_empty());
+        assert_eq!(
+            ExecutionProcess::compress_finished_stdout(&pool)
+                .await
+                .unwrap(),
+            1
+        );
+
+        let reparsed = reparse_run(&pool, run_id).await.unwrap().unwrap();
+        assert_eq!(reparsed, original);
+
+        let stored: String =
+            sqlx::query_scalar("SELECT normalized_json FROM execution_processes WHERE id = $1")
+                .bind(run_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        let stored: NormalizedConversation = serde_json::from_str(&stored).unwrap();
+        assert_eq!(stored, original);
+        let command_counts: String =
+            sqlx::query_scalar("SELECT command_counts FROM execution_processes WHERE id = $1")
+                .bind(run_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(command_counts, "{}");
+
+        assert!(reparse_run(&pool, Uuid::new_v4()).await.unwrap().is_none());
+    }
+
+    /// A git repository with one commit standing in for an attempt's worktree, and the
+    /// attempt of a task of a project with `use_commit_template` set as given
+    async fn setup_worktree(
+        use_commit_template: bool,
+    ) -> (SqlitePool, tempfile::TempDir, TaskAttempt) {
+        let worktree = tempfile::TempDir::new().unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(args)
+                .current_dir(worktree.path())
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?} failed", args);
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.name", "Test User"]);
+        git(&["config", "user.email", "test@example.com"]);
+        std::fs::write(worktree.path().join("notes.txt"), "hello\n").unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-q", "-m", "initial"]);
+
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        sqlx::query("UPDATE projects SET use_commit_template = $1")
+            .bind(use_commit_template)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let task_id = create_task(&pool, project_id, "task").await;
+        let attempt_id = create_attempt(&pool, task_id, &worktree.path().to_string_lossy()).await;
+        let attempt = TaskAttempt::find_by_id(&pool, attempt_id)
+            .await
+            .unwrap()
+            .unwrap();
+        (pool, worktree, attempt)
+    }
+
+    fn head_message(worktree: &Path) -> String {
+        let output = std::process::Command::new("git")
+            .args(["log", "-1", "--format=%s"])
+            .current_dir(worktree)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_successful_runs_use_the_commit_template_when_enabled() {
+        let (pool, worktree, attempt) = setup_worktree(true).await;
+        std::fs::write(worktree.path().join("notes.txt"), "hello\nworld\n").unwrap();
+
+        commit_run_changes(&pool, &attempt, true, Some("Added a line")).await;
+
+        assert_eq!(
+            head_message(worktree.path()),
+            format!("vibe-kanban: task [task:{}]", attempt.task_id)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_runs_are_committed_with_their_summary_otherwise() {
+        for (use_commit_template, success) in [(false, true), (true, false)] {
+            let (pool, worktree, attempt) = setup_worktree(use_commit_template).await;
+            std::fs::write(worktree.path().join("notes.txt"), "hello\nworld\n").unwrap();
+
+            commit_run_changes(&pool, &attempt, success, Some("Added a line")).await;
+
+            assert_eq!(head_message(worktree.path()), "Added a line");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_diff_without_commit_template() {
+        let (pool, worktree, attempt) = setup_worktree(false).await;
+        std::fs::write(worktree.path().join("notes.txt"), "hello\nworld\n").unwrap();
+
+        commit_run_changes(&pool, &attempt, true, Some("Added a line")).await;
+
+        let files = crate::services::git_service::latest_run_diff(worktree.path())
+            .await
+            .unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "notes.txt");
+    }
+
+    #[tokio::test]
+    async fn test_runs_without_changes_are_not_committed() {
+        let (pool, worktree, attempt) = setup_worktree(true).await;
+
+        commit_run_changes(&pool, &attempt, true, None).await;
+
+        assert_eq!(head_message(worktree.path()), "initial");
+    }
+
+    #[tokio::test]
+    async fn test_reparse_all_covers_every_batch() {
+        let (pool, run_ids) = setup(REPARSE_BATCH_SIZE as usize + 5).await;
+
+        assert_eq!(reparse_all_runs(&pool).await.unwrap(), run_ids.len());
+
+        let unparsed: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM execution_processes WHERE normalized_json IS NULL",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(unparsed, 0);
+    }
+}
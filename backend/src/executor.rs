@@ -1,22 +1,80 @@
-use std::str::FromStr;
+use std::{collections::HashMap, path::Path, str::FromStr, sync::Arc};
 
 use async_trait::async_trait;
+use futures_util::stream::{BoxStream, StreamExt};
 use serde::{Deserialize, Serialize};
-use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    sync::{broadcast, RwLock},
+};
+use tracing::Instrument;
 use ts_rs::TS;
 use uuid::Uuid;
 
-use crate::executors::{
-    AmpExecutor, CCRExecutor, CharmOpencodeExecutor, ClaudeExecutor, EchoExecutor, GeminiExecutor,
-    SetupScriptExecutor, SstOpencodeExecutor,
+use crate::{
+    executors::{
+        claude::ToolResult,
+        pipeline::{
+            redact_entry, ExecutorBuilder, LoggingMiddleware, RetryMiddleware,
+            SanitizationMiddleware,
+        },
+        AiderExecutor, AmpExecutor, CCRExecutor, CharmOpencodeExecutor, ClaudeExecutor,
+        EchoExecutor, ExecutorChain, GeminiExecutor, MistralExecutor, SetupScriptExecutor,
+        SstOpencodeExecutor,
+    },
+    models::task::Task,
+    utils::shell::find_in_path,
+    web_policy::WebPolicy,
 };
 
 // Constants for database streaming - fast for near-real-time updates
 const STDOUT_UPDATE_THRESHOLD: usize = 1;
 const BUFFER_SIZE_THRESHOLD: usize = 256;
 
+/// Output lines are cut off at this size; the rest of the line is discarded
+const MAX_LINE_BYTES: usize = 2 * 1024 * 1024;
+/// JSONL lines larger than this get their `content`/`text` fields truncated
+const TRUNCATE_LINE_THRESHOLD: usize = 1024 * 1024;
+/// Size a truncated `content`/`text` field is cut down to
+const TRUNCATED_FIELD_BYTES: usize = 500 * 1024;
+/// Lines buffered per run for live output subscribers; slower subscribers skip the excess
+const RUN_OUTPUT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Broadcast channels carrying the stdout lines of running processes, keyed by execution
+/// process ID. A run's channel exists from its first read until its stdout ends.
+pub type RunOutputChannels = Arc<RwLock<HashMap<Uuid, broadcast::Sender<String>>>>;
+
+/// Entries buffered per run for progress subscribers; slower subscribers skip the excess
+const RUN_PROGRESS_CHANNEL_CAPACITY: usize = 256;
+
+/// Broadcast channels carrying the entries executors report while their runs go, keyed by
+/// execution process ID. A run's channel exists while its executor's reporter is open.
+pub type RunProgressChannels = Arc<RwLock<HashMap<Uuid, broadcast::Sender<NormalizedEntry>>>>;
+
+/// Register a progress channel for `run_id` and return the sender its executor reports to.
+/// Entries are redacted before they are broadcast; the channel is removed once the
+/// returned sender is dropped.
+pub async fn register_run_progress(
+    channels: &RunProgressChannels,
+    run_id: Uuid,
+) -> tokio::sync::mpsc::Sender<NormalizedEntry> {
+    let (progress_tx, mut progress_rx) = tokio::sync::mpsc::channel(RUN_PROGRESS_CHANNEL_CAPACITY);
+    let (sender, _) = broadcast::channel(RUN_PROGRESS_CHANNEL_CAPACITY);
+    channels.write().await.insert(run_id, sender.clone());
+
+    let channels = channels.clone();
+    tokio::spawn(async move {
+        while let Some(entry) = progress_rx.recv().await {
+            // Nobody following the run is not an error
+            let _ = sender.send(redact_entry(entry));
+        }
+        channels.write().await.remove(&run_id);
+    });
+    progress_tx
+}
+
 /// Normalized conversation representation for different executor formats
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq)]
 #[ts(export)]
 pub struct NormalizedConversation {
     pub entries: Vec<NormalizedEntry>,
@@ -24,10 +82,92 @@ pub struct NormalizedConversation {
     pub executor_type: String,
     pub prompt: Option<String>,
     pub summary: Option<String>,
+    /// Token usage and cost reported by the executor at the end of the run
+    #[serde(default)]
+    pub stats: Option<ExecutionStats>,
+    #[serde(default)]
+    pub metadata: ConversationMetadata,
+}
+
+/// Usage figures from an executor's final result, each `None` when not reported
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS, PartialEq)]
+#[ts(export)]
+pub struct ExecutionStats {
+    pub num_turns: Option<u32>,
+    pub duration_ms: Option<u64>,
+    pub total_cost_usd: Option<f64>,
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+}
+
+/// Details about how a conversation was normalized
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS, PartialEq)]
+#[ts(export)]
+pub struct ConversationMetadata {
+    /// Debug entries left out of `entries` because the executor does not include them
+    pub debug_entry_count: u32,
+}
+
+/// Maximum number of distinct commands kept when profiling bash usage
+const BASH_COMMAND_PROFILE_LIMIT: usize = 10;
+
+impl NormalizedConversation {
+    /// Count how often each base command (first word) was run through `CommandRun` tool uses,
+    /// keeping only the most frequently called ones. File deletes and moves count as `rm`
+    /// and `mv`, since plain `rm` and `mv` commands are reported as those actions.
+    pub fn bash_command_counts(&self) -> HashMap<String, u32> {
+        let mut counts: HashMap<String, u32> = HashMap::new();
+        for entry in &self.entries {
+            let NormalizedEntryType::ToolUse { action_type, .. } = &entry.entry_type else {
+                continue;
+            };
+            let base = match action_type {
+                ActionType::CommandRun { command } => base_command(command),
+                ActionType::FileDelete { .. } => Some("rm"),
+                ActionType::FileMove { .. } => Some("mv"),
+                _ => None,
+            };
+            if let Some(base) = base {
+                *counts.entry(base.to_string()).or_insert(0) += entry.repeat_count;
+            }
+        }
+
+        let mut sorted: Vec<(String, u32)> = counts.into_iter().collect();
+        sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        sorted.truncate(BASH_COMMAND_PROFILE_LIMIT);
+        sorted.into_iter().collect()
+    }
+
+    /// Whether any tool use of the conversation failed
+    pub fn has_errors(&self) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| matches!(entry.entry_type, NormalizedEntryType::ToolError { .. }))
+    }
+
+    /// Wall-clock span from the first to the last timestamped entry
+    pub fn duration(&self) -> Option<chrono::Duration> {
+        let mut timestamps = self.entries.iter().filter_map(|entry| {
+            chrono::DateTime::parse_from_rfc3339(entry.timestamp.as_deref()?).ok()
+        });
+        let first = timestamps.next()?;
+        let last = timestamps.next_back().unwrap_or(first);
+        Some(last - first)
+    }
+}
+
+/// Extract the base command name from a shell command line, skipping leading
+/// `VAR=value` assignments and any directory prefix (e.g. `/usr/bin/git` -> `git`)
+pub fn base_command(command: &str) -> Option<&str> {
+    command
+        .split_whitespace()
+        .find(|word| !word.contains('=') || word.starts_with('='))
+        .map(|word| word.rsplit('/').next().unwrap_or(word))
+        .filter(|word| !word.is_empty())
 }
 
 /// Individual entry in a normalized conversation
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq)]
 #[ts(export)]
 pub struct NormalizedEntry {
     pub timestamp: Option<String>,
@@ -35,10 +175,37 @@ pub struct NormalizedEntry {
     pub content: String,
     #[ts(skip)]
     pub metadata: Option<serde_json::Value>,
+    /// How many identical consecutive entries this one stands for
+    #[serde(default = "default_repeat_count")]
+    pub repeat_count: u32,
+}
+
+fn default_repeat_count() -> u32 {
+    1
+}
+
+/// Merge each run of consecutive entries with the same type, content and metadata into its
+/// first entry, whose `repeat_count` becomes the length of the run. Entries that repeat
+/// with others in between are kept apart.
+pub fn collapse_repeated_entries(entries: Vec<NormalizedEntry>) -> Vec<NormalizedEntry> {
+    let mut collapsed: Vec<NormalizedEntry> = Vec::with_capacity(entries.len());
+    for entry in entries {
+        match collapsed.last_mut() {
+            Some(previous)
+                if previous.entry_type == entry.entry_type
+                    && previous.content == entry.content
+                    && previous.metadata == entry.metadata =>
+            {
+                previous.repeat_count += entry.repeat_count;
+            }
+            _ => collapsed.push(entry),
+        }
+    }
+    collapsed
 }
 
 /// Types of entries in a normalized conversation
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq)]
 #[serde(tag = "type", rename_all = "snake_case")]
 #[ts(export)]
 pub enum NormalizedEntryType {
@@ -48,24 +215,68 @@ pub enum NormalizedEntryType {
         tool_name: String,
         action_type: ActionType,
     },
+    /// What the tool use with id `tool_use_id` returned
+    ToolResult {
+        tool_use_id: String,
+    },
+    /// The tool use with id `tool_use_id` failed; the entry's content is its stderr
+    ToolError {
+        tool_use_id: String,
+        /// Exit code of a failed shell command, when the tool reported one
+        exit_code: Option<i32>,
+    },
     SystemMessage,
     ErrorMessage,
     Thinking,
+    /// Shell trace output, e.g. lines printed under `set -x`
+    DebugTrace,
+    /// Timestamped timing output such as `[12:34:56.789] ...`
+    Timing,
 }
 
 /// Types of tool actions that can be performed
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq)]
 #[serde(tag = "action", rename_all = "snake_case")]
 #[ts(export)]
 pub enum ActionType {
-    FileRead { path: String },
-    FileWrite { path: String },
-    CommandRun { command: String },
-    Search { query: String },
-    WebFetch { url: String },
-    TaskCreate { description: String },
-    PlanPresentation { plan: String },
-    Other { description: String },
+    FileRead {
+        path: String,
+    },
+    FileWrite {
+        path: String,
+    },
+    FileDelete {
+        path: String,
+    },
+    FileMove {
+        from: String,
+        to: String,
+    },
+    CommandRun {
+        command: String,
+    },
+    Search {
+        query: String,
+    },
+    WebFetch {
+        url: String,
+    },
+    TaskCreate {
+        description: String,
+    },
+    PlanPresentation {
+        plan: String,
+    },
+    /// Computer use beta: `action` is e.g. `screenshot`, `type` or `left_click`
+    ComputerUse {
+        // `action` is already the enum tag
+        #[serde(rename = "computer_action")]
+        action: String,
+        coordinate: Option<(u32, u32)>,
+    },
+    Other {
+        description: String,
+    },
 }
 
 /// Context information for spawn failures to provide comprehensive error details
@@ -141,12 +352,57 @@ impl From<&tokio::process::Command> for SpawnContext {
 pub enum ExecutorError {
     SpawnFailed {
         error: std::io::Error,
-        context: SpawnContext,
+        context: Box<SpawnContext>,
     },
     TaskNotFound,
     DatabaseError(sqlx::Error),
     ContextCollectionFailed(String),
     GitError(String),
+    Timeout {
+        session_id: Option<String>,
+        after_ms: u64,
+    },
+    CostLimitExceeded {
+        estimated_cost_usd: f64,
+        limit_usd: f64,
+    },
+    /// The CLI binary does not match the trusted binary manifest
+    UntrustedBinary(String),
+    /// The executor's settings are invalid or cannot work together
+    ConfigError {
+        message: String,
+    },
+    /// Every executor slot is taken and none freed up while waiting
+    PoolExhausted {
+        max_concurrent: usize,
+    },
+    /// The CLI exited without an answer to a single-turn prompt
+    NoResult(String),
+    /// A request to the executor, e.g. a context file, cannot be used
+    InvalidInput(String),
+    /// The program a command starts could not be found
+    BinaryNotFound {
+        binary_name: String,
+    },
+    /// The worktree a command runs in does not exist or cannot be entered
+    WorktreeNotAccessible {
+        path: String,
+    },
+    /// The worktree is outside the directories executors may run in, see
+    /// [`crate::security::ALLOWED_WORKTREE_BASE_DIRS_ENV`]
+    WorktreeNotAllowed {
+        path: String,
+    },
+    /// The task has no prompt to send to the executor
+    PromptEmpty,
+    /// The process was killed, by `signal` when it is known
+    ProcessKilled {
+        signal: Option<i32>,
+    },
+    /// The prompt could not be written to the process stdin
+    StdinWriteFailed {
+        source: std::io::Error,
+    },
 }
 
 impl std::fmt::Display for ExecutorError {
@@ -185,10 +441,70 @@ impl std::fmt::Display for ExecutorError {
                 write!(f, "Context collection failed: {}", msg)
             }
             ExecutorError::GitError(msg) => write!(f, "Git operation error: {}", msg),
+            ExecutorError::Timeout { session_id, .. } => match session_id {
+                Some(id) => write!(f, "Executor timed out (session {})", id),
+                None => write!(f, "Executor timed out"),
+            },
+            ExecutorError::CostLimitExceeded {
+                estimated_cost_usd,
+                limit_usd,
+            } => write!(
+                f,
+                "Estimated cost ${:.2} exceeded the task limit of ${:.2}",
+                estimated_cost_usd, limit_usd
+            ),
+            ExecutorError::UntrustedBinary(msg) => {
+                write!(f, "Refusing to run untrusted binary: {}", msg)
+            }
+            ExecutorError::ConfigError { message } => {
+                write!(f, "Invalid executor configuration: {}", message)
+            }
+            ExecutorError::PoolExhausted { max_concurrent } => write!(
+                f,
+                "All {} executor slots are busy, try again once a running task finishes",
+                max_concurrent
+            ),
+            ExecutorError::NoResult(msg) => write!(f, "Executor produced no result: {}", msg),
+            ExecutorError::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
+            ExecutorError::BinaryNotFound { binary_name } => {
+                write!(f, "Executable '{}' not found", binary_name)
+            }
+            ExecutorError::WorktreeNotAccessible { path } => {
+                write!(f, "Worktree '{}' is not accessible", path)
+            }
+            ExecutorError::WorktreeNotAllowed { path } => {
+                write!(f, "Worktree '{}' is outside the allowed directories", path)
+            }
+            ExecutorError::PromptEmpty => write!(f, "The task has no prompt to send"),
+            ExecutorError::ProcessKilled { signal } => match signal {
+                Some(signal) => write!(f, "Process was killed by signal {}", signal),
+                None => write!(f, "Process was killed"),
+            },
+            ExecutorError::StdinWriteFailed { source } => {
+                write!(
+                    f,
+                    "Failed to write the prompt to the process stdin: {}",
+                    source
+                )
+            }
         }
     }
 }
 
+/// Serialized as `{"error_code": ..., "message": ..., "detail": {...}}`, so clients can
+/// tell errors apart by their code
+impl Serialize for ExecutorError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("ExecutorError", 3)?;
+        state.serialize_field("error_code", self.error_code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("detail", &self.detail())?;
+        state.end()
+    }
+}
+
 impl std::error::Error for ExecutorError {}
 
 impl From<sqlx::Error> for ExecutorError {
@@ -224,6 +540,7 @@ impl From<crate::models::task_attempt::TaskAttemptError> for ExecutorError {
             crate::models::task_attempt::TaskAttemptError::GitHubService(e) => {
                 ExecutorError::GitError(format!("GitHub service error: {}", e))
             }
+            crate::models::task_attempt::TaskAttemptError::Executor(e) => e,
         }
     }
 }
@@ -231,7 +548,186 @@ impl From<crate::models::task_attempt::TaskAttemptError> for ExecutorError {
 impl ExecutorError {
     /// Create a new SpawnFailed error with context
     pub fn spawn_failed(error: std::io::Error, context: SpawnContext) -> Self {
-        ExecutorError::SpawnFailed { error, context }
+        ExecutorError::SpawnFailed {
+            error,
+            context: Box::new(context),
+        }
+    }
+
+    /// Like [`ExecutorError::spawn_failed`], telling a missing worktree or program apart from
+    /// other failures to start the command
+    pub fn from_spawn_error(error: std::io::Error, context: SpawnContext) -> Self {
+        if error.kind() != std::io::ErrorKind::NotFound {
+            return ExecutorError::spawn_failed(error, context);
+        }
+        if !Path::new(&context.working_dir).is_dir() {
+            ExecutorError::WorktreeNotAccessible {
+                path: context.working_dir,
+            }
+        } else {
+            ExecutorError::BinaryNotFound {
+                binary_name: context.command,
+            }
+        }
+    }
+
+    /// Stable snake_case identifier of the kind of error
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            ExecutorError::SpawnFailed { .. } => "spawn_failed",
+            ExecutorError::TaskNotFound => "task_not_found",
+            ExecutorError::DatabaseError(_) => "database_error",
+            ExecutorError::ContextCollectionFailed(_) => "context_collection_failed",
+            ExecutorError::GitError(_) => "git_error",
+            ExecutorError::Timeout { .. } => "timeout",
+            ExecutorError::CostLimitExceeded { .. } => "cost_limit_exceeded",
+            ExecutorError::UntrustedBinary(_) => "untrusted_binary",
+            ExecutorError::ConfigError { .. } => "config_error",
+            ExecutorError::PoolExhausted { .. } => "pool_exhausted",
+            ExecutorError::NoResult(_) => "no_result",
+            ExecutorError::InvalidInput(_) => "invalid_input",
+            ExecutorError::BinaryNotFound { .. } => "binary_not_found",
+            ExecutorError::WorktreeNotAccessible { .. } => "worktree_not_accessible",
+            ExecutorError::WorktreeNotAllowed { .. } => "worktree_not_allowed",
+            ExecutorError::PromptEmpty => "prompt_empty",
+            ExecutorError::ProcessKilled { .. } => "process_killed",
+            ExecutorError::StdinWriteFailed { .. } => "stdin_write_failed",
+        }
+    }
+
+    /// The error's fields; errors described by their message alone have none
+    fn detail(&self) -> serde_json::Value {
+        match self {
+            ExecutorError::SpawnFailed { error, context } => serde_json::json!({
+                "executor_type": context.executor_type,
+                "command": context.command,
+                "working_dir": context.working_dir,
+                "task_id": context.task_id,
+                "error": error.to_string(),
+            }),
+            ExecutorError::Timeout {
+                session_id,
+                after_ms,
+            } => serde_json::json!({ "session_id": session_id, "after_ms": after_ms }),
+            ExecutorError::CostLimitExceeded {
+                estimated_cost_usd,
+                limit_usd,
+            } => serde_json::json!({
+                "estimated_cost_usd": estimated_cost_usd,
+                "limit_usd": limit_usd,
+            }),
+            ExecutorError::PoolExhausted { max_concurrent } => {
+                serde_json::json!({ "max_concurrent": max_concurrent })
+            }
+            ExecutorError::BinaryNotFound { binary_name } => {
+                serde_json::json!({ "binary_name": binary_name })
+            }
+            ExecutorError::WorktreeNotAccessible { path }
+            | ExecutorError::WorktreeNotAllowed { path } => serde_json::json!({ "path": path }),
+            ExecutorError::ProcessKilled { signal } => serde_json::json!({ "signal": signal }),
+            ExecutorError::StdinWriteFailed { source } => {
+                serde_json::json!({ "source": source.to_string() })
+            }
+            _ => serde_json::json!({}),
+        }
+    }
+
+    /// Whether the process failed to start because the system was temporarily out of
+    /// resources (EAGAIN, ENOMEM), so the same command may succeed shortly
+    pub fn is_transient_spawn_failure(&self) -> bool {
+        matches!(
+            self,
+            ExecutorError::SpawnFailed { error, .. }
+                if matches!(
+                    error.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::OutOfMemory
+                )
+        )
+    }
+}
+
+/// Environment variable holding a JSON [`SpawnRetryPolicy`], e.g.
+/// `{"max_attempts": 5, "base_delay_ms": 200, "jitter": true}`
+pub const RETRY_POLICY_ENV: &str = "VIBE_EXECUTOR_RETRY_POLICY";
+
+/// How often a command is spawned again after a transient failure before moving on to the
+/// next command. Attempt `n` (from 0) waits `base_delay_ms * 2^n` before the retry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct SpawnRetryPolicy {
+    pub max_attempts: u8,
+    pub base_delay_ms: u64,
+    /// Wait a random duration between half and all of each delay, so processes that
+    /// failed together do not retry together
+    pub jitter: bool,
+}
+
+impl Default for SpawnRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_ms: 100,
+            jitter: true,
+        }
+    }
+}
+
+impl SpawnRetryPolicy {
+    /// Read the policy from `VIBE_EXECUTOR_RETRY_POLICY`; fields missing from the JSON keep
+    /// their defaults, and an unset or invalid value uses the default policy
+    pub fn from_env() -> Self {
+        match std::env::var(RETRY_POLICY_ENV) {
+            Ok(value) => serde_json::from_str(&value).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring invalid {}={:?}: {}", RETRY_POLICY_ENV, value, e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Delay before the retry that follows failed attempt `attempt` (from 0)
+    fn delay(&self, attempt: u32) -> std::time::Duration {
+        let delay_ms = self
+            .base_delay_ms
+            .saturating_mul(1u64.checked_shl(attempt).unwrap_or(u64::MAX));
+        let delay_ms = if self.jitter && delay_ms > 1 {
+            let nanos = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| u64::from(d.subsec_nanos()))
+                .unwrap_or(0);
+            delay_ms / 2 + nanos % (delay_ms - delay_ms / 2 + 1)
+        } else {
+            delay_ms
+        };
+        std::time::Duration::from_millis(delay_ms)
+    }
+
+    /// Run `spawn` until it succeeds, fails with a non-transient error or the attempts run
+    /// out, sleeping with exponential backoff between attempts
+    pub async fn retry<T, F, Fut>(&self, mut spawn: F) -> Result<T, ExecutorError>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T, ExecutorError>>,
+    {
+        let max_attempts = u32::from(self.max_attempts.max(1));
+        let mut attempt = 0;
+        loop {
+            match spawn().await {
+                Err(e) if e.is_transient_spawn_failure() && attempt + 1 < max_attempts => {
+                    let delay = self.delay(attempt);
+                    tracing::warn!(
+                        "Spawn attempt {}/{} failed, retrying in {:?}: {}",
+                        attempt + 1,
+                        max_attempts,
+                        delay,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
     }
 }
 
@@ -251,10 +747,59 @@ impl SpawnContext {
     }
 }
 
+/// Outcome of checking whether an executor could run a task, without spawning anything
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ValidationReport {
+    /// Command the run would execute; empty when the executor does not resolve one up front
+    pub command_resolved: String,
+    pub binary_exists: bool,
+    pub worktree_accessible: bool,
+    /// Whether the task has a title to build the prompt from
+    pub prompt_non_empty: bool,
+    pub all_ok: bool,
+}
+
+impl ValidationReport {
+    pub fn new(
+        command_resolved: String,
+        binary_exists: bool,
+        worktree_accessible: bool,
+        prompt_non_empty: bool,
+    ) -> Self {
+        Self {
+            command_resolved,
+            binary_exists,
+            worktree_accessible,
+            prompt_non_empty,
+            all_ok: binary_exists && worktree_accessible && prompt_non_empty,
+        }
+    }
+}
+
+/// Whether the program a shell command starts with is a file or can be found in `PATH`
+pub fn command_program_exists(command: &str) -> bool {
+    let program = command.split_whitespace().next().unwrap_or_default();
+    !program.is_empty() && (Path::new(program).is_file() || find_in_path(program).is_some())
+}
+
+/// Whether the task exists with a non-blank title, which every prompt starts from
+pub async fn task_has_prompt(
+    pool: &sqlx::SqlitePool,
+    task_id: Uuid,
+) -> Result<bool, ExecutorError> {
+    let task = Task::find_by_id(pool, task_id)
+        .await?
+        .ok_or(ExecutorError::TaskNotFound)?;
+    Ok(!task.title.trim().is_empty())
+}
+
 /// Trait for defining CLI commands that can be executed for task attempts
 #[async_trait]
 pub trait Executor: Send + Sync {
-    /// Spawn the command for a given task attempt
+    /// Spawn the command for a given task attempt. Executors do not bound how long the
+    /// command runs; a task's `timeout_seconds` is enforced by
+    /// `ProcessService::schedule_timeout` once the run is monitored.
     async fn spawn(
         &self,
         pool: &sqlx::SqlitePool,
@@ -275,9 +820,96 @@ pub trait Executor: Send + Sync {
             executor_type: "unknown".to_string(),
             prompt: None,
             summary: None,
+            stats: None,
+            metadata: Default::default(),
         })
     }
 
+    /// Normalize output lines as they arrive, e.g. from [`output_lines`].
+    /// The default waits for the last line and normalizes the whole output at once;
+    /// executors with line-oriented output yield each line's entries as soon as it is read.
+    fn normalize_logs_stream<'a>(
+        &'a self,
+        lines: BoxStream<'a, String>,
+        worktree_path: &'a str,
+    ) -> BoxStream<'a, NormalizedEntry> {
+        async_stream::stream! {
+            let logs: Vec<String> = lines.collect().await;
+            let entries = self
+                .normalize_logs(&logs.join("\n"), worktree_path)
+                .map(|conversation| conversation.entries)
+                .unwrap_or_default();
+            for entry in entries {
+                yield entry;
+            }
+        }
+        .boxed()
+    }
+
+    /// Normalize the chunks of a run's stderr output.
+    /// Returns `None` to show every chunk as an error message.
+    fn normalize_stderr(&self, _chunks: &[&str]) -> Option<Vec<NormalizedEntry>> {
+        None
+    }
+
+    /// Where to report entries normalized from output as it arrives, before the run finishes.
+    /// Returns `None` when nobody follows the run's progress.
+    fn progress_reporter(&self, _worktree_path: &str) -> Option<ProgressReporter> {
+        None
+    }
+
+    /// Check that the executor's settings are valid, e.g. that limits are positive.
+    /// Returns [`ExecutorError::ConfigError`] describing the first invalid setting.
+    fn validate_config(&self) -> Result<(), ExecutorError> {
+        Ok(())
+    }
+
+    /// Check that the executor can run, e.g. that its CLI is installed.
+    /// Returns a description of the problem when it cannot.
+    async fn health_check(&self) -> Result<(), String> {
+        self.validate_config().map_err(|e| e.to_string())
+    }
+
+    /// Dry run: check that a run of the task would start in `worktree_path` without spawning
+    /// it. The default relies on [`Executor::health_check`] to tell whether the CLI is there.
+    async fn validate(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<ValidationReport, ExecutorError> {
+        let prompt_non_empty = task_has_prompt(pool, task_id).await?;
+        Ok(ValidationReport::new(
+            String::new(),
+            self.health_check().await.is_ok(),
+            Path::new(worktree_path).is_dir(),
+            prompt_non_empty,
+        ))
+    }
+
+    /// Build a copy of this executor whose prompt starts with `prefix`.
+    /// Returns `None` when the executor does not support prompt prefixes.
+    fn with_prompt_prefix(&self, _prefix: &str) -> Option<Box<dyn Executor>> {
+        None
+    }
+
+    /// Build a copy of this executor whose prompt ends with `suffix`.
+    /// Returns `None` when the executor does not support prompt suffixes.
+    fn with_prompt_suffix(&self, _suffix: &str) -> Option<Box<dyn Executor>> {
+        None
+    }
+
+    /// Estimated cost in USD above which a streaming run is terminated, if any
+    fn cost_limit_usd(&self) -> Option<f64> {
+        None
+    }
+
+    /// The web policy a streaming run is terminated for breaking. Returns `None` when the
+    /// executor has no policy or cannot recognize web fetches in its output.
+    fn web_policy_guard(&self, _worktree_path: &str) -> Option<WebPolicyGuard> {
+        None
+    }
+
     // Note: Fast-path streaming is now handled by the Gemini WAL system.
     // The Gemini executor uses its own push_patch() method to emit patches,
     // which are automatically served via SSE endpoints with resumable streaming.
@@ -290,8 +922,12 @@ pub trait Executor: Send + Sync {
         attempt_id: Uuid,
         execution_process_id: Uuid,
         worktree_path: &str,
+        output_channels: &RunOutputChannels,
     ) -> Result<command_group::AsyncGroupChild, ExecutorError> {
-        let mut child = self.spawn(pool, task_id, worktree_path).await?;
+        let mut child = self
+            .spawn(pool, task_id, worktree_path)
+            .instrument(crate::telemetry::spawn_span())
+            .await?;
 
         // Take stdout and stderr pipes for streaming
         let stdout = child
@@ -305,6 +941,36 @@ pub trait Executor: Send + Sync {
             .take()
             .expect("Failed to take stderr from child process");
 
+        let stdout: Box<dyn tokio::io::AsyncRead + Unpin + Send> = match self.cost_limit_usd() {
+            Some(limit_usd) => Box::new(watch_cost_limit(
+                stdout,
+                child.id(),
+                limit_usd,
+                pool.clone(),
+                task_id,
+                execution_process_id,
+            )),
+            None => Box::new(stdout),
+        };
+
+        let stdout: Box<dyn tokio::io::AsyncRead + Unpin + Send> =
+            match self.web_policy_guard(worktree_path) {
+                Some(guard) => Box::new(watch_web_policy(
+                    stdout,
+                    child.id(),
+                    guard,
+                    pool.clone(),
+                    execution_process_id,
+                )),
+                None => stdout,
+            };
+
+        let stdout: Box<dyn tokio::io::AsyncRead + Unpin + Send> =
+            match self.progress_reporter(worktree_path) {
+                Some(reporter) => Box::new(report_progress(stdout, reporter)),
+                None => stdout,
+            };
+
         // Start streaming tasks
         let pool_clone1 = pool.clone();
         let pool_clone2 = pool.clone();
@@ -312,6 +978,7 @@ pub trait Executor: Send + Sync {
         tokio::spawn(stream_output_to_db(
             stdout,
             pool_clone1,
+            output_channels.clone(),
             attempt_id,
             execution_process_id,
             true,
@@ -319,6 +986,7 @@ pub trait Executor: Send + Sync {
         tokio::spawn(stream_output_to_db(
             stderr,
             pool_clone2,
+            output_channels.clone(),
             attempt_id,
             execution_process_id,
             false,
@@ -328,6 +996,342 @@ pub trait Executor: Send + Sync {
     }
 }
 
+/// Event name under which a run terminated for exceeding its cost limit is reported
+pub const COST_LIMIT_EXCEEDED_EVENT: &str = "task.cost_limit_exceeded";
+
+/// Pass `stdout` through byte for byte while estimating the run's cost from the `usage` of its
+/// assistant messages. Once the estimate exceeds `limit_usd`, the process group `pid` is sent
+/// SIGTERM and a [`ExecutorError::CostLimitExceeded`] note is added to the process stderr.
+fn watch_cost_limit(
+    stdout: impl tokio::io::AsyncRead + Unpin + Send + 'static,
+    pid: Option<u32>,
+    limit_usd: f64,
+    pool: sqlx::SqlitePool,
+    task_id: Uuid,
+    execution_process_id: Uuid,
+) -> tokio::io::DuplexStream {
+    use crate::{
+        executors::claude::CostTracker,
+        models::{execution_process::ExecutionProcess, task::Task},
+        services::WebhookService,
+    };
+
+    let (stdout, watched) = tee_output(stdout);
+
+    tokio::spawn(async move {
+        let mut lines = LineReader::new(watched);
+        let mut tracker = CostTracker::default();
+        let mut exceeded = false;
+
+        loop {
+            let line = match lines.next_line().await {
+                Ok(Some(line)) => line,
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::error!("Error reading stdout for cost tracking: {}", e);
+                    break;
+                }
+            };
+
+            let estimated_cost_usd = tracker.observe(&line);
+            if !exceeded && estimated_cost_usd > limit_usd {
+                exceeded = true;
+                let error = ExecutorError::CostLimitExceeded {
+                    estimated_cost_usd,
+                    limit_usd,
+                };
+                tracing::warn!(
+                    event = COST_LIMIT_EXCEEDED_EVENT,
+                    task_id = %task_id,
+                    execution_process_id = %execution_process_id,
+                    "{}",
+                    error
+                );
+                let note = format!("[vibe-kanban] {}\n", error);
+                if let Err(e) =
+                    ExecutionProcess::append_stderr(&pool, execution_process_id, &note).await
+                {
+                    tracing::error!(
+                        "Failed to record cost limit for execution process {}: {}",
+                        execution_process_id,
+                        e
+                    );
+                }
+                terminate_process_group(pid);
+
+                match Task::find_by_id(&pool, task_id).await {
+                    Ok(Some(task)) => {
+                        WebhookService::dispatch(
+                            &pool,
+                            task.project_id,
+                            COST_LIMIT_EXCEEDED_EVENT,
+                            serde_json::json!({
+                                "task": {
+                                    "id": task.id,
+                                    "title": task.title,
+                                },
+                                "execution_process_id": execution_process_id,
+                                "estimated_cost_usd": estimated_cost_usd,
+                                "limit_usd": limit_usd,
+                            }),
+                        )
+                        .await
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::error!(
+                        "Failed to load task {} for cost limit webhook: {}",
+                        task_id,
+                        e
+                    ),
+                }
+            }
+        }
+    });
+
+    stdout
+}
+
+/// Normalizes single output lines so their web fetches can be checked against a policy
+pub struct WebPolicyGuard {
+    policy: WebPolicy,
+    normalize_line: LineNormalizer,
+}
+
+impl WebPolicyGuard {
+    pub fn new(
+        policy: WebPolicy,
+        normalize_line: impl Fn(&str) -> Vec<NormalizedEntry> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            policy,
+            normalize_line: Box::new(normalize_line),
+        }
+    }
+}
+
+/// Pass `stdout` through byte for byte while checking each web fetch against the guard's policy.
+/// The first blocked fetch sends the process group `pid` SIGTERM and adds a note naming the
+/// domain to the process stderr.
+fn watch_web_policy(
+    stdout: impl tokio::io::AsyncRead + Unpin + Send + 'static,
+    pid: Option<u32>,
+    guard: WebPolicyGuard,
+    pool: sqlx::SqlitePool,
+    execution_process_id: Uuid,
+) -> tokio::io::DuplexStream {
+    use crate::models::execution_process::ExecutionProcess;
+
+    let (stdout, watched) = tee_output(stdout);
+
+    tokio::spawn(async move {
+        let mut lines = LineReader::new(watched);
+        let mut blocked = false;
+
+        loop {
+            let line = match lines.next_line().await {
+                Ok(Some(line)) => line,
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::error!("Error reading stdout for web policy checks: {}", e);
+                    break;
+                }
+            };
+
+            if !blocked && line.ends_with('\n') {
+                let domain = (guard.normalize_line)(&line)
+                    .iter()
+                    .find_map(|entry| guard.policy.blocked_domain(entry));
+                if let Some(domain) = domain {
+                    blocked = true;
+                    tracing::warn!(
+                        "Terminating execution process {}: web fetch blocked by project policy: {}",
+                        execution_process_id,
+                        domain
+                    );
+                    let note = format!("[vibe-kanban] Blocked by project policy: {}\n", domain);
+                    if let Err(e) =
+                        ExecutionProcess::append_stderr(&pool, execution_process_id, &note).await
+                    {
+                        tracing::error!(
+                            "Failed to record blocked fetch for execution process {}: {}",
+                            execution_process_id,
+                            e
+                        );
+                    }
+                    terminate_process_group(pid);
+                }
+            }
+        }
+    });
+
+    stdout
+}
+
+/// Copy `output` into two streams: the first passes it on byte for byte to be stored, the
+/// second is read by a watcher. Watchers read lines cut off at [`MAX_LINE_BYTES`]; the stored
+/// copy keeps oversized lines whole, so they are truncated once, knowing their real size,
+/// when they are stored. A watcher that stops reading does not hold up the stored copy.
+fn tee_output(
+    mut output: impl tokio::io::AsyncRead + Unpin + Send + 'static,
+) -> (tokio::io::DuplexStream, tokio::io::DuplexStream) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let (stored, mut stored_writer) = tokio::io::duplex(64 * 1024);
+    let (watched, mut watched_writer) = tokio::io::duplex(64 * 1024);
+
+    tokio::spawn(async move {
+        let mut buffer = vec![0; 8192];
+        let mut watching = true;
+        loop {
+            let read = match output.read(&mut buffer).await {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) => {
+                    tracing::error!("Error reading process output: {}", e);
+                    break;
+                }
+            };
+            if stored_writer.write_all(&buffer[..read]).await.is_err() {
+                break;
+            }
+            if watching && watched_writer.write_all(&buffer[..read]).await.is_err() {
+                watching = false;
+            }
+        }
+    });
+
+    (stored, watched)
+}
+
+/// Lines of a process's output as they are written, without their terminators
+pub fn output_lines(
+    output: impl tokio::io::AsyncRead + Unpin + Send + 'static,
+) -> BoxStream<'static, String> {
+    async_stream::stream! {
+        let mut lines = LineReader::new(output);
+        loop {
+            match lines.next_line().await {
+                Ok(Some(line)) => yield line.trim_end_matches(['\r', '\n']).to_string(),
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::error!("Error reading process output: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+    .boxed()
+}
+
+/// Turns one output line into the entries it stands for
+type LineNormalizer = Box<dyn Fn(&str) -> Vec<NormalizedEntry> + Send + Sync>;
+
+/// Normalizes single output lines and sends the entries to whoever follows a run
+pub struct ProgressReporter {
+    sender: tokio::sync::mpsc::Sender<NormalizedEntry>,
+    normalize_line: LineNormalizer,
+}
+
+impl ProgressReporter {
+    pub fn new(
+        sender: tokio::sync::mpsc::Sender<NormalizedEntry>,
+        normalize_line: impl Fn(&str) -> Vec<NormalizedEntry> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            sender,
+            normalize_line: Box::new(normalize_line),
+        }
+    }
+}
+
+/// Pass `stdout` through byte for byte while sending the entries of each complete line to the
+/// reporter. The raw output is still stored, so the final normalized logs hold every entry.
+fn report_progress(
+    stdout: impl tokio::io::AsyncRead + Unpin + Send + 'static,
+    reporter: ProgressReporter,
+) -> tokio::io::DuplexStream {
+    use tokio::sync::mpsc::error::TrySendError;
+
+    let (stdout, watched) = tee_output(stdout);
+
+    tokio::spawn(async move {
+        let mut lines = LineReader::new(watched);
+
+        loop {
+            let line = match lines.next_line().await {
+                Ok(Some(line)) => line,
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::error!("Error reading stdout for progress reporting: {}", e);
+                    break;
+                }
+            };
+
+            if !line.ends_with('\n') {
+                continue;
+            }
+            for entry in (reporter.normalize_line)(&line) {
+                match reporter.sender.try_send(entry) {
+                    Ok(()) => {}
+                    // A slow listener must not hold up the process; it can catch up from
+                    // the stored logs
+                    Err(TrySendError::Full(_)) => {
+                        tracing::debug!("Progress channel full, dropping entry")
+                    }
+                    // Nobody follows the run any more
+                    Err(TrySendError::Closed(_)) => return,
+                }
+            }
+        }
+    });
+
+    stdout
+}
+
+/// Ask a spawned process group to shut down
+fn terminate_process_group(pid: Option<u32>) {
+    let Some(pid) = pid else {
+        tracing::warn!("Cannot terminate a process without a PID");
+        return;
+    };
+
+    #[cfg(unix)]
+    {
+        use nix::{
+            sys::signal::{killpg, Signal},
+            unistd::Pid,
+        };
+        if let Err(e) = killpg(Pid::from_raw(pid as i32), Signal::SIGTERM) {
+            tracing::error!("Failed to send SIGTERM to process group {}: {}", pid, e);
+        }
+    }
+
+    #[cfg(not(unix))]
+    tracing::warn!("Terminating process {} is only supported on Unix", pid);
+}
+
+/// Limits on how often a timed out run is resumed automatically
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeoutResumePolicy {
+    pub max_resumes: u32,
+}
+
+impl Default for TimeoutResumePolicy {
+    fn default() -> Self {
+        Self { max_resumes: 1 }
+    }
+}
+
+fn with_default_middleware(executor: Box<dyn Executor>, retry_spawns: bool) -> Box<dyn Executor> {
+    let builder = ExecutorBuilder::new(executor).wrap(SanitizationMiddleware);
+    let builder = if retry_spawns {
+        builder.wrap(RetryMiddleware::default())
+    } else {
+        builder
+    };
+    builder.wrap(LoggingMiddleware).build()
+}
+
 /// Runtime executor types for internal use
 #[derive(Debug, Clone)]
 pub enum ExecutorType {
@@ -338,6 +1342,16 @@ pub enum ExecutorType {
         config: ExecutorConfig,
         session_id: Option<String>,
         prompt: String,
+        /// Files given to Claude alongside the prompt, relative to the worktree
+        context_files: Vec<String>,
+        /// Pre-computed tool outputs written to Claude ahead of the prompt
+        tool_results: Vec<ToolResult>,
+    },
+    /// A fresh session replacing one whose history grew past the project's
+    /// `max_context_turns`; `prompt_suffix` is appended to the task prompt
+    CondensedCodingAgent {
+        config: ExecutorConfig,
+        prompt_suffix: String,
     },
 }
 
@@ -349,6 +1363,8 @@ pub enum ExecutorConfig {
     Echo,
     Claude,
     ClaudePlan,
+    /// Claude in plan mode, then Claude implementing the plan it presented
+    ClaudePlanThenExecute,
     Amp,
     Gemini,
     #[serde(alias = "setup_script")]
@@ -360,6 +1376,8 @@ pub enum ExecutorConfig {
     CharmOpencode,
     #[serde(alias = "opencode")]
     SstOpencode,
+    Aider,
+    Mistral,
     // Future executors can be added here
     // Shell { command: String },
     // Docker { image: String, command: String },
@@ -381,11 +1399,14 @@ impl FromStr for ExecutorConfig {
             "echo" => Ok(ExecutorConfig::Echo),
             "claude" => Ok(ExecutorConfig::Claude),
             "claude-plan" => Ok(ExecutorConfig::ClaudePlan),
+            "claude-plan-then-execute" => Ok(ExecutorConfig::ClaudePlanThenExecute),
             "amp" => Ok(ExecutorConfig::Amp),
             "gemini" => Ok(ExecutorConfig::Gemini),
             "charm-opencode" => Ok(ExecutorConfig::CharmOpencode),
             "claude-code-router" => Ok(ExecutorConfig::ClaudeCodeRouter),
             "sst-opencode" => Ok(ExecutorConfig::SstOpencode),
+            "aider" => Ok(ExecutorConfig::Aider),
+            "mistral" => Ok(ExecutorConfig::Mistral),
             "setup-script" => Ok(ExecutorConfig::SetupScript {
                 script: "setup script".to_string(),
             }),
@@ -395,22 +1416,106 @@ impl FromStr for ExecutorConfig {
 }
 
 impl ExecutorConfig {
+    /// Every coding agent executor a task can be started with
+    pub fn coding_agents() -> Vec<ExecutorConfig> {
+        vec![
+            ExecutorConfig::Echo,
+            ExecutorConfig::Claude,
+            ExecutorConfig::ClaudePlan,
+            ExecutorConfig::ClaudePlanThenExecute,
+            ExecutorConfig::Amp,
+            ExecutorConfig::Gemini,
+            ExecutorConfig::ClaudeCodeRouter,
+            ExecutorConfig::CharmOpencode,
+            ExecutorConfig::SstOpencode,
+            ExecutorConfig::Aider,
+            ExecutorConfig::Mistral,
+        ]
+    }
+
+    /// Create the executor behind the default middleware: spawns are traced and retried
+    /// when the process fails to start transiently, and secrets are redacted from
+    /// normalized logs
     pub fn create_executor(&self) -> Box<dyn Executor> {
+        self.wrap_executor(self.create_unwrapped_executor())
+    }
+
+    /// Put an executor built for runs of this config behind the default middleware, e.g. one
+    /// configured per project
+    pub fn wrap_executor(&self, executor: Box<dyn Executor>) -> Box<dyn Executor> {
+        with_default_middleware(executor, !self.retries_own_spawns())
+    }
+
+    /// Claude-based executors already retry transient failures per command before falling
+    /// back to npx, so retrying them again from the middleware would multiply attempts
+    fn retries_own_spawns(&self) -> bool {
+        matches!(
+            self,
+            ExecutorConfig::Claude
+                | ExecutorConfig::ClaudePlan
+                | ExecutorConfig::ClaudePlanThenExecute
+                | ExecutorConfig::ClaudeCodeRouter
+        )
+    }
+
+    fn create_unwrapped_executor(&self) -> Box<dyn Executor> {
         match self {
             ExecutorConfig::Echo => Box::new(EchoExecutor),
             ExecutorConfig::Claude => Box::new(ClaudeExecutor::new()),
             ExecutorConfig::ClaudePlan => Box::new(ClaudeExecutor::new_plan_mode()),
+            ExecutorConfig::ClaudePlanThenExecute => Box::new(ExecutorChain::plan_then_execute(
+                Box::new(ClaudeExecutor::new_plan_mode()),
+                Box::new(ClaudeExecutor::new()),
+                true,
+            )),
             ExecutorConfig::Amp => Box::new(AmpExecutor),
             ExecutorConfig::Gemini => Box::new(GeminiExecutor),
             ExecutorConfig::ClaudeCodeRouter => Box::new(CCRExecutor::new()),
             ExecutorConfig::CharmOpencode => Box::new(CharmOpencodeExecutor),
             ExecutorConfig::SstOpencode => Box::new(SstOpencodeExecutor::new()),
+            ExecutorConfig::Aider => Box::new(AiderExecutor),
+            ExecutorConfig::Mistral => Box::new(MistralExecutor),
             ExecutorConfig::SetupScript { script } => {
                 Box::new(SetupScriptExecutor::new(script.clone()))
             }
         }
     }
 
+    /// Create the executor and validate its settings, so invalid configuration is caught
+    /// before anything is spawned
+    pub fn try_create_executor(&self) -> Result<Box<dyn Executor>, ExecutorError> {
+        let executor = self.create_executor();
+        executor.validate_config()?;
+        Ok(executor)
+    }
+
+    /// Like [`ExecutorConfig::try_create_executor`], running Claude executors on `model`
+    /// when set. Other executors have no model selection and ignore it.
+    pub fn try_create_executor_with_model(
+        &self,
+        model: Option<&str>,
+    ) -> Result<Box<dyn Executor>, ExecutorError> {
+        let executor: Box<dyn Executor> = match (self, model) {
+            (ExecutorConfig::Claude, Some(model)) => {
+                Box::new(ClaudeExecutor::new().with_model(model))
+            }
+            (ExecutorConfig::ClaudePlan, Some(model)) => {
+                Box::new(ClaudeExecutor::new_plan_mode().with_model(model))
+            }
+            (ExecutorConfig::ClaudePlanThenExecute, Some(model)) => {
+                Box::new(ExecutorChain::plan_then_execute(
+                    Box::new(ClaudeExecutor::new_plan_mode().with_model(model)),
+                    Box::new(ClaudeExecutor::new().with_model(model)),
+                    true,
+                ))
+            }
+            _ => self.create_unwrapped_executor(),
+        };
+        let executor = self.wrap_executor(executor);
+        executor.validate_config()?;
+        Ok(executor)
+    }
+
     pub fn config_path(&self) -> Option<std::path::PathBuf> {
         match self {
             ExecutorConfig::Echo => None,
@@ -418,7 +1523,9 @@ impl ExecutorConfig {
                 dirs::home_dir().map(|home| home.join(".opencode.json"))
             }
             ExecutorConfig::Claude => dirs::home_dir().map(|home| home.join(".claude.json")),
-            ExecutorConfig::ClaudePlan => dirs::home_dir().map(|home| home.join(".claude.json")),
+            ExecutorConfig::ClaudePlan | ExecutorConfig::ClaudePlanThenExecute => {
+                dirs::home_dir().map(|home| home.join(".claude.json"))
+            }
             ExecutorConfig::ClaudeCodeRouter => {
                 dirs::home_dir().map(|home| home.join(".claude.json"))
             }
@@ -438,6 +1545,8 @@ impl ExecutorConfig {
                     dirs::config_dir().map(|config| config.join("opencode").join("opencode.json"))
                 }
             }
+            ExecutorConfig::Aider => dirs::home_dir().map(|home| home.join(".aider.conf.yml")),
+            ExecutorConfig::Mistral => None,
             ExecutorConfig::SetupScript { .. } => None,
         }
     }
@@ -450,9 +1559,12 @@ impl ExecutorConfig {
             ExecutorConfig::SstOpencode => Some(vec!["mcp"]),
             ExecutorConfig::Claude => Some(vec!["mcpServers"]),
             ExecutorConfig::ClaudePlan => Some(vec!["mcpServers"]),
+            ExecutorConfig::ClaudePlanThenExecute => Some(vec!["mcpServers"]),
             ExecutorConfig::Amp => Some(vec!["amp", "mcpServers"]), // Nested path for Amp
             ExecutorConfig::Gemini => Some(vec!["mcpServers"]),
             ExecutorConfig::ClaudeCodeRouter => Some(vec!["mcpServers"]),
+            ExecutorConfig::Aider => None, // aider doesn't support MCP
+            ExecutorConfig::Mistral => None, // codestral doesn't support MCP
             ExecutorConfig::SetupScript { .. } => None, // Setup scripts don't support MCP
         }
     }
@@ -461,7 +1573,10 @@ impl ExecutorConfig {
     pub fn supports_mcp(&self) -> bool {
         !matches!(
             self,
-            ExecutorConfig::Echo | ExecutorConfig::SetupScript { .. }
+            ExecutorConfig::Echo
+                | ExecutorConfig::Aider
+                | ExecutorConfig::Mistral
+                | ExecutorConfig::SetupScript { .. }
         )
     }
 
@@ -473,9 +1588,12 @@ impl ExecutorConfig {
             ExecutorConfig::SstOpencode => "SST Opencode",
             ExecutorConfig::Claude => "Claude",
             ExecutorConfig::ClaudePlan => "Claude Plan",
+            ExecutorConfig::ClaudePlanThenExecute => "Claude Plan then Execute",
             ExecutorConfig::Amp => "Amp",
             ExecutorConfig::Gemini => "Gemini",
             ExecutorConfig::ClaudeCodeRouter => "Claude Code Router",
+            ExecutorConfig::Aider => "Aider",
+            ExecutorConfig::Mistral => "Mistral Codestral",
             ExecutorConfig::SetupScript { .. } => "Setup Script",
         }
     }
@@ -487,11 +1605,14 @@ impl std::fmt::Display for ExecutorConfig {
             ExecutorConfig::Echo => "echo",
             ExecutorConfig::Claude => "claude",
             ExecutorConfig::ClaudePlan => "claude-plan",
+            ExecutorConfig::ClaudePlanThenExecute => "claude-plan-then-execute",
             ExecutorConfig::Amp => "amp",
             ExecutorConfig::Gemini => "gemini",
             ExecutorConfig::SstOpencode => "sst-opencode",
             ExecutorConfig::CharmOpencode => "charm-opencode",
             ExecutorConfig::ClaudeCodeRouter => "claude-code-router",
+            ExecutorConfig::Aider => "aider",
+            ExecutorConfig::Mistral => "mistral",
             ExecutorConfig::SetupScript { .. } => "setup-script",
         };
         write!(f, "{}", s)
@@ -502,37 +1623,222 @@ impl std::fmt::Display for ExecutorConfig {
 pub async fn stream_output_to_db(
     output: impl tokio::io::AsyncRead + Unpin,
     pool: sqlx::SqlitePool,
+    output_channels: RunOutputChannels,
     attempt_id: Uuid,
     execution_process_id: Uuid,
     is_stdout: bool,
 ) {
     if is_stdout {
-        stream_stdout_to_db(output, pool, attempt_id, execution_process_id).await;
+        stream_stdout_to_db(
+            output,
+            pool,
+            output_channels,
+            attempt_id,
+            execution_process_id,
+        )
+        .await;
     } else {
         stream_stderr_to_db(output, pool, attempt_id, execution_process_id).await;
     }
 }
 
+/// Reads newline-terminated lines from a byte stream.
+///
+/// Bytes are accumulated in a buffer until the terminating `\n` arrives, so JSONL lines
+/// split across several reads (or larger than the `BufReader` capacity) are reassembled
+/// intact. Lines longer than the limit keep only their first bytes; the count of discarded
+/// bytes is available from [`LineReader::dropped_bytes`] and the size of the line as read
+/// from [`LineReader::line_size`]. The buffer survives a cancelled
+/// `next_line` call, which makes it safe to use under `tokio::time::timeout`.
+struct LineReader<R> {
+    reader: BufReader<R>,
+    buffer: Vec<u8>,
+    max_line_bytes: usize,
+    dropped: usize,
+    last_dropped: usize,
+    last_size: usize,
+}
+
+impl<R: tokio::io::AsyncRead + Unpin> LineReader<R> {
+    fn new(reader: R) -> Self {
+        Self::with_max_line_bytes(reader, MAX_LINE_BYTES)
+    }
+
+    fn with_max_line_bytes(reader: R, max_line_bytes: usize) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            buffer: Vec::new(),
+            max_line_bytes,
+            dropped: 0,
+            last_dropped: 0,
+            last_size: 0,
+        }
+    }
+
+    /// Returns the next complete line including its `\n`, the trailing partial line at
+    /// EOF, or `None` once the stream is exhausted
+    async fn next_line(&mut self) -> std::io::Result<Option<String>> {
+        loop {
+            let available = self.reader.fill_buf().await?;
+            if available.is_empty() {
+                if self.buffer.is_empty() && self.dropped == 0 {
+                    return Ok(None);
+                }
+                break;
+            }
+
+            let (chunk, complete) = match available.iter().position(|b| *b == b'\n') {
+                Some(end) => (&available[..=end], true),
+                None => (available, false),
+            };
+            let consumed = chunk.len();
+            let keep = consumed.min(self.max_line_bytes.saturating_sub(self.buffer.len()));
+            self.buffer.extend_from_slice(&chunk[..keep]);
+            self.dropped += consumed - keep;
+            self.reader.consume(consumed);
+
+            if complete {
+                break;
+            }
+        }
+
+        self.last_size = self.buffer.len() + self.dropped;
+        // A cut-off line lost its terminator with the discarded bytes
+        if self.dropped > 0 && self.buffer.last() != Some(&b'\n') {
+            self.buffer.push(b'\n');
+        }
+        let line = String::from_utf8_lossy(&self.buffer).into_owned();
+        self.buffer.clear();
+        self.last_dropped = std::mem::take(&mut self.dropped);
+        Ok(Some(line))
+    }
+
+    /// Number of bytes discarded from the line last returned by `next_line`
+    fn dropped_bytes(&self) -> usize {
+        self.last_dropped
+    }
+
+    /// Size in bytes of the line last returned by `next_line` as it was read from the
+    /// stream, before invalid UTF-8 was replaced and including any discarded bytes
+    fn line_size(&self) -> usize {
+        self.last_size
+    }
+}
+
+/// Shrink the `content` and `text` fields of an oversized JSONL line so it can be stored
+/// and rendered. Returns `None` when the line is small enough or not valid JSON.
+pub fn truncate_oversized_line(line: &str) -> Option<String> {
+    fn truncate_fields(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, field) in map.iter_mut() {
+                    match field {
+                        serde_json::Value::String(text)
+                            if (key == "content" || key == "text")
+                                && text.len() > TRUNCATED_FIELD_BYTES =>
+                        {
+                            let original_size = text.len();
+                            let mut end = TRUNCATED_FIELD_BYTES;
+                            while !text.is_char_boundary(end) {
+                                end -= 1;
+                            }
+                            text.truncate(end);
+                            text.push_str(&format!(
+                                "[TRUNCATED: original size {} bytes]",
+                                original_size
+                            ));
+                        }
+                        _ => truncate_fields(field),
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => items.iter_mut().for_each(truncate_fields),
+            _ => {}
+        }
+    }
+
+    if line.len() <= TRUNCATE_LINE_THRESHOLD {
+        return None;
+    }
+    let mut json: serde_json::Value = serde_json::from_str(line.trim_end()).ok()?;
+    truncate_fields(&mut json);
+    Some(format!("{}\n", json))
+}
+
+/// Valid JSONL line standing in for an output line cut off at [`MAX_LINE_BYTES`], whose
+/// remaining start would not parse. Its `content` keeps the first [`TRUNCATED_FIELD_BYTES`]
+/// of the line followed by a truncation marker.
+pub fn truncated_line_placeholder(line: &str, original_size: usize) -> String {
+    let mut end = TRUNCATED_FIELD_BYTES.min(line.len());
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    let placeholder = serde_json::json!({
+        "type": "system",
+        "subtype": "output_truncated",
+        "original_size": original_size,
+        "content": format!(
+            "{}[TRUNCATED: original size {} bytes]",
+            &line[..end],
+            original_size
+        ),
+    });
+    format!("{}\n", placeholder)
+}
+
 /// Stream stdout from a child process to the database (immediate updates)
 async fn stream_stdout_to_db(
     output: impl tokio::io::AsyncRead + Unpin,
     pool: sqlx::SqlitePool,
+    output_channels: RunOutputChannels,
     attempt_id: Uuid,
     execution_process_id: Uuid,
 ) {
     use crate::models::{execution_process::ExecutionProcess, executor_session::ExecutorSession};
 
-    let mut reader = BufReader::new(output);
-    let mut line = String::new();
+    let mut reader = LineReader::new(output);
     let mut accumulated_output = String::new();
     let mut update_counter = 0;
     let mut session_id_parsed = false;
 
+    let (live_output, _) = broadcast::channel(RUN_OUTPUT_CHANNEL_CAPACITY);
+    output_channels
+        .write()
+        .await
+        .insert(execution_process_id, live_output.clone());
+
     loop {
-        line.clear();
-        match reader.read_line(&mut line).await {
-            Ok(0) => break, // EOF
-            Ok(_) => {
+        match reader.next_line().await {
+            Ok(None) => break, // EOF
+            Ok(Some(line)) => {
+                let original_size = reader.line_size();
+                let (line, truncated_from) = match reader.dropped_bytes() {
+                    0 => match truncate_oversized_line(&line) {
+                        Some(truncated) => (truncated, Some(original_size)),
+                        None => (line, None),
+                    },
+                    _ => (
+                        truncated_line_placeholder(&line, original_size),
+                        Some(original_size),
+                    ),
+                };
+                if let Some(original_size) = truncated_from {
+                    tracing::warn!(
+                        "Truncated an output line of {} bytes from execution process {}",
+                        original_size,
+                        execution_process_id
+                    );
+                    if let Err(e) =
+                        ExecutionProcess::record_truncated_line(&pool, execution_process_id).await
+                    {
+                        tracing::error!(
+                            "Failed to record line truncation for execution process {}: {}",
+                            execution_process_id,
+                            e
+                        );
+                    }
+                }
+
                 // Parse session ID from the first JSONL line
                 if !session_id_parsed {
                     if let Some(external_session_id) = parse_session_id_from_line(&line) {
@@ -559,6 +1865,9 @@ async fn stream_stdout_to_db(
                     }
                 }
 
+                // Nobody may be listening, which is fine
+                let _ = live_output.send(line.clone());
+
                 accumulated_output.push_str(&line);
                 update_counter += 1;
 
@@ -604,6 +1913,9 @@ async fn stream_stdout_to_db(
             tracing::error!("Failed to flush stdout for attempt {}: {}", attempt_id, e);
         }
     }
+
+    // Dropping the last sender ends every subscriber's stream
+    output_channels.write().await.remove(&execution_process_id);
 }
 
 /// Stream stderr from a child process to the database (buffered with timeout)
@@ -615,24 +1927,21 @@ async fn stream_stderr_to_db(
 ) {
     use tokio::time::{timeout, Duration};
 
-    let mut reader = BufReader::new(output);
-    let mut line = String::new();
+    let mut reader = LineReader::new(output);
     let mut accumulated_output = String::new();
     const STDERR_FLUSH_TIMEOUT_MS: u64 = 100; // Fast flush for near-real-time streaming
     const STDERR_FLUSH_TIMEOUT: Duration = Duration::from_millis(STDERR_FLUSH_TIMEOUT_MS);
 
     loop {
-        line.clear();
-
-        // Try to read a line with a timeout
-        let read_result = timeout(STDERR_FLUSH_TIMEOUT, reader.read_line(&mut line)).await;
+        // Try to read a line with a timeout; a partial line stays buffered in the reader
+        let read_result = timeout(STDERR_FLUSH_TIMEOUT, reader.next_line()).await;
 
         match read_result {
-            Ok(Ok(0)) => {
+            Ok(Ok(None)) => {
                 // EOF - flush remaining output and break
                 break;
             }
-            Ok(Ok(_)) => {
+            Ok(Ok(Some(line))) => {
                 // Successfully read a line - just accumulate it
                 accumulated_output.push_str(&line);
             }
@@ -813,7 +2122,10 @@ fn parse_session_id_from_line(line: &str) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::executors::{AmpExecutor, ClaudeExecutor};
+    use crate::{
+        executors::{AmpExecutor, ClaudeExecutor},
+        test_fixtures::{create_attempt, create_project, create_task, memory_pool},
+    };
 
     #[test]
     fn test_parse_claude_session_id() {
@@ -835,6 +2147,337 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_report_progress_sends_entries_and_forwards_output() {
+        use tokio::io::AsyncReadExt;
+
+        let output = "first line\nsecond line\n";
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(8);
+        let reporter = ProgressReporter::new(sender, |line| {
+            vec![NormalizedEntry {
+                timestamp: None,
+                entry_type: NormalizedEntryType::AssistantMessage,
+                content: line.trim().to_string(),
+                metadata: None,
+                repeat_count: 1,
+            }]
+        });
+
+        let mut forwarded = String::new();
+        report_progress(std::io::Cursor::new(output.as_bytes().to_vec()), reporter)
+            .read_to_string(&mut forwarded)
+            .await
+            .unwrap();
+        assert_eq!(forwarded, output);
+
+        let mut contents = Vec::new();
+        while let Some(entry) = receiver.recv().await {
+            contents.push(entry.content);
+        }
+        assert_eq!(contents, vec!["first line", "second line"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_watch_web_policy_terminates_blocked_fetch() {
+        use std::os::unix::process::ExitStatusExt;
+
+        use command_group::AsyncCommandGroup;
+        use tokio::io::AsyncReadExt;
+
+        let mut child = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg("echo 'fetch https://docs.rs/'; echo 'fetch https://pastebin.com/raw/x'; sleep 30")
+            .stdout(std::process::Stdio::piped())
+            .group_spawn()
+            .unwrap();
+        let stdout = child.inner().stdout.take().unwrap();
+        let guard = WebPolicyGuard::new(
+            WebPolicy {
+                allowlist: None,
+                denylist: vec!["pastebin.com".to_string()],
+            },
+            |line| {
+                let url = line.trim().strip_prefix("fetch ").unwrap_or_default();
+                vec![NormalizedEntry {
+                    timestamp: None,
+                    entry_type: NormalizedEntryType::ToolUse {
+                        tool_name: "WebFetch".to_string(),
+                        action_type: ActionType::WebFetch {
+                            url: url.to_string(),
+                        },
+                    },
+                    content: url.to_string(),
+                    metadata: None,
+                    repeat_count: 1,
+                }]
+            },
+        );
+        let pool = sqlx::SqlitePool::connect_lazy("sqlite::memory:").unwrap();
+
+        let mut forwarded = String::new();
+        watch_web_policy(stdout, child.id(), guard, pool, Uuid::new_v4())
+            .read_to_string(&mut forwarded)
+            .await
+            .unwrap();
+        let status = tokio::time::timeout(std::time::Duration::from_secs(5), child.wait())
+            .await
+            .expect("the blocked fetch terminates the run")
+            .unwrap();
+
+        assert_eq!(status.signal(), Some(libc::SIGTERM));
+        assert_eq!(
+            forwarded,
+            "fetch https://docs.rs/\nfetch https://pastebin.com/raw/x\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_line_reader_reassembles_chunked_jsonl() {
+        use tokio::io::AsyncWriteExt;
+
+        let line = r#"{"type":"assistant","message":{"content":[{"type":"text","text":"hello"}]}}"#;
+        let (mut writer, reader) = tokio::io::duplex(16);
+        let chunks = vec![
+            line[..10].to_string(),
+            line[10..40].to_string(),
+            format!("{}\n", &line[40..]),
+        ];
+        tokio::spawn(async move {
+            for chunk in chunks {
+                writer.write_all(chunk.as_bytes()).await.unwrap();
+                writer.flush().await.unwrap();
+                tokio::task::yield_now().await;
+            }
+        });
+
+        let mut reader = LineReader::new(reader);
+        assert_eq!(
+            reader.next_line().await.unwrap(),
+            Some(format!("{}\n", line))
+        );
+        assert_eq!(reader.next_line().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_line_reader_handles_lines_larger_than_buffer() {
+        let line = format!("{{\"data\":\"{}\"}}", "x".repeat(100 * 1024));
+        let input = format!("{}\ntrailing", line);
+
+        let mut reader = LineReader::new(input.as_bytes());
+        assert_eq!(
+            reader.next_line().await.unwrap(),
+            Some(format!("{}\n", line))
+        );
+        assert_eq!(
+            reader.next_line().await.unwrap(),
+            Some("trailing".to_string())
+        );
+        assert_eq!(reader.next_line().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_line_reader_caps_line_length() {
+        let input = format!("{}\nnext\n", "x".repeat(100));
+
+        let mut reader = LineReader::with_max_line_bytes(input.as_bytes(), 10);
+        assert_eq!(
+            reader.next_line().await.unwrap(),
+            Some(format!("{}\n", "x".repeat(10)))
+        );
+        assert_eq!(reader.dropped_bytes(), 91);
+        assert_eq!(reader.line_size(), 101);
+        assert_eq!(
+            reader.next_line().await.unwrap(),
+            Some("next\n".to_string())
+        );
+        assert_eq!(reader.dropped_bytes(), 0);
+        assert_eq!(reader.line_size(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_line_reader_sizes_invalid_utf8_lines_in_raw_bytes() {
+        let input: &[u8] = b"\xff\xfe\xfd\n";
+
+        let mut reader = LineReader::new(input);
+        let line = reader.next_line().await.unwrap().unwrap();
+        assert_eq!(line, "\u{FFFD}\u{FFFD}\u{FFFD}\n");
+        assert_eq!(reader.line_size(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_jsonl_line_is_truncated() {
+        let content = "y".repeat(1536 * 1024);
+        let line = serde_json::json!({
+            "type": "user",
+            "message": {
+                "role": "user",
+                "content": [{"type": "tool_result", "tool_use_id": "toolu_01", "content": content}]
+            }
+        });
+        let input = format!("{}\n{{\"type\":\"result\"}}\n", line);
+
+        let mut reader = LineReader::new(input.as_bytes());
+        let read = reader.next_line().await.unwrap().unwrap();
+        assert_eq!(reader.dropped_bytes(), 0);
+
+        let truncated = truncate_oversized_line(&read).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&truncated).unwrap();
+        let result = json["message"]["content"][0]["content"].as_str().unwrap();
+        assert!(result.starts_with(&"y".repeat(TRUNCATED_FIELD_BYTES)));
+        assert!(result.ends_with(&format!(
+            "[TRUNCATED: original size {} bytes]",
+            content.len()
+        )));
+
+        // Regular lines pass through untouched
+        let next = reader.next_line().await.unwrap().unwrap();
+        assert_eq!(truncate_oversized_line(&next), None);
+    }
+
+    #[tokio::test]
+    async fn test_line_over_max_size_is_stored_as_placeholder() {
+        use crate::models::{execution_process::ExecutionProcess, task_run::TaskRun};
+
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let task_id = create_task(&pool, project_id, "t").await;
+        let attempt_id = create_attempt(&pool, task_id, "/tmp").await;
+        let run_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO execution_processes (id, task_attempt_id, process_type, status, command, working_directory)
+             VALUES ($1, $2, 'codingagent', 'running', 'claude', '/tmp')",
+        )
+        .bind(run_id)
+        .bind(attempt_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let oversized = serde_json::json!({
+            "type": "user",
+            "message": {"content": "z".repeat(3 * 1024 * 1024)}
+        })
+        .to_string();
+        let input = format!("{}\n{{\"type\":\"result\"}}\n", oversized);
+        stream_output_to_db(
+            input.as_bytes(),
+            pool.clone(),
+            RunOutputChannels::default(),
+            attempt_id,
+            run_id,
+            true,
+        )
+        .await;
+
+        let process = ExecutionProcess::find_by_id(&pool, run_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let stdout = process.stdout.unwrap();
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let placeholder: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(placeholder["subtype"], "output_truncated");
+        assert_eq!(placeholder["original_size"], oversized.len() + 1);
+        let content = placeholder["content"].as_str().unwrap();
+        assert!(content.starts_with(&oversized[..TRUNCATED_FIELD_BYTES]));
+        assert!(content.ends_with(&format!(
+            "[TRUNCATED: original size {} bytes]",
+            oversized.len() + 1
+        )));
+        assert_eq!(lines[1], "{\"type\":\"result\"}");
+
+        let run = TaskRun::find_by_id_and_task_id(&pool, run_id, task_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(run.truncated_line_count, 1);
+        assert_eq!(process.stderr, None);
+    }
+
+    #[tokio::test]
+    async fn test_watchers_pass_lines_over_max_size_through_whole() {
+        use crate::models::{execution_process::ExecutionProcess, task_run::TaskRun};
+
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let task_id = create_task(&pool, project_id, "t").await;
+        let attempt_id = create_attempt(&pool, task_id, "/tmp").await;
+        let run_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO execution_processes (id, task_attempt_id, process_type, status, command, working_directory)
+             VALUES ($1, $2, 'codingagent', 'running', 'claude', '/tmp')",
+        )
+        .bind(run_id)
+        .bind(attempt_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let oversized = serde_json::json!({
+            "type": "user",
+            "message": {"content": "z".repeat(3 * 1024 * 1024)}
+        })
+        .to_string();
+        let input = format!("{}\n{{\"type\":\"result\"}}\n", oversized);
+        let (sender, _receiver) = tokio::sync::mpsc::channel(8);
+        let reporter = ProgressReporter::new(sender, |_| Vec::new());
+        let watched = report_progress(std::io::Cursor::new(input.into_bytes()), reporter);
+        stream_output_to_db(
+            watched,
+            pool.clone(),
+            RunOutputChannels::default(),
+            attempt_id,
+            run_id,
+            true,
+        )
+        .await;
+
+        let stdout = ExecutionProcess::find_by_id(&pool, run_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .stdout
+            .unwrap();
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let placeholder: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(placeholder["original_size"], oversized.len() + 1);
+        assert!(placeholder["content"]
+            .as_str()
+            .unwrap()
+            .starts_with(&oversized[..TRUNCATED_FIELD_BYTES]));
+        assert_eq!(lines[1], "{\"type\":\"result\"}");
+        let run = TaskRun::find_by_id_and_task_id(&pool, run_id, task_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(run.truncated_line_count, 1);
+    }
+
+    #[test]
+    fn test_computer_use_action_serialization() {
+        let action = ActionType::ComputerUse {
+            action: "left_click".to_string(),
+            coordinate: Some((100, 200)),
+        };
+
+        let json = serde_json::to_value(&action).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "action": "computer_use",
+                "computer_action": "left_click",
+                "coordinate": [100, 200],
+            })
+        );
+        assert!(matches!(
+            serde_json::from_value::<ActionType>(json).unwrap(),
+            ActionType::ComputerUse { action, coordinate: Some((100, 200)) } if action == "left_click"
+        ));
+    }
+
     #[test]
     fn test_parse_invalid_json() {
         let invalid_line = "not json at all";
@@ -995,4 +2638,258 @@ mod tests {
         // Should be the task description, not "Tool: Task with input: ..."
         assert_eq!(task_tool_use.content, "Find vibe-kanban projects");
     }
+
+    fn command_entry(command: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: "Bash".to_string(),
+                action_type: ActionType::CommandRun {
+                    command: command.to_string(),
+                },
+            },
+            content: format!("`{}`", command),
+            metadata: None,
+            repeat_count: 1,
+        }
+    }
+
+    #[test]
+    fn test_base_command() {
+        assert_eq!(base_command("cargo test --workspace"), Some("cargo"));
+        assert_eq!(base_command("/usr/bin/git status"), Some("git"));
+        assert_eq!(base_command("RUST_LOG=debug cargo run"), Some("cargo"));
+        assert_eq!(base_command("   "), None);
+    }
+
+    #[test]
+    fn test_bash_command_counts_keeps_top_ten() {
+        let mut entries = vec![
+            command_entry("git status"),
+            command_entry("git diff"),
+            command_entry("git add -A"),
+            command_entry("cargo build"),
+            command_entry("cargo test"),
+        ];
+        for i in 0..12 {
+            entries.push(command_entry(&format!("tool{:02} --flag", i)));
+        }
+        entries.push(NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::AssistantMessage,
+            content: "git is not a command here".to_string(),
+            metadata: None,
+            repeat_count: 1,
+        });
+
+        let conversation = NormalizedConversation {
+            entries,
+            session_id: None,
+            executor_type: "claude".to_string(),
+            prompt: None,
+            summary: None,
+            stats: None,
+            metadata: Default::default(),
+        };
+
+        let counts = conversation.bash_command_counts();
+        assert_eq!(counts.len(), 10);
+        assert_eq!(counts.get("git"), Some(&3));
+        assert_eq!(counts.get("cargo"), Some(&2));
+        // Ties are broken alphabetically, so only the first eight single-use tools survive
+        assert_eq!(counts.get("tool07"), Some(&1));
+        assert!(!counts.contains_key("tool08"));
+    }
+
+    #[test]
+    fn test_bash_command_counts_include_file_deletes_and_moves() {
+        let file_entry = |action_type: ActionType| NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: "Bash".to_string(),
+                action_type,
+            },
+            content: String::new(),
+            metadata: None,
+            repeat_count: 1,
+        };
+        let conversation = NormalizedConversation {
+            entries: vec![
+                command_entry("rm -r a.rs b.rs"),
+                file_entry(ActionType::FileDelete {
+                    path: "src/foo.rs".to_string(),
+                }),
+                file_entry(ActionType::FileMove {
+                    from: "src/a.rs".to_string(),
+                    to: "src/b.rs".to_string(),
+                }),
+            ],
+            session_id: None,
+            executor_type: "claude".to_string(),
+            prompt: None,
+            summary: None,
+            stats: None,
+            metadata: Default::default(),
+        };
+
+        let counts = conversation.bash_command_counts();
+        assert_eq!(counts.get("rm"), Some(&2));
+        assert_eq!(counts.get("mv"), Some(&1));
+    }
+
+    #[test]
+    fn test_collapse_repeated_entries_merges_identical_runs() {
+        let mut first = command_entry("curl https://example.com");
+        first.timestamp = Some("2025-07-01T10:00:00Z".to_string());
+        let entries = vec![
+            first.clone(),
+            command_entry("curl https://example.com"),
+            command_entry("curl https://example.com"),
+        ];
+
+        let collapsed = collapse_repeated_entries(entries);
+        assert_eq!(collapsed.len(), 1);
+        assert_eq!(collapsed[0].repeat_count, 3);
+        assert_eq!(collapsed[0].timestamp, first.timestamp);
+    }
+
+    #[test]
+    fn test_collapse_repeated_entries_keeps_non_adjacent_repeats() {
+        let entries = vec![
+            command_entry("cargo test"),
+            command_entry("git status"),
+            command_entry("git diff"),
+            command_entry("cargo test"),
+        ];
+
+        let collapsed = collapse_repeated_entries(entries.clone());
+        assert_eq!(collapsed, entries);
+        assert!(collapsed.iter().all(|entry| entry.repeat_count == 1));
+    }
+
+    #[test]
+    fn test_binary_not_found_serializes_with_error_code() {
+        let error = ExecutorError::BinaryNotFound {
+            binary_name: "claude".to_string(),
+        };
+
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "error_code": "binary_not_found",
+                "message": "Executable 'claude' not found",
+                "detail": { "binary_name": "claude" },
+            })
+        );
+    }
+
+    #[test]
+    fn test_spawn_errors_tell_missing_worktree_from_missing_binary() {
+        let worktree = tempfile::tempdir().unwrap();
+        let mut command = tokio::process::Command::new("claude");
+        command.current_dir(worktree.path());
+        let not_found = || std::io::Error::from(std::io::ErrorKind::NotFound);
+
+        let error = ExecutorError::from_spawn_error(
+            not_found(),
+            SpawnContext::from_command(&command, "Claude"),
+        );
+        assert_eq!(error.error_code(), "binary_not_found");
+
+        command.current_dir(worktree.path().join("missing"));
+        let error = ExecutorError::from_spawn_error(
+            not_found(),
+            SpawnContext::from_command(&command, "Claude"),
+        );
+        assert_eq!(error.error_code(), "worktree_not_accessible");
+
+        let error = ExecutorError::from_spawn_error(
+            std::io::Error::from(std::io::ErrorKind::PermissionDenied),
+            SpawnContext::from_command(&command, "Claude"),
+        );
+        assert!(matches!(error, ExecutorError::SpawnFailed { .. }));
+    }
+
+    fn spawn_failure(kind: std::io::ErrorKind) -> ExecutorError {
+        let command = tokio::process::Command::new("claude");
+        SpawnContext::from_command(&command, "Claude").spawn_error(std::io::Error::from(kind))
+    }
+
+    #[tokio::test]
+    async fn test_retry_policy_retries_transient_spawn_failures() {
+        let policy = SpawnRetryPolicy {
+            max_attempts: 3,
+            base_delay_ms: 1,
+            jitter: false,
+        };
+        let mut spawns = 0;
+
+        let result = policy
+            .retry(|| {
+                spawns += 1;
+                let attempt = spawns;
+                async move {
+                    if attempt <= 2 {
+                        Err(spawn_failure(std::io::ErrorKind::WouldBlock))
+                    } else {
+                        Ok(attempt)
+                    }
+                }
+            })
+            .await;
+
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(spawns, 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_policy_gives_up_on_permanent_failures() {
+        let policy = SpawnRetryPolicy {
+            max_attempts: 3,
+            base_delay_ms: 1,
+            jitter: false,
+        };
+        let mut spawns = 0;
+
+        let result: Result<(), _> = policy
+            .retry(|| {
+                spawns += 1;
+                async { Err(spawn_failure(std::io::ErrorKind::NotFound)) }
+            })
+            .await;
+
+        assert!(matches!(result, Err(ExecutorError::SpawnFailed { .. })));
+        assert_eq!(spawns, 1);
+    }
+
+    #[test]
+    fn test_retry_policy_parses_partial_json() {
+        let policy: SpawnRetryPolicy = serde_json::from_str(r#"{"max_attempts": 5}"#).unwrap();
+        assert_eq!(
+            policy,
+            SpawnRetryPolicy {
+                max_attempts: 5,
+                ..SpawnRetryPolicy::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_retry_policy_delay_doubles_per_attempt() {
+        let policy = SpawnRetryPolicy {
+            max_attempts: 3,
+            base_delay_ms: 100,
+            jitter: false,
+        };
+        assert_eq!(policy.delay(0), std::time::Duration::from_millis(100));
+        assert_eq!(policy.delay(2), std::time::Duration::from_millis(400));
+
+        let jittered = SpawnRetryPolicy {
+            jitter: true,
+            ..policy
+        };
+        let delay = jittered.delay(1);
+        assert!(delay >= std::time::Duration::from_millis(100));
+        assert!(delay <= std::time::Duration::from_millis(200));
+    }
 }
@@ -0,0 +1,113 @@
+//! Structured tracing events for executor lifecycle milestones
+//!
+//! Each event is logged at `INFO` under the [`TARGET`] target, with an `event` field naming
+//! it and its data as key-value fields. A `tracing_subscriber` layer can filter on the target
+//! to forward the events to a metrics backend.
+
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// Target every executor lifecycle event is logged under
+pub const TARGET: &str = "executor_events";
+
+/// An executor process was started
+#[derive(Debug, Clone)]
+pub struct ExecutorSpawned<'a> {
+    pub task_id: Uuid,
+    pub executor_type: &'a str,
+    /// Hash of the command line, so runs can be grouped without logging their arguments
+    pub command_hash: String,
+    pub worktree: &'a str,
+}
+
+impl<'a> ExecutorSpawned<'a> {
+    pub fn new(task_id: Uuid, executor_type: &'a str, command: &str, worktree: &'a str) -> Self {
+        Self {
+            task_id,
+            executor_type,
+            command_hash: command_hash(command),
+            worktree,
+        }
+    }
+
+    pub fn emit(&self) {
+        tracing::info!(
+            target: TARGET,
+            event = "executor_spawned",
+            task_id = %self.task_id,
+            executor_type = self.executor_type,
+            command_hash = %self.command_hash,
+            worktree = self.worktree,
+            "Executor spawned"
+        );
+    }
+}
+
+/// An executor process finished
+#[derive(Debug, Clone)]
+pub struct ExecutorExited {
+    pub task_id: Uuid,
+    /// `None` when the process was killed by a signal
+    pub exit_code: Option<i64>,
+    pub duration_ms: i64,
+}
+
+impl ExecutorExited {
+    pub fn emit(&self) {
+        tracing::info!(
+            target: TARGET,
+            event = "executor_exited",
+            task_id = %self.task_id,
+            exit_code = self.exit_code,
+            duration_ms = self.duration_ms,
+            "Executor exited"
+        );
+    }
+}
+
+/// The primary command could not be spawned and the fallback command is tried instead
+#[derive(Debug, Clone)]
+pub struct ExecutorFallbackTriggered<'a> {
+    pub task_id: Uuid,
+    pub reason: &'a str,
+}
+
+impl ExecutorFallbackTriggered<'_> {
+    pub fn emit(&self) {
+        tracing::info!(
+            target: TARGET,
+            event = "executor_fallback_triggered",
+            task_id = %self.task_id,
+            reason = self.reason,
+            "Executor fallback triggered"
+        );
+    }
+}
+
+/// An executor process could not be started
+#[derive(Debug, Clone)]
+pub struct ExecutorSpawnFailed<'a> {
+    pub task_id: Uuid,
+    pub error: &'a str,
+}
+
+impl ExecutorSpawnFailed<'_> {
+    pub fn emit(&self) {
+        tracing::info!(
+            target: TARGET,
+            event = "executor_spawn_failed",
+            task_id = %self.task_id,
+            error = self.error,
+            "Executor spawn failed"
+        );
+    }
+}
+
+/// First 16 hex digits of the SHA-256 of `command`
+pub(crate) fn command_hash(command: &str) -> String {
+    Sha256::digest(command.as_bytes())
+        .iter()
+        .take(8)
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
@@ -0,0 +1,292 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tokio::sync::oneshot;
+
+use crate::executor::ExecutorError;
+
+/// Environment variable overriding how many executors may run at once per project
+pub const MAX_CONCURRENT_ENV: &str = "VIBE_MAX_CONCURRENT_EXECUTORS";
+const DEFAULT_MAX_CONCURRENT: usize = 4;
+/// How long a spawn waits for a free slot before giving up with `PoolExhausted`
+const DEFAULT_QUEUE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Caps how many executor processes run at the same time.
+///
+/// Each running process holds a permit; it is released when the permit is dropped,
+/// which the caller should tie to the process exiting. A released slot goes to the
+/// waiting caller with the highest priority, the longest-waiting one among equals.
+#[derive(Debug, Clone)]
+pub struct ExecutorPool {
+    max_concurrent: usize,
+    slots: Arc<Mutex<Slots>>,
+    queue_timeout: Duration,
+}
+
+#[derive(Debug)]
+struct Slots {
+    available: usize,
+    waiters: Vec<Waiter>,
+    next_ticket: u64,
+}
+
+#[derive(Debug)]
+struct Waiter {
+    ticket: u64,
+    priority: i64,
+    /// Waiting this long raises the priority by one, repeatedly
+    boost_after: Option<Duration>,
+    enqueued_at: Instant,
+    sender: oneshot::Sender<ExecutorPermit>,
+}
+
+impl Waiter {
+    fn effective_priority(&self, now: Instant) -> i64 {
+        let boost = match self.boost_after {
+            Some(period) if !period.is_zero() => {
+                (now.duration_since(self.enqueued_at).as_secs_f64() / period.as_secs_f64()) as i64
+            }
+            _ => 0,
+        };
+        self.priority + boost
+    }
+}
+
+/// A slot of an [`ExecutorPool`], handed to the next waiter when dropped
+#[derive(Debug)]
+pub struct ExecutorPermit {
+    slots: Option<Arc<Mutex<Slots>>>,
+}
+
+impl Drop for ExecutorPermit {
+    fn drop(&mut self) {
+        if let Some(slots) = self.slots.take() {
+            release(&slots);
+        }
+    }
+}
+
+fn release(slots: &Arc<Mutex<Slots>>) {
+    let mut state = slots.lock().unwrap();
+    let now = Instant::now();
+    while let Some(index) = state
+        .waiters
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| {
+            a.effective_priority(now)
+                .cmp(&b.effective_priority(now))
+                .then(b.ticket.cmp(&a.ticket))
+        })
+        .map(|(index, _)| index)
+    {
+        let waiter = state.waiters.swap_remove(index);
+        let permit = ExecutorPermit {
+            slots: Some(slots.clone()),
+        };
+        match waiter.sender.send(permit) {
+            Ok(()) => return,
+            // The waiter gave up; disarm the permit so it does not release again
+            Err(mut permit) => {
+                permit.slots = None;
+            }
+        }
+    }
+    state.available += 1;
+}
+
+impl ExecutorPool {
+    pub fn new(max_concurrent: usize) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        Self {
+            max_concurrent,
+            slots: Arc::new(Mutex::new(Slots {
+                available: max_concurrent,
+                waiters: Vec::new(),
+                next_ticket: 0,
+            })),
+            queue_timeout: DEFAULT_QUEUE_TIMEOUT,
+        }
+    }
+
+    /// Read the limit from `VIBE_MAX_CONCURRENT_EXECUTORS`, falling back to the default
+    /// when it is unset or not a positive number
+    pub fn max_concurrent_from_env() -> usize {
+        match std::env::var(MAX_CONCURRENT_ENV) {
+            Ok(value) => match value.trim().parse::<usize>() {
+                Ok(n) if n > 0 => n,
+                _ => {
+                    tracing::warn!(
+                        "Ignoring invalid {}={:?}, using {}",
+                        MAX_CONCURRENT_ENV,
+                        value,
+                        DEFAULT_MAX_CONCURRENT
+                    );
+                    DEFAULT_MAX_CONCURRENT
+                }
+            },
+            Err(_) => DEFAULT_MAX_CONCURRENT,
+        }
+    }
+
+    /// Wait for a free slot for at most the queue timeout. While waiting, callers with a
+    /// higher `priority` are served first; `boost_after` raises the priority by one for
+    /// every period spent waiting.
+    pub async fn acquire(
+        &self,
+        priority: i64,
+        boost_after: Option<Duration>,
+    ) -> Result<ExecutorPermit, ExecutorError> {
+        let (ticket, mut receiver) = {
+            let mut state = self.slots.lock().unwrap();
+            if state.available > 0 && state.waiters.is_empty() {
+                state.available -= 1;
+                return Ok(ExecutorPermit {
+                    slots: Some(self.slots.clone()),
+                });
+            }
+            let (sender, receiver) = oneshot::channel();
+            let ticket = state.next_ticket;
+            state.next_ticket += 1;
+            state.waiters.push(Waiter {
+                ticket,
+                priority,
+                boost_after,
+                enqueued_at: Instant::now(),
+                sender,
+            });
+            (ticket, receiver)
+        };
+
+        match tokio::time::timeout(self.queue_timeout, &mut receiver).await {
+            Ok(Ok(permit)) => Ok(permit),
+            // Senders are only dropped after sending, but treat it as exhausted rather than panic
+            Ok(Err(_)) | Err(_) => {
+                self.slots
+                    .lock()
+                    .unwrap()
+                    .waiters
+                    .retain(|waiter| waiter.ticket != ticket);
+                // A slot may have been handed over just as the timeout fired
+                receiver
+                    .try_recv()
+                    .map_err(|_| ExecutorError::PoolExhausted {
+                        max_concurrent: self.max_concurrent,
+                    })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::task::DEFAULT_TASK_PRIORITY;
+
+    #[tokio::test]
+    async fn test_extra_spawn_queues_until_a_permit_is_released() {
+        let pool = ExecutorPool::new(2);
+
+        let mut handles = Vec::new();
+        for _ in 0..3 {
+            let pool = pool.clone();
+            handles.push(tokio::spawn(async move {
+                pool.acquire(DEFAULT_TASK_PRIORITY, None).await
+            }));
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let finished: Vec<_> = handles.iter().map(|h| h.is_finished()).collect();
+        assert_eq!(finished.iter().filter(|done| **done).count(), 2);
+        assert_eq!(pool.slots.lock().unwrap().available, 0);
+
+        let mut permits = Vec::new();
+        let mut queued = None;
+        for (handle, done) in handles.into_iter().zip(finished) {
+            if done {
+                permits.push(handle.await.unwrap().unwrap());
+            } else {
+                queued = Some(handle);
+            }
+        }
+        let queued = queued.unwrap();
+
+        drop(permits.pop());
+        let permit = tokio::time::timeout(Duration::from_secs(1), queued)
+            .await
+            .expect("queued task should be unblocked")
+            .unwrap();
+        assert!(permit.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_saturated_pool_returns_pool_exhausted() {
+        let pool = ExecutorPool {
+            queue_timeout: Duration::from_millis(20),
+            ..ExecutorPool::new(1)
+        };
+        let _permit = pool.acquire(DEFAULT_TASK_PRIORITY, None).await.unwrap();
+
+        match pool.acquire(DEFAULT_TASK_PRIORITY, None).await {
+            Err(ExecutorError::PoolExhausted { max_concurrent }) => assert_eq!(max_concurrent, 1),
+            other => panic!("expected PoolExhausted, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    /// Queue callers one after another while a slot is taken, then free it and return the
+    /// names in the order they got the slot
+    async fn service_order(waiters: &[(&'static str, i64, Option<Duration>)]) -> Vec<&'static str> {
+        let pool = ExecutorPool::new(1);
+        let running = pool.acquire(DEFAULT_TASK_PRIORITY, None).await.unwrap();
+
+        let (order_tx, mut order_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut handles = Vec::new();
+        for &(name, priority, boost_after) in waiters {
+            let pool = pool.clone();
+            let order_tx = order_tx.clone();
+            handles.push(tokio::spawn(async move {
+                let permit = pool.acquire(priority, boost_after).await.unwrap();
+                order_tx.send(name).unwrap();
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                drop(permit);
+            }));
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        drop(running);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        drop(order_tx);
+        let mut order = Vec::new();
+        while let Some(name) = order_rx.recv().await {
+            order.push(name);
+        }
+        order
+    }
+
+    #[tokio::test]
+    async fn test_higher_priority_waiter_gets_the_next_slot() {
+        let order = service_order(&[("low", 10, None), ("high", 90, None)]).await;
+        assert_eq!(order, vec!["high", "low"]);
+    }
+
+    #[tokio::test]
+    async fn test_equal_priority_waiters_are_served_in_order() {
+        let order = service_order(&[("first", 50, None), ("second", 50, None)]).await;
+        assert_eq!(order, vec!["first", "second"]);
+    }
+
+    #[tokio::test]
+    async fn test_waiting_boosts_priority() {
+        // 50ms of waiting at one point per millisecond outweighs the difference
+        let order = service_order(&[
+            ("low", 10, Some(Duration::from_millis(1))),
+            ("high", 20, None),
+        ])
+        .await;
+        assert_eq!(order, vec!["low", "high"]);
+    }
+}
@@ -0,0 +1,400 @@
+//! Automatic restarts of crashed coding agent runs
+//!
+//! `claude-code` occasionally dies mid-task for reasons unrelated to the task, such as being
+//! killed for running out of memory. When a run exits with one of the configured
+//! restartable codes, [`ExecutorSupervisor`] resumes its Claude session under the same run
+//! instead of letting the attempt fail, up to [`RestartPolicy::max_restarts`] times.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    executor::ExecutorConfig,
+    models::{
+        execution_process::{ExecutionProcess, ExecutionProcessType},
+        executor_session::ExecutorSession,
+        task_attempt::{TaskAttempt, TaskAttemptError},
+    },
+    services::ProcessService,
+};
+
+/// Which crashed runs are restarted, and how often
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(default)]
+pub struct RestartPolicy {
+    /// Exit codes of transient crashes, by default 137 (SIGKILL), 139 (SIGSEGV) and 1
+    pub restartable_exit_codes: Vec<i64>,
+    /// How often one run is restarted before it is left failed
+    pub max_restarts: u32,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            restartable_exit_codes: vec![137, 139, 1],
+            max_restarts: 2,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SupervisorError {
+    Database(sqlx::Error),
+    Process(TaskAttemptError),
+}
+
+impl std::fmt::Display for SupervisorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SupervisorError::Database(e) => write!(f, "Database error: {}", e),
+            SupervisorError::Process(e) => write!(f, "Process error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SupervisorError {}
+
+impl From<sqlx::Error> for SupervisorError {
+    fn from(err: sqlx::Error) -> Self {
+        SupervisorError::Database(err)
+    }
+}
+
+impl From<TaskAttemptError> for SupervisorError {
+    fn from(err: TaskAttemptError) -> Self {
+        SupervisorError::Process(err)
+    }
+}
+
+/// Restarts crashed coding agent runs, consulted by the execution monitor whenever a
+/// run exits unsuccessfully
+#[derive(Clone)]
+pub struct ExecutorSupervisor {
+    policy: RestartPolicy,
+}
+
+impl ExecutorSupervisor {
+    pub fn new(policy: RestartPolicy) -> Self {
+        Self { policy }
+    }
+
+    pub fn is_restartable(&self, exit_code: Option<i64>) -> bool {
+        exit_code.is_some_and(|code| self.policy.restartable_exit_codes.contains(&code))
+    }
+
+    /// Restart a run recorded in `pool` that exited with `exit_code` by resuming its session
+    /// under the same run id, returning whether it was restarted. The restarted process is registered
+    /// for monitoring; runs that are not restarted are left for the caller to mark failed.
+    pub async fn restart_if_crashed(
+        &self,
+        pool: &sqlx::SqlitePool,
+        app_state: &AppState,
+        run_id: Uuid,
+        task_attempt_id: Uuid,
+        exit_code: Option<i64>,
+    ) -> bool {
+        if !self.is_restartable(exit_code) {
+            return false;
+        }
+
+        match self.restart(pool, app_state, run_id, task_attempt_id).await {
+            Ok(restarted) => restarted,
+            Err(e) => {
+                tracing::error!("Failed to restart crashed run {}: {}", run_id, e);
+                false
+            }
+        }
+    }
+
+    async fn restart(
+        &self,
+        pool: &sqlx::SqlitePool,
+        app_state: &AppState,
+        run_id: Uuid,
+        task_attempt_id: Uuid,
+    ) -> Result<bool, SupervisorError> {
+        let Some(run) = ExecutionProcess::find_by_id(pool, run_id).await? else {
+            return Ok(false);
+        };
+        // Only Claude sessions can be resumed by the follow-up executor
+        if !matches!(run.process_type, ExecutionProcessType::CodingAgent) {
+            return Ok(false);
+        }
+        let config = match run.executor_type.as_deref().map(str::parse) {
+            Some(Ok(config @ (ExecutorConfig::Claude | ExecutorConfig::ClaudePlan))) => config,
+            _ => return Ok(false),
+        };
+
+        let restarts = ExecutionProcess::restart_count(pool, run_id)
+            .await?
+            .unwrap_or(0);
+        if restarts >= i64::from(self.policy.max_restarts) {
+            tracing::warn!(
+                "Run {} crashed again after {} restarts, giving up",
+                run_id,
+                restarts
+            );
+            return Ok(false);
+        }
+
+        let session_id = ExecutorSession::find_by_execution_process_id(pool, run_id)
+            .await?
+            .and_then(|session| session.session_id);
+        let Some(session_id) = session_id else {
+            tracing::warn!("Run {} crashed before its session id was captured", run_id);
+            return Ok(false);
+        };
+        let Some(attempt) = TaskAttempt::find_by_id(pool, task_attempt_id).await? else {
+            return Ok(false);
+        };
+
+        // Waits for a pool slot like any other run; while the pool is full this holds up the
+        // monitor for at most the pool's queue timeout, after which the run is left failed
+        let (child, permit) = ProcessService::spawn_crash_resume(
+            pool,
+            app_state,
+            run_id,
+            &attempt,
+            &config,
+            &session_id,
+        )
+        .await?;
+        let restart_count = ExecutionProcess::record_restart(pool, run_id).await?;
+        tracing::info!(
+            "Restarted crashed run {} (restart {}/{})",
+            run_id,
+            restart_count,
+            self.policy.max_restarts
+        );
+
+        ProcessService::register_for_monitoring(
+            pool,
+            app_state,
+            run_id,
+            task_attempt_id,
+            &ExecutionProcessType::CodingAgent,
+            child,
+            Some(permit),
+        )
+        .await;
+        Ok(true)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{process::Stdio, sync::Arc};
+
+    use command_group::AsyncCommandGroup;
+
+    use super::*;
+    use crate::{
+        models::{
+            config::Config,
+            execution_process::ExecutionProcessStatus,
+            project_executor_config::{ProjectExecutorConfig, UpdateProjectExecutorConfig},
+            task::DEFAULT_TASK_PRIORITY,
+        },
+        test_fixtures::{create_attempt, create_project, create_task, memory_pool},
+    };
+
+    /// A project whose Claude binary is a script exiting with 1 while `failures` is
+    /// positive and successfully afterwards, with one running run of it; returns the state,
+    /// the attempt and run IDs and the script
+    async fn setup(failures: usize) -> (AppState, Uuid, Uuid, tempfile::TempDir) {
+        let pool = memory_pool().await;
+
+        let script_dir = tempfile::TempDir::new().unwrap();
+        let counter = script_dir.path().join("failures");
+        let script = script_dir.path().join("claude.sh");
+        std::fs::write(&counter, failures.to_string()).unwrap();
+        std::fs::write(
+            &script,
+            format!(
+                "cat > /dev/null\n\
+                 n=$(cat '{counter}')\n\
+                 if [ \"$n\" -gt 0 ]; then echo $((n - 1)) > '{counter}'; exit 1; fi\n",
+                counter = counter.display()
+            ),
+        )
+        .unwrap();
+
+        let worktree = std::env::temp_dir().to_string_lossy().to_string();
+        let project_id = create_project(&pool).await;
+        let task_id = create_task(&pool, project_id, "t").await;
+        let attempt_id = create_attempt(&pool, task_id, &worktree).await;
+        let run_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO execution_processes (id, task_attempt_id, process_type, executor_type, status, command, working_directory)
+             VALUES ($1, $2, 'codingagent', 'claude', 'running', 'claude', $3)",
+        )
+        .bind(run_id)
+        .bind(attempt_id)
+        .bind(&worktree)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO executor_sessions (id, task_attempt_id, execution_process_id, session_id)
+             VALUES ($1, $2, $3, 'session-1')",
+        )
+        .bind(Uuid::new_v4())
+        .bind(attempt_id)
+        .bind(run_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        ProjectExecutorConfig::upsert(
+            &pool,
+            project_id,
+            &UpdateProjectExecutorConfig {
+                executor_type: ExecutorConfig::Claude.to_string(),
+                binary_path: format!("sh {}", script.display()),
+                model: None,
+                extra_flags: None,
+                use_plan_mode: false,
+                web_allowlist: None,
+                web_denylist: None,
+                stdin_format: None,
+                auto_resume_on_timeout: false,
+            },
+        )
+        .await
+        .unwrap();
+
+        let app_state =
+            AppState::new(pool, Arc::new(tokio::sync::RwLock::new(Config::default()))).await;
+        (app_state, attempt_id, run_id, script_dir)
+    }
+
+    /// Start the run with `failures` crashes ahead and handle its exits the way the
+    /// execution monitor does, returning the final status
+    async fn supervise(failures: usize) -> (AppState, Uuid, ExecutionProcessStatus) {
+        let (app_state, attempt_id, run_id, script_dir) = setup(failures).await;
+        let supervisor = ExecutorSupervisor::new(RestartPolicy::default());
+
+        let child = tokio::process::Command::new("sh")
+            .arg(script_dir.path().join("claude.sh"))
+            .stdin(Stdio::null())
+            .group_spawn()
+            .unwrap();
+        ProcessService::register_for_monitoring(
+            &app_state.db_pool,
+            &app_state,
+            run_id,
+            attempt_id,
+            &ExecutionProcessType::CodingAgent,
+            child,
+            None,
+        )
+        .await;
+
+        loop {
+            for (run_id, attempt_id, pool, success, exit_code) in
+                app_state.get_running_executions_for_monitor().await
+            {
+                if !success
+                    && supervisor
+                        .restart_if_crashed(&pool, &app_state, run_id, attempt_id, exit_code)
+                        .await
+                {
+                    continue;
+                }
+                let status = if success {
+                    ExecutionProcessStatus::Completed
+                } else {
+                    ExecutionProcessStatus::Failed
+                };
+                ExecutionProcess::update_completion(
+                    &app_state.db_pool,
+                    run_id,
+                    status.clone(),
+                    exit_code,
+                )
+                .await
+                .unwrap();
+                return (app_state, run_id, status);
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_crashed_run_is_restarted_until_it_succeeds() {
+        let (app_state, run_id, status) = supervise(2).await;
+
+        assert_eq!(status, ExecutionProcessStatus::Completed);
+        assert_eq!(
+            ExecutionProcess::restart_count(&app_state.db_pool, run_id)
+                .await
+                .unwrap(),
+            Some(2)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_fails_once_restarts_are_exhausted() {
+        let (app_state, run_id, status) = supervise(3).await;
+
+        assert_eq!(status, ExecutionProcessStatus::Failed);
+        let run = ExecutionProcess::find_by_id(&app_state.db_pool, run_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(run.status, ExecutionProcessStatus::Failed);
+        assert_eq!(run.exit_code, Some(1));
+        assert_eq!(
+            ExecutionProcess::restart_count(&app_state.db_pool, run_id)
+                .await
+                .unwrap(),
+            Some(2)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restarts_wait_for_a_slot_in_the_executor_pool() {
+        let (app_state, attempt_id, run_id, _script_dir) = setup(0).await;
+        let project_id: Uuid = sqlx::query_scalar("SELECT id FROM projects")
+            .fetch_one(&app_state.db_pool)
+            .await
+            .unwrap();
+        let executor_pool = app_state.executor_pool(project_id);
+        let mut permits = Vec::new();
+        while let Ok(Ok(permit)) = tokio::time::timeout(
+            std::time::Duration::from_millis(50),
+            executor_pool.acquire(DEFAULT_TASK_PRIORITY, None),
+        )
+        .await
+        {
+            permits.push(permit);
+        }
+
+        let supervisor = ExecutorSupervisor::new(RestartPolicy::default());
+        let restart = supervisor.restart_if_crashed(
+            &app_state.db_pool,
+            &app_state,
+            run_id,
+            attempt_id,
+            Some(1),
+        );
+        tokio::pin!(restart);
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(200), &mut restart)
+                .await
+                .is_err(),
+            "the restart did not wait for a free slot"
+        );
+
+        permits.pop();
+        assert!(restart.await);
+        assert_eq!(
+            ExecutionProcess::restart_count(&app_state.db_pool, run_id)
+                .await
+                .unwrap(),
+            Some(1)
+        );
+    }
+}
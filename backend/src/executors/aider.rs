@@ -0,0 +1,505 @@
+use std::{path::Path, process::Stdio};
+
+use async_trait::async_trait;
+use command_group::{AsyncCommandGroup, AsyncGroupChild};
+use tokio::{io::AsyncWriteExt, process::Command};
+use uuid::Uuid;
+
+use crate::{
+    api_key_store::{apply_api_key_env, ProjectApiKeyStore},
+    executor::{
+        stream_output_to_db, ActionType, Executor, ExecutorError, NormalizedConversation,
+        NormalizedEntry, NormalizedEntryType, RunOutputChannels, SpawnContext,
+    },
+    models::{executor_session::ExecutorSession, task::Task},
+    utils::shell::get_shell_command,
+};
+
+/// Flags for a non-interactive run that reads its message from stdin
+const AIDER_ARGS: &str = "--no-pretty --yes-always --message-file /dev/stdin";
+
+/// Marker aider prints on its own line before each proposed edit or shell command
+const EDIT_BLOCK_MARKER: &str = "EDIT_BLOCK";
+
+/// Edit block targets that hold shell commands rather than a file path
+const SHELL_BLOCK_TARGETS: &[&str] = &["bash", "sh", "shell", "zsh"];
+
+/// An executor that uses aider to process tasks
+pub struct AiderExecutor;
+
+/// An executor that continues an aider chat from the history aider keeps in the worktree
+pub struct AiderFollowupExecutor {
+    pub prompt: String,
+}
+
+/// Detect if aider is installed locally
+async fn detect_local_aider() -> Option<String> {
+    let (shell_cmd, shell_arg) = get_shell_command();
+
+    let output = Command::new(shell_cmd)
+        .arg(shell_arg)
+        .arg("which aider 2>/dev/null || where aider 2>NUL || echo")
+        .output()
+        .await
+        .ok()?;
+
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success()
+        && !path.is_empty()
+        && !path.contains("not found")
+        && !path.contains("Could not find")
+    {
+        tracing::info!("Detected local aider at: {}", path);
+        return Some(path);
+    }
+
+    None
+}
+
+/// Get the aider command, preferring a local installation over running it through uvx
+async fn get_aider_command(restore_chat_history: bool) -> String {
+    let base_command = match detect_local_aider().await {
+        Some(local_path) => local_path,
+        None => {
+            tracing::info!("Falling back to uvx aider");
+            "uvx --from aider-chat aider".to_string()
+        }
+    };
+    build_aider_command(&base_command, restore_chat_history)
+}
+
+/// Build the complete aider command with appropriate flags
+fn build_aider_command(base_command: &str, restore_chat_history: bool) -> String {
+    if restore_chat_history {
+        format!("{} {} --restore-chat-history", base_command, AIDER_ARGS)
+    } else {
+        format!("{} {}", base_command, AIDER_ARGS)
+    }
+}
+
+impl AiderExecutor {
+    /// Spawn aider in the worktree and write `prompt` to its stdin, given the project's API
+    /// key when it has one
+    async fn spawn_with_prompt(
+        worktree_path: &str,
+        prompt: &str,
+        restore_chat_history: bool,
+        api_key: Option<&str>,
+        context: String,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
+        let (shell_cmd, shell_arg) = get_shell_command();
+        let aider_command = get_aider_command(restore_chat_history).await;
+
+        let mut command = Command::new(shell_cmd);
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .current_dir(worktree_path)
+            .arg(shell_arg)
+            .arg(&aider_command);
+        apply_api_key_env(&mut command, "aider", api_key);
+
+        let mut child = command
+            .group_spawn() // Create new process group so we can kill entire tree
+            .map_err(|e| {
+                SpawnContext::from_command(&command, "Aider")
+                    .with_context(context.clone())
+                    .spawn_error(e)
+            })?;
+
+        // Feed the message in, then close the pipe so aider sees EOF
+        if let Some(mut stdin) = child.inner().stdin.take() {
+            stdin.write_all(prompt.as_bytes()).await.map_err(|e| {
+                let context = SpawnContext::from_command(&command, "Aider").with_context(format!(
+                    "Failed to write prompt to aider stdin ({})",
+                    context
+                ));
+                ExecutorError::spawn_failed(e, context)
+            })?;
+            stdin.shutdown().await.map_err(|e| {
+                let context = SpawnContext::from_command(&command, "Aider")
+                    .with_context(format!("Failed to close aider stdin ({})", context));
+                ExecutorError::spawn_failed(e, context)
+            })?;
+        }
+
+        Ok(child)
+    }
+
+    /// Record the attempt as the session, since aider resumes from its chat history file
+    /// rather than a session ID, and stream both pipes to the database
+    async fn stream_to_db(
+        pool: &sqlx::SqlitePool,
+        output_channels: &RunOutputChannels,
+        mut child: AsyncGroupChild,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+    ) -> AsyncGroupChild {
+        if let Err(e) =
+            ExecutorSession::update_session_id(pool, execution_process_id, &attempt_id.to_string())
+                .await
+        {
+            tracing::error!(
+                "Failed to update session ID for aider execution process {}: {}",
+                execution_process_id,
+                e
+            );
+        }
+
+        let stdout = child
+            .inner()
+            .stdout
+            .take()
+            .expect("Failed to take stdout from child process");
+        let stderr = child
+            .inner()
+            .stderr
+            .take()
+            .expect("Failed to take stderr from child process");
+
+        tokio::spawn(stream_output_to_db(
+            stdout,
+            pool.clone(),
+            output_channels.clone(),
+            attempt_id,
+            execution_process_id,
+            true,
+        ));
+        tokio::spawn(stream_output_to_db(
+            stderr,
+            pool.clone(),
+            output_channels.clone(),
+            attempt_id,
+            execution_process_id,
+            false,
+        ));
+
+        child
+    }
+}
+
+#[async_trait]
+impl Executor for AiderExecutor {
+    async fn spawn(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
+        let task = Task::find_by_id(pool, task_id)
+            .await?
+            .ok_or(ExecutorError::TaskNotFound)?;
+
+        let prompt = match &task.description {
+            Some(description) => format!("{}\n\n{}", task.title, description),
+            None => task.title.clone(),
+        };
+
+        let api_key = ProjectApiKeyStore::get_for_task(pool, task_id, "aider").await;
+        Self::spawn_with_prompt(
+            worktree_path,
+            &prompt,
+            false,
+            api_key.as_deref(),
+            format!("aider execution for task {}", task_id),
+        )
+        .await
+    }
+
+    async fn execute_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        worktree_path: &str,
+        output_channels: &RunOutputChannels,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
+        let child = self.spawn(pool, task_id, worktree_path).await?;
+        Ok(Self::stream_to_db(
+            pool,
+            output_channels,
+            child,
+            attempt_id,
+            execution_process_id,
+        )
+        .await)
+    }
+
+    fn normalize_logs(
+        &self,
+        logs: &str,
+        worktree_path: &str,
+    ) -> Result<NormalizedConversation, String> {
+        Ok(NormalizedConversation {
+            entries: normalize_aider_output(logs, worktree_path),
+            session_id: None,
+            executor_type: "aider".to_string(),
+            prompt: None,
+            summary: None,
+            stats: None,
+            metadata: Default::default(),
+        })
+    }
+}
+
+#[async_trait]
+impl Executor for AiderFollowupExecutor {
+    async fn spawn(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
+        let api_key = ProjectApiKeyStore::get_for_task(pool, task_id, "aider").await;
+        AiderExecutor::spawn_with_prompt(
+            worktree_path,
+            &self.prompt,
+            true,
+            api_key.as_deref(),
+            format!("aider followup execution for task {}", task_id),
+        )
+        .await
+    }
+
+    async fn execute_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        worktree_path: &str,
+        output_channels: &RunOutputChannels,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
+        let child = self.spawn(pool, task_id, worktree_path).await?;
+        Ok(AiderExecutor::stream_to_db(
+            pool,
+            output_channels,
+            child,
+            attempt_id,
+            execution_process_id,
+        )
+        .await)
+    }
+
+    fn normalize_logs(
+        &self,
+        logs: &str,
+        worktree_path: &str,
+    ) -> Result<NormalizedConversation, String> {
+        // Reuse the same logic as the main AiderExecutor
+        AiderExecutor.normalize_logs(logs, worktree_path)
+    }
+}
+
+/// Whose turn a run of plain output lines belongs to
+#[derive(Clone, Copy, PartialEq)]
+enum Turn {
+    User,
+    Assistant,
+}
+
+/// Turn aider's chat-style output into normalized entries: `>` lines are the user,
+/// `EDIT_BLOCK` starts a file edit or shell command, and everything else is the assistant
+fn normalize_aider_output(logs: &str, worktree_path: &str) -> Vec<NormalizedEntry> {
+    let mut entries = Vec::new();
+    let mut turn: Option<(Turn, Vec<&str>)> = None;
+    let mut lines = logs.lines().map(str::trim_end).peekable();
+
+    while let Some(line) = lines.next() {
+        if let Some(target) = edit_block_target(line) {
+            flush_turn(&mut entries, turn.take());
+
+            // The block's content is the fenced code that follows the marker
+            let mut body = Vec::new();
+            if lines.peek().is_some_and(|next| next.starts_with("```")) {
+                lines.next();
+                for line in lines.by_ref() {
+                    if line.trim() == "```" {
+                        break;
+                    }
+                    body.push(line);
+                }
+            }
+            entries.extend(edit_block_entries(target, &body, worktree_path));
+            continue;
+        }
+
+        let (line_turn, text) = match line.strip_prefix('>') {
+            Some(text) => (Turn::User, text.strip_prefix(' ').unwrap_or(text)),
+            None => (Turn::Assistant, line),
+        };
+        match &mut turn {
+            Some((current, lines)) if *current == line_turn => lines.push(text),
+            _ => {
+                flush_turn(&mut entries, turn.take());
+                turn = Some((line_turn, vec![text]));
+            }
+        }
+    }
+    flush_turn(&mut entries, turn);
+
+    entries
+}
+
+/// The target of an `EDIT_BLOCK <target>` line
+fn edit_block_target(line: &str) -> Option<&str> {
+    let (marker, target) = line.trim().split_once(char::is_whitespace)?;
+    (marker == EDIT_BLOCK_MARKER && !target.trim().is_empty()).then(|| target.trim())
+}
+
+fn flush_turn(entries: &mut Vec<NormalizedEntry>, turn: Option<(Turn, Vec<&str>)>) {
+    let Some((turn, lines)) = turn else {
+        return;
+    };
+    let content = lines.join("\n").trim().to_string();
+    if content.is_empty() {
+        return;
+    }
+    entries.push(NormalizedEntry {
+        timestamp: None,
+        entry_type: match turn {
+            Turn::User => NormalizedEntryType::UserMessage,
+            Turn::Assistant => NormalizedEntryType::AssistantMessage,
+        },
+        content,
+        metadata: None,
+        repeat_count: 1,
+    });
+}
+
+/// One tool use per shell command, or a single file write for an edit
+fn edit_block_entries(target: &str, body: &[&str], worktree_path: &str) -> Vec<NormalizedEntry> {
+    if SHELL_BLOCK_TARGETS.contains(&target) {
+        return body
+            .iter()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|command| NormalizedEntry {
+                timestamp: None,
+                entry_type: NormalizedEntryType::ToolUse {
+                    tool_name: "bash".to_string(),
+                    action_type: ActionType::CommandRun {
+                        command: command.to_string(),
+                    },
+                },
+                content: format!("`{}`", command),
+                metadata: None,
+                repeat_count: 1,
+            })
+            .collect();
+    }
+
+    let path = Path::new(target)
+        .strip_prefix(worktree_path)
+        .map(|relative| relative.to_string_lossy().to_string())
+        .unwrap_or_else(|_| target.to_string());
+    vec![NormalizedEntry {
+        timestamp: None,
+        entry_type: NormalizedEntryType::ToolUse {
+            tool_name: "edit".to_string(),
+            action_type: ActionType::FileWrite { path: path.clone() },
+        },
+        content: format!("`{}`", path),
+        metadata: Some(serde_json::json!({ "edit": body.join("\n") })),
+        repeat_count: 1,
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_aider_command() {
+        assert_eq!(
+            build_aider_command("aider", false),
+            "aider --no-pretty --yes-always --message-file /dev/stdin"
+        );
+        assert!(build_aider_command("aider", true).ends_with(" --restore-chat-history"));
+    }
+
+    #[test]
+    fn test_normalize_logs_file_write_block() {
+        let logs = r#"> Rename the greeting
+> to say hello
+I'll update the greeting in main.rs.
+
+EDIT_BLOCK /tmp/worktree/src/main.rs
+```rust
+<<<<<<< SEARCH
+    println!("hi");
+=======
+    println!("hello");
+>>>>>>> REPLACE
+```
+Applied edit to src/main.rs"#;
+
+        let result = AiderExecutor.normalize_logs(logs, "/tmp/worktree").unwrap();
+        assert_eq!(result.entries.len(), 4);
+
+        assert!(matches!(
+            result.entries[0].entry_type,
+            NormalizedEntryType::UserMessage
+        ));
+        assert_eq!(
+            result.entries[0].content,
+            "Rename the greeting\nto say hello"
+        );
+        assert!(matches!(
+            result.entries[1].entry_type,
+            NormalizedEntryType::AssistantMessage
+        ));
+        assert_eq!(
+            result.entries[1].content,
+            "I'll update the greeting in main.rs."
+        );
+
+        match &result.entries[2].entry_type {
+            NormalizedEntryType::ToolUse {
+                tool_name,
+                action_type: ActionType::FileWrite { path },
+            } => {
+                assert_eq!(tool_name, "edit");
+                assert_eq!(path, "src/main.rs");
+            }
+            other => panic!("expected a file write, got {:?}", other),
+        }
+        assert!(result.entries[2].metadata.as_ref().unwrap()["edit"]
+            .as_str()
+            .unwrap()
+            .contains(">>>>>>> REPLACE"));
+
+        assert_eq!(result.entries[3].content, "Applied edit to src/main.rs");
+    }
+
+    #[test]
+    fn test_normalize_logs_bash_command_block() {
+        let logs = r#"Run the tests to check the change:
+EDIT_BLOCK bash
+```bash
+# run the whole suite
+cargo test
+
+cargo clippy
+```"#;
+
+        let result = AiderExecutor.normalize_logs(logs, "/tmp/worktree").unwrap();
+        let commands: Vec<&str> = result
+            .entries
+            .iter()
+            .filter_map(|entry| match &entry.entry_type {
+                NormalizedEntryType::ToolUse {
+                    action_type: ActionType::CommandRun { command },
+                    ..
+                } => Some(command.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(commands, vec!["cargo test", "cargo clippy"]);
+        assert_eq!(result.entries.len(), 3);
+        assert_eq!(result.entries[1].content, "`cargo test`");
+    }
+}
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
+    api_key_store::{apply_api_key_env, ProjectApiKeyStore},
     executor::{
         ActionType, Executor, ExecutorError, NormalizedConversation, NormalizedEntry,
         NormalizedEntryType,
@@ -164,6 +165,7 @@ impl AmpContentItem {
                     entry_type,
                     content: text.clone(),
                     metadata: Some(serde_json::to_value(self).unwrap_or(Value::Null)),
+                    repeat_count: 1,
                 })
             }
             AmpContentItem::Thinking { thinking } => Some(NormalizedEntry {
@@ -171,6 +173,7 @@ impl AmpContentItem {
                 entry_type: NormalizedEntryType::Thinking,
                 content: thinking.clone(),
                 metadata: Some(serde_json::to_value(self).unwrap_or(Value::Null)),
+                repeat_count: 1,
             }),
             AmpContentItem::ToolUse { name, input, .. } => {
                 let action_type = executor.extract_action_type(name, input, worktree_path);
@@ -185,6 +188,7 @@ impl AmpContentItem {
                     },
                     content,
                     metadata: Some(serde_json::to_value(self).unwrap_or(Value::Null)),
+                    repeat_count: 1,
                 })
             }
             AmpContentItem::ToolResult { .. } => None,
@@ -240,6 +244,8 @@ Task title: {}"#,
             .current_dir(worktree_path)
             .arg(shell_arg)
             .arg(amp_command);
+        let api_key = ProjectApiKeyStore::get_for_task(pool, task_id, "amp").await;
+        apply_api_key_env(&mut command, "amp", api_key.as_deref());
 
         let mut child = command
             .group_spawn() // Create new process group so we can kill entire tree
@@ -283,6 +289,7 @@ Task title: {}"#,
                         entry_type: NormalizedEntryType::SystemMessage,
                         content: format!("Raw output: {}", trimmed),
                         metadata: None,
+                        repeat_count: 1,
                     });
                     continue;
                 }
@@ -308,6 +315,8 @@ Task title: {}"#,
             executor_type: "amp".to_string(),
             prompt: None,
             summary: None,
+            stats: None,
+            metadata: Default::default(),
         })
     }
 }
@@ -342,11 +351,14 @@ impl AmpExecutor {
         match action_type {
             ActionType::FileRead { path } => format!("`{}`", path),
             ActionType::FileWrite { path } => format!("`{}`", path),
+            ActionType::FileDelete { path } => format!("`{}`", path),
+            ActionType::FileMove { from, to } => format!("`{}` → `{}`", from, to),
             ActionType::CommandRun { command } => format!("`{}`", command),
             ActionType::Search { query } => format!("`{}`", query),
             ActionType::WebFetch { url } => format!("`{}`", url),
             ActionType::PlanPresentation { plan } => format!("Plan Presentation: `{}`", plan),
             ActionType::TaskCreate { description } => description.clone(),
+            ActionType::ComputerUse { action, .. } => action.clone(),
             ActionType::Other { description: _ } => {
                 // For other tools, try to extract key information or fall back to tool name
                 match tool_name.to_lowercase().as_str() {
@@ -594,8 +606,8 @@ impl AmpExecutor {
 impl Executor for AmpFollowupExecutor {
     async fn spawn(
         &self,
-        _pool: &sqlx::SqlitePool,
-        _task_id: Uuid,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
         worktree_path: &str,
     ) -> Result<AsyncGroupChild, ExecutorError> {
         use std::process::Stdio;
@@ -618,6 +630,8 @@ impl Executor for AmpFollowupExecutor {
             .current_dir(worktree_path)
             .arg(shell_arg)
             .arg(&amp_command);
+        let api_key = ProjectApiKeyStore::get_for_task(pool, task_id, "amp").await;
+        apply_api_key_env(&mut command, "amp", api_key.as_deref());
 
         let mut child = command
             .group_spawn() // Create new process group so we can kill entire tree
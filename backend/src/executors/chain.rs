@@ -0,0 +1,536 @@
+use std::{process::Stdio, sync::Arc};
+
+use async_trait::async_trait;
+use command_group::{AsyncCommandGroup, AsyncGroupChild};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    process::{ChildStderr, ChildStdin, ChildStdout, Command},
+};
+use uuid::Uuid;
+
+use crate::{
+    executor::{
+        ActionType, Executor, ExecutorError, NormalizedConversation, NormalizedEntry,
+        NormalizedEntryType, SpawnContext,
+    },
+    utils::shell::get_shell_command,
+};
+
+/// Line written between the plan run's output and the execute run's output
+pub const CHAIN_SEPARATOR: &str = "--- vibe-kanban: plan approved, starting execution ---";
+
+/// Run by the process standing in for the chain: waits for the chain's exit code on stdin
+/// and exits with it. It never writes anything, so its stdout closes only when it exits.
+#[cfg(not(windows))]
+const EXIT_CODE_SCRIPT: &str = r#"read code; exit "${code:-1}""#;
+#[cfg(windows)]
+const EXIT_CODE_SCRIPT: &str = "set /p code= & exit !code!";
+
+#[cfg(unix)]
+type OwnedPipe = std::os::fd::OwnedFd;
+#[cfg(windows)]
+type OwnedPipe = std::os::windows::io::OwnedHandle;
+
+/// An executor that runs a plan-mode executor and then, if approved, an execute-mode
+/// executor primed with the resulting plan. Both runs are streamed through in-process pipes
+/// handed out as the output of a single stand-in process, so the chain is started, followed
+/// and cancelled like any other run.
+pub struct ExecutorChain {
+    plan_executor: Arc<dyn Executor>,
+    execute_executor: Arc<dyn Executor>,
+    auto_approve: bool,
+}
+
+impl ExecutorChain {
+    /// Chain `plan_executor` and `execute_executor`. Without `auto_approve` the chain
+    /// stops after the plan run, leaving the plan for manual approval.
+    pub fn plan_then_execute(
+        plan_executor: Box<dyn Executor>,
+        execute_executor: Box<dyn Executor>,
+        auto_approve: bool,
+    ) -> Self {
+        Self {
+            plan_executor: plan_executor.into(),
+            execute_executor: execute_executor.into(),
+            auto_approve,
+        }
+    }
+}
+
+/// Text of the last plan presented in `conversation`
+pub fn extract_plan(conversation: &NormalizedConversation) -> Option<String> {
+    conversation
+        .entries
+        .iter()
+        .rev()
+        .find_map(|entry| match &entry.entry_type {
+            NormalizedEntryType::ToolUse {
+                action_type: ActionType::PlanPresentation { plan },
+                ..
+            } => Some(plan.clone()),
+            _ => None,
+        })
+}
+
+/// Split chained logs into the plan run's output and the execute run's output, if any
+fn split_logs(logs: &str) -> (&str, Option<&str>) {
+    match logs.find(CHAIN_SEPARATOR) {
+        Some(index) => {
+            let execute_logs = &logs[index + CHAIN_SEPARATOR.len()..];
+            (
+                &logs[..index],
+                Some(execute_logs.strip_prefix('\n').unwrap_or(execute_logs)),
+            )
+        }
+        None => (logs, None),
+    }
+}
+
+/// A pipe whose read end is handed out as a child's output and whose write end the chain
+/// writes into
+fn output_pipe() -> std::io::Result<(OwnedPipe, ChildStdin)> {
+    let (reader, writer) = std::io::pipe()?;
+    let writer = ChildStdin::from_std(std::process::ChildStdin::from(OwnedPipe::from(writer)))?;
+    Ok((reader.into(), writer))
+}
+
+/// Copy `from` into `to` as it is written, returning everything copied. Keeps draining
+/// `from` if nobody reads `to` anymore, so the run is never blocked on a closed pipe.
+async fn copy_output(
+    from: Option<impl AsyncRead + Unpin>,
+    to: &mut (impl AsyncWrite + Unpin),
+) -> Vec<u8> {
+    let mut output = Vec::new();
+    let Some(mut from) = from else {
+        return output;
+    };
+    let mut buffer = vec![0; 8192];
+    let mut forwarding = true;
+    loop {
+        match from.read(&mut buffer).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                output.extend_from_slice(&buffer[..n]);
+                forwarding = forwarding && to.write_all(&buffer[..n]).await.is_ok();
+            }
+        }
+    }
+    output
+}
+
+/// Output of one run of a chain
+struct RelayedRun {
+    output: String,
+    exit_code: i32,
+}
+
+/// Copy the run's stdout and stderr into the chain's pipes until it exits, returning its
+/// stdout and exit code. Returns `None`, after killing the run, once `stopped` closes,
+/// which happens when the chain's stand-in process was killed.
+async fn relay_run(
+    mut child: AsyncGroupChild,
+    stdout: &mut ChildStdin,
+    stderr: &mut ChildStdin,
+    stopped: &mut ChildStdout,
+    task_id: Uuid,
+) -> Option<RelayedRun> {
+    let run_stdout = child.inner().stdout.take();
+    let run_stderr = child.inner().stderr.take();
+    let mut closed = [0; 1];
+    let (output, _) = tokio::select! {
+        copied = async {
+            tokio::join!(copy_output(run_stdout, stdout), copy_output(run_stderr, stderr))
+        } => copied,
+        _ = stopped.read(&mut closed) => {
+            tracing::info!("Chain of task {} was stopped, killing its run", task_id);
+            child.kill().await.ok();
+            return None;
+        }
+    };
+
+    let exit_code = match child.wait().await {
+        Ok(status) => status.code().unwrap_or(1),
+        Err(e) => {
+            tracing::error!("Failed to wait for chained run of task {}: {}", task_id, e);
+            1
+        }
+    };
+    Some(RelayedRun {
+        output: String::from_utf8_lossy(&output).into_owned(),
+        exit_code,
+    })
+}
+
+#[async_trait]
+impl Executor for ExecutorChain {
+    async fn spawn(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
+        let plan_child = self
+            .plan_executor
+            .spawn(pool, task_id, worktree_path)
+            .await?;
+
+        let (shell_cmd, shell_arg) = get_shell_command();
+        let mut command = Command::new(shell_cmd);
+        if cfg!(windows) {
+            // Expand !code! after set /p has read it
+            command.arg("/V:ON");
+        }
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .current_dir(worktree_path)
+            .arg(shell_arg)
+            .arg(EXIT_CODE_SCRIPT);
+        let context = |command: &Command| {
+            SpawnContext::from_command(command, "ExecutorChain")
+                .with_task(task_id, None)
+                .with_context("Stand-in process for the chained plan and execute runs")
+        };
+        let (stdout_reader, mut stdout) =
+            output_pipe().map_err(|e| context(&command).spawn_error(e))?;
+        let (stderr_reader, mut stderr) =
+            output_pipe().map_err(|e| context(&command).spawn_error(e))?;
+        let mut chain = command
+            .group_spawn()
+            .map_err(|e| context(&command).spawn_error(e))?;
+        let exit_code_input = chain.inner().stdin.take();
+        let stopped = chain.inner().stdout.take();
+        chain.inner().stdout = Some(
+            ChildStdout::from_std(std::process::ChildStdout::from(stdout_reader))
+                .map_err(|e| context(&command).spawn_error(e))?,
+        );
+        chain.inner().stderr = Some(
+            ChildStderr::from_std(std::process::ChildStderr::from(stderr_reader))
+                .map_err(|e| context(&command).spawn_error(e))?,
+        );
+
+        let plan_executor = self.plan_executor.clone();
+        let execute_executor = self.execute_executor.clone();
+        let auto_approve = self.auto_approve;
+        let pool = pool.clone();
+        let worktree_path = worktree_path.to_string();
+        tokio::spawn(async move {
+            let (Some(mut exit_code_input), Some(mut stopped)) = (exit_code_input, stopped) else {
+                return;
+            };
+            let Some(plan_run) =
+                relay_run(plan_child, &mut stdout, &mut stderr, &mut stopped, task_id).await
+            else {
+                return;
+            };
+            let plan_logs = plan_run.output;
+            let mut exit_code = plan_run.exit_code;
+
+            let plan = plan_executor
+                .normalize_logs(&plan_logs, &worktree_path)
+                .ok()
+                .and_then(|conversation| extract_plan(&conversation));
+            match plan {
+                Some(plan) if auto_approve && exit_code == 0 => {
+                    let prefix = format!("Implement the following approved plan:\n\n{}\n\n", plan);
+                    let executor = execute_executor.with_prompt_prefix(&prefix);
+                    if executor.is_none() {
+                        tracing::warn!(
+                            "Execute executor for task {} does not accept a prompt prefix; running it without the plan",
+                            task_id
+                        );
+                    }
+                    let executor = executor.as_deref().unwrap_or(execute_executor.as_ref());
+
+                    let separator = if plan_logs.is_empty() || plan_logs.ends_with('\n') {
+                        format!("{}\n", CHAIN_SEPARATOR)
+                    } else {
+                        format!("\n{}\n", CHAIN_SEPARATOR)
+                    };
+                    stdout.write_all(separator.as_bytes()).await.ok();
+                    exit_code = match executor.spawn(&pool, task_id, &worktree_path).await {
+                        Ok(child) => {
+                            match relay_run(child, &mut stdout, &mut stderr, &mut stopped, task_id)
+                                .await
+                            {
+                                Some(execute_run) => execute_run.exit_code,
+                                None => return,
+                            }
+                        }
+                        Err(e) => {
+                            tracing::error!(
+                                "Failed to start execution for task {}: {}",
+                                task_id,
+                                e
+                            );
+                            1
+                        }
+                    };
+                }
+                // Nothing to execute yet; the chain ends with the plan run
+                _ => {}
+            }
+
+            // Close the output before the stand-in exits, so readers see all of it
+            drop(stdout);
+            drop(stderr);
+            if let Err(e) = exit_code_input
+                .write_all(format!("{}\n", exit_code).as_bytes())
+                .await
+            {
+                tracing::error!(
+                    "Failed to hand the exit code of task {}'s chain over: {}",
+                    task_id,
+                    e
+                );
+            }
+        });
+
+        Ok(chain)
+    }
+
+    fn normalize_logs(
+        &self,
+        logs: &str,
+        worktree_path: &str,
+    ) -> Result<NormalizedConversation, String> {
+        let (plan_logs, execute_logs) = split_logs(logs);
+        let mut conversation = self
+            .plan_executor
+            .normalize_logs(plan_logs, worktree_path)?;
+
+        if let Some(execute_logs) = execute_logs {
+            let execution = self
+                .execute_executor
+                .normalize_logs(execute_logs, worktree_path)?;
+
+            conversation.entries.push(NormalizedEntry {
+                timestamp: None,
+                entry_type: NormalizedEntryType::SystemMessage,
+                content: "Plan approved, starting execution".to_string(),
+                metadata: None,
+                repeat_count: 1,
+            });
+            conversation.entries.extend(execution.entries);
+            conversation.session_id = execution.session_id.or(conversation.session_id);
+            conversation.executor_type =
+                format!("{}+{}", conversation.executor_type, execution.executor_type);
+            conversation.summary = execution.summary.or(conversation.summary);
+            conversation.stats = execution.stats.or(conversation.stats);
+            conversation.metadata.debug_entry_count += execution.metadata.debug_entry_count;
+        }
+
+        Ok(conversation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan_entry(plan: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: "exit_plan_mode".to_string(),
+                action_type: ActionType::PlanPresentation {
+                    plan: plan.to_string(),
+                },
+            },
+            content: plan.to_string(),
+            metadata: None,
+            repeat_count: 1,
+        }
+    }
+
+    fn conversation(entries: Vec<NormalizedEntry>) -> NormalizedConversation {
+        NormalizedConversation {
+            entries,
+            session_id: None,
+            executor_type: "ClaudePlan".to_string(),
+            prompt: None,
+            summary: None,
+            stats: None,
+            metadata: Default::default(),
+        }
+    }
+
+    /// Runs a shell script; lines starting with `PLAN: ` present a plan
+    struct ScriptExecutor {
+        script: String,
+    }
+
+    #[async_trait]
+    impl Executor for ScriptExecutor {
+        async fn spawn(
+            &self,
+            _pool: &sqlx::SqlitePool,
+            _task_id: Uuid,
+            _worktree_path: &str,
+        ) -> Result<AsyncGroupChild, ExecutorError> {
+            let mut command = Command::new("sh");
+            command
+                .kill_on_drop(true)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .arg("-c")
+                .arg(&self.script);
+            command
+                .group_spawn()
+                .map_err(|e| SpawnContext::from_command(&command, "Script").spawn_error(e))
+        }
+
+        fn normalize_logs(
+            &self,
+            logs: &str,
+            _worktree_path: &str,
+        ) -> Result<NormalizedConversation, String> {
+            Ok(conversation(
+                logs.lines()
+                    .filter_map(|line| line.strip_prefix("PLAN: "))
+                    .map(plan_entry)
+                    .collect(),
+            ))
+        }
+
+        fn with_prompt_prefix(&self, prefix: &str) -> Option<Box<dyn Executor>> {
+            Some(Box::new(ScriptExecutor {
+                script: format!("echo '{}'; {}", prefix.lines().next()?, self.script),
+            }))
+        }
+    }
+
+    fn script(script: &str) -> Box<dyn Executor> {
+        Box::new(ScriptExecutor {
+            script: script.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_extract_plan_uses_last_plan() {
+        let conversation = conversation(vec![plan_entry("first draft"), plan_entry("final plan")]);
+        assert_eq!(extract_plan(&conversation), Some("final plan".to_string()));
+    }
+
+    #[test]
+    fn test_split_logs() {
+        let logs = format!("plan line\n{}\nexecute line\n", CHAIN_SEPARATOR);
+        assert_eq!(split_logs(&logs), ("plan line\n", Some("execute line\n")));
+        assert_eq!(split_logs("plan line\n"), ("plan line\n", None));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_chain_streams_both_runs_through_one_child() {
+        let pool = sqlx::SqlitePool::connect_lazy("sqlite::memory:").unwrap();
+        let chain = ExecutorChain::plan_then_execute(
+            script("sleep 1; echo 'PLAN: fix the bug'"),
+            script("echo executed; exit 3"),
+            true,
+        );
+
+        let mut child = chain.spawn(&pool, Uuid::new_v4(), "/tmp").await.unwrap();
+        // Handed back while the plan run is still going
+        assert!(child.try_wait().unwrap().is_none());
+
+        let mut output = String::new();
+        child
+            .inner()
+            .stdout
+            .take()
+            .unwrap()
+            .read_to_string(&mut output)
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            format!(
+                "PLAN: fix the bug\n{}\nImplement the following approved plan:\nexecuted\n",
+                CHAIN_SEPARATOR
+            )
+        );
+        assert_eq!(child.wait().await.unwrap().code(), Some(3));
+
+        let conversation = chain.normalize_logs(&output, "/tmp").unwrap();
+        assert_eq!(extract_plan(&conversation), Some("fix the bug".to_string()));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_chain_writes_the_stderr_of_both_runs() {
+        let pool = sqlx::SqlitePool::connect_lazy("sqlite::memory:").unwrap();
+        let chain = ExecutorChain::plan_then_execute(
+            script("echo 'PLAN: fix the bug'; echo 'plan warning' >&2"),
+            script("echo executed; echo 'execute error' >&2; exit 1"),
+            true,
+        );
+
+        let mut child = chain.spawn(&pool, Uuid::new_v4(), "/tmp").await.unwrap();
+        let mut stdout = child.inner().stdout.take().unwrap();
+        let mut stderr = child.inner().stderr.take().unwrap();
+        let (mut output, mut errors) = (String::new(), String::new());
+        let (read_output, read_errors) = tokio::join!(
+            stdout.read_to_string(&mut output),
+            stderr.read_to_string(&mut errors)
+        );
+        read_output.unwrap();
+        read_errors.unwrap();
+        assert!(output.ends_with("executed\n"));
+        assert_eq!(errors, "plan warning\nexecute error\n");
+        assert_eq!(child.wait().await.unwrap().code(), Some(1));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_killing_the_chain_stops_its_run() {
+        let pool = sqlx::SqlitePool::connect_lazy("sqlite::memory:").unwrap();
+        let chain = ExecutorChain::plan_then_execute(
+            script("echo started; sleep 30; echo 'PLAN: fix the bug'"),
+            script("echo executed"),
+            true,
+        );
+
+        let mut child = chain.spawn(&pool, Uuid::new_v4(), "/tmp").await.unwrap();
+        let mut stdout = child.inner().stdout.take().unwrap();
+        let mut started = [0; 8];
+        stdout.read_exact(&mut started).await.unwrap();
+        child.kill().await.unwrap();
+
+        // The plan run is killed with the chain, which closes the chain's output
+        let mut rest = String::new();
+        tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            stdout.read_to_string(&mut rest),
+        )
+        .await
+        .expect("chain output was not closed")
+        .unwrap();
+        assert_eq!(rest, "");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_chain_without_approval_stops_after_plan() {
+        let pool = sqlx::SqlitePool::connect_lazy("sqlite::memory:").unwrap();
+        let chain = ExecutorChain::plan_then_execute(
+            script("echo 'PLAN: fix the bug'"),
+            script("echo executed"),
+            false,
+        );
+
+        let mut child = chain.spawn(&pool, Uuid::new_v4(), "/tmp").await.unwrap();
+        let mut output = String::new();
+        child
+            .inner()
+            .stdout
+            .take()
+            .unwrap()
+            .read_to_string(&mut output)
+            .await
+            .unwrap();
+        assert_eq!(output, "PLAN: fix the bug\n");
+        assert_eq!(child.wait().await.unwrap().code(), Some(0));
+    }
+}
@@ -1,25 +1,132 @@
-use std::path::Path;
-use std::sync::OnceLock;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
 
 use async_trait::async_trait;
+use futures_util::stream::{self, BoxStream, StreamExt};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 use command_group::{AsyncCommandGroup, AsyncGroupChild};
-use tokio::process::Command;
+use sha2::{Digest, Sha256};
+use tokio::{process::Command, sync::mpsc};
+use tracing::Instrument;
+use ts_rs::TS;
 use uuid::Uuid;
 
 use crate::{
+    api_key_store::ProjectApiKeyStore,
     executor::{
-        ActionType, Executor, ExecutorError, NormalizedConversation, NormalizedEntry,
-        NormalizedEntryType,
+        collapse_repeated_entries, command_program_exists, task_has_prompt, ActionType,
+        ConversationMetadata, ExecutionStats, Executor, ExecutorError, NormalizedConversation,
+        NormalizedEntry, NormalizedEntryType, ProgressReporter, SpawnRetryPolicy, ValidationReport,
+        WebPolicyGuard,
     },
-    models::task::Task,
-    utils::shell::get_shell_command,
+    executor_events::{ExecutorFallbackTriggered, ExecutorSpawnFailed, ExecutorSpawned},
+    models::{
+        project::Project,
+        task::{Task, TaskEnvVarError},
+    },
+    prompt_builder::build_task_prompt,
+    utils::{
+        path::make_path_relative,
+        shell::{current_shell_supports_pipestatus, find_in_path, get_shell_command, shell_quote},
+    },
+    web_policy::WebPolicy,
 };
 
 // Static cache for local Claude Code detection
 static LOCAL_CLAUDE_CODE: OnceLock<Option<String>> = OnceLock::new();
 
+/// Where claude-code is usually installed when it is not on `PATH`
+const COMMON_CLAUDE_CODE_PATHS: &[&str] = &[
+    "/usr/local/bin/claude-code",
+    "/usr/bin/claude-code",
+    "/opt/homebrew/bin/claude-code",
+    "~/.local/bin/claude-code",
+];
+
+/// Inline JSON tool aliases, taking precedence over the aliases file
+pub const TOOL_ALIASES_ENV: &str = "VIBE_TOOL_ALIASES";
+
+// Tool aliases are read once and kept for the life of the process
+static TOOL_ALIASES: OnceLock<ToolAliasMap> = OnceLock::new();
+
+/// The Claude tools `extract_action_type` knows how to interpret
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KnownTool {
+    Read,
+    #[serde(alias = "write", alias = "multiedit")]
+    Edit,
+    Bash,
+    Grep,
+    Glob,
+    #[serde(rename = "webfetch")]
+    WebFetch,
+    Task,
+    Computer,
+    ExitPlanMode,
+}
+
+impl KnownTool {
+    fn from_name(name: &str) -> Option<Self> {
+        serde_json::from_value(serde_json::Value::String(name.to_lowercase())).ok()
+    }
+}
+
+/// Maps third-party tool names (e.g. from MCP servers) onto known Claude tools,
+/// loaded from `VIBE_TOOL_ALIASES` or `~/.vibe-kanban/tool-aliases.json`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct ToolAliasMap(HashMap<String, KnownTool>);
+
+impl ToolAliasMap {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let aliases: HashMap<String, KnownTool> = serde_json::from_str(json)?;
+        Ok(Self(
+            aliases
+                .into_iter()
+                .map(|(name, tool)| (name.to_lowercase(), tool))
+                .collect(),
+        ))
+    }
+
+    fn load() -> Self {
+        let (source, json) = match std::env::var(TOOL_ALIASES_ENV) {
+            Ok(json) => (TOOL_ALIASES_ENV.to_string(), json),
+            Err(_) => {
+                let Some(path) = dirs::home_dir()
+                    .map(|home| home.join(".vibe-kanban").join("tool-aliases.json"))
+                else {
+                    return Self::default();
+                };
+                match std::fs::read_to_string(&path) {
+                    Ok(json) => (path.display().to_string(), json),
+                    Err(_) => return Self::default(),
+                }
+            }
+        };
+        Self::from_json(&json).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid tool aliases from {}: {}", source, e);
+            Self::default()
+        })
+    }
+
+    /// Resolve a tool name, preferring a configured alias over the built-in names
+    pub fn resolve(&self, tool_name: &str) -> Option<KnownTool> {
+        self.0
+            .get(&tool_name.to_lowercase())
+            .copied()
+            .or_else(|| KnownTool::from_name(tool_name))
+    }
+}
+
+/// The process-wide tool aliases, loaded on first use
+pub fn tool_aliases() -> &'static ToolAliasMap {
+    TOOL_ALIASES.get_or_init(ToolAliasMap::load)
+}
+
 /// Detect if claude-code is installed locally
-async fn detect_local_claude_code() -> Option<String> {
+pub(crate) async fn detect_local_claude_code() -> Option<String> {
     let (shell_cmd, shell_arg) = get_shell_command();
     
     // Try to find claude-code in PATH
@@ -39,14 +146,7 @@ async fn detect_local_claude_code() -> Option<String> {
     }
     
     // Check common installation locations
-    let common_paths = vec![
-        "/usr/local/bin/claude-code",
-        "/usr/bin/claude-code",
-        "/opt/homebrew/bin/claude-code",
-        "~/.local/bin/claude-code",
-    ];
-    
-    for path in common_paths {
+    for path in COMMON_CLAUDE_CODE_PATHS {
         let expanded_path = shellexpand::tilde(path).to_string();
         if std::path::Path::new(&expanded_path).exists() {
             tracing::info!("Found claude-code at common location: {}", expanded_path);
@@ -58,11 +158,11 @@ async fn detect_local_claude_code() -> Option<String> {
 }
 
 /// Get the appropriate Claude Code command based on configuration and availability
-async fn get_claude_command(use_plan_mode: bool) -> String {
+async fn get_claude_command(use_plan_mode: bool, model: Option<&str>) -> String {
     // First, check if there's a configured path in .claude.json
     if let Some(config_path) = get_claude_config_path().await {
         tracing::info!("Using Claude Code from config: {}", config_path);
-        return build_claude_command(&config_path, use_plan_mode);
+        return build_claude_command(&config_path, use_plan_mode, model);
     }
     
     // Check if we have a cached result for local detection
@@ -73,12 +173,8 @@ async fn get_claude_command(use_plan_mode: bool) -> String {
     
     // If not cached, detect it
     let claude_path = if local_claude.is_none() {
-        if let Some(path) = detect_local_claude_code().await {
-            // Note: We can't update the OnceLock here due to async context
-            Some(path)
-        } else {
-            None
-        }
+        // Note: We can't update the OnceLock here due to async context
+        detect_local_claude_code().await
     } else {
         local_claude.clone()
     };
@@ -86,20 +182,28 @@ async fn get_claude_command(use_plan_mode: bool) -> String {
     // Use local installation if available
     if let Some(local_path) = claude_path {
         tracing::info!("Using local Claude Code: {}", local_path);
-        return build_claude_command(&local_path, use_plan_mode);
+        return build_claude_command(&local_path, use_plan_mode, model);
     }
     
     // Fall back to npx
     tracing::info!("Falling back to npx Claude Code");
-    build_claude_command("npx -y @anthropic-ai/claude-code@latest", use_plan_mode)
+    build_claude_command("npx -y @anthropic-ai/claude-code@latest", use_plan_mode, model)
 }
 
-/// Build the complete Claude command with appropriate flags
-fn build_claude_command(base_command: &str, use_plan_mode: bool) -> String {
-    if use_plan_mode {
+/// Build the complete Claude command with appropriate flags, selecting `model` when set
+pub fn build_claude_command(
+    base_command: &str,
+    use_plan_mode: bool,
+    model: Option<&str>,
+) -> String {
+    let command = if use_plan_mode {
         format!("{} -p --permission-mode=plan --verbose --output-format=stream-json", base_command)
     } else {
         format!("{} -p --dangerously-skip-permissions --verbose --output-format=stream-json", base_command)
+    };
+    match model {
+        Some(model) => format!("{} --model {}", command, model),
+        None => command,
     }
 }
 
@@ -116,16 +220,514 @@ async fn get_claude_config_path() -> Option<String> {
         .map(|s| s.to_string())
 }
 
-fn create_watchkill_script(command: &str) -> String {
+/// The claude-code binary local runs use, identified for verification
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ClaudeBinary {
+    pub path: String,
+    pub version: String,
+    /// Hex SHA-256 of the binary's contents
+    pub sha256: String,
+}
+
+impl ClaudeBinary {
+    /// Locate the binary that local runs use, following symlinks, and hash it.
+    /// Runs through npx have no local binary and cannot be verified.
+    pub async fn detect() -> Result<Self, String> {
+        let configured = get_claude_config_path()
+            .await
+            .map(|path| PathBuf::from(shellexpand::tilde(&path).as_ref()));
+        let path = configured
+            .or_else(|| find_in_path("claude-code"))
+            .or_else(|| {
+                COMMON_CLAUDE_CODE_PATHS
+                    .iter()
+                    .map(|path| PathBuf::from(shellexpand::tilde(path).as_ref()))
+                    .find(|path| path.is_file())
+            })
+            .ok_or_else(|| "no local claude-code binary found".to_string())?;
+        let path = tokio::fs::canonicalize(&path)
+            .await
+            .map_err(|e| format!("failed to resolve {}: {}", path.display(), e))?;
+
+        let output = Command::new(&path)
+            .arg("--version")
+            .output()
+            .await
+            .map_err(|e| format!("failed to run {} --version: {}", path.display(), e))?;
+        // e.g. "1.0.51 (Claude Code)"
+        let version = String::from_utf8_lossy(&output.stdout)
+            .split_whitespace()
+            .next()
+            .map(str::to_string)
+            .filter(|_| output.status.success())
+            .ok_or_else(|| format!("{} did not report its version", path.display()))?;
+
+        let contents = tokio::fs::read(&path)
+            .await
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+
+        Ok(Self {
+            path: path.to_string_lossy().to_string(),
+            version,
+            sha256: format!("{:x}", Sha256::digest(&contents)),
+        })
+    }
+
+    /// Check the binary against a manifest of trusted SHA-256 hashes keyed by version
+    pub fn check_manifest(&self, manifest: &HashMap<String, String>) -> Result<(), String> {
+        match manifest.get(&self.version) {
+            Some(trusted) if trusted.eq_ignore_ascii_case(&self.sha256) => Ok(()),
+            Some(trusted) => Err(format!(
+                "{} (version {}) has SHA-256 {} but the manifest trusts {}",
+                self.path, self.version, self.sha256, trusted
+            )),
+            None => Err(format!(
+                "{} (version {}, SHA-256 {}) is not in the binary manifest",
+                self.path, self.version, self.sha256
+            )),
+        }
+    }
+}
+
+/// Why the claude-code binary failed verification; Claude runs refuse to spawn while set
+static BINARY_VERIFICATION_ERROR: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+
+fn binary_verification_error() -> Option<String> {
+    BINARY_VERIFICATION_ERROR
+        .get_or_init(|| Mutex::new(None))
+        .lock()
+        .unwrap()
+        .clone()
+}
+
+/// Verify the claude-code binary against `manifest` when `verify` is set, blocking Claude
+/// runs until a later verification passes if it does not match
+pub async fn verify_claude_binary(verify: bool, manifest: &HashMap<String, String>) {
+    let error = if verify {
+        match ClaudeBinary::detect().await {
+            Ok(binary) => match binary.check_manifest(manifest) {
+                Ok(()) => {
+                    tracing::info!("Verified claude-code {} at {}", binary.version, binary.path);
+                    None
+                }
+                Err(e) => Some(e),
+            },
+            Err(e) => Some(e),
+        }
+    } else {
+        None
+    };
+
+    if let Some(ref e) = error {
+        tracing::error!("claude-code binary verification failed: {}", e);
+    }
+    *BINARY_VERIFICATION_ERROR
+        .get_or_init(|| Mutex::new(None))
+        .lock()
+        .unwrap() = error;
+}
+
+/// Load the environment variables set on the task. Runs whose variables cannot be loaded,
+/// e.g. because `VIBE_ENCRYPTION_KEY` changed, fail to spawn rather than start without them.
+async fn task_env_vars(
+    pool: &sqlx::SqlitePool,
+    task_id: Uuid,
+) -> Result<Vec<(String, String)>, ExecutorError> {
+    Task::get_env_vars(pool, task_id)
+        .await
+        .map_err(|e| match e {
+            TaskEnvVarError::Database(e) => ExecutorError::DatabaseError(e),
+            TaskEnvVarError::Encryption(e) => ExecutorError::ConfigError {
+                message: format!(
+                    "Cannot load the environment variables of task {}: {}",
+                    task_id, e
+                ),
+            },
+        })
+}
+
+/// Load the API key the task's project gives the Claude CLI. A run whose key cannot be
+/// loaded starts with the server's credentials.
+async fn project_api_key(pool: &sqlx::SqlitePool, task_id: Uuid) -> Option<String> {
+    ProjectApiKeyStore::get_for_task(pool, task_id, "claude").await
+}
+
+/// Fail with [`ExecutorError::UntrustedBinary`] while the binary has failed verification
+fn ensure_binary_verified(task_id: Uuid) -> Result<(), ExecutorError> {
+    match binary_verification_error() {
+        Some(error) => {
+            tracing::error!("Refusing to spawn Claude for task {}: {}", task_id, error);
+            Err(ExecutorError::UntrustedBinary(error))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Check the settings shared by the Claude executors
+fn validate_claude_settings(
+    command: Option<&str>,
+    auth_token: Option<&str>,
+    cloud_provider: &CloudProvider,
+    max_task_cost_usd: Option<f64>,
+) -> Result<(), ExecutorError> {
+    let invalid = |message: String| Err(ExecutorError::ConfigError { message });
+
+    if command.is_some_and(|command| command.trim().is_empty()) {
+        return invalid("command must not be empty".to_string());
+    }
+    if auth_token.is_some_and(|token| token.trim().is_empty()) {
+        return invalid("auth_token must not be empty".to_string());
+    }
+    if let Some(limit) = max_task_cost_usd {
+        if !limit.is_finite() || limit <= 0.0 {
+            return invalid(format!(
+                "max_task_cost_usd must be a positive number, got {}",
+                limit
+            ));
+        }
+    }
+    match cloud_provider {
+        CloudProvider::Anthropic => {}
+        CloudProvider::Bedrock(region) if region.trim().is_empty() => {
+            return invalid("Bedrock requires an AWS region".to_string());
+        }
+        CloudProvider::Bedrock(_) => {}
+        CloudProvider::Vertex(project_id, _) if project_id.trim().is_empty() => {
+            return invalid(
+                "Vertex AI requires a Google Cloud project, set GOOGLE_CLOUD_PROJECT".to_string(),
+            );
+        }
+        CloudProvider::Vertex(_, region) if region.trim().is_empty() => {
+            return invalid("Vertex AI requires a Google Cloud region".to_string());
+        }
+        CloudProvider::Vertex(..) => {}
+    }
+    Ok(())
+}
+
+/// Parent environment variables the CLI keeps when `sanitize_env` is set
+const ENV_ALLOWLIST: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "TERM",
+    "LANG",
+    "LC_ALL",
+    "TMPDIR",
+    "TEMP",
+    "TMP",
+    "SYSTEMROOT",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+];
+
+/// Prefixes of the credential and provider variables the CLI needs, kept with the allowlist
+const ENV_ALLOWLIST_PREFIXES: &[&str] = &["ANTHROPIC_", "CLAUDE_", "AWS_", "GOOGLE_", "CLOUD_ML_"];
+
+/// Filter the parent environment down to the allowlist plus the `passthrough` names
+fn sanitized_env(
+    vars: impl Iterator<Item = (String, String)>,
+    passthrough: &[String],
+) -> Vec<(String, String)> {
+    vars.filter(|(name, _)| {
+        ENV_ALLOWLIST.contains(&name.as_str())
+            || ENV_ALLOWLIST_PREFIXES
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+            || passthrough.iter().any(|allowed| allowed == name)
+    })
+    .collect()
+}
+
+/// Pass an explicit auth token to the CLI. The token wins over an inherited API key,
+/// which is removed so the CLI cannot pick the wrong credential.
+fn apply_auth_env(command: &mut Command, auth_token: Option<&str>) {
+    if let Some(token) = auth_token {
+        command
+            .env("ANTHROPIC_AUTH_TOKEN", token)
+            .env_remove("ANTHROPIC_API_KEY");
+    }
+}
+
+/// Pass the project's own API key to the CLI. It replaces both the inherited key and an
+/// explicit auth token, so the run is billed to the project.
+fn apply_project_api_key(command: &mut Command, api_key: Option<&str>) {
+    if let Some(api_key) = api_key {
+        command
+            .env("ANTHROPIC_API_KEY", api_key)
+            .env_remove("ANTHROPIC_AUTH_TOKEN");
+    }
+}
+
+/// Whether the CLI has credentials from an explicit token or the environment
+fn has_auth_configured(auth_token: Option<&str>) -> bool {
+    auth_token.is_some_and(|token| !token.is_empty())
+        || ["ANTHROPIC_AUTH_TOKEN", "ANTHROPIC_API_KEY"]
+            .iter()
+            .any(|var| std::env::var(var).is_ok_and(|value| !value.is_empty()))
+}
+
+/// Where the CLI sends its model requests
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum CloudProvider {
+    /// The Anthropic API, authenticated with `ANTHROPIC_AUTH_TOKEN` or `ANTHROPIC_API_KEY`
+    #[default]
+    Anthropic,
+    /// AWS Bedrock in the given region
+    Bedrock(String),
+    /// GCP Vertex AI with the given project ID and region
+    Vertex(String, String),
+}
+
+impl CloudProvider {
+    /// Detect the provider the server itself is configured for, using the same variables
+    /// the CLI reads
+    pub fn from_env() -> Self {
+        Self::from_vars(|key| std::env::var(key).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let enabled = |key: &str| var(key).is_some_and(|value| value == "1" || value == "true");
+        let first = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|key| var(key).filter(|v| !v.is_empty()))
+        };
+
+        if enabled("CLAUDE_CODE_USE_BEDROCK") {
+            let region = first(&["AWS_REGION", "AWS_DEFAULT_REGION"])
+                .unwrap_or_else(|| "us-east-1".to_string());
+            CloudProvider::Bedrock(region)
+        } else if enabled("CLAUDE_CODE_USE_VERTEX") {
+            let project_id =
+                first(&["GOOGLE_CLOUD_PROJECT", "ANTHROPIC_VERTEX_PROJECT_ID"]).unwrap_or_default();
+            let region = first(&["GOOGLE_CLOUD_LOCATION", "CLOUD_ML_REGION"])
+                .unwrap_or_else(|| "us-east5".to_string());
+            CloudProvider::Vertex(project_id, region)
+        } else {
+            CloudProvider::Anthropic
+        }
+    }
+
+    /// Point the CLI at this provider, clearing the other provider's switch
+    fn apply_env(&self, command: &mut Command) {
+        match self {
+            CloudProvider::Anthropic => {
+                command
+                    .env_remove("CLAUDE_CODE_USE_BEDROCK")
+                    .env_remove("CLAUDE_CODE_USE_VERTEX");
+            }
+            CloudProvider::Bedrock(region) => {
+                command
+                    .env("CLAUDE_CODE_USE_BEDROCK", "1")
+                    .env("AWS_REGION", region)
+                    .env_remove("CLAUDE_CODE_USE_VERTEX");
+            }
+            CloudProvider::Vertex(project_id, region) => {
+                command
+                    .env("CLAUDE_CODE_USE_VERTEX", "1")
+                    .env("GOOGLE_CLOUD_PROJECT", project_id)
+                    .env("GOOGLE_CLOUD_LOCATION", region)
+                    .env_remove("CLAUDE_CODE_USE_BEDROCK");
+            }
+        }
+    }
+}
+
+/// USD per million tokens: input, output, cache writes and cache reads
+fn model_prices(model: &str) -> [f64; 4] {
+    if model.contains("opus") {
+        [15.0, 75.0, 18.75, 1.5]
+    } else if model.contains("haiku") {
+        [0.8, 4.0, 1.0, 0.08]
+    } else {
+        [3.0, 15.0, 3.75, 0.3]
+    }
+}
+
+/// Read the turn count, duration, cost and token usage from a `result` line
+fn parse_result_stats(json: &serde_json::Value) -> ExecutionStats {
+    let usage = &json["usage"];
+    ExecutionStats {
+        num_turns: json["num_turns"].as_u64().map(|turns| turns as u32),
+        duration_ms: json["duration_ms"].as_u64(),
+        // Older CLI versions report `cost_usd` instead
+        total_cost_usd: json["total_cost_usd"]
+            .as_f64()
+            .or_else(|| json["cost_usd"].as_f64()),
+        input_tokens: usage["input_tokens"].as_u64(),
+        output_tokens: usage["output_tokens"].as_u64(),
+    }
+}
+
+/// The `timestamp` of a stream-json message, normalized to UTC RFC 3339
+fn parse_message_timestamp(json: &serde_json::Value) -> Option<String> {
+    let timestamp = json.get("timestamp").and_then(|v| v.as_str())?;
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|timestamp| timestamp.with_timezone(&chrono::Utc).to_rfc3339())
+}
+
+/// Running cost estimate of a Claude run, built from the `usage` of its assistant messages
+#[derive(Debug, Default)]
+pub struct CostTracker {
+    /// Streamed messages repeat their usage on every line, so keep the latest per message ID
+    message_costs: HashMap<String, f64>,
+}
+
+impl CostTracker {
+    /// Account for one stream-json line and return the estimated total cost in USD
+    pub fn observe(&mut self, line: &str) -> f64 {
+        if let Ok(json) = serde_json::from_str::<serde_json::Value>(line.trim()) {
+            if json.get("type").and_then(|t| t.as_str()) == Some("assistant") {
+                let message = &json["message"];
+                if let (Some(id), Some(usage)) = (
+                    message.get("id").and_then(|id| id.as_str()),
+                    message.get("usage"),
+                ) {
+                    let prices = model_prices(message["model"].as_str().unwrap_or_default());
+                    let tokens = [
+                        "input_tokens",
+                        "output_tokens",
+                        "cache_creation_input_tokens",
+                        "cache_read_input_tokens",
+                    ]
+                    .map(|field| usage.get(field).and_then(|n| n.as_u64()).unwrap_or(0));
+                    let cost = tokens
+                        .iter()
+                        .zip(prices)
+                        .map(|(tokens, price)| *tokens as f64 * price / 1_000_000.0)
+                        .sum();
+                    self.message_costs.insert(id.to_string(), cost);
+                }
+            }
+        }
+        self.total_usd()
+    }
+
+    pub fn total_usd(&self) -> f64 {
+        self.message_costs.values().sum()
+    }
+}
+
+/// Append `--output-dir <dir>` to a Claude command when an output directory is set
+fn with_output_dir_flag(command: String, output_dir: Option<&Path>) -> String {
+    match output_dir {
+        Some(dir) => format!("{} --output-dir {}", command, dir.display()),
+        None => command,
+    }
+}
+
+/// Append `--mcp-config <path>` to a Claude command when an MCP config is set
+fn with_mcp_config_flag(command: String, mcp_config_path: Option<&str>) -> String {
+    match mcp_config_path {
+        Some(path) => format!("{} --mcp-config {}", command, path),
+        None => command,
+    }
+}
+
+/// Check the web fetches of a Claude run against `policy` as its output streams; `None` when
+/// the policy allows everything
+fn web_policy_guard(policy: &WebPolicy, worktree_path: &str) -> Option<WebPolicyGuard> {
+    if policy.is_unrestricted() {
+        return None;
+    }
+    // Normalized without the policy, which would turn blocked fetches into errors
+    let executor = ClaudeExecutor::new();
+    let worktree_path = worktree_path.to_string();
+    Some(WebPolicyGuard::new(policy.clone(), move |line| {
+        executor.normalize_line(line, &worktree_path)
+    }))
+}
+
+/// Append `--context-file <path>` to a Claude command for each context file
+fn with_context_file_flags(command: String, context_files: &[String]) -> String {
+    context_files.iter().fold(command, |command, path| {
+        format!("{} --context-file {}", command, shell_quote(path))
+    })
+}
+
+/// Check that each context file exists inside the worktree. Paths are relative to the
+/// worktree; `..` and symlinks are resolved before the check.
+pub async fn validate_context_files(
+    context_files: &[String],
+    worktree_path: &str,
+) -> Result<(), ExecutorError> {
+    for file in context_files {
+        let path = Path::new(worktree_path).join(file);
+        if tokio::fs::metadata(&path).await.is_err() {
+            return Err(ExecutorError::InvalidInput(format!(
+                "Context file '{}' does not exist in the worktree",
+                file
+            )));
+        }
+        let resolved = tokio::fs::canonicalize(&path).await.map_err(|e| {
+            ExecutorError::InvalidInput(format!("Cannot resolve context file '{}': {}", file, e))
+        })?;
+        let resolved = resolved.to_string_lossy();
+        // Paths outside the worktree come back unchanged
+        if make_path_relative(&resolved, worktree_path) == resolved {
+            return Err(ExecutorError::InvalidInput(format!(
+                "Context file '{}' is outside the worktree",
+                file
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn create_watchkill_script(command: &str, strict_mode: bool) -> String {
+    build_watchkill_script(command, current_shell_supports_pipestatus(), strict_mode)
+}
+
+/// Build the plan-mode watchkill script. Shells without `PIPESTATUS` get a POSIX
+/// variant that cannot propagate the command's own exit code.
+///
+/// In strict mode the script aborts on the first failing subcommand (`set -e`); otherwise it
+/// only checks that the command can be found and reports the exit status itself.
+fn build_watchkill_script(command: &str, supports_pipestatus: bool, strict_mode: bool) -> String {
     let claude_plan_stop_indicator =
         "Claude requested permissions to use exit_plan_mode, but you haven't granted it yet";
+    let command_check = r#"if ! command -v "${command%% *}" >/dev/null 2>&1; then
+    echo "watchkill: command not found: ${command%% *}" >&2
+    exit 127
+fi
+"#;
+    if !supports_pipestatus {
+        let preamble = if strict_mode { "set -eu\n" } else { "" };
+        return format!(
+            r#"#!/bin/sh
+{}
+word="{}"
+command="{}"
+{}
+$command <&0 2>&1 | while IFS= read -r line; do
+    printf '%s\n' "$line"
+    case "$line" in
+        *"$word"*) exit 0 ;;
+    esac
+done
+"#,
+            preamble,
+            claude_plan_stop_indicator,
+            command,
+            if strict_mode { "" } else { command_check }
+        );
+    }
+    let preamble = if strict_mode {
+        "set -euo pipefail\n"
+    } else {
+        ""
+    };
     format!(
         r#"#!/usr/bin/env bash
-set -euo pipefail
-
+{}
 word="{}"
 command="{}"
-
+{}
 exit_code=0
 while IFS= read -r line; do
     printf '%s\n' "$line"
@@ -137,15 +739,268 @@ done < <($command <&0 2>&1)
 exit_code=${{PIPESTATUS[0]}}
 exit "$exit_code"
 "#,
-        claude_plan_stop_indicator, command
+        preamble,
+        claude_plan_stop_indicator,
+        command,
+        if strict_mode { "" } else { command_check }
     )
 }
 
+/// Prefixes of lines printed by the watchkill script itself rather than by Claude
+const WATCHKILL_OUTPUT_PREFIXES: &[&str] = &["watchkill: "];
+
+lazy_static::lazy_static! {
+    static ref TIMING_LINE_REGEX: Regex = Regex::new(r"^\[\d{2}:\d{2}:\d{2}\.\d{3}\]").unwrap();
+}
+
+/// Normalize a non-JSON output line, or return `None` for lines that should be dropped
+fn normalize_plain_line(line: &str) -> Option<NormalizedEntry> {
+    if WATCHKILL_OUTPUT_PREFIXES
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+    {
+        return None;
+    }
+
+    let (entry_type, content) = if line.starts_with('+') {
+        (NormalizedEntryType::DebugTrace, line.to_string())
+    } else if TIMING_LINE_REGEX.is_match(line) {
+        (NormalizedEntryType::Timing, line.to_string())
+    } else {
+        (
+            NormalizedEntryType::SystemMessage,
+            format!("Raw output: {}", line),
+        )
+    };
+
+    Some(NormalizedEntry {
+        timestamp: None,
+        entry_type,
+        content,
+        metadata: None,
+        repeat_count: 1,
+    })
+}
+
+/// Strip `worktree_path` from `path` when both are Windows paths such as `C:/Users/foo`,
+/// ignoring case as Windows does. Both must already use forward slashes.
+fn strip_windows_path_prefix(path: &str, worktree_path: &str) -> Option<String> {
+    let (drive, rest) = split_windows_drive(path)?;
+    let (worktree_drive, worktree_rest) = split_windows_drive(worktree_path)?;
+    if !drive.eq_ignore_ascii_case(&worktree_drive) {
+        return None;
+    }
+
+    let worktree_rest = worktree_rest.trim_end_matches('/');
+    if !rest
+        .to_ascii_lowercase()
+        .starts_with(&worktree_rest.to_ascii_lowercase())
+    {
+        return None;
+    }
+    // ASCII lowercasing keeps byte offsets, so the prefix length applies to `rest` as well
+    match rest.get(worktree_rest.len()..)? {
+        "" => Some(String::new()),
+        relative if worktree_rest.is_empty() => Some(relative.to_string()),
+        relative => relative.strip_prefix('/').map(str::to_string),
+    }
+}
+
+/// Split `C:/Users/foo` into its drive letter and `Users/foo`
+fn split_windows_drive(path: &str) -> Option<(char, &str)> {
+    let drive = path.chars().next().filter(char::is_ascii_alphabetic)?;
+    path.get(1..)?.strip_prefix(":/").map(|rest| (drive, rest))
+}
+
+/// Longest tool output kept in a conversation entry, since file contents can be enormous
+const MAX_TOOL_RESULT_CHARS: usize = 2048;
+/// How much of a tool's output its concise form shows
+const TOOL_RESULT_PREVIEW_CHARS: usize = 80;
+/// How much of a failed tool's stderr its concise form shows
+const TOOL_ERROR_PREVIEW_CHARS: usize = 120;
+
+/// Text of a `tool_result` block, whose `content` is either a string or a list of blocks
+fn tool_result_text(content: &serde_json::Value) -> String {
+    match content {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .filter_map(|item| item.get("text")?.as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Split off the `Exit code N` line that Claude's Bash tool puts before the stderr of a
+/// failed command
+fn split_exit_code(output: &str) -> (Option<i32>, &str) {
+    let (first_line, rest) = output.split_once('\n').unwrap_or((output, ""));
+    match first_line
+        .trim()
+        .strip_prefix("Exit code ")
+        .and_then(|code| code.parse().ok())
+    {
+        Some(exit_code) => (Some(exit_code), rest),
+        None => (None, output),
+    }
+}
+
+/// The first `max_chars` characters of `text`, with an ellipsis when anything was cut
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+lazy_static::lazy_static! {
+    /// Stderr lines from the Node.js runtime and npm that say nothing about the run itself
+    static ref NOISY_STDERR_REGEXES: Vec<Regex> = vec![
+        // Runtime warnings that slip past NODE_NO_WARNINGS=1, e.g. from npx shims
+        Regex::new(r"^\(node:\d+\) (\[\w+\] )?\w*Warning: ").unwrap(),
+        Regex::new(r"^\(Use `node --trace-[\w-]+ \.\.\.` to show where the warning was created\)").unwrap(),
+        // npm and npx install output
+        Regex::new(r"(?i)^npm (warn|notice|http|info|timing|verb)\b").unwrap(),
+        Regex::new(r"^(added|removed|changed|audited) \d+ packages?\b").unwrap(),
+        Regex::new(r"^up to date\b").unwrap(),
+        Regex::new(r"^\d+ packages? (is|are) looking for funding").unwrap(),
+        Regex::new(r"^\s*run `npm (fund|audit[\w ]*)`").unwrap(),
+        Regex::new(r"^found \d+ vulnerabilit").unwrap(),
+        Regex::new(r"^Need to install the following packages:").unwrap(),
+        Regex::new(r"^\s+[@\w./-]+@[\w.^~-]+$").unwrap(),
+        Regex::new(r"^Ok to proceed\?").unwrap(),
+    ];
+}
+
+/// Turn the significant lines of each stderr chunk into a system message tagged with its source
+fn normalize_stderr_chunks(chunks: &[&str]) -> Vec<NormalizedEntry> {
+    chunks
+        .iter()
+        .filter_map(|chunk| {
+            let significant: Vec<&str> = chunk
+                .lines()
+                .filter(|line| {
+                    !line.trim().is_empty()
+                        && !NOISY_STDERR_REGEXES
+                            .iter()
+                            .any(|regex| regex.is_match(line))
+                })
+                .collect();
+            if significant.is_empty() {
+                return None;
+            }
+
+            Some(NormalizedEntry {
+                timestamp: Some(chrono::Utc::now().to_rfc3339()),
+                entry_type: NormalizedEntryType::SystemMessage,
+                content: significant.join("\n"),
+                metadata: Some(serde_json::json!({ "source": "stderr" })),
+                repeat_count: 1,
+            })
+        })
+        .collect()
+}
+
+/// Output directories of runs that have not been collected yet, keyed by worktree path
+static PENDING_OUTPUT_DIRS: OnceLock<Mutex<HashMap<String, PathBuf>>> = OnceLock::new();
+
+fn pending_output_dirs() -> &'static Mutex<HashMap<String, PathBuf>> {
+    PENDING_OUTPUT_DIRS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Take the `--output-dir` used by the last Claude run in `worktree_path`, if any,
+/// so its artifacts can be collected once the run completes
+pub fn take_output_dir(worktree_path: &str) -> Option<PathBuf> {
+    pending_output_dirs().lock().unwrap().remove(worktree_path)
+}
+
+/// How the prompt is written to the CLI's stdin
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, sqlx::Type, Serialize, Deserialize, TS)]
+#[sqlx(type_name = "stdin_format", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum StdinFormat {
+    /// The prompt text as is
+    #[default]
+    PlainText,
+    /// `{"prompt": "...", "context": {"project_id": "...", "task_id": "..."}}`
+    StructuredJson,
+}
+
+#[derive(Serialize)]
+struct StructuredPrompt<'a> {
+    prompt: &'a str,
+    context: PromptContext,
+}
+
+#[derive(Serialize)]
+struct PromptContext {
+    project_id: Uuid,
+    task_id: Uuid,
+}
+
+impl StdinFormat {
+    /// Encode a task's prompt for the CLI's stdin
+    pub fn encode(&self, prompt: &str, project_id: Uuid, task_id: Uuid) -> String {
+        match self {
+            StdinFormat::PlainText => prompt.to_string(),
+            StdinFormat::StructuredJson => serde_json::to_string(&StructuredPrompt {
+                prompt,
+                context: PromptContext {
+                    project_id,
+                    task_id,
+                },
+            })
+            .expect("structured prompts always serialize to JSON"),
+        }
+    }
+}
+
+/// Prompt used to continue a session whose previous run timed out
+pub const TIMEOUT_RESUME_PROMPT: &str =
+    "The previous run timed out. Please continue from where you left off.";
+
+/// Prompt used to continue a session whose previous run crashed
+pub const CRASH_RESUME_PROMPT: &str =
+    "The previous run exited unexpectedly. Please continue from where you left off.";
+
 /// An executor that uses Claude CLI to process tasks
+#[derive(Clone)]
 pub struct ClaudeExecutor {
     executor_type: String,
     command: Option<String>,
     use_plan_mode: bool,
+    /// Run the plan-mode watchkill script under `set -euo pipefail`
+    pub watchkill_strict_mode: bool,
+    /// Text placed ahead of the task prompt, e.g. an approved plan
+    prompt_prefix: Option<String>,
+    /// Text placed after the task prompt, e.g. a summary of an earlier session
+    prompt_suffix: Option<String>,
+    /// Directory passed to `--output-dir` for file artifacts; relative paths are
+    /// resolved against the worktree
+    pub output_dir: Option<PathBuf>,
+    /// Passed to the CLI as `ANTHROPIC_AUTH_TOKEN`, taking precedence over `ANTHROPIC_API_KEY`
+    pub auth_token: Option<String>,
+    /// Defaults to the provider detected from the server's environment
+    pub cloud_provider: CloudProvider,
+    /// Terminate the run once its estimated cost exceeds this many USD
+    pub max_task_cost_usd: Option<f64>,
+    /// Start the CLI with only the allowlisted parent environment variables
+    pub sanitize_env: bool,
+    /// Further parent environment variables kept when `sanitize_env` is set, e.g. `CARGO_HOME`
+    pub extra_env_passthrough: Vec<String>,
+    /// Format of the prompt written to the CLI's stdin
+    pub stdin_format: StdinFormat,
+    /// Keep the CLI's `"type": "debug"` lines in normalized logs instead of only counting them
+    pub include_debug: bool,
+    /// Model passed to the CLI as `--model`, e.g. `claude-sonnet-4`; the CLI default when
+    /// `None`. Not applied to custom commands.
+    pub model: Option<String>,
+    /// File passed to the CLI as `--mcp-config`, declaring the MCP servers of the run
+    pub mcp_config_path: Option<String>,
+    /// Domains the run may fetch from; fetches of others terminate it
+    pub web_policy: WebPolicy,
 }
 
 impl Default for ClaudeExecutor {
@@ -161,6 +1016,20 @@ impl ClaudeExecutor {
             executor_type: "Claude".to_string(),
             command: None, // Will be determined dynamically
             use_plan_mode: false,
+            watchkill_strict_mode: true,
+            prompt_prefix: None,
+            prompt_suffix: None,
+            output_dir: None,
+            auth_token: None,
+            cloud_provider: CloudProvider::from_env(),
+            max_task_cost_usd: None,
+            sanitize_env: false,
+            extra_env_passthrough: Vec::new(),
+            stdin_format: StdinFormat::PlainText,
+            include_debug: false,
+            model: None,
+            mcp_config_path: None,
+            web_policy: WebPolicy::default(),
         }
     }
 
@@ -169,6 +1038,20 @@ impl ClaudeExecutor {
             executor_type: "ClaudePlan".to_string(),
             command: None, // Will be determined dynamically
             use_plan_mode: true,
+            watchkill_strict_mode: true,
+            prompt_prefix: None,
+            prompt_suffix: None,
+            output_dir: None,
+            auth_token: None,
+            cloud_provider: CloudProvider::from_env(),
+            max_task_cost_usd: None,
+            sanitize_env: false,
+            extra_env_passthrough: Vec::new(),
+            stdin_format: StdinFormat::PlainText,
+            include_debug: false,
+            model: None,
+            mcp_config_path: None,
+            web_policy: WebPolicy::default(),
         }
     }
 
@@ -178,52 +1061,234 @@ impl ClaudeExecutor {
             executor_type,
             command: Some(command),
             use_plan_mode: false,
+            watchkill_strict_mode: true,
+            prompt_prefix: None,
+            prompt_suffix: None,
+            output_dir: None,
+            auth_token: None,
+            cloud_provider: CloudProvider::from_env(),
+            max_task_cost_usd: None,
+            sanitize_env: false,
+            extra_env_passthrough: Vec::new(),
+            stdin_format: StdinFormat::PlainText,
+            include_debug: false,
+            model: None,
+            mcp_config_path: None,
+            web_policy: WebPolicy::default(),
         }
     }
     
+    /// Create a ClaudeExecutor that runs the CLI on `model`, e.g. a cheaper one for
+    /// single-turn prompts
+    pub fn new_with_model(model: &str) -> Self {
+        Self::new().with_model(model)
+    }
+
+    /// Run the CLI on `model` instead of its default
+    pub fn with_model(mut self, model: &str) -> Self {
+        self.model = Some(model.to_string());
+        self
+    }
+
+    /// Give the run the MCP servers declared in the config file at `path`
+    pub fn with_mcp_config(mut self, path: &str) -> Self {
+        self.mcp_config_path = Some(path.to_string());
+        self
+    }
+
+    /// Limit the domains the run may fetch from
+    pub fn with_web_policy(mut self, policy: WebPolicy) -> Self {
+        self.web_policy = policy;
+        self
+    }
+
+    /// Write the prompt to the CLI's stdin in `format`
+    pub fn with_stdin_format(mut self, format: StdinFormat) -> Self {
+        self.stdin_format = format;
+        self
+    }
+
+    /// Stop the run once a plan is presented; custom commands are wrapped in the
+    /// watchkill script like detected ones
+    pub fn with_plan_mode(mut self, use_plan_mode: bool) -> Self {
+        self.use_plan_mode = use_plan_mode;
+        self
+    }
+
+    /// Answer `prompt` in a single turn and return the CLI's final result. Unlike task runs,
+    /// the prompt is passed as an argument and the output is read once the CLI has exited.
+    pub async fn run_single_turn(
+        &self,
+        prompt: &str,
+        working_dir: &str,
+    ) -> Result<String, ExecutorError> {
+        let claude_command = format!("{} --max-turns 1", self.base_command().await);
+        let (shell_cmd, shell_arg) = get_shell_command();
+
+        let mut command = Command::new(shell_cmd);
+        if self.sanitize_env {
+            command
+                .env_clear()
+                .envs(sanitized_env(std::env::vars(), &self.extra_env_passthrough));
+        }
+        // The prompt reaches the CLI as the script's first positional parameter, so it
+        // needs no quoting
+        command
+            .kill_on_drop(true)
+            .stdin(std::process::Stdio::null())
+            .current_dir(working_dir)
+            .arg(shell_arg)
+            .arg(format!("{} \"$1\"", claude_command))
+            .arg(shell_cmd)
+            .arg(prompt)
+            .env("NODE_NO_WARNINGS", "1");
+        apply_auth_env(&mut command, self.auth_token.as_deref());
+        self.cloud_provider.apply_env(&mut command);
+
+        let output = command.output().await.map_err(|e| {
+            let context =
+                crate::executor::SpawnContext::from_command(&command, &self.executor_type)
+                    .with_context(format!("{} CLI single-turn prompt", self.executor_type));
+            ExecutorError::from_spawn_error(e, context)
+        })?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+
+            if let Some(signal) = output.status.signal() {
+                return Err(ExecutorError::ProcessKilled {
+                    signal: Some(signal),
+                });
+            }
+        }
+        if !output.status.success() {
+            return Err(ExecutorError::NoResult(format!(
+                "{} CLI exited with {}: {}",
+                self.executor_type,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let conversation = self
+            .normalize_logs(&stdout, working_dir)
+            .map_err(ExecutorError::NoResult)?;
+        conversation
+            .summary
+            .filter(|result| !result.trim().is_empty())
+            .ok_or_else(|| ExecutorError::NoResult("the CLI output has no result".to_string()))
+    }
+
+    /// The configured or detected Claude command, before plan mode wraps it in a script
+    async fn base_command(&self) -> String {
+        match self.command {
+            Some(ref command) => command.clone(),
+            None => get_claude_command(self.use_plan_mode, self.model.as_deref()).await,
+        }
+    }
+
     /// Get the command to execute, using dynamic detection if not set
-    async fn get_command(&self) -> String {
+    async fn get_command(&self, output_dir: Option<&Path>) -> String {
         if let Some(ref cmd) = self.command {
-            cmd.clone()
+            let command = self.with_run_flags(cmd.clone(), output_dir);
+            if self.use_plan_mode {
+                create_watchkill_script(&command, self.watchkill_strict_mode)
+            } else {
+                command
+            }
         } else if self.use_plan_mode {
-            let command = get_claude_command(true).await;
-            create_watchkill_script(&command)
+            let command = get_claude_command(true, self.model.as_deref()).await;
+            let command = self.with_run_flags(command, output_dir);
+            create_watchkill_script(&command, self.watchkill_strict_mode)
         } else {
-            get_claude_command(false).await
+            let command = get_claude_command(false, self.model.as_deref()).await;
+            self.with_run_flags(command, output_dir)
         }
     }
-    
-    /// Try to spawn with a specific command, with fallback on failure
-    async fn try_spawn_with_fallback(
+
+    /// Append the flags of this particular run, which custom commands cannot carry
+    fn with_run_flags(&self, command: String, output_dir: Option<&Path>) -> String {
+        let command = with_output_dir_flag(command, output_dir);
+        with_mcp_config_flag(command, self.mcp_config_path.as_deref())
+    }
+
+    /// Resolve `output_dir` against the worktree, create it and remember it for artifact collection
+    async fn prepare_output_dir(&self, worktree_path: &str) -> Result<Option<PathBuf>, ExecutorError> {
+        let Some(ref output_dir) = self.output_dir else {
+            return Ok(None);
+        };
+
+        let output_dir = Path::new(worktree_path).join(output_dir);
+        tokio::fs::create_dir_all(&output_dir).await.map_err(|e| {
+            ExecutorError::ContextCollectionFailed(format!(
+                "Failed to create output directory {}: {}",
+                output_dir.display(),
+                e
+            ))
+        })?;
+        pending_output_dirs()
+            .lock()
+            .unwrap()
+            .insert(worktree_path.to_string(), output_dir.clone());
+
+        Ok(Some(output_dir))
+    }
+    
+    /// Try to spawn with a specific command, with fallback on failure. Each command is
+    /// retried on transient failures according to `retry_policy`.
+    async fn try_spawn_with_fallback(
         &self,
         pool: &sqlx::SqlitePool,
         task_id: Uuid,
         worktree_path: &str,
         prompt: &str,
+        retry_policy: &SpawnRetryPolicy,
     ) -> Result<command_group::AsyncGroupChild, ExecutorError> {
-        let primary_command = self.get_command().await;
+        let output_dir = self.prepare_output_dir(worktree_path).await?;
+        let primary_command = self.get_command(output_dir.as_deref()).await;
         
         // Check if this is already the fallback command (npx)
         let is_fallback = primary_command.contains("npx");
         
-        match self.try_spawn_with_command(pool, task_id, worktree_path, prompt, &primary_command).await {
+        let primary = retry_policy
+            .retry(|| {
+                self.try_spawn_with_command(pool, task_id, worktree_path, prompt, &primary_command)
+            })
+            .await;
+        match primary {
             Ok(child) => Ok(child),
             Err(e) if !is_fallback => {
                 // If primary command failed and it's not already npx, try fallback
-                tracing::warn!("Primary command failed: {}. Attempting fallback to npx...", e);
+                ExecutorFallbackTriggered {
+                    task_id,
+                    reason: &e.to_string(),
+                }
+                .emit();
                 
+                let fallback_command = build_claude_command(
+                    "npx -y @anthropic-ai/claude-code@latest",
+                    self.use_plan_mode,
+                    self.model.as_deref(),
+                );
+                let fallback_command = self.with_run_flags(fallback_command, output_dir.as_deref());
                 let fallback_command = if self.use_plan_mode {
-                    let cmd = build_claude_command("npx -y @anthropic-ai/claude-code@latest", true);
-                    create_watchkill_script(&cmd)
+                    create_watchkill_script(&fallback_command, self.watchkill_strict_mode)
                 } else {
-                    build_claude_command("npx -y @anthropic-ai/claude-code@latest", false)
+                    fallback_command
                 };
                 
-                self.try_spawn_with_command(pool, task_id, worktree_path, prompt, &fallback_command).await
-                    .map_err(|fallback_err| {
-                        tracing::error!("Fallback command also failed: {}", fallback_err);
-                        fallback_err
+                retry_policy
+                    .retry(|| {
+                        self.try_spawn_with_command(
+                            pool,
+                            task_id,
+                            worktree_path,
+                            prompt,
+                            &fallback_command,
+                        )
                     })
+                    .await
             }
             Err(e) => Err(e),
         }
@@ -232,15 +1297,21 @@ impl ClaudeExecutor {
     /// Try to spawn with a specific command
     async fn try_spawn_with_command(
         &self,
-        _pool: &sqlx::SqlitePool,
+        pool: &sqlx::SqlitePool,
         task_id: Uuid,
         worktree_path: &str,
         prompt: &str,
         claude_command: &str,
     ) -> Result<command_group::AsyncGroupChild, ExecutorError> {
+        crate::security::check_worktree_path(worktree_path)?;
         let (shell_cmd, shell_arg) = get_shell_command();
 
         let mut command = Command::new(shell_cmd);
+        if self.sanitize_env {
+            command
+                .env_clear()
+                .envs(sanitized_env(std::env::vars(), &self.extra_env_passthrough));
+        }
         command
             .kill_on_drop(true)
             .stdin(std::process::Stdio::piped())
@@ -249,55 +1320,154 @@ impl ClaudeExecutor {
             .current_dir(worktree_path)
             .arg(shell_arg)
             .arg(claude_command)
-            .env("NODE_NO_WARNINGS", "1");
+            .env("NODE_NO_WARNINGS", "1")
+            .envs(task_env_vars(pool, task_id).await?);
+        apply_auth_env(&mut command, self.auth_token.as_deref());
+        let api_key = project_api_key(pool, task_id).await;
+        apply_project_api_key(&mut command, api_key.as_deref());
+        self.cloud_provider.apply_env(&mut command);
 
+        crate::telemetry::record_spawned_command(claude_command);
         let mut child = command
             .group_spawn()
             .map_err(|e| {
-                crate::executor::SpawnContext::from_command(&command, &self.executor_type)
-                    .with_task(task_id, None)
-                    .with_context(format!("{} CLI execution for new task", self.executor_type))
-                    .spawn_error(e)
+                ExecutorSpawnFailed {
+                    task_id,
+                    error: &e.to_string(),
+                }
+                .emit();
+                let context =
+                    crate::executor::SpawnContext::from_command(&command, &self.executor_type)
+                        .with_task(task_id, None)
+                        .with_context(format!("{} CLI execution for new task", self.executor_type));
+                ExecutorError::from_spawn_error(e, context)
             })?;
+        ExecutorSpawned::new(task_id, &self.executor_type, claude_command, worktree_path).emit();
 
         // Write prompt to stdin safely
         if let Some(mut stdin) = child.inner().stdin.take() {
             use tokio::io::AsyncWriteExt;
-            tracing::debug!(
-                "Writing prompt to Claude stdin for task {}: {:?}",
-                task_id,
-                prompt
+            let write_span = tracing::debug_span!(
+                "executor.write_prompt",
+                task.id = %task_id,
+                prompt.bytes = prompt.len()
             );
-            stdin.write_all(prompt.as_bytes()).await.map_err(|e| {
-                let context =
-                    crate::executor::SpawnContext::from_command(&command, &self.executor_type)
-                        .with_task(task_id, None)
-                        .with_context(format!(
-                            "Failed to write prompt to {} CLI stdin",
-                            self.executor_type
-                        ));
-                ExecutorError::spawn_failed(e, context)
-            })?;
-            stdin.shutdown().await.map_err(|e| {
-                let context =
-                    crate::executor::SpawnContext::from_command(&command, &self.executor_type)
-                        .with_task(task_id, None)
-                        .with_context(format!("Failed to close {} CLI stdin", self.executor_type));
-                ExecutorError::spawn_failed(e, context)
-            })?;
+            stdin
+                .write_all(prompt.as_bytes())
+                .instrument(write_span.clone())
+                .await
+                .map_err(|source| ExecutorError::StdinWriteFailed { source })?;
+            stdin
+                .shutdown()
+                .instrument(write_span)
+                .await
+                .map_err(|source| ExecutorError::StdinWriteFailed { source })?;
         }
 
         Ok(child)
     }
 }
 
+/// Number of trailing assistant messages used to describe a previous run without a summary
+const PREVIOUS_OUTCOME_MESSAGE_COUNT: usize = 3;
+
+/// Describe how a previous run ended, preferring its stored summary and falling back to
+/// the last few assistant messages
+fn summarize_previous_outcome(conversation: &NormalizedConversation) -> Option<String> {
+    if let Some(summary) = conversation
+        .summary
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+    {
+        return Some(summary.to_string());
+    }
+
+    let mut messages: Vec<&str> = conversation
+        .entries
+        .iter()
+        .rev()
+        .filter(|entry| matches!(entry.entry_type, NormalizedEntryType::AssistantMessage))
+        .map(|entry| entry.content.trim())
+        .filter(|content| !content.is_empty())
+        .take(PREVIOUS_OUTCOME_MESSAGE_COUNT)
+        .collect();
+    if messages.is_empty() {
+        return None;
+    }
+    messages.reverse();
+    Some(messages.join("\n\n"))
+}
+
+/// Turn a computer use action such as `left_click` into `Left click`
+fn humanize_action(action: &str) -> String {
+    let action = action.replace('_', " ");
+    let mut chars = action.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => action,
+    }
+}
+
+/// A pre-computed tool output, injected into a follow-up as if Claude had run the tool.
+/// Serializes as an Anthropic Messages API `tool_result` content block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ToolResult {
+    // A field rather than a serde tag, which ts-rs would also take as the type's name
+    #[serde(rename = "type")]
+    #[ts(type = "\"tool_result\"")]
+    pub kind: ToolResultKind,
+    /// Id of the `tool_use` block this result answers
+    pub tool_use_id: String,
+    pub content: String,
+    pub is_error: bool,
+}
+
+/// The `type` of a [`ToolResult`] content block
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ToolResultKind {
+    #[default]
+    #[serde(rename = "tool_result")]
+    ToolResult,
+}
+
 /// An executor that resumes a Claude session
 pub struct ClaudeFollowupExecutor {
     pub session_id: String,
     pub prompt: String,
+    /// Prefix the prompt with a short description of the previous run's outcome
+    pub include_previous_outcome: bool,
+    previous_outcome: Option<String>,
     executor_type: String,
     command_base: Option<String>,
     use_plan_mode: bool,
+    /// Run the plan-mode watchkill script under `set -euo pipefail`
+    pub watchkill_strict_mode: bool,
+    /// Passed to the CLI as `ANTHROPIC_AUTH_TOKEN`, taking precedence over `ANTHROPIC_API_KEY`
+    pub auth_token: Option<String>,
+    /// Defaults to the provider detected from the server's environment
+    pub cloud_provider: CloudProvider,
+    /// Terminate the run once its estimated cost exceeds this many USD
+    pub max_task_cost_usd: Option<f64>,
+    /// Start the CLI with only the allowlisted parent environment variables
+    pub sanitize_env: bool,
+    /// Further parent environment variables kept when `sanitize_env` is set, e.g. `CARGO_HOME`
+    pub extra_env_passthrough: Vec<String>,
+    /// Model passed to the CLI as `--model`; the CLI default when `None`. Not applied to
+    /// custom commands.
+    pub model: Option<String>,
+    /// Files passed to the CLI as `--context-file`, relative to the worktree
+    pub context_files: Vec<String>,
+    /// Domains the run may fetch from; fetches of others terminate it
+    pub web_policy: WebPolicy,
+    /// Format of the prompt written to the CLI's stdin
+    pub stdin_format: StdinFormat,
+    /// Tool outputs injected ahead of the prompt, see [`ClaudeFollowupExecutor::with_tool_results`]
+    tool_results: Vec<ToolResult>,
+    /// Receives each entry as soon as its output line arrives, see
+    /// [`ClaudeFollowupExecutor::with_progress`]
+    progress_tx: Option<mpsc::Sender<NormalizedEntry>>,
 }
 
 impl ClaudeFollowupExecutor {
@@ -306,9 +1476,23 @@ impl ClaudeFollowupExecutor {
         Self {
             session_id,
             prompt,
+            include_previous_outcome: true,
+            previous_outcome: None,
             executor_type: "Claude".to_string(),
             command_base: None, // Will be determined dynamically
             use_plan_mode: false,
+            watchkill_strict_mode: true,
+            auth_token: None,
+            cloud_provider: CloudProvider::from_env(),
+            max_task_cost_usd: None,
+            sanitize_env: false,
+            extra_env_passthrough: Vec::new(),
+            model: None,
+            context_files: Vec::new(),
+            web_policy: WebPolicy::default(),
+            stdin_format: StdinFormat::PlainText,
+            tool_results: Vec::new(),
+            progress_tx: None,
         }
     }
 
@@ -316,9 +1500,23 @@ impl ClaudeFollowupExecutor {
         Self {
             session_id: session_id.clone(),
             prompt,
+            include_previous_outcome: true,
+            previous_outcome: None,
             executor_type: "ClaudePlan".to_string(),
             command_base: None, // Will be determined dynamically
             use_plan_mode: true,
+            watchkill_strict_mode: true,
+            auth_token: None,
+            cloud_provider: CloudProvider::from_env(),
+            max_task_cost_usd: None,
+            sanitize_env: false,
+            extra_env_passthrough: Vec::new(),
+            model: None,
+            context_files: Vec::new(),
+            web_policy: WebPolicy::default(),
+            stdin_format: StdinFormat::PlainText,
+            tool_results: Vec::new(),
+            progress_tx: None,
         }
     }
 
@@ -332,54 +1530,186 @@ impl ClaudeFollowupExecutor {
         Self {
             session_id,
             prompt,
+            include_previous_outcome: true,
+            previous_outcome: None,
             executor_type,
             command_base: Some(command_base),
             use_plan_mode: false,
+            watchkill_strict_mode: true,
+            auth_token: None,
+            cloud_provider: CloudProvider::from_env(),
+            max_task_cost_usd: None,
+            sanitize_env: false,
+            extra_env_passthrough: Vec::new(),
+            model: None,
+            context_files: Vec::new(),
+            web_policy: WebPolicy::default(),
+            stdin_format: StdinFormat::PlainText,
+            tool_results: Vec::new(),
+            progress_tx: None,
+        }
+    }
+
+    /// Resume the session on `model` instead of the CLI's default
+    pub fn with_model(mut self, model: &str) -> Self {
+        self.model = Some(model.to_string());
+        self
+    }
+
+    /// Stop the run once a plan is presented, see [`ClaudeExecutor::with_plan_mode`]
+    pub fn with_plan_mode(mut self, use_plan_mode: bool) -> Self {
+        self.use_plan_mode = use_plan_mode;
+        self
+    }
+
+    /// Limit the domains the run may fetch from
+    pub fn with_web_policy(mut self, policy: WebPolicy) -> Self {
+        self.web_policy = policy;
+        self
+    }
+
+    /// Write the prompt to the CLI's stdin in `format`, see [`ClaudeExecutor::with_stdin_format`]
+    pub fn with_stdin_format(mut self, format: StdinFormat) -> Self {
+        self.stdin_format = format;
+        self
+    }
+
+    /// Give Claude these files, e.g. fresh test output, without pasting them into the prompt
+    pub fn with_context_files(mut self, files: Vec<String>) -> Self {
+        self.context_files = files;
+        self
+    }
+
+    /// Record the outcome of the previous run so it can be prepended to the prompt
+    pub fn with_previous_outcome(mut self, previous: &NormalizedConversation) -> Self {
+        self.previous_outcome = summarize_previous_outcome(previous);
+        self
+    }
+
+    /// Inject pre-computed tool outputs into the follow-up.
+    ///
+    /// They are written to stdin ahead of the prompt as a single-line JSON array of
+    /// Messages API `tool_result` content blocks, followed by a blank line:
+    ///
+    /// ```text
+    /// [{"type":"tool_result","tool_use_id":"toolu_01","content":"...","is_error":false}]
+    ///
+    /// <prompt>
+    /// ```
+    pub fn with_tool_results(mut self, results: Vec<ToolResult>) -> Self {
+        self.tool_results = results;
+        self
+    }
+
+    /// Send entries to `progress_tx` while the session runs, so long follow-ups show progress
+    /// before they finish. Entries are dropped rather than waited on when the channel is full.
+    pub fn with_progress(mut self, progress_tx: mpsc::Sender<NormalizedEntry>) -> Self {
+        self.progress_tx = Some(progress_tx);
+        self
+    }
+
+    /// Build the prompt written to stdin, including injected tool results and the
+    /// previous outcome when enabled
+    fn build_prompt(&self, project_id: Uuid, task_id: Uuid) -> String {
+        let prompt = match &self.previous_outcome {
+            Some(outcome) if self.include_previous_outcome => format!(
+                "Outcome of the previous run:\n{}\n\n{}",
+                outcome, self.prompt
+            ),
+            _ => self.prompt.clone(),
+        };
+        let prompt = self.stdin_format.encode(&prompt, project_id, task_id);
+
+        if self.tool_results.is_empty() {
+            return prompt;
         }
+        let tool_results = serde_json::to_string(&self.tool_results)
+            .expect("tool results always serialize to JSON");
+        format!("{}\n\n{}", tool_results, prompt)
     }
     
     /// Get the command to execute, using dynamic detection if not set
     async fn get_command(&self) -> String {
         if let Some(ref cmd) = self.command_base {
-            format!("{} --resume={}", cmd, self.session_id)
+            let full_command = self.resume_command(cmd);
+            if self.use_plan_mode {
+                create_watchkill_script(&full_command, self.watchkill_strict_mode)
+            } else {
+                full_command
+            }
         } else {
-            let base_command = get_claude_command(self.use_plan_mode).await;
-            let full_command = format!("{} --resume={}", base_command, self.session_id);
+            let base_command = get_claude_command(self.use_plan_mode, self.model.as_deref()).await;
+            let full_command = self.resume_command(&base_command);
             
             if self.use_plan_mode {
-                create_watchkill_script(&full_command)
+                create_watchkill_script(&full_command, self.watchkill_strict_mode)
             } else {
                 full_command
             }
         }
     }
     
-    /// Try to spawn with fallback support
+    /// `base_command` resuming the session, with the context files
+    fn resume_command(&self, base_command: &str) -> String {
+        let command = format!("{} --resume={}", base_command, self.session_id);
+        with_context_file_flags(command, &self.context_files)
+    }
+
+    /// Try to spawn with fallback support, retrying each command on transient failures
+    /// according to `retry_policy`
     async fn try_spawn_with_fallback(
         &self,
+        task_id: Uuid,
         worktree_path: &str,
+        prompt: &str,
+        task_env: &[(String, String)],
+        api_key: Option<&str>,
+        retry_policy: &SpawnRetryPolicy,
     ) -> Result<command_group::AsyncGroupChild, ExecutorError> {
         let primary_command = self.get_command().await;
         let is_fallback = primary_command.contains("npx");
         
-        match self.try_spawn_with_command(worktree_path, &primary_command).await {
+        let primary = retry_policy
+            .retry(|| {
+                self.try_spawn_with_command(
+                    task_id,
+                    worktree_path,
+                    prompt,
+                    task_env,
+                    api_key,
+                    &primary_command,
+                )
+            })
+            .await;
+        match primary {
             Ok(child) => Ok(child),
             Err(e) if !is_fallback => {
-                tracing::warn!("Primary command failed: {}. Attempting fallback to npx...", e);
+                ExecutorFallbackTriggered {
+                    task_id,
+                    reason: &e.to_string(),
+                }
+                .emit();
                 
-                let base_fallback = build_claude_command("npx -y @anthropic-ai/claude-code@latest", self.use_plan_mode);
-                let fallback_command = format!("{} --resume={}", base_fallback, self.session_id);
+                let base_fallback = build_claude_command("npx -y @anthropic-ai/claude-code@latest", self.use_plan_mode, self.model.as_deref());
+                let fallback_command = self.resume_command(&base_fallback);
                 let final_command = if self.use_plan_mode {
-                    create_watchkill_script(&fallback_command)
+                    create_watchkill_script(&fallback_command, self.watchkill_strict_mode)
                 } else {
                     fallback_command
                 };
                 
-                self.try_spawn_with_command(worktree_path, &final_command).await
-                    .map_err(|fallback_err| {
-                        tracing::error!("Fallback command also failed: {}", fallback_err);
-                        fallback_err
+                retry_policy
+                    .retry(|| {
+                        self.try_spawn_with_command(
+                            task_id,
+                            worktree_path,
+                            prompt,
+                            task_env,
+                            api_key,
+                            &final_command,
+                        )
                     })
+                    .await
             }
             Err(e) => Err(e),
         }
@@ -388,12 +1718,22 @@ impl ClaudeFollowupExecutor {
     /// Try to spawn with a specific command
     async fn try_spawn_with_command(
         &self,
+        task_id: Uuid,
         worktree_path: &str,
+        prompt: &str,
+        task_env: &[(String, String)],
+        api_key: Option<&str>,
         claude_command: &str,
     ) -> Result<command_group::AsyncGroupChild, ExecutorError> {
+        crate::security::check_worktree_path(worktree_path)?;
         let (shell_cmd, shell_arg) = get_shell_command();
 
         let mut command = Command::new(shell_cmd);
+        if self.sanitize_env {
+            command
+                .env_clear()
+                .envs(sanitized_env(std::env::vars(), &self.extra_env_passthrough));
+        }
         command
             .kill_on_drop(true)
             .stdin(std::process::Stdio::piped())
@@ -402,46 +1742,51 @@ impl ClaudeFollowupExecutor {
             .current_dir(worktree_path)
             .arg(shell_arg)
             .arg(claude_command)
-            .env("NODE_NO_WARNINGS", "1");
+            .env("NODE_NO_WARNINGS", "1")
+            .envs(task_env.iter().cloned());
+        apply_auth_env(&mut command, self.auth_token.as_deref());
+        apply_project_api_key(&mut command, api_key);
+        self.cloud_provider.apply_env(&mut command);
 
+        crate::telemetry::record_spawned_command(claude_command);
         let mut child = command
             .group_spawn()
             .map_err(|e| {
-                crate::executor::SpawnContext::from_command(&command, &self.executor_type)
-                    .with_context(format!(
-                        "{} CLI followup execution for session {}",
-                        self.executor_type, self.session_id
-                    ))
-                    .spawn_error(e)
-            })?;
-
-        // Write prompt to stdin safely
-        if let Some(mut stdin) = child.inner().stdin.take() {
-            use tokio::io::AsyncWriteExt;
-            tracing::debug!(
-                "Writing prompt to {} stdin for session {}: {:?}",
-                self.executor_type,
-                self.session_id,
-                self.prompt
-            );
-            stdin.write_all(self.prompt.as_bytes()).await.map_err(|e| {
-                let context =
-                    crate::executor::SpawnContext::from_command(&command, &self.executor_type)
-                        .with_context(format!(
-                            "Failed to write prompt to {} CLI stdin for session {}",
-                            self.executor_type, self.session_id
-                        ));
-                ExecutorError::spawn_failed(e, context)
-            })?;
-            stdin.shutdown().await.map_err(|e| {
+                ExecutorSpawnFailed {
+                    task_id,
+                    error: &e.to_string(),
+                }
+                .emit();
                 let context =
                     crate::executor::SpawnContext::from_command(&command, &self.executor_type)
+                        .with_task(task_id, None)
                         .with_context(format!(
-                            "Failed to close {} CLI stdin for session {}",
+                            "{} CLI followup execution for session {}",
                             self.executor_type, self.session_id
                         ));
-                ExecutorError::spawn_failed(e, context)
+                ExecutorError::from_spawn_error(e, context)
             })?;
+        ExecutorSpawned::new(task_id, &self.executor_type, claude_command, worktree_path).emit();
+
+        // Write prompt to stdin safely
+        if let Some(mut stdin) = child.inner().stdin.take() {
+            use tokio::io::AsyncWriteExt;
+            let write_span = tracing::debug_span!(
+                "executor.write_prompt",
+                task.id = %task_id,
+                session.id = %self.session_id,
+                prompt.bytes = prompt.len()
+            );
+            stdin
+                .write_all(prompt.as_bytes())
+                .instrument(write_span.clone())
+                .await
+                .map_err(|source| ExecutorError::StdinWriteFailed { source })?;
+            stdin
+                .shutdown()
+                .instrument(write_span)
+                .await
+                .map_err(|source| ExecutorError::StdinWriteFailed { source })?;
         }
 
         Ok(child)
@@ -450,36 +1795,131 @@ impl ClaudeFollowupExecutor {
 
 #[async_trait]
 impl Executor for ClaudeExecutor {
+    fn validate_config(&self) -> Result<(), ExecutorError> {
+        if self
+            .output_dir
+            .as_ref()
+            .is_some_and(|dir| dir.as_os_str().is_empty())
+        {
+            return Err(ExecutorError::ConfigError {
+                message: "output_dir must not be empty".to_string(),
+            });
+        }
+        validate_claude_settings(
+            self.command.as_deref(),
+            self.auth_token.as_deref(),
+            &self.cloud_provider,
+            self.max_task_cost_usd,
+        )
+    }
+
+    async fn health_check(&self) -> Result<(), String> {
+        self.validate_config().map_err(|e| e.to_string())?;
+        let command = self.base_command().await;
+        if !command_program_exists(&command) {
+            let program = command.split_whitespace().next().unwrap_or_default();
+            return Err(format!("`{}` was not found in PATH", program));
+        }
+        if self.cloud_provider == CloudProvider::Anthropic
+            && !has_auth_configured(self.auth_token.as_deref())
+        {
+            return Err(
+                "Neither ANTHROPIC_AUTH_TOKEN nor ANTHROPIC_API_KEY is configured".to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    async fn validate(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<ValidationReport, ExecutorError> {
+        let prompt_non_empty = task_has_prompt(pool, task_id).await?;
+        let binary_exists = command_program_exists(&self.base_command().await);
+        Ok(ValidationReport::new(
+            self.get_command(None).await,
+            binary_exists,
+            Path::new(worktree_path).is_dir(),
+            prompt_non_empty,
+        ))
+    }
+
+    fn cost_limit_usd(&self) -> Option<f64> {
+        self.max_task_cost_usd
+    }
+
+    fn with_prompt_prefix(&self, prefix: &str) -> Option<Box<dyn Executor>> {
+        let mut executor = self.clone();
+        executor.prompt_prefix = Some(prefix.to_string());
+        Some(Box::new(executor))
+    }
+
+    fn with_prompt_suffix(&self, suffix: &str) -> Option<Box<dyn Executor>> {
+        let mut executor = self.clone();
+        executor.prompt_suffix = Some(suffix.to_string());
+        Some(Box::new(executor))
+    }
+
+    fn web_policy_guard(&self, worktree_path: &str) -> Option<WebPolicyGuard> {
+        web_policy_guard(&self.web_policy, worktree_path)
+    }
+
     async fn spawn(
         &self,
         pool: &sqlx::SqlitePool,
         task_id: Uuid,
         worktree_path: &str,
     ) -> Result<AsyncGroupChild, ExecutorError> {
+        ensure_binary_verified(task_id)?;
+
         // Get the task to fetch its description
         let task = Task::find_by_id(pool, task_id)
             .await?
             .ok_or(ExecutorError::TaskNotFound)?;
+        if task.title.trim().is_empty() {
+            return Err(ExecutorError::PromptEmpty);
+        }
 
-        let prompt = if let Some(task_description) = task.description {
-            format!(
-                r#"project_id: {}
-            
-Task title: {}
-Task description: {}"#,
-                task.project_id, task.title, task_description
-            )
-        } else {
-            format!(
-                r#"project_id: {}
-            
-Task title: {}"#,
-                task.project_id, task.title
-            )
+        let project_prompt = Project::find_prompts(pool, task.project_id)
+            .await?
+            .and_then(|prompts| prompts.combined());
+        let prompt = build_task_prompt(&task, project_prompt.as_deref());
+        let prompt = match self.prompt_prefix {
+            Some(ref prefix) => format!("{}{}", prefix, prompt),
+            None => prompt,
+        };
+        let prompt = match self.prompt_suffix {
+            Some(ref suffix) => format!("{}{}", prompt, suffix),
+            None => prompt,
         };
+        let prompt = self.stdin_format.encode(&prompt, task.project_id, task_id);
 
         // Use the new method with fallback support
-        self.try_spawn_with_fallback(pool, task_id, worktree_path, &prompt).await
+        self.try_spawn_with_fallback(
+            pool,
+            task_id,
+            worktree_path,
+            &prompt,
+            &SpawnRetryPolicy::from_env(),
+        )
+        .await
+    }
+
+    fn normalize_stderr(&self, chunks: &[&str]) -> Option<Vec<NormalizedEntry>> {
+        Some(normalize_stderr_chunks(chunks))
+    }
+
+    fn normalize_logs_stream<'a>(
+        &'a self,
+        lines: BoxStream<'a, String>,
+        worktree_path: &'a str,
+    ) -> BoxStream<'a, NormalizedEntry> {
+        // Each stream-json line stands on its own, so it can be normalized as soon as it is read
+        lines
+            .flat_map(move |line| stream::iter(self.normalize_line(&line, worktree_path)))
+            .boxed()
     }
 
     fn normalize_logs(
@@ -491,24 +1931,25 @@ Task title: {}"#,
 
         let mut entries = Vec::new();
         let mut session_id = None;
+        let mut debug_entry_count = 0;
+        let mut summary = None;
+        let mut stats = None;
 
         for line in logs.lines() {
             let trimmed = line.trim();
             if trimmed.is_empty() {
                 continue;
             }
+            let line_start = entries.len();
 
             // Try to parse as JSON
             let json: Value = match serde_json::from_str(trimmed) {
                 Ok(json) => json,
                 Err(_) => {
-                    // If line isn't valid JSON, add it as raw text
-                    entries.push(NormalizedEntry {
-                        timestamp: None,
-                        entry_type: NormalizedEntryType::SystemMessage,
-                        content: format!("Raw output: {}", trimmed),
-                        metadata: None,
-                    });
+                    // If line isn't valid JSON, classify it as plain-text output
+                    if let Some(entry) = normalize_plain_line(trimmed) {
+                        entries.push(entry);
+                    }
                     continue;
                 }
             };
@@ -543,6 +1984,7 @@ Task title: {}"#,
                                                             NormalizedEntryType::AssistantMessage,
                                                         content: text.to_string(),
                                                         metadata: Some(content_item.clone()),
+                                                        repeat_count: 1,
                                                     });
                                                 }
                                             }
@@ -574,6 +2016,7 @@ Task title: {}"#,
                                                         },
                                                         content,
                                                         metadata: Some(content_item.clone()),
+                                                        repeat_count: 1,
                                                     });
                                                 }
                                             }
@@ -593,17 +2036,25 @@ Task title: {}"#,
                                     if let Some(content_type) =
                                         content_item.get("type").and_then(|t| t.as_str())
                                     {
-                                        if content_type == "text" {
-                                            if let Some(text) =
-                                                content_item.get("text").and_then(|t| t.as_str())
-                                            {
-                                                entries.push(NormalizedEntry {
-                                                    timestamp: None,
-                                                    entry_type: NormalizedEntryType::UserMessage,
-                                                    content: text.to_string(),
-                                                    metadata: Some(content_item.clone()),
-                                                });
+                                        match content_type {
+                                            "text" => {
+                                                if let Some(text) = content_item
+                                                    .get("text")
+                                                    .and_then(|t| t.as_str())
+                                                {
+                                                    entries.push(NormalizedEntry {
+                                                        timestamp: None,
+                                                        entry_type:
+                                                            NormalizedEntryType::UserMessage,
+                                                        content: text.to_string(),
+                                                        metadata: Some(content_item.clone()),
+                                                        repeat_count: 1,
+                                                    });
+                                                }
                                             }
+                                            "tool_result" => entries
+                                                .push(self.normalize_tool_result(content_item)),
+                                            _ => {}
                                         }
                                     }
                                 }
@@ -624,11 +2075,49 @@ Task title: {}"#,
                                             .unwrap_or("unknown")
                                     ),
                                     metadata: Some(json.clone()),
+                                    repeat_count: 1,
+                                });
+                            } else if subtype == "output_truncated" {
+                                entries.push(NormalizedEntry {
+                                    timestamp: None,
+                                    entry_type: NormalizedEntryType::SystemMessage,
+                                    content: format!(
+                                        "An output line of {} bytes was cut off at its first 500 KB",
+                                        json.get("original_size")
+                                            .and_then(|size| size.as_u64())
+                                            .unwrap_or_default()
+                                    ),
+                                    metadata: Some(json.clone()),
+                                    repeat_count: 1,
+                                });
+                            }
+                        }
+                        true
+                    }
+                    "debug" => {
+                        // Internal CLI state, counted but only shown when asked for
+                        debug_entry_count += 1;
+                        if self.include_debug {
+                            if let Some(message) = json.get("message").and_then(|m| m.as_str()) {
+                                entries.push(NormalizedEntry {
+                                    timestamp: None,
+                                    entry_type: NormalizedEntryType::DebugTrace,
+                                    content: message.to_string(),
+                                    metadata: Some(json.clone()),
+                                    repeat_count: 1,
                                 });
                             }
                         }
                         true
                     }
+                    "result" => {
+                        // Not shown as an entry; a later result line supersedes an earlier one
+                        stats = Some(parse_result_stats(&json));
+                        if let Some(result) = json.get("result").and_then(|r| r.as_str()) {
+                            summary = Some(result.to_string());
+                        }
+                        true
+                    }
                     _ => false,
                 }
             } else {
@@ -636,41 +2125,72 @@ Task title: {}"#,
             };
 
             // If JSON didn't match expected patterns, add it as unrecognized JSON
-            // Skip JSON with type "result" as requested
             if !processed {
-                if let Some(msg_type) = json.get("type").and_then(|t| t.as_str()) {
-                    if msg_type == "result" {
-                        // Skip result entries
-                        continue;
-                    }
-                }
                 entries.push(NormalizedEntry {
                     timestamp: None,
                     entry_type: NormalizedEntryType::SystemMessage,
                     content: format!("Unrecognized JSON: {}", trimmed),
-                    metadata: Some(json),
+                    metadata: Some(json.clone()),
+                    repeat_count: 1,
                 });
             }
+
+            if let Some(timestamp) = parse_message_timestamp(&json) {
+                for entry in &mut entries[line_start..] {
+                    entry.timestamp = Some(timestamp.clone());
+                }
+            }
+        }
+
+        // Entries without a timestamp of their own take the one of the entry before them
+        let mut last_timestamp = None;
+        for entry in &mut entries {
+            match &entry.timestamp {
+                Some(timestamp) => last_timestamp = Some(timestamp.clone()),
+                None => entry.timestamp = last_timestamp.clone(),
+            }
         }
+        self.web_policy.apply(&mut entries);
 
         Ok(NormalizedConversation {
-            entries,
+            // Claude retries a failing command verbatim, which would list it once per try
+            entries: collapse_repeated_entries(entries),
             session_id,
             executor_type: self.executor_type.clone(),
             prompt: None,
-            summary: None,
+            summary,
+            stats,
+            metadata: ConversationMetadata { debug_entry_count },
         })
     }
 }
 
 impl ClaudeExecutor {
+    /// Entries for a single line of output
+    fn normalize_line(&self, line: &str, worktree_path: &str) -> Vec<NormalizedEntry> {
+        self.normalize_logs(line, worktree_path)
+            .map(|conversation| conversation.entries)
+            .unwrap_or_default()
+    }
+
     /// Convert absolute paths to relative paths based on worktree path
     fn make_path_relative(&self, path: &str, worktree_path: &str) -> String {
+        // Claude reports paths with forward slashes, even when the worktree path comes from
+        // Windows with backslashes
+        let path = path.replace('\\', "/");
+        let path = path.as_str();
+        let worktree_path = worktree_path.replace('\\', "/");
+        let worktree_path = worktree_path.as_str();
         let path_obj = Path::new(path);
         let worktree_path_obj = Path::new(worktree_path);
 
         tracing::debug!("Making path relative: {} -> {}", path, worktree_path);
 
+        // `Path` only understands drive letters on Windows, so those are compared as text
+        if let Some(relative_path) = strip_windows_path_prefix(path, worktree_path) {
+            return relative_path;
+        }
+
         // If path is already relative, return as is
         if path_obj.is_relative() {
             return path.to_string();
@@ -732,6 +2252,73 @@ impl ClaudeExecutor {
         }
     }
 
+    /// Entry for a `tool_result` block of a user message, which carries what a tool returned.
+    /// A failed tool, e.g. a Bash command exiting non-zero, gives a `ToolError` with its stderr.
+    fn normalize_tool_result(&self, content_item: &serde_json::Value) -> NormalizedEntry {
+        let tool_use_id = content_item
+            .get("tool_use_id")
+            .and_then(|id| id.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let is_error = content_item
+            .get("is_error")
+            .and_then(|is_error| is_error.as_bool())
+            .unwrap_or(false);
+        let output = content_item
+            .get("content")
+            .map(tool_result_text)
+            .unwrap_or_default();
+
+        let (content, summary, entry_type) = if is_error {
+            let (exit_code, stderr) = split_exit_code(&output);
+            (
+                stderr,
+                self.generate_concise_tool_error(stderr),
+                NormalizedEntryType::ToolError {
+                    tool_use_id: tool_use_id.clone(),
+                    exit_code,
+                },
+            )
+        } else {
+            (
+                output.as_str(),
+                self.generate_concise_tool_result(&output),
+                NormalizedEntryType::ToolResult {
+                    tool_use_id: tool_use_id.clone(),
+                },
+            )
+        };
+
+        NormalizedEntry {
+            timestamp: None,
+            content: truncate_chars(content, MAX_TOOL_RESULT_CHARS),
+            // The raw block is left out, since it holds the whole output
+            metadata: Some(serde_json::json!({
+                "type": "tool_result",
+                "tool_use_id": tool_use_id,
+                "is_error": is_error,
+                "summary": summary,
+            })),
+            entry_type,
+            repeat_count: 1,
+        }
+    }
+
+    /// `↩` followed by the start of a tool's output
+    fn generate_concise_tool_result(&self, output: &str) -> String {
+        let output = output.split_whitespace().collect::<Vec<_>>().join(" ");
+        format!("↩ {}", truncate_chars(&output, TOOL_RESULT_PREVIEW_CHARS))
+    }
+
+    /// `Error:` followed by the start of a failed tool's stderr
+    fn generate_concise_tool_error(&self, stderr: &str) -> String {
+        let stderr = stderr.split_whitespace().collect::<Vec<_>>().join(" ");
+        format!(
+            "Error: {}",
+            truncate_chars(&stderr, TOOL_ERROR_PREVIEW_CHARS)
+        )
+    }
+
     fn generate_concise_content(
         &self,
         tool_name: &str,
@@ -742,11 +2329,26 @@ impl ClaudeExecutor {
         match action_type {
             ActionType::FileRead { path } => format!("`{}`", path),
             ActionType::FileWrite { path } => format!("`{}`", path),
+            ActionType::FileDelete { path } => format!("`{}`", path),
+            ActionType::FileMove { from, to } => format!("`{}` → `{}`", from, to),
             ActionType::CommandRun { command } => format!("`{}`", command),
             ActionType::Search { query } => format!("`{}`", query),
             ActionType::WebFetch { url } => format!("`{}`", url),
             ActionType::TaskCreate { description } => description.clone(),
             ActionType::PlanPresentation { plan } => plan.clone(),
+            ActionType::ComputerUse { action, coordinate } => {
+                let text = input.get("text").and_then(|t| t.as_str());
+                match (action.as_str(), coordinate, text) {
+                    ("screenshot", _, _) => "Screenshot".to_string(),
+                    ("type", _, Some(text)) => format!("Type `{}`", text),
+                    ("key", _, Some(key)) => format!("Press `{}`", key),
+                    ("mouse_move", Some((x, y)), _) => format!("Move mouse to ({}, {})", x, y),
+                    (_, Some((x, y)), _) => {
+                        format!("{} at ({}, {})", humanize_action(action), x, y)
+                    }
+                    _ => humanize_action(action),
+                }
+            }
             ActionType::Other { description: _ } => {
                 // For other tools, try to extract key information or fall back to tool name
                 match tool_name.to_lowercase().as_str() {
@@ -825,14 +2427,62 @@ impl ClaudeExecutor {
         }
     }
 
+    /// Recognize a bash command that only deletes or moves files, e.g. `rm -f src/foo.rs`,
+    /// `git rm -r dist/` or `mv src/a.rs src/b.rs`. Commands that chain, pipe, redirect or
+    /// expand variables, or touch several files, stay plain command runs.
+    fn extract_file_command(&self, command: &str, worktree_path: &str) -> Option<ActionType> {
+        if command.contains([';', '&', '|', '<', '>', '$', '`', '\n']) {
+            return None;
+        }
+
+        let mut words = command.split_whitespace();
+        let program = match words.next()? {
+            "git" => match words.next()? {
+                "rm" => "rm",
+                "mv" => "mv",
+                _ => return None,
+            },
+            program => program,
+        };
+        let paths: Vec<String> = words
+            .filter(|word| !word.starts_with('-'))
+            .map(|word| word.trim_matches(|c| c == '\'' || c == '"'))
+            .map(|path| self.make_path_relative(path, worktree_path))
+            .collect();
+
+        match (program, paths.as_slice()) {
+            ("rm" | "unlink", [path]) => Some(ActionType::FileDelete { path: path.clone() }),
+            ("mv", [from, to]) => Some(ActionType::FileMove {
+                from: from.clone(),
+                to: to.clone(),
+            }),
+            _ => None,
+        }
+    }
+
     fn extract_action_type(
         &self,
         tool_name: &str,
         input: &serde_json::Value,
         worktree_path: &str,
     ) -> ActionType {
-        match tool_name.to_lowercase().as_str() {
-            "read" => {
+        self.extract_action_type_with_aliases(tool_name, input, worktree_path, tool_aliases())
+    }
+
+    fn extract_action_type_with_aliases(
+        &self,
+        tool_name: &str,
+        input: &serde_json::Value,
+        worktree_path: &str,
+        aliases: &ToolAliasMap,
+    ) -> ActionType {
+        let Some(tool) = aliases.resolve(tool_name) else {
+            return ActionType::Other {
+                description: format!("Tool: {}", tool_name),
+            };
+        };
+        match tool {
+            KnownTool::Read => {
                 if let Some(file_path) = input.get("file_path").and_then(|p| p.as_str()) {
                     ActionType::FileRead {
                         path: self.make_path_relative(file_path, worktree_path),
@@ -843,7 +2493,7 @@ impl ClaudeExecutor {
                     }
                 }
             }
-            "edit" | "write" | "multiedit" => {
+            KnownTool::Edit => {
                 if let Some(file_path) = input.get("file_path").and_then(|p| p.as_str()) {
                     ActionType::FileWrite {
                         path: self.make_path_relative(file_path, worktree_path),
@@ -858,18 +2508,19 @@ impl ClaudeExecutor {
                     }
                 }
             }
-            "bash" => {
+            KnownTool::Bash => {
                 if let Some(command) = input.get("command").and_then(|c| c.as_str()) {
-                    ActionType::CommandRun {
-                        command: command.to_string(),
-                    }
+                    self.extract_file_command(command, worktree_path)
+                        .unwrap_or_else(|| ActionType::CommandRun {
+                            command: command.to_string(),
+                        })
                 } else {
                     ActionType::Other {
                         description: "Command execution".to_string(),
                     }
                 }
             }
-            "grep" => {
+            KnownTool::Grep => {
                 if let Some(pattern) = input.get("pattern").and_then(|p| p.as_str()) {
                     ActionType::Search {
                         query: pattern.to_string(),
@@ -880,7 +2531,7 @@ impl ClaudeExecutor {
                     }
                 }
             }
-            "glob" => {
+            KnownTool::Glob => {
                 if let Some(pattern) = input.get("pattern").and_then(|p| p.as_str()) {
                     ActionType::Other {
                         description: format!("Find files: {}", pattern),
@@ -891,7 +2542,7 @@ impl ClaudeExecutor {
                     }
                 }
             }
-            "webfetch" => {
+            KnownTool::WebFetch => {
                 if let Some(url) = input.get("url").and_then(|u| u.as_str()) {
                     ActionType::WebFetch {
                         url: url.to_string(),
@@ -902,7 +2553,7 @@ impl ClaudeExecutor {
                     }
                 }
             }
-            "task" => {
+            KnownTool::Task => {
                 if let Some(description) = input.get("description").and_then(|d| d.as_str()) {
                     ActionType::TaskCreate {
                         description: description.to_string(),
@@ -917,7 +2568,26 @@ impl ClaudeExecutor {
                     }
                 }
             }
-            "exit_plan_mode" => {
+            KnownTool::Computer => {
+                if let Some(action) = input.get("action").and_then(|a| a.as_str()) {
+                    let coordinate = match input.get("coordinate").and_then(|c| c.as_array()) {
+                        Some(coordinate) if coordinate.len() == 2 => coordinate[0]
+                            .as_u64()
+                            .zip(coordinate[1].as_u64())
+                            .map(|(x, y)| (x as u32, y as u32)),
+                        _ => None,
+                    };
+                    ActionType::ComputerUse {
+                        action: action.to_string(),
+                        coordinate,
+                    }
+                } else {
+                    ActionType::Other {
+                        description: "Computer use".to_string(),
+                    }
+                }
+            }
+            KnownTool::ExitPlanMode => {
                 if let Some(plan) = input.get("plan").and_then(|p| p.as_str()) {
                     ActionType::PlanPresentation {
                         plan: plan.to_string(),
@@ -928,23 +2598,64 @@ impl ClaudeExecutor {
                     }
                 }
             }
-            _ => ActionType::Other {
-                description: format!("Tool: {}", tool_name),
-            },
         }
     }
 }
 
 #[async_trait]
 impl Executor for ClaudeFollowupExecutor {
+    fn cost_limit_usd(&self) -> Option<f64> {
+        self.max_task_cost_usd
+    }
+
+    fn web_policy_guard(&self, worktree_path: &str) -> Option<WebPolicyGuard> {
+        web_policy_guard(&self.web_policy, worktree_path)
+    }
+
+    fn progress_reporter(&self, worktree_path: &str) -> Option<ProgressReporter> {
+        let progress_tx = self.progress_tx.clone()?;
+        let executor = ClaudeExecutor::new();
+        let worktree_path = worktree_path.to_string();
+        Some(ProgressReporter::new(progress_tx, move |line| {
+            executor.normalize_line(line, &worktree_path)
+        }))
+    }
+
+    fn validate_config(&self) -> Result<(), ExecutorError> {
+        validate_claude_settings(
+            self.command_base.as_deref(),
+            self.auth_token.as_deref(),
+            &self.cloud_provider,
+            self.max_task_cost_usd,
+        )
+    }
+
     async fn spawn(
         &self,
-        _pool: &sqlx::SqlitePool,
-        _task_id: Uuid,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
         worktree_path: &str,
     ) -> Result<AsyncGroupChild, ExecutorError> {
+        ensure_binary_verified(task_id)?;
+        validate_context_files(&self.context_files, worktree_path).await?;
+
+        let task = Task::find_by_id(pool, task_id)
+            .await?
+            .ok_or(ExecutorError::TaskNotFound)?;
+        let prompt = self.build_prompt(task.project_id, task_id);
+
         // Use the new method with fallback support
-        self.try_spawn_with_fallback(worktree_path).await
+        let task_env = task_env_vars(pool, task_id).await?;
+        let api_key = project_api_key(pool, task_id).await;
+        self.try_spawn_with_fallback(
+            task_id,
+            worktree_path,
+            &prompt,
+            &task_env,
+            api_key.as_deref(),
+            &SpawnRetryPolicy::from_env(),
+        )
+        .await
     }
 
     fn normalize_logs(
@@ -953,14 +2664,30 @@ impl Executor for ClaudeFollowupExecutor {
         worktree_path: &str,
     ) -> Result<NormalizedConversation, String> {
         // Reuse the same logic as the main ClaudeExecutor
-        let main_executor = ClaudeExecutor::new();
+        let main_executor = ClaudeExecutor::new().with_web_policy(self.web_policy.clone());
         main_executor.normalize_logs(logs, worktree_path)
     }
+
+    fn normalize_logs_stream<'a>(
+        &'a self,
+        lines: BoxStream<'a, String>,
+        worktree_path: &'a str,
+    ) -> BoxStream<'a, NormalizedEntry> {
+        let main_executor = ClaudeExecutor::new().with_web_policy(self.web_policy.clone());
+        lines
+            .flat_map(move |line| stream::iter(main_executor.normalize_line(&line, worktree_path)))
+            .boxed()
+    }
+
+    fn normalize_stderr(&self, chunks: &[&str]) -> Option<Vec<NormalizedEntry>> {
+        Some(normalize_stderr_chunks(chunks))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_fixtures::{create_project, create_task, memory_pool};
 
     #[test]
     fn test_normalize_logs_ignores_result_type() {
@@ -976,52 +2703,789 @@ mod tests {
         // but NOT the result message
         assert_eq!(result.entries.len(), 3);
 
-        // Check that no entry contains "result"
-        for entry in &result.entries {
-            assert!(!entry.content.contains("result"));
-        }
+        // Check that no entry contains "result"
+        for entry in &result.entries {
+            assert!(!entry.content.contains("result"));
+        }
+
+        // Check that unknown JSON is still processed
+        assert!(result
+            .entries
+            .iter()
+            .any(|e| e.content.contains("Unrecognized JSON")));
+    }
+
+    #[test]
+    fn test_normalize_logs_carries_timestamps_forward() {
+        let executor = ClaudeExecutor::new();
+        let logs = r#"{"type":"system","subtype":"init","model":"claude-sonnet-4-20250514","timestamp":"2025-08-01T10:00:00Z"}
+{"type":"assistant","message":{"content":[{"type":"text","text":"Looking"},{"type":"tool_use","name":"Bash","input":{"command":"ls"}}]}}
+plain output line
+{"type":"assistant","message":{"content":[{"type":"text","text":"Done"}]},"timestamp":"2025-08-01T12:00:30+02:00"}
+{"type":"user","message":{"content":[{"type":"text","text":"Thanks"}]}}"#;
+
+        let result = executor.normalize_logs(logs, "/tmp/test-worktree").unwrap();
+        let timestamps: Vec<_> = result
+            .entries
+            .iter()
+            .map(|entry| entry.timestamp.as_deref())
+            .collect();
+
+        let start = Some("2025-08-01T10:00:00+00:00");
+        let end = Some("2025-08-01T10:00:30+00:00");
+        assert_eq!(timestamps, vec![start, start, start, start, end, end]);
+        assert!(timestamps.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(result.duration(), Some(chrono::Duration::seconds(30)));
+    }
+
+    #[test]
+    fn test_normalize_logs_without_timestamps_has_no_duration() {
+        let executor = ClaudeExecutor::new();
+        let logs = r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Hi"}]}}"#;
+
+        let result = executor.normalize_logs(logs, "/tmp/test-worktree").unwrap();
+
+        assert_eq!(result.entries[0].timestamp, None);
+        assert_eq!(result.duration(), None);
+    }
+
+    #[test]
+    fn test_normalize_logs_reads_result_stats() {
+        let executor = ClaudeExecutor::new();
+        let logs = r#"{"type":"result","subtype":"success","num_turns":1,"result":"First"}
+{"type":"result","subtype":"success","is_error":false,"duration_ms":6059,"num_turns":4,"result":"Fixed the tests","total_cost_usd":0.0421,"usage":{"input_tokens":1200,"output_tokens":350}}"#;
+
+        let result = executor.normalize_logs(logs, "/tmp/test-worktree").unwrap();
+        assert!(result.entries.is_empty());
+        assert_eq!(result.summary.as_deref(), Some("Fixed the tests"));
+        assert_eq!(
+            result.stats,
+            Some(ExecutionStats {
+                num_turns: Some(4),
+                duration_ms: Some(6059),
+                total_cost_usd: Some(0.0421),
+                input_tokens: Some(1200),
+                output_tokens: Some(350),
+            })
+        );
+
+        // Missing fields are left out rather than failing the parse
+        let result = executor
+            .normalize_logs(r#"{"type":"result","subtype":"error_max_turns"}"#, "/tmp")
+            .unwrap();
+        assert_eq!(result.summary, None);
+        assert_eq!(result.stats, Some(ExecutionStats::default()));
+    }
+
+    #[test]
+    fn test_normalize_logs_reads_tool_result() {
+        let executor = ClaudeExecutor::new();
+        let logs = r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_01","content":[{"type":"text","text":"running 3 tests"},{"type":"text","text":"test result: ok. 3 passed"}]}]}}"#;
+
+        let result = executor.normalize_logs(logs, "/tmp/test-worktree").unwrap();
+
+        assert_eq!(result.entries.len(), 1);
+        let entry = &result.entries[0];
+        assert_eq!(
+            entry.entry_type,
+            NormalizedEntryType::ToolResult {
+                tool_use_id: "toolu_01".to_string(),
+            }
+        );
+        assert_eq!(entry.content, "running 3 tests\ntest result: ok. 3 passed");
+        assert_eq!(
+            entry.metadata.as_ref().unwrap()["summary"],
+            "↩ running 3 tests test result: ok. 3 passed"
+        );
+    }
+
+    #[test]
+    fn test_normalize_logs_reads_tool_error() {
+        let executor = ClaudeExecutor::new();
+        let logs = r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_02","is_error":true,"content":"File does not exist."}]}}"#;
+
+        let result = executor.normalize_logs(logs, "/tmp/test-worktree").unwrap();
+
+        assert_eq!(result.entries.len(), 1);
+        let entry = &result.entries[0];
+        assert_eq!(
+            entry.entry_type,
+            NormalizedEntryType::ToolError {
+                tool_use_id: "toolu_02".to_string(),
+                exit_code: None,
+            }
+        );
+        assert_eq!(entry.content, "File does not exist.");
+        assert_eq!(
+            entry.metadata.as_ref().unwrap()["summary"],
+            "Error: File does not exist."
+        );
+    }
+
+    #[test]
+    fn test_normalize_logs_bash_command_exiting_1_is_a_tool_error() {
+        let executor = ClaudeExecutor::new();
+        let logs = r#"{"type":"assistant","message":{"content":[{"type":"tool_use","id":"toolu_03","name":"Bash","input":{"command":"cargo build"}}]}}
+{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_03","is_error":true,"content":"Exit code 1\nerror[E0425]: cannot find value `x` in this scope"}]}}"#;
+
+        let result = executor.normalize_logs(logs, "/tmp/test-worktree").unwrap();
+
+        assert_eq!(result.entries.len(), 2);
+        let entry = &result.entries[1];
+        assert_eq!(
+            entry.entry_type,
+            NormalizedEntryType::ToolError {
+                tool_use_id: "toolu_03".to_string(),
+                exit_code: Some(1),
+            }
+        );
+        assert_eq!(
+            entry.content,
+            "error[E0425]: cannot find value `x` in this scope"
+        );
+        assert_eq!(
+            entry.metadata.as_ref().unwrap()["summary"],
+            "Error: error[E0425]: cannot find value `x` in this scope"
+        );
+        assert!(result.has_errors());
+    }
+
+    #[test]
+    fn test_normalize_logs_blocks_denied_web_fetch() {
+        let executor = ClaudeExecutor::new().with_web_policy(WebPolicy {
+            allowlist: None,
+            denylist: vec!["pastebin.com".to_string()],
+        });
+        let logs = r#"{"type":"assistant","message":{"content":[{"type":"tool_use","id":"toolu_05","name":"WebFetch","input":{"url":"https://docs.rs/tokio"}}]}}
+{"type":"assistant","message":{"content":[{"type":"tool_use","id":"toolu_06","name":"WebFetch","input":{"url":"https://pastebin.com/raw/abc"}}]}}"#;
+
+        let result = executor.normalize_logs(logs, "/tmp/test-worktree").unwrap();
+
+        assert_eq!(result.entries.len(), 2);
+        assert!(matches!(
+            &result.entries[0].entry_type,
+            NormalizedEntryType::ToolUse {
+                action_type: ActionType::WebFetch { url },
+                ..
+            } if url == "https://docs.rs/tokio"
+        ));
+        assert_eq!(
+            result.entries[1].entry_type,
+            NormalizedEntryType::ToolError {
+                tool_use_id: "policy".to_string(),
+                exit_code: None,
+            }
+        );
+        assert_eq!(
+            result.entries[1].content,
+            "Blocked by project policy: pastebin.com"
+        );
+        // Without a policy the fetch is shown as it was made
+        let unrestricted = ClaudeExecutor::new()
+            .normalize_logs(logs, "/tmp/test-worktree")
+            .unwrap();
+        assert!(matches!(
+            unrestricted.entries[1].entry_type,
+            NormalizedEntryType::ToolUse { .. }
+        ));
+    }
+
+    #[test]
+    fn test_normalize_logs_bash_command_exiting_0_is_a_tool_result() {
+        let executor = ClaudeExecutor::new();
+        let logs = r#"{"type":"assistant","message":{"content":[{"type":"tool_use","id":"toolu_04","name":"Bash","input":{"command":"cargo build"}}]}}
+{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_04","is_error":false,"content":"Finished dev profile"}]}}"#;
+
+        let result = executor.normalize_logs(logs, "/tmp/test-worktree").unwrap();
+
+        assert_eq!(result.entries.len(), 2);
+        assert_eq!(
+            result.entries[1].entry_type,
+            NormalizedEntryType::ToolResult {
+                tool_use_id: "toolu_04".to_string(),
+            }
+        );
+        assert!(!result.has_errors());
+    }
+
+    #[test]
+    fn test_normalize_logs_truncates_long_tool_result() {
+        let executor = ClaudeExecutor::new();
+        let output = "é".repeat(MAX_TOOL_RESULT_CHARS + 100);
+        let logs = serde_json::json!({
+            "type": "user",
+            "message": {
+                "role": "user",
+                "content": [{"type": "tool_result", "tool_use_id": "toolu_03", "content": output}]
+            }
+        })
+        .to_string();
+
+        let result = executor.normalize_logs(&logs, "/tmp/test-worktree").unwrap();
+
+        let entry = &result.entries[0];
+        assert_eq!(entry.content.chars().count(), MAX_TOOL_RESULT_CHARS + 1);
+        assert!(entry.content.ends_with("é…"));
+        let summary = entry.metadata.as_ref().unwrap()["summary"].as_str().unwrap();
+        assert_eq!(
+            summary,
+            format!("↩ {}…", "é".repeat(TOOL_RESULT_PREVIEW_CHARS))
+        );
+        // The raw block is not kept alongside the truncated output
+        assert!(!entry.metadata.as_ref().unwrap().to_string().contains(&output));
+    }
+
+    #[test]
+    fn test_build_claude_command() {
+        // Test normal mode
+        let cmd = build_claude_command("claude-code", false, None);
+        assert_eq!(cmd, "claude-code -p --dangerously-skip-permissions --verbose --output-format=stream-json");
+        
+        // Test plan mode
+        let cmd = build_claude_command("claude-code", true, None);
+        assert_eq!(cmd, "claude-code -p --permission-mode=plan --verbose --output-format=stream-json");
+        
+        // Test with npx
+        let cmd = build_claude_command("npx -y @anthropic-ai/claude-code@latest", false, None);
+        assert_eq!(cmd, "npx -y @anthropic-ai/claude-code@latest -p --dangerously-skip-permissions --verbose --output-format=stream-json");
+    }
+
+    #[test]
+    fn test_build_claude_command_with_model() {
+        let cmd = build_claude_command("claude", false, Some("claude-haiku-4"));
+        assert_eq!(
+            cmd,
+            "claude -p --dangerously-skip-permissions --verbose --output-format=stream-json --model claude-haiku-4"
+        );
+
+        let cmd = build_claude_command("claude", true, Some("claude-opus-4"));
+        assert_eq!(
+            cmd,
+            "claude -p --permission-mode=plan --verbose --output-format=stream-json --model claude-opus-4"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_followup_command_selects_its_model() {
+        // Follow-ups append --resume after the model
+        let executor = ClaudeFollowupExecutor::new("session-1".to_string(), "next".to_string())
+            .with_model("claude-sonnet-4");
+        let command = executor.get_command().await;
+        assert!(
+            command.ends_with("--output-format=stream-json --model claude-sonnet-4 --resume=session-1"),
+            "{}",
+            command
+        );
+
+        let executor = ClaudeFollowupExecutor::new("session-1".to_string(), "next".to_string());
+        let command = executor.get_command().await;
+        assert!(!command.contains("--model"), "{}", command);
+        assert!(
+            command.ends_with("--output-format=stream-json --resume=session-1"),
+            "{}",
+            command
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_claude_command_fallback() {
+        // This test assumes no local claude-code is installed
+        // and no config file exists
+        let cmd = get_claude_command(false, None).await;
+        assert!(cmd.contains("npx"));
+        assert!(cmd.contains("@anthropic-ai/claude-code@latest"));
+        assert!(cmd.contains("--dangerously-skip-permissions"));
+    }
+
+    #[test]
+    fn test_create_watchkill_script() {
+        let command = "claude-code -p --permission-mode=plan";
+        let script = create_watchkill_script(command, true);
+        
+        assert!(script.contains("#!/usr/bin/env bash"));
+        assert!(script.contains("set -euo pipefail"));
+        assert!(script.contains(command));
+        assert!(script.contains("Claude requested permissions to use exit_plan_mode"));
+    }
+
+    #[test]
+    fn test_create_watchkill_script_without_pipestatus() {
+        let command = "claude-code -p --permission-mode=plan";
+        let script = build_watchkill_script(command, false, true);
+
+        assert!(script.starts_with("#!/bin/sh"));
+        assert!(!script.contains("PIPESTATUS"));
+        assert!(!script.contains("pipefail"));
+        assert!(script.contains(command));
+        assert!(script.contains("Claude requested permissions to use exit_plan_mode"));
+    }
+
+    #[test]
+    fn test_watchkill_script_strict_mode() {
+        let command = "claude-code -p --permission-mode=plan";
+
+        let strict = build_watchkill_script(command, true, true);
+        assert!(strict.contains("set -euo pipefail"));
+        assert!(!strict.contains("command not found"));
+
+        let permissive = build_watchkill_script(command, true, false);
+        assert!(!permissive.contains("set -euo pipefail"));
+        assert!(permissive.contains("command not found"));
+        assert!(permissive.contains(command));
+
+        let permissive_posix = build_watchkill_script(command, false, false);
+        assert!(permissive_posix.starts_with("#!/bin/sh"));
+        assert!(!permissive_posix.contains("set -eu"));
+        assert!(permissive_posix.contains("command not found"));
+    }
+
+    #[tokio::test]
+    async fn test_custom_command_in_plan_mode_uses_watchkill() {
+        let command = build_claude_command("/opt/claude/bin/claude", true, Some("sonnet"));
+        assert!(command.starts_with("/opt/claude/bin/claude -p --permission-mode=plan"));
+        assert!(command.ends_with("--model sonnet"));
+
+        let executor = ClaudeExecutor::with_command("ClaudePlan".to_string(), command.clone());
+        assert_eq!(executor.get_command(None).await, command);
+
+        let executor = executor.with_plan_mode(true);
+        let script = executor.get_command(None).await;
+        assert!(script.contains(&command));
+        assert!(script.contains("Claude requested permissions to use exit_plan_mode"));
+
+        let followup = ClaudeFollowupExecutor::with_command(
+            "sess-1".to_string(),
+            "continue".to_string(),
+            "ClaudePlan".to_string(),
+            command.clone(),
+        )
+        .with_plan_mode(true);
+        let script = followup.get_command().await;
+        assert!(script.contains(&format!("{} --resume=sess-1", command)));
+        assert!(script.contains("Claude requested permissions to use exit_plan_mode"));
+    }
+
+    #[test]
+    fn test_auth_token_replaces_api_key() {
+        let env_of = |command: &Command, key: &str| {
+            command
+                .as_std()
+                .get_envs()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.map(|v| v.to_string_lossy().to_string()))
+        };
+
+        let mut command = Command::new("claude");
+        apply_auth_env(&mut command, Some("token-123"));
+        assert_eq!(
+            env_of(&command, "ANTHROPIC_AUTH_TOKEN"),
+            Some(Some("token-123".to_string()))
+        );
+        // `None` means the variable is removed from the inherited environment
+        assert_eq!(env_of(&command, "ANTHROPIC_API_KEY"), Some(None));
+
+        let mut command = Command::new("claude");
+        apply_auth_env(&mut command, None);
+        assert_eq!(command.as_std().get_envs().count(), 0);
+    }
+
+    #[test]
+    fn test_normalize_stderr_keeps_errors_and_drops_noise() {
+        let chunks = [
+            "(node:4242) [DEP0040] DeprecationWarning: The `punycode` module is deprecated.\n(Use `node --trace-deprecation ...` to show where the warning was created)",
+            "npm warn exec The following package was not found and will be installed: @anthropic-ai/claude-code@1.0.0\nadded 3 packages in 2s",
+            "Error: Invalid API key \u{b7} Please run /login\n    at main (file:///cli.js:10:5)",
+        ];
+
+        let entries = ClaudeExecutor::new().normalize_stderr(&chunks).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(matches!(
+            entries[0].entry_type,
+            NormalizedEntryType::SystemMessage
+        ));
+        assert_eq!(
+            entries[0].content,
+            "Error: Invalid API key \u{b7} Please run /login\n    at main (file:///cli.js:10:5)"
+        );
+        assert_eq!(
+            entries[0].metadata,
+            Some(serde_json::json!({ "source": "stderr" }))
+        );
+    }
+
+    #[test]
+    fn test_normalize_logs_counts_and_hides_debug_entries() {
+        let logs = r#"{"type":"debug","message":"Loaded 3 MCP servers"}
+{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Done"}]},"session_id":"abc123"}
+{"type":"debug","message":"Compacting conversation"}"#;
+
+        let conversation = ClaudeExecutor::new()
+            .normalize_logs(logs, "/tmp/test-worktree")
+            .unwrap();
+        assert_eq!(conversation.metadata.debug_entry_count, 2);
+        assert_eq!(conversation.entries.len(), 1);
+        assert!(matches!(
+            conversation.entries[0].entry_type,
+            NormalizedEntryType::AssistantMessage
+        ));
+
+        let mut executor = ClaudeExecutor::new();
+        executor.include_debug = true;
+        let conversation = executor.normalize_logs(logs, "/tmp/test-worktree").unwrap();
+        assert_eq!(conversation.metadata.debug_entry_count, 2);
+        assert_eq!(conversation.entries.len(), 3);
+        assert!(matches!(
+            conversation.entries[0].entry_type,
+            NormalizedEntryType::DebugTrace
+        ));
+        assert_eq!(conversation.entries[0].content, "Loaded 3 MCP servers");
+    }
+
+    #[test]
+    fn test_sanitized_env_keeps_passthrough_vars() {
+        let parent = vec![
+            ("PATH".to_string(), "/usr/bin".to_string()),
+            ("CARGO_HOME".to_string(), "/home/dev/.cargo".to_string()),
+            ("GOPATH".to_string(), "/home/dev/go".to_string()),
+            ("ANTHROPIC_API_KEY".to_string(), "sk-ant-test".to_string()),
+        ];
+
+        let env = sanitized_env(parent.into_iter(), &["CARGO_HOME".to_string()]);
+        let names: Vec<&str> = env.iter().map(|(name, _)| name.as_str()).collect();
+        assert!(names.contains(&"CARGO_HOME"));
+        assert!(!names.contains(&"GOPATH"));
+        assert!(names.contains(&"PATH"));
+        assert!(names.contains(&"ANTHROPIC_API_KEY"));
+    }
+
+    #[test]
+    fn test_validate_config_rejects_invalid_settings() {
+        let mut executor = ClaudeExecutor::new();
+        executor.cloud_provider = CloudProvider::Anthropic;
+        executor.max_task_cost_usd = Some(5.0);
+        assert!(executor.validate_config().is_ok());
+
+        let mut negative_limit = executor.clone();
+        negative_limit.max_task_cost_usd = Some(-1.0);
+        assert!(matches!(
+            negative_limit.validate_config(),
+            Err(ExecutorError::ConfigError { .. })
+        ));
+
+        let mut missing_project = executor.clone();
+        missing_project.cloud_provider =
+            CloudProvider::Vertex(String::new(), "us-east5".to_string());
+        assert!(matches!(
+            missing_project.validate_config(),
+            Err(ExecutorError::ConfigError { .. })
+        ));
+
+        let mut blank_token = executor.clone();
+        blank_token.auth_token = Some("  ".to_string());
+        assert!(matches!(
+            blank_token.validate_config(),
+            Err(ExecutorError::ConfigError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_binary_manifest_check() {
+        let binary = ClaudeBinary {
+            path: "/usr/local/bin/claude-code".to_string(),
+            version: "1.0.51".to_string(),
+            sha256: "ab12".to_string(),
+        };
+
+        let trusted = HashMap::from([("1.0.51".to_string(), "AB12".to_string())]);
+        assert!(binary.check_manifest(&trusted).is_ok());
+
+        let tampered = HashMap::from([("1.0.51".to_string(), "cd34".to_string())]);
+        assert!(binary.check_manifest(&tampered).is_err());
+
+        let other_version = HashMap::from([("1.0.50".to_string(), "ab12".to_string())]);
+        assert!(binary.check_manifest(&other_version).is_err());
+    }
+
+    #[test]
+    fn test_stdin_format_encode() {
+        let project_id = Uuid::parse_str("11111111-1111-4111-8111-111111111111").unwrap();
+        let task_id = Uuid::parse_str("22222222-2222-4222-8222-222222222222").unwrap();
+        let prompt = "Task title: Fix \"quotes\"\nTask description: line two";
+
+        assert_eq!(
+            StdinFormat::PlainText.encode(prompt, project_id, task_id),
+            prompt
+        );
+        assert_eq!(
+            StdinFormat::StructuredJson.encode(prompt, project_id, task_id),
+            r#"{"prompt":"Task title: Fix \"quotes\"\nTask description: line two","context":{"project_id":"11111111-1111-4111-8111-111111111111","task_id":"22222222-2222-4222-8222-222222222222"}}"#
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_structured_stdin_reaches_the_cli_unchanged() {
+        let worktree = tempfile::tempdir().unwrap();
+        let stdin = StdinFormat::StructuredJson.encode("Say hi", Uuid::new_v4(), Uuid::new_v4());
+        let (pool, task_id) = pool_with_task("Say hi").await;
+
+        // `cat` echoes its stdin, so stdout holds exactly the bytes the CLI would read
+        let mut child = ClaudeExecutor::new()
+            .try_spawn_with_command(
+                &pool,
+                task_id,
+                worktree.path().to_str().unwrap(),
+                &stdin,
+                "cat",
+            )
+            .await
+            .unwrap();
+
+        let mut stdout = Vec::new();
+        tokio::io::AsyncReadExt::read_to_end(child.inner().stdout.as_mut().unwrap(), &mut stdout)
+            .await
+            .unwrap();
+        assert!(child.wait().await.unwrap().success());
+        assert_eq!(stdout, stdin.into_bytes());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_task_env_vars_reach_the_cli() {
+        std::env::set_var(crate::utils::encryption::ENCRYPTION_KEY_ENV, "test secret");
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let task_id = create_task(&pool, project_id, "t").await;
+        Task::set_env_var(&pool, task_id, "VIBE_TEST_SECRET", "s3cret value")
+            .await
+            .unwrap();
+
+        let stored: Vec<u8> = sqlx::query_scalar("SELECT value FROM task_env_vars")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(!String::from_utf8_lossy(&stored).contains("s3cret"));
+
+        let worktree = tempfile::tempdir().unwrap();
+        let mut child = ClaudeExecutor::new()
+            .try_spawn_with_command(
+                &pool,
+                task_id,
+                worktree.path().to_str().unwrap(),
+                "",
+                "printenv VIBE_TEST_SECRET",
+            )
+            .await
+            .unwrap();
+
+        let mut stdout = String::new();
+        tokio::io::AsyncReadExt::read_to_string(
+            child.inner().stdout.as_mut().unwrap(),
+            &mut stdout,
+        )
+        .await
+        .unwrap();
+        assert!(child.wait().await.unwrap().success());
+        assert_eq!(stdout, "s3cret value\n");
+    }
+
+    #[tokio::test]
+    async fn test_undecryptable_task_env_vars_fail_the_spawn() {
+        std::env::set_var(crate::utils::encryption::ENCRYPTION_KEY_ENV, "test secret");
+        let (pool, task_id) = pool_with_task("t").await;
+        sqlx::query("INSERT INTO task_env_vars (task_id, key, value) VALUES ($1, 'K', $2)")
+            .bind(task_id)
+            .bind(b"not sealed under the test secret".to_vec())
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let worktree = tempfile::tempdir().unwrap();
+        let result = ClaudeExecutor::new()
+            .try_spawn_with_command(
+                &pool,
+                task_id,
+                worktree.path().to_str().unwrap(),
+                "",
+                "true",
+            )
+            .await;
+
+        match result {
+            Err(ExecutorError::ConfigError { message }) => {
+                assert!(message.contains(&task_id.to_string()), "{}", message);
+            }
+            other => panic!("expected a configuration error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_project_api_key_reaches_the_cli() {
+        std::env::set_var(crate::utils::encryption::ENCRYPTION_KEY_ENV, "test secret");
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let task_id = create_task(&pool, project_id, "t").await;
+        ProjectApiKeyStore::set(&pool, project_id, "claude", "sk-ant-project-key")
+            .await
+            .unwrap();
+
+        // The project key also wins over an explicit auth token
+        let mut executor = ClaudeExecutor::new();
+        executor.auth_token = Some("token-123".to_string());
+        let worktree = tempfile::tempdir().unwrap();
+        let mut child = executor
+            .try_spawn_with_command(
+                &pool,
+                task_id,
+                worktree.path().to_str().unwrap(),
+                "",
+                "printenv ANTHROPIC_API_KEY && ! printenv ANTHROPIC_AUTH_TOKEN",
+            )
+            .await
+            .unwrap();
+
+        let mut stdout = String::new();
+        tokio::io::AsyncReadExt::read_to_string(
+            child.inner().stdout.as_mut().unwrap(),
+            &mut stdout,
+        )
+        .await
+        .unwrap();
+        assert!(child.wait().await.unwrap().success());
+        assert_eq!(stdout, "sk-ant-project-key\n");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_spawn_emits_executor_spawned_event() {
+        let worktree = tempfile::tempdir().unwrap();
+        let worktree_path = worktree.path().to_str().unwrap();
+        let (pool, task_id) = pool_with_task("Spawn").await;
+
+        let mut child = ClaudeExecutor::new()
+            .try_spawn_with_command(&pool, task_id, worktree_path, "", "true")
+            .await
+            .unwrap();
+        assert!(child.wait().await.unwrap().success());
+
+        assert!(logs_contain("executor_events"));
+        assert!(logs_contain("event=\"executor_spawned\""));
+        assert!(logs_contain(&format!("task_id={}", task_id)));
+        assert!(logs_contain("executor_type=\"Claude\""));
+        assert!(!logs_contain("executor_spawn_failed"));
+    }
+
+    #[test]
+    fn test_cost_tracker_counts_each_message_once() {
+        let mut tracker = CostTracker::default();
+        let usage = r#"{"input_tokens":1000000,"output_tokens":100000,"cache_creation_input_tokens":0,"cache_read_input_tokens":0}"#;
+        let line = |id: &str, text: &str| {
+            format!(
+                r#"{{"type":"assistant","message":{{"id":"{}","model":"claude-sonnet-4-20250514","content":[{{"type":"text","text":"{}"}}],"usage":{}}}}}"#,
+                id, text, usage
+            )
+        };
+
+        assert_eq!(
+            tracker.observe(r#"{"type":"system","subtype":"init"}"#),
+            0.0
+        );
+        // $3 for input plus $1.50 for output at Sonnet prices
+        assert!((tracker.observe(&line("msg_1", "first block")) - 4.5).abs() < 1e-9);
+        assert!((tracker.observe(&line("msg_1", "second block")) - 4.5).abs() < 1e-9);
+        assert!((tracker.observe(&line("msg_2", "next message")) - 9.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cloud_provider_from_vars() {
+        let provider = |vars: &[(&str, &str)]| {
+            let vars: Vec<(String, String)> = vars
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            CloudProvider::from_vars(|key| {
+                vars.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone())
+            })
+        };
+
+        assert_eq!(provider(&[]), CloudProvider::Anthropic);
+        assert_eq!(
+            provider(&[
+                ("CLAUDE_CODE_USE_BEDROCK", "1"),
+                ("AWS_REGION", "eu-west-1")
+            ]),
+            CloudProvider::Bedrock("eu-west-1".to_string())
+        );
+        assert_eq!(
+            provider(&[
+                ("CLAUDE_CODE_USE_VERTEX", "1"),
+                ("GOOGLE_CLOUD_PROJECT", "my-project"),
+                ("CLOUD_ML_REGION", "europe-west4"),
+            ]),
+            CloudProvider::Vertex("my-project".to_string(), "europe-west4".to_string())
+        );
+        assert_eq!(
+            provider(&[("CLAUDE_CODE_USE_BEDROCK", "0")]),
+            CloudProvider::Anthropic
+        );
+    }
+
+    #[test]
+    fn test_cloud_provider_env() {
+        let mut command = Command::new("claude");
+        CloudProvider::Vertex("my-project".to_string(), "us-east5".to_string())
+            .apply_env(&mut command);
+        let envs: Vec<(String, Option<String>)> = command
+            .as_std()
+            .get_envs()
+            .map(|(k, v)| {
+                (
+                    k.to_string_lossy().to_string(),
+                    v.map(|v| v.to_string_lossy().to_string()),
+                )
+            })
+            .collect();
 
-        // Check that unknown JSON is still processed
-        assert!(result
-            .entries
-            .iter()
-            .any(|e| e.content.contains("Unrecognized JSON")));
+        assert!(envs.contains(&("CLAUDE_CODE_USE_VERTEX".to_string(), Some("1".to_string()))));
+        assert!(envs.contains(&(
+            "GOOGLE_CLOUD_PROJECT".to_string(),
+            Some("my-project".to_string())
+        )));
+        assert!(envs.contains(&(
+            "GOOGLE_CLOUD_LOCATION".to_string(),
+            Some("us-east5".to_string())
+        )));
+        assert!(envs.contains(&("CLAUDE_CODE_USE_BEDROCK".to_string(), None)));
     }
 
     #[test]
-    fn test_build_claude_command() {
-        // Test normal mode
-        let cmd = build_claude_command("claude-code", false);
-        assert_eq!(cmd, "claude-code -p --dangerously-skip-permissions --verbose --output-format=stream-json");
-        
-        // Test plan mode
-        let cmd = build_claude_command("claude-code", true);
-        assert_eq!(cmd, "claude-code -p --permission-mode=plan --verbose --output-format=stream-json");
-        
-        // Test with npx
-        let cmd = build_claude_command("npx -y @anthropic-ai/claude-code@latest", false);
-        assert_eq!(cmd, "npx -y @anthropic-ai/claude-code@latest -p --dangerously-skip-permissions --verbose --output-format=stream-json");
+    fn test_with_output_dir_flag() {
+        let command = "claude -p --output-format=stream-json".to_string();
+        assert_eq!(with_output_dir_flag(command.clone(), None), command);
+        assert_eq!(
+            with_output_dir_flag(command, Some(Path::new("/tmp/worktree/artifacts"))),
+            "claude -p --output-format=stream-json --output-dir /tmp/worktree/artifacts"
+        );
     }
 
     #[tokio::test]
-    async fn test_get_claude_command_fallback() {
-        // This test assumes no local claude-code is installed
-        // and no config file exists
-        let cmd = get_claude_command(false).await;
-        assert!(cmd.contains("npx"));
-        assert!(cmd.contains("@anthropic-ai/claude-code@latest"));
-        assert!(cmd.contains("--dangerously-skip-permissions"));
-    }
+    async fn test_mcp_config_flag_is_appended() {
+        let executor = ClaudeExecutor::with_command(
+            "Claude".to_string(),
+            "claude -p --output-format=stream-json".to_string(),
+        );
+        assert!(!executor.get_command(None).await.contains("--mcp-config"));
 
-    #[test]
-    fn test_create_watchkill_script() {
-        let command = "claude-code -p --permission-mode=plan";
-        let script = create_watchkill_script(command);
-        
-        assert!(script.contains("#!/usr/bin/env bash"));
-        assert!(script.contains("set -euo pipefail"));
-        assert!(script.contains(command));
-        assert!(script.contains("Claude requested permissions to use exit_plan_mode"));
+        let executor = executor.with_mcp_config("/tmp/worktree/.vibe-kanban/mcp-config.json");
+        assert_eq!(
+            executor
+                .get_command(Some(Path::new("/tmp/worktree/artifacts")))
+                .await,
+            "claude -p --output-format=stream-json --output-dir /tmp/worktree/artifacts \
+             --mcp-config /tmp/worktree/.vibe-kanban/mcp-config.json"
+        );
     }
 
     #[test]
@@ -1041,6 +3505,32 @@ mod tests {
         assert_eq!(result, "src/main.rs");
     }
 
+    #[test]
+    fn test_make_path_relative_windows_paths() {
+        let executor = ClaudeExecutor::new();
+
+        assert_eq!(
+            executor.make_path_relative(r"C:\Users\foo\repo\src\main.rs", r"C:\Users\foo\repo"),
+            "src/main.rs"
+        );
+        // Claude's forward slashes against a backslashed worktree, with the drive letter and
+        // directories in a different case
+        assert_eq!(
+            executor.make_path_relative("c:/users/Foo/repo/src/main.rs", r"C:\Users\foo\repo\"),
+            "src/main.rs"
+        );
+
+        // Paths outside the worktree stay absolute
+        assert_eq!(
+            executor.make_path_relative(r"D:\Users\foo\repo\src\main.rs", r"C:\Users\foo\repo"),
+            "D:/Users/foo/repo/src/main.rs"
+        );
+        assert_eq!(
+            executor.make_path_relative(r"C:\Users\foo\repository\main.rs", r"C:\Users\foo\repo"),
+            "C:/Users/foo/repository/main.rs"
+        );
+    }
+
     #[test]
     fn test_todo_tool_content_extraction() {
         let executor = ClaudeExecutor::new();
@@ -1189,4 +3679,577 @@ mod tests {
 
         assert_eq!(result, "List directory: `components`");
     }
+
+    #[test]
+    fn test_extract_action_type_ignores_tool_name_case() {
+        let executor = ClaudeExecutor::new();
+        let worktree = "/tmp/test-worktree";
+        let input = serde_json::json!({
+            "file_path": "/tmp/test-worktree/src/main.rs",
+            "command": "cargo test",
+            "url": "https://example.com",
+            "plan": "1. Do the thing",
+        });
+
+        for tool_name in ["Read", "READ", "read"] {
+            let action = executor.extract_action_type(tool_name, &input, worktree);
+            assert!(
+                matches!(&action, ActionType::FileRead { path } if path == "src/main.rs"),
+                "{} produced {:?}",
+                tool_name,
+                action
+            );
+        }
+
+        for tool_name in ["Edit", "EDIT", "edit"] {
+            let action = executor.extract_action_type(tool_name, &input, worktree);
+            assert!(
+                matches!(&action, ActionType::FileWrite { path } if path == "src/main.rs"),
+                "{} produced {:?}",
+                tool_name,
+                action
+            );
+        }
+
+        for tool_name in ["Bash", "BASH"] {
+            let action = executor.extract_action_type(tool_name, &input, worktree);
+            assert!(
+                matches!(&action, ActionType::CommandRun { command } if command == "cargo test"),
+                "{} produced {:?}",
+                tool_name,
+                action
+            );
+        }
+
+        for tool_name in ["WebFetch", "WEBFETCH", "webfetch"] {
+            let action = executor.extract_action_type(tool_name, &input, worktree);
+            assert!(
+                matches!(&action, ActionType::WebFetch { url } if url == "https://example.com"),
+                "{} produced {:?}",
+                tool_name,
+                action
+            );
+        }
+
+        for tool_name in ["exit_plan_mode", "EXIT_PLAN_MODE"] {
+            let action = executor.extract_action_type(tool_name, &input, worktree);
+            assert!(
+                matches!(&action, ActionType::PlanPresentation { plan } if plan == "1. Do the thing"),
+                "{} produced {:?}",
+                tool_name,
+                action
+            );
+        }
+    }
+
+    #[test]
+    fn test_extract_action_type_resolves_tool_aliases() {
+        let executor = ClaudeExecutor::new();
+        let aliases =
+            ToolAliasMap::from_json(r#"{"my_bash_wrapper": "bash", "File_Reader": "read"}"#)
+                .unwrap();
+        let input = serde_json::json!({
+            "command": "cargo test",
+            "file_path": "/tmp/test-worktree/src/main.rs",
+        });
+
+        let action = executor.extract_action_type_with_aliases(
+            "my_bash_wrapper",
+            &input,
+            "/tmp/test-worktree",
+            &aliases,
+        );
+        assert_eq!(
+            action,
+            ActionType::CommandRun {
+                command: "cargo test".to_string()
+            }
+        );
+
+        let action = executor.extract_action_type_with_aliases(
+            "file_reader",
+            &input,
+            "/tmp/test-worktree",
+            &aliases,
+        );
+        assert_eq!(
+            action,
+            ActionType::FileRead {
+                path: "src/main.rs".to_string()
+            }
+        );
+
+        // Built-in names still resolve, and unknown tools stay generic
+        assert_eq!(aliases.resolve("MultiEdit"), Some(KnownTool::Edit));
+        assert_eq!(aliases.resolve("shell_exec"), None);
+        assert!(ToolAliasMap::from_json(r#"{"shell_exec": "teleport"}"#).is_err());
+    }
+
+    #[test]
+    fn test_extract_action_type_recognizes_file_commands() {
+        let executor = ClaudeExecutor::new();
+        let worktree = "/tmp/test-worktree";
+        let bash = |command: &str| {
+            let input = serde_json::json!({ "command": command });
+            executor.extract_action_type("Bash", &input, worktree)
+        };
+
+        assert_eq!(
+            bash("rm src/foo.rs"),
+            ActionType::FileDelete {
+                path: "src/foo.rs".to_string()
+            }
+        );
+        assert_eq!(
+            bash("mv src/a.rs src/b.rs"),
+            ActionType::FileMove {
+                from: "src/a.rs".to_string(),
+                to: "src/b.rs".to_string()
+            }
+        );
+        assert_eq!(
+            bash("git rm -r dist/"),
+            ActionType::FileDelete {
+                path: "dist/".to_string()
+            }
+        );
+        assert_eq!(
+            bash("rm -rf /tmp/test-worktree/target"),
+            ActionType::FileDelete {
+                path: "target".to_string()
+            }
+        );
+
+        // Anything beyond a single plain delete or move stays a command run
+        for command in [
+            "rm a.rs b.rs",
+            "rm foo.rs && cargo build",
+            "mv a.rs",
+            "rmdir old",
+        ] {
+            assert!(
+                matches!(bash(command), ActionType::CommandRun { .. }),
+                "{} was not a command run",
+                command
+            );
+        }
+
+        let input = serde_json::json!({ "command": "mv src/a.rs src/b.rs" });
+        let action = bash("mv src/a.rs src/b.rs");
+        assert_eq!(
+            executor.generate_concise_content("Bash", &input, &action, worktree),
+            "`src/a.rs` → `src/b.rs`"
+        );
+    }
+
+    #[test]
+    fn test_normalize_plain_line() {
+        let entry = normalize_plain_line("+ claude -p --permission-mode=plan").unwrap();
+        assert!(matches!(entry.entry_type, NormalizedEntryType::DebugTrace));
+        assert_eq!(entry.content, "+ claude -p --permission-mode=plan");
+
+        let entry = normalize_plain_line("[12:34:56.789] plan ready").unwrap();
+        assert!(matches!(entry.entry_type, NormalizedEntryType::Timing));
+        assert_eq!(entry.content, "[12:34:56.789] plan ready");
+
+        assert!(normalize_plain_line("watchkill: command not found: claude").is_none());
+
+        let entry = normalize_plain_line("[12:34] not a timing line").unwrap();
+        assert!(matches!(
+            entry.entry_type,
+            NormalizedEntryType::SystemMessage
+        ));
+        assert_eq!(entry.content, "Raw output: [12:34] not a timing line");
+    }
+
+    #[test]
+    fn test_normalize_logs_mixed_plain_text() {
+        let executor = ClaudeExecutor::new_plan_mode();
+        let logs = include_str!("../../tests/fixtures/claude/plan_mode_mixed_output.txt");
+
+        let result = executor.normalize_logs(logs, "/tmp/test-worktree").unwrap();
+
+        let kinds: Vec<&str> = result
+            .entries
+            .iter()
+            .map(|entry| match entry.entry_type {
+                NormalizedEntryType::DebugTrace => "debug_trace",
+                NormalizedEntryType::Timing => "timing",
+                NormalizedEntryType::SystemMessage => "system_message",
+                NormalizedEntryType::AssistantMessage => "assistant_message",
+                _ => "other",
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                "debug_trace",
+                "debug_trace",
+                "system_message",
+                "timing",
+                "assistant_message",
+                "timing",
+                "system_message",
+            ]
+        );
+        assert!(!result
+            .entries
+            .iter()
+            .any(|entry| entry.content.contains("watchkill")));
+    }
+
+    #[test]
+    fn test_normalize_logs_computer_use() {
+        let executor = ClaudeExecutor::new();
+        let logs = include_str!("../../tests/fixtures/claude/computer_use.jsonl");
+
+        let result = executor.normalize_logs(logs, "/tmp/test-worktree").unwrap();
+
+        let tool_uses: Vec<_> = result
+            .entries
+            .iter()
+            .filter_map(|entry| match &entry.entry_type {
+                NormalizedEntryType::ToolUse { action_type, .. } => {
+                    Some((action_type, entry.content.as_str()))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(tool_uses.len(), 4);
+
+        assert!(matches!(
+            tool_uses[0].0,
+            ActionType::ComputerUse { action, coordinate: None } if action == "screenshot"
+        ));
+        assert_eq!(tool_uses[0].1, "Screenshot");
+
+        assert!(matches!(
+            tool_uses[1].0,
+            ActionType::ComputerUse { action, coordinate: Some((100, 200)) } if action == "left_click"
+        ));
+        assert_eq!(tool_uses[1].1, "Left click at (100, 200)");
+
+        assert_eq!(tool_uses[2].1, "Type `hello world`");
+        assert_eq!(tool_uses[3].1, "Press `Return`");
+    }
+
+    #[test]
+    fn test_computer_use_without_action() {
+        let executor = ClaudeExecutor::new();
+        let action = executor.extract_action_type(
+            "computer",
+            &serde_json::json!({ "coordinate": [1, 2] }),
+            "/tmp/test-worktree",
+        );
+
+        assert!(matches!(action, ActionType::Other { .. }));
+    }
+
+    fn assistant_entry(content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::AssistantMessage,
+            content: content.to_string(),
+            metadata: None,
+            repeat_count: 1,
+        }
+    }
+
+    #[test]
+    fn test_followup_prompt_includes_previous_summary() {
+        let previous = NormalizedConversation {
+            entries: vec![assistant_entry("Ignored because a summary exists")],
+            session_id: None,
+            executor_type: "claude".to_string(),
+            prompt: None,
+            summary: Some("Tests were still failing".to_string()),
+            stats: None,
+            metadata: Default::default(),
+        };
+
+        let executor =
+            ClaudeFollowupExecutor::new("session".to_string(), "Fix the tests".to_string())
+                .with_previous_outcome(&previous);
+
+        assert_eq!(
+            executor.build_prompt(Uuid::nil(), Uuid::nil()),
+            "Outcome of the previous run:\nTests were still failing\n\nFix the tests"
+        );
+    }
+
+    #[test]
+    fn test_followup_prompt_falls_back_to_last_assistant_messages() {
+        let previous = NormalizedConversation {
+            entries: vec![
+                assistant_entry("first"),
+                assistant_entry("second"),
+                assistant_entry("third"),
+                assistant_entry("fourth"),
+            ],
+            session_id: None,
+            executor_type: "claude".to_string(),
+            prompt: None,
+            summary: None,
+            stats: None,
+            metadata: Default::default(),
+        };
+
+        let mut executor =
+            ClaudeFollowupExecutor::new("session".to_string(), "Continue".to_string())
+                .with_previous_outcome(&previous);
+
+        assert_eq!(
+            executor.build_prompt(Uuid::nil(), Uuid::nil()),
+            "Outcome of the previous run:\nsecond\n\nthird\n\nfourth\n\nContinue"
+        );
+
+        executor.include_previous_outcome = false;
+        assert_eq!(executor.build_prompt(Uuid::nil(), Uuid::nil()), "Continue");
+    }
+
+    #[test]
+    fn test_followup_prompt_in_structured_json() {
+        let (project_id, task_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let executor = ClaudeFollowupExecutor::new("session".to_string(), "Continue".to_string())
+            .with_stdin_format(StdinFormat::StructuredJson);
+
+        let stdin: serde_json::Value =
+            serde_json::from_str(&executor.build_prompt(project_id, task_id)).unwrap();
+        assert_eq!(stdin["prompt"], "Continue");
+        assert_eq!(stdin["context"]["project_id"], project_id.to_string());
+        assert_eq!(stdin["context"]["task_id"], task_id.to_string());
+    }
+
+    #[test]
+    fn test_followup_prompt_with_tool_results() {
+        let executor = ClaudeFollowupExecutor::new("session".to_string(), "Continue".to_string())
+            .with_tool_results(vec![
+                ToolResult {
+                    kind: ToolResultKind::ToolResult,
+                    tool_use_id: "toolu_01".to_string(),
+                    content: "src/main.rs\nsrc/lib.rs".to_string(),
+                    is_error: false,
+                },
+                ToolResult {
+                    kind: ToolResultKind::ToolResult,
+                    tool_use_id: "toolu_02".to_string(),
+                    content: "cat: missing.txt: No such file or directory".to_string(),
+                    is_error: true,
+                },
+            ]);
+
+        assert_eq!(
+            executor.build_prompt(Uuid::nil(), Uuid::nil()),
+            include_str!("../../tests/fixtures/claude/tool_results_prompt.txt")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_followup_passes_context_files() {
+        let executor = ClaudeFollowupExecutor::with_command(
+            "sess-1".to_string(),
+            "Fix the failing test".to_string(),
+            "Claude".to_string(),
+            "claude -p".to_string(),
+        )
+        .with_context_files(vec![
+            "test-output.txt".to_string(),
+            "logs/run 2.txt".to_string(),
+        ]);
+
+        assert_eq!(
+            executor.get_command().await,
+            "claude -p --resume=sess-1 --context-file test-output.txt --context-file 'logs/run 2.txt'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_context_files_outside_worktree_are_rejected() {
+        let base = tempfile::tempdir().unwrap();
+        let worktree = base.path().join("worktree");
+        std::fs::create_dir_all(worktree.join("logs")).unwrap();
+        std::fs::write(worktree.join("logs/test.txt"), "1 failed").unwrap();
+        let outside = base.path().join("outside.txt");
+        std::fs::write(&outside, "secret").unwrap();
+        let worktree_path = worktree.to_str().unwrap();
+
+        assert!(
+            validate_context_files(&["logs/test.txt".to_string()], worktree_path)
+                .await
+                .is_ok()
+        );
+        for file in [
+            "../outside.txt".to_string(),
+            outside.to_string_lossy().to_string(),
+            "missing.txt".to_string(),
+        ] {
+            assert!(
+                matches!(
+                    validate_context_files(std::slice::from_ref(&file), worktree_path).await,
+                    Err(ExecutorError::InvalidInput(_))
+                ),
+                "{} should be rejected",
+                file
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spawn_passes_worktree_path_unmodified() {
+        // Backslash separators on Windows and a space on both must reach the child untouched
+        let base = tempfile::tempdir().unwrap();
+        let worktree = base.path().join("my projects").join("task-123");
+        std::fs::create_dir_all(&worktree).unwrap();
+        let worktree_path = worktree.to_str().unwrap();
+        if cfg!(windows) {
+            assert!(worktree_path.contains('\\'));
+        }
+
+        let print_cwd = if cfg!(windows) { "cd" } else { "pwd -P" };
+        let (pool, task_id) = pool_with_task("Print the working directory").await;
+        let mut child = ClaudeExecutor::new()
+            .try_spawn_with_command(&pool, task_id, worktree_path, "", print_cwd)
+            .await
+            .unwrap();
+
+        let mut stdout = String::new();
+        tokio::io::AsyncReadExt::read_to_string(
+            child.inner().stdout.as_mut().unwrap(),
+            &mut stdout,
+        )
+        .await
+        .unwrap();
+        assert!(child.wait().await.unwrap().success());
+
+        // `pwd -P` resolves symlinked temp dirs such as /tmp -> /private/tmp on macOS
+        let expected = if cfg!(windows) {
+            worktree.clone()
+        } else {
+            worktree.canonicalize().unwrap()
+        };
+        assert_eq!(std::path::Path::new(stdout.trim()), expected);
+    }
+
+    #[tokio::test]
+    async fn test_normalize_logs_stream_yields_entries_as_lines_arrive() {
+        let (line_tx, mut line_rx) = mpsc::unbounded_channel::<String>();
+        let lines = async_stream::stream! {
+            while let Some(line) = line_rx.recv().await {
+                yield line;
+            }
+        }
+        .boxed();
+
+        let executor = ClaudeExecutor::new();
+        let mut entries = executor.normalize_logs_stream(lines, "/tmp/test-worktree");
+
+        line_tx
+            .send(r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Looking at the tests"}]}}"#.to_string())
+            .unwrap();
+        // Arrives while the output is still open
+        let entry = entries.next().await.unwrap();
+        assert_eq!(entry.content, "Looking at the tests");
+
+        line_tx
+            .send(r#"{"type":"assistant","message":{"content":[{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"cargo test"}}]}}"#.to_string())
+            .unwrap();
+        let entry = entries.next().await.unwrap();
+        assert!(matches!(
+            entry.entry_type,
+            NormalizedEntryType::ToolUse {
+                action_type: ActionType::CommandRun { ref command },
+                ..
+            } if command == "cargo test"
+        ));
+
+        drop(line_tx);
+        assert!(entries.next().await.is_none());
+    }
+
+    async fn pool_with_task(title: &str) -> (sqlx::SqlitePool, Uuid) {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let task_id = create_task(&pool, project_id, title).await;
+        (pool, task_id)
+    }
+
+    #[tokio::test]
+    async fn test_validate_reports_missing_binary() {
+        let (pool, task_id) = pool_with_task("Fix the login form").await;
+        let worktree = tempfile::tempdir().unwrap();
+        let executor = ClaudeExecutor::with_command(
+            "Claude".to_string(),
+            "/nonexistent/claude-code -p".to_string(),
+        );
+
+        let report = executor
+            .validate(&pool, task_id, worktree.path().to_str().unwrap())
+            .await
+            .unwrap();
+
+        assert!(report
+            .command_resolved
+            .starts_with("/nonexistent/claude-code"));
+        assert!(!report.binary_exists);
+        assert!(report.worktree_accessible);
+        assert!(report.prompt_non_empty);
+        assert!(!report.all_ok);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_validate_reports_missing_worktree() {
+        let (pool, task_id) = pool_with_task("Fix the login form").await;
+        let executor = ClaudeExecutor::with_command("Claude".to_string(), "cat".to_string());
+
+        let report = executor
+            .validate(&pool, task_id, "/nonexistent/worktree")
+            .await
+            .unwrap();
+
+        assert!(report.binary_exists);
+        assert!(!report.worktree_accessible);
+        assert!(report.prompt_non_empty);
+        assert!(!report.all_ok);
+    }
+
+    #[test]
+    fn normalize_logs_is_deterministic() {
+        let fixtures_dir =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/claude");
+        let executor = ClaudeExecutor::new();
+
+        let mut fixture_count = 0;
+        for fixture in std::fs::read_dir(&fixtures_dir).unwrap() {
+            let path = fixture.unwrap().path();
+            let logs = std::fs::read_to_string(&path).unwrap();
+
+            let first = executor.normalize_logs(&logs, "/tmp/test-worktree");
+            let second = executor.normalize_logs(&logs, "/tmp/test-worktree");
+            assert_eq!(first, second, "{} normalized differently", path.display());
+            fixture_count += 1;
+        }
+        assert!(fixture_count > 0);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn normalize_logs_is_idempotent(
+            lines in proptest::collection::vec(
+                proptest::prop_oneof![
+                    r#"\{"type":"(assistant|user|system|result)","session_id":"[a-z0-9-]{0,12}"\}"#,
+                    r#"\{"type":"assistant","message":\{"content":\[\{"type":"text","text":"[ -~]{0,40}"\}\]\}\}"#,
+                    "[ -~]{0,80}",
+                ],
+                0..20,
+            )
+        ) {
+            let logs = lines.join("\n");
+            let executor = ClaudeExecutor::new();
+            proptest::prop_assert_eq!(
+                executor.normalize_logs(&logs, "/tmp/test-worktree"),
+                executor.normalize_logs(&logs, "/tmp/test-worktree")
+            );
+        }
+    }
 }
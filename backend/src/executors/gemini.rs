@@ -3,15 +3,14 @@
 //! This module provides Gemini CLI-based task execution with streaming support.
 
 mod config;
+mod normalize;
 mod streaming;
 
-use std::{process::Stdio, time::Instant};
+use std::process::Stdio;
 
 use async_trait::async_trait;
 use command_group::{AsyncCommandGroup, AsyncGroupChild};
-use config::{
-    max_chunk_size, max_display_size, max_latency_ms, max_message_size, GeminiStreamConfig,
-};
+use normalize::GeminiLogProcessor;
 // Re-export for external use
 use serde_json::Value;
 pub use streaming::GeminiPatchBatch;
@@ -20,13 +19,76 @@ use tokio::{io::AsyncWriteExt, process::Command};
 use uuid::Uuid;
 
 use crate::{
-    executor::{
-        Executor, ExecutorError, NormalizedConversation, NormalizedEntry, NormalizedEntryType,
-    },
-    models::task::Task,
+    api_key_store::{apply_api_key_env, ProjectApiKeyStore},
+    executor::{Executor, ExecutorError, NormalizedConversation, RunOutputChannels, SpawnContext},
+    models::{execution_process::ExecutionProcess, task::Task},
     utils::shell::get_shell_command,
 };
 
+/// Gemini CLI run through npx, used when no local installation is found or it fails to spawn
+const GEMINI_NPX_COMMAND: &str = "npx -y @google/gemini-cli@latest";
+
+/// Detect if the gemini CLI is installed locally
+async fn detect_local_gemini() -> Option<String> {
+    let (shell_cmd, shell_arg) = get_shell_command();
+
+    let output = Command::new(shell_cmd)
+        .arg(shell_arg)
+        .arg("which gemini 2>/dev/null || where gemini 2>NUL || echo")
+        .output()
+        .await
+        .ok()?;
+
+    if output.status.success() {
+        let path = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+        if !path.is_empty() && !path.contains("not found") && !path.contains("Could not find") {
+            tracing::info!("Detected local gemini at: {}", path);
+            return Some(path);
+        }
+    }
+
+    None
+}
+
+/// Get the Gemini CLI command: the path configured in `~/.gemini.json`, then a local
+/// installation, then npx
+async fn get_gemini_command() -> String {
+    if let Some(config_path) = get_gemini_config_path().await {
+        tracing::info!("Using Gemini CLI from config: {}", config_path);
+        return build_gemini_command(&config_path);
+    }
+
+    if let Some(local_path) = detect_local_gemini().await {
+        tracing::info!("Using local Gemini CLI: {}", local_path);
+        return build_gemini_command(&local_path);
+    }
+
+    tracing::info!("Falling back to npx Gemini CLI");
+    build_gemini_command(GEMINI_NPX_COMMAND)
+}
+
+/// Build the complete Gemini command with the flags for unattended stream-JSON output
+fn build_gemini_command(base_command: &str) -> String {
+    format!("{} --yolo --output_format stream_json", base_command)
+}
+
+/// Read `geminiCodePath` from `~/.gemini.json`
+async fn get_gemini_config_path() -> Option<String> {
+    let config_path = dirs::home_dir()?.join(".gemini.json");
+    let content = tokio::fs::read_to_string(&config_path).await.ok()?;
+    let config: Value = serde_json::from_str(&content).ok()?;
+
+    config
+        .get("geminiCodePath")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+}
+
 /// An executor that uses Gemini CLI to process tasks
 pub struct GeminiExecutor;
 
@@ -66,43 +128,13 @@ Task title: {}"#,
             )
         };
 
-        let mut command = Self::create_gemini_command(worktree_path);
-
-        let mut child = command
-            .group_spawn() // Create new process group so we can kill entire tree
-            .map_err(|e| {
-                crate::executor::SpawnContext::from_command(&command, "Gemini")
-                    .with_task(task_id, Some(task.title.clone()))
-                    .with_context("Gemini CLI execution for new task")
-                    .spawn_error(e)
-            })?;
-
-        // Write prompt to stdin
-        if let Some(mut stdin) = child.inner().stdin.take() {
-            tracing::debug!(
-                "Writing prompt to Gemini stdin for task {}: {:?}",
-                task_id,
-                prompt
-            );
-            stdin.write_all(prompt.as_bytes()).await.map_err(|e| {
-                let context = crate::executor::SpawnContext::from_command(&command, "Gemini")
-                    .with_task(task_id, Some(task.title.clone()))
-                    .with_context("Failed to write prompt to Gemini CLI stdin");
-                ExecutorError::spawn_failed(e, context)
-            })?;
-            stdin.shutdown().await.map_err(|e| {
-                let context = crate::executor::SpawnContext::from_command(&command, "Gemini")
-                    .with_task(task_id, Some(task.title.clone()))
-                    .with_context("Failed to close Gemini CLI stdin");
-                ExecutorError::spawn_failed(e, context)
-            })?;
-            tracing::info!(
-                "Successfully sent prompt to Gemini stdin for task {}",
-                task_id
-            );
-        }
-
-        Ok(child)
+        let api_key = ProjectApiKeyStore::get_for_task(pool, task_id, "gemini").await;
+        Self::try_spawn_with_fallback(worktree_path, &prompt, api_key.as_deref(), |command| {
+            SpawnContext::from_command(command, "Gemini")
+                .with_task(task_id, Some(task.title.clone()))
+                .with_context("Gemini CLI execution for new task")
+        })
+        .await
     }
 
     async fn execute_streaming(
@@ -112,6 +144,7 @@ Task title: {}"#,
         attempt_id: Uuid,
         execution_process_id: Uuid,
         worktree_path: &str,
+        output_channels: &RunOutputChannels,
     ) -> Result<AsyncGroupChild, ExecutorError> {
         tracing::info!(
             "Starting Gemini execution for task {} attempt {}",
@@ -129,7 +162,14 @@ Task title: {}"#,
             child.inner().id()
         );
 
-        Self::setup_streaming(pool, &mut child, attempt_id, execution_process_id);
+        Self::setup_streaming(
+            pool,
+            output_channels,
+            &mut child,
+            attempt_id,
+            execution_process_id,
+            worktree_path,
+        );
 
         Ok(child)
     }
@@ -137,86 +177,36 @@ Task title: {}"#,
     fn normalize_logs(
         &self,
         logs: &str,
-        _worktree_path: &str,
+        worktree_path: &str,
     ) -> Result<NormalizedConversation, String> {
-        let mut entries: Vec<NormalizedEntry> = Vec::new();
-        let mut parse_errors = Vec::new();
-
-        for (line_num, line) in logs.lines().enumerate() {
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
-                continue;
-            }
-
-            // Try to parse as JSON first (for NormalizedEntry format)
-            if trimmed.starts_with('{') {
-                match serde_json::from_str::<NormalizedEntry>(trimmed) {
-                    Ok(entry) => {
-                        entries.push(entry);
-                    }
-                    Err(e) => {
-                        tracing::warn!(
-                            "Failed to parse JSONL line {} in Gemini logs: {} - Line: {}",
-                            line_num + 1,
-                            e,
-                            trimmed
-                        );
-                        parse_errors.push(format!("Line {}: {}", line_num + 1, e));
-
-                        // Create a fallback entry for unrecognized JSON
-                        let fallback_entry = NormalizedEntry {
-                            timestamp: Some(chrono::Utc::now().to_rfc3339()),
-                            entry_type: NormalizedEntryType::SystemMessage,
-                            content: format!("Raw output: {}", trimmed),
-                            metadata: None,
-                        };
-                        entries.push(fallback_entry);
-                    }
-                }
-            } else {
-                // For non-JSON lines, treat as plain text content
-                let text_entry = NormalizedEntry {
-                    timestamp: Some(chrono::Utc::now().to_rfc3339()),
-                    entry_type: NormalizedEntryType::AssistantMessage,
-                    content: trimmed.to_string(),
-                    metadata: None,
-                };
-                entries.push(text_entry);
-            }
-        }
-
-        if !parse_errors.is_empty() {
-            tracing::warn!(
-                "Gemini normalize_logs encountered {} parse errors: {}",
-                parse_errors.len(),
-                parse_errors.join("; ")
-            );
+        let mut processor = GeminiLogProcessor::default();
+        for line in logs.lines() {
+            processor.process_line(line, worktree_path);
         }
 
-        tracing::debug!(
-            "Gemini normalize_logs processed {} lines, created {} entries",
-            logs.lines().count(),
-            entries.len()
-        );
-
         Ok(NormalizedConversation {
-            entries,
+            entries: processor.into_entries(),
             session_id: None, // Session ID is managed directly via database, not extracted from logs
             executor_type: "gemini".to_string(),
             prompt: None,
             summary: None,
+            stats: None,
+            metadata: Default::default(),
         })
     }
 
     // Note: Gemini streaming is handled by the Gemini-specific WAL system.
-    // See emit_content_batch() method which calls GeminiExecutor::push_patch().
+    // See stream_gemini_json() which calls GeminiExecutor::push_patch().
 }
 
 impl GeminiExecutor {
-    /// Create a standardized Gemini CLI command
-    fn create_gemini_command(worktree_path: &str) -> Command {
+    /// Create a standardized Gemini CLI command, given the project's API key when it has one
+    fn create_gemini_command(
+        worktree_path: &str,
+        gemini_command: &str,
+        api_key: Option<&str>,
+    ) -> Command {
         let (shell_cmd, shell_arg) = get_shell_command();
-        let gemini_command = "npx @google/gemini-cli@latest --yolo";
 
         let mut command = Command::new(shell_cmd);
         command
@@ -228,9 +218,88 @@ impl GeminiExecutor {
             .arg(shell_arg)
             .arg(gemini_command)
             .env("NODE_NO_WARNINGS", "1");
+        apply_api_key_env(&mut command, "gemini", api_key);
         command
     }
 
+    /// Spawn the detected Gemini CLI with `prompt` on stdin, retrying through npx when it
+    /// fails. `spawn_context` describes the run in spawn errors.
+    async fn try_spawn_with_fallback(
+        worktree_path: &str,
+        prompt: &str,
+        api_key: Option<&str>,
+        spawn_context: impl Fn(&Command) -> SpawnContext,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
+        let primary_command = get_gemini_command().await;
+
+        // Check if this is already the fallback command (npx)
+        let is_fallback = primary_command.contains("npx");
+
+        match Self::try_spawn_with_command(
+            worktree_path,
+            prompt,
+            &primary_command,
+            api_key,
+            &spawn_context,
+        )
+        .await
+        {
+            Ok(child) => Ok(child),
+            Err(e) if !is_fallback => {
+                tracing::warn!(
+                    "Primary command failed: {}. Attempting fallback to npx...",
+                    e
+                );
+
+                let fallback_command = build_gemini_command(GEMINI_NPX_COMMAND);
+                Self::try_spawn_with_command(
+                    worktree_path,
+                    prompt,
+                    &fallback_command,
+                    api_key,
+                    &spawn_context,
+                )
+                .await
+                .map_err(|fallback_err| {
+                    tracing::error!("Fallback command also failed: {}", fallback_err);
+                    fallback_err
+                })
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Spawn `gemini_command` and write `prompt` to its stdin
+    async fn try_spawn_with_command(
+        worktree_path: &str,
+        prompt: &str,
+        gemini_command: &str,
+        api_key: Option<&str>,
+        spawn_context: &impl Fn(&Command) -> SpawnContext,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
+        let mut command = Self::create_gemini_command(worktree_path, gemini_command, api_key);
+
+        let mut child = command
+            .group_spawn() // Create new process group so we can kill entire tree
+            .map_err(|e| spawn_context(&command).spawn_error(e))?;
+
+        if let Some(mut stdin) = child.inner().stdin.take() {
+            tracing::debug!("Writing {} character prompt to Gemini stdin", prompt.len());
+            stdin.write_all(prompt.as_bytes()).await.map_err(|e| {
+                let context = spawn_context(&command)
+                    .with_context("Failed to write prompt to Gemini CLI stdin");
+                ExecutorError::spawn_failed(e, context)
+            })?;
+            stdin.shutdown().await.map_err(|e| {
+                let context =
+                    spawn_context(&command).with_context("Failed to close Gemini CLI stdin");
+                ExecutorError::spawn_failed(e, context)
+            })?;
+        }
+
+        Ok(child)
+    }
+
     /// Update executor session ID with error handling
     async fn update_session_id(
         pool: &sqlx::SqlitePool,
@@ -261,9 +330,11 @@ impl GeminiExecutor {
     /// Setup streaming for both stdout and stderr
     fn setup_streaming(
         pool: &sqlx::SqlitePool,
+        output_channels: &RunOutputChannels,
         child: &mut AsyncGroupChild,
         attempt_id: Uuid,
         execution_process_id: Uuid,
+        worktree_path: &str,
     ) {
         // Take stdout and stderr pipes for streaming
         let stdout = child
@@ -277,20 +348,22 @@ impl GeminiExecutor {
             .take()
             .expect("Failed to take stderr from child process");
 
-        // Start streaming tasks with Gemini-specific line-based message updates
+        // Start streaming tasks with Gemini-specific stream-JSON normalization
         let pool_clone1 = pool.clone();
         let pool_clone2 = pool.clone();
 
-        tokio::spawn(Self::stream_gemini_chunked(
+        tokio::spawn(Self::stream_gemini_json(
             stdout,
             pool_clone1,
             attempt_id,
             execution_process_id,
+            worktree_path.to_string(),
         ));
         // Use default stderr streaming (no custom parsing)
         tokio::spawn(crate::executor::stream_output_to_db(
             stderr,
             pool_clone2,
+            output_channels.clone(),
             attempt_id,
             execution_process_id,
             false,
@@ -309,93 +382,6 @@ impl GeminiExecutor {
     ) -> Option<Vec<GeminiPatchBatch>> {
         GeminiStreaming::get_wal_batches(execution_process_id, after_batch_id)
     }
-
-    /// Clean up WAL when execution process finishes
-    pub async fn finalize_execution(
-        pool: &sqlx::SqlitePool,
-        execution_process_id: Uuid,
-        final_buffer: &str,
-    ) {
-        GeminiStreaming::finalize_execution(pool, execution_process_id, final_buffer).await;
-    }
-
-    /// Find the best boundary to split a chunk (newline preferred, sentence fallback)
-    pub fn find_chunk_boundary(buffer: &str, max_size: usize) -> usize {
-        GeminiStreaming::find_chunk_boundary(buffer, max_size)
-    }
-
-    /// Conditionally flush accumulated content to database in chunks
-    pub async fn maybe_flush_chunk(
-        pool: &sqlx::SqlitePool,
-        execution_process_id: Uuid,
-        buffer: &mut String,
-        config: &GeminiStreamConfig,
-    ) {
-        GeminiStreaming::maybe_flush_chunk(pool, execution_process_id, buffer, config).await;
-    }
-
-    /// Emit JSON patch for current message state - either "replace" for growing message or "add" for new message.
-    fn emit_message_patch(
-        execution_process_id: Uuid,
-        current_message: &str,
-        entry_count: &mut usize,
-        force_new_message: bool,
-    ) {
-        if current_message.is_empty() {
-            return;
-        }
-
-        if force_new_message && *entry_count > 0 {
-            // Start new message: add new entry to array
-            *entry_count += 1;
-            let patch_vec = vec![serde_json::json!({
-                "op": "add",
-                "path": format!("/entries/{}", *entry_count - 1),
-                "value": {
-                    "timestamp": chrono::Utc::now().to_rfc3339(),
-                    "entry_type": {"type": "assistant_message"},
-                    "content": current_message,
-                    "metadata": null,
-                }
-            })];
-
-            Self::push_patch(execution_process_id, patch_vec, current_message.len());
-        } else {
-            // Growing message: replace current entry
-            if *entry_count == 0 {
-                *entry_count = 1; // Initialize first message
-            }
-
-            let patch_vec = vec![serde_json::json!({
-                "op": "replace",
-                "path": format!("/entries/{}", *entry_count - 1),
-                "value": {
-                    "timestamp": chrono::Utc::now().to_rfc3339(),
-                    "entry_type": {"type": "assistant_message"},
-                    "content": current_message,
-                    "metadata": null,
-                }
-            })];
-
-            Self::push_patch(execution_process_id, patch_vec, current_message.len());
-        }
-    }
-
-    /// Emit final content when stream ends
-    async fn emit_final_content(
-        execution_process_id: Uuid,
-        remaining_content: &str,
-        entry_count: &mut usize,
-    ) {
-        if !remaining_content.trim().is_empty() {
-            Self::emit_message_patch(
-                execution_process_id,
-                remaining_content,
-                entry_count,
-                false, // Don't force new message for final content
-            );
-        }
-    }
 }
 
 impl GeminiFollowupExecutor {
@@ -480,6 +466,7 @@ You are continuing work on the above task. The execution history shows what has
         &self,
         worktree_path: &str,
         comprehensive_prompt: &str,
+        api_key: Option<&str>,
     ) -> Result<AsyncGroupChild, ExecutorError> {
         tracing::info!(
             "Spawning Gemini followup execution for attempt {} with resume context ({} chars)",
@@ -487,63 +474,18 @@ You are continuing work on the above task. The execution history shows what has
             comprehensive_prompt.len()
         );
 
-        let mut command = GeminiExecutor::create_gemini_command(worktree_path);
-
-        let mut child = command.group_spawn().map_err(|e| {
-            crate::executor::SpawnContext::from_command(&command, "Gemini")
-                .with_context(format!(
+        GeminiExecutor::try_spawn_with_fallback(
+            worktree_path,
+            comprehensive_prompt,
+            api_key,
+            |command| {
+                SpawnContext::from_command(command, "Gemini").with_context(format!(
                     "Gemini CLI followup execution with context for attempt {}",
                     self.attempt_id
                 ))
-                .spawn_error(e)
-        })?;
-
-        self.send_prompt_to_stdin(&mut child, &command, comprehensive_prompt)
-            .await?;
-        Ok(child)
-    }
-
-    async fn send_prompt_to_stdin(
-        &self,
-        child: &mut AsyncGroupChild,
-        command: &Command,
-        comprehensive_prompt: &str,
-    ) -> Result<(), ExecutorError> {
-        if let Some(mut stdin) = child.inner().stdin.take() {
-            tracing::debug!(
-                "Sending resume context to Gemini for attempt {}: {} characters",
-                self.attempt_id,
-                comprehensive_prompt.len()
-            );
-
-            stdin
-                .write_all(comprehensive_prompt.as_bytes())
-                .await
-                .map_err(|e| {
-                    let context = crate::executor::SpawnContext::from_command(command, "Gemini")
-                        .with_context(format!(
-                            "Failed to write resume prompt to Gemini CLI stdin for attempt {}",
-                            self.attempt_id
-                        ));
-                    ExecutorError::spawn_failed(e, context)
-                })?;
-
-            stdin.shutdown().await.map_err(|e| {
-                let context = crate::executor::SpawnContext::from_command(command, "Gemini")
-                    .with_context(format!(
-                        "Failed to close Gemini CLI stdin for attempt {}",
-                        self.attempt_id
-                    ));
-                ExecutorError::spawn_failed(e, context)
-            })?;
-
-            tracing::info!(
-                "Successfully sent resume context to Gemini for attempt {}",
-                self.attempt_id
-            );
-        }
-
-        Ok(())
+            },
+        )
+        .await
     }
 }
 
@@ -558,7 +500,8 @@ impl Executor for GeminiFollowupExecutor {
         let task = self.load_task(pool, task_id).await?;
         let resume_context = self.collect_resume_context(pool, &task).await?;
         let comprehensive_prompt = self.build_comprehensive_prompt(&task, &resume_context);
-        self.spawn_process(worktree_path, &comprehensive_prompt)
+        let api_key = ProjectApiKeyStore::get_for_task(pool, task_id, "gemini").await;
+        self.spawn_process(worktree_path, &comprehensive_prompt, api_key.as_deref())
             .await
     }
 
@@ -569,6 +512,7 @@ impl Executor for GeminiFollowupExecutor {
         attempt_id: Uuid,
         execution_process_id: Uuid,
         worktree_path: &str,
+        output_channels: &RunOutputChannels,
     ) -> Result<AsyncGroupChild, ExecutorError> {
         tracing::info!(
             "Starting Gemini followup execution for task {} attempt {} with resume context",
@@ -588,7 +532,14 @@ impl Executor for GeminiFollowupExecutor {
             child.inner().id()
         );
 
-        GeminiExecutor::setup_streaming(pool, &mut child, attempt_id, execution_process_id);
+        GeminiExecutor::setup_streaming(
+            pool,
+            output_channels,
+            &mut child,
+            attempt_id,
+            execution_process_id,
+            worktree_path,
+        );
 
         Ok(child)
     }
@@ -605,163 +556,30 @@ impl Executor for GeminiFollowupExecutor {
 }
 
 impl GeminiExecutor {
-    /// Format Gemini CLI output by inserting line breaks where periods are directly
-    /// followed by capital letters (common Gemini CLI formatting issue).
-    /// Handles both intra-chunk and cross-chunk period-to-capital transitions.
-    fn format_gemini_output(content: &str, accumulated_message: &str) -> String {
-        let mut result = String::with_capacity(content.len() + 100); // Reserve some extra space for potential newlines
-        let chars: Vec<char> = content.chars().collect();
-
-        // Check for cross-chunk boundary: previous chunk ended with period, current starts with capital
-        if !accumulated_message.is_empty() && !content.is_empty() {
-            let ends_with_period = accumulated_message.ends_with('.');
-            let starts_with_capital = chars
-                .first()
-                .map(|&c| c.is_uppercase() && c.is_alphabetic())
-                .unwrap_or(false);
-
-            if ends_with_period && starts_with_capital {
-                result.push('\n');
-            }
-        }
-
-        // Handle intra-chunk period-to-capital transitions
-        for i in 0..chars.len() {
-            result.push(chars[i]);
-
-            // Check if current char is '.' and next char is uppercase letter (no space between)
-            if chars[i] == '.' && i + 1 < chars.len() {
-                let next_char = chars[i + 1];
-                if next_char.is_uppercase() && next_char.is_alphabetic() {
-                    result.push('\n');
-                }
-            }
-        }
-
-        result
-    }
-
-    /// Stream Gemini output with dual-buffer approach: chunks for UI updates, messages for storage.
-    ///
-    /// **Chunks** (~2KB): Frequent UI updates using "replace" patches for smooth streaming
-    /// **Messages** (~8KB): Logical boundaries using "add" patches for new entries
-    /// **Consistent WAL/DB**: Both systems see same message structure via JSON patches
-    pub async fn stream_gemini_chunked(
-        mut output: impl tokio::io::AsyncRead + Unpin,
+    /// Store Gemini's stream-JSON output line by line and push the entries it produces to
+    /// the WAL, so clients see the conversation grow while the run is going
+    pub async fn stream_gemini_json(
+        output: impl tokio::io::AsyncRead + Unpin,
         pool: sqlx::SqlitePool,
         attempt_id: Uuid,
         execution_process_id: Uuid,
+        worktree_path: String,
     ) {
-        use tokio::io::{AsyncReadExt, BufReader};
-
-        let chunk_limit = max_chunk_size();
-        let display_chunk_size = max_display_size(); // ~2KB for UI updates
-        let message_boundary_size = max_message_size(); // ~8KB for new message boundaries
-        let max_latency = std::time::Duration::from_millis(max_latency_ms());
-
-        let mut reader = BufReader::new(&mut output);
-
-        // Dual buffers: chunk buffer for UI, message buffer for DB
-        let mut current_message = String::new(); // Current assistant message content
-        let mut db_buffer = String::new(); // Buffer for database storage (using ChunkStore)
-        let mut entry_count = 0usize; // Track assistant message entries
-
-        let mut read_buf = vec![0u8; chunk_limit.min(max_chunk_size())]; // Use configurable chunk limit, capped for memory efficiency
-        let mut last_chunk_emit = Instant::now();
+        use tokio::io::{AsyncBufReadExt, BufReader};
 
-        // Configuration for WAL and DB management
-        let config = GeminiStreamConfig::default();
+        let mut lines = BufReader::new(output).lines();
+        let mut processor = GeminiLogProcessor::default();
+        let mut content_length = 0usize;
 
         tracing::info!(
-            "Starting dual-buffer Gemini streaming for attempt {} (chunks: {}B, messages: {}B)",
-            attempt_id,
-            display_chunk_size,
-            message_boundary_size
+            "Starting Gemini stream-JSON streaming for attempt {}",
+            attempt_id
         );
 
         loop {
-            match reader.read(&mut read_buf).await {
-                Ok(0) => {
-                    // EOF: emit final content and flush to database
-                    Self::emit_final_content(
-                        execution_process_id,
-                        &current_message,
-                        &mut entry_count,
-                    )
-                    .await;
-
-                    // Flush any remaining database buffer
-                    Self::finalize_execution(&pool, execution_process_id, &db_buffer).await;
-                    break;
-                }
-                Ok(n) => {
-                    // Convert bytes to string and apply Gemini-specific formatting
-                    let raw_chunk = String::from_utf8_lossy(&read_buf[..n]);
-                    let formatted_chunk = Self::format_gemini_output(&raw_chunk, &current_message);
-
-                    // Add to both buffers
-                    current_message.push_str(&formatted_chunk);
-                    db_buffer.push_str(&formatted_chunk);
-
-                    // 1. Check for chunk emission (frequent UI updates ~2KB)
-                    let should_emit_chunk = current_message.len() >= display_chunk_size
-                        || (last_chunk_emit.elapsed() >= max_latency
-                            && !current_message.is_empty());
-
-                    if should_emit_chunk {
-                        // Emit "replace" patch for growing message (smooth UI)
-                        Self::emit_message_patch(
-                            execution_process_id,
-                            &current_message,
-                            &mut entry_count,
-                            false, // Not forcing new message
-                        );
-                        last_chunk_emit = Instant::now();
-                    }
-
-                    // 2. Check for message boundary (new assistant message ~8KB)
-                    let should_start_new_message = current_message.len() >= message_boundary_size;
-
-                    if should_start_new_message {
-                        // Find optimal boundary for new message
-                        let boundary =
-                            Self::find_chunk_boundary(&current_message, message_boundary_size);
-
-                        if boundary > 0 && boundary < current_message.len() {
-                            // Split at boundary: complete current message, start new one
-                            let completed_message = current_message[..boundary].to_string();
-                            let remaining_content = current_message[boundary..].to_string();
-
-                            // CRITICAL FIX: Only emit "replace" patch to complete current message
-                            // Do NOT emit "add" patch as it shifts existing database entries
-                            Self::emit_message_patch(
-                                execution_process_id,
-                                &completed_message,
-                                &mut entry_count,
-                                false, // Complete current message
-                            );
-
-                            // Store the completed message to database
-                            // This ensures the database gets the completed content at the boundary
-                            Self::maybe_flush_chunk(
-                                &pool,
-                                execution_process_id,
-                                &mut db_buffer,
-                                &config,
-                            )
-                            .await;
-
-                            // Start fresh message with remaining content (no WAL patch yet)
-                            // Next chunk emission will create "replace" patch for entry_count + 1
-                            current_message = remaining_content;
-                            entry_count += 1; // Move to next entry index for future patches
-                        }
-                    }
-
-                    // 3. Flush to database (same boundary detection)
-                    Self::maybe_flush_chunk(&pool, execution_process_id, &mut db_buffer, &config)
-                        .await;
-                }
+            let line = match lines.next_line().await {
+                Ok(Some(line)) => line,
+                Ok(None) => break,
                 Err(e) => {
                     tracing::error!(
                         "Error reading stdout for Gemini attempt {}: {}",
@@ -770,13 +588,55 @@ impl GeminiExecutor {
                     );
                     break;
                 }
+            };
+
+            let raw_line = format!("{}\n", line);
+            content_length += raw_line.len();
+            if let Err(e) =
+                ExecutionProcess::append_stdout(&pool, execution_process_id, &raw_line).await
+            {
+                tracing::error!("Failed to store Gemini output: {}", e);
+            }
+
+            let previous_count = processor.entries().len();
+            let previous_last_len = processor.entries().last().map(|e| e.content.len());
+            processor.process_line(&line, &worktree_path);
+
+            let entries = processor.entries();
+            let patches: Vec<Value> = if entries.len() > previous_count {
+                entries[previous_count..]
+                    .iter()
+                    .enumerate()
+                    .map(|(offset, entry)| {
+                        serde_json::json!({
+                            "op": "add",
+                            "path": format!("/entries/{}", previous_count + offset),
+                            "value": entry,
+                        })
+                    })
+                    .collect()
+            } else if entries.last().map(|e| e.content.len()) != previous_last_len {
+                // A text delta grew the current assistant message
+                vec![serde_json::json!({
+                    "op": "replace",
+                    "path": format!("/entries/{}", entries.len() - 1),
+                    "value": entries[entries.len() - 1],
+                })]
+            } else {
+                Vec::new()
+            };
+
+            if !patches.is_empty() {
+                Self::push_patch(execution_process_id, patches, content_length);
             }
         }
 
+        GeminiStreaming::purge_wal(execution_process_id);
+
         tracing::info!(
-            "Dual-buffer Gemini streaming completed for attempt {} ({} messages)",
+            "Gemini streaming completed for attempt {} ({} entries)",
             attempt_id,
-            entry_count
+            processor.entries().len()
         );
     }
 }
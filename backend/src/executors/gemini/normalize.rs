@@ -0,0 +1,312 @@
+//! Gemini stream-JSON log normalization
+//!
+//! With `--output_format stream_json` the Gemini CLI prints one JSON event per line. Text
+//! arrives as `content_block_delta` events that extend the current assistant message, and
+//! tool calls as `content_block_start` events whose block is a `tool_use`:
+//!
+//! ```text
+//! {"type":"content_block_delta","delta":{"type":"text_delta","text":"Reading the file"}}
+//! {"type":"content_block_start","content_block":{"type":"tool_use","name":"read_file","input":{"absolute_path":"/repo/src/main.rs"}}}
+//! ```
+//!
+//! Runs stored before Gemini used stream JSON hold `NormalizedEntry` lines and plain text,
+//! which are still accepted.
+
+use serde_json::Value;
+
+use crate::{
+    executor::{ActionType, NormalizedEntry, NormalizedEntryType},
+    utils::path::make_path_relative,
+};
+
+/// Builds normalized entries from Gemini output, one line at a time
+#[derive(Debug, Default)]
+pub struct GeminiLogProcessor {
+    entries: Vec<NormalizedEntry>,
+    /// Whether text deltas still extend the last assistant message
+    text_open: bool,
+}
+
+impl GeminiLogProcessor {
+    pub fn entries(&self) -> &[NormalizedEntry] {
+        &self.entries
+    }
+
+    pub fn into_entries(self) -> Vec<NormalizedEntry> {
+        self.entries
+    }
+
+    /// Add the entries described by one line of output
+    pub fn process_line(&mut self, line: &str, worktree_path: &str) {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            return;
+        }
+
+        let Ok(json) = serde_json::from_str::<Value>(trimmed) else {
+            self.push(NormalizedEntryType::AssistantMessage, trimmed.to_string());
+            return;
+        };
+
+        match json.get("type").and_then(Value::as_str) {
+            Some("content_block_delta") => {
+                if let Some(text) = json.pointer("/delta/text").and_then(Value::as_str) {
+                    self.push_text(text);
+                }
+            }
+            Some("content_block_start") => {
+                let block = json.get("content_block").unwrap_or(&Value::Null);
+                match block.get("type").and_then(Value::as_str) {
+                    Some("tool_use") => self.push_tool_use(block, worktree_path),
+                    Some("text") => {
+                        self.text_open = false;
+                        if let Some(text) = block.get("text").and_then(Value::as_str) {
+                            if !text.is_empty() {
+                                self.push_text(text);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Some("content_block_stop") | Some("message_stop") => self.text_open = false,
+            Some("error") => {
+                let message = json
+                    .pointer("/error/message")
+                    .and_then(Value::as_str)
+                    .unwrap_or("Unknown error");
+                self.push(NormalizedEntryType::ErrorMessage, message.to_string());
+            }
+            // Bookkeeping events such as `message_start` or `ping` carry no content
+            Some(_) => {}
+            None => match serde_json::from_value::<NormalizedEntry>(json) {
+                Ok(entry) => {
+                    self.text_open = false;
+                    self.entries.push(entry);
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Unrecognized JSON in Gemini logs: {} - Line: {}",
+                        e,
+                        trimmed
+                    );
+                    self.push(
+                        NormalizedEntryType::SystemMessage,
+                        format!("Raw output: {}", trimmed),
+                    );
+                }
+            },
+        }
+    }
+
+    fn push(&mut self, entry_type: NormalizedEntryType, content: String) {
+        self.text_open = false;
+        self.entries.push(NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content,
+            metadata: None,
+            repeat_count: 1,
+        });
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if self.text_open {
+            if let Some(last) = self.entries.last_mut() {
+                last.content.push_str(text);
+                return;
+            }
+        }
+        self.push(NormalizedEntryType::AssistantMessage, text.to_string());
+        self.text_open = true;
+    }
+
+    fn push_tool_use(&mut self, block: &Value, worktree_path: &str) {
+        let tool_name = block
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown");
+        let input = block.get("input").unwrap_or(&Value::Null);
+        let action_type = action_type(tool_name, input, worktree_path);
+        let content = concise_content(tool_name, &action_type);
+
+        self.text_open = false;
+        self.entries.push(NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: tool_name.to_string(),
+                action_type,
+            },
+            content,
+            metadata: Some(input.clone()),
+            repeat_count: 1,
+        });
+    }
+}
+
+/// Map a Gemini CLI tool call onto the shared action taxonomy
+fn action_type(tool_name: &str, input: &Value, worktree_path: &str) -> ActionType {
+    let str_arg = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| input.get(*key).and_then(Value::as_str))
+            .map(str::to_string)
+    };
+    let path_arg =
+        |keys: &[&str]| str_arg(keys).map(|path| make_path_relative(&path, worktree_path));
+
+    match tool_name {
+        "read_file" => match path_arg(&["absolute_path", "file_path", "path"]) {
+            Some(path) => ActionType::FileRead { path },
+            None => other(tool_name),
+        },
+        "read_many_files" => {
+            let paths = input
+                .get("paths")
+                .and_then(Value::as_array)
+                .map(|paths| {
+                    paths
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(|path| make_path_relative(path, worktree_path))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .unwrap_or_default();
+            ActionType::FileRead { path: paths }
+        }
+        "write_file" | "replace" | "edit" => {
+            match path_arg(&["file_path", "absolute_path", "path"]) {
+                Some(path) => ActionType::FileWrite { path },
+                None => other(tool_name),
+            }
+        }
+        "run_shell_command" | "shell" => match str_arg(&["command"]) {
+            Some(command) => ActionType::CommandRun { command },
+            None => other(tool_name),
+        },
+        "glob" | "search_file_content" | "grep" | "google_web_search" => {
+            match str_arg(&["pattern", "query"]) {
+                Some(query) => ActionType::Search { query },
+                None => other(tool_name),
+            }
+        }
+        "list_directory" | "ls" => ActionType::Search {
+            query: path_arg(&["path", "absolute_path"]).unwrap_or_else(|| ".".to_string()),
+        },
+        "web_fetch" => match str_arg(&["url", "prompt"]) {
+            Some(url) => ActionType::WebFetch { url },
+            None => other(tool_name),
+        },
+        _ => other(tool_name),
+    }
+}
+
+fn other(tool_name: &str) -> ActionType {
+    ActionType::Other {
+        description: format!("Tool: {}", tool_name),
+    }
+}
+
+fn concise_content(tool_name: &str, action_type: &ActionType) -> String {
+    match action_type {
+        ActionType::FileRead { path }
+        | ActionType::FileWrite { path }
+        | ActionType::FileDelete { path } => format!("`{}`", path),
+        ActionType::FileMove { from, to } => format!("`{}` → `{}`", from, to),
+        ActionType::CommandRun { command } => format!("`{}`", command),
+        ActionType::Search { query } => format!("`{}`", query),
+        ActionType::WebFetch { url } => format!("`{}`", url),
+        ActionType::TaskCreate { description } => description.clone(),
+        ActionType::PlanPresentation { plan } => plan.clone(),
+        ActionType::ComputerUse { action, .. } => action.clone(),
+        ActionType::Other { .. } => format!("`{}`", tool_name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(logs: &str) -> Vec<NormalizedEntry> {
+        let mut processor = GeminiLogProcessor::default();
+        for line in logs.lines() {
+            processor.process_line(line, "/repo");
+        }
+        processor.into_entries()
+    }
+
+    #[test]
+    fn test_text_deltas_extend_one_assistant_message() {
+        let entries = process(
+            r#"{"type":"message_start","message":{"role":"model"}}
+{"type":"content_block_delta","delta":{"type":"text_delta","text":"Looking at "}}
+{"type":"content_block_delta","delta":{"type":"text_delta","text":"the code."}}
+{"type":"content_block_stop"}
+{"type":"content_block_delta","delta":{"type":"text_delta","text":"Done."}}"#,
+        );
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].entry_type, NormalizedEntryType::AssistantMessage);
+        assert_eq!(entries[0].content, "Looking at the code.");
+        assert_eq!(entries[1].content, "Done.");
+    }
+
+    #[test]
+    fn test_tool_calls_map_to_action_types() {
+        let entries = process(
+            r#"{"type":"content_block_start","content_block":{"type":"tool_use","name":"read_file","input":{"absolute_path":"/repo/src/main.rs"}}}
+{"type":"content_block_start","content_block":{"type":"tool_use","name":"replace","input":{"file_path":"/repo/src/lib.rs","old_string":"a","new_string":"b"}}}
+{"type":"content_block_start","content_block":{"type":"tool_use","name":"run_shell_command","input":{"command":"cargo test"}}}
+{"type":"content_block_start","content_block":{"type":"tool_use","name":"search_file_content","input":{"pattern":"fn main"}}}
+{"type":"content_block_start","content_block":{"type":"tool_use","name":"web_fetch","input":{"url":"https://example.com"}}}
+{"type":"content_block_start","content_block":{"type":"tool_use","name":"save_memory","input":{"fact":"x"}}}"#,
+        );
+
+        let actions: Vec<_> = entries
+            .iter()
+            .map(|entry| match &entry.entry_type {
+                NormalizedEntryType::ToolUse { action_type, .. } => action_type.clone(),
+                other => panic!("expected a tool use, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            actions,
+            vec![
+                ActionType::FileRead {
+                    path: "src/main.rs".to_string()
+                },
+                ActionType::FileWrite {
+                    path: "src/lib.rs".to_string()
+                },
+                ActionType::CommandRun {
+                    command: "cargo test".to_string()
+                },
+                ActionType::Search {
+                    query: "fn main".to_string()
+                },
+                ActionType::WebFetch {
+                    url: "https://example.com".to_string()
+                },
+                ActionType::Other {
+                    description: "Tool: save_memory".to_string()
+                },
+            ]
+        );
+        assert_eq!(entries[0].content, "`src/main.rs`");
+    }
+
+    #[test]
+    fn test_legacy_entries_and_plain_text_are_kept() {
+        let entries = process(
+            r#"{"timestamp":null,"entry_type":{"type":"assistant_message"},"content":"stored chunk","metadata":null}
+plain output
+{"unexpected":true}"#,
+        );
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].content, "stored chunk");
+        assert_eq!(entries[1].entry_type, NormalizedEntryType::AssistantMessage);
+        assert_eq!(entries[1].content, "plain output");
+        assert_eq!(entries[2].entry_type, NormalizedEntryType::SystemMessage);
+    }
+}
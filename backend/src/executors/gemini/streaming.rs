@@ -1,4 +1,4 @@
-//! Gemini streaming functionality with WAL and chunked storage
+//! Gemini streaming functionality with WAL
 //!
 //! This module provides real-time streaming support for Gemini execution processes
 //! with Write-Ahead Log (WAL) capabilities for resumable streaming.
@@ -11,10 +11,6 @@ use serde_json::Value;
 use uuid::Uuid;
 
 use super::config::GeminiStreamConfig;
-use crate::{
-    executor::{NormalizedEntry, NormalizedEntryType},
-    models::execution_process::ExecutionProcess,
-};
 
 lazy_static::lazy_static! {
     /// Write-Ahead Log: Maps execution_process_id → WAL state (Gemini-specific)
@@ -41,7 +37,6 @@ pub struct GeminiWalState {
     pub total_content_length: usize,
     pub next_batch_id: u64,
     pub last_compaction: Instant,
-    pub last_db_flush: Instant,
     pub last_access: Instant,
 }
 
@@ -59,7 +54,6 @@ impl GeminiWalState {
             total_content_length: 0,
             next_batch_id: 1,
             last_compaction: now,
-            last_db_flush: now,
             last_access: now,
         }
     }
@@ -150,21 +144,6 @@ impl GeminiStreaming {
         })
     }
 
-    /// Clean up WAL when execution process finishes
-    pub async fn finalize_execution(
-        pool: &sqlx::SqlitePool,
-        execution_process_id: Uuid,
-        final_buffer: &str,
-    ) {
-        // Flush any remaining content to database
-        if !final_buffer.trim().is_empty() {
-            Self::store_chunk_to_db(pool, execution_process_id, final_buffer).await;
-        }
-
-        // Remove WAL entry
-        Self::purge_wal(execution_process_id);
-    }
-
     /// Remove WAL entry for a specific execution process
     pub fn purge_wal(execution_process_id: Uuid) {
         if let Ok(mut wal_map) = GEMINI_WAL_MAP.lock() {
@@ -176,96 +155,6 @@ impl GeminiStreaming {
         }
     }
 
-    /// Find the best boundary to split a chunk (newline preferred, sentence fallback)
-    pub fn find_chunk_boundary(buffer: &str, max_size: usize) -> usize {
-        if buffer.len() <= max_size {
-            return buffer.len();
-        }
-
-        let search_window = &buffer[..max_size];
-
-        // First preference: newline boundary
-        if let Some(pos) = search_window.rfind('\n') {
-            return pos + 1; // Include the newline
-        }
-
-        // Second preference: sentence boundary (., !, ?)
-        if let Some(pos) = search_window.rfind(&['.', '!', '?'][..]) {
-            if pos + 1 < search_window.len() {
-                return pos + 1;
-            }
-        }
-
-        // Fallback: word boundary
-        if let Some(pos) = search_window.rfind(' ') {
-            return pos + 1;
-        }
-
-        // Last resort: split at max_size
-        max_size
-    }
-
-    /// Store a chunk to the database
-    async fn store_chunk_to_db(pool: &sqlx::SqlitePool, execution_process_id: Uuid, content: &str) {
-        if content.trim().is_empty() {
-            return;
-        }
-
-        let entry = NormalizedEntry {
-            timestamp: Some(chrono::Utc::now().to_rfc3339()),
-            entry_type: NormalizedEntryType::AssistantMessage,
-            content: content.to_string(),
-            metadata: None,
-        };
-
-        match serde_json::to_string(&entry) {
-            Ok(jsonl_line) => {
-                let formatted_line = format!("{}\n", jsonl_line);
-                if let Err(e) =
-                    ExecutionProcess::append_stdout(pool, execution_process_id, &formatted_line)
-                        .await
-                {
-                    tracing::error!("Failed to store chunk to database: {}", e);
-                } else {
-                    tracing::debug!("Stored {}B chunk to database", content.len());
-                }
-            }
-            Err(e) => {
-                tracing::error!("Failed to serialize chunk: {}", e);
-            }
-        }
-    }
-
-    /// Conditionally flush accumulated content to database in chunks
-    pub async fn maybe_flush_chunk(
-        pool: &sqlx::SqlitePool,
-        execution_process_id: Uuid,
-        buffer: &mut String,
-        config: &GeminiStreamConfig,
-    ) {
-        if buffer.len() < config.max_db_chunk_size {
-            return;
-        }
-
-        // Find the best split point (newline preferred, sentence boundary fallback)
-        let split_point = Self::find_chunk_boundary(buffer, config.max_db_chunk_size);
-
-        if split_point > 0 {
-            let chunk = buffer[..split_point].to_string();
-            buffer.drain(..split_point);
-
-            // Store chunk to database
-            Self::store_chunk_to_db(pool, execution_process_id, &chunk).await;
-
-            // Update WAL flush time
-            if let Ok(mut wal_map) = GEMINI_WAL_MAP.lock() {
-                if let Some(wal_state) = wal_map.get_mut(&execution_process_id) {
-                    wal_state.last_db_flush = Instant::now();
-                }
-            }
-        }
-    }
-
     /// Check if WAL compaction is needed based on configured thresholds
     fn should_compact(wal_state: &GeminiWalState, config: &GeminiStreamConfig) -> bool {
         wal_state.batches.len() >= config.wal_compaction_threshold
@@ -0,0 +1,577 @@
+use std::{path::Path, process::Stdio};
+
+use async_trait::async_trait;
+use command_group::{AsyncCommandGroup, AsyncGroupChild};
+use serde::Deserialize;
+use tokio::{io::AsyncWriteExt, process::Command};
+use uuid::Uuid;
+
+use crate::{
+    executor::{
+        stream_output_to_db, ActionType, Executor, ExecutorError, NormalizedConversation,
+        NormalizedEntry, NormalizedEntryType, RunOutputChannels, SpawnContext,
+    },
+    models::{executor_session::ExecutorSession, task::Task},
+    utils::shell::get_shell_command,
+};
+
+/// Environment variable holding the Mistral API key codestral authenticates with
+pub const MISTRAL_API_KEY_ENV: &str = "MISTRAL_API_KEY";
+
+/// Flags for a non-interactive run that reads its prompt from stdin and streams JSON lines
+const CODESTRAL_ARGS: &str = "--stream-json --no-interactive";
+
+/// Opening fence of the blocks codestral embeds tool calls in
+const TOOL_CALL_FENCE: &str = "```tool_call";
+
+/// An executor that uses Mistral's codestral CLI to process tasks
+pub struct MistralExecutor;
+
+/// An executor that sends a follow-up prompt to codestral in the same worktree.
+/// codestral keeps no session to resume, so the prompt runs as a new conversation.
+pub struct MistralFollowupExecutor {
+    pub prompt: String,
+}
+
+/// Fail early when codestral has no API key to run with
+fn require_api_key() -> Result<(), ExecutorError> {
+    match std::env::var(MISTRAL_API_KEY_ENV) {
+        Ok(key) if !key.trim().is_empty() => Ok(()),
+        _ => Err(ExecutorError::ConfigError {
+            message: format!("{} must be set to run codestral", MISTRAL_API_KEY_ENV),
+        }),
+    }
+}
+
+/// Detect if codestral is installed locally
+async fn detect_local_codestral() -> Option<String> {
+    let (shell_cmd, shell_arg) = get_shell_command();
+
+    let output = Command::new(shell_cmd)
+        .arg(shell_arg)
+        .arg("which codestral 2>/dev/null || where codestral 2>NUL || echo")
+        .output()
+        .await
+        .ok()?;
+
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success()
+        && !path.is_empty()
+        && !path.contains("not found")
+        && !path.contains("Could not find")
+    {
+        tracing::info!("Detected local codestral at: {}", path);
+        return Some(path);
+    }
+
+    None
+}
+
+/// Get the codestral command, installing it with pip when it is not on PATH
+async fn get_codestral_command() -> String {
+    let base_command = match detect_local_codestral().await {
+        Some(local_path) => local_path,
+        None => {
+            tracing::info!("Falling back to pip-installed codestral");
+            "pip install --user codestral && codestral".to_string()
+        }
+    };
+    build_codestral_command(&base_command)
+}
+
+fn build_codestral_command(base_command: &str) -> String {
+    format!("{} {}", base_command, CODESTRAL_ARGS)
+}
+
+impl MistralExecutor {
+    /// Spawn codestral in the worktree and write `prompt` to its stdin
+    async fn spawn_with_prompt(
+        worktree_path: &str,
+        prompt: &str,
+        context: String,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
+        require_api_key()?;
+
+        let (shell_cmd, shell_arg) = get_shell_command();
+        let codestral_command = get_codestral_command().await;
+
+        let mut command = Command::new(shell_cmd);
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .current_dir(worktree_path)
+            .arg(shell_arg)
+            .arg(&codestral_command);
+
+        let mut child = command
+            .group_spawn() // Create new process group so we can kill entire tree
+            .map_err(|e| {
+                SpawnContext::from_command(&command, "Mistral")
+                    .with_context(context.clone())
+                    .spawn_error(e)
+            })?;
+
+        // Feed the prompt in, then close the pipe so codestral sees EOF
+        if let Some(mut stdin) = child.inner().stdin.take() {
+            stdin.write_all(prompt.as_bytes()).await.map_err(|e| {
+                let context = SpawnContext::from_command(&command, "Mistral").with_context(
+                    format!("Failed to write prompt to codestral stdin ({})", context),
+                );
+                ExecutorError::spawn_failed(e, context)
+            })?;
+            stdin.shutdown().await.map_err(|e| {
+                let context = SpawnContext::from_command(&command, "Mistral")
+                    .with_context(format!("Failed to close codestral stdin ({})", context));
+                ExecutorError::spawn_failed(e, context)
+            })?;
+        }
+
+        Ok(child)
+    }
+
+    /// Record the attempt as the session, since codestral has no session ID of its own,
+    /// and stream both pipes to the database
+    async fn stream_to_db(
+        pool: &sqlx::SqlitePool,
+        output_channels: &RunOutputChannels,
+        mut child: AsyncGroupChild,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+    ) -> AsyncGroupChild {
+        if let Err(e) =
+            ExecutorSession::update_session_id(pool, execution_process_id, &attempt_id.to_string())
+                .await
+        {
+            tracing::error!(
+                "Failed to update session ID for codestral execution process {}: {}",
+                execution_process_id,
+                e
+            );
+        }
+
+        let stdout = child
+            .inner()
+            .stdout
+            .take()
+            .expect("Failed to take stdout from child process");
+        let stderr = child
+            .inner()
+            .stderr
+            .take()
+            .expect("Failed to take stderr from child process");
+
+        tokio::spawn(stream_output_to_db(
+            stdout,
+            pool.clone(),
+            output_channels.clone(),
+            attempt_id,
+            execution_process_id,
+            true,
+        ));
+        tokio::spawn(stream_output_to_db(
+            stderr,
+            pool.clone(),
+            output_channels.clone(),
+            attempt_id,
+            execution_process_id,
+            false,
+        ));
+
+        child
+    }
+}
+
+#[async_trait]
+impl Executor for MistralExecutor {
+    async fn spawn(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
+        let task = Task::find_by_id(pool, task_id)
+            .await?
+            .ok_or(ExecutorError::TaskNotFound)?;
+
+        let prompt = match &task.description {
+            Some(description) => format!("{}\n\n{}", task.title, description),
+            None => task.title.clone(),
+        };
+
+        Self::spawn_with_prompt(
+            worktree_path,
+            &prompt,
+            format!("codestral execution for task {}", task_id),
+        )
+        .await
+    }
+
+    async fn execute_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        worktree_path: &str,
+        output_channels: &RunOutputChannels,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
+        let child = self.spawn(pool, task_id, worktree_path).await?;
+        Ok(Self::stream_to_db(
+            pool,
+            output_channels,
+            child,
+            attempt_id,
+            execution_process_id,
+        )
+        .await)
+    }
+
+    fn normalize_logs(
+        &self,
+        logs: &str,
+        worktree_path: &str,
+    ) -> Result<NormalizedConversation, String> {
+        Ok(NormalizedConversation {
+            entries: normalize_codestral_output(logs, worktree_path),
+            session_id: None,
+            executor_type: "mistral".to_string(),
+            prompt: None,
+            summary: None,
+            stats: None,
+            metadata: Default::default(),
+        })
+    }
+
+    fn validate_config(&self) -> Result<(), ExecutorError> {
+        require_api_key()
+    }
+}
+
+#[async_trait]
+impl Executor for MistralFollowupExecutor {
+    async fn spawn(
+        &self,
+        _pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
+        MistralExecutor::spawn_with_prompt(
+            worktree_path,
+            &self.prompt,
+            format!("codestral followup execution for task {}", task_id),
+        )
+        .await
+    }
+
+    async fn execute_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        worktree_path: &str,
+        output_channels: &RunOutputChannels,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
+        let child = self.spawn(pool, task_id, worktree_path).await?;
+        Ok(MistralExecutor::stream_to_db(
+            pool,
+            output_channels,
+            child,
+            attempt_id,
+            execution_process_id,
+        )
+        .await)
+    }
+
+    fn normalize_logs(
+        &self,
+        logs: &str,
+        worktree_path: &str,
+    ) -> Result<NormalizedConversation, String> {
+        // Reuse the same logic as the main MistralExecutor
+        MistralExecutor.normalize_logs(logs, worktree_path)
+    }
+
+    fn validate_config(&self) -> Result<(), ExecutorError> {
+        require_api_key()
+    }
+}
+
+/// One line of codestral's `--stream-json` output
+#[derive(Deserialize)]
+struct CodestralMessage {
+    role: String,
+    #[serde(default)]
+    content: String,
+}
+
+/// A tool call codestral embeds in a message as a fenced `tool_call` JSON block
+#[derive(Deserialize)]
+struct CodestralToolCall {
+    name: String,
+    #[serde(default)]
+    arguments: serde_json::Value,
+}
+
+/// Turn codestral's line-delimited JSON messages into normalized entries, splitting
+/// assistant messages around the tool call blocks embedded in them
+fn normalize_codestral_output(logs: &str, worktree_path: &str) -> Vec<NormalizedEntry> {
+    let mut entries = Vec::new();
+
+    for line in logs.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let message: CodestralMessage = match serde_json::from_str(trimmed) {
+            Ok(message) => message,
+            Err(_) => {
+                // If line isn't valid JSON, add it as raw text
+                entries.push(NormalizedEntry {
+                    timestamp: None,
+                    entry_type: NormalizedEntryType::SystemMessage,
+                    content: format!("Raw output: {}", trimmed),
+                    metadata: None,
+                    repeat_count: 1,
+                });
+                continue;
+            }
+        };
+
+        match message.role.as_str() {
+            "assistant" => entries.extend(assistant_entries(&message.content, worktree_path)),
+            "user" => push_text(
+                &mut entries,
+                NormalizedEntryType::UserMessage,
+                &message.content,
+            ),
+            _ => push_text(
+                &mut entries,
+                NormalizedEntryType::SystemMessage,
+                &message.content,
+            ),
+        }
+    }
+
+    entries
+}
+
+fn push_text(entries: &mut Vec<NormalizedEntry>, entry_type: NormalizedEntryType, text: &str) {
+    let content = text.trim();
+    if content.is_empty() {
+        return;
+    }
+    entries.push(NormalizedEntry {
+        timestamp: None,
+        entry_type,
+        content: content.to_string(),
+        metadata: None,
+        repeat_count: 1,
+    });
+}
+
+/// The text of an assistant message as messages, with one tool use per `tool_call` block
+fn assistant_entries(content: &str, worktree_path: &str) -> Vec<NormalizedEntry> {
+    let mut entries = Vec::new();
+    let mut text = Vec::new();
+    let mut lines = content.lines();
+
+    while let Some(line) = lines.next() {
+        if line.trim() != TOOL_CALL_FENCE {
+            text.push(line);
+            continue;
+        }
+
+        push_text(
+            &mut entries,
+            NormalizedEntryType::AssistantMessage,
+            &text.join("\n"),
+        );
+        text.clear();
+
+        let mut body = Vec::new();
+        for line in lines.by_ref() {
+            if line.trim() == "```" {
+                break;
+            }
+            body.push(line);
+        }
+        let body = body.join("\n");
+        match serde_json::from_str::<CodestralToolCall>(&body) {
+            Ok(call) => entries.push(tool_call_entry(call, worktree_path)),
+            // Keep a block we cannot read as part of the message
+            Err(_) => push_text(
+                &mut entries,
+                NormalizedEntryType::AssistantMessage,
+                &format!("{}\n{}\n```", TOOL_CALL_FENCE, body),
+            ),
+        }
+    }
+    push_text(
+        &mut entries,
+        NormalizedEntryType::AssistantMessage,
+        &text.join("\n"),
+    );
+
+    entries
+}
+
+fn tool_call_entry(call: CodestralToolCall, worktree_path: &str) -> NormalizedEntry {
+    let argument = |key: &str| {
+        call.arguments
+            .get(key)
+            .and_then(|value| value.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let relative = |path: String| {
+        Path::new(&path)
+            .strip_prefix(worktree_path)
+            .map(|relative| relative.to_string_lossy().to_string())
+            .unwrap_or(path)
+    };
+
+    let (action_type, content) = match call.name.as_str() {
+        "run_command" | "bash" => {
+            let command = argument("command");
+            (
+                ActionType::CommandRun {
+                    command: command.clone(),
+                },
+                format!("`{}`", command),
+            )
+        }
+        "read_file" => {
+            let path = relative(argument("path"));
+            (
+                ActionType::FileRead { path: path.clone() },
+                format!("`{}`", path),
+            )
+        }
+        "write_file" | "edit_file" => {
+            let path = relative(argument("path"));
+            (
+                ActionType::FileWrite { path: path.clone() },
+                format!("`{}`", path),
+            )
+        }
+        "delete_file" => {
+            let path = relative(argument("path"));
+            (
+                ActionType::FileDelete { path: path.clone() },
+                format!("`{}`", path),
+            )
+        }
+        "search" => {
+            let query = argument("query");
+            (
+                ActionType::Search {
+                    query: query.clone(),
+                },
+                format!("`{}`", query),
+            )
+        }
+        name => (
+            ActionType::Other {
+                description: name.to_string(),
+            },
+            format!("`{}`", name),
+        ),
+    };
+
+    NormalizedEntry {
+        timestamp: None,
+        entry_type: NormalizedEntryType::ToolUse {
+            tool_name: call.name.clone(),
+            action_type,
+        },
+        content,
+        metadata: Some(call.arguments),
+        repeat_count: 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_codestral_command() {
+        assert_eq!(
+            build_codestral_command("codestral"),
+            "codestral --stream-json --no-interactive"
+        );
+    }
+
+    #[test]
+    fn test_normalize_logs_message_with_tool_call_block() {
+        let message = serde_json::json!({
+            "role": "assistant",
+            "content": "I'll run the tests first.\n```tool_call\n{\"name\": \"run_command\", \"arguments\": {\"command\": \"cargo test\"}}\n```\nThen update the greeting:\n```tool_call\n{\"name\": \"edit_file\", \"arguments\": {\"path\": \"/tmp/worktree/src/main.rs\"}}\n```",
+        });
+        let logs = format!(
+            "{}\n{}\nnot json",
+            serde_json::json!({"role": "user", "content": "Fix the greeting"}),
+            message
+        );
+
+        let result = MistralExecutor
+            .normalize_logs(&logs, "/tmp/worktree")
+            .unwrap();
+        assert_eq!(result.executor_type, "mistral");
+        assert_eq!(result.entries.len(), 6);
+
+        assert!(matches!(
+            result.entries[0].entry_type,
+            NormalizedEntryType::UserMessage
+        ));
+        assert_eq!(result.entries[1].content, "I'll run the tests first.");
+        match &result.entries[2].entry_type {
+            NormalizedEntryType::ToolUse {
+                tool_name,
+                action_type: ActionType::CommandRun { command },
+            } => {
+                assert_eq!(tool_name, "run_command");
+                assert_eq!(command, "cargo test");
+            }
+            other => panic!("expected a command run, got {:?}", other),
+        }
+        assert_eq!(result.entries[2].content, "`cargo test`");
+
+        assert_eq!(result.entries[3].content, "Then update the greeting:");
+        match &result.entries[4].entry_type {
+            NormalizedEntryType::ToolUse {
+                action_type: ActionType::FileWrite { path },
+                ..
+            } => assert_eq!(path, "src/main.rs"),
+            other => panic!("expected a file write, got {:?}", other),
+        }
+
+        assert!(matches!(
+            result.entries[5].entry_type,
+            NormalizedEntryType::SystemMessage
+        ));
+    }
+
+    #[test]
+    fn test_normalize_logs_keeps_unreadable_tool_call_block() {
+        let logs = serde_json::json!({
+            "role": "assistant",
+            "content": "```tool_call\nnot a call\n```",
+        })
+        .to_string();
+
+        let result = MistralExecutor
+            .normalize_logs(&logs, "/tmp/worktree")
+            .unwrap();
+        assert_eq!(result.entries.len(), 1);
+        assert!(matches!(
+            result.entries[0].entry_type,
+            NormalizedEntryType::AssistantMessage
+        ));
+        assert!(result.entries[0].content.contains("not a call"));
+    }
+}
@@ -1,19 +1,26 @@
+pub mod aider;
 pub mod amp;
 pub mod ccr;
+pub mod chain;
 pub mod charm_opencode;
 pub mod claude;
 pub mod dev_server;
 pub mod echo;
 pub mod gemini;
+pub mod mistral;
+pub mod pipeline;
 pub mod setup_script;
 pub mod sst_opencode;
 
+pub use aider::{AiderExecutor, AiderFollowupExecutor};
 pub use amp::{AmpExecutor, AmpFollowupExecutor};
 pub use ccr::{CCRExecutor, CCRFollowupExecutor};
+pub use chain::ExecutorChain;
 pub use charm_opencode::{CharmOpencodeExecutor, CharmOpencodeFollowupExecutor};
 pub use claude::{ClaudeExecutor, ClaudeFollowupExecutor};
 pub use dev_server::DevServerExecutor;
 pub use echo::EchoExecutor;
 pub use gemini::{GeminiExecutor, GeminiFollowupExecutor};
+pub use mistral::{MistralExecutor, MistralFollowupExecutor};
 pub use setup_script::SetupScriptExecutor;
 pub use sst_opencode::{SstOpencodeExecutor, SstOpencodeFollowupExecutor};
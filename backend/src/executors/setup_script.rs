@@ -71,6 +71,7 @@ impl Executor for SetupScriptExecutor {
             entry_type: crate::executor::NormalizedEntryType::SystemMessage,
             content: format!("Executing setup script:\n{}", self.script),
             metadata: None,
+            repeat_count: 1,
         });
 
         // Process the logs - split by lines and create entries
@@ -102,6 +103,7 @@ impl Executor for SetupScriptExecutor {
                         entry_type,
                         content: current_chunk.trim().to_string(),
                         metadata: None,
+                        repeat_count: 1,
                     });
 
                     current_chunk.clear();
@@ -115,6 +117,7 @@ impl Executor for SetupScriptExecutor {
                     entry_type: crate::executor::NormalizedEntryType::SystemMessage,
                     content: current_chunk.trim().to_string(),
                     metadata: None,
+                    repeat_count: 1,
                 });
             }
         }
@@ -125,6 +128,8 @@ impl Executor for SetupScriptExecutor {
             executor_type: "setup-script".to_string(),
             prompt: Some(self.script.clone()),
             summary: None,
+            stats: None,
+            metadata: Default::default(),
         })
     }
 }
@@ -8,7 +8,9 @@ use tokio::{
 use uuid::Uuid;
 
 use crate::{
-    executor::{Executor, ExecutorError, NormalizedConversation, NormalizedEntry},
+    executor::{
+        Executor, ExecutorError, NormalizedConversation, NormalizedEntry, RunOutputChannels,
+    },
     models::{execution_process::ExecutionProcess, executor_session::ExecutorSession, task::Task},
     utils::shell::get_shell_command,
 };
@@ -347,6 +349,7 @@ Task title: {}"#,
         attempt_id: Uuid,
         execution_process_id: Uuid,
         worktree_path: &str,
+        _output_channels: &RunOutputChannels,
     ) -> Result<command_group::AsyncGroupChild, ExecutorError> {
         let mut child = self.spawn(pool, task_id, worktree_path).await?;
 
@@ -396,6 +399,8 @@ Task title: {}"#,
             executor_type: "sst-opencode".to_string(),
             prompt: None,
             summary: None,
+            stats: None,
+            metadata: Default::default(),
         })
     }
 }
@@ -474,6 +479,7 @@ impl Executor for SstOpencodeFollowupExecutor {
         attempt_id: Uuid,
         execution_process_id: Uuid,
         worktree_path: &str,
+        _output_channels: &RunOutputChannels,
     ) -> Result<command_group::AsyncGroupChild, ExecutorError> {
         let mut child = self.spawn(pool, task_id, worktree_path).await?;
 
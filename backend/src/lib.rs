@@ -2,15 +2,30 @@ use rust_embed::RustEmbed;
 use sentry_tracing::{EventFilter, SentryLayer};
 use tracing::Level;
 
+pub mod api_key_store;
 pub mod app_state;
+pub mod conversation_diff;
+pub mod conversation_export;
+pub mod conversation_summarizer;
+pub mod db;
+pub mod diff_parser;
 pub mod execution_monitor;
 pub mod executor;
+pub mod executor_events;
+pub mod executor_pool;
+pub mod executor_supervisor;
 pub mod executors;
 pub mod mcp;
 pub mod models;
+pub mod prompt_builder;
 pub mod routes;
+pub mod security;
 pub mod services;
+pub mod telemetry;
+#[cfg(test)]
+pub(crate) mod test_fixtures;
 pub mod utils;
+pub mod web_policy;
 
 #[derive(RustEmbed)]
 #[folder = "../frontend/dist"]
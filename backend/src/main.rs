@@ -9,30 +9,47 @@ use axum::{
     Json, Router,
 };
 use sentry_tower::NewSentryLayer;
-use sqlx::{sqlite::SqliteConnectOptions, SqlitePool};
+use sqlx::sqlite::SqliteConnectOptions;
 use strip_ansi_escapes::strip;
 use tokio::sync::RwLock;
 use tower_http::cors::CorsLayer;
 use tracing_subscriber::{filter::LevelFilter, prelude::*};
 use vibe_kanban::{sentry_layer, Assets, ScriptAssets, SoundAssets};
 
+mod api_key_store;
 mod app_state;
+mod conversation_diff;
+mod conversation_export;
+mod conversation_summarizer;
+mod db;
+mod diff_parser;
 mod execution_monitor;
 mod executor;
+mod executor_events;
+mod executor_pool;
+mod executor_supervisor;
 mod executors;
 mod mcp;
 mod models;
+mod prompt_builder;
 mod routes;
+mod security;
 mod services;
+mod telemetry;
+#[cfg(test)]
+mod test_fixtures;
 mod utils;
+mod web_policy;
 
 use app_state::AppState;
+use db::multi_tenant::{MultiTenantPool, META_DB};
 use execution_monitor::execution_monitor;
 use models::{ApiResponse, Config};
 use routes::{
-    auth, config, filesystem, health, projects, stream, task_attempts, task_templates, tasks,
+    admin, audit, auth, config, filesystem, health, labels, notifications, projects, stream,
+    task_attempts, task_runs, task_templates, tasks, webhooks,
 };
-use services::PrMonitorService;
+use services::{ExecutorHealthMonitor, PrMonitorService, WorktreeCleanup};
 
 async fn echo_handler(
     Json(payload): Json<serde_json::Value>,
@@ -144,30 +161,43 @@ fn main() -> anyhow::Result<()> {
             tracing_subscriber::registry()
                 .with(tracing_subscriber::fmt::layer().with_filter(LevelFilter::INFO))
                 .with(sentry_layer())
+                .with(telemetry::otlp_layer())
                 .init();
+            // Reports right away when worktrees are not checked or the setting is unusable
+            security::allowed_worktree_bases();
 
             // Create asset directory if it doesn't exist
             if !utils::asset_dir().exists() {
                 std::fs::create_dir_all(utils::asset_dir())?;
             }
 
-            // Database connection
+            // Database connection; with one database per project this holds only the projects
+            let multi_tenant = MultiTenantPool::enabled_from_env();
+            let database_file = if multi_tenant { META_DB } else { "db.sqlite" };
             let database_url = format!(
                 "sqlite://{}",
-                utils::asset_dir().join("db.sqlite").to_string_lossy()
+                utils::asset_dir().join(database_file).to_string_lossy()
             );
 
             let options = SqliteConnectOptions::from_str(&database_url)?.create_if_missing(true);
-            let pool = SqlitePool::connect_with(options).await?;
+            let pool = utils::database::connect_pool(options).await?;
             sqlx::migrate!("./migrations").run(&pool).await?;
 
             // Load configuration
             let config_path = utils::config_path();
             let config = Config::load(&config_path)?;
+            executors::claude::verify_claude_binary(config.verify_binary, &config.binary_manifest)
+                .await;
             let config_arc = Arc::new(RwLock::new(config));
 
             // Create app state
-            let app_state = AppState::new(pool.clone(), config_arc.clone()).await;
+            let mut app_state = AppState::new(pool.clone(), config_arc.clone()).await;
+            if multi_tenant {
+                app_state = app_state.with_tenants(Arc::new(MultiTenantPool::new(
+                    pool.clone(),
+                    utils::asset_dir(),
+                )));
+            }
 
             app_state.update_sentry_scope().await;
 
@@ -179,14 +209,41 @@ fn main() -> anyhow::Result<()> {
                 execution_monitor(state_clone).await;
             });
 
+            let backfill_state = app_state.clone();
+            tokio::spawn(async move {
+                match backfill_state.task_pools().await {
+                    Ok(task_pools) => {
+                        for backfill_pool in &task_pools {
+                            execution_monitor::backfill_conversation_index(backfill_pool).await;
+                            execution_monitor::compress_stored_logs(backfill_pool).await;
+                        }
+                    }
+                    Err(e) => tracing::error!("Failed to open task databases: {}", e),
+                }
+            });
+
+            tokio::spawn(execution_monitor::archived_task_purger(app_state.clone()));
+
             // Start PR monitoring service
-            let pr_monitor = PrMonitorService::new(pool.clone());
+            let pr_monitor = PrMonitorService::new(app_state.clone());
             let config_for_monitor = config_arc.clone();
 
             tokio::spawn(async move {
                 pr_monitor.start_with_config(config_for_monitor).await;
             });
 
+            // Start executor health monitoring
+            let health_monitor = ExecutorHealthMonitor::new(pool.clone());
+            tokio::spawn(async move {
+                health_monitor.start().await;
+            });
+
+            // Remove the worktrees of finished runs once they are past retention
+            tokio::spawn(WorktreeCleanup::start(
+                app_state.clone(),
+                WorktreeCleanup::retention_from_env(),
+            ));
+
             // Public routes (no auth required)
             let public_routes = Router::new()
                 .route("/api/health", get(health::health_check))
@@ -200,11 +257,21 @@ fn main() -> anyhow::Result<()> {
                         .merge(projects::projects_router())
                         .merge(tasks::tasks_router())
                         .merge(task_attempts::task_attempts_router())
+                        .merge(task_runs::task_runs_router())
+                        .merge(labels::labels_router())
                         .merge(stream::stream_router())
                         .merge(task_templates::templates_router())
                         .merge(filesystem::filesystem_router())
                         .merge(config::config_router())
                         .merge(auth::auth_router())
+                        .merge(notifications::notifications_router())
+                        .merge(admin::admin_router())
+                        .merge(webhooks::webhooks_router())
+                        .merge(audit::audit_router())
+                        .route(
+                            "/executors/:name/health-history",
+                            get(health::get_executor_health_history),
+                        )
                         .route("/sounds/:filename", get(serve_sound_file))
                         .layer(from_fn_with_state(app_state.clone(), auth::sentry_user_context_middleware)),
                 );
@@ -247,6 +314,7 @@ fn main() -> anyhow::Result<()> {
             }
 
             axum::serve(listener, app).await?;
+            telemetry::shutdown();
 
             Ok(())
         })
@@ -0,0 +1,152 @@
+//! MCP servers a project makes available to its Claude runs
+//!
+//! A project stores a document in the format of the Claude CLI's `--mcp-config` file. It is
+//! validated when it is saved, and written into the worktree for each run because the CLI
+//! only reads it from disk.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+use uuid::Uuid;
+
+/// Worktree directory the config files of runs are written to
+pub const CONFIG_DIR: &str = ".vibe-kanban";
+
+#[derive(Debug)]
+pub enum McpConfigError {
+    InvalidJson(serde_json::Error),
+    /// The JSON does not describe MCP servers
+    InvalidSchema(String),
+}
+
+impl std::fmt::Display for McpConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            McpConfigError::InvalidJson(e) => write!(f, "MCP config is not valid JSON: {}", e),
+            McpConfigError::InvalidSchema(reason) => write!(f, "Invalid MCP config: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for McpConfigError {}
+
+fn invalid(reason: impl Into<String>) -> McpConfigError {
+    McpConfigError::InvalidSchema(reason.into())
+}
+
+/// Check that `json` has the shape the CLI expects:
+/// `{"mcpServers": {"<name>": {"command": "...", "args": ["..."]}}}`
+pub fn validate(json: &str) -> Result<(), McpConfigError> {
+    let config: Value = serde_json::from_str(json).map_err(McpConfigError::InvalidJson)?;
+    let servers = config
+        .get("mcpServers")
+        .ok_or_else(|| invalid("missing \"mcpServers\""))?
+        .as_object()
+        .ok_or_else(|| invalid("\"mcpServers\" must be an object"))?;
+
+    for (name, server) in servers {
+        let server = server
+            .as_object()
+            .ok_or_else(|| invalid(format!("server \"{}\" must be an object", name)))?;
+        match server.get("command").and_then(Value::as_str) {
+            Some(command) if !command.trim().is_empty() => {}
+            _ => {
+                return Err(invalid(format!(
+                    "server \"{}\" needs a non-empty \"command\"",
+                    name
+                )))
+            }
+        }
+        if let Some(args) = server.get("args") {
+            let all_strings = args
+                .as_array()
+                .is_some_and(|args| args.iter().all(Value::is_string));
+            if !all_strings {
+                return Err(invalid(format!(
+                    "\"args\" of server \"{}\" must be an array of strings",
+                    name
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Where the config of a run is written; each process gets its own file so runs sharing a
+/// worktree do not remove each other's
+pub fn config_path(worktree_path: &str, process_id: Uuid) -> PathBuf {
+    Path::new(worktree_path)
+        .join(CONFIG_DIR)
+        .join(format!("mcp-config-{}.json", process_id))
+}
+
+/// Write a run's config into its worktree and return the file's path
+pub async fn write(worktree_path: &str, process_id: Uuid, json: &str) -> std::io::Result<PathBuf> {
+    let path = config_path(worktree_path, process_id);
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    tokio::fs::write(&path, json).await?;
+    Ok(path)
+}
+
+/// Delete a run's config once it has exited; runs without one are ignored
+pub async fn remove(worktree_path: &str, process_id: Uuid) -> std::io::Result<()> {
+    match tokio::fs::remove_file(config_path(worktree_path, process_id)).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_accepts_cli_format() {
+        let json = r#"{"mcpServers": {
+            "filesystem": {"command": "npx", "args": ["-y", "@modelcontextprotocol/server-filesystem", "."]},
+            "memory": {"command": "mcp-memory"}
+        }}"#;
+        assert!(validate(json).is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_malformed_configs() {
+        assert!(matches!(
+            validate("{\"mcpServers\": "),
+            Err(McpConfigError::InvalidJson(_))
+        ));
+        for json in [
+            r#"{"servers": {}}"#,
+            r#"{"mcpServers": []}"#,
+            r#"{"mcpServers": {"fs": "npx"}}"#,
+            r#"{"mcpServers": {"fs": {"args": ["."]}}}"#,
+            r#"{"mcpServers": {"fs": {"command": "npx", "args": "-y"}}}"#,
+            r#"{"mcpServers": {"fs": {"command": "npx", "args": [1]}}}"#,
+        ] {
+            assert!(
+                matches!(validate(json), Err(McpConfigError::InvalidSchema(_))),
+                "{} should be rejected",
+                json
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_config_file_is_removed_after_run() {
+        let worktree = tempfile::tempdir().unwrap();
+        let worktree_path = worktree.path().to_str().unwrap();
+        let process_id = Uuid::new_v4();
+        let json = r#"{"mcpServers": {"memory": {"command": "mcp-memory"}}}"#;
+
+        let path = write(worktree_path, process_id, json).await.unwrap();
+        assert!(path.starts_with(worktree.path().join(CONFIG_DIR)));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), json);
+
+        remove(worktree_path, process_id).await.unwrap();
+        assert!(!path.exists());
+        // Removing twice, e.g. after a spawn failure, is not an error
+        remove(worktree_path, process_id).await.unwrap();
+    }
+}
@@ -1 +1,2 @@
+pub mod client_config;
 pub mod task_server;
@@ -270,6 +270,11 @@ impl TaskServer {
             title: title.clone(),
             description: description.clone(),
             parent_task_attempt: None,
+            labels: None,
+            timeout_seconds: None,
+            executor_model: None,
+            priority: None,
+            parent_task_id: None,
         };
 
         match Task::create(&self.pool, &create_task_data, task_id).await {
@@ -422,7 +427,8 @@ impl TaskServer {
         let task_limit = limit.unwrap_or(50).clamp(1, 200); // Reasonable limits
 
         let tasks_result =
-            Task::find_by_project_id_with_attempt_status(&self.pool, project_uuid).await;
+            Task::find_by_project_id_with_attempt_status(&self.pool, project_uuid, false, None)
+                .await;
 
         match tasks_result {
             Ok(tasks) => {
@@ -584,6 +590,8 @@ impl TaskServer {
             new_description,
             new_status,
             new_parent_task_attempt,
+            current_task.timeout_seconds,
+            current_task.executor_model,
         )
         .await
         {
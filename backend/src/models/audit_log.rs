@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// One recorded change to a task or project
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct AuditLogEntry {
+    pub id: Uuid,
+    /// `task` or `project`
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    /// What happened, e.g. `task.created` or `task.status_changed`
+    pub action: String,
+    pub actor_id: String,
+    /// The entity as JSON before the change, `None` for creations
+    pub before_json: Option<String>,
+    /// The entity as JSON after the change, `None` for deletions
+    pub after_json: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AuditLogEntry {
+    pub async fn create(
+        conn: &mut SqliteConnection,
+        entity_type: &str,
+        entity_id: Uuid,
+        action: &str,
+        actor_id: &str,
+        before_json: Option<String>,
+        after_json: Option<String>,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            AuditLogEntry,
+            r#"INSERT INTO audit_log (id, entity_type, entity_id, action, actor_id, before_json, after_json)
+               VALUES ($1, $2, $3, $4, $5, $6, $7)
+               RETURNING id as "id!: Uuid", entity_type, entity_id as "entity_id!: Uuid", action, actor_id, before_json, after_json, created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            entity_type,
+            entity_id,
+            action,
+            actor_id,
+            before_json,
+            after_json
+        )
+        .fetch_one(&mut *conn)
+        .await
+    }
+
+    /// Find the changes made to an entity, newest first
+    pub async fn find_by_entity_id(
+        pool: &SqlitePool,
+        entity_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            AuditLogEntry,
+            r#"SELECT id as "id!: Uuid", entity_type, entity_id as "entity_id!: Uuid", action, actor_id, before_json, after_json, created_at as "created_at!: DateTime<Utc>"
+               FROM audit_log
+               WHERE entity_id = $1
+               ORDER BY created_at DESC, rowid DESC
+               LIMIT $2"#,
+            entity_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -1,9 +1,9 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
-use crate::executor::ExecutorConfig;
+use crate::{executor::ExecutorConfig, executor_supervisor::RestartPolicy};
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export)]
@@ -20,6 +20,13 @@ pub struct Config {
     pub editor: EditorConfig,
     pub github: GitHubConfig,
     pub analytics_enabled: Option<bool>,
+    /// Check the claude-code binary against `binary_manifest` at startup and refuse to run
+    /// Claude if it does not match
+    pub verify_binary: bool,
+    /// Trusted SHA-256 hashes of the claude-code binary, keyed by version
+    pub binary_manifest: HashMap<String, String>,
+    /// Which crashed coding agent runs are resumed automatically
+    pub restart_policy: RestartPolicy,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -168,6 +175,9 @@ impl Default for Config {
             editor: EditorConfig::default(),
             github: GitHubConfig::default(),
             analytics_enabled: None,
+            verify_binary: false,
+            binary_manifest: HashMap::new(),
+            restart_policy: RestartPolicy::default(),
         }
     }
 }
@@ -0,0 +1,215 @@
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::executor::NormalizedEntry;
+
+/// Most results a search returns
+pub const MAX_SEARCH_RESULTS: i64 = 50;
+
+/// A conversation entry matching a search, best matches first
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ConversationSearchResult {
+    pub task_id: Uuid,
+    pub run_id: Uuid,
+    /// Excerpt of the entry with the matched terms wrapped in `**`
+    pub snippet: String,
+    /// `type` tag of the entry, e.g. `tool_use` or `assistant_message`
+    pub entry_type: String,
+}
+
+/// Full-text index over the normalized conversation entries of coding agent runs
+pub struct ConversationIndex;
+
+impl ConversationIndex {
+    /// Replace the indexed entries of a run
+    pub async fn index_run(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        run_id: Uuid,
+        entries: &[NormalizedEntry],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        sqlx::query!(
+            "DELETE FROM conversation_entries_fts WHERE run_id = $1",
+            run_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for entry in entries
+            .iter()
+            .filter(|entry| !entry.content.trim().is_empty())
+        {
+            let entry_type = entry_type_tag(entry);
+            sqlx::query!(
+                "INSERT INTO conversation_entries_fts (task_id, run_id, entry_type, content) VALUES ($1, $2, $3, $4)",
+                task_id,
+                run_id,
+                entry_type,
+                entry.content
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await
+    }
+
+    /// Search every indexed entry, or those of one project's tasks, ranked by BM25
+    pub async fn search(
+        pool: &SqlitePool,
+        query: &str,
+        project_id: Option<Uuid>,
+    ) -> Result<Vec<ConversationSearchResult>, sqlx::Error> {
+        let Some(query) = match_expression(query) else {
+            return Ok(Vec::new());
+        };
+        sqlx::query_as!(
+            ConversationSearchResult,
+            r#"SELECT f.task_id AS "task_id!: Uuid",
+                      f.run_id AS "run_id!: Uuid",
+                      snippet(conversation_entries_fts, 3, '**', '**', '…', 16) AS "snippet!: String",
+                      f.entry_type AS "entry_type!: String"
+               FROM conversation_entries_fts f
+               JOIN tasks t ON t.id = f.task_id
+               WHERE conversation_entries_fts MATCH $1
+                 AND ($2 IS NULL OR t.project_id = $2)
+               ORDER BY bm25(conversation_entries_fts)
+               LIMIT $3"#,
+            query,
+            project_id,
+            MAX_SEARCH_RESULTS
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Find finished coding agent runs that have nothing in the index yet
+    pub async fn find_unindexed_run_ids(pool: &SqlitePool) -> Result<Vec<Uuid>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT ep.id AS "id!: Uuid"
+               FROM execution_processes ep
+               WHERE ep.process_type = 'codingagent'
+                 AND ep.completed_at IS NOT NULL
+                 AND ep.id NOT IN (SELECT run_id FROM conversation_entries_fts)
+               ORDER BY ep.completed_at ASC"#
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(records.into_iter().map(|record| record.id).collect())
+    }
+}
+
+fn entry_type_tag(entry: &NormalizedEntry) -> String {
+    serde_json::to_value(&entry.entry_type)
+        .ok()
+        .and_then(|value| value.get("type")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Turn free text into an FTS5 query matching entries that contain every word, so user
+/// input cannot be misread as FTS5 syntax
+fn match_expression(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        executor::{ActionType, NormalizedEntryType},
+        test_fixtures::{create_project, create_task, memory_pool},
+    };
+
+    /// A task of a project of its own; returns the project and task IDs
+    async fn create_project_task(pool: &SqlitePool) -> (Uuid, Uuid) {
+        let project_id = create_project(pool).await;
+        (project_id, create_task(pool, project_id, "task").await)
+    }
+
+    fn entry(entry_type: NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+            repeat_count: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_finds_terms_only_in_tool_use_entries() {
+        let pool = memory_pool().await;
+        let (project_id, task_id) = create_project_task(&pool).await;
+        let (other_project_id, _) = create_project_task(&pool).await;
+        let run_id = Uuid::new_v4();
+        let entries = vec![
+            entry(
+                NormalizedEntryType::AssistantMessage,
+                "Let me look at the parser",
+            ),
+            entry(
+                NormalizedEntryType::ToolUse {
+                    tool_name: "Read".to_string(),
+                    action_type: ActionType::FileRead {
+                        path: "src/frobnicate.rs".to_string(),
+                    },
+                },
+                "`src/frobnicate.rs`",
+            ),
+        ];
+
+        ConversationIndex::index_run(&pool, task_id, run_id, &entries)
+            .await
+            .unwrap();
+        // Indexing a run again replaces its entries
+        ConversationIndex::index_run(&pool, task_id, run_id, &entries)
+            .await
+            .unwrap();
+
+        let results = ConversationIndex::search(&pool, "frobnicate", Some(project_id))
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].task_id, task_id);
+        assert_eq!(results[0].run_id, run_id);
+        assert_eq!(results[0].entry_type, "tool_use");
+        assert!(results[0].snippet.contains("**frobnicate**"));
+
+        assert!(
+            ConversationIndex::search(&pool, "frobnicate", Some(other_project_id))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_ignores_diacritics_and_query_syntax() {
+        let pool = memory_pool().await;
+        let (_, task_id) = create_project_task(&pool).await;
+        let entries = vec![entry(
+            NormalizedEntryType::AssistantMessage,
+            "Fixed the café menu rendering",
+        )];
+        ConversationIndex::index_run(&pool, task_id, Uuid::new_v4(), &entries)
+            .await
+            .unwrap();
+
+        let results = ConversationIndex::search(&pool, "cafe MENU", None)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+
+        for query in ["\"menu", "menu OR", "NEAR(", "  "] {
+            assert!(ConversationIndex::search(&pool, query, None).await.is_ok());
+        }
+    }
+}
@@ -1,10 +1,16 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize, Serializer};
-use sqlx::{FromRow, SqlitePool, Type};
+use sqlx::{FromRow, Sqlite, SqlitePool, Type};
 use ts_rs::TS;
 use uuid::Uuid;
 
-use crate::app_state::ExecutionType;
+use crate::{
+    app_state::ExecutionType,
+    utils::log_compression::{compress_log, decompress_log, CompressionStats},
+};
+
+/// How many processes the startup compression of old logs loads at a time
+const LOG_COMPRESSION_BATCH_SIZE: i64 = 100;
 
 /// Filter out stderr boundary markers from output
 fn filter_stderr_boundary_markers(stderr: &Option<String>) -> Option<String> {
@@ -75,6 +81,10 @@ pub struct ExecutionProcess {
     pub args: Option<String>, // JSON array of arguments
     pub working_directory: String,
     pub stdout: Option<String>,
+    /// Output of a finished process, zstd-compressed; loaders decompress it into `stdout`
+    #[serde(skip)]
+    #[ts(skip)]
+    pub stdout_zstd: Option<Vec<u8>>,
     #[serde(serialize_with = "serialize_filtered_stderr")]
     pub stderr: Option<String>,
     pub exit_code: Option<i64>,
@@ -137,6 +147,7 @@ impl ExecutionProcess {
                 args, 
                 working_directory, 
                 stdout, 
+                stdout_zstd, 
                 stderr, 
                 exit_code,
                 started_at as "started_at!: DateTime<Utc>",
@@ -149,6 +160,7 @@ impl ExecutionProcess {
         )
         .fetch_optional(pool)
         .await
+        .map(|process| process.map(Self::with_decompressed_stdout))
     }
 
     /// Find all execution processes for a task attempt
@@ -168,6 +180,7 @@ impl ExecutionProcess {
                 args, 
                 working_directory, 
                 stdout, 
+                stdout_zstd, 
                 stderr, 
                 exit_code,
                 started_at as "started_at!: DateTime<Utc>",
@@ -181,6 +194,7 @@ impl ExecutionProcess {
         )
         .fetch_all(pool)
         .await
+        .map(Self::all_with_decompressed_stdout)
     }
 
     /// Find execution process summaries for a task attempt (excluding stdio)
@@ -227,6 +241,7 @@ impl ExecutionProcess {
                 args, 
                 working_directory, 
                 stdout, 
+                stdout_zstd, 
                 stderr, 
                 exit_code,
                 started_at as "started_at!: DateTime<Utc>",
@@ -258,6 +273,7 @@ impl ExecutionProcess {
                 ep.args, 
                 ep.working_directory, 
                 ep.stdout, 
+                ep.stdout_zstd, 
                 ep.stderr, 
                 ep.exit_code,
                 ep.started_at as "started_at!: DateTime<Utc>",
@@ -278,11 +294,14 @@ impl ExecutionProcess {
     }
 
     /// Create a new execution process
-    pub async fn create(
-        pool: &SqlitePool,
+    pub async fn create<'e, E>(
+        executor: E,
         data: &CreateExecutionProcess,
         process_id: Uuid,
-    ) -> Result<Self, sqlx::Error> {
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
         let now = Utc::now();
 
         sqlx::query_as!(
@@ -303,6 +322,7 @@ impl ExecutionProcess {
                 args, 
                 working_directory, 
                 stdout, 
+                stdout_zstd, 
                 stderr, 
                 exit_code,
                 started_at as "started_at!: DateTime<Utc>",
@@ -325,7 +345,7 @@ impl ExecutionProcess {
             now,                   // created_at
             now                    // updated_at
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await
     }
 
@@ -357,6 +377,108 @@ impl ExecutionProcess {
         Ok(())
     }
 
+    /// Move the stdout of a finished process into zstd-compressed storage, returning how much
+    /// it shrank, or `None` when there was no plain text output to compress
+    pub async fn compress_stdout(
+        pool: &SqlitePool,
+        id: Uuid,
+    ) -> Result<Option<CompressionStats>, sqlx::Error> {
+        let Some(record) = sqlx::query!(
+            r#"SELECT stdout as "stdout!", stdout_zstd FROM execution_processes WHERE id = $1 AND stdout IS NOT NULL"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let mut log = match &record.stdout_zstd {
+            Some(compressed) => decompress_log(compressed)?,
+            None => String::new(),
+        };
+        log.push_str(&record.stdout);
+        let (compressed, stats) = compress_log(&log)?;
+
+        // Output appended since it was read stays in `stdout`; substr counts characters
+        let compressed_chars = record.stdout.chars().count() as i64;
+        sqlx::query!(
+            r#"UPDATE execution_processes
+               SET stdout_zstd = $1, stdout = NULLIF(substr(stdout, $2 + 1), '')
+               WHERE id = $3"#,
+            compressed,
+            compressed_chars,
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        tracing::debug!("Compressed stdout of execution process {}: {:?}", id, stats);
+        Ok(Some(stats))
+    }
+
+    /// Compress the stdout of every finished process that still keeps it as plain text,
+    /// `LOG_COMPRESSION_BATCH_SIZE` processes at a time. Returns how many were compressed.
+    pub async fn compress_finished_stdout(pool: &SqlitePool) -> Result<usize, sqlx::Error> {
+        let mut after_rowid = 0;
+        let mut compressed = 0;
+        loop {
+            let records = sqlx::query!(
+                r#"SELECT rowid as "rowid!: i64", id as "id!: Uuid"
+                   FROM execution_processes
+                   WHERE rowid > $1 AND status != 'running' AND stdout IS NOT NULL
+                   ORDER BY rowid ASC
+                   LIMIT $2"#,
+                after_rowid,
+                LOG_COMPRESSION_BATCH_SIZE
+            )
+            .fetch_all(pool)
+            .await?;
+            let Some(last) = records.last() else {
+                return Ok(compressed);
+            };
+            after_rowid = last.rowid;
+
+            for record in records {
+                match Self::compress_stdout(pool, record.id).await {
+                    Ok(Some(_)) => compressed += 1,
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!(
+                        "Failed to compress stdout of execution process {}: {}",
+                        record.id,
+                        e
+                    ),
+                }
+            }
+        }
+    }
+
+    /// Restore `stdout` from its compressed copy, followed by any output appended after
+    /// the compression
+    fn with_decompressed_stdout(mut self) -> Self {
+        if let Some(compressed) = self.stdout_zstd.take() {
+            match decompress_log(&compressed) {
+                Ok(mut stdout) => {
+                    stdout.push_str(self.stdout.as_deref().unwrap_or_default());
+                    self.stdout = Some(stdout);
+                }
+                Err(e) => tracing::warn!(
+                    "Failed to decompress stdout of execution process {}: {}",
+                    self.id,
+                    e
+                ),
+            }
+        }
+        self
+    }
+
+    fn all_with_decompressed_stdout(processes: Vec<Self>) -> Vec<Self> {
+        processes
+            .into_iter()
+            .map(Self::with_decompressed_stdout)
+            .collect()
+    }
+
     /// Append to stdout for this execution process (for streaming updates)
     pub async fn append_stdout(
         pool: &SqlitePool,
@@ -391,6 +513,41 @@ impl ExecutionProcess {
         Ok(())
     }
 
+    /// How often the run was restarted after crashing, `None` when it does not exist
+    pub async fn restart_count(pool: &SqlitePool, id: Uuid) -> Result<Option<i64>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT restart_count as "restart_count!: i64" FROM execution_processes WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Mark a crashed run as running again, returning its new restart count
+    pub async fn record_restart(pool: &SqlitePool, id: Uuid) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"UPDATE execution_processes
+               SET status = 'running', exit_code = NULL, completed_at = NULL,
+                   restart_count = restart_count + 1, updated_at = datetime('now')
+               WHERE id = $1
+               RETURNING restart_count as "restart_count!: i64""#,
+            id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Count an output line of the run that was too large to store as it was
+    pub async fn record_truncated_line(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE execution_processes SET truncated_line_count = truncated_line_count + 1 WHERE id = $1",
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     /// Append to both stdout and stderr for this execution process
     pub async fn append_output(
         pool: &SqlitePool,
@@ -424,4 +581,26 @@ impl ExecutionProcess {
 
         Ok(())
     }
+
+    /// Remember the PID of the spawned process, which leads its process group, and when that
+    /// process started
+    pub async fn set_pid(
+        pool: &SqlitePool,
+        id: Uuid,
+        pid: u32,
+        start_time: Option<u64>,
+    ) -> Result<(), sqlx::Error> {
+        let pid = pid as i64;
+        let start_time = start_time.map(|ticks| ticks as i64);
+        sqlx::query!(
+            "UPDATE execution_processes SET pid = $2, pid_start_time = $3 WHERE id = $1",
+            id,
+            pid,
+            start_time
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Number of probe results kept per executor: 24 hours at one probe every 5 minutes
+pub const HEALTH_HISTORY_LIMIT: i64 = 288;
+
+/// Outcome of a single executor health probe
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct HealthCheckResult {
+    pub id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub executor_name: String,
+    pub healthy: bool,
+    pub error: Option<String>,
+    pub latency_ms: i64,
+}
+
+impl HealthCheckResult {
+    /// Store a probe result and drop the executor's results beyond [`HEALTH_HISTORY_LIMIT`]
+    pub async fn record(
+        pool: &SqlitePool,
+        executor_name: &str,
+        error: Option<&str>,
+        latency_ms: i64,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let healthy = error.is_none();
+        let mut tx = pool.begin().await?;
+
+        let result = sqlx::query_as!(
+            HealthCheckResult,
+            r#"INSERT INTO executor_health_history (id, executor_name, healthy, error, latency_ms)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING id as "id!: Uuid", timestamp as "timestamp!: DateTime<Utc>", executor_name, healthy as "healthy!: bool", error, latency_ms"#,
+            id,
+            executor_name,
+            healthy,
+            error,
+            latency_ms
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"DELETE FROM executor_health_history
+               WHERE executor_name = $1
+                 AND id NOT IN (
+                     SELECT id FROM executor_health_history
+                     WHERE executor_name = $1
+                     ORDER BY timestamp DESC
+                     LIMIT $2
+                 )"#,
+            executor_name,
+            HEALTH_HISTORY_LIMIT
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(result)
+    }
+
+    /// Find an executor's probe results, newest first
+    pub async fn find_by_executor_name(
+        pool: &SqlitePool,
+        executor_name: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            HealthCheckResult,
+            r#"SELECT id as "id!: Uuid", timestamp as "timestamp!: DateTime<Utc>", executor_name, healthy as "healthy!: bool", error, latency_ms
+               FROM executor_health_history
+               WHERE executor_name = $1
+               ORDER BY timestamp DESC"#,
+            executor_name
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,324 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Color of labels created without one
+pub const DEFAULT_LABEL_COLOR: &str = "#6b7280";
+
+/// A project's label, used to group and filter its tasks
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Label {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    /// Unique within the project
+    pub name: String,
+    /// `#rrggbb`
+    pub color_hex: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A label as embedded in the tasks it is attached to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TaskLabel {
+    pub id: Uuid,
+    pub name: String,
+    pub color_hex: String,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct CreateLabel {
+    pub project_id: Uuid,
+    pub name: String,
+    /// `#rrggbb`, gray when omitted
+    #[ts(optional)]
+    pub color_hex: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct AttachLabel {
+    pub label_id: Uuid,
+}
+
+#[derive(Debug)]
+pub enum LabelError {
+    Database(sqlx::Error),
+    /// The task or label does not exist, or they belong to different projects
+    NotFound,
+    InvalidName,
+    InvalidColor(String),
+    /// The project already has a label with this name
+    DuplicateName(String),
+}
+
+impl std::fmt::Display for LabelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LabelError::Database(e) => write!(f, "Database error: {}", e),
+            LabelError::NotFound => write!(f, "Label not found"),
+            LabelError::InvalidName => write!(f, "Label name must not be empty"),
+            LabelError::InvalidColor(color) => {
+                write!(f, "Invalid label color '{}', expected #rrggbb", color)
+            }
+            LabelError::DuplicateName(name) => {
+                write!(f, "The project already has a label named '{}'", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LabelError {}
+
+impl From<sqlx::Error> for LabelError {
+    fn from(err: sqlx::Error) -> Self {
+        LabelError::Database(err)
+    }
+}
+
+fn is_valid_color(color: &str) -> bool {
+    color
+        .strip_prefix('#')
+        .is_some_and(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+impl Label {
+    pub async fn create(pool: &SqlitePool, data: &CreateLabel) -> Result<Self, LabelError> {
+        let name = data.name.trim();
+        if name.is_empty() {
+            return Err(LabelError::InvalidName);
+        }
+        let color_hex = data
+            .color_hex
+            .as_deref()
+            .unwrap_or(DEFAULT_LABEL_COLOR)
+            .to_lowercase();
+        if !is_valid_color(&color_hex) {
+            return Err(LabelError::InvalidColor(color_hex));
+        }
+
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            Label,
+            r#"INSERT INTO labels (id, project_id, name, color_hex)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", name, color_hex, created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            data.project_id,
+            name,
+            color_hex
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(|e| match e.as_database_error() {
+            Some(db_error) if db_error.is_unique_violation() => {
+                LabelError::DuplicateName(name.to_string())
+            }
+            _ => LabelError::Database(e),
+        })
+    }
+
+    /// Find the project's labels, by name
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Label,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", name, color_hex, created_at as "created_at!: DateTime<Utc>"
+               FROM labels
+               WHERE project_id = $1
+               ORDER BY name ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Delete a label, detaching it from every task
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM labels WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Attach a label to a task of the same project. Attaching it again does nothing.
+    pub async fn attach(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        label_id: Uuid,
+    ) -> Result<(), LabelError> {
+        let result = sqlx::query!(
+            r#"INSERT OR IGNORE INTO task_labels (task_id, label_id)
+               SELECT t.id, l.id
+               FROM tasks t
+               JOIN labels l ON l.project_id = t.project_id
+               WHERE t.id = $1 AND l.id = $2"#,
+            task_id,
+            label_id
+        )
+        .execute(pool)
+        .await?;
+        if result.rows_affected() > 0 {
+            return Ok(());
+        }
+
+        // Nothing was inserted: either it is attached already or the pair is invalid
+        let attached = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM task_labels WHERE task_id = $1 AND label_id = $2) as "attached!: bool""#,
+            task_id,
+            label_id
+        )
+        .fetch_one(pool)
+        .await?;
+        if attached {
+            Ok(())
+        } else {
+            Err(LabelError::NotFound)
+        }
+    }
+
+    /// Detach a label from a task, returning how many attachments were removed
+    pub async fn detach(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        label_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM task_labels WHERE task_id = $1 AND label_id = $2",
+            task_id,
+            label_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Attach the project's label called `name` to a task, creating the label first when
+    /// the project has none by that name
+    pub async fn attach_by_name(
+        conn: &mut SqliteConnection,
+        project_id: Uuid,
+        task_id: Uuid,
+        name: &str,
+    ) -> Result<(), sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query!(
+            "INSERT OR IGNORE INTO labels (id, project_id, name) VALUES ($1, $2, $3)",
+            id,
+            project_id,
+            name
+        )
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query!(
+            "INSERT OR IGNORE INTO task_labels (task_id, label_id) SELECT $1, id FROM labels WHERE project_id = $2 AND name = $3",
+            task_id,
+            project_id,
+            name
+        )
+        .execute(&mut *conn)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::task::Task,
+        test_fixtures::{create_project, create_task, memory_pool},
+    };
+
+    async fn setup() -> (SqlitePool, Uuid) {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        (pool, project_id)
+    }
+
+    async fn create_label(pool: &SqlitePool, project_id: Uuid, name: &str) -> Label {
+        Label::create(
+            pool,
+            &CreateLabel {
+                project_id,
+                name: name.to_string(),
+                color_hex: None,
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_label_filter_only_returns_tasks_with_the_label() {
+        let (pool, project_id) = setup().await;
+        let backend = create_label(&pool, project_id, "backend").await;
+        let ui = create_label(&pool, project_id, "ui").await;
+
+        let api_task = create_task(&pool, project_id, "Add the endpoint").await;
+        let ui_task = create_task(&pool, project_id, "Style the button").await;
+        let both_task = create_task(&pool, project_id, "Wire up the form").await;
+        Label::attach(&pool, api_task, backend.id).await.unwrap();
+        Label::attach(&pool, ui_task, ui.id).await.unwrap();
+        Label::attach(&pool, both_task, backend.id).await.unwrap();
+        Label::attach(&pool, both_task, ui.id).await.unwrap();
+
+        let listed =
+            Task::find_by_project_id_with_attempt_status(&pool, project_id, false, Some("backend"))
+                .await
+                .unwrap();
+        let mut ids: Vec<Uuid> = listed.iter().map(|task| task.id).collect();
+        ids.sort();
+        let mut expected = vec![api_task, both_task];
+        expected.sort();
+        assert_eq!(ids, expected);
+
+        let both = listed.iter().find(|task| task.id == both_task).unwrap();
+        assert_eq!(
+            both.labels,
+            vec![
+                TaskLabel {
+                    id: backend.id,
+                    name: "backend".to_string(),
+                    color_hex: DEFAULT_LABEL_COLOR.to_string(),
+                },
+                TaskLabel {
+                    id: ui.id,
+                    name: "ui".to_string(),
+                    color_hex: DEFAULT_LABEL_COLOR.to_string(),
+                },
+            ]
+        );
+
+        let unfiltered =
+            Task::find_by_project_id_with_attempt_status(&pool, project_id, false, None)
+                .await
+                .unwrap();
+        assert_eq!(unfiltered.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_deleting_a_label_detaches_it_from_tasks() {
+        let (pool, project_id) = setup().await;
+        let ops = create_label(&pool, project_id, "ops").await;
+        let first = create_task(&pool, project_id, "Rotate the keys").await;
+        let second = create_task(&pool, project_id, "Renew the certificate").await;
+        Label::attach(&pool, first, ops.id).await.unwrap();
+        Label::attach(&pool, second, ops.id).await.unwrap();
+
+        assert_eq!(Label::delete(&pool, ops.id).await.unwrap(), 1);
+
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM task_labels")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(remaining, 0);
+        assert!(Task::labels(&pool, first).await.unwrap().is_empty());
+    }
+}
@@ -1,12 +1,23 @@
 pub mod api_response;
+pub mod audit_log;
 pub mod config;
+pub mod conversation_index;
 pub mod execution_process;
+pub mod executor_health;
 pub mod executor_session;
+pub mod label;
+pub mod notification;
 pub mod project;
+pub mod project_archive;
+pub mod project_executor_config;
 pub mod task;
+pub mod task_artifact;
 pub mod task_attempt;
+pub mod task_run;
 
 pub mod task_template;
+pub mod tool_use_event;
+pub mod webhook;
 
 pub use api_response::ApiResponse;
 pub use config::Config;
@@ -0,0 +1,93 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "notification_type", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum NotificationType {
+    TaskCompleted,
+    TaskFailed,
+    StatusChanged,
+}
+
+/// In-app notification shown to a user and pushed to their connected clients
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Notification {
+    pub id: Uuid,
+    pub user_id: String,
+    pub task_id: Option<Uuid>,
+    #[serde(rename = "type")]
+    pub notification_type: NotificationType,
+    pub message: String,
+    /// When the user marked the notification as read, `None` while unread
+    pub read_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Notification {
+    pub async fn create(
+        pool: &SqlitePool,
+        user_id: &str,
+        task_id: Option<Uuid>,
+        notification_type: NotificationType,
+        message: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            Notification,
+            r#"INSERT INTO notifications (id, user_id, task_id, type, message)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING id as "id!: Uuid", user_id, task_id as "task_id: Uuid", type as "notification_type!: NotificationType", message, read_at as "read_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            user_id,
+            task_id,
+            notification_type,
+            message
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Find a user's notifications, newest first, optionally only the unread ones
+    pub async fn find_by_user_id(
+        pool: &SqlitePool,
+        user_id: &str,
+        unread_only: bool,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Notification,
+            r#"SELECT id as "id!: Uuid", user_id, task_id as "task_id: Uuid", type as "notification_type!: NotificationType", message, read_at as "read_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>"
+               FROM notifications
+               WHERE user_id = $1 AND ($2 = FALSE OR read_at IS NULL)
+               ORDER BY created_at DESC"#,
+            user_id,
+            unread_only
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Mark one of a user's notifications as read, keeping the original time if it already was
+    pub async fn mark_read(
+        pool: &SqlitePool,
+        id: Uuid,
+        user_id: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Notification,
+            r#"UPDATE notifications
+               SET read_at = COALESCE(read_at, datetime('now', 'subsec'))
+               WHERE id = $1 AND user_id = $2
+               RETURNING id as "id!: Uuid", user_id, task_id as "task_id: Uuid", type as "notification_type!: NotificationType", message, read_at as "read_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+}
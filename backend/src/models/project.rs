@@ -1,10 +1,46 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
 use chrono::{DateTime, Utc};
-use git2::{BranchType, Repository};
+use git2::{BranchType, Error as GitError, Repository};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{types::Json, FromRow, Sqlite, SqlitePool, Transaction};
 use ts_rs::TS;
 use uuid::Uuid;
 
+use crate::utils::worktree_manager::WorktreeManager;
+
+/// Labels starting with this prefix are treated as sprint labels by `Project::with_sprint`
+const SPRINT_LABEL_PREFIX: &str = "sprint-";
+
+/// How the tasks of a project share one ongoing Claude session
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum SessionAffinity {
+    /// Every task starts a fresh session
+    #[default]
+    None,
+    /// All tasks continue the project's pinned session
+    SharedPerProject,
+    /// Tasks labelled with the given tag continue the project's pinned session
+    SharedPerTaskGroup(String),
+}
+
+impl SessionAffinity {
+    /// Whether a task with these labels should continue the pinned session
+    pub fn applies_to(&self, labels: &[String]) -> bool {
+        match self {
+            SessionAffinity::None => false,
+            SessionAffinity::SharedPerProject => true,
+            SessionAffinity::SharedPerTaskGroup(tag) => labels.contains(tag),
+        }
+    }
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 #[ts(export)]
 pub struct Project {
@@ -13,6 +49,29 @@ pub struct Project {
     pub git_repo_path: String,
     pub setup_script: Option<String>,
     pub dev_script: Option<String>,
+    /// Branch that new task attempt worktrees are created from
+    pub base_branch: String,
+    /// Labels given to new tasks that are created without any
+    #[ts(type = "Array<string>")]
+    pub default_labels: Json<Vec<String>>,
+    /// Which tasks continue the project's pinned Claude session
+    #[ts(type = "SessionAffinity")]
+    pub session_affinity: Json<SessionAffinity>,
+    /// Session continued by tasks covered by `session_affinity`
+    pub pinned_session_id: Option<String>,
+    /// Every finished coding agent run is committed in its worktree. With this on, successful
+    /// runs are committed with `git_commit_template` instead of the run's summary.
+    pub use_commit_template: bool,
+    /// Message of those commits, with `{title}` and `{task_id}` placeholders; `None` uses
+    /// the default template
+    pub git_commit_template: Option<String>,
+    /// Seconds a waiting task needs to gain one priority point; `None` never boosts
+    pub priority_boost_after_seconds: Option<i64>,
+    /// Summarize each finished run with a cheaper model, see `ConversationSummarizer`
+    pub auto_summarize: bool,
+    /// Turns, one user and one assistant entry each, after which a follow-up starts a fresh
+    /// Claude session from a summary instead of resuming; `None` always resumes
+    pub max_context_turns: Option<i64>,
 
     #[ts(type = "Date")]
     pub created_at: DateTime<Utc>,
@@ -37,6 +96,23 @@ pub struct UpdateProject {
     pub git_repo_path: Option<String>,
     pub setup_script: Option<String>,
     pub dev_script: Option<String>,
+    #[ts(optional)]
+    pub default_labels: Option<Vec<String>>,
+    #[ts(optional)]
+    pub session_affinity: Option<SessionAffinity>,
+    #[ts(optional)]
+    pub use_commit_template: Option<bool>,
+    /// An empty template restores the default
+    #[ts(optional)]
+    pub git_commit_template: Option<String>,
+    /// 0 turns the boost off
+    #[ts(optional)]
+    pub priority_boost_after_seconds: Option<i64>,
+    #[ts(optional)]
+    pub auto_summarize: Option<bool>,
+    /// 0 removes the limit
+    #[ts(optional)]
+    pub max_context_turns: Option<i64>,
 }
 
 #[derive(Debug, Serialize, TS)]
@@ -47,6 +123,15 @@ pub struct ProjectWithBranch {
     pub git_repo_path: String,
     pub setup_script: Option<String>,
     pub dev_script: Option<String>,
+    pub base_branch: String,
+    pub default_labels: Vec<String>,
+    pub session_affinity: SessionAffinity,
+    pub pinned_session_id: Option<String>,
+    pub use_commit_template: bool,
+    pub git_commit_template: Option<String>,
+    pub priority_boost_after_seconds: Option<i64>,
+    pub auto_summarize: bool,
+    pub max_context_turns: Option<i64>,
     pub current_branch: Option<String>,
 
     #[ts(type = "Date")]
@@ -81,6 +166,98 @@ pub struct GitBranch {
     pub last_commit_date: DateTime<Utc>,
 }
 
+/// Why worktree garbage collection considers a worktree orphaned
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum WorktreeGcReason {
+    /// No task attempt of the project uses the worktree
+    NoRecord,
+    /// The worktree's task attempts all belong to tasks that are not in progress
+    TaskNotInProgress,
+}
+
+/// An orphaned worktree found by worktree garbage collection
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct WorktreeGcEntry {
+    pub path: String,
+    pub attempt_ids: Vec<Uuid>,
+    pub reason: WorktreeGcReason,
+    /// False for dry runs and for worktrees that failed to be removed
+    pub removed: bool,
+    pub error: Option<String>,
+}
+
+/// State of a project's repository, shown on the dashboard before dispatching tasks
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ProjectGitStatus {
+    /// Checked out branch, `None` when HEAD is detached
+    pub branch: Option<String>,
+    pub has_uncommitted_changes: bool,
+    pub has_untracked_files: bool,
+    /// Linked worktrees, i.e. not counting the main checkout
+    pub active_worktrees: u32,
+}
+
+#[derive(Debug)]
+pub enum ProjectError {
+    Database(sqlx::Error),
+    Git(GitError),
+    ProjectNotFound,
+}
+
+impl std::fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectError::Database(e) => write!(f, "Database error: {}", e),
+            ProjectError::Git(e) => write!(f, "Git error: {}", e),
+            ProjectError::ProjectNotFound => write!(f, "Project not found"),
+        }
+    }
+}
+
+impl std::error::Error for ProjectError {}
+
+impl From<sqlx::Error> for ProjectError {
+    fn from(err: sqlx::Error) -> Self {
+        ProjectError::Database(err)
+    }
+}
+
+impl From<GitError> for ProjectError {
+    fn from(err: GitError) -> Self {
+        ProjectError::Git(err)
+    }
+}
+
+/// How long a project's git status is served from the cache
+const GIT_STATUS_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Recently computed git statuses, keyed by project
+static GIT_STATUS_CACHE: OnceLock<Mutex<HashMap<Uuid, (Instant, ProjectGitStatus)>>> =
+    OnceLock::new();
+
+fn git_status_cache() -> &'static Mutex<HashMap<Uuid, (Instant, ProjectGitStatus)>> {
+    GIT_STATUS_CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Split `git status --porcelain` output into (has uncommitted changes, has untracked files)
+fn parse_porcelain_status(output: &str) -> (bool, bool) {
+    output
+        .lines()
+        .fold((false, false), |(changed, untracked), line| {
+            if line.starts_with("??") {
+                (changed, true)
+            } else if line.trim().is_empty() {
+                (changed, untracked)
+            } else {
+                (true, untracked)
+            }
+        })
+}
+
 #[derive(Debug, Deserialize, TS)]
 #[ts(export)]
 pub struct CreateBranch {
@@ -88,23 +265,102 @@ pub struct CreateBranch {
     pub base_branch: Option<String>,
 }
 
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct SetProjectBaseBranch {
+    pub base_branch: String,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct SetProjectSprint {
+    pub sprint_name: String,
+}
+
+/// Instructions given to the coding agent ahead of every task of a project
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ProjectPrompts {
+    /// Standing instructions, e.g. "never modify files outside `src/`"
+    pub system_prompt: Option<String>,
+    /// Text placed after the system prompt
+    pub prompt_prefix: Option<String>,
+}
+
+impl ProjectPrompts {
+    /// The system prompt followed by the prefix, `None` when both are blank
+    pub fn combined(&self) -> Option<String> {
+        let parts: Vec<&str> = [&self.system_prompt, &self.prompt_prefix]
+            .into_iter()
+            .filter_map(|part| part.as_deref().map(str::trim))
+            .filter(|part| !part.is_empty())
+            .collect();
+        (!parts.is_empty()).then(|| parts.join("\n\n"))
+    }
+}
+
+/// Fields left out are kept; an empty string clears them
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct UpdateProjectPrompts {
+    #[ts(optional)]
+    pub system_prompt: Option<String>,
+    #[ts(optional)]
+    pub prompt_prefix: Option<String>,
+}
+
+/// MCP servers given to a project's Claude runs
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ProjectMcpConfig {
+    /// `{"mcpServers": {"<name>": {"command": "...", "args": [...]}}}`; `null` clears it
+    pub mcp_config_json: Option<String>,
+}
+
+/// What a project's runs have cost since its budget was last reset, against its limit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ProjectBudget {
+    /// `None` when the project has no limit
+    pub monthly_cost_limit_usd: Option<f64>,
+    pub monthly_cost_usd_used: f64,
+}
+
+impl ProjectBudget {
+    /// New runs are refused once the spend reaches the limit
+    pub fn is_exceeded(&self) -> bool {
+        self.monthly_cost_limit_usd
+            .is_some_and(|limit| self.monthly_cost_usd_used >= limit)
+    }
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct SetProjectBudget {
+    /// `None` removes the limit
+    pub monthly_cost_limit_usd: Option<f64>,
+}
+
 impl Project {
     pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects ORDER BY created_at DESC"#
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, base_branch, default_labels as "default_labels!: Json<Vec<String>>", session_affinity as "session_affinity!: Json<SessionAffinity>", pinned_session_id, use_commit_template as "use_commit_template!: bool", git_commit_template, priority_boost_after_seconds, auto_summarize as "auto_summarize!: bool", max_context_turns, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects ORDER BY created_at DESC"#
         )
         .fetch_all(pool)
         .await
     }
 
-    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+    pub async fn find_by_id<'e, E>(executor: E, id: Uuid) -> Result<Option<Self>, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
         sqlx::query_as!(
             Project,
-            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE id = $1"#,
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, base_branch, default_labels as "default_labels!: Json<Vec<String>>", session_affinity as "session_affinity!: Json<SessionAffinity>", pinned_session_id, use_commit_template as "use_commit_template!: bool", git_commit_template, priority_boost_after_seconds, auto_summarize as "auto_summarize!: bool", max_context_turns, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE id = $1"#,
             id
         )
-        .fetch_optional(pool)
+        .fetch_optional(executor)
         .await
     }
 
@@ -114,7 +370,7 @@ impl Project {
     ) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE git_repo_path = $1"#,
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, base_branch, default_labels as "default_labels!: Json<Vec<String>>", session_affinity as "session_affinity!: Json<SessionAffinity>", pinned_session_id, use_commit_template as "use_commit_template!: bool", git_commit_template, priority_boost_after_seconds, auto_summarize as "auto_summarize!: bool", max_context_turns, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE git_repo_path = $1"#,
             git_repo_path
         )
         .fetch_optional(pool)
@@ -128,7 +384,7 @@ impl Project {
     ) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE git_repo_path = $1 AND id != $2"#,
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, base_branch, default_labels as "default_labels!: Json<Vec<String>>", session_affinity as "session_affinity!: Json<SessionAffinity>", pinned_session_id, use_commit_template as "use_commit_template!: bool", git_commit_template, priority_boost_after_seconds, auto_summarize as "auto_summarize!: bool", max_context_turns, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE git_repo_path = $1 AND id != $2"#,
             git_repo_path,
             exclude_id
         )
@@ -136,48 +392,275 @@ impl Project {
         .await
     }
 
-    pub async fn create(
-        pool: &SqlitePool,
+    pub async fn create<'e, E>(
+        executor: E,
         data: &CreateProject,
         project_id: Uuid,
-    ) -> Result<Self, sqlx::Error> {
+        base_branch: &str,
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
         sqlx::query_as!(
             Project,
-            r#"INSERT INTO projects (id, name, git_repo_path, setup_script, dev_script) VALUES ($1, $2, $3, $4, $5) RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"INSERT INTO projects (id, name, git_repo_path, setup_script, dev_script, base_branch) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, base_branch, default_labels as "default_labels!: Json<Vec<String>>", session_affinity as "session_affinity!: Json<SessionAffinity>", pinned_session_id, use_commit_template as "use_commit_template!: bool", git_commit_template, priority_boost_after_seconds, auto_summarize as "auto_summarize!: bool", max_context_turns, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             project_id,
             data.name,
             data.git_repo_path,
             data.setup_script,
-            data.dev_script
+            data.dev_script,
+            base_branch
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await
     }
 
-    pub async fn update(
-        pool: &SqlitePool,
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update<'e, E>(
+        executor: E,
         id: Uuid,
         name: String,
         git_repo_path: String,
         setup_script: Option<String>,
         dev_script: Option<String>,
-    ) -> Result<Self, sqlx::Error> {
+        default_labels: Vec<String>,
+        session_affinity: SessionAffinity,
+        use_commit_template: bool,
+        git_commit_template: Option<String>,
+        priority_boost_after_seconds: Option<i64>,
+        auto_summarize: bool,
+        max_context_turns: Option<i64>,
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
+        let default_labels = Json(default_labels);
+        let session_affinity = Json(session_affinity);
         sqlx::query_as!(
             Project,
-            r#"UPDATE projects SET name = $2, git_repo_path = $3, setup_script = $4, dev_script = $5 WHERE id = $1 RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"UPDATE projects SET name = $2, git_repo_path = $3, setup_script = $4, dev_script = $5, default_labels = $6, session_affinity = $7, use_commit_template = $8, git_commit_template = $9, priority_boost_after_seconds = $10, auto_summarize = $11, max_context_turns = $12 WHERE id = $1 RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, base_branch, default_labels as "default_labels!: Json<Vec<String>>", session_affinity as "session_affinity!: Json<SessionAffinity>", pinned_session_id, use_commit_template as "use_commit_template!: bool", git_commit_template, priority_boost_after_seconds, auto_summarize as "auto_summarize!: bool", max_context_turns, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             id,
             name,
             git_repo_path,
             setup_script,
-            dev_script
+            dev_script,
+            default_labels,
+            session_affinity,
+            use_commit_template,
+            git_commit_template,
+            priority_boost_after_seconds,
+            auto_summarize,
+            max_context_turns
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
+        .await
+    }
+
+    /// Replace the previous sprint's label in the default labels with `sprint-<sprint_name>`.
+    /// Intended to be called at sprint boundaries, within a transaction the caller commits.
+    pub async fn with_sprint(
+        tx: &mut Transaction<'_, Sqlite>,
+        id: Uuid,
+        sprint_name: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let Some(project) = Self::find_by_id(&mut **tx, id).await? else {
+            return Ok(None);
+        };
+
+        let mut default_labels: Vec<String> = project
+            .default_labels
+            .0
+            .into_iter()
+            .filter(|label| !label.starts_with(SPRINT_LABEL_PREFIX))
+            .collect();
+        default_labels.push(if sprint_name.starts_with(SPRINT_LABEL_PREFIX) {
+            sprint_name.to_string()
+        } else {
+            format!("{}{}", SPRINT_LABEL_PREFIX, sprint_name)
+        });
+        let default_labels = Json(default_labels);
+
+        sqlx::query_as!(
+            Project,
+            r#"UPDATE projects SET default_labels = $2, updated_at = datetime('now', 'subsec') WHERE id = $1 RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, base_branch, default_labels as "default_labels!: Json<Vec<String>>", session_affinity as "session_affinity!: Json<SessionAffinity>", pinned_session_id, use_commit_template as "use_commit_template!: bool", git_commit_template, priority_boost_after_seconds, auto_summarize as "auto_summarize!: bool", max_context_turns, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            default_labels
+        )
+        .fetch_optional(&mut **tx)
         .await
     }
 
-    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+    /// Set the branch that new worktrees for this project are created from
+    pub async fn set_git_branch<'e, E>(
+        executor: E,
+        id: Uuid,
+        branch: &str,
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_as!(
+            Project,
+            r#"UPDATE projects SET base_branch = $2, updated_at = datetime('now', 'subsec') WHERE id = $1 RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, base_branch, default_labels as "default_labels!: Json<Vec<String>>", session_affinity as "session_affinity!: Json<SessionAffinity>", pinned_session_id, use_commit_template as "use_commit_template!: bool", git_commit_template, priority_boost_after_seconds, auto_summarize as "auto_summarize!: bool", max_context_turns, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            branch
+        )
+        .fetch_one(executor)
+        .await
+    }
+
+    /// Pin the session continued by tasks covered by the session affinity, or clear it with `None`
+    pub async fn set_pinned_session_id<'e, E>(
+        executor: E,
+        id: Uuid,
+        session_id: Option<&str>,
+    ) -> Result<Option<Self>, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_as!(
+            Project,
+            r#"UPDATE projects SET pinned_session_id = $2, updated_at = datetime('now', 'subsec') WHERE id = $1 RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, base_branch, default_labels as "default_labels!: Json<Vec<String>>", session_affinity as "session_affinity!: Json<SessionAffinity>", pinned_session_id, use_commit_template as "use_commit_template!: bool", git_commit_template, priority_boost_after_seconds, auto_summarize as "auto_summarize!: bool", max_context_turns, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            session_id
+        )
+        .fetch_optional(executor)
+        .await
+    }
+
+    pub async fn find_prompts(
+        pool: &SqlitePool,
+        id: Uuid,
+    ) -> Result<Option<ProjectPrompts>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectPrompts,
+            r#"SELECT system_prompt, prompt_prefix FROM projects WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn set_prompts<'e, E>(
+        executor: E,
+        id: Uuid,
+        prompts: &ProjectPrompts,
+    ) -> Result<Option<ProjectPrompts>, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_as!(
+            ProjectPrompts,
+            r#"UPDATE projects SET system_prompt = $2, prompt_prefix = $3, updated_at = datetime('now', 'subsec') WHERE id = $1 RETURNING system_prompt, prompt_prefix"#,
+            id,
+            prompts.system_prompt,
+            prompts.prompt_prefix
+        )
+        .fetch_optional(executor)
+        .await
+    }
+
+    pub async fn find_mcp_config(
+        pool: &SqlitePool,
+        id: Uuid,
+    ) -> Result<Option<ProjectMcpConfig>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectMcpConfig,
+            r#"SELECT mcp_config_json FROM projects WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Store a config checked with [`crate::mcp::client_config::validate`]
+    pub async fn set_mcp_config<'e, E>(
+        executor: E,
+        id: Uuid,
+        mcp_config_json: Option<&str>,
+    ) -> Result<Option<ProjectMcpConfig>, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_as!(
+            ProjectMcpConfig,
+            r#"UPDATE projects SET mcp_config_json = $2, updated_at = datetime('now', 'subsec') WHERE id = $1 RETURNING mcp_config_json"#,
+            id,
+            mcp_config_json
+        )
+        .fetch_optional(executor)
+        .await
+    }
+
+    pub async fn find_budget<'e, E>(
+        executor: E,
+        id: Uuid,
+    ) -> Result<Option<ProjectBudget>, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_as!(
+            ProjectBudget,
+            r#"SELECT monthly_cost_limit_usd, monthly_cost_usd_used FROM projects WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(executor)
+        .await
+    }
+
+    pub async fn set_budget_limit<'e, E>(
+        executor: E,
+        id: Uuid,
+        monthly_cost_limit_usd: Option<f64>,
+    ) -> Result<Option<ProjectBudget>, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_as!(
+            ProjectBudget,
+            r#"UPDATE projects SET monthly_cost_limit_usd = $2, updated_at = datetime('now', 'subsec') WHERE id = $1 RETURNING monthly_cost_limit_usd, monthly_cost_usd_used"#,
+            id,
+            monthly_cost_limit_usd
+        )
+        .fetch_optional(executor)
+        .await
+    }
+
+    /// Add the cost of a finished run to the project's spend. The increment happens in SQL
+    /// so runs finishing at the same time are all counted.
+    pub async fn add_cost(pool: &SqlitePool, id: Uuid, cost_usd: f64) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE projects SET monthly_cost_usd_used = monthly_cost_usd_used + $2 WHERE id = $1",
+            id,
+            cost_usd
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn reset_budget<'e, E>(
+        executor: E,
+        id: Uuid,
+    ) -> Result<Option<ProjectBudget>, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_as!(
+            ProjectBudget,
+            r#"UPDATE projects SET monthly_cost_usd_used = 0.0, updated_at = datetime('now', 'subsec') WHERE id = $1 RETURNING monthly_cost_limit_usd, monthly_cost_usd_used"#,
+            id
+        )
+        .fetch_optional(executor)
+        .await
+    }
+
+    pub async fn delete<'e, E>(executor: E, id: Uuid) -> Result<u64, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
         let result = sqlx::query!("DELETE FROM projects WHERE id = $1", id)
-            .execute(pool)
+            .execute(executor)
             .await?;
         Ok(result.rows_affected())
     }
@@ -197,6 +680,68 @@ impl Project {
         Ok(result.count > 0)
     }
 
+    /// Report whether the project's repository is clean, cached for 30 seconds per project
+    pub async fn git_status(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<ProjectGitStatus, ProjectError> {
+        if let Some((computed_at, status)) = git_status_cache().lock().unwrap().get(&project_id) {
+            if computed_at.elapsed() < GIT_STATUS_CACHE_TTL {
+                return Ok(status.clone());
+            }
+        }
+
+        let project = Self::find_by_id(pool, project_id)
+            .await?
+            .ok_or(ProjectError::ProjectNotFound)?;
+        let repo_path = project.git_repo_path.clone();
+
+        let (branch, (has_uncommitted_changes, has_untracked_files)) =
+            tokio::task::spawn_blocking(move || {
+                let repo = Repository::open(&repo_path)?;
+                let branch = if repo.head_detached()? {
+                    None
+                } else {
+                    repo.head()?.shorthand().map(str::to_string)
+                };
+
+                let output = std::process::Command::new("git")
+                    .args(["status", "--porcelain"])
+                    .current_dir(&repo_path)
+                    .output()
+                    .map_err(|e| GitError::from_str(&format!("Failed to run git status: {}", e)))?;
+                if !output.status.success() {
+                    return Err(GitError::from_str(&format!(
+                        "git status failed in {}: {}",
+                        repo_path,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    )));
+                }
+
+                Ok((
+                    branch,
+                    parse_porcelain_status(&String::from_utf8_lossy(&output.stdout)),
+                ))
+            })
+            .await
+            .map_err(|e| GitError::from_str(&format!("Task join error: {}", e)))??;
+
+        let active_worktrees =
+            WorktreeManager::list_linked_worktrees(&project.git_repo_path).await?;
+
+        let status = ProjectGitStatus {
+            branch,
+            has_uncommitted_changes,
+            has_untracked_files,
+            active_worktrees: active_worktrees.len() as u32,
+        };
+        git_status_cache()
+            .lock()
+            .unwrap()
+            .insert(project_id, (Instant::now(), status.clone()));
+        Ok(status)
+    }
+
     pub fn get_current_branch(&self) -> Result<String, git2::Error> {
         let repo = Repository::open(&self.git_repo_path)?;
         let head = repo.head()?;
@@ -217,6 +762,15 @@ impl Project {
             git_repo_path: self.git_repo_path,
             setup_script: self.setup_script,
             dev_script: self.dev_script,
+            base_branch: self.base_branch,
+            default_labels: self.default_labels.0,
+            session_affinity: self.session_affinity.0,
+            pinned_session_id: self.pinned_session_id,
+            use_commit_template: self.use_commit_template,
+            git_commit_template: self.git_commit_template,
+            priority_boost_after_seconds: self.priority_boost_after_seconds,
+            auto_summarize: self.auto_summarize,
+            max_context_turns: self.max_context_turns,
             current_branch,
             created_at: self.created_at,
             updated_at: self.updated_at,
@@ -341,3 +895,74 @@ impl Project {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{create_project, memory_pool};
+
+    async fn setup() -> (SqlitePool, Uuid) {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        (pool, project_id)
+    }
+
+    #[tokio::test]
+    async fn test_budget_is_exceeded_once_spend_reaches_limit() {
+        let (pool, project_id) = setup().await;
+
+        // No limit means runs are never refused
+        Project::add_cost(&pool, project_id, 100.0).await.unwrap();
+        assert!(!Project::find_budget(&pool, project_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .is_exceeded());
+
+        let budget = Project::set_budget_limit(&pool, project_id, Some(101.0))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!budget.is_exceeded());
+
+        Project::add_cost(&pool, project_id, 0.5).await.unwrap();
+        Project::add_cost(&pool, project_id, 0.5).await.unwrap();
+        let budget = Project::find_budget(&pool, project_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(budget.monthly_cost_usd_used, 101.0);
+        assert!(budget.is_exceeded());
+    }
+
+    #[tokio::test]
+    async fn test_reset_budget_unlocks_project() {
+        let (pool, project_id) = setup().await;
+        Project::set_budget_limit(&pool, project_id, Some(5.0))
+            .await
+            .unwrap();
+        Project::add_cost(&pool, project_id, 7.25).await.unwrap();
+        assert!(Project::find_budget(&pool, project_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .is_exceeded());
+
+        let budget = Project::reset_budget(&pool, project_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            budget,
+            ProjectBudget {
+                monthly_cost_limit_usd: Some(5.0),
+                monthly_cost_usd_used: 0.0,
+            }
+        );
+        assert!(!budget.is_exceeded());
+        assert!(Project::reset_budget(&pool, Uuid::new_v4())
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,928 @@
+use std::collections::HashMap;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{types::Json, FromRow, Sqlite, SqlitePool, Transaction};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    models::{
+        execution_process::ExecutionProcessStatus, label::Label, project::Project,
+        project_executor_config::ProjectExecutorConfig, task::TaskStatus, webhook::Webhook,
+    },
+    utils::log_compression::{compress_log, validate_log_frame},
+};
+
+/// Version of the archive layout written by `ProjectArchive::export`; imports of any other
+/// version are rejected
+pub const ARCHIVE_SCHEMA_VERSION: i64 = 1;
+
+/// Stands in for the parts of webhook URLs left out of archives
+const REDACTED: &str = "[redacted]";
+
+/// A project with its tasks, runs and settings, portable between vibe-kanban instances.
+/// Ids are those of the exporting instance; they only link the entities of the archive.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ProjectArchive {
+    pub schema_version: i64,
+    pub project: ArchivedProject,
+    pub tasks: Vec<ArchivedTask>,
+    pub task_attempts: Vec<ArchivedTaskAttempt>,
+    /// Coding agent runs of the tasks
+    pub task_runs: Vec<ArchivedTaskRun>,
+    pub labels: Vec<ArchivedLabel>,
+    pub task_labels: Vec<ArchivedTaskLabel>,
+    /// Missing from archives written before dependencies were exported
+    #[serde(default)]
+    pub task_dependencies: Vec<ArchivedTaskDependency>,
+    /// Names only: values are encrypted with the exporting instance's key, so imported
+    /// tasks declare the variables for the user to set again
+    #[serde(default)]
+    pub task_env_vars: Vec<ArchivedTaskEnvVar>,
+    pub executor_config: Option<ArchivedExecutorConfig>,
+    /// For reference only: webhooks are not imported since their URLs are redacted and
+    /// their secrets left out
+    pub webhooks: Vec<ArchivedWebhook>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ArchivedProject {
+    pub id: Uuid,
+    pub name: String,
+    pub git_repo_path: String,
+    pub setup_script: Option<String>,
+    pub dev_script: Option<String>,
+    pub base_branch: String,
+    #[ts(type = "Array<string>")]
+    pub default_labels: Json<Vec<String>>,
+    /// Archives exported before the rename name it `auto_commit`
+    #[serde(alias = "auto_commit")]
+    pub use_commit_template: bool,
+    pub git_commit_template: Option<String>,
+    pub priority_boost_after_seconds: Option<i64>,
+    pub system_prompt: Option<String>,
+    pub prompt_prefix: Option<String>,
+    pub monthly_cost_limit_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ArchivedTask {
+    pub id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub status: TaskStatus,
+    pub priority: i64,
+    pub complexity_score: f32,
+    pub timeout_seconds: Option<i64>,
+    pub executor_model: Option<String>,
+    /// Task this one is a sub-task of
+    pub parent_task_id: Option<Uuid>,
+    /// Attempt this task was created from
+    pub parent_task_attempt: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub archived_at: Option<DateTime<Utc>>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub total_execution_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ArchivedTaskAttempt {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    /// Worktree on the exporting machine; imported attempts are marked as having none
+    pub worktree_path: String,
+    pub branch: String,
+    pub base_branch: String,
+    pub executor: Option<String>,
+    pub merge_commit: Option<String>,
+    pub pr_url: Option<String>,
+    pub pr_number: Option<i64>,
+    pub pr_status: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ArchivedTaskRun {
+    pub id: Uuid,
+    pub task_attempt_id: Uuid,
+    pub executor_type: Option<String>,
+    /// Runs still going when the project was exported are imported as killed
+    pub status: ExecutionProcessStatus,
+    pub command: String,
+    pub args: Option<String>,
+    pub working_directory: String,
+    pub exit_code: Option<i64>,
+    pub had_tool_errors: bool,
+    /// Normalized conversation as stored for the run
+    pub normalized_json: Option<String>,
+    /// Raw executor output as a base64-encoded zstd frame
+    pub logs_zstd_base64: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ArchivedLabel {
+    pub id: Uuid,
+    pub name: String,
+    pub color_hex: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ArchivedTaskLabel {
+    pub task_id: Uuid,
+    pub label_id: Uuid,
+}
+
+/// `task_id` cannot start until `depends_on_task_id` is done
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ArchivedTaskDependency {
+    pub task_id: Uuid,
+    pub depends_on_task_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ArchivedTaskEnvVar {
+    pub task_id: Uuid,
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ArchivedExecutorConfig {
+    pub executor_type: String,
+    pub binary_path: String,
+    pub model: Option<String>,
+    pub extra_flags: Option<String>,
+    pub use_plan_mode: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ArchivedWebhook {
+    /// Scheme and host only, e.g. `https://hooks.example.com/[redacted]`
+    pub url: String,
+    pub events: Vec<String>,
+}
+
+/// Project created by importing an archive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ImportedProject {
+    pub project_id: Uuid,
+    /// The archive had been imported before and no new project was created
+    pub already_imported: bool,
+}
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    Database(sqlx::Error),
+    /// A run log could not be compressed
+    Io(std::io::Error),
+    UnsupportedSchemaVersion(i64),
+    /// An entity refers to one missing from the archive, or a run log cannot be decoded
+    Invalid(String),
+    /// A project of this instance already uses the archived repository path
+    DuplicateRepoPath(String),
+}
+
+impl std::fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArchiveError::Database(e) => write!(f, "Database error: {}", e),
+            ArchiveError::Io(e) => write!(f, "Failed to compress run log: {}", e),
+            ArchiveError::UnsupportedSchemaVersion(version) => write!(
+                f,
+                "Unsupported archive schema version {}, expected {}",
+                version, ARCHIVE_SCHEMA_VERSION
+            ),
+            ArchiveError::Invalid(reason) => write!(f, "Invalid archive: {}", reason),
+            ArchiveError::DuplicateRepoPath(path) => {
+                write!(f, "A project already uses the repository at '{}'", path)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {}
+
+impl From<sqlx::Error> for ArchiveError {
+    fn from(err: sqlx::Error) -> Self {
+        ArchiveError::Database(err)
+    }
+}
+
+impl From<std::io::Error> for ArchiveError {
+    fn from(err: std::io::Error) -> Self {
+        ArchiveError::Io(err)
+    }
+}
+
+/// Keep only the scheme and host of a webhook URL, since paths and queries often carry tokens
+fn redact_url(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) => match parsed.host_str() {
+            Some(host) => format!("{}://{}/{}", parsed.scheme(), host, REDACTED),
+            None => REDACTED.to_string(),
+        },
+        Err(_) => REDACTED.to_string(),
+    }
+}
+
+/// New id of an archived entity, failing when the archive does not contain it
+fn remap(ids: &HashMap<Uuid, Uuid>, id: Uuid, what: &str) -> Result<Uuid, ArchiveError> {
+    ids.get(&id)
+        .copied()
+        .ok_or_else(|| ArchiveError::Invalid(format!("{} {} is not in the archive", what, id)))
+}
+
+impl ProjectArchive {
+    /// Collect everything the project owns into an archive, `None` when it does not exist
+    pub async fn export(pool: &SqlitePool, project_id: Uuid) -> Result<Option<Self>, ArchiveError> {
+        let Some(project) = sqlx::query_as!(
+            ArchivedProject,
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, base_branch, default_labels as "default_labels!: Json<Vec<String>>", use_commit_template as "use_commit_template!: bool", git_commit_template, priority_boost_after_seconds, system_prompt, prompt_prefix, monthly_cost_limit_usd
+               FROM projects
+               WHERE id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let tasks = sqlx::query_as!(
+            ArchivedTask,
+            r#"SELECT id as "id!: Uuid", title, description, status as "status!: TaskStatus", priority as "priority!: i64", complexity_score as "complexity_score!: f32", timeout_seconds as "timeout_seconds: i64", executor_model, parent_task_id as "parent_task_id: Uuid", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", archived_at as "archived_at: DateTime<Utc>", started_at as "started_at: DateTime<Utc>", completed_at as "completed_at: DateTime<Utc>", total_execution_ms as "total_execution_ms: i64"
+               FROM tasks
+               WHERE project_id = $1
+               ORDER BY created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let task_attempts = sqlx::query_as!(
+            ArchivedTaskAttempt,
+            r#"SELECT ta.id as "id!: Uuid", ta.task_id as "task_id!: Uuid", ta.worktree_path, ta.branch, ta.base_branch, ta.executor, ta.merge_commit, ta.pr_url, ta.pr_number, ta.pr_status, ta.created_at as "created_at!: DateTime<Utc>"
+               FROM task_attempts ta
+               JOIN tasks t ON t.id = ta.task_id
+               WHERE t.project_id = $1
+               ORDER BY ta.created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let runs = sqlx::query!(
+            r#"SELECT ep.id as "id!: Uuid", ep.task_attempt_id as "task_attempt_id!: Uuid", ep.executor_type, ep.status as "status!: ExecutionProcessStatus", ep.command, ep.args, ep.working_directory, ep.exit_code, ep.had_tool_errors as "had_tool_errors!: bool", ep.normalized_json, ep.stdout, ep.stdout_zstd, ep.started_at as "started_at!: DateTime<Utc>", ep.completed_at as "completed_at: DateTime<Utc>"
+               FROM execution_processes ep
+               JOIN task_attempts ta ON ta.id = ep.task_attempt_id
+               JOIN tasks t ON t.id = ta.task_id
+               WHERE t.project_id = $1
+                 AND ep.process_type = 'codingagent'
+               ORDER BY ep.started_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+        let mut task_runs = Vec::with_capacity(runs.len());
+        for run in runs {
+            // Output of running processes is not compressed yet
+            let logs_zstd = match (run.stdout_zstd, run.stdout) {
+                (Some(compressed), _) => Some(compressed),
+                (None, Some(stdout)) => Some(compress_log(&stdout)?.0),
+                (None, None) => None,
+            };
+            task_runs.push(ArchivedTaskRun {
+                id: run.id,
+                task_attempt_id: run.task_attempt_id,
+                executor_type: run.executor_type,
+                status: run.status,
+                command: run.command,
+                args: run.args,
+                working_directory: run.working_directory,
+                exit_code: run.exit_code,
+                had_tool_errors: run.had_tool_errors,
+                normalized_json: run.normalized_json,
+                logs_zstd_base64: logs_zstd.map(|bytes| BASE64.encode(bytes)),
+                started_at: run.started_at,
+                completed_at: run.completed_at,
+            });
+        }
+
+        let labels = Label::find_by_project_id(pool, project_id)
+            .await?
+            .into_iter()
+            .map(|label| ArchivedLabel {
+                id: label.id,
+                name: label.name,
+                color_hex: label.color_hex,
+            })
+            .collect();
+
+        let task_labels = sqlx::query_as!(
+            ArchivedTaskLabel,
+            r#"SELECT tl.task_id as "task_id!: Uuid", tl.label_id as "label_id!: Uuid"
+               FROM task_labels tl
+               JOIN tasks t ON t.id = tl.task_id
+               WHERE t.project_id = $1
+               ORDER BY tl.created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let task_dependencies = sqlx::query_as!(
+            ArchivedTaskDependency,
+            r#"SELECT d.task_id as "task_id!: Uuid", d.depends_on_task_id as "depends_on_task_id!: Uuid"
+               FROM task_dependencies d
+               JOIN tasks t ON t.id = d.task_id
+               WHERE t.project_id = $1
+               ORDER BY d.created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let task_env_vars = sqlx::query_as!(
+            ArchivedTaskEnvVar,
+            r#"SELECT v.task_id as "task_id!: Uuid", v.key
+               FROM task_env_vars v
+               JOIN tasks t ON t.id = v.task_id
+               WHERE t.project_id = $1
+               ORDER BY v.task_id ASC, v.key ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let executor_config = ProjectExecutorConfig::find_by_project_id(pool, project_id)
+            .await?
+            .map(|config| ArchivedExecutorConfig {
+                executor_type: config.executor_type,
+                binary_path: config.binary_path,
+                model: config.model,
+                extra_flags: config.extra_flags,
+                use_plan_mode: config.use_plan_mode,
+            });
+
+        let webhooks = Webhook::find_by_project_id(pool, project_id)
+            .await?
+            .into_iter()
+            .map(|webhook| ArchivedWebhook {
+                url: redact_url(&webhook.url),
+                events: webhook.events.0,
+            })
+            .collect();
+
+        Ok(Some(Self {
+            schema_version: ARCHIVE_SCHEMA_VERSION,
+            project,
+            tasks,
+            task_attempts,
+            task_runs,
+            labels,
+            task_labels,
+            task_dependencies,
+            task_env_vars,
+            executor_config,
+            webhooks,
+        }))
+    }
+
+    /// Create the archived project and everything it owns under new ids. The project is
+    /// created in the database holding the projects and what it owns in the project's own
+    /// database, in one transaction; if that fails the project is deleted again. Importing an
+    /// archive of the same project and schema version again returns the project created the
+    /// first time.
+    pub async fn import(&self, app_state: &AppState) -> Result<ImportedProject, ArchiveError> {
+        if self.schema_version != ARCHIVE_SCHEMA_VERSION {
+            return Err(ArchiveError::UnsupportedSchemaVersion(self.schema_version));
+        }
+
+        let meta_pool = &app_state.db_pool;
+        let existing = sqlx::query_scalar!(
+            r#"SELECT project_id as "project_id!: Uuid"
+               FROM import_receipts
+               WHERE original_project_id = $1 AND schema_version = $2"#,
+            self.project.id,
+            self.schema_version
+        )
+        .fetch_optional(meta_pool)
+        .await?;
+        if let Some(project_id) = existing {
+            return Ok(ImportedProject {
+                project_id,
+                already_imported: true,
+            });
+        }
+
+        let project_id = Uuid::new_v4();
+        let project = &self.project;
+        sqlx::query!(
+            r#"INSERT INTO projects (id, name, git_repo_path, setup_script, dev_script, base_branch, default_labels, use_commit_template, git_commit_template, priority_boost_after_seconds, system_prompt, prompt_prefix, monthly_cost_limit_usd)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)"#,
+            project_id,
+            project.name,
+            project.git_repo_path,
+            project.setup_script,
+            project.dev_script,
+            project.base_branch,
+            project.default_labels,
+            project.use_commit_template,
+            project.git_commit_template,
+            project.priority_boost_after_seconds,
+            project.system_prompt,
+            project.prompt_prefix,
+            project.monthly_cost_limit_usd
+        )
+        .execute(meta_pool)
+        .await
+        .map_err(|e| match e.as_database_error() {
+            Some(db_error) if db_error.is_unique_violation() => {
+                ArchiveError::DuplicateRepoPath(project.git_repo_path.clone())
+            }
+            _ => ArchiveError::Database(e),
+        })?;
+
+        let contents = async {
+            let pool = app_state.project_pool(project_id).await?;
+            let mut tx = pool.begin().await?;
+            self.import_contents(&mut tx, project_id).await?;
+            tx.commit().await?;
+            Ok::<_, ArchiveError>(())
+        }
+        .await;
+        if let Err(e) = contents {
+            if let Err(e) = Project::delete(meta_pool, project_id).await {
+                tracing::error!(
+                    "Failed to delete partly imported project {}: {}",
+                    project_id,
+                    e
+                );
+            }
+            if let Err(e) = app_state.remove_project_database(project_id).await {
+                tracing::error!(
+                    "Failed to remove database of partly imported project {}: {}",
+                    project_id,
+                    e
+                );
+            }
+            return Err(e);
+        }
+
+        sqlx::query!(
+            "INSERT INTO import_receipts (original_project_id, schema_version, project_id) VALUES ($1, $2, $3)",
+            self.project.id,
+            self.schema_version,
+            project_id
+        )
+        .execute(meta_pool)
+        .await?;
+
+        Ok(ImportedProject {
+            project_id,
+            already_imported: false,
+        })
+    }
+
+    /// Insert everything the archived project owns under new ids into `project_id`
+    async fn import_contents(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        project_id: Uuid,
+    ) -> Result<(), ArchiveError> {
+        let task_ids: HashMap<Uuid, Uuid> = self
+            .tasks
+            .iter()
+            .map(|task| (task.id, Uuid::new_v4()))
+            .collect();
+        let attempt_ids: HashMap<Uuid, Uuid> = self
+            .task_attempts
+            .iter()
+            .map(|attempt| (attempt.id, Uuid::new_v4()))
+            .collect();
+        let label_ids: HashMap<Uuid, Uuid> = self
+            .labels
+            .iter()
+            .map(|label| (label.id, Uuid::new_v4()))
+            .collect();
+
+        for label in &self.labels {
+            let id = label_ids[&label.id];
+            sqlx::query!(
+                "INSERT INTO labels (id, project_id, name, color_hex) VALUES ($1, $2, $3, $4)",
+                id,
+                project_id,
+                label.name,
+                label.color_hex
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        // Parent links are set once every task and attempt they may point to exists
+        for task in &self.tasks {
+            let id = task_ids[&task.id];
+            sqlx::query!(
+                r#"INSERT INTO tasks (id, project_id, title, description, status, priority, complexity_score, timeout_seconds, executor_model, created_at, archived_at, started_at, completed_at, total_execution_ms)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)"#,
+                id,
+                project_id,
+                task.title,
+                task.description,
+                task.status,
+                task.priority,
+                task.complexity_score,
+                task.timeout_seconds,
+                task.executor_model,
+                task.created_at,
+                task.archived_at,
+                task.started_at,
+                task.completed_at,
+                task.total_execution_ms
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        for attempt in &self.task_attempts {
+            let id = attempt_ids[&attempt.id];
+            let task_id = remap(&task_ids, attempt.task_id, "Task")?;
+            sqlx::query!(
+                r#"INSERT INTO task_attempts (id, task_id, worktree_path, branch, base_branch, executor, merge_commit, pr_url, pr_number, pr_status, created_at, worktree_deleted)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, TRUE)"#,
+                id,
+                task_id,
+                attempt.worktree_path,
+                attempt.branch,
+                attempt.base_branch,
+                attempt.executor,
+                attempt.merge_commit,
+                attempt.pr_url,
+                attempt.pr_number,
+                attempt.pr_status,
+                attempt.created_at
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        for task in &self.tasks {
+            if task.parent_task_id.is_none() && task.parent_task_attempt.is_none() {
+                continue;
+            }
+            let id = task_ids[&task.id];
+            let parent_task_id = task
+                .parent_task_id
+                .map(|parent| remap(&task_ids, parent, "Parent task"))
+                .transpose()?;
+            let parent_task_attempt = task
+                .parent_task_attempt
+                .map(|parent| remap(&attempt_ids, parent, "Parent task attempt"))
+                .transpose()?;
+            sqlx::query!(
+                "UPDATE tasks SET parent_task_id = $2, parent_task_attempt = $3 WHERE id = $1",
+                id,
+                parent_task_id,
+                parent_task_attempt
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        for run in &self.task_runs {
+            let id = Uuid::new_v4();
+            let attempt_id = remap(&attempt_ids, run.task_attempt_id, "Task attempt")?;
+            let stdout_zstd = run
+                .logs_zstd_base64
+                .as_deref()
+                .map(|encoded| {
+                    let bytes = BASE64.decode(encoded).map_err(|e| {
+                        ArchiveError::Invalid(format!("Logs of run {}: {}", run.id, e))
+                    })?;
+                    validate_log_frame(&bytes).map_err(|e| {
+                        ArchiveError::Invalid(format!("Logs of run {}: {}", run.id, e))
+                    })?;
+                    Ok::<_, ArchiveError>(bytes)
+                })
+                .transpose()?;
+            // No process is behind an imported run
+            let status = match run.status {
+                ExecutionProcessStatus::Running => ExecutionProcessStatus::Killed,
+                ref status => status.clone(),
+            };
+            sqlx::query!(
+                r#"INSERT INTO execution_processes (id, task_attempt_id, process_type, executor_type, status, command, args, working_directory, exit_code, had_tool_errors, normalized_json, stdout_zstd, started_at, completed_at)
+                   VALUES ($1, $2, 'codingagent', $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)"#,
+                id,
+                attempt_id,
+                run.executor_type,
+                status,
+                run.command,
+                run.args,
+                run.working_directory,
+                run.exit_code,
+                run.had_tool_errors,
+                run.normalized_json,
+                stdout_zstd,
+                run.started_at,
+                run.completed_at
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        for task_label in &self.task_labels {
+            let task_id = remap(&task_ids, task_label.task_id, "Task")?;
+            let label_id = remap(&label_ids, task_label.label_id, "Label")?;
+            sqlx::query!(
+                "INSERT INTO task_labels (task_id, label_id) VALUES ($1, $2)",
+                task_id,
+                label_id
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        for dependency in &self.task_dependencies {
+            let task_id = remap(&task_ids, dependency.task_id, "Task")?;
+            let depends_on_task_id = remap(&task_ids, dependency.depends_on_task_id, "Task")?;
+            sqlx::query!(
+                "INSERT INTO task_dependencies (task_id, depends_on_task_id) VALUES ($1, $2)",
+                task_id,
+                depends_on_task_id
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        for env_var in &self.task_env_vars {
+            let task_id = remap(&task_ids, env_var.task_id, "Task")?;
+            sqlx::query!(
+                "INSERT INTO task_env_vars (task_id, key, value) VALUES ($1, $2, X'')",
+                task_id,
+                env_var.key
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        if let Some(config) = &self.executor_config {
+            sqlx::query!(
+                r#"INSERT INTO project_executor_config (project_id, executor_type, binary_path, model, extra_flags, use_plan_mode)
+                   VALUES ($1, $2, $3, $4, $5, $6)"#,
+                project_id,
+                config.executor_type,
+                config.binary_path,
+                config.model,
+                config.extra_flags,
+                config.use_plan_mode
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{config::Config, label::CreateLabel, task::Task, webhook::CreateWebhook},
+        test_fixtures::{create_attempt, create_project, create_task, memory_pool},
+        utils::log_compression::decompress_log,
+    };
+
+    /// State of an instance storing everything in `pool`
+    async fn app_state(pool: &SqlitePool) -> AppState {
+        AppState::new(
+            pool.clone(),
+            std::sync::Arc::new(tokio::sync::RwLock::new(Config::default())),
+        )
+        .await
+    }
+
+    async fn create_run(pool: &SqlitePool, attempt_id: Uuid, log: &str) {
+        let (stdout_zstd, _) = compress_log(log).unwrap();
+        sqlx::query(
+            "INSERT INTO execution_processes (id, task_attempt_id, process_type, executor_type, status, command, working_directory, exit_code, stdout_zstd)
+             VALUES ($1, $2, 'codingagent', 'claude', 'completed', 'claude', '/tmp/worktree', 0, $3)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(attempt_id)
+        .bind(stdout_zstd)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    /// Project with three tasks, the last a sub-task of the first and a chain of dependencies
+    /// through all three, two runs, one label and one environment variable
+    async fn seed_project(pool: &SqlitePool) -> Uuid {
+        let project_id = create_project(pool).await;
+
+        let api = create_task(pool, project_id, "Add the endpoint").await;
+        let docs = create_task(pool, project_id, "Document the endpoint").await;
+        let tests = create_task(pool, project_id, "Test the endpoint").await;
+        sqlx::query("UPDATE tasks SET parent_task_id = $1 WHERE id = $2")
+            .bind(api)
+            .bind(tests)
+            .execute(pool)
+            .await
+            .unwrap();
+
+        let mut tx = pool.begin().await.unwrap();
+        Task::add_dependency(&mut tx, docs, api).await.unwrap();
+        Task::add_dependency(&mut tx, tests, docs).await.unwrap();
+        tx.commit().await.unwrap();
+        sqlx::query("INSERT INTO task_env_vars (task_id, key, value) VALUES ($1, $2, $3)")
+            .bind(api)
+            .bind("API_TOKEN")
+            .bind(b"sealed-by-the-source".to_vec())
+            .execute(pool)
+            .await
+            .unwrap();
+
+        let api_attempt = create_attempt(pool, api, "/tmp/worktrees/api").await;
+        create_run(pool, api_attempt, "{\"type\":\"system\"}\n").await;
+        let docs_attempt = create_attempt(pool, docs, "/tmp/worktrees/docs").await;
+        create_run(pool, docs_attempt, "{\"type\":\"result\"}\n").await;
+
+        let label = Label::create(
+            pool,
+            &CreateLabel {
+                project_id,
+                name: "backend".to_string(),
+                color_hex: Some("#ff0000".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+        Label::attach(pool, api, label.id).await.unwrap();
+        Label::attach(pool, tests, label.id).await.unwrap();
+
+        Webhook::create(
+            pool,
+            project_id,
+            &CreateWebhook {
+                url: "https://hooks.example.com/services/T000/B000/secret-token".to_string(),
+                events: vec!["task.completed".to_string()],
+                secret: "hmac-key".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        project_id
+    }
+
+    /// Id, title and parent of the project's tasks
+    async fn task_rows(pool: &SqlitePool, project_id: Uuid) -> Vec<(Uuid, String, Option<Uuid>)> {
+        sqlx::query_as("SELECT id, title, parent_task_id FROM tasks WHERE project_id = $1")
+            .bind(project_id)
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn run_logs(pool: &SqlitePool, task_id: Uuid) -> Vec<String> {
+        let compressed: Vec<Vec<u8>> = sqlx::query_scalar(
+            "SELECT ep.stdout_zstd
+             FROM execution_processes ep
+             JOIN task_attempts ta ON ta.id = ep.task_attempt_id
+             WHERE ta.task_id = $1",
+        )
+        .bind(task_id)
+        .fetch_all(pool)
+        .await
+        .unwrap();
+        compressed
+            .iter()
+            .map(|bytes| decompress_log(bytes).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_archive_round_trips_between_instances() {
+        let source = memory_pool().await;
+        let original_id = seed_project(&source).await;
+
+        let archive = ProjectArchive::export(&source, original_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            archive.webhooks[0].url,
+            "https://hooks.example.com/[redacted]"
+        );
+        // Archives travel as JSON
+        let archive: ProjectArchive =
+            serde_json::from_str(&serde_json::to_string(&archive).unwrap()).unwrap();
+
+        let target = memory_pool().await;
+        let imported = archive.import(&app_state(&target).await).await.unwrap();
+        assert!(!imported.already_imported);
+        let project_id = imported.project_id;
+        assert_ne!(project_id, original_id);
+
+        let tasks = task_rows(&target, project_id).await;
+        assert_eq!(tasks.len(), 3);
+        let task_id = |title: &str| tasks.iter().find(|task| task.1 == title).unwrap().0;
+        let (api, docs, tests) = (
+            task_id("Add the endpoint"),
+            task_id("Document the endpoint"),
+            task_id("Test the endpoint"),
+        );
+        let parent = |id: Uuid| tasks.iter().find(|task| task.0 == id).unwrap().2;
+        assert_eq!(parent(tests), Some(api));
+        assert_eq!(parent(api), None);
+        assert!(archive.tasks.iter().all(|task| task.id != api));
+
+        assert_eq!(
+            run_logs(&target, api).await,
+            vec!["{\"type\":\"system\"}\n"]
+        );
+        assert_eq!(
+            run_logs(&target, docs).await,
+            vec!["{\"type\":\"result\"}\n"]
+        );
+        assert!(run_logs(&target, tests).await.is_empty());
+
+        let labels = Label::find_by_project_id(&target, project_id)
+            .await
+            .unwrap();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].color_hex, "#ff0000");
+        assert_eq!(Task::labels(&target, api).await.unwrap(), vec!["backend"]);
+        assert!(Task::labels(&target, docs).await.unwrap().is_empty());
+        assert_eq!(Task::labels(&target, tests).await.unwrap(), vec!["backend"]);
+
+        for (task, blocker) in [(api, None), (docs, Some(api)), (tests, Some(docs))] {
+            let blocked_by: Vec<Uuid> = Task::get_blocked_by(&target, task)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|task| task.id)
+                .collect();
+            assert_eq!(blocked_by, blocker.into_iter().collect::<Vec<_>>());
+        }
+
+        // The value was sealed with the source's key, so only the name comes across
+        assert_eq!(
+            Task::env_var_keys(&target, api).await.unwrap(),
+            vec!["API_TOKEN"]
+        );
+        assert!(Task::get_env_vars(&target, api).await.unwrap().is_empty());
+
+        // Webhooks are not recreated from redacted URLs
+        assert!(Webhook::find_by_project_id(&target, project_id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_importing_an_archive_twice_returns_the_first_project() {
+        let source = memory_pool().await;
+        let original_id = seed_project(&source).await;
+        let archive = ProjectArchive::export(&source, original_id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let target = app_state(&memory_pool().await).await;
+        let first = archive.import(&target).await.unwrap();
+        let second = archive.import(&target).await.unwrap();
+        assert_eq!(
+            second,
+            ImportedProject {
+                project_id: first.project_id,
+                already_imported: true,
+            }
+        );
+
+        let mut newer = archive.clone();
+        newer.schema_version = ARCHIVE_SCHEMA_VERSION + 1;
+        assert!(matches!(
+            newer.import(&target).await,
+            Err(ArchiveError::UnsupportedSchemaVersion(_))
+        ));
+    }
+}
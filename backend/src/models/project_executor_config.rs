@@ -0,0 +1,137 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{types::Json, FromRow, Sqlite, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::executors::claude::StdinFormat;
+
+/// Claude CLI settings a project's runs use instead of the globally detected command
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ProjectExecutorConfig {
+    pub project_id: Uuid,
+    /// Executor the settings apply to, e.g. `claude` or `claude-plan`
+    pub executor_type: String,
+    /// Path or command that starts the CLI, e.g. `/opt/claude-1.0/bin/claude`
+    pub binary_path: String,
+    /// Passed as `--model`; a task's own model takes precedence
+    pub model: Option<String>,
+    /// Appended verbatim to the command line
+    pub extra_flags: Option<String>,
+    pub use_plan_mode: bool,
+    /// Domains web fetches are limited to, subdomains included; `None` allows every domain
+    /// that is not denied
+    #[ts(type = "Array<string> | null")]
+    pub web_allowlist: Option<Json<Vec<String>>>,
+    /// Domains web fetches may not reach, even when allowlisted
+    #[ts(type = "Array<string>")]
+    pub web_denylist: Json<Vec<String>>,
+    /// How the prompt is written to the CLI's stdin
+    pub stdin_format: StdinFormat,
+    /// Resume the session of a run that timed out instead of failing the task
+    pub auto_resume_on_timeout: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct UpdateProjectExecutorConfig {
+    pub executor_type: String,
+    pub binary_path: String,
+    pub model: Option<String>,
+    pub extra_flags: Option<String>,
+    #[serde(default)]
+    pub use_plan_mode: bool,
+    #[serde(default)]
+    #[ts(optional)]
+    pub web_allowlist: Option<Vec<String>>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub web_denylist: Option<Vec<String>>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub stdin_format: Option<StdinFormat>,
+    #[serde(default)]
+    pub auto_resume_on_timeout: bool,
+}
+
+impl ProjectExecutorConfig {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectExecutorConfig,
+            r#"SELECT project_id as "project_id!: Uuid", executor_type, binary_path, model, extra_flags, use_plan_mode as "use_plan_mode!: bool", web_allowlist as "web_allowlist: Json<Vec<String>>", web_denylist as "web_denylist!: Json<Vec<String>>", stdin_format as "stdin_format!: StdinFormat", auto_resume_on_timeout as "auto_resume_on_timeout!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_executor_config
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// The configuration of the project an execution process belongs to
+    pub async fn find_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectExecutorConfig,
+            r#"SELECT pec.project_id as "project_id!: Uuid", pec.executor_type, pec.binary_path, pec.model, pec.extra_flags, pec.use_plan_mode as "use_plan_mode!: bool", pec.web_allowlist as "web_allowlist: Json<Vec<String>>", pec.web_denylist as "web_denylist!: Json<Vec<String>>", pec.stdin_format as "stdin_format!: StdinFormat", pec.auto_resume_on_timeout as "auto_resume_on_timeout!: bool", pec.created_at as "created_at!: DateTime<Utc>", pec.updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_executor_config pec
+               JOIN tasks t ON t.project_id = pec.project_id
+               JOIN task_attempts ta ON ta.task_id = t.id
+               JOIN execution_processes ep ON ep.task_attempt_id = ta.id
+               WHERE ep.id = $1"#,
+            execution_process_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Create the project's configuration or replace the existing one
+    pub async fn upsert<'e, E>(
+        executor: E,
+        project_id: Uuid,
+        data: &UpdateProjectExecutorConfig,
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
+        let web_allowlist = data.web_allowlist.clone().map(Json);
+        let web_denylist = Json(data.web_denylist.clone().unwrap_or_default());
+        let stdin_format = data.stdin_format.unwrap_or_default();
+        sqlx::query_as!(
+            ProjectExecutorConfig,
+            r#"INSERT INTO project_executor_config (project_id, executor_type, binary_path, model, extra_flags, use_plan_mode, web_allowlist, web_denylist, stdin_format, auto_resume_on_timeout)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+               ON CONFLICT(project_id) DO UPDATE SET
+                   executor_type = excluded.executor_type,
+                   binary_path = excluded.binary_path,
+                   model = excluded.model,
+                   extra_flags = excluded.extra_flags,
+                   use_plan_mode = excluded.use_plan_mode,
+                   web_allowlist = excluded.web_allowlist,
+                   web_denylist = excluded.web_denylist,
+                   stdin_format = excluded.stdin_format,
+                   auto_resume_on_timeout = excluded.auto_resume_on_timeout,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING project_id as "project_id!: Uuid", executor_type, binary_path, model, extra_flags, use_plan_mode as "use_plan_mode!: bool", web_allowlist as "web_allowlist: Json<Vec<String>>", web_denylist as "web_denylist!: Json<Vec<String>>", stdin_format as "stdin_format!: StdinFormat", auto_resume_on_timeout as "auto_resume_on_timeout!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            project_id,
+            data.executor_type,
+            data.binary_path,
+            data.model,
+            data.extra_flags,
+            data.use_plan_mode,
+            web_allowlist,
+            web_denylist,
+            stdin_format,
+            data.auto_resume_on_timeout
+        )
+        .fetch_one(executor)
+        .await
+    }
+}
@@ -1,9 +1,17 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool, Type};
+use sqlx::{types::Json, FromRow, QueryBuilder, Sqlite, SqlitePool, Transaction, Type};
 use ts_rs::TS;
 use uuid::Uuid;
 
+use super::{
+    label::{Label, TaskLabel},
+    project::Project,
+};
+use crate::utils::encryption::{EncryptionError, SecretKey};
+
 #[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS)]
 #[sqlx(type_name = "task_status", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
@@ -27,6 +35,20 @@ pub struct Task {
     pub parent_task_attempt: Option<Uuid>, // Foreign key to parent TaskAttempt
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Heuristic estimate of the work involved, from 0 to 10
+    pub complexity_score: f32,
+    /// Seconds an execution may run before it is killed, unconstrained when `None`
+    pub timeout_seconds: Option<i64>,
+    /// Executor session of the latest coding agent run, used to resume it in follow-ups
+    pub session_id: Option<String>,
+    /// Model Claude runs for this task use, e.g. `claude-haiku-4`; the CLI default when `None`
+    pub executor_model: Option<String>,
+    /// From 1 to 100; runs of higher-priority tasks take free executor slots first
+    pub priority: i64,
+    /// Task this one is a sub-task of
+    pub parent_task_id: Option<Uuid>,
+    /// When the task was deleted; archived tasks are purged for good after 30 days
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -40,10 +62,19 @@ pub struct TaskWithAttemptStatus {
     pub parent_task_attempt: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub complexity_score: f32,
+    pub timeout_seconds: Option<i64>,
     pub has_in_progress_attempt: bool,
     pub has_merged_attempt: bool,
     pub last_attempt_failed: bool,
     pub latest_attempt_executor: Option<String>,
+    pub parent_task_id: Option<Uuid>,
+    /// Direct sub-tasks of the task
+    pub subtask_count: i64,
+    pub completed_subtask_count: i64,
+    pub archived_at: Option<DateTime<Utc>>,
+    /// The task's labels, by name
+    pub labels: Vec<TaskLabel>,
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -53,6 +84,19 @@ pub struct CreateTask {
     pub title: String,
     pub description: Option<String>,
     pub parent_task_attempt: Option<Uuid>,
+    /// Falls back to the project's default labels when empty
+    #[ts(optional)]
+    pub labels: Option<Vec<String>>,
+    #[ts(optional)]
+    pub timeout_seconds: Option<i64>,
+    #[ts(optional)]
+    pub executor_model: Option<String>,
+    /// Defaults to 50
+    #[ts(optional)]
+    pub priority: Option<i64>,
+    /// Makes the new task a sub-task of another task of the same project
+    #[ts(optional)]
+    pub parent_task_id: Option<Uuid>,
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -63,6 +107,42 @@ pub struct CreateTaskAndStart {
     pub description: Option<String>,
     pub parent_task_attempt: Option<Uuid>,
     pub executor: Option<crate::executor::ExecutorConfig>,
+    #[ts(optional)]
+    pub labels: Option<Vec<String>>,
+    #[ts(optional)]
+    pub timeout_seconds: Option<i64>,
+    #[ts(optional)]
+    pub executor_model: Option<String>,
+}
+
+/// Priority of tasks created without one
+pub const DEFAULT_TASK_PRIORITY: i64 = 50;
+/// Priorities run from 1 (least urgent) to 100 (most urgent)
+pub const TASK_PRIORITY_RANGE: std::ops::RangeInclusive<i64> = 1..=100;
+
+/// Largest number of tasks a single batch may create
+pub const MAX_TASK_BATCH_SIZE: usize = 50;
+
+/// Days an archived task is kept before it is deleted for good
+pub const ARCHIVED_TASK_RETENTION_DAYS: i64 = 30;
+
+/// One task of a batch creation request
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct CreateTaskBatchItem {
+    /// Lets other tasks of the batch depend on this one; generated when omitted
+    #[ts(optional)]
+    pub id: Option<Uuid>,
+    pub title: String,
+    pub description: Option<String>,
+    /// Board column the task starts in, `todo` when omitted
+    #[ts(optional)]
+    pub status: Option<TaskStatus>,
+    #[ts(optional)]
+    pub executor_model: Option<String>,
+    /// Tasks of the batch or of the project this task waits for
+    #[serde(default)]
+    pub dependencies: Vec<Uuid>,
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -72,12 +152,563 @@ pub struct UpdateTask {
     pub description: Option<String>,
     pub status: Option<TaskStatus>,
     pub parent_task_attempt: Option<Uuid>,
+    /// Leaves the current timeout unchanged when omitted; 0 removes it
+    #[ts(optional)]
+    pub timeout_seconds: Option<i64>,
+    /// Leaves the current model unchanged when omitted; an empty string removes it
+    #[ts(optional)]
+    pub executor_model: Option<String>,
+    /// Leaves the current priority unchanged when omitted
+    #[ts(optional)]
+    pub priority: Option<i64>,
+}
+
+/// Deliberately not `Debug`, so the value cannot end up in logs
+#[derive(Deserialize, TS)]
+#[ts(export)]
+pub struct SetTaskEnvVar {
+    pub value: String,
+}
+
+/// A row of `task_dependencies`: `task_id` cannot start until `depends_on_task_id` is done
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TaskDependency {
+    pub task_id: Uuid,
+    pub depends_on_task_id: Uuid,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct AddTaskDependency {
+    pub depends_on_task_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct TaskGraphNode {
+    pub id: Uuid,
+    pub title: String,
+    pub status: TaskStatus,
+}
+
+/// Edge pointing from the blocking task to the task that depends on it
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct TaskGraphEdge {
+    pub from: Uuid,
+    pub to: Uuid,
+    /// True once the blocking task is done
+    pub satisfied: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct TaskGraph {
+    pub nodes: Vec<TaskGraphNode>,
+    pub edges: Vec<TaskGraphEdge>,
+}
+
+impl TaskGraph {
+    pub fn new(tasks: Vec<Task>, dependencies: Vec<TaskDependency>) -> Self {
+        let edges = dependencies
+            .into_iter()
+            .map(|dependency| TaskGraphEdge {
+                satisfied: tasks.iter().any(|task| {
+                    task.id == dependency.depends_on_task_id && task.status == TaskStatus::Done
+                }),
+                from: dependency.depends_on_task_id,
+                to: dependency.task_id,
+            })
+            .collect();
+
+        let nodes = tasks
+            .into_iter()
+            .map(|task| TaskGraphNode {
+                id: task.id,
+                title: task.title,
+                status: task.status,
+            })
+            .collect();
+
+        Self { nodes, edges }
+    }
+
+    /// Render the graph in Graphviz DOT format
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n    rankdir=LR;\n    node [shape=box];\n");
+
+        for node in &self.nodes {
+            let status = match node.status {
+                TaskStatus::Todo => "todo",
+                TaskStatus::InProgress => "in progress",
+                TaskStatus::InReview => "in review",
+                TaskStatus::Done => "done",
+                TaskStatus::Cancelled => "cancelled",
+            };
+            dot.push_str(&format!(
+                "    \"{}\" [label=\"{}\\n({})\"];\n",
+                node.id,
+                escape_dot_label(&node.title),
+                status
+            ));
+        }
+
+        for edge in &self.edges {
+            let style = if edge.satisfied {
+                "style=solid, color=darkgreen"
+            } else {
+                "style=dashed, color=red"
+            };
+            dot.push_str(&format!(
+                "    \"{}\" -> \"{}\" [{}];\n",
+                edge.from, edge.to, style
+            ));
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Map every task in the graph to the tasks it depends on
+    pub fn adjacency_list(&self) -> BTreeMap<Uuid, Vec<Uuid>> {
+        let mut adjacency: BTreeMap<Uuid, Vec<Uuid>> = self
+            .nodes
+            .iter()
+            .map(|node| (node.id, Vec::new()))
+            .collect();
+        for edge in &self.edges {
+            adjacency.entry(edge.to).or_default().push(edge.from);
+        }
+        adjacency
+    }
+}
+
+#[derive(Debug)]
+pub enum TaskDependencyError {
+    Database(sqlx::Error),
+    /// One of the tasks does not exist, or they belong to different projects
+    TaskNotFound,
+    /// The dependency would close a cycle; the path leads from the new dependency back to
+    /// the dependent task
+    Cycle(Vec<Uuid>),
+}
+
+impl std::fmt::Display for TaskDependencyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskDependencyError::Database(e) => write!(f, "Database error: {}", e),
+            TaskDependencyError::TaskNotFound => write!(f, "Task not found"),
+            TaskDependencyError::Cycle(path) => write!(
+                f,
+                "Dependency would create a cycle: {}",
+                path.iter()
+                    .map(Uuid::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" -> ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TaskDependencyError {}
+
+impl From<sqlx::Error> for TaskDependencyError {
+    fn from(err: sqlx::Error) -> Self {
+        TaskDependencyError::Database(err)
+    }
+}
+
+#[derive(Debug)]
+pub enum TaskBatchError {
+    Database(sqlx::Error),
+    TooManyTasks(usize),
+    /// A dependency is neither part of the batch nor a task of the project
+    UnresolvedDependency {
+        task_id: Uuid,
+        depends_on_task_id: Uuid,
+    },
+    /// Tasks of the batch depend on each other in a cycle
+    Cycle(Vec<Uuid>),
+}
+
+impl std::fmt::Display for TaskBatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskBatchError::Database(e) => write!(f, "Database error: {}", e),
+            TaskBatchError::TooManyTasks(count) => write!(
+                f,
+                "A batch may create at most {} tasks, got {}",
+                MAX_TASK_BATCH_SIZE, count
+            ),
+            TaskBatchError::UnresolvedDependency {
+                task_id,
+                depends_on_task_id,
+            } => write!(
+                f,
+                "Task {} depends on {}, which is not in the batch or the project",
+                task_id, depends_on_task_id
+            ),
+            TaskBatchError::Cycle(path) => write!(
+                f,
+                "Dependencies form a cycle: {}",
+                path.iter()
+                    .map(Uuid::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" -> ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TaskBatchError {}
+
+impl From<sqlx::Error> for TaskBatchError {
+    fn from(err: sqlx::Error) -> Self {
+        TaskBatchError::Database(err)
+    }
+}
+
+#[derive(Debug)]
+pub enum TaskEnvVarError {
+    Database(sqlx::Error),
+    Encryption(EncryptionError),
+}
+
+impl std::fmt::Display for TaskEnvVarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskEnvVarError::Database(e) => write!(f, "Database error: {}", e),
+            TaskEnvVarError::Encryption(e) => write!(f, "Encryption error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TaskEnvVarError {}
+
+impl From<sqlx::Error> for TaskEnvVarError {
+    fn from(err: sqlx::Error) -> Self {
+        TaskEnvVarError::Database(err)
+    }
+}
+
+impl From<EncryptionError> for TaskEnvVarError {
+    fn from(err: EncryptionError) -> Self {
+        TaskEnvVarError::Encryption(err)
+    }
+}
+
+/// Find a chain of dependencies leading from `from` to `to` with a depth-first search
+fn dependency_path(edges: &[TaskDependency], from: Uuid, to: Uuid) -> Option<Vec<Uuid>> {
+    let mut adjacency: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for edge in edges {
+        adjacency
+            .entry(edge.task_id)
+            .or_default()
+            .push(edge.depends_on_task_id);
+    }
+
+    let mut visited = HashSet::new();
+    let mut stack = vec![vec![from]];
+    while let Some(path) = stack.pop() {
+        let current = *path.last().expect("paths are never empty");
+        if current == to {
+            return Some(path);
+        }
+        if !visited.insert(current) {
+            continue;
+        }
+        for next in adjacency.get(&current).into_iter().flatten() {
+            if !visited.contains(next) {
+                let mut next_path = path.clone();
+                next_path.push(*next);
+                stack.push(next_path);
+            }
+        }
+    }
+    None
+}
+
+fn escape_dot_label(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// A completed task offered as a reference when starting a similar one
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SimilarCompletion {
+    pub task_id: Uuid,
+    pub title: String,
+    /// When the task was last updated, i.e. moved to done
+    pub completed_at: DateTime<Utc>,
+    /// First sentence of the summary of the task's latest coding agent run
+    pub summary: Option<String>,
+}
+
+/// A task's direct sub-tasks and how far along they are
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct TaskSubtasks {
+    pub subtasks: Vec<Task>,
+    /// Share of the sub-tasks that are done, from 0 to 100
+    pub completion_percentage: f32,
+}
+
+/// How much work a task looks like and how long a coding agent is expected to take on it
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+#[ts(export)]
+pub struct TaskEstimate {
+    pub word_count: u32,
+    /// Heuristic from 0 to 10, see [`Task::complexity_score`]
+    pub complexity_score: f32,
+    /// `None` until the project has a completed coding agent run
+    pub estimated_completion_ms: Option<i64>,
+}
+
+/// Most similar completions returned for a task
+const MAX_SIMILAR_COMPLETIONS: i64 = 5;
+
+/// When the task's latest coding agent run started and ended
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TaskTimeTracking {
+    /// `None` before the task's first run
+    pub started_at: Option<DateTime<Utc>>,
+    /// `None` while the run is going
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Milliseconds from `started_at` to `completed_at`
+    pub total_execution_ms: Option<i64>,
+}
+
+/// How long the runs of a project's tasks took
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TaskTimingStats {
+    /// Tasks whose latest run completed within the time window
+    pub completed_tasks: usize,
+    pub avg_task_execution_ms: Option<f64>,
+    pub p95_task_execution_ms: Option<i64>,
+}
+
+impl TaskTimingStats {
+    fn from_sorted_durations(durations_ms: &[i64]) -> Self {
+        let avg_task_execution_ms = (!durations_ms.is_empty()).then(|| {
+            durations_ms.iter().map(|ms| *ms as f64).sum::<f64>() / durations_ms.len() as f64
+        });
+        Self {
+            completed_tasks: durations_ms.len(),
+            avg_task_execution_ms,
+            p95_task_execution_ms: percentile_disc(durations_ms, 0.95),
+        }
+    }
+}
+
+/// The first of the ascending `sorted` values at or above the `fraction` percentile, like
+/// SQL's `percentile_disc`, which SQLite lacks
+fn percentile_disc(sorted: &[i64], fraction: f64) -> Option<i64> {
+    let rank = (fraction * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.max(1) - 1).copied()
+}
+
+/// Build an FTS5 query matching any word of the task, or `None` if it has no words.
+/// Words are quoted so that FTS5 operators and punctuation in the task are taken literally.
+fn fts_match_query(title: &str, description: Option<&str>) -> Option<String> {
+    let mut terms: Vec<String> = task_words(title, description)
+        .flat_map(|word| word.split(|c: char| !c.is_alphanumeric()))
+        .filter(|term| term.len() > 2)
+        .map(|term| term.to_lowercase())
+        .collect();
+    terms.sort();
+    terms.dedup();
+    if terms.is_empty() {
+        return None;
+    }
+    Some(
+        terms
+            .iter()
+            .map(|term| format!("\"{}\"", term))
+            .collect::<Vec<_>>()
+            .join(" OR "),
+    )
+}
+
+/// The text up to and including the first sentence-ending punctuation, or the first line
+fn first_sentence(text: &str) -> String {
+    let text = text.trim();
+    let line = text.lines().next().unwrap_or_default();
+    let end = line
+        .char_indices()
+        .find(|(i, c)| {
+            matches!(c, '.' | '!' | '?')
+                && line[i + c.len_utf8()..]
+                    .chars()
+                    .next()
+                    .is_none_or(char::is_whitespace)
+        })
+        .map(|(i, c)| i + c.len_utf8())
+        .unwrap_or(line.len());
+    line[..end].to_string()
+}
+
+/// Extensions of files whose mention suggests the agent will have to touch them
+const SOURCE_FILE_EXTENSIONS: &[&str] = &[
+    "rs", "ts", "tsx", "js", "jsx", "py", "go", "java", "kt", "swift", "rb", "c", "h", "cpp", "cs",
+    "sql", "sh", "toml", "json", "yaml", "yml", "html", "css", "md",
+];
+
+/// Word stems suggesting a change that reaches beyond a single spot, e.g. "refactoring"
+const COMPLEXITY_INDICATORS: &[&str] = &["refactor", "migrat", "architect", "redesign", "rewrite"];
+
+fn task_words<'a>(title: &'a str, description: Option<&'a str>) -> impl Iterator<Item = &'a str> {
+    title
+        .split_whitespace()
+        .chain(description.unwrap_or_default().split_whitespace())
+}
+
+fn word_count(title: &str, description: Option<&str>) -> u32 {
+    task_words(title, description).count() as u32
+}
+
+/// Whether a word looks like a file name such as `src/main.rs` or `App.tsx`
+fn mentions_source_file(word: &str) -> bool {
+    let word = word.trim_matches(|c: char| !c.is_alphanumeric() && c != '_' && c != '/');
+    match word.rsplit_once('.') {
+        Some((stem, extension)) => !stem.is_empty() && SOURCE_FILE_EXTENSIONS.contains(&extension),
+        None => false,
+    }
+}
+
+/// Score from 0 to 10: a point per 100 words (at most 4), a point per distinct file mentioned
+/// (at most 3) and a point per complexity indicator present (at most 3)
+fn complexity_score(title: &str, description: Option<&str>) -> f32 {
+    let length = (word_count(title, description) as f32 / 100.0).min(4.0);
+    let files = task_words(title, description)
+        .filter(|word| mentions_source_file(word))
+        .collect::<HashSet<_>>()
+        .len()
+        .min(3);
+    let text = format!("{} {}", title, description.unwrap_or_default()).to_lowercase();
+    let indicators = COMPLEXITY_INDICATORS
+        .iter()
+        .filter(|indicator| text.contains(*indicator))
+        .count()
+        .min(3);
+    length + files as f32 + indicators as f32
 }
 
 impl Task {
+    /// Number of words in the title and description
+    pub fn word_count_estimate(&self) -> u32 {
+        word_count(&self.title, self.description.as_deref())
+    }
+
+    /// Heuristic estimate of the work involved, from 0 to 10, combining the length of the
+    /// task, the files it mentions and words such as "refactor" or "migrate"
+    pub fn complexity_score(&self) -> f32 {
+        complexity_score(&self.title, self.description.as_deref())
+    }
+
+    /// Estimate how long a coding agent will take on this task from the project's completed
+    /// coding agent runs, scaled by this task's complexity relative to the tasks they ran for.
+    /// Returns `None` until the project has a completed run.
+    pub async fn estimate_completion_time(
+        &self,
+        pool: &SqlitePool,
+    ) -> Result<Option<chrono::Duration>, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"SELECT AVG((julianday(ep.completed_at) - julianday(ep.started_at)) * 86400.0) as "average_seconds: f64",
+                      AVG(t.complexity_score) as "average_complexity: f64"
+               FROM execution_processes ep
+               JOIN task_attempts ta ON ta.id = ep.task_attempt_id
+               JOIN tasks t ON t.id = ta.task_id
+               WHERE t.project_id = $1
+                 AND ep.process_type = 'codingagent'
+                 AND ep.status = 'completed'
+                 AND ep.completed_at IS NOT NULL"#,
+            self.project_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        let (Some(average_seconds), Some(average_complexity)) =
+            (record.average_seconds, record.average_complexity)
+        else {
+            return Ok(None);
+        };
+        // Adding one keeps trivial tasks from scaling the estimate down to nothing
+        let factor = (self.complexity_score as f64 + 1.0) / (average_complexity + 1.0);
+        Ok(Some(chrono::Duration::milliseconds(
+            (average_seconds * factor * 1000.0) as i64,
+        )))
+    }
+
+    /// The task's size and expected completion time, for showing before it is started
+    pub async fn estimate(&self, pool: &SqlitePool) -> Result<TaskEstimate, sqlx::Error> {
+        let estimated_completion_time = self.estimate_completion_time(pool).await?;
+        Ok(TaskEstimate {
+            word_count: self.word_count_estimate(),
+            complexity_score: self.complexity_score(),
+            estimated_completion_ms: estimated_completion_time
+                .map(|duration| duration.num_milliseconds()),
+        })
+    }
+
+    /// Find up to five done tasks in the same project whose title and description are most
+    /// similar to this task's, best match first
+    pub async fn find_similar_completions(
+        &self,
+        pool: &SqlitePool,
+    ) -> Result<Vec<SimilarCompletion>, sqlx::Error> {
+        let Some(match_query) = fts_match_query(&self.title, self.description.as_deref()) else {
+            return Ok(vec![]);
+        };
+        let limit = MAX_SIMILAR_COMPLETIONS;
+
+        let records = sqlx::query!(
+            r#"SELECT t.id as "id!: Uuid", t.title, t.updated_at as "completed_at!: DateTime<Utc>",
+                      (SELECT es.summary
+                       FROM executor_sessions es
+                       JOIN task_attempts ta ON ta.id = es.task_attempt_id
+                       WHERE ta.task_id = t.id AND es.summary IS NOT NULL
+                       ORDER BY es.created_at DESC
+                       LIMIT 1) as "summary: String"
+               FROM tasks_fts
+               JOIN tasks t ON t.id = tasks_fts.task_id
+               WHERE tasks_fts MATCH $1
+                 AND t.project_id = $2
+                 AND t.id != $3
+                 AND t.status = 'done'
+               ORDER BY bm25(tasks_fts)
+               LIMIT $4"#,
+            match_query,
+            self.project_id,
+            self.id,
+            limit
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|rec| SimilarCompletion {
+                task_id: rec.id,
+                title: rec.title,
+                completed_at: rec.completed_at,
+                summary: rec.summary.as_deref().map(first_sentence),
+            })
+            .collect())
+    }
+
+    /// List the project's tasks, newest first. Archived tasks are left out unless
+    /// `include_archived` is set.
     pub async fn find_by_project_id_with_attempt_status(
         pool: &SqlitePool,
         project_id: Uuid,
+        include_archived: bool,
+        label: Option<&str>,
     ) -> Result<Vec<TaskWithAttemptStatus>, sqlx::Error> {
         let records = sqlx::query!(
             r#"SELECT 
@@ -89,6 +720,8 @@ impl Task {
             t.parent_task_attempt AS "parent_task_attempt: Uuid", 
             t.created_at                AS "created_at!: DateTime<Utc>",
             t.updated_at                AS "updated_at!: DateTime<Utc>",
+            t.complexity_score          AS "complexity_score!: f32",
+            t.timeout_seconds           AS "timeout_seconds: i64",
             CASE 
               WHEN ip.task_id IS NOT NULL THEN true 
               ELSE false 
@@ -101,7 +734,21 @@ impl Task {
               WHEN fa.task_id IS NOT NULL THEN true 
               ELSE false 
             END                         AS "last_attempt_failed!: i64",
-            latest_executor_attempts.executor AS "latest_attempt_executor"
+            latest_executor_attempts.executor AS "latest_attempt_executor",
+            t.parent_task_id            AS "parent_task_id: Uuid",
+            COALESCE(st.total, 0)       AS "subtask_count!: i64",
+            COALESCE(st.done, 0)        AS "completed_subtask_count!: i64",
+            t.archived_at               AS "archived_at: DateTime<Utc>",
+            (
+                SELECT json_group_array(json_object('id', lower(hex(tl.id)), 'name', tl.name, 'color_hex', tl.color_hex))
+                FROM (
+                    SELECT l.id, l.name, l.color_hex
+                    FROM task_labels x
+                    JOIN labels l ON l.id = x.label_id
+                    WHERE x.task_id = t.id
+                    ORDER BY l.name
+                ) tl
+            )                           AS "labels!: Json<Vec<TaskLabel>>"
         FROM tasks t
 
         -- in-progress if any running setupscript/codingagent
@@ -157,10 +804,29 @@ impl Task {
         ) latest_executor_attempts 
         ON t.id = latest_executor_attempts.task_id
 
-        WHERE t.project_id = $1
+        -- count the direct sub-tasks and how many of them are done
+        LEFT JOIN (
+            SELECT parent_task_id,
+                   COUNT(*) AS total,
+                   SUM(CASE WHEN status = 'done' THEN 1 ELSE 0 END) AS done
+            FROM tasks
+            WHERE parent_task_id IS NOT NULL AND archived_at IS NULL
+            GROUP BY parent_task_id
+        ) st
+          ON t.id = st.parent_task_id
+
+        WHERE t.project_id = $1 AND ($2 OR t.archived_at IS NULL)
+          AND ($3 IS NULL OR EXISTS (
+              SELECT 1
+              FROM task_labels x
+              JOIN labels l ON l.id = x.label_id
+              WHERE x.task_id = t.id AND l.name = $3
+          ))
         ORDER BY t.created_at DESC;
         "#,
-            project_id
+            project_id,
+            include_archived,
+            label
         )
         .fetch_all(pool)
         .await?;
@@ -176,42 +842,56 @@ impl Task {
                 parent_task_attempt: rec.parent_task_attempt,
                 created_at: rec.created_at,
                 updated_at: rec.updated_at,
+                complexity_score: rec.complexity_score,
+                timeout_seconds: rec.timeout_seconds,
                 has_in_progress_attempt: rec.has_in_progress_attempt != 0,
                 has_merged_attempt: rec.has_merged_attempt != 0,
                 last_attempt_failed: rec.last_attempt_failed != 0,
                 latest_attempt_executor: rec.latest_attempt_executor,
+                parent_task_id: rec.parent_task_id,
+                subtask_count: rec.subtask_count,
+                completed_subtask_count: rec.completed_subtask_count,
+                archived_at: rec.archived_at,
+                labels: rec.labels.0,
             })
             .collect();
 
         Ok(tasks)
     }
 
-    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+    pub async fn find_by_id<'e, E>(executor: E, id: Uuid) -> Result<Option<Self>, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>", complexity_score as "complexity_score!: f32", timeout_seconds as "timeout_seconds: i64", session_id, executor_model, priority as "priority!: i64", parent_task_id as "parent_task_id: Uuid", archived_at as "archived_at: DateTime<Utc>"
                FROM tasks 
                WHERE id = $1"#,
             id
         )
-        .fetch_optional(pool)
+        .fetch_optional(executor)
         .await
     }
 
-    pub async fn find_by_id_and_project_id(
-        pool: &SqlitePool,
+    /// Find a task of the project that has not been archived
+    pub async fn find_by_id_and_project_id<'e, E>(
+        executor: E,
         id: Uuid,
         project_id: Uuid,
-    ) -> Result<Option<Self>, sqlx::Error> {
+    ) -> Result<Option<Self>, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>", complexity_score as "complexity_score!: f32", timeout_seconds as "timeout_seconds: i64", session_id, executor_model, priority as "priority!: i64", parent_task_id as "parent_task_id: Uuid", archived_at as "archived_at: DateTime<Utc>"
                FROM tasks 
-               WHERE id = $1 AND project_id = $2"#,
+               WHERE id = $1 AND project_id = $2 AND archived_at IS NULL"#,
             id,
             project_id
         )
-        .fetch_optional(pool)
+        .fetch_optional(executor)
         .await
     }
 
@@ -220,55 +900,329 @@ impl Task {
         data: &CreateTask,
         task_id: Uuid,
     ) -> Result<Self, sqlx::Error> {
-        sqlx::query_as!(
+        let mut tx = pool.begin().await?;
+        let task = Self::create_in_tx(&mut tx, data, task_id).await?;
+        tx.commit().await?;
+        Ok(task)
+    }
+
+    /// Create a task within a transaction the caller commits
+    pub async fn create_in_tx(
+        tx: &mut Transaction<'_, Sqlite>,
+        data: &CreateTask,
+        task_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let labels = match data.labels.as_deref() {
+            Some(labels) if !labels.is_empty() => labels.to_vec(),
+            _ => Project::find_by_id(&mut **tx, data.project_id)
+                .await?
+                .map(|project| project.default_labels.0)
+                .unwrap_or_default(),
+        };
+
+        let complexity_score = complexity_score(&data.title, data.description.as_deref());
+        let priority = data.priority.unwrap_or(DEFAULT_TASK_PRIORITY);
+
+        let task = sqlx::query_as!(
             Task,
-            r#"INSERT INTO tasks (id, project_id, title, description, status, parent_task_attempt) 
-               VALUES ($1, $2, $3, $4, $5, $6) 
-               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"INSERT INTO tasks (id, project_id, title, description, status, parent_task_attempt, complexity_score, timeout_seconds, executor_model, priority, parent_task_id) 
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) 
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>", complexity_score as "complexity_score!: f32", timeout_seconds as "timeout_seconds: i64", session_id, executor_model, priority as "priority!: i64", parent_task_id as "parent_task_id: Uuid", archived_at as "archived_at: DateTime<Utc>""#,
             task_id,
             data.project_id,
             data.title,
             data.description,
             TaskStatus::Todo as TaskStatus,
-            data.parent_task_attempt
+            data.parent_task_attempt,
+            complexity_score,
+            data.timeout_seconds,
+            data.executor_model,
+            priority,
+            data.parent_task_id
         )
-        .fetch_one(pool)
+        .fetch_one(&mut **tx)
+        .await?;
+
+        for label in &labels {
+            Label::attach_by_name(tx, data.project_id, task_id, label).await?;
+        }
+
+        Ok(task)
+    }
+
+    /// Create several tasks of a project within a transaction the caller commits, returned
+    /// in input order. On an error the transaction must be dropped, so nothing is created
+    /// when a dependency cannot be resolved.
+    pub async fn create_batch(
+        tx: &mut Transaction<'_, Sqlite>,
+        project_id: Uuid,
+        items: &[CreateTaskBatchItem],
+    ) -> Result<Vec<Self>, TaskBatchError> {
+        if items.len() > MAX_TASK_BATCH_SIZE {
+            return Err(TaskBatchError::TooManyTasks(items.len()));
+        }
+        if items.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ids: Vec<Uuid> = items
+            .iter()
+            .map(|item| item.id.unwrap_or_else(Uuid::new_v4))
+            .collect();
+        let edges: Vec<TaskDependency> = ids
+            .iter()
+            .zip(items)
+            .flat_map(|(task_id, item)| {
+                item.dependencies
+                    .iter()
+                    .map(move |depends_on_task_id| TaskDependency {
+                        task_id: *task_id,
+                        depends_on_task_id: *depends_on_task_id,
+                    })
+            })
+            .collect();
+        // Existing tasks cannot depend on new ones, so any cycle lies within the batch
+        for edge in &edges {
+            if let Some(path) = dependency_path(&edges, edge.depends_on_task_id, edge.task_id) {
+                return Err(TaskBatchError::Cycle(path));
+            }
+        }
+
+        let labels = Project::find_by_id(&mut **tx, project_id)
+            .await?
+            .map(|project| project.default_labels.0)
+            .unwrap_or_default();
+
+        let mut insert = QueryBuilder::<Sqlite>::new(
+            "INSERT INTO tasks (id, project_id, title, description, status, complexity_score, executor_model) ",
+        );
+        insert.push_values(ids.iter().zip(items), |mut row, (id, item)| {
+            row.push_bind(*id)
+                .push_bind(project_id)
+                .push_bind(item.title.clone())
+                .push_bind(item.description.clone())
+                .push_bind(item.status.clone().unwrap_or(TaskStatus::Todo))
+                .push_bind(complexity_score(&item.title, item.description.as_deref()))
+                .push_bind(item.executor_model.clone());
+        });
+        insert.push(
+            " RETURNING id, project_id, title, description, status, parent_task_attempt, created_at, updated_at, complexity_score, timeout_seconds, session_id, executor_model, priority, parent_task_id, archived_at",
+        );
+        let mut created: HashMap<Uuid, Task> = insert
+            .build_query_as::<Task>()
+            .fetch_all(&mut **tx)
+            .await?
+            .into_iter()
+            .map(|task| (task.id, task))
+            .collect();
+
+        if !edges.is_empty() {
+            let mut lookup =
+                QueryBuilder::<Sqlite>::new("SELECT id FROM tasks WHERE project_id = ");
+            lookup.push_bind(project_id).push(" AND id IN (");
+            let mut separated = lookup.separated(", ");
+            for edge in &edges {
+                separated.push_bind(edge.depends_on_task_id);
+            }
+            lookup.push(")");
+            let known: HashSet<Uuid> = lookup
+                .build_query_scalar::<Uuid>()
+                .fetch_all(&mut **tx)
+                .await?
+                .into_iter()
+                .collect();
+            if let Some(edge) = edges
+                .iter()
+                .find(|edge| !known.contains(&edge.depends_on_task_id))
+            {
+                // The caller drops the transaction, rolling back the inserted tasks
+                return Err(TaskBatchError::UnresolvedDependency {
+                    task_id: edge.task_id,
+                    depends_on_task_id: edge.depends_on_task_id,
+                });
+            }
+
+            let mut insert = QueryBuilder::<Sqlite>::new(
+                "INSERT OR IGNORE INTO task_dependencies (task_id, depends_on_task_id) ",
+            );
+            insert.push_values(&edges, |mut row, edge| {
+                row.push_bind(edge.task_id)
+                    .push_bind(edge.depends_on_task_id);
+            });
+            insert.build().execute(&mut **tx).await?;
+        }
+
+        for id in &ids {
+            for label in &labels {
+                Label::attach_by_name(tx, project_id, *id, label).await?;
+            }
+        }
+
+        Ok(ids.iter().filter_map(|id| created.remove(id)).collect())
+    }
+
+    /// Copy a task of the project into a new one titled "<title> (copy)", keeping its
+    /// status, parent, executor settings, labels and environment variables. Run history and
+    /// dependencies stay with the original. `None` when the source task does not exist. Runs
+    /// within a transaction the caller commits.
+    pub async fn duplicate(
+        tx: &mut Transaction<'_, Sqlite>,
+        source_id: Uuid,
+        project_id: Uuid,
+        new_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let Some(task) = sqlx::query_as!(
+            Task,
+            r#"INSERT INTO tasks (id, project_id, title, description, status, complexity_score, timeout_seconds, executor_model, priority, parent_task_id)
+               SELECT $1, project_id, title || ' (copy)', description, status, complexity_score, timeout_seconds, executor_model, priority, parent_task_id
+               FROM tasks
+               WHERE id = $2 AND project_id = $3
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>", complexity_score as "complexity_score!: f32", timeout_seconds as "timeout_seconds: i64", session_id, executor_model, priority as "priority!: i64", parent_task_id as "parent_task_id: Uuid", archived_at as "archived_at: DateTime<Utc>""#,
+            new_id,
+            source_id,
+            project_id
+        )
+        .fetch_optional(&mut **tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        sqlx::query!(
+            "INSERT INTO task_labels (task_id, label_id) SELECT $1, label_id FROM task_labels WHERE task_id = $2",
+            new_id,
+            source_id
+        )
+        .execute(&mut **tx)
+        .await?;
+        // Values are sealed with the server-wide key, so the ciphertext can be copied as-is
+        sqlx::query!(
+            "INSERT INTO task_env_vars (task_id, key, value) SELECT $1, key, value FROM task_env_vars WHERE task_id = $2",
+            new_id,
+            source_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(Some(task))
+    }
+
+    /// Find the direct sub-tasks of a task that are not archived, oldest first
+    pub async fn get_children(
+        pool: &SqlitePool,
+        parent_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>", complexity_score as "complexity_score!: f32", timeout_seconds as "timeout_seconds: i64", session_id, executor_model, priority as "priority!: i64", parent_task_id as "parent_task_id: Uuid", archived_at as "archived_at: DateTime<Utc>"
+               FROM tasks
+               WHERE parent_task_id = $1 AND archived_at IS NULL
+               ORDER BY created_at ASC"#,
+            parent_id
+        )
+        .fetch_all(pool)
         .await
     }
 
-    pub async fn update(
+    /// Share of the task's direct sub-tasks that are done, from 0 to 100; 0 when it has none
+    pub async fn completion_percentage(&self, pool: &SqlitePool) -> Result<f32, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"SELECT COUNT(*) as "total!: i64",
+                      COALESCE(SUM(CASE WHEN status = 'done' THEN 1 ELSE 0 END), 0) as "done!: i64"
+               FROM tasks
+               WHERE parent_task_id = $1 AND archived_at IS NULL"#,
+            self.id
+        )
+        .fetch_one(pool)
+        .await?;
+        if record.total == 0 {
+            return Ok(0.0);
+        }
+        Ok(record.done as f32 * 100.0 / record.total as f32)
+    }
+
+    /// Count the sub-tasks below a task, at any depth, that are not done yet. A task may
+    /// only be completed once this is zero.
+    pub async fn incomplete_subtask_count(
         pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"WITH RECURSIVE subtasks(id, status) AS (
+                   SELECT id, status FROM tasks WHERE parent_task_id = $1 AND archived_at IS NULL
+                   UNION
+                   SELECT t.id, t.status FROM tasks t JOIN subtasks s ON t.parent_task_id = s.id
+                   WHERE t.archived_at IS NULL
+               )
+               SELECT COUNT(*) as "count!: i64" FROM subtasks WHERE status != 'done'"#,
+            task_id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(record.count)
+    }
+
+    /// Find the names of the labels attached to a task
+    pub async fn labels(pool: &SqlitePool, task_id: Uuid) -> Result<Vec<String>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT l.name
+               FROM task_labels tl
+               JOIN labels l ON l.id = tl.label_id
+               WHERE tl.task_id = $1
+               ORDER BY tl.created_at ASC, l.name ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(records.into_iter().map(|r| r.name).collect())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update<'e, E>(
+        executor: E,
         id: Uuid,
         project_id: Uuid,
         title: String,
         description: Option<String>,
         status: TaskStatus,
         parent_task_attempt: Option<Uuid>,
-    ) -> Result<Self, sqlx::Error> {
+        timeout_seconds: Option<i64>,
+        executor_model: Option<String>,
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
         let status_value = status as TaskStatus;
+        let complexity_score = complexity_score(&title, description.as_deref());
         sqlx::query_as!(
             Task,
             r#"UPDATE tasks 
-               SET title = $3, description = $4, status = $5, parent_task_attempt = $6 
+               SET title = $3, description = $4, status = $5, parent_task_attempt = $6, complexity_score = $7, timeout_seconds = $8, executor_model = $9 
                WHERE id = $1 AND project_id = $2 
-               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>", complexity_score as "complexity_score!: f32", timeout_seconds as "timeout_seconds: i64", session_id, executor_model, priority as "priority!: i64", parent_task_id as "parent_task_id: Uuid", archived_at as "archived_at: DateTime<Utc>""#,
             id,
             project_id,
             title,
             description,
             status_value,
-            parent_task_attempt
+            parent_task_attempt,
+            complexity_score,
+            timeout_seconds,
+            executor_model
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await
     }
 
-    pub async fn update_status(
-        pool: &SqlitePool,
+    pub async fn update_status<'e, E>(
+        executor: E,
         id: Uuid,
         project_id: Uuid,
         status: TaskStatus,
-    ) -> Result<(), sqlx::Error> {
+    ) -> Result<(), sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
         let status_value = status as TaskStatus;
         sqlx::query!(
             "UPDATE tasks SET status = $3, updated_at = CURRENT_TIMESTAMP WHERE id = $1 AND project_id = $2",
@@ -276,33 +1230,250 @@ impl Task {
             project_id,
             status_value
         )
-        .execute(pool)
+        .execute(executor)
         .await?;
         Ok(())
     }
 
-    pub async fn delete(pool: &SqlitePool, id: Uuid, project_id: Uuid) -> Result<u64, sqlx::Error> {
-        let result = sqlx::query!(
-            "DELETE FROM tasks WHERE id = $1 AND project_id = $2",
+    pub async fn set_priority<'e, E>(
+        executor: E,
+        id: Uuid,
+        project_id: Uuid,
+        priority: i64,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            "UPDATE tasks SET priority = $3, updated_at = CURRENT_TIMESTAMP WHERE id = $1 AND project_id = $2",
             id,
-            project_id
+            project_id,
+            priority
         )
-        .execute(pool)
+        .execute(executor)
         .await?;
-        Ok(result.rows_affected())
+        Ok(())
     }
 
-    pub async fn exists(
+    /// Find the project's tasks that are still to do, most urgent first. Waiting tasks gain
+    /// one priority point per `priority_boost_after_seconds` of the project; ties go to the
+    /// task created first.
+    pub async fn list_pending(
         pool: &SqlitePool,
-        id: Uuid,
         project_id: Uuid,
-    ) -> Result<bool, sqlx::Error> {
-        let result = sqlx::query!(
-            "SELECT id as \"id!: Uuid\" FROM tasks WHERE id = $1 AND project_id = $2",
-            id,
-            project_id
-        )
-        .fetch_optional(pool)
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_task_attempt as "parent_task_attempt: Uuid", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>", t.complexity_score as "complexity_score!: f32", t.timeout_seconds as "timeout_seconds: i64", t.session_id, t.executor_model, t.priority as "priority!: i64", t.parent_task_id as "parent_task_id: Uuid", t.archived_at as "archived_at: DateTime<Utc>"
+               FROM tasks t
+               JOIN projects p ON p.id = t.project_id
+               WHERE t.project_id = $1 AND t.status = 'todo' AND t.archived_at IS NULL
+               ORDER BY t.priority + COALESCE(CAST((julianday('now') - julianday(t.created_at)) * 86400 / p.priority_boost_after_seconds AS INTEGER), 0) DESC,
+                        t.created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Remember the executor session of the task's latest coding agent run
+    pub async fn set_session_id(
+        pool: &SqlitePool,
+        id: Uuid,
+        session_id: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE tasks SET session_id = $2 WHERE id = $1",
+            id,
+            session_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record that a coding agent run of the task started, clearing the previous run's timing
+    pub async fn mark_started(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        let started_at = Utc::now();
+        sqlx::query!(
+            "UPDATE tasks SET started_at = $2, completed_at = NULL, total_execution_ms = NULL WHERE id = $1",
+            id,
+            started_at
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record that the task's run reached a terminal state, along with how long it took.
+    /// Tasks that never started are left alone.
+    pub async fn mark_completed(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        let Some(started_at) = sqlx::query_scalar!(
+            r#"SELECT started_at as "started_at: DateTime<Utc>" FROM tasks WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?
+        .flatten() else {
+            return Ok(());
+        };
+
+        let completed_at = Utc::now();
+        let total_execution_ms = (completed_at - started_at).num_milliseconds().max(0);
+        sqlx::query!(
+            "UPDATE tasks SET completed_at = $2, total_execution_ms = $3 WHERE id = $1",
+            id,
+            completed_at,
+            total_execution_ms
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// When the task's latest run started and ended, `None` when there is no such task
+    pub async fn time_tracking(
+        pool: &SqlitePool,
+        id: Uuid,
+    ) -> Result<Option<TaskTimeTracking>, sqlx::Error> {
+        let Some(record) = sqlx::query!(
+            r#"SELECT started_at as "started_at: DateTime<Utc>", completed_at as "completed_at: DateTime<Utc>"
+               FROM tasks
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let total_execution_ms = Self::execution_duration(pool, id)
+            .await?
+            .map(|duration| duration.as_millis() as i64);
+        Ok(Some(TaskTimeTracking {
+            started_at: record.started_at,
+            completed_at: record.completed_at,
+            total_execution_ms,
+        }))
+    }
+
+    /// How long the task's latest run took, `None` until it has completed
+    pub async fn execution_duration(
+        pool: &SqlitePool,
+        id: Uuid,
+    ) -> Result<Option<std::time::Duration>, sqlx::Error> {
+        let total_execution_ms =
+            sqlx::query_scalar!("SELECT total_execution_ms FROM tasks WHERE id = $1", id)
+                .fetch_optional(pool)
+                .await?
+                .flatten();
+        Ok(total_execution_ms.map(|ms| std::time::Duration::from_millis(ms as u64)))
+    }
+
+    /// Average and 95th percentile run time of the project's tasks that completed within
+    /// `[from, to]`. Either bound may be left open.
+    pub async fn timing_stats_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<TaskTimingStats, sqlx::Error> {
+        let durations_ms = sqlx::query_scalar!(
+            r#"SELECT total_execution_ms as "total_execution_ms!: i64"
+               FROM tasks
+               WHERE project_id = $1
+                 AND total_execution_ms IS NOT NULL
+                 AND ($2 IS NULL OR julianday(completed_at) >= julianday($2))
+                 AND ($3 IS NULL OR julianday(completed_at) <= julianday($3))
+               ORDER BY total_execution_ms ASC"#,
+            project_id,
+            from,
+            to
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(TaskTimingStats::from_sorted_durations(&durations_ms))
+    }
+
+    /// Archive a task instead of deleting it, keeping its runs and conversations
+    pub async fn archive<'e, E>(executor: E, id: Uuid, project_id: Uuid) -> Result<u64, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query!(
+            "UPDATE tasks SET archived_at = datetime('now', 'subsec'), updated_at = datetime('now', 'subsec') WHERE id = $1 AND project_id = $2 AND archived_at IS NULL",
+            id,
+            project_id
+        )
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Bring back an archived task; `None` when the task is missing or not archived
+    pub async fn restore<'e, E>(
+        executor: E,
+        id: Uuid,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_as!(
+            Task,
+            r#"UPDATE tasks
+               SET archived_at = NULL, updated_at = datetime('now', 'subsec')
+               WHERE id = $1 AND project_id = $2 AND archived_at IS NOT NULL
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>", complexity_score as "complexity_score!: f32", timeout_seconds as "timeout_seconds: i64", session_id, executor_model, priority as "priority!: i64", parent_task_id as "parent_task_id: Uuid", archived_at as "archived_at: DateTime<Utc>""#,
+            id,
+            project_id
+        )
+        .fetch_optional(executor)
+        .await
+    }
+
+    /// Find tasks archived more than `ARCHIVED_TASK_RETENTION_DAYS` ago, with their projects
+    pub async fn find_expired_archived(
+        pool: &SqlitePool,
+    ) -> Result<Vec<(Uuid, Uuid)>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid"
+               FROM tasks
+               WHERE archived_at < datetime('now', '-' || $1 || ' days')"#,
+            ARCHIVED_TASK_RETENTION_DAYS
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(records
+            .into_iter()
+            .map(|rec| (rec.id, rec.project_id))
+            .collect())
+    }
+
+    /// Delete a task for good, together with its attempts and everything they recorded
+    pub async fn delete(pool: &SqlitePool, id: Uuid, project_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM tasks WHERE id = $1 AND project_id = $2",
+            id,
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn exists(
+        pool: &SqlitePool,
+        id: Uuid,
+        project_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "SELECT id as \"id!: Uuid\" FROM tasks WHERE id = $1 AND project_id = $2",
+            id,
+            project_id
+        )
+        .fetch_optional(pool)
         .await?;
         Ok(result.is_some())
     }
@@ -315,7 +1486,7 @@ impl Task {
         // Find both children and parent for this attempt
         sqlx::query_as!(
             Task,
-            r#"SELECT DISTINCT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_task_attempt as "parent_task_attempt: Uuid", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT DISTINCT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_task_attempt as "parent_task_attempt: Uuid", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>", t.complexity_score as "complexity_score!: f32", t.timeout_seconds as "timeout_seconds: i64", t.session_id, t.executor_model, t.priority as "priority!: i64", t.parent_task_id as "parent_task_id: Uuid", t.archived_at as "archived_at: DateTime<Utc>"
                FROM tasks t
                WHERE (
                    -- Find children: tasks that have this attempt as parent
@@ -339,4 +1510,895 @@ impl Task {
         .fetch_all(pool)
         .await
     }
+
+    /// Find tasks that directly depend on the given task
+    pub async fn dependent_tasks(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_task_attempt as "parent_task_attempt: Uuid", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>", t.complexity_score as "complexity_score!: f32", t.timeout_seconds as "timeout_seconds: i64", t.session_id, t.executor_model, t.priority as "priority!: i64", t.parent_task_id as "parent_task_id: Uuid", t.archived_at as "archived_at: DateTime<Utc>"
+               FROM tasks t
+               JOIN task_dependencies d ON t.id = d.task_id
+               WHERE d.depends_on_task_id = $1
+               ORDER BY t.created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Find every task that depends on the given task, directly or through other tasks
+    pub async fn all_transitive_dependents(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        // UNION (rather than UNION ALL) stops the recursion if the graph contains a cycle
+        sqlx::query_as!(
+            Task,
+            r#"WITH RECURSIVE dependents(id) AS (
+                   SELECT task_id FROM task_dependencies WHERE depends_on_task_id = $1
+                   UNION
+                   SELECT d.task_id
+                   FROM task_dependencies d
+                   JOIN dependents ON d.depends_on_task_id = dependents.id
+               )
+               SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_task_attempt as "parent_task_attempt: Uuid", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>", t.complexity_score as "complexity_score!: f32", t.timeout_seconds as "timeout_seconds: i64", t.session_id, t.executor_model, t.priority as "priority!: i64", t.parent_task_id as "parent_task_id: Uuid", t.archived_at as "archived_at: DateTime<Utc>"
+               FROM tasks t
+               JOIN dependents ON t.id = dependents.id
+               WHERE t.id != $1
+               ORDER BY t.created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Find every task that has had a run with the given executor session ID.
+    /// Sessions may be shared on purpose, so several tasks can match.
+    pub async fn find_by_run_session_id(
+        pool: &SqlitePool,
+        session_id: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT DISTINCT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_task_attempt as "parent_task_attempt: Uuid", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>", t.complexity_score as "complexity_score!: f32", t.timeout_seconds as "timeout_seconds: i64", t.session_id, t.executor_model, t.priority as "priority!: i64", t.parent_task_id as "parent_task_id: Uuid", t.archived_at as "archived_at: DateTime<Utc>"
+               FROM tasks t
+               JOIN task_attempts ta ON ta.task_id = t.id
+               JOIN executor_sessions es ON es.task_attempt_id = ta.id
+               WHERE es.session_id = $1
+               ORDER BY t.created_at ASC"#,
+            session_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Find every task reachable from the given task by following dependencies in either
+    /// direction, including the task itself
+    pub async fn dependency_graph_tasks(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"WITH RECURSIVE
+                   upstream(id) AS (
+                       SELECT $1
+                       UNION
+                       SELECT d.depends_on_task_id
+                       FROM task_dependencies d
+                       JOIN upstream ON d.task_id = upstream.id
+                   ),
+                   downstream(id) AS (
+                       SELECT $1
+                       UNION
+                       SELECT d.task_id
+                       FROM task_dependencies d
+                       JOIN downstream ON d.depends_on_task_id = downstream.id
+                   )
+               SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_task_attempt as "parent_task_attempt: Uuid", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>", t.complexity_score as "complexity_score!: f32", t.timeout_seconds as "timeout_seconds: i64", t.session_id, t.executor_model, t.priority as "priority!: i64", t.parent_task_id as "parent_task_id: Uuid", t.archived_at as "archived_at: DateTime<Utc>"
+               FROM tasks t
+               WHERE t.id IN (SELECT id FROM upstream UNION SELECT id FROM downstream)
+               ORDER BY t.created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Make `task_id` wait for `depends_on_task_id` to be done. Both tasks must belong to
+    /// the same project, and a dependency that would close a cycle is rejected.
+    pub async fn add_dependency(
+        tx: &mut Transaction<'_, Sqlite>,
+        task_id: Uuid,
+        depends_on_task_id: Uuid,
+    ) -> Result<TaskDependency, TaskDependencyError> {
+        let task = Self::find_by_id(&mut **tx, task_id)
+            .await?
+            .ok_or(TaskDependencyError::TaskNotFound)?;
+        if Self::find_by_id_and_project_id(&mut **tx, depends_on_task_id, task.project_id)
+            .await?
+            .is_none()
+        {
+            return Err(TaskDependencyError::TaskNotFound);
+        }
+
+        let edges = sqlx::query_as!(
+            TaskDependency,
+            r#"SELECT d.task_id as "task_id!: Uuid", d.depends_on_task_id as "depends_on_task_id!: Uuid"
+               FROM task_dependencies d
+               JOIN tasks t ON t.id = d.task_id
+               WHERE t.project_id = $1"#,
+            task.project_id
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+        if let Some(path) = dependency_path(&edges, depends_on_task_id, task_id) {
+            return Err(TaskDependencyError::Cycle(path));
+        }
+
+        sqlx::query!(
+            "INSERT OR IGNORE INTO task_dependencies (task_id, depends_on_task_id) VALUES ($1, $2)",
+            task_id,
+            depends_on_task_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(TaskDependency {
+            task_id,
+            depends_on_task_id,
+        })
+    }
+
+    pub async fn remove_dependency<'e, E>(
+        executor: E,
+        task_id: Uuid,
+        depends_on_task_id: Uuid,
+    ) -> Result<u64, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query!(
+            "DELETE FROM task_dependencies WHERE task_id = $1 AND depends_on_task_id = $2",
+            task_id,
+            depends_on_task_id
+        )
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Find the direct dependencies of the given task that are not done yet
+    pub async fn get_blocked_by(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_task_attempt as "parent_task_attempt: Uuid", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>", t.complexity_score as "complexity_score!: f32", t.timeout_seconds as "timeout_seconds: i64", t.session_id, t.executor_model, t.priority as "priority!: i64", t.parent_task_id as "parent_task_id: Uuid", t.archived_at as "archived_at: DateTime<Utc>"
+               FROM tasks t
+               JOIN task_dependencies d ON t.id = d.depends_on_task_id
+               WHERE d.task_id = $1 AND t.status != 'done'
+               ORDER BY t.created_at ASC, t.rowid ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Set an environment variable on the task's executor processes. The value is stored
+    /// encrypted with the key derived from `VIBE_ENCRYPTION_KEY`.
+    pub async fn set_env_var<'e, E>(
+        executor: E,
+        task_id: Uuid,
+        key: &str,
+        value: &str,
+    ) -> Result<(), TaskEnvVarError>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
+        let sealed = SecretKey::from_env()?.encrypt(value.as_bytes());
+        sqlx::query!(
+            r#"INSERT INTO task_env_vars (task_id, key, value)
+               VALUES ($1, $2, $3)
+               ON CONFLICT(task_id, key) DO UPDATE SET
+                   value = excluded.value,
+                   updated_at = datetime('now', 'subsec')"#,
+            task_id,
+            key,
+            sealed
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// Add environment variables without a value for the user to fill in. They are left
+    /// out of the executor environment until set; existing variables are kept.
+    pub async fn declare_env_vars(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        keys: &[String],
+    ) -> Result<(), sqlx::Error> {
+        for key in keys {
+            sqlx::query!(
+                "INSERT OR IGNORE INTO task_env_vars (task_id, key, value) VALUES ($1, $2, X'')",
+                task_id,
+                key
+            )
+            .execute(pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn delete_env_var<'e, E>(
+        executor: E,
+        task_id: Uuid,
+        key: &str,
+    ) -> Result<u64, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query!(
+            "DELETE FROM task_env_vars WHERE task_id = $1 AND key = $2",
+            task_id,
+            key
+        )
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Names of the task's environment variables, without their values
+    pub async fn env_var_keys(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let records = sqlx::query!(
+            "SELECT key FROM task_env_vars WHERE task_id = $1 ORDER BY key ASC",
+            task_id
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(records.into_iter().map(|record| record.key).collect())
+    }
+
+    /// The task's environment variables with their decrypted values, ordered by name
+    pub async fn get_env_vars(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<(String, String)>, TaskEnvVarError> {
+        // Variables declared by a template but not filled in yet have an empty value
+        let records = sqlx::query!(
+            "SELECT key, value FROM task_env_vars WHERE task_id = $1 AND length(value) > 0 ORDER BY key ASC",
+            task_id
+        )
+        .fetch_all(pool)
+        .await?;
+        // Tasks without variables run even when no encryption key is configured
+        if records.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let secret_key = SecretKey::from_env()?;
+        records
+            .into_iter()
+            .map(|record| {
+                let value = String::from_utf8(secret_key.decrypt(&record.value)?)
+                    .map_err(|_| EncryptionError::Decrypt)?;
+                Ok((record.key, value))
+            })
+            .collect()
+    }
+
+    /// Find the dependencies between the tasks returned by `dependency_graph_tasks`
+    pub async fn dependency_graph_edges(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<TaskDependency>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskDependency,
+            r#"WITH RECURSIVE
+                   upstream(id) AS (
+                       SELECT $1
+                       UNION
+                       SELECT d.depends_on_task_id
+                       FROM task_dependencies d
+                       JOIN upstream ON d.task_id = upstream.id
+                   ),
+                   downstream(id) AS (
+                       SELECT $1
+                       UNION
+                       SELECT d.task_id
+                       FROM task_dependencies d
+                       JOIN downstream ON d.depends_on_task_id = downstream.id
+                   ),
+                   reachable(id) AS (
+                       SELECT id FROM upstream UNION SELECT id FROM downstream
+                   )
+               SELECT d.task_id as "task_id!: Uuid", d.depends_on_task_id as "depends_on_task_id!: Uuid"
+               FROM task_dependencies d
+               WHERE d.task_id IN (SELECT id FROM reachable)
+                 AND d.depends_on_task_id IN (SELECT id FROM reachable)
+               ORDER BY d.created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{create_project, memory_pool};
+
+    /// Add a dependency in a transaction of its own
+    async fn add_dependency(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        depends_on_task_id: Uuid,
+    ) -> Result<TaskDependency, TaskDependencyError> {
+        let mut tx = pool.begin().await?;
+        let dependency = Task::add_dependency(&mut tx, task_id, depends_on_task_id).await?;
+        tx.commit().await?;
+        Ok(dependency)
+    }
+
+    /// Create a batch in a transaction of its own, committed only when it succeeds
+    async fn create_batch(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        items: &[CreateTaskBatchItem],
+    ) -> Result<Vec<Task>, TaskBatchError> {
+        let mut tx = pool.begin().await?;
+        let tasks = Task::create_batch(&mut tx, project_id, items).await?;
+        tx.commit().await?;
+        Ok(tasks)
+    }
+
+    async fn duplicate(pool: &SqlitePool, source_id: Uuid, project_id: Uuid) -> Option<Task> {
+        let mut tx = pool.begin().await.unwrap();
+        let task = Task::duplicate(&mut tx, source_id, project_id, Uuid::new_v4())
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+        task
+    }
+
+    async fn create_task(pool: &SqlitePool, project_id: Uuid, title: &str) -> Task {
+        let data = CreateTask {
+            project_id,
+            title: title.to_string(),
+            description: None,
+            parent_task_attempt: None,
+            labels: None,
+            timeout_seconds: None,
+            executor_model: None,
+            priority: None,
+            parent_task_id: None,
+        };
+        Task::create(pool, &data, Uuid::new_v4()).await.unwrap()
+    }
+
+    async fn create_subtask(pool: &SqlitePool, parent: &Task, title: &str) -> Task {
+        let data = CreateTask {
+            project_id: parent.project_id,
+            title: title.to_string(),
+            description: None,
+            parent_task_attempt: None,
+            labels: None,
+            timeout_seconds: None,
+            executor_model: None,
+            priority: None,
+            parent_task_id: Some(parent.id),
+        };
+        Task::create(pool, &data, Uuid::new_v4()).await.unwrap()
+    }
+
+    fn ids(tasks: &[Task]) -> Vec<Uuid> {
+        tasks.iter().map(|task| task.id).collect()
+    }
+
+    #[tokio::test]
+    async fn test_subtask_hierarchy_rolls_up_completion() {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let refactor = create_task(&pool, project_id, "Refactor storage").await;
+        let schema = create_subtask(&pool, &refactor, "Change schema").await;
+        let queries = create_subtask(&pool, &refactor, "Port queries").await;
+        let migration = create_subtask(&pool, &schema, "Write migration").await;
+
+        assert_eq!(migration.parent_task_id, Some(schema.id));
+        assert_eq!(
+            ids(&Task::get_children(&pool, refactor.id).await.unwrap()),
+            vec![schema.id, queries.id]
+        );
+        assert_eq!(refactor.completion_percentage(&pool).await.unwrap(), 0.0);
+        assert_eq!(migration.completion_percentage(&pool).await.unwrap(), 0.0);
+
+        // Grandchildren keep the root open even when every direct child looks finished
+        Task::update_status(&pool, queries.id, project_id, TaskStatus::Done)
+            .await
+            .unwrap();
+        assert_eq!(refactor.completion_percentage(&pool).await.unwrap(), 50.0);
+        assert_eq!(
+            Task::incomplete_subtask_count(&pool, refactor.id)
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            Task::incomplete_subtask_count(&pool, schema.id)
+                .await
+                .unwrap(),
+            1
+        );
+
+        Task::update_status(&pool, migration.id, project_id, TaskStatus::Done)
+            .await
+            .unwrap();
+        Task::update_status(&pool, schema.id, project_id, TaskStatus::Done)
+            .await
+            .unwrap();
+        assert_eq!(refactor.completion_percentage(&pool).await.unwrap(), 100.0);
+        assert_eq!(
+            Task::incomplete_subtask_count(&pool, refactor.id)
+                .await
+                .unwrap(),
+            0
+        );
+
+        let listed = Task::find_by_project_id_with_attempt_status(&pool, project_id, false, None)
+            .await
+            .unwrap();
+        let root = listed.iter().find(|task| task.id == refactor.id).unwrap();
+        assert_eq!((root.subtask_count, root.completed_subtask_count), (2, 2));
+        let leaf = listed.iter().find(|task| task.id == migration.id).unwrap();
+        assert_eq!(leaf.parent_task_id, Some(schema.id));
+        assert_eq!(leaf.subtask_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_copies_task_without_dependencies() {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let build = create_task(&pool, project_id, "Build").await;
+        let original = Task::create(
+            &pool,
+            &CreateTask {
+                project_id,
+                title: "Release".to_string(),
+                description: Some("Tag and publish".to_string()),
+                parent_task_attempt: None,
+                labels: Some(vec!["ops".to_string()]),
+                timeout_seconds: Some(600),
+                executor_model: Some("claude-haiku-4".to_string()),
+                priority: Some(80),
+                parent_task_id: None,
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        add_dependency(&pool, original.id, build.id).await.unwrap();
+        sqlx::query("INSERT INTO task_env_vars (task_id, key, value) VALUES ($1, $2, $3)")
+            .bind(original.id)
+            .bind("TOKEN")
+            .bind(vec![1u8, 2, 3])
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let clone = duplicate(&pool, original.id, project_id).await.unwrap();
+        assert_ne!(clone.id, original.id);
+        assert_eq!(clone.title, "Release (copy)");
+        assert_eq!(clone.description, original.description);
+        assert_eq!(clone.timeout_seconds, Some(600));
+        assert_eq!(clone.executor_model.as_deref(), Some("claude-haiku-4"));
+        assert_eq!(clone.priority, 80);
+        assert_eq!(Task::labels(&pool, clone.id).await.unwrap(), vec!["ops"]);
+        assert_eq!(
+            Task::env_var_keys(&pool, clone.id).await.unwrap(),
+            vec!["TOKEN"]
+        );
+        assert!(Task::dependency_graph_edges(&pool, clone.id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        Task::update(
+            &pool,
+            clone.id,
+            project_id,
+            clone.title.clone(),
+            Some("Only tag".to_string()),
+            clone.status.clone(),
+            None,
+            clone.timeout_seconds,
+            clone.executor_model.clone(),
+        )
+        .await
+        .unwrap();
+        let original = Task::find_by_id(&pool, original.id).await.unwrap().unwrap();
+        assert_eq!(original.description.as_deref(), Some("Tag and publish"));
+
+        assert!(duplicate(&pool, Uuid::new_v4(), project_id).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_archived_task_is_hidden_until_restored() {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let kept = create_task(&pool, project_id, "Kept").await;
+        let archived = create_task(&pool, project_id, "Archived").await;
+
+        assert_eq!(
+            Task::archive(&pool, archived.id, project_id).await.unwrap(),
+            1
+        );
+        assert_eq!(
+            Task::archive(&pool, archived.id, project_id).await.unwrap(),
+            0
+        );
+
+        let listed = Task::find_by_project_id_with_attempt_status(&pool, project_id, false, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            listed.iter().map(|task| task.id).collect::<Vec<_>>(),
+            vec![kept.id]
+        );
+        assert!(
+            Task::find_by_id_and_project_id(&pool, archived.id, project_id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let listed = Task::find_by_project_id_with_attempt_status(&pool, project_id, true, None)
+            .await
+            .unwrap();
+        let listed_archived = listed.iter().find(|task| task.id == archived.id).unwrap();
+        assert!(listed_archived.archived_at.is_some());
+        let found = Task::find_by_id(&pool, archived.id).await.unwrap().unwrap();
+        assert!(found.archived_at.is_some());
+
+        let restored = Task::restore(&pool, archived.id, project_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(restored.archived_at.is_none());
+        assert!(Task::restore(&pool, archived.id, project_id)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            Task::find_by_project_id_with_attempt_status(&pool, project_id, false, None)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_only_long_archived_tasks_expire() {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let recent = create_task(&pool, project_id, "Recent").await;
+        let old = create_task(&pool, project_id, "Old").await;
+        create_task(&pool, project_id, "Active").await;
+
+        Task::archive(&pool, recent.id, project_id).await.unwrap();
+        sqlx::query("UPDATE tasks SET archived_at = datetime('now', '-31 days') WHERE id = $1")
+            .bind(old.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            Task::find_expired_archived(&pool).await.unwrap(),
+            vec![(old.id, project_id)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_task_is_blocked_until_dependencies_are_done() {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let build = create_task(&pool, project_id, "Build").await;
+        let test = create_task(&pool, project_id, "Test").await;
+        let release = create_task(&pool, project_id, "Release").await;
+
+        add_dependency(&pool, release.id, build.id).await.unwrap();
+        add_dependency(&pool, release.id, test.id).await.unwrap();
+        // Adding the same dependency again is a no-op
+        add_dependency(&pool, release.id, test.id).await.unwrap();
+
+        let blocked_by = Task::get_blocked_by(&pool, release.id).await.unwrap();
+        assert_eq!(ids(&blocked_by), vec![build.id, test.id]);
+
+        Task::update_status(&pool, build.id, project_id, TaskStatus::Done)
+            .await
+            .unwrap();
+        let blocked_by = Task::get_blocked_by(&pool, release.id).await.unwrap();
+        assert_eq!(ids(&blocked_by), vec![test.id]);
+
+        Task::update_status(&pool, test.id, project_id, TaskStatus::Done)
+            .await
+            .unwrap();
+        assert!(Task::get_blocked_by(&pool, release.id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(
+            Task::remove_dependency(&pool, release.id, build.id)
+                .await
+                .unwrap(),
+            1
+        );
+        let edges = Task::dependency_graph_edges(&pool, release.id)
+            .await
+            .unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].depends_on_task_id, test.id);
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency_requires_task_in_same_project() {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let other_project_id = create_project(&pool).await;
+        let task = create_task(&pool, project_id, "Task").await;
+        let foreign = create_task(&pool, other_project_id, "Foreign").await;
+
+        for depends_on_task_id in [Uuid::new_v4(), foreign.id] {
+            let result = add_dependency(&pool, task.id, depends_on_task_id).await;
+            assert!(matches!(result, Err(TaskDependencyError::TaskNotFound)));
+        }
+        assert!(Task::get_blocked_by(&pool, task.id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency_rejects_cycles() {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let a = create_task(&pool, project_id, "A").await;
+        let b = create_task(&pool, project_id, "B").await;
+        let c = create_task(&pool, project_id, "C").await;
+
+        add_dependency(&pool, b.id, a.id).await.unwrap();
+        add_dependency(&pool, c.id, b.id).await.unwrap();
+
+        match add_dependency(&pool, a.id, c.id).await {
+            Err(TaskDependencyError::Cycle(path)) => assert_eq!(path, vec![c.id, b.id, a.id]),
+            other => panic!("expected a cycle, got {:?}", other),
+        }
+        match add_dependency(&pool, a.id, a.id).await {
+            Err(TaskDependencyError::Cycle(path)) => assert_eq!(path, vec![a.id]),
+            other => panic!("expected a cycle, got {:?}", other),
+        }
+        assert!(Task::get_blocked_by(&pool, a.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_pending_orders_by_priority() {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let low = create_task(&pool, project_id, "Low").await;
+        let high = create_task(&pool, project_id, "High").await;
+        let done = create_task(&pool, project_id, "Done").await;
+        assert_eq!(low.priority, DEFAULT_TASK_PRIORITY);
+
+        Task::set_priority(&pool, low.id, project_id, 10)
+            .await
+            .unwrap();
+        Task::set_priority(&pool, high.id, project_id, 90)
+            .await
+            .unwrap();
+        Task::update_status(&pool, done.id, project_id, TaskStatus::Done)
+            .await
+            .unwrap();
+
+        let pending = Task::list_pending(&pool, project_id).await.unwrap();
+        assert_eq!(ids(&pending), vec![high.id, low.id]);
+
+        // A boost of one point per second lets the long-waiting task overtake
+        sqlx::query("UPDATE tasks SET created_at = datetime('now', '-1 hour') WHERE id = $1")
+            .bind(low.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE projects SET priority_boost_after_seconds = 1 WHERE id = $1")
+            .bind(project_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let pending = Task::list_pending(&pool, project_id).await.unwrap();
+        assert_eq!(ids(&pending), vec![low.id, high.id]);
+    }
+
+    fn batch_item(title: &str, dependencies: Vec<Uuid>) -> CreateTaskBatchItem {
+        CreateTaskBatchItem {
+            id: Some(Uuid::new_v4()),
+            title: title.to_string(),
+            description: None,
+            status: None,
+            executor_model: None,
+            dependencies,
+        }
+    }
+
+    async fn task_count(pool: &SqlitePool, project_id: Uuid) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM tasks WHERE project_id = $1")
+            .bind(project_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_create_batch_links_dependencies_in_input_order() {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let existing = create_task(&pool, project_id, "Existing").await;
+
+        let mut release = batch_item("Release", vec![]);
+        let build = batch_item("Build", vec![existing.id]);
+        let mut test = batch_item("Test", vec![build.id.unwrap()]);
+        test.status = Some(TaskStatus::InProgress);
+        release.dependencies = vec![build.id.unwrap(), test.id.unwrap()];
+        let items = vec![release, build, test];
+
+        let tasks = create_batch(&pool, project_id, &items).await.unwrap();
+
+        assert_eq!(
+            tasks
+                .iter()
+                .map(|task| task.title.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Release", "Build", "Test"]
+        );
+        assert_eq!(
+            ids(&tasks),
+            items
+                .iter()
+                .map(|item| item.id.unwrap())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(tasks[0].status, TaskStatus::Todo);
+        assert_eq!(tasks[2].status, TaskStatus::InProgress);
+
+        // Tasks of a batch share their creation time, so compare without order
+        let blocked_by = Task::get_blocked_by(&pool, tasks[0].id).await.unwrap();
+        assert_eq!(
+            ids(&blocked_by).into_iter().collect::<HashSet<_>>(),
+            HashSet::from([tasks[1].id, tasks[2].id])
+        );
+        let blocked_by = Task::get_blocked_by(&pool, tasks[1].id).await.unwrap();
+        assert_eq!(ids(&blocked_by), vec![existing.id]);
+    }
+
+    #[tokio::test]
+    async fn test_create_batch_rolls_back_on_unresolved_dependency() {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let other_project_id = create_project(&pool).await;
+        let foreign = create_task(&pool, other_project_id, "Foreign").await;
+
+        let build = batch_item("Build", vec![]);
+        let test = batch_item("Test", vec![build.id.unwrap(), foreign.id]);
+        let test_id = test.id.unwrap();
+
+        match create_batch(&pool, project_id, &[build, test]).await {
+            Err(TaskBatchError::UnresolvedDependency {
+                task_id,
+                depends_on_task_id,
+            }) => {
+                assert_eq!(task_id, test_id);
+                assert_eq!(depends_on_task_id, foreign.id);
+            }
+            other => panic!("expected an unresolved dependency, got {:?}", other),
+        }
+        assert_eq!(task_count(&pool, project_id).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_create_batch_rejects_more_than_the_maximum() {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let items: Vec<_> = (0..=MAX_TASK_BATCH_SIZE)
+            .map(|i| batch_item(&format!("Task {}", i), vec![]))
+            .collect();
+
+        let result = create_batch(&pool, project_id, &items).await;
+
+        assert!(matches!(result, Err(TaskBatchError::TooManyTasks(51))));
+        assert_eq!(task_count(&pool, project_id).await, 0);
+
+        let tasks = create_batch(&pool, project_id, &items[..MAX_TASK_BATCH_SIZE])
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), MAX_TASK_BATCH_SIZE);
+    }
+
+    #[tokio::test]
+    async fn test_execution_duration_is_none_until_the_run_completes() {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let task = create_task(&pool, project_id, "Migrate the settings page").await;
+
+        assert_eq!(
+            Task::execution_duration(&pool, task.id).await.unwrap(),
+            None
+        );
+        Task::mark_started(&pool, task.id).await.unwrap();
+        assert_eq!(
+            Task::execution_duration(&pool, task.id).await.unwrap(),
+            None
+        );
+
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        Task::mark_completed(&pool, task.id).await.unwrap();
+
+        let duration = Task::execution_duration(&pool, task.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(duration > std::time::Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_time_tracking_reports_the_execution_duration() {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let task = create_task(&pool, project_id, "Migrate the settings page").await;
+
+        let timing = Task::time_tracking(&pool, task.id).await.unwrap().unwrap();
+        assert_eq!(timing.started_at, None);
+        assert_eq!(timing.total_execution_ms, None);
+        Task::mark_started(&pool, task.id).await.unwrap();
+        let timing = Task::time_tracking(&pool, task.id).await.unwrap().unwrap();
+        assert!(timing.started_at.is_some());
+        assert_eq!(timing.total_execution_ms, None);
+
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        Task::mark_completed(&pool, task.id).await.unwrap();
+
+        let timing = Task::time_tracking(&pool, task.id).await.unwrap().unwrap();
+        let duration = Task::execution_duration(&pool, task.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(timing.total_execution_ms, Some(duration.as_millis() as i64));
+        assert!(timing.completed_at.unwrap() > timing.started_at.unwrap());
+    }
+
+    #[test]
+    fn test_timing_stats_use_the_discrete_95th_percentile() {
+        let durations_ms: Vec<i64> = (1..=20).map(|i| i * 100).collect();
+
+        let stats = TaskTimingStats::from_sorted_durations(&durations_ms);
+
+        assert_eq!(stats.completed_tasks, 20);
+        assert_eq!(stats.avg_task_execution_ms, Some(1050.0));
+        assert_eq!(stats.p95_task_execution_ms, Some(1900));
+        assert_eq!(
+            TaskTimingStats::from_sorted_durations(&[]),
+            TaskTimingStats {
+                completed_tasks: 0,
+                avg_task_execution_ms: None,
+                p95_task_execution_ms: None,
+            }
+        );
+    }
 }
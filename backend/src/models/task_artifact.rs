@@ -0,0 +1,139 @@
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A file produced by an execution process in its output directory
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TaskArtifact {
+    pub id: Uuid,
+    pub execution_process_id: Uuid,
+    pub filename: String,
+    pub size_bytes: i64,
+    pub mime_type: String,
+    pub stored_path: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TaskArtifact {
+    /// Find all artifacts produced by any run of a task, oldest first
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskArtifact,
+            r#"SELECT ta.id                   AS "id!: Uuid",
+                      ta.execution_process_id AS "execution_process_id!: Uuid",
+                      ta.filename,
+                      ta.size_bytes,
+                      ta.mime_type,
+                      ta.stored_path,
+                      ta.created_at           AS "created_at!: DateTime<Utc>"
+               FROM task_artifacts ta
+               JOIN execution_processes ep ON ep.id = ta.execution_process_id
+               JOIN task_attempts att ON att.id = ep.task_attempt_id
+               WHERE att.task_id = $1
+               ORDER BY ta.created_at ASC, ta.filename ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        filename: &str,
+        size_bytes: i64,
+        mime_type: &str,
+        stored_path: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            TaskArtifact,
+            r#"INSERT INTO task_artifacts (id, execution_process_id, filename, size_bytes, mime_type, stored_path)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               RETURNING id                   AS "id!: Uuid",
+                         execution_process_id AS "execution_process_id!: Uuid",
+                         filename,
+                         size_bytes,
+                         mime_type,
+                         stored_path,
+                         created_at           AS "created_at!: DateTime<Utc>""#,
+            id,
+            execution_process_id,
+            filename,
+            size_bytes,
+            mime_type,
+            stored_path
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Record every regular file in `dir` as an artifact of `execution_process_id`.
+    /// Subdirectories are walked recursively; filenames are stored relative to `dir`.
+    pub async fn collect_from_dir(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        dir: &Path,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut artifacts = Vec::new();
+        let mut pending = vec![dir.to_path_buf()];
+
+        while let Some(current) = pending.pop() {
+            let mut entries = match tokio::fs::read_dir(&current).await {
+                Ok(entries) => entries,
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to read artifact directory {}: {}",
+                        current.display(),
+                        e
+                    );
+                    continue;
+                }
+            };
+
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let path = entry.path();
+                let Ok(metadata) = entry.metadata().await else {
+                    continue;
+                };
+                if metadata.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+                if !metadata.is_file() {
+                    continue;
+                }
+
+                let filename = path
+                    .strip_prefix(dir)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .to_string();
+                let mime_type = mime_guess::from_path(&path)
+                    .first_or_octet_stream()
+                    .to_string();
+                artifacts.push(
+                    Self::create(
+                        pool,
+                        execution_process_id,
+                        &filename,
+                        metadata.len() as i64,
+                        &mime_type,
+                        &path.to_string_lossy(),
+                    )
+                    .await?,
+                );
+            }
+        }
+
+        Ok(artifacts)
+    }
+}
@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use chrono::{DateTime, Utc};
 use git2::{BranchType, Error as GitError, Repository};
@@ -8,10 +8,17 @@ use tracing::info;
 use ts_rs::TS;
 use uuid::Uuid;
 
-use super::{project::Project, task::Task};
-use crate::services::{
-    CreatePrRequest, GitHubRepoInfo, GitHubService, GitHubServiceError, GitService,
-    GitServiceError, ProcessService,
+use super::{
+    project::Project,
+    task::{Task, TaskStatus},
+};
+use crate::{
+    executors::claude::ToolResult,
+    services::{
+        CreatePrRequest, GitHubRepoInfo, GitHubService, GitHubServiceError, GitService,
+        GitServiceError, ProcessService,
+    },
+    utils::worktree_manager::WorktreeManager,
 };
 
 // Constants for git diff operations
@@ -28,6 +35,7 @@ pub enum TaskAttemptError {
     ProjectNotFound,
     ValidationError(String),
     BranchNotFound(String),
+    Executor(crate::executor::ExecutorError),
 }
 
 impl std::fmt::Display for TaskAttemptError {
@@ -41,6 +49,7 @@ impl std::fmt::Display for TaskAttemptError {
             TaskAttemptError::ProjectNotFound => write!(f, "Project not found"),
             TaskAttemptError::ValidationError(e) => write!(f, "Validation error: {}", e),
             TaskAttemptError::BranchNotFound(branch) => write!(f, "Branch '{}' not found", branch),
+            TaskAttemptError::Executor(e) => write!(f, "Executor error: {}", e),
         }
     }
 }
@@ -71,6 +80,12 @@ impl From<GitHubServiceError> for TaskAttemptError {
     }
 }
 
+impl From<crate::executor::ExecutorError> for TaskAttemptError {
+    fn from(err: crate::executor::ExecutorError) -> Self {
+        TaskAttemptError::Executor(err)
+    }
+}
+
 #[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS)]
 #[sqlx(type_name = "task_attempt_status", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
@@ -132,6 +147,71 @@ pub struct CreatePrParams<'a> {
 #[ts(export)]
 pub struct CreateFollowUpAttempt {
     pub prompt: String,
+    /// Files handed to Claude with the prompt, relative to the worktree
+    #[ts(optional)]
+    pub context_files: Option<Vec<String>>,
+    /// Pre-computed tool outputs injected into Claude's session ahead of the prompt
+    #[ts(optional)]
+    pub tool_results: Option<Vec<ToolResult>>,
+}
+
+/// What a follow-up run is started with. It is stored in the operation params of the setup
+/// script that may run first, and read back when the follow-up starts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FollowupRequest {
+    pub prompt: String,
+    /// Files handed to Claude with the prompt, relative to the worktree
+    #[serde(default)]
+    pub context_files: Vec<String>,
+    /// Pre-computed tool outputs injected into Claude's session ahead of the prompt
+    #[serde(default)]
+    pub tool_results: Vec<ToolResult>,
+}
+
+impl From<CreateFollowUpAttempt> for FollowupRequest {
+    fn from(data: CreateFollowUpAttempt) -> Self {
+        Self {
+            prompt: data.prompt,
+            context_files: data.context_files.unwrap_or_default(),
+            tool_results: data.tool_results.unwrap_or_default(),
+        }
+    }
+}
+
+impl FollowupRequest {
+    /// Read a follow-up back from the operation params it was stored in
+    pub fn from_operation_params(
+        operation_params: Option<&serde_json::Value>,
+    ) -> Result<Self, TaskAttemptError> {
+        let params = operation_params.ok_or_else(|| {
+            TaskAttemptError::ValidationError("Missing follow-up parameters".to_string())
+        })?;
+        serde_json::from_value(params.clone()).map_err(|e| {
+            TaskAttemptError::ValidationError(format!("Invalid follow-up parameters: {}", e))
+        })
+    }
+}
+
+/// Continue a Claude session started outside vibe-kanban
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct ResumeSession {
+    /// Claude session ID, a UUID
+    pub session_id: String,
+    pub prompt: String,
+}
+
+/// Check that a session ID is a UUID, the form Claude's session IDs take
+pub fn validate_session_id(session_id: &str) -> Result<Uuid, TaskAttemptError> {
+    let session_id = session_id.trim();
+    if session_id.is_empty() {
+        return Err(TaskAttemptError::ValidationError(
+            "Session ID must not be empty".to_string(),
+        ));
+    }
+    Uuid::parse_str(session_id).map_err(|_| {
+        TaskAttemptError::ValidationError(format!("Session ID '{}' is not a UUID", session_id))
+    })
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -381,6 +461,31 @@ impl TaskAttempt {
             .collect())
     }
 
+    /// Find the attempts of a project's tasks that use `worktree_path`, with each task's status
+    pub async fn find_by_worktree_path_with_task_status(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<Vec<(Uuid, TaskStatus)>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"
+            SELECT ta.id as "attempt_id!: Uuid", t.status as "task_status!: TaskStatus"
+            FROM task_attempts ta
+            JOIN tasks t ON ta.task_id = t.id
+            WHERE t.project_id = $1 AND ta.worktree_path = $2
+            "#,
+            project_id,
+            worktree_path
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|r| (r.attempt_id, r.task_status))
+            .collect())
+    }
+
     /// Find task attempts that are expired (24+ hours since last activity) and eligible for worktree cleanup
     /// Activity includes: execution completion, task attempt updates (including worktree recreation),
     /// and any attempts that are currently in progress
@@ -463,21 +568,28 @@ impl TaskAttempt {
         // Create GitService instance
         let git_service = GitService::new(&project.git_repo_path)?;
 
-        // Determine the resolved base branch name first
-        let resolved_base_branch = if let Some(ref base_branch) = data.base_branch {
-            base_branch.clone()
-        } else {
-            // Default to current HEAD branch name or "main"
-            git_service.get_default_branch_name()?
-        };
+        // Fall back to the project's configured base branch
+        let resolved_base_branch = data
+            .base_branch
+            .clone()
+            .unwrap_or_else(|| project.base_branch.clone());
 
         // Create the worktree using GitService
         git_service.create_worktree(
             &task_attempt_branch,
             &worktree_path,
-            data.base_branch.as_deref(),
+            Some(&resolved_base_branch),
         )?;
 
+        // Cache it so later runs on the branch reuse it
+        WorktreeManager::acquire(
+            project.id,
+            &project.git_repo_path,
+            &task_attempt_branch,
+            worktree_path,
+        )
+        .await?;
+
         // Insert the record into the database
         Ok(sqlx::query_as!(
             TaskAttempt,
@@ -635,15 +747,37 @@ impl TaskAttempt {
         attempt_id: Uuid,
         task_id: Uuid,
         project_id: Uuid,
-        prompt: &str,
+        request: &FollowupRequest,
     ) -> Result<Uuid, TaskAttemptError> {
         ProcessService::start_followup_execution(
-            pool, app_state, attempt_id, task_id, project_id, prompt,
+            pool, app_state, attempt_id, task_id, project_id, request,
         )
         .await
     }
 
-    /// Ensure worktree exists, recreating from branch if needed (cold task support)
+    /// Continue an externally started Claude session in the task's latest attempt,
+    /// returning the new run's ID
+    pub async fn resume_session(
+        pool: &SqlitePool,
+        app_state: &crate::app_state::AppState,
+        task_id: Uuid,
+        project_id: Uuid,
+        data: &ResumeSession,
+    ) -> Result<Uuid, TaskAttemptError> {
+        ProcessService::start_session_resume(
+            pool,
+            app_state,
+            task_id,
+            project_id,
+            &data.session_id,
+            &data.prompt,
+        )
+        .await
+    }
+
+    /// Ensure worktree exists, recreating from branch if needed (cold task support). The
+    /// worktree is acquired from the `WorktreeManager`, which cancels any removal pending
+    /// since the branch's last run finished.
     pub async fn ensure_worktree_exists(
         pool: &SqlitePool,
         attempt_id: Uuid,
@@ -653,55 +787,55 @@ impl TaskAttempt {
         let task_attempt = TaskAttempt::find_by_id(pool, attempt_id)
             .await?
             .ok_or(TaskAttemptError::TaskNotFound)?;
+        let project = Project::find_by_id(pool, project_id)
+            .await?
+            .ok_or(TaskAttemptError::ProjectNotFound)?;
 
-        // Return existing path if worktree still exists
-        if std::path::Path::new(&task_attempt.worktree_path).exists() {
-            return Ok(task_attempt.worktree_path);
+        let recreated = !Path::new(&task_attempt.worktree_path).exists();
+        if recreated {
+            info!(
+                "Worktree {} no longer exists, recreating from branch {} for {}",
+                task_attempt.worktree_path, task_attempt.branch, context
+            );
         }
 
-        // Recreate worktree from branch
-        info!(
-            "Worktree {} no longer exists, recreating from branch {} for {}",
-            task_attempt.worktree_path, task_attempt.branch, context
-        );
-
-        let new_worktree_path =
-            Self::recreate_worktree_from_branch(pool, &task_attempt, project_id).await?;
-
-        // Update database with new path, reset worktree_deleted flag, and clear setup completion
-        sqlx::query!(
-            "UPDATE task_attempts SET worktree_path = $1, worktree_deleted = FALSE, setup_completed_at = NULL, updated_at = datetime('now') WHERE id = $2",
-            new_worktree_path,
-            attempt_id
+        // Use the stored worktree path - this ensures we recreate in the exact same location
+        // where Claude originally created its session, maintaining session continuity
+        let worktree_path = WorktreeManager::acquire(
+            project_id,
+            &project.git_repo_path,
+            &task_attempt.branch,
+            PathBuf::from(&task_attempt.worktree_path),
         )
-        .execute(pool)
-        .await?;
+        .await?
+        .to_string_lossy()
+        .to_string();
+
+        if recreated || worktree_path != task_attempt.worktree_path {
+            // Update database with new path, reset worktree_deleted flag, and clear setup completion
+            sqlx::query!(
+                "UPDATE task_attempts SET worktree_path = $1, worktree_deleted = FALSE, setup_completed_at = NULL, updated_at = datetime('now') WHERE id = $2",
+                worktree_path,
+                attempt_id
+            )
+            .execute(pool)
+            .await?;
+        }
 
-        Ok(new_worktree_path)
+        Ok(worktree_path)
     }
 
-    /// Recreate a worktree from an existing branch (for cold task support)
-    pub async fn recreate_worktree_from_branch(
+    /// Release the attempt's branch worktree once its run has finished. It is removed after
+    /// the retention period unless another run acquires it first.
+    pub async fn release_worktree(
         pool: &SqlitePool,
-        task_attempt: &TaskAttempt,
+        attempt_id: Uuid,
         project_id: Uuid,
-    ) -> Result<String, TaskAttemptError> {
-        let project = Project::find_by_id(pool, project_id)
-            .await?
-            .ok_or(TaskAttemptError::ProjectNotFound)?;
-
-        // Create GitService instance
-        let git_service = GitService::new(&project.git_repo_path)?;
-
-        // Use the stored worktree path from database - this ensures we recreate in the exact same location
-        // where Claude originally created its session, maintaining session continuity
-        let stored_worktree_path = std::path::PathBuf::from(&task_attempt.worktree_path);
-
-        let result_path = git_service
-            .recreate_worktree_from_branch(&task_attempt.branch, &stored_worktree_path)
-            .await?;
-
-        Ok(result_path.to_string_lossy().to_string())
+    ) -> Result<(), sqlx::Error> {
+        if let Some(task_attempt) = TaskAttempt::find_by_id(pool, attempt_id).await? {
+            WorktreeManager::release(project_id, &task_attempt.branch).await;
+        }
+        Ok(())
     }
 
     /// Get the git diff between the base commit and the current committed worktree state
@@ -1229,3 +1363,35 @@ impl TaskAttempt {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_session_id_rejects_empty() {
+        assert!(matches!(
+            validate_session_id(""),
+            Err(TaskAttemptError::ValidationError(_))
+        ));
+        assert!(matches!(
+            validate_session_id("   "),
+            Err(TaskAttemptError::ValidationError(_))
+        ));
+        assert!(matches!(
+            validate_session_id("not-a-session"),
+            Err(TaskAttemptError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_session_id_accepts_uuid() {
+        let session_id = "3f1c2a4e-8b7d-4c2e-9a51-6d0f2b7e1c34";
+        assert_eq!(
+            validate_session_id(&format!(" {} ", session_id))
+                .unwrap()
+                .to_string(),
+            session_id
+        );
+    }
+}
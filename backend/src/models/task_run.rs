@@ -0,0 +1,390 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{types::Json, FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::utils::log_compression::decompress_log;
+
+/// Outcome of removing the worktree a finished run used
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "worktree_cleanup_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum WorktreeCleanupStatus {
+    Success,
+    /// The worktree was already gone
+    NotFound,
+    Error,
+}
+
+/// A finished run whose worktree is due for removal
+#[derive(Debug, Clone)]
+pub struct WorktreeCleanupCandidate {
+    pub id: Uuid,
+    pub task_attempt_id: Uuid,
+    pub worktree_path: String,
+    pub git_repo_path: String,
+}
+
+/// A coding agent run that is still going
+#[derive(Debug, Clone)]
+pub struct RunningTaskRun {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub task_attempt_id: Uuid,
+    /// `None` for runs started before PIDs were recorded
+    pub pid: Option<i64>,
+    /// Start time of the process `pid` referred to, `None` where it cannot be read
+    pub pid_start_time: Option<i64>,
+}
+
+/// Outcome of cancelling every running run of a project
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CancelledRuns {
+    pub cancelled: Vec<Uuid>,
+    /// Runs whose process could not be signalled; they are left running
+    pub failed_to_cancel: Vec<Uuid>,
+}
+
+/// One coding agent run of a task, across all of its attempts.
+///
+/// Runs are the task's coding agent execution processes; each keeps its own logs, so
+/// later runs never overwrite earlier ones.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TaskRun {
+    /// Id of the execution process
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub task_attempt_id: Uuid,
+    pub started_at: DateTime<Utc>,
+    /// `None` while the run is still going
+    pub finished_at: Option<DateTime<Utc>>,
+    pub exit_code: Option<i64>,
+    /// Raw executor output, normalized with the executor named by `executor_type`
+    pub logs: Option<String>,
+    /// Logs of a finished run, zstd-compressed; loaders decompress them into `logs`
+    #[serde(skip)]
+    #[ts(skip)]
+    pub logs_zstd: Option<Vec<u8>>,
+    pub session_id: Option<String>,
+    pub executor_type: Option<String>,
+    /// Worktree the run executed in
+    pub worktree_path: String,
+    /// `None` until the worktree has been cleaned up
+    pub cleanup_status: Option<WorktreeCleanupStatus>,
+    /// Whether a tool use of the run failed, e.g. a shell command exiting non-zero
+    pub had_tool_errors: bool,
+    /// Written by `ConversationSummarizer` for projects with `auto_summarize`
+    pub summary: Option<String>,
+    /// Whether the run started a fresh session because the one it followed up on had more
+    /// turns than the project's `max_context_turns`
+    pub was_context_truncated: bool,
+    /// How often the run called each of its most used base commands; `None` until the run
+    /// finished
+    #[ts(type = "Record<string, number> | null")]
+    pub command_counts: Option<Json<HashMap<String, u32>>>,
+    /// How many output lines of the run were cut off for exceeding the line size limit
+    pub truncated_line_count: i64,
+}
+
+impl TaskRun {
+    /// Find every run of a task, newest first
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskRun,
+            r#"SELECT ep.id              AS "id!: Uuid",
+                      ta.task_id         AS "task_id!: Uuid",
+                      ep.task_attempt_id AS "task_attempt_id!: Uuid",
+                      ep.started_at      AS "started_at!: DateTime<Utc>",
+                      ep.completed_at    AS "finished_at: DateTime<Utc>",
+                      ep.exit_code,
+                      ep.stdout          AS logs,
+                      ep.stdout_zstd     AS logs_zstd,
+                      es.session_id,
+                      ep.executor_type,
+                      ep.working_directory AS worktree_path,
+                      ep.cleanup_status  AS "cleanup_status: WorktreeCleanupStatus",
+                      ep.had_tool_errors AS "had_tool_errors!: bool",
+                      ep.summary,
+                      ep.was_context_truncated AS "was_context_truncated!: bool",
+                      ep.command_counts  AS "command_counts: Json<HashMap<String, u32>>",
+                      ep.truncated_line_count AS "truncated_line_count!: i64"
+               FROM execution_processes ep
+               JOIN task_attempts ta ON ta.id = ep.task_attempt_id
+               LEFT JOIN executor_sessions es ON es.execution_process_id = ep.id
+               WHERE ta.task_id = $1
+                 AND ep.process_type = 'codingagent'
+               ORDER BY ep.started_at DESC, ep.created_at DESC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+        .map(|runs| runs.into_iter().map(Self::with_decompressed_logs).collect())
+    }
+
+    /// Find a run of a task
+    pub async fn find_by_id_and_task_id(
+        pool: &SqlitePool,
+        id: Uuid,
+        task_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskRun,
+            r#"SELECT ep.id              AS "id!: Uuid",
+                      ta.task_id         AS "task_id!: Uuid",
+                      ep.task_attempt_id AS "task_attempt_id!: Uuid",
+                      ep.started_at      AS "started_at!: DateTime<Utc>",
+                      ep.completed_at    AS "finished_at: DateTime<Utc>",
+                      ep.exit_code,
+                      ep.stdout          AS logs,
+                      ep.stdout_zstd     AS logs_zstd,
+                      es.session_id,
+                      ep.executor_type,
+                      ep.working_directory AS worktree_path,
+                      ep.cleanup_status  AS "cleanup_status: WorktreeCleanupStatus",
+                      ep.had_tool_errors AS "had_tool_errors!: bool",
+                      ep.summary,
+                      ep.was_context_truncated AS "was_context_truncated!: bool",
+                      ep.command_counts  AS "command_counts: Json<HashMap<String, u32>>",
+                      ep.truncated_line_count AS "truncated_line_count!: i64"
+               FROM execution_processes ep
+               JOIN task_attempts ta ON ta.id = ep.task_attempt_id
+               LEFT JOIN executor_sessions es ON es.execution_process_id = ep.id
+               WHERE ep.id = $1
+                 AND ta.task_id = $2
+                 AND ep.process_type = 'codingagent'"#,
+            id,
+            task_id
+        )
+        .fetch_optional(pool)
+        .await
+        .map(|run| run.map(Self::with_decompressed_logs))
+    }
+
+    /// How often the project's finished coding agent runs called each base command, summed
+    /// over the per-run counts
+    pub async fn command_counts_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<HashMap<String, u32>, sqlx::Error> {
+        let runs = sqlx::query_scalar!(
+            r#"SELECT ep.command_counts AS "command_counts!: Json<HashMap<String, u32>>"
+               FROM execution_processes ep
+               JOIN task_attempts ta ON ta.id = ep.task_attempt_id
+               JOIN tasks t ON t.id = ta.task_id
+               WHERE t.project_id = $1
+                 AND ep.process_type = 'codingagent'
+                 AND ep.command_counts IS NOT NULL"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut totals = HashMap::new();
+        for Json(counts) in runs {
+            for (command, count) in counts {
+                *totals.entry(command).or_insert(0) += count;
+            }
+        }
+        Ok(totals)
+    }
+
+    /// Find finished runs whose worktree has not been cleaned up and that ended more than
+    /// `retention_seconds` ago, oldest first. Runs of attempts that are still running or
+    /// ran again within the retention period are skipped, since they share the worktree.
+    pub async fn find_due_for_worktree_cleanup(
+        pool: &SqlitePool,
+        retention_seconds: i64,
+    ) -> Result<Vec<WorktreeCleanupCandidate>, sqlx::Error> {
+        sqlx::query_as!(
+            WorktreeCleanupCandidate,
+            r#"SELECT ep.id                AS "id!: Uuid",
+                      ep.task_attempt_id   AS "task_attempt_id!: Uuid",
+                      ep.working_directory AS worktree_path,
+                      p.git_repo_path
+               FROM execution_processes ep
+               JOIN task_attempts ta ON ta.id = ep.task_attempt_id
+               JOIN tasks t ON t.id = ta.task_id
+               JOIN projects p ON p.id = t.project_id
+               WHERE ep.process_type = 'codingagent'
+                 AND ep.status IN ('completed', 'failed', 'killed')
+                 AND ep.cleanup_status IS NULL
+                 AND julianday(ep.completed_at) < julianday('now', '-' || $1 || ' seconds')
+                 AND NOT EXISTS (
+                     SELECT 1 FROM execution_processes other
+                     WHERE other.task_attempt_id = ep.task_attempt_id
+                       AND (other.status = 'running'
+                            OR julianday(other.completed_at) >= julianday('now', '-' || $1 || ' seconds'))
+                 )
+               ORDER BY ep.completed_at ASC"#,
+            retention_seconds
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Find the project's coding agent runs that are still running
+    pub async fn find_running_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<RunningTaskRun>, sqlx::Error> {
+        sqlx::query_as!(
+            RunningTaskRun,
+            r#"SELECT ep.id              AS "id!: Uuid",
+                      ta.task_id         AS "task_id!: Uuid",
+                      ep.task_attempt_id AS "task_attempt_id!: Uuid",
+                      ep.pid,
+                      ep.pid_start_time
+               FROM execution_processes ep
+               JOIN task_attempts ta ON ta.id = ep.task_attempt_id
+               JOIN tasks t ON t.id = ta.task_id
+               WHERE t.project_id = $1
+                 AND ep.process_type = 'codingagent'
+                 AND ep.status = 'running'
+               ORDER BY ep.started_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn set_cleanup_status(
+        pool: &SqlitePool,
+        id: Uuid,
+        status: WorktreeCleanupStatus,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE execution_processes SET cleanup_status = $2 WHERE id = $1",
+            id,
+            status
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Ids of coding agent runs after `after_rowid`, in insertion order, with their rowids so
+    /// callers can page through every run
+    pub async fn find_ids_after(
+        pool: &SqlitePool,
+        after_rowid: i64,
+        limit: i64,
+    ) -> Result<Vec<(i64, Uuid)>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT rowid as "rowid!: i64", id as "id!: Uuid"
+               FROM execution_processes
+               WHERE rowid > $1 AND process_type = 'codingagent'
+               ORDER BY rowid ASC
+               LIMIT $2"#,
+            after_rowid,
+            limit
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(records
+            .into_iter()
+            .map(|record| (record.rowid, record.id))
+            .collect())
+    }
+
+    pub async fn set_normalized_json(
+        pool: &SqlitePool,
+        id: Uuid,
+        normalized_json: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE execution_processes SET normalized_json = $2 WHERE id = $1",
+            id,
+            normalized_json
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_had_tool_errors(
+        pool: &SqlitePool,
+        id: Uuid,
+        had_tool_errors: bool,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE execution_processes SET had_tool_errors = $2 WHERE id = $1",
+            id,
+            had_tool_errors
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_command_counts(
+        pool: &SqlitePool,
+        id: Uuid,
+        command_counts: &HashMap<String, u32>,
+    ) -> Result<(), sqlx::Error> {
+        let command_counts = Json(command_counts);
+        sqlx::query!(
+            "UPDATE execution_processes SET command_counts = $2 WHERE id = $1",
+            id,
+            command_counts
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn find_summary(pool: &SqlitePool, id: Uuid) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!("SELECT summary FROM execution_processes WHERE id = $1", id)
+            .fetch_optional(pool)
+            .await
+            .map(Option::flatten)
+    }
+
+    pub async fn set_summary(
+        pool: &SqlitePool,
+        id: Uuid,
+        summary: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE execution_processes SET summary = $2 WHERE id = $1",
+            id,
+            summary
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_context_truncated(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE execution_processes SET was_context_truncated = TRUE WHERE id = $1",
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Restore `logs` from their compressed copy, followed by any output appended after
+    /// the compression
+    fn with_decompressed_logs(mut self) -> Self {
+        if let Some(compressed) = self.logs_zstd.take() {
+            match decompress_log(&compressed) {
+                Ok(mut logs) => {
+                    logs.push_str(self.logs.as_deref().unwrap_or_default());
+                    self.logs = Some(logs);
+                }
+                Err(e) => tracing::warn!("Failed to decompress logs of run {}: {}", self.id, e),
+            }
+        }
+        self
+    }
+}
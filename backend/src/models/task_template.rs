@@ -1,9 +1,17 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{types::Json, FromRow, SqlitePool};
 use ts_rs::TS;
 use uuid::Uuid;
 
+lazy_static::lazy_static! {
+    /// `{name}` placeholder in a template title or description
+    static ref PLACEHOLDER: Regex = Regex::new(r"\{([A-Za-z_][A-Za-z0-9_]*)\}").unwrap();
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 #[ts(export)]
 pub struct TaskTemplate {
@@ -12,6 +20,11 @@ pub struct TaskTemplate {
     pub title: String,
     pub description: Option<String>,
     pub template_name: String,
+    /// Model tasks created from the template run with; the CLI default when `None`
+    pub executor_model: Option<String>,
+    /// Environment variables tasks created from the template get, without a value
+    #[ts(type = "Array<string>")]
+    pub env_var_keys: Json<Vec<String>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -23,6 +36,10 @@ pub struct CreateTaskTemplate {
     pub title: String,
     pub description: Option<String>,
     pub template_name: String,
+    #[ts(optional)]
+    pub executor_model: Option<String>,
+    #[ts(optional)]
+    pub env_var_keys: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -31,13 +48,59 @@ pub struct UpdateTaskTemplate {
     pub title: Option<String>,
     pub description: Option<String>,
     pub template_name: Option<String>,
+    /// Leaves the current model unchanged when omitted; an empty string removes it
+    #[ts(optional)]
+    pub executor_model: Option<String>,
+    #[ts(optional)]
+    pub env_var_keys: Option<Vec<String>>,
+}
+
+/// Values for the `{name}` placeholders of a template's title and description
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct InstantiateTaskTemplate {
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
+
+/// A template placeholder that no variable was given for
+#[derive(Debug, PartialEq)]
+pub struct MissingTemplateVariable(pub String);
+
+impl std::fmt::Display for MissingTemplateVariable {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "No value given for template variable '{}'", self.0)
+    }
+}
+
+impl std::error::Error for MissingTemplateVariable {}
+
+/// Replace the `{name}` placeholders of a template with their variables. Braces that do
+/// not wrap a variable name, as in code snippets, are kept as they are.
+pub fn render_template(
+    template: &str,
+    variables: &HashMap<String, String>,
+) -> Result<String, MissingTemplateVariable> {
+    if let Some(missing) = PLACEHOLDER
+        .captures_iter(template)
+        .map(|captures| captures[1].to_string())
+        .find(|name| !variables.contains_key(name))
+    {
+        return Err(MissingTemplateVariable(missing));
+    }
+
+    Ok(PLACEHOLDER
+        .replace_all(template, |captures: &regex::Captures| {
+            variables[&captures[1]].clone()
+        })
+        .into_owned())
 }
 
 impl TaskTemplate {
     pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as!(
             TaskTemplate,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id?: Uuid", title, description, template_name, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id?: Uuid", title, description, template_name, executor_model, env_var_keys as "env_var_keys!: Json<Vec<String>>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM task_templates 
                ORDER BY project_id IS NULL DESC, template_name ASC"#
         )
@@ -52,7 +115,7 @@ impl TaskTemplate {
         if let Some(pid) = project_id {
             // Return only project-specific templates
             sqlx::query_as::<_, TaskTemplate>(
-                r#"SELECT id, project_id, title, description, template_name, created_at, updated_at
+                r#"SELECT id, project_id, title, description, template_name, executor_model, env_var_keys, created_at, updated_at
                    FROM task_templates 
                    WHERE project_id = ?
                    ORDER BY template_name ASC"#,
@@ -64,7 +127,7 @@ impl TaskTemplate {
             // Return only global templates
             sqlx::query_as!(
                 TaskTemplate,
-                r#"SELECT id as "id!: Uuid", project_id as "project_id?: Uuid", title, description, template_name, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+                r#"SELECT id as "id!: Uuid", project_id as "project_id?: Uuid", title, description, template_name, executor_model, env_var_keys as "env_var_keys!: Json<Vec<String>>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                    FROM task_templates 
                    WHERE project_id IS NULL
                    ORDER BY template_name ASC"#
@@ -77,7 +140,7 @@ impl TaskTemplate {
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             TaskTemplate,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id?: Uuid", title, description, template_name, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id?: Uuid", title, description, template_name, executor_model, env_var_keys as "env_var_keys!: Json<Vec<String>>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM task_templates 
                WHERE id = $1"#,
             id
@@ -88,16 +151,24 @@ impl TaskTemplate {
 
     pub async fn create(pool: &SqlitePool, data: &CreateTaskTemplate) -> Result<Self, sqlx::Error> {
         let id = Uuid::new_v4();
+        let executor_model = data
+            .executor_model
+            .as_deref()
+            .map(str::trim)
+            .filter(|model| !model.is_empty());
+        let env_var_keys = Json(data.env_var_keys.clone().unwrap_or_default());
         sqlx::query_as!(
             TaskTemplate,
-            r#"INSERT INTO task_templates (id, project_id, title, description, template_name) 
-               VALUES ($1, $2, $3, $4, $5) 
-               RETURNING id as "id!: Uuid", project_id as "project_id?: Uuid", title, description, template_name, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"INSERT INTO task_templates (id, project_id, title, description, template_name, executor_model, env_var_keys) 
+               VALUES ($1, $2, $3, $4, $5, $6, $7) 
+               RETURNING id as "id!: Uuid", project_id as "project_id?: Uuid", title, description, template_name, executor_model, env_var_keys as "env_var_keys!: Json<Vec<String>>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             id,
             data.project_id,
             data.title,
             data.description,
-            data.template_name
+            data.template_name,
+            executor_model,
+            env_var_keys
         )
         .fetch_one(pool)
         .await
@@ -120,22 +191,48 @@ impl TaskTemplate {
             .template_name
             .as_ref()
             .unwrap_or(&existing.template_name);
+        let executor_model = match data.executor_model.as_deref().map(str::trim) {
+            Some("") => None,
+            Some(model) => Some(model.to_string()),
+            None => existing.executor_model,
+        };
+        let env_var_keys = data
+            .env_var_keys
+            .clone()
+            .map(Json)
+            .unwrap_or(existing.env_var_keys);
 
         sqlx::query_as!(
             TaskTemplate,
             r#"UPDATE task_templates 
-               SET title = $2, description = $3, template_name = $4, updated_at = datetime('now', 'subsec')
+               SET title = $2, description = $3, template_name = $4, executor_model = $5, env_var_keys = $6, updated_at = datetime('now', 'subsec')
                WHERE id = $1 
-               RETURNING id as "id!: Uuid", project_id as "project_id?: Uuid", title, description, template_name, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+               RETURNING id as "id!: Uuid", project_id as "project_id?: Uuid", title, description, template_name, executor_model, env_var_keys as "env_var_keys!: Json<Vec<String>>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             id,
             title,
             description,
-            template_name
+            template_name,
+            executor_model,
+            env_var_keys
         )
         .fetch_one(pool)
         .await
     }
 
+    /// Fill in the template for a new task, failing when a placeholder has no variable
+    pub fn render(
+        &self,
+        variables: &HashMap<String, String>,
+    ) -> Result<(String, Option<String>), MissingTemplateVariable> {
+        let title = render_template(&self.title, variables)?;
+        let description = self
+            .description
+            .as_deref()
+            .map(|description| render_template(description, variables))
+            .transpose()?;
+        Ok((title, description))
+    }
+
     pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
         let result = sqlx::query!("DELETE FROM task_templates WHERE id = $1", id)
             .execute(pool)
@@ -143,3 +240,57 @@ impl TaskTemplate {
         Ok(result.rows_affected())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_render_template_substitutes_variables() {
+        let rendered = render_template(
+            "Add {method} /{path} endpoint; keep `fn handler() {}` and {not valid} as is",
+            &variables(&[("method", "GET"), ("path", "health")]),
+        )
+        .unwrap();
+        assert_eq!(
+            rendered,
+            "Add GET /health endpoint; keep `fn handler() {}` and {not valid} as is"
+        );
+    }
+
+    #[test]
+    fn test_render_rejects_missing_variable() {
+        let template = TaskTemplate {
+            id: Uuid::new_v4(),
+            project_id: None,
+            title: "Write tests for {module}".to_string(),
+            description: Some("Cover {module} edge cases in {crate_name}".to_string()),
+            template_name: "Tests".to_string(),
+            executor_model: None,
+            env_var_keys: Json(Vec::new()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        assert_eq!(
+            template.render(&variables(&[("module", "parser")])),
+            Err(MissingTemplateVariable("crate_name".to_string()))
+        );
+        assert_eq!(
+            template
+                .render(&variables(&[("module", "parser"), ("crate_name", "core")]))
+                .unwrap(),
+            (
+                "Write tests for parser".to_string(),
+                Some("Cover parser edge cases in core".to_string())
+            )
+        );
+    }
+}
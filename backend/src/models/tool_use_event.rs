@@ -0,0 +1,236 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::executor::{ActionType, NormalizedEntry, NormalizedEntryType};
+
+/// How often a tool was used across a project's runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ToolUseStat {
+    pub tool_name: String,
+    /// `action` tag of the tool use, e.g. `command_run` or `file_read`
+    pub action_type: String,
+    pub count: i64,
+    /// Average length of the entry content shown for each use
+    pub avg_content_length: f64,
+}
+
+/// Tool uses recorded from the normalized logs of finished runs
+pub struct ToolUseEvent;
+
+impl ToolUseEvent {
+    /// Replace the recorded tool uses of a run
+    pub async fn record_run(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        run_id: Uuid,
+        entries: &[NormalizedEntry],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        sqlx::query!("DELETE FROM tool_use_events WHERE run_id = $1", run_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for entry in entries {
+            let NormalizedEntryType::ToolUse {
+                tool_name,
+                action_type,
+            } = &entry.entry_type
+            else {
+                continue;
+            };
+            let id = Uuid::new_v4();
+            let action_type = action_type_tag(action_type);
+            let content_length = entry.content.chars().count() as i64;
+            sqlx::query!(
+                "INSERT INTO tool_use_events (id, run_id, task_id, tool_name, action_type, content_length) VALUES ($1, $2, $3, $4, $5, $6)",
+                id,
+                run_id,
+                task_id,
+                tool_name,
+                action_type,
+                content_length
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await
+    }
+
+    /// Count the tool uses of a project's runs recorded within `[from, to]`, most used first.
+    /// Either bound may be left open.
+    pub async fn stats_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<ToolUseStat>, sqlx::Error> {
+        sqlx::query_as!(
+            ToolUseStat,
+            r#"SELECT e.tool_name,
+                      e.action_type,
+                      COUNT(*) AS "count!: i64",
+                      AVG(e.content_length) AS "avg_content_length!: f64"
+               FROM tool_use_events e
+               JOIN tasks t ON t.id = e.task_id
+               WHERE t.project_id = $1
+                 AND ($2 IS NULL OR julianday(e.created_at) >= julianday($2))
+                 AND ($3 IS NULL OR julianday(e.created_at) <= julianday($3))
+               GROUP BY e.tool_name, e.action_type
+               ORDER BY COUNT(*) DESC, e.tool_name ASC"#,
+            project_id,
+            from,
+            to
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+fn action_type_tag(action_type: &ActionType) -> String {
+    serde_json::to_value(action_type)
+        .ok()
+        .and_then(|value| value.get("action")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{create_attempt, create_project, create_task, memory_pool};
+
+    async fn setup() -> (SqlitePool, Uuid, Uuid) {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let task_id = create_task(&pool, project_id, "task").await;
+        create_attempt(&pool, task_id, "/tmp/test-worktree").await;
+        (pool, project_id, task_id)
+    }
+
+    async fn create_run(pool: &SqlitePool, task_id: Uuid) -> Uuid {
+        let run_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO execution_processes (id, task_attempt_id, process_type, status, command, working_directory)
+             SELECT $1, id, 'codingagent', 'completed', 'claude', worktree_path FROM task_attempts WHERE task_id = $2",
+        )
+        .bind(run_id)
+        .bind(task_id)
+        .execute(pool)
+        .await
+        .unwrap();
+        run_id
+    }
+
+    fn tool_use(tool_name: &str, action_type: ActionType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: tool_name.to_string(),
+                action_type,
+            },
+            content: content.to_string(),
+            metadata: None,
+            repeat_count: 1,
+        }
+    }
+
+    fn bash(command: &str) -> NormalizedEntry {
+        tool_use(
+            "Bash",
+            ActionType::CommandRun {
+                command: command.to_string(),
+            },
+            &format!("`{}`", command),
+        )
+    }
+
+    fn read(path: &str) -> NormalizedEntry {
+        tool_use(
+            "Read",
+            ActionType::FileRead {
+                path: path.to_string(),
+            },
+            &format!("`{}`", path),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_stats_count_tool_uses_by_tool() {
+        let (pool, project_id, task_id) = setup().await;
+        let run_id = create_run(&pool, task_id).await;
+        let entries = vec![
+            bash("cargo build"),
+            read("src/lib.rs"),
+            NormalizedEntry {
+                timestamp: None,
+                entry_type: NormalizedEntryType::AssistantMessage,
+                content: "Running the tests".to_string(),
+                metadata: None,
+                repeat_count: 1,
+            },
+            bash("cargo test"),
+            read("src/main.rs"),
+            bash("ls"),
+        ];
+
+        ToolUseEvent::record_run(&pool, task_id, run_id, &entries)
+            .await
+            .unwrap();
+        // Recording a run again replaces its events
+        ToolUseEvent::record_run(&pool, task_id, run_id, &entries)
+            .await
+            .unwrap();
+
+        let stats = ToolUseEvent::stats_for_project(&pool, project_id, None, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            stats,
+            vec![
+                ToolUseStat {
+                    tool_name: "Bash".to_string(),
+                    action_type: "command_run".to_string(),
+                    count: 3,
+                    // `cargo build`, `cargo test` and `ls`
+                    avg_content_length: (13.0 + 12.0 + 4.0) / 3.0,
+                },
+                ToolUseStat {
+                    tool_name: "Read".to_string(),
+                    action_type: "file_read".to_string(),
+                    count: 2,
+                    avg_content_length: 12.5,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stats_only_include_the_time_window() {
+        let (pool, project_id, task_id) = setup().await;
+        let run_id = create_run(&pool, task_id).await;
+        ToolUseEvent::record_run(&pool, task_id, run_id, &[bash("ls")])
+            .await
+            .unwrap();
+
+        let hour = chrono::Duration::hours(1);
+        let now = Utc::now();
+        let within = ToolUseEvent::stats_for_project(&pool, project_id, Some(now - hour), None)
+            .await
+            .unwrap();
+        assert_eq!(within.len(), 1);
+
+        let before = ToolUseEvent::stats_for_project(
+            &pool,
+            project_id,
+            Some(now - hour * 2),
+            Some(now - hour),
+        )
+        .await
+        .unwrap();
+        assert!(before.is_empty());
+    }
+}
@@ -0,0 +1,192 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{types::Json, FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Endpoint that receives signed task events of a project
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub url: String,
+    /// Event names the webhook is subscribed to, e.g. `task.completed`
+    #[ts(type = "Array<string>")]
+    pub events: Json<Vec<String>>,
+    /// Key for the `X-Vibe-Signature` HMAC; never sent back to clients
+    #[serde(skip_serializing)]
+    #[ts(skip)]
+    pub secret: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct CreateWebhook {
+    pub url: String,
+    pub events: Vec<String>,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "webhook_delivery_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+/// A queued event for one webhook and the outcome of delivering it
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event: String,
+    /// JSON body that is posted
+    pub payload: String,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i64,
+    /// HTTP status of the last attempt, `None` when no response arrived
+    pub response_status: Option<i64>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Webhook {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Webhook,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", url, events as "events!: Json<Vec<String>>", secret, created_at as "created_at!: DateTime<Utc>"
+               FROM webhooks
+               WHERE project_id = $1
+               ORDER BY created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Find the project's webhooks whose events include `event`
+    pub async fn find_subscribed(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        event: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Webhook,
+            r#"SELECT w.id as "id!: Uuid", w.project_id as "project_id!: Uuid", w.url, w.events as "events!: Json<Vec<String>>", w.secret, w.created_at as "created_at!: DateTime<Utc>"
+               FROM webhooks w
+               WHERE w.project_id = $1
+                 AND EXISTS (SELECT 1 FROM json_each(w.events) WHERE json_each.value = $2)
+               ORDER BY w.created_at ASC"#,
+            project_id,
+            event
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &CreateWebhook,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let events = Json(data.events.clone());
+        sqlx::query_as!(
+            Webhook,
+            r#"INSERT INTO webhooks (id, project_id, url, events, secret)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", url, events as "events!: Json<Vec<String>>", secret, created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            data.url,
+            events,
+            data.secret
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid, project_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM webhooks WHERE id = $1 AND project_id = $2",
+            id,
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+impl WebhookDelivery {
+    /// Find a webhook's deliveries, newest first
+    pub async fn find_by_webhook_id(
+        pool: &SqlitePool,
+        webhook_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WebhookDelivery,
+            r#"SELECT id as "id!: Uuid", webhook_id as "webhook_id!: Uuid", event, payload, status as "status!: WebhookDeliveryStatus", attempts, response_status, last_error, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM webhook_deliveries
+               WHERE webhook_id = $1
+               ORDER BY created_at DESC"#,
+            webhook_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        webhook_id: Uuid,
+        event: &str,
+        payload: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            WebhookDelivery,
+            r#"INSERT INTO webhook_deliveries (id, webhook_id, event, payload)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id as "id!: Uuid", webhook_id as "webhook_id!: Uuid", event, payload, status as "status!: WebhookDeliveryStatus", attempts, response_status, last_error, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            webhook_id,
+            event,
+            payload
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Record the outcome of one delivery attempt
+    pub async fn record_attempt(
+        pool: &SqlitePool,
+        id: Uuid,
+        status: WebhookDeliveryStatus,
+        response_status: Option<i64>,
+        last_error: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE webhook_deliveries
+               SET status = $2, attempts = attempts + 1, response_status = $3, last_error = $4,
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $1"#,
+            id,
+            status,
+            response_status,
+            last_error
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+//! Assemble the prompt a coding agent receives for a task.
+
+use crate::{
+    executor::{NormalizedConversation, NormalizedEntryType},
+    models::task::Task,
+};
+
+/// Tool uses listed under "Current State" in a condensed prompt
+const CONDENSED_TOOL_USES: usize = 5;
+
+/// Build the prompt for `task`.
+///
+/// The project metadata header comes first, then the project's standing instructions when
+/// there are any, then the task title and description. Without instructions the prompt is
+/// the header followed by the task.
+pub fn build_task_prompt(task: &Task, project_system_prompt: Option<&str>) -> String {
+    // The blank line keeps the indentation the prompt has always been sent with
+    let header = format!("project_id: {}\n            \n", task.project_id);
+
+    let body = match task.description {
+        Some(ref description) => format!(
+            "Task title: {}\nTask description: {}",
+            task.title, description
+        ),
+        None => format!("Task title: {}", task.title),
+    };
+
+    match project_system_prompt
+        .map(str::trim)
+        .filter(|prompt| !prompt.is_empty())
+    {
+        Some(system_prompt) => format!("{}{}\n\n{}", header, system_prompt, body),
+        None => format!("{}{}", header, body),
+    }
+}
+
+/// Whether a conversation of `entry_count` entries has more than `max_context_turns` turns,
+/// a turn being one user and one assistant entry. `None` and 0 set no limit.
+pub fn exceeds_context_turns(entry_count: usize, max_context_turns: Option<i64>) -> bool {
+    match max_context_turns {
+        Some(turns) if turns > 0 => entry_count > turns as usize * 2,
+        _ => false,
+    }
+}
+
+/// Build the sections appended to the task prompt of a fresh session that replaces one
+/// whose history grew too long: the previous run's summary, its last tool uses and the
+/// follow-up that was asked for.
+pub fn build_condensed_context(
+    summary: Option<&str>,
+    previous: &NormalizedConversation,
+    followup_prompt: &str,
+) -> String {
+    let summary = summary
+        .map(str::trim)
+        .filter(|summary| !summary.is_empty())
+        .unwrap_or("No summary of the previous run is available.");
+
+    let tool_uses: Vec<String> = previous
+        .entries
+        .iter()
+        .filter_map(|entry| match &entry.entry_type {
+            NormalizedEntryType::ToolUse { tool_name, .. } => {
+                Some(format!("- {}: {}", tool_name, entry.content.trim()))
+            }
+            _ => None,
+        })
+        .collect();
+    let current_state = if tool_uses.is_empty() {
+        "No tool uses were recorded.".to_string()
+    } else {
+        tool_uses[tool_uses.len().saturating_sub(CONDENSED_TOOL_USES)..].join("\n")
+    };
+
+    format!(
+        "\n\n### Previous Work Summary\n{}\n\n### Current State\n{}\n\n### Follow-up Request\n{}",
+        summary, current_state, followup_prompt
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        executor::{ActionType, NormalizedEntry},
+        models::task::TaskStatus,
+    };
+
+    fn task(description: Option<&str>) -> Task {
+        Task {
+            id: Uuid::new_v4(),
+            project_id: Uuid::new_v4(),
+            title: "Add login".to_string(),
+            description: description.map(str::to_string),
+            status: TaskStatus::Todo,
+            parent_task_attempt: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            complexity_score: 0.0,
+            timeout_seconds: None,
+            session_id: None,
+            executor_model: None,
+            priority: 50,
+            parent_task_id: None,
+            archived_at: None,
+        }
+    }
+
+    #[test]
+    fn test_system_prompt_goes_between_header_and_task() {
+        let task = task(Some("Use OAuth"));
+
+        let prompt = build_task_prompt(&task, Some("Always use Rust 2021 edition"));
+
+        let header = prompt.find(&format!("project_id: {}", task.project_id));
+        let system = prompt.find("Always use Rust 2021 edition");
+        let title = prompt.find("Task title: Add login");
+        let description = prompt.find("Task description: Use OAuth");
+        assert!(header.is_some() && system.is_some() && title.is_some() && description.is_some());
+        assert!(header < system && system < title && title < description);
+    }
+
+    #[test]
+    fn test_without_system_prompt_matches_plain_prompt() {
+        let with_description = task(Some("Use OAuth"));
+        assert_eq!(
+            build_task_prompt(&with_description, None),
+            format!(
+                "project_id: {}\n            \nTask title: Add login\nTask description: Use OAuth",
+                with_description.project_id
+            )
+        );
+
+        let without_description = task(None);
+        assert_eq!(
+            build_task_prompt(&without_description, None),
+            format!(
+                "project_id: {}\n            \nTask title: Add login",
+                without_description.project_id
+            )
+        );
+        assert_eq!(
+            build_task_prompt(&without_description, Some("  ")),
+            build_task_prompt(&without_description, None)
+        );
+    }
+
+    fn bash(command: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: "Bash".to_string(),
+                action_type: ActionType::CommandRun {
+                    command: command.to_string(),
+                },
+            },
+            content: format!("`{}`", command),
+            metadata: None,
+            repeat_count: 1,
+        }
+    }
+
+    #[test]
+    fn test_context_turn_limit() {
+        // Two entries make a turn
+        assert!(!exceeds_context_turns(20, Some(10)));
+        assert!(exceeds_context_turns(21, Some(10)));
+        // No limit set
+        assert!(!exceeds_context_turns(1_000, None));
+        assert!(!exceeds_context_turns(1_000, Some(0)));
+    }
+
+    #[test]
+    fn test_condensed_context_carries_summary_and_last_tool_uses() {
+        let previous = NormalizedConversation {
+            entries: (1..=7).map(|i| bash(&format!("step {}", i))).collect(),
+            session_id: Some("sess-1".to_string()),
+            executor_type: "claude".to_string(),
+            prompt: None,
+            summary: None,
+            stats: None,
+            metadata: Default::default(),
+        };
+
+        let context = build_condensed_context(
+            Some("Moved the parser to nom; two tests still fail."),
+            &previous,
+            "Fix the remaining tests",
+        );
+        let prompt = format!("{}{}", build_task_prompt(&task(None), None), context);
+
+        let title = prompt.find("Task title: Add login").unwrap();
+        let summary = prompt
+            .find("### Previous Work Summary\nMoved the parser to nom; two tests still fail.")
+            .unwrap();
+        let state = prompt.find("### Current State\n- Bash: `step 3`").unwrap();
+        let followup = prompt
+            .find("### Follow-up Request\nFix the remaining tests")
+            .unwrap();
+        assert!(title < summary && summary < state && state < followup);
+        assert!(prompt.contains("- Bash: `step 7`"));
+        assert!(!prompt.contains("`step 2`"));
+    }
+
+    #[test]
+    fn test_condensed_context_without_summary() {
+        let previous = NormalizedConversation {
+            entries: Vec::new(),
+            session_id: None,
+            executor_type: "claude".to_string(),
+            prompt: None,
+            summary: None,
+            stats: None,
+            metadata: Default::default(),
+        };
+
+        let context = build_condensed_context(None, &previous, "Continue");
+        assert!(context.contains("No summary of the previous run is available."));
+        assert!(context.contains("No tool uses were recorded."));
+    }
+}
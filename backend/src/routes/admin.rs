@@ -0,0 +1,55 @@
+use axum::{
+    extract::State, http::StatusCode, response::Json as ResponseJson, routing::post, Router,
+};
+
+use crate::{
+    app_state::AppState,
+    executors::claude::{verify_claude_binary, ClaudeBinary},
+    models::ApiResponse,
+    utils,
+};
+
+/// Trust the claude-code binary currently installed by adding its hash to the manifest
+///
+/// POST /api/admin/trust-binary
+pub async fn trust_binary(
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<ClaudeBinary>>, StatusCode> {
+    let binary = match ClaudeBinary::detect().await {
+        Ok(binary) => binary,
+        Err(e) => {
+            return Ok(ResponseJson(ApiResponse {
+                success: false,
+                data: None,
+                message: Some(format!("Failed to inspect claude-code binary: {}", e)),
+            }));
+        }
+    };
+
+    let mut config = app_state.get_config().write().await;
+    config
+        .binary_manifest
+        .insert(binary.version.clone(), binary.sha256.clone());
+    if let Err(e) = config.save(&utils::config_path()) {
+        tracing::error!("Failed to save binary manifest: {}", e);
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+    verify_claude_binary(config.verify_binary, &config.binary_manifest).await;
+    drop(config);
+
+    tracing::info!(
+        "Trusted claude-code {} at {} with SHA-256 {}",
+        binary.version,
+        binary.path,
+        binary.sha256
+    );
+    Ok(ResponseJson(ApiResponse {
+        success: true,
+        data: Some(binary),
+        message: Some("Binary added to the manifest".to_string()),
+    }))
+}
+
+pub fn admin_router() -> Router<AppState> {
+    Router::new().route("/admin/trust-binary", post(trust_binary))
+}
@@ -0,0 +1,52 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json as ResponseJson,
+    routing::get,
+    Router,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    models::{audit_log::AuditLogEntry, ApiResponse},
+    routes::tasks::project_pool,
+    services::AuditLogger,
+};
+
+fn default_limit() -> i64 {
+    50
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditLogQuery {
+    entity_id: Uuid,
+    /// Project of the entity; changes to a project's tasks, runs and executor config are
+    /// logged in its database
+    project_id: Option<Uuid>,
+    #[serde(default = "default_limit")]
+    limit: i64,
+}
+
+pub async fn get_audit_log(
+    State(app_state): State<AppState>,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<AuditLogEntry>>>, StatusCode> {
+    let pool = match query.project_id {
+        Some(project_id) => project_pool(&app_state, project_id).await?,
+        None => app_state.db_pool.clone(),
+    };
+    let audit = AuditLogger::new(pool);
+    match audit.entries(query.entity_id, query.limit).await {
+        Ok(entries) => Ok(ResponseJson(ApiResponse::success(entries))),
+        Err(e) => {
+            tracing::error!("Failed to fetch audit log of {}: {}", query.entity_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub fn audit_router() -> Router<AppState> {
+    Router::new().route("/audit", get(get_audit_log))
+}
@@ -51,6 +51,12 @@ async fn update_config(
             *config = new_config.clone();
             drop(config);
 
+            crate::executors::claude::verify_claude_binary(
+                new_config.verify_binary,
+                &new_config.binary_manifest,
+            )
+            .await;
+
             app_state
                 .update_analytics_config(new_config.analytics_enabled.unwrap_or(true))
                 .await;
@@ -1,11 +1,39 @@
-use axum::response::Json;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
 
-use crate::models::ApiResponse;
+use crate::{
+    app_state::AppState,
+    models::{executor_health::HealthCheckResult, task_attempt::TaskAttempt, ApiResponse},
+    services::system_health::{check_system_health, SystemHealthReport},
+};
 
-pub async fn health_check() -> Json<ApiResponse<String>> {
-    Json(ApiResponse {
-        success: true,
-        data: Some("OK".to_string()),
-        message: Some("Service is healthy".to_string()),
-    })
+/// Check the database, the Claude CLI and the free space for worktrees. Responds 200 when
+/// all are ok, 207 when some are degraded and 503 when any failed.
+pub async fn health_check(State(app_state): State<AppState>) -> SystemHealthReport {
+    check_system_health(&app_state.db_pool, &TaskAttempt::get_worktree_base_dir()).await
+}
+
+/// Recorded health probe results of one executor over the last 24 hours, newest first
+pub async fn get_executor_health_history(
+    Path(executor_name): Path<String>,
+    State(app_state): State<AppState>,
+) -> Result<Json<ApiResponse<Vec<HealthCheckResult>>>, StatusCode> {
+    match HealthCheckResult::find_by_executor_name(&app_state.db_pool, &executor_name).await {
+        Ok(history) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(history),
+            message: None,
+        })),
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch health history of executor {}: {}",
+                executor_name,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
 }
@@ -0,0 +1,160 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json as ResponseJson, Response},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    models::{
+        label::{AttachLabel, CreateLabel, Label, LabelError},
+        project::Project,
+        ApiResponse,
+    },
+    routes::tasks::{project_pool, task_pool},
+};
+
+/// List the project's labels
+///
+/// GET /api/projects/:project_id/labels
+pub async fn get_project_labels(
+    Path(project_id): Path<Uuid>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<Label>>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    match Label::find_by_project_id(&pool, project_id).await {
+        Ok(labels) => Ok(ResponseJson(ApiResponse::success(labels))),
+        Err(e) => {
+            tracing::error!("Failed to fetch labels of project {}: {}", project_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Create a label in a project
+///
+/// POST /api/labels
+pub async fn create_label(
+    State(app_state): State<AppState>,
+    Json(payload): Json<CreateLabel>,
+) -> Result<Response, StatusCode> {
+    match Project::exists(&app_state.db_pool, payload.project_id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check project existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
+
+    let pool = project_pool(&app_state, payload.project_id).await?;
+    match Label::create(&pool, &payload).await {
+        Ok(label) => Ok(ResponseJson(ApiResponse::success(label)).into_response()),
+        Err(e @ (LabelError::InvalidName | LabelError::InvalidColor(_))) => Ok((
+            StatusCode::BAD_REQUEST,
+            ResponseJson(ApiResponse::<Label>::error(&e.to_string())),
+        )
+            .into_response()),
+        Err(e @ LabelError::DuplicateName(_)) => Ok((
+            StatusCode::CONFLICT,
+            ResponseJson(ApiResponse::<Label>::error(&e.to_string())),
+        )
+            .into_response()),
+        Err(e) => {
+            tracing::error!(
+                "Failed to create label in project {}: {}",
+                payload.project_id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Delete a label, detaching it from every task
+///
+/// DELETE /api/labels/:label_id
+pub async fn delete_label(
+    Path(label_id): Path<Uuid>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
+    // Labels belong to a project, so at most one database has the label
+    let deleted = async {
+        let mut deleted = 0;
+        for pool in app_state.task_pools().await? {
+            deleted += Label::delete(&pool, label_id).await?;
+        }
+        Ok::<_, sqlx::Error>(deleted)
+    }
+    .await;
+    match deleted {
+        Ok(0) => Err(StatusCode::NOT_FOUND),
+        Ok(_) => Ok(ResponseJson(ApiResponse::success(()))),
+        Err(e) => {
+            tracing::error!("Failed to delete label {}: {}", label_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Attach a label of the task's project to the task
+///
+/// POST /api/tasks/:task_id/labels
+pub async fn attach_task_label(
+    Path(task_id): Path<Uuid>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<AttachLabel>,
+) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
+    let pool = task_pool(&app_state, task_id).await?;
+    match Label::attach(&pool, task_id, payload.label_id).await {
+        Ok(()) => Ok(ResponseJson(ApiResponse::success(()))),
+        Err(LabelError::NotFound) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!(
+                "Failed to attach label {} to task {}: {}",
+                payload.label_id,
+                task_id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Detach a label from a task
+///
+/// DELETE /api/tasks/:task_id/labels/:label_id
+pub async fn detach_task_label(
+    Path((task_id, label_id)): Path<(Uuid, Uuid)>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
+    let pool = task_pool(&app_state, task_id).await?;
+    match Label::detach(&pool, task_id, label_id).await {
+        Ok(0) => Err(StatusCode::NOT_FOUND),
+        Ok(_) => Ok(ResponseJson(ApiResponse::success(()))),
+        Err(e) => {
+            tracing::error!(
+                "Failed to detach label {} from task {}: {}",
+                label_id,
+                task_id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub fn labels_router() -> Router<AppState> {
+    Router::new()
+        .route("/labels", post(create_label))
+        .route("/labels/:label_id", delete(delete_label))
+        .route("/projects/:project_id/labels", get(get_project_labels))
+        .route("/tasks/:task_id/labels", post(attach_task_label))
+        .route(
+            "/tasks/:task_id/labels/:label_id",
+            delete(detach_task_label),
+        )
+}
@@ -1,9 +1,15 @@
+pub mod admin;
+pub mod audit;
 pub mod auth;
 pub mod config;
 pub mod filesystem;
 pub mod health;
+pub mod labels;
+pub mod notifications;
 pub mod projects;
 pub mod stream;
 pub mod task_attempts;
+pub mod task_runs;
 pub mod task_templates;
 pub mod tasks;
+pub mod webhooks;
@@ -0,0 +1,139 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    http::StatusCode,
+    response::{Json as ResponseJson, Response},
+    routing::{get, patch},
+    Router,
+};
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    models::{notification::Notification, ApiResponse},
+    services::NotificationService,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct NotificationsQuery {
+    /// Only return notifications that have not been read yet
+    #[serde(default)]
+    unread: bool,
+}
+
+/// The user's notifications from every task database, newest first
+pub async fn get_notifications(
+    State(app_state): State<AppState>,
+    Query(query): Query<NotificationsQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<Notification>>>, StatusCode> {
+    let notifications = async {
+        let mut notifications = Vec::new();
+        for pool in app_state.task_pools().await? {
+            notifications.extend(
+                Notification::find_by_user_id(&pool, app_state.user_id(), query.unread).await?,
+            );
+        }
+        notifications.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok::<_, sqlx::Error>(notifications)
+    }
+    .await;
+    match notifications {
+        Ok(notifications) => Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: Some(notifications),
+            message: None,
+        })),
+        Err(e) => {
+            tracing::error!("Failed to fetch notifications: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn mark_notification_read(
+    Path(notification_id): Path<Uuid>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Notification>>, StatusCode> {
+    let marked = async {
+        for pool in app_state.task_pools().await? {
+            if let Some(notification) =
+                Notification::mark_read(&pool, notification_id, app_state.user_id()).await?
+            {
+                return Ok(Some(notification));
+            }
+        }
+        Ok::<_, sqlx::Error>(None)
+    }
+    .await;
+    match marked {
+        Ok(Some(notification)) => Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: Some(notification),
+            message: Some("Notification marked as read".to_string()),
+        })),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!(
+                "Failed to mark notification {} as read: {}",
+                notification_id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// WebSocket pushing each new notification to the client as a JSON text message
+///
+/// GET /api/notifications/ws
+pub async fn notifications_ws(ws: WebSocketUpgrade, State(app_state): State<AppState>) -> Response {
+    let user_id = app_state.user_id().to_string();
+    ws.on_upgrade(move |socket| forward_notifications(socket, user_id))
+}
+
+async fn forward_notifications(mut socket: WebSocket, user_id: String) {
+    let mut receiver = NotificationService::subscribe(&user_id);
+
+    loop {
+        tokio::select! {
+            notification = receiver.recv() => {
+                let notification = match notification {
+                    Ok(notification) => notification,
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Notification client lagged, skipped {} notifications", skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                let payload = match serde_json::to_string(&notification) {
+                    Ok(payload) => payload,
+                    Err(e) => {
+                        tracing::error!("Failed to serialize notification {}: {}", notification.id, e);
+                        continue;
+                    }
+                };
+                if socket.send(Message::Text(payload)).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => {
+                // Clients only listen; stop once they close or the connection drops
+                match message {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                }
+            }
+        }
+    }
+}
+
+pub fn notifications_router() -> Router<AppState> {
+    Router::new()
+        .route("/notifications", get(get_notifications))
+        .route("/notifications/ws", get(notifications_ws))
+        .route("/notifications/:id/read", patch(mark_notification_read))
+}
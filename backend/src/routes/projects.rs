@@ -1,23 +1,44 @@
 use std::collections::HashMap;
 
 use axum::{
-    extract::{Path, Query, State},
+    extract::{DefaultBodyLimit, Path, Query, State},
     http::StatusCode,
-    response::Json as ResponseJson,
+    response::{IntoResponse, Json as ResponseJson, Response},
     routing::get,
     Json, Router,
 };
 use uuid::Uuid;
 
 use crate::{
+    api_key_store::{ApiKeyError, ProjectApiKey, ProjectApiKeyStore, SetProjectApiKey},
     app_state::AppState,
+    execution_monitor,
+    executor::ExecutorConfig,
+    mcp::client_config,
     models::{
         project::{
-            CreateBranch, CreateProject, GitBranch, Project, ProjectWithBranch, SearchMatchType,
-            SearchResult, UpdateProject,
+            CreateBranch, CreateProject, GitBranch, Project, ProjectBudget, ProjectError,
+            ProjectGitStatus, ProjectMcpConfig, ProjectPrompts, ProjectWithBranch, SearchMatchType,
+            SearchResult, SetProjectBaseBranch, SetProjectBudget, SetProjectSprint, UpdateProject,
+            UpdateProjectPrompts, WorktreeGcEntry,
         },
+        project_archive::{ArchiveError, ImportedProject, ProjectArchive},
+        project_executor_config::{ProjectExecutorConfig, UpdateProjectExecutorConfig},
+        task::{Task, TaskTimingStats},
+        task_run::{CancelledRuns, TaskRun},
+        tool_use_event::{ToolUseEvent, ToolUseStat},
         ApiResponse,
     },
+    routes::tasks::project_pool,
+    services::{
+        audit_logger::{
+            PROJECT_BUDGET_UPDATED, PROJECT_CREATED, PROJECT_DELETED,
+            PROJECT_EXECUTOR_CONFIG_UPDATED, PROJECT_MCP_CONFIG_UPDATED, PROJECT_PROMPTS_UPDATED,
+            PROJECT_SESSION_RESET, PROJECT_UPDATED,
+        },
+        git_service::{is_valid_repo, GitService},
+        AuditLogger, ProcessService,
+    },
 };
 
 pub async fn get_projects(
@@ -96,6 +117,24 @@ pub async fn get_project_branches(
     }
 }
 
+pub async fn get_project_git_status(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<ProjectGitStatus>>, StatusCode> {
+    match Project::git_status(&app_state.db_pool, id).await {
+        Ok(status) => Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: Some(status),
+            message: None,
+        })),
+        Err(ProjectError::ProjectNotFound) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to get git status for project {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub async fn create_project_branch(
     Path(id): Path<Uuid>,
     State(app_state): State<AppState>,
@@ -150,10 +189,22 @@ pub async fn create_project_branch(
     }
 }
 
+/// Build the 422 response returned when a project path is not a usable git repository
+fn invalid_git_repo(message: &str) -> Response {
+    (
+        StatusCode::UNPROCESSABLE_ENTITY,
+        ResponseJson(serde_json::json!({
+            "code": "INVALID_GIT_REPO",
+            "message": message,
+        })),
+    )
+        .into_response()
+}
+
 pub async fn create_project(
     State(app_state): State<AppState>,
     Json(payload): Json<CreateProject>,
-) -> Result<ResponseJson<ApiResponse<Project>>, StatusCode> {
+) -> Result<ResponseJson<ApiResponse<Project>>, Response> {
     let id = Uuid::new_v4();
 
     tracing::debug!("Creating project '{}'", payload.name);
@@ -172,7 +223,7 @@ pub async fn create_project(
         }
         Err(e) => {
             tracing::error!("Failed to check for existing git repo path: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
         }
     }
 
@@ -182,27 +233,23 @@ pub async fn create_project(
     if payload.use_existing_repo {
         // For existing repos, validate that the path exists and is a git repository
         if !path.exists() {
-            return Ok(ResponseJson(ApiResponse {
-                success: false,
-                data: None,
-                message: Some("The specified path does not exist".to_string()),
-            }));
+            return Err(invalid_git_repo("The specified path does not exist"));
         }
 
         if !path.is_dir() {
-            return Ok(ResponseJson(ApiResponse {
-                success: false,
-                data: None,
-                message: Some("The specified path is not a directory".to_string()),
-            }));
+            return Err(invalid_git_repo("The specified path is not a directory"));
         }
 
-        if !path.join(".git").exists() {
-            return Ok(ResponseJson(ApiResponse {
-                success: false,
-                data: None,
-                message: Some("The specified directory is not a git repository".to_string()),
-            }));
+        if std::fs::read_dir(path).is_err() {
+            return Err(invalid_git_repo(
+                "The specified directory is not readable by the server",
+            ));
+        }
+
+        if !is_valid_repo(path).await {
+            return Err(invalid_git_repo(
+                "The specified directory is not a git repository",
+            ));
         }
     } else {
         // For new repos, create directory and initialize git
@@ -249,7 +296,31 @@ pub async fn create_project(
         }
     }
 
-    match Project::create(&app_state.db_pool, &payload, id).await {
+    // New worktrees branch off whatever the repository currently has checked out
+    let base_branch = GitService::new(path)
+        .and_then(|git_service| git_service.get_default_branch_name())
+        .unwrap_or_else(|_| "main".to_string());
+
+    let audit = AuditLogger::new(app_state.db_pool.clone());
+    let created = async {
+        let mut tx = audit.begin().await?;
+        let project = Project::create(&mut *tx, &payload, id, &base_branch).await?;
+        audit
+            .log_change(
+                &mut tx,
+                PROJECT_CREATED,
+                project.id,
+                app_state.user_id(),
+                None,
+                Some(&project),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(project)
+    }
+    .await;
+
+    match created {
         Ok(project) => {
             // Track project creation event
             app_state
@@ -272,6 +343,830 @@ pub async fn create_project(
         }
         Err(e) => {
             tracing::error!("Failed to create project: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR.into_response())
+        }
+    }
+}
+
+pub async fn set_project_base_branch(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<SetProjectBaseBranch>,
+) -> Result<ResponseJson<ApiResponse<Project>>, StatusCode> {
+    let project = match Project::find_by_id(&app_state.db_pool, id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch project: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let branch_exists = GitService::new(&project.git_repo_path)
+        .map(|git_service| git_service.branch_exists(&payload.base_branch))
+        .unwrap_or(false);
+    if !branch_exists {
+        return Ok(ResponseJson(ApiResponse {
+            success: false,
+            data: None,
+            message: Some(format!("Branch '{}' not found", payload.base_branch)),
+        }));
+    }
+
+    let audit = AuditLogger::new(app_state.db_pool.clone());
+    let updated = async {
+        let mut tx = audit.begin().await?;
+        let updated = Project::set_git_branch(&mut *tx, id, &payload.base_branch).await?;
+        audit
+            .log_change(
+                &mut tx,
+                PROJECT_UPDATED,
+                id,
+                app_state.user_id(),
+                Some(&project),
+                Some(&updated),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(updated)
+    }
+    .await;
+
+    match updated {
+        Ok(updated) => Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: Some(updated),
+            message: Some("Project base branch updated successfully".to_string()),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to set base branch for project {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Clear the project's pinned Claude session so the next task starts a fresh one
+pub async fn reset_project_session(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Project>>, StatusCode> {
+    let audit = AuditLogger::new(app_state.db_pool.clone());
+    let reset = async {
+        let mut tx = audit.begin().await?;
+        let Some(project) = Project::set_pinned_session_id(&mut *tx, id, None).await? else {
+            return Ok(None);
+        };
+        audit
+            .log_change(
+                &mut tx,
+                PROJECT_SESSION_RESET,
+                id,
+                app_state.user_id(),
+                None,
+                Some(&project),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Some(project))
+    }
+    .await;
+
+    match reset {
+        Ok(Some(project)) => Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: Some(project),
+            message: Some("Project session reset successfully".to_string()),
+        })),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to reset session for project {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// The project's executor configuration; `null` when runs use the globally detected CLI
+pub async fn get_project_executor_config(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Option<ProjectExecutorConfig>>>, StatusCode> {
+    match Project::exists(&app_state.db_pool, id).await {
+        Ok(true) => {}
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch project: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+    let pool = project_pool(&app_state, id).await?;
+
+    match ProjectExecutorConfig::find_by_project_id(&pool, id).await {
+        Ok(config) => Ok(ResponseJson(ApiResponse::success(config))),
+        Err(e) => {
+            tracing::error!("Failed to fetch executor config for project {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// The project's API keys, showing only their last characters
+///
+/// GET /api/projects/:id/api-keys
+pub async fn get_project_api_keys(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<ProjectApiKey>>>, StatusCode> {
+    match Project::exists(&app_state.db_pool, id).await {
+        Ok(true) => {}
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch project: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+    let pool = project_pool(&app_state, id).await?;
+
+    match ProjectApiKeyStore::list(&pool, id).await {
+        Ok(keys) => Ok(ResponseJson(ApiResponse::success(keys))),
+        Err(e) => {
+            tracing::error!("Failed to fetch API keys of project {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Give the executor's runs of this project their own API key instead of the server's
+///
+/// PUT /api/projects/:id/api-keys/:executor_type
+pub async fn set_project_api_key(
+    Path((id, executor_type)): Path<(Uuid, String)>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<SetProjectApiKey>,
+) -> Result<ResponseJson<ApiResponse<ProjectApiKey>>, StatusCode> {
+    match Project::exists(&app_state.db_pool, id).await {
+        Ok(true) => {}
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch project: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+    let pool = project_pool(&app_state, id).await?;
+
+    match ProjectApiKeyStore::set(&pool, id, &executor_type, &payload.api_key).await {
+        Ok(key) => Ok(ResponseJson(ApiResponse::success(key))),
+        Err(ApiKeyError::Database(e)) => {
+            tracing::error!("Failed to store API key of project {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+        Err(e) => {
+            tracing::warn!("Cannot store API key of project {}: {}", id, e);
+            Ok(ResponseJson(ApiResponse::error(&e.to_string())))
+        }
+    }
+}
+
+/// DELETE /api/projects/:id/api-keys/:executor_type
+pub async fn delete_project_api_key(
+    Path((id, executor_type)): Path<(Uuid, String)>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
+    let pool = project_pool(&app_state, id).await?;
+    match ProjectApiKeyStore::delete(&pool, id, &executor_type).await {
+        Ok(0) => Err(StatusCode::NOT_FOUND),
+        Ok(_) => Ok(ResponseJson(ApiResponse::success(()))),
+        Err(e) => {
+            tracing::error!("Failed to delete API key of project {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Executors whose CLI command a project can configure
+const CONFIGURABLE_EXECUTORS: [ExecutorConfig; 2] =
+    [ExecutorConfig::Claude, ExecutorConfig::ClaudePlan];
+
+pub async fn set_project_executor_config(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+    Json(mut payload): Json<UpdateProjectExecutorConfig>,
+) -> Result<ResponseJson<ApiResponse<ProjectExecutorConfig>>, StatusCode> {
+    match Project::exists(&app_state.db_pool, id).await {
+        Ok(true) => {}
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch project: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+    let pool = project_pool(&app_state, id).await?;
+
+    if !CONFIGURABLE_EXECUTORS
+        .iter()
+        .any(|executor| executor.to_string() == payload.executor_type)
+    {
+        return Ok(ResponseJson(ApiResponse::error(&format!(
+            "Executor '{}' cannot be configured per project, expected one of: {}",
+            payload.executor_type,
+            CONFIGURABLE_EXECUTORS
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))));
+    }
+    payload.binary_path = payload.binary_path.trim().to_string();
+    if payload.binary_path.is_empty() {
+        return Ok(ResponseJson(ApiResponse::error("Binary path is required")));
+    }
+    let blank_to_none = |value: Option<String>| value.filter(|value| !value.trim().is_empty());
+    payload.model = blank_to_none(payload.model.take());
+    payload.extra_flags = blank_to_none(payload.extra_flags.take());
+
+    let before = match ProjectExecutorConfig::find_by_project_id(&pool, id).await {
+        Ok(before) => before,
+        Err(e) => {
+            tracing::error!("Failed to fetch executor config for project {}: {}", id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    let audit = AuditLogger::new(pool.clone());
+    let upserted = async {
+        let mut tx = audit.begin().await?;
+        let config = ProjectExecutorConfig::upsert(&mut *tx, id, &payload).await?;
+        audit
+            .log_change(
+                &mut tx,
+                PROJECT_EXECUTOR_CONFIG_UPDATED,
+                id,
+                app_state.user_id(),
+                before.as_ref(),
+                Some(&config),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(config)
+    }
+    .await;
+
+    match upserted {
+        Ok(config) => Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: Some(config),
+            message: Some("Project executor config updated successfully".to_string()),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to set executor config for project {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct WorktreeGcQuery {
+    /// Report the orphaned worktrees without removing them
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// List the project's orphaned worktrees without removing them
+pub async fn preview_project_worktree_gc(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<WorktreeGcEntry>>>, StatusCode> {
+    run_project_worktree_gc(&app_state, id, true).await
+}
+
+/// Remove the project's orphaned worktrees, or only list them with `?dry_run=true`
+pub async fn gc_project_worktrees(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+    Query(query): Query<WorktreeGcQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<WorktreeGcEntry>>>, StatusCode> {
+    run_project_worktree_gc(&app_state, id, query.dry_run).await
+}
+
+async fn run_project_worktree_gc(
+    app_state: &AppState,
+    id: Uuid,
+    dry_run: bool,
+) -> Result<ResponseJson<ApiResponse<Vec<WorktreeGcEntry>>>, StatusCode> {
+    let project = match Project::find_by_id(&app_state.db_pool, id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch project {}: {}", id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    match execution_monitor::gc_project_worktrees(app_state, &project, dry_run).await {
+        Ok(entries) => {
+            let message = if dry_run {
+                format!("{} orphaned worktrees would be removed", entries.len())
+            } else {
+                format!(
+                    "Removed {} of {} orphaned worktrees",
+                    entries.iter().filter(|entry| entry.removed).count(),
+                    entries.len()
+                )
+            };
+            Ok(ResponseJson(ApiResponse {
+                success: true,
+                data: Some(entries),
+                message: Some(message),
+            }))
+        }
+        Err(e) => {
+            tracing::error!("Failed to collect worktrees of project {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn set_project_sprint(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<SetProjectSprint>,
+) -> Result<ResponseJson<ApiResponse<Project>>, StatusCode> {
+    let sprint_name = payload.sprint_name.trim();
+    if sprint_name.is_empty() {
+        return Ok(ResponseJson(ApiResponse {
+            success: false,
+            data: None,
+            message: Some("Sprint name cannot be empty".to_string()),
+        }));
+    }
+
+    let before = match Project::find_by_id(&app_state.db_pool, id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch project: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let audit = AuditLogger::new(app_state.db_pool.clone());
+    let updated = async {
+        let mut tx = audit.begin().await?;
+        let Some(project) = Project::with_sprint(&mut tx, id, sprint_name).await? else {
+            return Ok(None);
+        };
+        audit
+            .log_change(
+                &mut tx,
+                PROJECT_UPDATED,
+                id,
+                app_state.user_id(),
+                Some(&before),
+                Some(&project),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Some(project))
+    }
+    .await;
+
+    match updated {
+        Ok(Some(project)) => Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: Some(project),
+            message: Some("Project sprint updated successfully".to_string()),
+        })),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to set sprint for project {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_project_prompts(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<ProjectPrompts>>, StatusCode> {
+    match Project::find_prompts(&app_state.db_pool, id).await {
+        Ok(Some(prompts)) => Ok(ResponseJson(ApiResponse::success(prompts))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch prompts for project {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn update_project_prompts(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<UpdateProjectPrompts>,
+) -> Result<ResponseJson<ApiResponse<ProjectPrompts>>, StatusCode> {
+    let existing = match Project::find_prompts(&app_state.db_pool, id).await {
+        Ok(Some(prompts)) => prompts,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch prompts for project {}: {}", id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let merge = |update: Option<String>, current: Option<String>| match update {
+        Some(value) if value.trim().is_empty() => None,
+        Some(value) => Some(value),
+        None => current,
+    };
+    let prompts = ProjectPrompts {
+        system_prompt: merge(payload.system_prompt, existing.system_prompt.clone()),
+        prompt_prefix: merge(payload.prompt_prefix, existing.prompt_prefix.clone()),
+    };
+
+    let audit = AuditLogger::new(app_state.db_pool.clone());
+    let updated = async {
+        let mut tx = audit.begin().await?;
+        let Some(prompts) = Project::set_prompts(&mut *tx, id, &prompts).await? else {
+            return Ok(None);
+        };
+        audit
+            .log_change(
+                &mut tx,
+                PROJECT_PROMPTS_UPDATED,
+                id,
+                app_state.user_id(),
+                Some(&existing),
+                Some(&prompts),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Some(prompts))
+    }
+    .await;
+
+    match updated {
+        Ok(Some(prompts)) => Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: Some(prompts),
+            message: Some("Project prompts updated successfully".to_string()),
+        })),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to update prompts for project {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_project_mcp_config(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<ProjectMcpConfig>>, StatusCode> {
+    match Project::find_mcp_config(&app_state.db_pool, id).await {
+        Ok(Some(config)) => Ok(ResponseJson(ApiResponse::success(config))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch MCP config for project {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Replace the MCP servers given to the project's Claude runs; configs the CLI would
+/// reject are refused with 400
+///
+/// PUT /api/projects/:id/mcp-config
+pub async fn set_project_mcp_config(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<ProjectMcpConfig>,
+) -> Result<Response, StatusCode> {
+    let mcp_config_json = payload
+        .mcp_config_json
+        .as_deref()
+        .filter(|json| !json.trim().is_empty());
+    if let Some(json) = mcp_config_json {
+        if let Err(e) = client_config::validate(json) {
+            return Ok((
+                StatusCode::BAD_REQUEST,
+                ResponseJson(ApiResponse::<ProjectMcpConfig>::error(&e.to_string())),
+            )
+                .into_response());
+        }
+    }
+
+    let before = match Project::find_mcp_config(&app_state.db_pool, id).await {
+        Ok(Some(config)) => config,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch MCP config for project {}: {}", id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let audit = AuditLogger::new(app_state.db_pool.clone());
+    let updated = async {
+        let mut tx = audit.begin().await?;
+        let Some(config) = Project::set_mcp_config(&mut *tx, id, mcp_config_json).await? else {
+            return Ok(None);
+        };
+        audit
+            .log_change(
+                &mut tx,
+                PROJECT_MCP_CONFIG_UPDATED,
+                id,
+                app_state.user_id(),
+                Some(&before),
+                Some(&config),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Some(config))
+    }
+    .await;
+
+    match updated {
+        Ok(Some(config)) => Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: Some(config),
+            message: Some("Project MCP config updated successfully".to_string()),
+        })
+        .into_response()),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to update MCP config for project {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_project_budget(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<ProjectBudget>>, StatusCode> {
+    match Project::find_budget(&app_state.db_pool, id).await {
+        Ok(Some(budget)) => Ok(ResponseJson(ApiResponse::success(budget))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch budget for project {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn set_project_budget(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<SetProjectBudget>,
+) -> Result<ResponseJson<ApiResponse<ProjectBudget>>, StatusCode> {
+    if payload
+        .monthly_cost_limit_usd
+        .is_some_and(|limit| !limit.is_finite() || limit < 0.0)
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let before = match Project::find_budget(&app_state.db_pool, id).await {
+        Ok(Some(budget)) => budget,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch budget for project {}: {}", id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let audit = AuditLogger::new(app_state.db_pool.clone());
+    let updated = async {
+        let mut tx = audit.begin().await?;
+        let Some(budget) =
+            Project::set_budget_limit(&mut *tx, id, payload.monthly_cost_limit_usd).await?
+        else {
+            return Ok(None);
+        };
+        audit
+            .log_change(
+                &mut tx,
+                PROJECT_BUDGET_UPDATED,
+                id,
+                app_state.user_id(),
+                Some(&before),
+                Some(&budget),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Some(budget))
+    }
+    .await;
+
+    match updated {
+        Ok(Some(budget)) => Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: Some(budget),
+            message: Some("Project budget updated successfully".to_string()),
+        })),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to update budget for project {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Zero the project's spend so it can start runs again
+///
+/// POST /api/projects/:id/reset-budget
+pub async fn reset_project_budget(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<ProjectBudget>>, StatusCode> {
+    let audit = AuditLogger::new(app_state.db_pool.clone());
+    let reset = async {
+        let mut tx = audit.begin().await?;
+        let Some(before) = Project::find_budget(&mut *tx, id).await? else {
+            return Ok(None);
+        };
+        let Some(budget) = Project::reset_budget(&mut *tx, id).await? else {
+            return Ok(None);
+        };
+        audit
+            .log_change(
+                &mut tx,
+                PROJECT_BUDGET_UPDATED,
+                id,
+                app_state.user_id(),
+                Some(&before),
+                Some(&budget),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Some(budget))
+    }
+    .await;
+
+    match reset {
+        Ok(Some(budget)) => {
+            tracing::info!("Reset budget of project {}", id);
+            Ok(ResponseJson(ApiResponse {
+                success: true,
+                data: Some(budget),
+                message: Some("Project budget reset successfully".to_string()),
+            }))
+        }
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to reset budget for project {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Stop every running coding agent run of the project at once, e.g. when it hit a billing
+/// limit or an agent misbehaves
+///
+/// POST /api/projects/:id/cancel-all-running
+pub async fn cancel_all_running(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<CancelledRuns>>, StatusCode> {
+    match Project::exists(&app_state.db_pool, id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check project existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
+
+    match ProcessService::cancel_running_for_project(&app_state, id).await {
+        Ok(outcome) => Ok(ResponseJson(ApiResponse::success(outcome))),
+        Err(e) => {
+            tracing::error!("Failed to cancel running runs of project {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Largest archive `POST /api/import` accepts; archives carry the compressed logs of every run
+const MAX_ARCHIVE_BYTES: usize = 256 * 1024 * 1024;
+
+/// The project with its tasks, runs, labels and settings as a portable archive, which
+/// `POST /api/import` accepts as is
+///
+/// GET /api/projects/:id/export
+pub async fn export_project(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ProjectArchive>, StatusCode> {
+    let pool = project_pool(&app_state, id).await?;
+    match ProjectArchive::export(&pool, id).await {
+        Ok(Some(archive)) => Ok(ResponseJson(archive)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to export project {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Create a project from an archive of `GET /api/projects/:id/export`
+///
+/// POST /api/import
+pub async fn import_project(
+    State(app_state): State<AppState>,
+    Json(archive): Json<ProjectArchive>,
+) -> Result<Response, StatusCode> {
+    match archive.import(&app_state).await {
+        Ok(imported) => {
+            let message = if imported.already_imported {
+                "Project was already imported"
+            } else {
+                "Project imported successfully"
+            };
+            Ok(ResponseJson(ApiResponse {
+                success: true,
+                data: Some(imported),
+                message: Some(message.to_string()),
+            })
+            .into_response())
+        }
+        Err(e @ (ArchiveError::UnsupportedSchemaVersion(_) | ArchiveError::Invalid(_))) => Ok((
+            StatusCode::BAD_REQUEST,
+            ResponseJson(ApiResponse::<ImportedProject>::error(&e.to_string())),
+        )
+            .into_response()),
+        Err(e @ ArchiveError::DuplicateRepoPath(_)) => Ok((
+            StatusCode::CONFLICT,
+            ResponseJson(ApiResponse::<ImportedProject>::error(&e.to_string())),
+        )
+            .into_response()),
+        Err(e) => {
+            tracing::error!(
+                "Failed to import archive of project {}: {}",
+                archive.project.id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Time window of the project statistics endpoints
+#[derive(serde::Deserialize)]
+pub struct StatsQuery {
+    from: Option<chrono::DateTime<chrono::Utc>>,
+    to: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// How often each tool was used by the project's runs, most used first
+///
+/// GET /api/projects/:id/stats/tools?from=<iso>&to=<iso>
+pub async fn get_project_tool_stats(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+    Query(query): Query<StatsQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<ToolUseStat>>>, StatusCode> {
+    match Project::exists(&app_state.db_pool, id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check project existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
+    let pool = project_pool(&app_state, id).await?;
+
+    match ToolUseEvent::stats_for_project(&pool, id, query.from, query.to).await {
+        Ok(stats) => Ok(ResponseJson(ApiResponse::success(stats))),
+        Err(e) => {
+            tracing::error!("Failed to fetch tool statistics for project {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Average and 95th percentile run time of the project's tasks that completed in the window
+///
+/// GET /api/projects/:id/stats/timing?from=<iso>&to=<iso>
+pub async fn get_project_timing_stats(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+    Query(query): Query<StatsQuery>,
+) -> Result<ResponseJson<ApiResponse<TaskTimingStats>>, StatusCode> {
+    match Project::exists(&app_state.db_pool, id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check project existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
+    let pool = project_pool(&app_state, id).await?;
+
+    match Task::timing_stats_for_project(&pool, id, query.from, query.to).await {
+        Ok(stats) => Ok(ResponseJson(ApiResponse::success(stats))),
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch timing statistics for project {}: {}",
+                id,
+                e
+            );
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
@@ -325,26 +1220,80 @@ pub async fn update_project(
     // Destructure payload to handle field updates.
     // This allows us to treat `None` from the payload as an explicit `null` to clear a field,
     // as the frontend currently sends all fields on update.
+    let before = existing_project.clone();
     let UpdateProject {
         name,
         git_repo_path,
         setup_script,
         dev_script,
+        default_labels,
+        session_affinity,
+        use_commit_template,
+        git_commit_template,
+        priority_boost_after_seconds,
+        auto_summarize,
+        max_context_turns,
     } = payload;
 
     let name = name.unwrap_or(existing_project.name);
     let git_repo_path = git_repo_path.unwrap_or(existing_project.git_repo_path);
+    let default_labels = default_labels.unwrap_or(existing_project.default_labels.0);
+    let session_affinity = session_affinity.unwrap_or(existing_project.session_affinity.0);
+    let use_commit_template = use_commit_template.unwrap_or(existing_project.use_commit_template);
+    let git_commit_template = match git_commit_template {
+        Some(template) if template.trim().is_empty() => None,
+        Some(template) => Some(template),
+        None => existing_project.git_commit_template,
+    };
+    let priority_boost_after_seconds = match priority_boost_after_seconds {
+        Some(0) => None,
+        Some(seconds) if seconds < 0 => return Err(StatusCode::BAD_REQUEST),
+        Some(seconds) => Some(seconds),
+        None => existing_project.priority_boost_after_seconds,
+    };
+    let auto_summarize = auto_summarize.unwrap_or(existing_project.auto_summarize);
+    let max_context_turns = match max_context_turns {
+        Some(0) => None,
+        Some(turns) if turns < 0 => return Err(StatusCode::BAD_REQUEST),
+        Some(turns) => Some(turns),
+        None => existing_project.max_context_turns,
+    };
 
-    match Project::update(
-        &app_state.db_pool,
-        id,
-        name,
-        git_repo_path,
-        setup_script,
-        dev_script,
-    )
-    .await
-    {
+    let audit = AuditLogger::new(app_state.db_pool.clone());
+    let updated = async {
+        let mut tx = audit.begin().await?;
+        let project = Project::update(
+            &mut *tx,
+            id,
+            name,
+            git_repo_path,
+            setup_script,
+            dev_script,
+            default_labels,
+            session_affinity,
+            use_commit_template,
+            git_commit_template,
+            priority_boost_after_seconds,
+            auto_summarize,
+            max_context_turns,
+        )
+        .await?;
+        audit
+            .log_change(
+                &mut tx,
+                PROJECT_UPDATED,
+                id,
+                app_state.user_id(),
+                Some(&before),
+                Some(&project),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(project)
+    }
+    .await;
+
+    match updated {
         Ok(project) => Ok(ResponseJson(ApiResponse {
             success: true,
             data: Some(project),
@@ -361,11 +1310,36 @@ pub async fn delete_project(
     Path(id): Path<Uuid>,
     State(app_state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
-    match Project::delete(&app_state.db_pool, id).await {
+    let audit = AuditLogger::new(app_state.db_pool.clone());
+    let deleted = async {
+        let mut tx = audit.begin().await?;
+        let Some(before) = Project::find_by_id(&mut *tx, id).await? else {
+            return Ok(0);
+        };
+        let rows_affected = Project::delete(&mut *tx, id).await?;
+        audit
+            .log_change(
+                &mut tx,
+                PROJECT_DELETED,
+                id,
+                app_state.user_id(),
+                Some(&before),
+                None,
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(rows_affected)
+    }
+    .await;
+
+    match deleted {
         Ok(rows_affected) => {
             if rows_affected == 0 {
                 Err(StatusCode::NOT_FOUND)
             } else {
+                if let Err(e) = app_state.remove_project_database(id).await {
+                    tracing::error!("Failed to remove database of project {}: {}", id, e);
+                }
                 Ok(ResponseJson(ApiResponse {
                     success: true,
                     data: None,
@@ -502,6 +1476,36 @@ pub async fn search_project_files(
     }
 }
 
+pub async fn get_project_command_frequency(
+    Path(id): Path<Uuid>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<HashMap<String, u32>>>, StatusCode> {
+    match Project::exists(&app_state.db_pool, id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check project existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
+    let pool = project_pool(&app_state, id).await?;
+
+    // Sum the per-run top commands recorded for every finished coding agent run
+    let totals = match TaskRun::command_counts_by_project_id(&pool, id).await {
+        Ok(totals) => totals,
+        Err(e) => {
+            tracing::error!("Failed to fetch command counts for project {}: {}", id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    Ok(ResponseJson(ApiResponse {
+        success: true,
+        data: Some(totals),
+        message: None,
+    }))
+}
+
 async fn search_files_in_repo(
     repo_path: &str,
     query: &str,
@@ -602,7 +1606,7 @@ async fn search_files_in_repo(
 }
 
 pub fn projects_router() -> Router<AppState> {
-    use axum::routing::post;
+    use axum::routing::{patch, post, put};
 
     Router::new()
         .route("/projects", get(get_projects).post(create_project))
@@ -611,10 +1615,52 @@ pub fn projects_router() -> Router<AppState> {
             get(get_project).put(update_project).delete(delete_project),
         )
         .route("/projects/:id/with-branch", get(get_project_with_branch))
+        .route("/projects/:id/base-branch", put(set_project_base_branch))
+        .route("/projects/:id/sprint", patch(set_project_sprint))
+        .route("/projects/:id/reset-session", post(reset_project_session))
+        .route(
+            "/projects/:id/prompts",
+            get(get_project_prompts).patch(update_project_prompts),
+        )
+        .route(
+            "/projects/:id/mcp-config",
+            get(get_project_mcp_config).put(set_project_mcp_config),
+        )
+        .route(
+            "/projects/:id/budget",
+            get(get_project_budget).put(set_project_budget),
+        )
+        .route("/projects/:id/reset-budget", post(reset_project_budget))
+        .route("/projects/:id/cancel-all-running", post(cancel_all_running))
+        .route("/projects/:id/export", get(export_project))
+        .route(
+            "/import",
+            post(import_project).layer(DefaultBodyLimit::max(MAX_ARCHIVE_BYTES)),
+        )
+        .route("/projects/:id/stats/tools", get(get_project_tool_stats))
+        .route("/projects/:id/stats/timing", get(get_project_timing_stats))
+        .route(
+            "/projects/:id/executor-config",
+            get(get_project_executor_config).put(set_project_executor_config),
+        )
+        .route("/projects/:id/api-keys", get(get_project_api_keys))
+        .route(
+            "/projects/:id/api-keys/:executor_type",
+            put(set_project_api_key).delete(delete_project_api_key),
+        )
+        .route(
+            "/projects/:id/worktrees/gc",
+            get(preview_project_worktree_gc).post(gc_project_worktrees),
+        )
         .route(
             "/projects/:id/branches",
             get(get_project_branches).post(create_project_branch),
         )
+        .route("/projects/:id/git-status", get(get_project_git_status))
         .route("/projects/:id/search", get(search_project_files))
         .route("/projects/:id/open-editor", post(open_project_in_editor))
+        .route(
+            "/projects/:id/command-frequency",
+            get(get_project_command_frequency),
+        )
 }
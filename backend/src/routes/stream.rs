@@ -2,6 +2,7 @@ use std::{str::FromStr, time::Duration};
 
 use axum::{
     extract::{Path, Query, State},
+    http::StatusCode,
     response::sse::{Event, Sse},
     routing::get,
     Router,
@@ -15,6 +16,7 @@ use crate::{
     app_state::AppState,
     executors::gemini::GeminiExecutor,
     models::execution_process::{ExecutionProcess, ExecutionProcessStatus},
+    routes::tasks::project_pool,
 };
 
 /// Interval for DB tail polling (ms) - now blazing fast for real-time updates
@@ -38,12 +40,14 @@ pub struct StreamQuery {
 ///
 /// GET /api/projects/:project_id/execution-processes/:process_id/normalized-logs/stream?since_batch_id=123
 pub async fn normalized_logs_stream(
-    Path((_project_id, process_id)): Path<(Uuid, Uuid)>,
+    Path((project_id, process_id)): Path<(Uuid, Uuid)>,
     Query(query): Query<StreamQuery>,
     State(app_state): State<AppState>,
-) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+
     // Check if this is a Gemini executor (only executor with streaming support)
-    let is_gemini = match ExecutionProcess::find_by_id(&app_state.db_pool, process_id).await {
+    let is_gemini = match ExecutionProcess::find_by_id(&pool, process_id).await {
         Ok(Some(process)) => process.executor_type.as_deref() == Some("gemini"),
         _ => {
             tracing::warn!(
@@ -70,7 +74,7 @@ pub async fn normalized_logs_stream(
             interval.tick().await;
 
             // Check process status first
-            let process_status = match ExecutionProcess::find_by_id(&app_state.db_pool, process_id).await {
+            let process_status = match ExecutionProcess::find_by_id(&pool, process_id).await {
                 Ok(Some(proc)) => proc.status,
                 _ => {
                     tracing::warn!("Execution process {} not found during SSE streaming", process_id);
@@ -97,7 +101,7 @@ pub async fn normalized_logs_stream(
                 }
             } else {
                 // Fallback: Database polling for non-streaming executors
-                let patch_result = ExecutionProcess::find_by_id(&app_state.db_pool, process_id)
+                let patch_result = ExecutionProcess::find_by_id(&pool, process_id)
                     .await
                     .ok()
                     .and_then(|proc_option| proc_option)
@@ -158,7 +162,7 @@ pub async fn normalized_logs_stream(
         }
     };
 
-    Sse::new(stream).keep_alive(axum::response::sse::KeepAlive::default())
+    Ok(Sse::new(stream).keep_alive(axum::response::sse::KeepAlive::default()))
 }
 
 /// Router exposing `/normalized-logs/stream`
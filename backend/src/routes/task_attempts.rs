@@ -1,7 +1,9 @@
+use std::collections::HashMap;
+
 use axum::{
-    extract::{Path, Query, State},
+    extract::{rejection::JsonRejection, Path, Query, State},
     http::StatusCode,
-    response::Json as ResponseJson,
+    response::{IntoResponse, Json as ResponseJson, Response},
     routing::get,
     Json, Router,
 };
@@ -13,20 +15,26 @@ use uuid::Uuid;
 use crate::{
     app_state::AppState,
     executor::{
-        ActionType, ExecutorConfig, NormalizedConversation, NormalizedEntry, NormalizedEntryType,
+        ActionType, ConversationMetadata, ExecutorConfig, ExecutorError, NormalizedConversation,
+        NormalizedEntry, NormalizedEntryType, ValidationReport,
     },
     models::{
         config::Config,
         execution_process::{
             ExecutionProcess, ExecutionProcessStatus, ExecutionProcessSummary, ExecutionProcessType,
         },
+        project::{Project, ProjectBudget},
+        project_executor_config::ProjectExecutorConfig,
         task::{Task, TaskStatus},
         task_attempt::{
-            BranchStatus, CreateFollowUpAttempt, CreatePrParams, CreateTaskAttempt, TaskAttempt,
-            TaskAttemptState, WorktreeDiff,
+            BranchStatus, CreateFollowUpAttempt, CreatePrParams, CreateTaskAttempt,
+            FollowupRequest, ResumeSession, TaskAttempt, TaskAttemptError, TaskAttemptState,
+            WorktreeDiff,
         },
         ApiResponse,
     },
+    routes::tasks::{project_pool, task_pool},
+    web_policy::WebPolicy,
 };
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -57,20 +65,43 @@ pub struct ProcessLogsResponse {
     pub executor_type: Option<String>,
     pub status: ExecutionProcessStatus,
     pub normalized_conversation: NormalizedConversation,
+    pub bash_command_counts: HashMap<String, u32>,
+    /// Wall-clock span of the conversation's timestamped entries; `None` without timestamps
+    pub duration_ms: Option<i64>,
+}
+
+/// The web policy of the project a coding agent process ran under, when its project
+/// configuration applies to the process's executor
+async fn web_policy_for_process(
+    db_pool: &SqlitePool,
+    process: &ExecutionProcess,
+) -> Option<WebPolicy> {
+    let config = ProjectExecutorConfig::find_by_execution_process_id(db_pool, process.id)
+        .await
+        .ok()
+        .flatten()?;
+    (process.executor_type.as_deref() == Some(config.executor_type.as_str()))
+        .then(|| WebPolicy::from_config(&config))
 }
 
 // Helper to normalize logs for a process (extracted from get_execution_process_normalized_logs)
-async fn normalize_process_logs(
+pub(crate) async fn normalize_process_logs(
     db_pool: &SqlitePool,
     process: &ExecutionProcess,
 ) -> NormalizedConversation {
     use crate::models::{
         execution_process::ExecutionProcessType, executor_session::ExecutorSession,
+        task_run::TaskRun,
     };
     let executor_session = ExecutorSession::find_by_execution_process_id(db_pool, process.id)
         .await
         .ok()
         .flatten();
+    // Written by the summarizer, preferred over the CLI's own final message
+    let run_summary = TaskRun::find_summary(db_pool, process.id)
+        .await
+        .ok()
+        .flatten();
 
     let has_stdout = process
         .stdout
@@ -93,42 +124,55 @@ async fn normalize_process_logs(
                 .unwrap_or("unknown".to_string()),
             prompt: executor_session.as_ref().and_then(|s| s.prompt.clone()),
             summary: executor_session.as_ref().and_then(|s| s.summary.clone()),
+            stats: None,
+            metadata: Default::default(),
         };
     }
 
+    let executor_type = process.executor_type.as_deref().unwrap_or("unknown");
+    let executor = if process.process_type == ExecutionProcessType::SetupScript {
+        Some(ExecutorConfig::SetupScript {
+            script: executor_session
+                .as_ref()
+                .and_then(|s| s.prompt.clone())
+                .unwrap_or_else(|| "setup script".to_string()),
+        })
+    } else {
+        executor_type.to_string().parse::<ExecutorConfig>().ok()
+    }
+    .map(|config| config.create_executor());
+
     // Parse stdout as JSONL using executor normalization
     let mut stdout_entries = Vec::new();
+    let mut metadata = ConversationMetadata::default();
+    let mut stats = None;
+    let mut log_summary = None;
     if let Some(stdout) = &process.stdout {
         if !stdout.trim().is_empty() {
-            let executor_type = process.executor_type.as_deref().unwrap_or("unknown");
-            let executor_config = if process.process_type == ExecutionProcessType::SetupScript {
-                ExecutorConfig::SetupScript {
-                    script: executor_session
-                        .as_ref()
-                        .and_then(|s| s.prompt.clone())
-                        .unwrap_or_else(|| "setup script".to_string()),
-                }
-            } else {
-                match executor_type.to_string().parse() {
-                    Ok(config) => config,
-                    Err(_) => {
-                        return NormalizedConversation {
-                            entries: vec![],
-                            session_id: None,
-                            executor_type: executor_type.to_string(),
-                            prompt: executor_session.as_ref().and_then(|s| s.prompt.clone()),
-                            summary: executor_session.as_ref().and_then(|s| s.summary.clone()),
-                        };
-                    }
-                }
+            let Some(executor) = &executor else {
+                return NormalizedConversation {
+                    entries: vec![],
+                    session_id: None,
+                    executor_type: executor_type.to_string(),
+                    prompt: executor_session.as_ref().and_then(|s| s.prompt.clone()),
+                    summary: executor_session.as_ref().and_then(|s| s.summary.clone()),
+                    stats: None,
+                    metadata: Default::default(),
+                };
             };
-            let executor = executor_config.create_executor();
             let working_dir_path = match std::fs::canonicalize(&process.working_directory) {
                 Ok(canonical_path) => canonical_path.to_string_lossy().to_string(),
                 Err(_) => process.working_directory.clone(),
             };
-            if let Ok(normalized) = executor.normalize_logs(stdout, &working_dir_path) {
+            if let Ok(mut normalized) = executor.normalize_logs(stdout, &working_dir_path) {
+                // Fetches the project's web policy blocked are shown as errors
+                if let Some(policy) = web_policy_for_process(db_pool, process).await {
+                    policy.apply(&mut normalized.entries);
+                }
                 stdout_entries = normalized.entries;
+                stats = normalized.stats;
+                log_summary = normalized.summary;
+                metadata = normalized.metadata;
             }
         }
     }
@@ -137,17 +181,25 @@ async fn normalize_process_logs(
     if let Some(stderr) = &process.stderr {
         let trimmed = stderr.trim();
         if !trimmed.is_empty() {
-            let chunks: Vec<&str> = trimmed.split("---STDERR_CHUNK_BOUNDARY---").collect();
-            for chunk in chunks {
-                let chunk_trimmed = chunk.trim();
-                if !chunk_trimmed.is_empty() {
-                    let filtered_content = chunk_trimmed.replace("---STDERR_CHUNK_BOUNDARY---", "");
-                    if !filtered_content.trim().is_empty() {
+            let chunks: Vec<&str> = trimmed
+                .split("---STDERR_CHUNK_BOUNDARY---")
+                .map(str::trim)
+                .filter(|chunk| !chunk.is_empty())
+                .collect();
+            // Executors that understand their stderr pick out what is worth showing
+            match executor
+                .as_ref()
+                .and_then(|executor| executor.normalize_stderr(&chunks))
+            {
+                Some(entries) => stderr_entries = entries,
+                None => {
+                    for chunk in chunks {
                         stderr_entries.push(NormalizedEntry {
                             timestamp: Some(chrono::Utc::now().to_rfc3339()),
                             entry_type: NormalizedEntryType::ErrorMessage,
-                            content: filtered_content.trim().to_string(),
+                            content: chunk.to_string(),
                             metadata: None,
+                            repeat_count: 1,
                         });
                     }
                 }
@@ -176,7 +228,11 @@ async fn normalize_process_logs(
         session_id: None,
         executor_type,
         prompt: executor_session.as_ref().and_then(|s| s.prompt.clone()),
-        summary: executor_session.as_ref().and_then(|s| s.summary.clone()),
+        summary: run_summary
+            .or_else(|| executor_session.as_ref().and_then(|s| s.summary.clone()))
+            .or(log_summary),
+        stats,
+        metadata,
     }
 }
 
@@ -185,23 +241,25 @@ pub async fn get_task_attempt_all_logs(
     Path((project_id, task_id, attempt_id)): Path<(Uuid, Uuid, Uuid)>,
     State(app_state): State<AppState>,
 ) -> Result<Json<ApiResponse<Vec<ProcessLogsResponse>>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
     // Validate attempt belongs to task and project
-    let _ctx = match TaskAttempt::load_context(&app_state.db_pool, attempt_id, task_id, project_id)
-        .await
-    {
+    let _ctx = match TaskAttempt::load_context(&pool, attempt_id, task_id, project_id).await {
         Ok(ctx) => ctx,
         Err(_) => return Err(StatusCode::NOT_FOUND),
     };
     // Fetch all execution processes for this attempt
-    let processes =
-        match ExecutionProcess::find_by_task_attempt_id(&app_state.db_pool, attempt_id).await {
-            Ok(list) => list,
-            Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
-        };
+    let processes = match ExecutionProcess::find_by_task_attempt_id(&pool, attempt_id).await {
+        Ok(list) => list,
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
     // For each process, normalize logs
     let mut result = Vec::new();
     for process in processes {
-        let normalized_conversation = normalize_process_logs(&app_state.db_pool, &process).await;
+        let normalized_conversation = normalize_process_logs(&pool, &process).await;
+        let bash_command_counts = normalized_conversation.bash_command_counts();
+        let duration_ms = normalized_conversation
+            .duration()
+            .map(|duration| duration.num_milliseconds());
         result.push(ProcessLogsResponse {
             id: process.id,
             process_type: process.process_type.clone(),
@@ -209,6 +267,8 @@ pub async fn get_task_attempt_all_logs(
             executor_type: process.executor_type.clone(),
             status: process.status.clone(),
             normalized_conversation,
+            bash_command_counts,
+            duration_ms,
         });
     }
     Ok(Json(ApiResponse {
@@ -222,8 +282,9 @@ pub async fn get_task_attempts(
     Path((project_id, task_id)): Path<(Uuid, Uuid)>,
     State(app_state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<Vec<TaskAttempt>>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
     // Verify task exists in project first
-    match Task::exists(&app_state.db_pool, task_id, project_id).await {
+    match Task::exists(&pool, task_id, project_id).await {
         Ok(false) => return Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!("Failed to check task existence: {}", e);
@@ -232,7 +293,7 @@ pub async fn get_task_attempts(
         Ok(true) => {}
     }
 
-    match TaskAttempt::find_by_task_id(&app_state.db_pool, task_id).await {
+    match TaskAttempt::find_by_task_id(&pool, task_id).await {
         Ok(attempts) => Ok(ResponseJson(ApiResponse {
             success: true,
             data: Some(attempts),
@@ -245,24 +306,107 @@ pub async fn get_task_attempts(
     }
 }
 
+/// The project's budget when its runs have already spent the monthly limit
+async fn exceeded_budget(
+    pool: &SqlitePool,
+    project_id: Uuid,
+) -> Result<Option<ProjectBudget>, StatusCode> {
+    match Project::find_budget(pool, project_id).await {
+        Ok(budget) => Ok(budget.filter(ProjectBudget::is_exceeded)),
+        Err(e) => {
+            tracing::error!("Failed to check budget of project {}: {}", project_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub async fn create_task_attempt(
     Path((project_id, task_id)): Path<(Uuid, Uuid)>,
     State(app_state): State<AppState>,
     Json(payload): Json<CreateTaskAttempt>,
-) -> Result<ResponseJson<ApiResponse<TaskAttempt>>, StatusCode> {
+) -> Result<ResponseJson<ApiResponse<TaskAttempt>>, Response> {
+    let pool = project_pool(&app_state, project_id)
+        .await
+        .map_err(IntoResponse::into_response)?;
     // Verify task exists in project first
-    match Task::exists(&app_state.db_pool, task_id, project_id).await {
-        Ok(false) => return Err(StatusCode::NOT_FOUND),
+    match Task::exists(&pool, task_id, project_id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND.into_response()),
         Err(e) => {
             tracing::error!("Failed to check task existence: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
         }
         Ok(true) => {}
     }
 
+    // A task cannot start until every task it depends on is done
+    match Task::get_blocked_by(&pool, task_id).await {
+        Ok(blockers) if !blockers.is_empty() => {
+            let blocking_task_ids: Vec<Uuid> = blockers.iter().map(|task| task.id).collect();
+            return Err((
+                StatusCode::CONFLICT,
+                ResponseJson(ApiResponse {
+                    success: false,
+                    data: Some(blocking_task_ids),
+                    message: Some("Task is blocked by incomplete dependencies".to_string()),
+                }),
+            )
+                .into_response());
+        }
+        Ok(_) => {}
+        Err(e) => {
+            tracing::error!("Failed to check dependencies of task {}: {}", task_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+        }
+    }
+
+    if let Some(budget) = exceeded_budget(&pool, project_id)
+        .await
+        .map_err(IntoResponse::into_response)?
+    {
+        let message = format!(
+            "Monthly budget exceeded: ${:.2} spent of the ${:.2} limit. Reset the budget or raise the limit to start new runs.",
+            budget.monthly_cost_usd_used,
+            budget.monthly_cost_limit_usd.unwrap_or_default()
+        );
+        return Err((
+            StatusCode::PAYMENT_REQUIRED,
+            ResponseJson(ApiResponse {
+                success: false,
+                data: Some(budget),
+                message: Some(message),
+            }),
+        )
+            .into_response());
+    }
+
+    // Reject settings the executor cannot run with before creating the attempt
+    if let Some(executor_config) = payload
+        .executor
+        .as_deref()
+        .and_then(|executor| executor.parse::<ExecutorConfig>().ok())
+    {
+        if let Err(e) = executor_config.try_create_executor() {
+            tracing::warn!("Rejected task attempt for task {}: {}", task_id, e);
+            let status = match e {
+                ExecutorError::ConfigError { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            // The error's code tells clients what to fix
+            return Err((
+                status,
+                ResponseJson(ApiResponse {
+                    success: false,
+                    message: Some(e.to_string()),
+                    data: Some(e),
+                }),
+            )
+                .into_response());
+        }
+    }
+
     let executor_string = payload.executor.as_ref().map(|exec| exec.to_string());
 
-    match TaskAttempt::create(&app_state.db_pool, &payload, task_id).await {
+    match TaskAttempt::create(&pool, &payload, task_id).await {
         Ok(attempt) => {
             app_state
                 .track_analytics_event(
@@ -277,10 +421,11 @@ pub async fn create_task_attempt(
 
             // Start execution asynchronously (don't block the response)
             let app_state_clone = app_state.clone();
+            let pool = pool.clone();
             let attempt_id = attempt.id;
             tokio::spawn(async move {
                 if let Err(e) = TaskAttempt::start_execution(
-                    &app_state_clone.db_pool,
+                    &pool,
                     &app_state_clone,
                     attempt_id,
                     task_id,
@@ -304,7 +449,7 @@ pub async fn create_task_attempt(
         }
         Err(e) => {
             tracing::error!("Failed to create task attempt: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(StatusCode::INTERNAL_SERVER_ERROR.into_response())
         }
     }
 }
@@ -313,8 +458,9 @@ pub async fn get_task_attempt_diff(
     Path((project_id, task_id, attempt_id)): Path<(Uuid, Uuid, Uuid)>,
     State(app_state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<WorktreeDiff>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
     // Verify task attempt exists and belongs to the correct task
-    match TaskAttempt::exists_for_task(&app_state.db_pool, attempt_id, task_id, project_id).await {
+    match TaskAttempt::exists_for_task(&pool, attempt_id, task_id, project_id).await {
         Ok(false) => return Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!("Failed to check task attempt existence: {}", e);
@@ -323,7 +469,7 @@ pub async fn get_task_attempt_diff(
         Ok(true) => {}
     }
 
-    match TaskAttempt::get_diff(&app_state.db_pool, attempt_id, task_id, project_id).await {
+    match TaskAttempt::get_diff(&pool, attempt_id, task_id, project_id).await {
         Ok(diff) => Ok(ResponseJson(ApiResponse {
             success: true,
             data: Some(diff),
@@ -341,8 +487,9 @@ pub async fn merge_task_attempt(
     Path((project_id, task_id, attempt_id)): Path<(Uuid, Uuid, Uuid)>,
     State(app_state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
     // Verify task attempt exists and belongs to the correct task
-    match TaskAttempt::exists_for_task(&app_state.db_pool, attempt_id, task_id, project_id).await {
+    match TaskAttempt::exists_for_task(&pool, attempt_id, task_id, project_id).await {
         Ok(false) => return Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!("Failed to check task attempt existence: {}", e);
@@ -351,11 +498,24 @@ pub async fn merge_task_attempt(
         Ok(true) => {}
     }
 
-    match TaskAttempt::merge_changes(&app_state.db_pool, attempt_id, task_id, project_id).await {
+    match TaskAttempt::merge_changes(&pool, attempt_id, task_id, project_id).await {
         Ok(_) => {
-            // Update task status to Done
-            if let Err(e) = Task::update_status(
-                &app_state.db_pool,
+            // Update task status to Done, unless it still has sub-tasks to finish
+            let incomplete_subtasks = match Task::incomplete_subtask_count(&pool, task_id).await {
+                Ok(count) => count,
+                Err(e) => {
+                    tracing::error!("Failed to count sub-tasks of task {}: {}", task_id, e);
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+            };
+            if incomplete_subtasks > 0 {
+                tracing::info!(
+                    "Task {} stays open after merge, {} sub-tasks are not done",
+                    task_id,
+                    incomplete_subtasks
+                );
+            } else if let Err(e) = Task::update_status(
+                &pool,
                 task_id,
                 project_id,
                 crate::models::task::TaskStatus::Done,
@@ -400,8 +560,9 @@ pub async fn create_github_pr(
     State(app_state): State<AppState>,
     Json(request): Json<CreateGitHubPRRequest>,
 ) -> Result<ResponseJson<ApiResponse<String>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
     // Verify task attempt exists and belongs to the correct task
-    match TaskAttempt::exists_for_task(&app_state.db_pool, attempt_id, task_id, project_id).await {
+    match TaskAttempt::exists_for_task(&pool, attempt_id, task_id, project_id).await {
         Ok(false) => return Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!("Failed to check task attempt existence: {}", e);
@@ -433,7 +594,7 @@ pub async fn create_github_pr(
     };
 
     // Get the task attempt to access the stored base branch
-    let attempt = match TaskAttempt::find_by_id(&app_state.db_pool, attempt_id).await {
+    let attempt = match TaskAttempt::find_by_id(&pool, attempt_id).await {
         Ok(Some(attempt)) => attempt,
         Ok(None) => return Err(StatusCode::NOT_FOUND),
         Err(e) => {
@@ -456,7 +617,7 @@ pub async fn create_github_pr(
     });
 
     match TaskAttempt::create_github_pr(
-        &app_state.db_pool,
+        &pool,
         CreatePrParams {
             attempt_id,
             task_id,
@@ -536,8 +697,9 @@ pub async fn open_task_attempt_in_editor(
     State(app_state): State<AppState>,
     Json(payload): Json<Option<OpenEditorRequest>>,
 ) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
     // Verify task attempt exists and belongs to the correct task
-    match TaskAttempt::exists_for_task(&app_state.db_pool, attempt_id, task_id, project_id).await {
+    match TaskAttempt::exists_for_task(&pool, attempt_id, task_id, project_id).await {
         Ok(false) => return Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!("Failed to check task attempt existence: {}", e);
@@ -547,7 +709,7 @@ pub async fn open_task_attempt_in_editor(
     }
 
     // Get the task attempt to access the worktree path
-    let attempt = match TaskAttempt::find_by_id(&app_state.db_pool, attempt_id).await {
+    let attempt = match TaskAttempt::find_by_id(&pool, attempt_id).await {
         Ok(Some(attempt)) => attempt,
         Ok(None) => return Err(StatusCode::NOT_FOUND),
         Err(e) => {
@@ -622,8 +784,9 @@ pub async fn get_task_attempt_branch_status(
     Path((project_id, task_id, attempt_id)): Path<(Uuid, Uuid, Uuid)>,
     State(app_state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<BranchStatus>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
     // Verify task attempt exists and belongs to the correct task
-    match TaskAttempt::exists_for_task(&app_state.db_pool, attempt_id, task_id, project_id).await {
+    match TaskAttempt::exists_for_task(&pool, attempt_id, task_id, project_id).await {
         Ok(false) => return Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!("Failed to check task attempt existence: {}", e);
@@ -632,8 +795,7 @@ pub async fn get_task_attempt_branch_status(
         Ok(true) => {}
     }
 
-    match TaskAttempt::get_branch_status(&app_state.db_pool, attempt_id, task_id, project_id).await
-    {
+    match TaskAttempt::get_branch_status(&pool, attempt_id, task_id, project_id).await {
         Ok(status) => Ok(ResponseJson(ApiResponse {
             success: true,
             data: Some(status),
@@ -656,8 +818,9 @@ pub async fn rebase_task_attempt(
     State(app_state): State<AppState>,
     request_body: Option<Json<RebaseTaskAttemptRequest>>,
 ) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
     // Verify task attempt exists and belongs to the correct task
-    match TaskAttempt::exists_for_task(&app_state.db_pool, attempt_id, task_id, project_id).await {
+    match TaskAttempt::exists_for_task(&pool, attempt_id, task_id, project_id).await {
         Ok(false) => return Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!("Failed to check task attempt existence: {}", e);
@@ -669,14 +832,7 @@ pub async fn rebase_task_attempt(
     // Extract new base branch from request body if provided
     let new_base_branch = request_body.and_then(|body| body.new_base_branch.clone());
 
-    match TaskAttempt::rebase_attempt(
-        &app_state.db_pool,
-        attempt_id,
-        task_id,
-        project_id,
-        new_base_branch,
-    )
-    .await
+    match TaskAttempt::rebase_attempt(&pool, attempt_id, task_id, project_id, new_base_branch).await
     {
         Ok(_new_base_commit) => Ok(ResponseJson(ApiResponse {
             success: true,
@@ -698,8 +854,9 @@ pub async fn get_task_attempt_execution_processes(
     Path((project_id, task_id, attempt_id)): Path<(Uuid, Uuid, Uuid)>,
     State(app_state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<Vec<ExecutionProcessSummary>>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
     // Verify task attempt exists and belongs to the correct task
-    match TaskAttempt::exists_for_task(&app_state.db_pool, attempt_id, task_id, project_id).await {
+    match TaskAttempt::exists_for_task(&pool, attempt_id, task_id, project_id).await {
         Ok(false) => return Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!("Failed to check task attempt existence: {}", e);
@@ -708,8 +865,7 @@ pub async fn get_task_attempt_execution_processes(
         Ok(true) => {}
     }
 
-    match ExecutionProcess::find_summaries_by_task_attempt_id(&app_state.db_pool, attempt_id).await
-    {
+    match ExecutionProcess::find_summaries_by_task_attempt_id(&pool, attempt_id).await {
         Ok(processes) => Ok(ResponseJson(ApiResponse {
             success: true,
             data: Some(processes),
@@ -730,12 +886,13 @@ pub async fn get_execution_process(
     Path((project_id, process_id)): Path<(Uuid, Uuid)>,
     State(app_state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<ExecutionProcess>>, StatusCode> {
-    match ExecutionProcess::find_by_id(&app_state.db_pool, process_id).await {
+    let pool = project_pool(&app_state, project_id).await?;
+    match ExecutionProcess::find_by_id(&pool, process_id).await {
         Ok(Some(process)) => {
             // Verify the process belongs to a task attempt in the correct project
-            match TaskAttempt::find_by_id(&app_state.db_pool, process.task_attempt_id).await {
+            match TaskAttempt::find_by_id(&pool, process.task_attempt_id).await {
                 Ok(Some(attempt)) => {
-                    match Task::find_by_id(&app_state.db_pool, attempt.task_id).await {
+                    match Task::find_by_id(&pool, attempt.task_id).await {
                         Ok(Some(task)) if task.project_id == project_id => {
                             Ok(ResponseJson(ApiResponse {
                                 success: true,
@@ -771,8 +928,9 @@ pub async fn stop_all_execution_processes(
     Path((project_id, task_id, attempt_id)): Path<(Uuid, Uuid, Uuid)>,
     State(app_state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
     // Verify task attempt exists and belongs to the correct task
-    match TaskAttempt::exists_for_task(&app_state.db_pool, attempt_id, task_id, project_id).await {
+    match TaskAttempt::exists_for_task(&pool, attempt_id, task_id, project_id).await {
         Ok(false) => return Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!("Failed to check task attempt existence: {}", e);
@@ -782,31 +940,32 @@ pub async fn stop_all_execution_processes(
     }
 
     // Get all execution processes for the task attempt
-    let processes =
-        match ExecutionProcess::find_by_task_attempt_id(&app_state.db_pool, attempt_id).await {
-            Ok(processes) => processes,
-            Err(e) => {
-                tracing::error!(
-                    "Failed to fetch execution processes for attempt {}: {}",
-                    attempt_id,
-                    e
-                );
-                return Err(StatusCode::INTERNAL_SERVER_ERROR);
-            }
-        };
+    let processes = match ExecutionProcess::find_by_task_attempt_id(&pool, attempt_id).await {
+        Ok(processes) => processes,
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch execution processes for attempt {}: {}",
+                attempt_id,
+                e
+            );
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
 
     let mut stopped_count = 0;
     let mut errors = Vec::new();
 
     // Stop all running processes
+    let mut stopped_coding_agent = false;
     for process in processes {
         match app_state.stop_running_execution_by_id(process.id).await {
             Ok(true) => {
                 stopped_count += 1;
+                stopped_coding_agent |= process.process_type == ExecutionProcessType::CodingAgent;
 
                 // Update the execution process status in the database
                 if let Err(e) = ExecutionProcess::update_completion(
-                    &app_state.db_pool,
+                    &pool,
                     process.id,
                     crate::models::execution_process::ExecutionProcessStatus::Killed,
                     None,
@@ -829,6 +988,19 @@ pub async fn stop_all_execution_processes(
         }
     }
 
+    if stopped_coding_agent {
+        if let Err(e) = Task::mark_completed(&pool, task_id).await {
+            tracing::error!("Failed to record the end of task {}: {}", task_id, e);
+        }
+        if let Err(e) = TaskAttempt::release_worktree(&pool, attempt_id, project_id).await {
+            tracing::error!(
+                "Failed to release worktree of attempt {}: {}",
+                attempt_id,
+                e
+            );
+        }
+    }
+
     if !errors.is_empty() {
         return Ok(ResponseJson(ApiResponse {
             success: false,
@@ -864,8 +1036,9 @@ pub async fn stop_execution_process(
     Path((project_id, task_id, attempt_id, process_id)): Path<(Uuid, Uuid, Uuid, Uuid)>,
     State(app_state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
     // Verify task attempt exists and belongs to the correct task
-    match TaskAttempt::exists_for_task(&app_state.db_pool, attempt_id, task_id, project_id).await {
+    match TaskAttempt::exists_for_task(&pool, attempt_id, task_id, project_id).await {
         Ok(false) => return Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!("Failed to check task attempt existence: {}", e);
@@ -875,7 +1048,7 @@ pub async fn stop_execution_process(
     }
 
     // Verify execution process exists and belongs to the task attempt
-    match ExecutionProcess::find_by_id(&app_state.db_pool, process_id).await {
+    let process = match ExecutionProcess::find_by_id(&pool, process_id).await {
         Ok(Some(process)) if process.task_attempt_id == attempt_id => process,
         Ok(Some(_)) => return Err(StatusCode::NOT_FOUND), // Process exists but wrong attempt
         Ok(None) => return Err(StatusCode::NOT_FOUND),
@@ -904,7 +1077,7 @@ pub async fn stop_execution_process(
 
     // Update the execution process status in the database
     if let Err(e) = ExecutionProcess::update_completion(
-        &app_state.db_pool,
+        &pool,
         process_id,
         crate::models::execution_process::ExecutionProcessStatus::Killed,
         None,
@@ -915,6 +1088,19 @@ pub async fn stop_execution_process(
         return Err(StatusCode::INTERNAL_SERVER_ERROR);
     }
 
+    if process.process_type == ExecutionProcessType::CodingAgent {
+        if let Err(e) = Task::mark_completed(&pool, task_id).await {
+            tracing::error!("Failed to record the end of task {}: {}", task_id, e);
+        }
+        if let Err(e) = TaskAttempt::release_worktree(&pool, attempt_id, project_id).await {
+            tracing::error!(
+                "Failed to release worktree of attempt {}: {}",
+                attempt_id,
+                e
+            );
+        }
+    }
+
     // Process stopped successfully
 
     Ok(ResponseJson(ApiResponse {
@@ -938,8 +1124,9 @@ pub async fn delete_task_attempt_file(
     Query(query): Query<DeleteFileQuery>,
     State(app_state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
     // Verify task attempt exists and belongs to the correct task
-    match TaskAttempt::exists_for_task(&app_state.db_pool, attempt_id, task_id, project_id).await {
+    match TaskAttempt::exists_for_task(&pool, attempt_id, task_id, project_id).await {
         Ok(false) => return Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!("Failed to check task attempt existence: {}", e);
@@ -948,15 +1135,7 @@ pub async fn delete_task_attempt_file(
         Ok(true) => {}
     }
 
-    match TaskAttempt::delete_file(
-        &app_state.db_pool,
-        attempt_id,
-        task_id,
-        project_id,
-        &query.file_path,
-    )
-    .await
-    {
+    match TaskAttempt::delete_file(&pool, attempt_id, task_id, project_id, &query.file_path).await {
         Ok(_commit_id) => Ok(ResponseJson(ApiResponse {
             success: true,
             data: None,
@@ -981,10 +1160,21 @@ pub async fn delete_task_attempt_file(
 pub async fn create_followup_attempt(
     Path((project_id, task_id, attempt_id)): Path<(Uuid, Uuid, Uuid)>,
     State(app_state): State<AppState>,
-    Json(payload): Json<CreateFollowUpAttempt>,
+    payload: Result<Json<CreateFollowUpAttempt>, JsonRejection>,
 ) -> Result<ResponseJson<ApiResponse<FollowUpResponse>>, StatusCode> {
+    // Malformed tool results or context files are rejected rather than dropped
+    let Json(payload) = payload.map_err(|e| {
+        tracing::warn!(
+            "Rejected follow-up on task attempt {}: {}",
+            attempt_id,
+            e.body_text()
+        );
+        StatusCode::BAD_REQUEST
+    })?;
+    let request = FollowupRequest::from(payload);
+    let pool = project_pool(&app_state, project_id).await?;
     // Verify task attempt exists
-    if !TaskAttempt::exists_for_task(&app_state.db_pool, attempt_id, task_id, project_id)
+    if !TaskAttempt::exists_for_task(&pool, attempt_id, task_id, project_id)
         .await
         .map_err(|e| {
             tracing::error!("Failed to check task attempt existence: {}", e);
@@ -994,14 +1184,13 @@ pub async fn create_followup_attempt(
         return Err(StatusCode::NOT_FOUND);
     }
 
+    if exceeded_budget(&pool, project_id).await?.is_some() {
+        return Err(StatusCode::PAYMENT_REQUIRED);
+    }
+
     // Start follow-up execution synchronously to catch errors
     match TaskAttempt::start_followup_execution(
-        &app_state.db_pool,
-        &app_state,
-        attempt_id,
-        task_id,
-        project_id,
-        &payload.prompt,
+        &pool, &app_state, attempt_id, task_id, project_id, &request,
     )
     .await
     {
@@ -1026,6 +1215,21 @@ pub async fn create_followup_attempt(
                 message: Some(message),
             }))
         }
+        Err(TaskAttemptError::Executor(ExecutorError::PoolExhausted { .. })) => {
+            tracing::warn!(
+                "No free executor slot for follow-up on task attempt {}",
+                attempt_id
+            );
+            Err(StatusCode::TOO_MANY_REQUESTS)
+        }
+        Err(TaskAttemptError::Executor(ExecutorError::InvalidInput(reason))) => {
+            tracing::warn!(
+                "Rejected follow-up on task attempt {}: {}",
+                attempt_id,
+                reason
+            );
+            Err(StatusCode::BAD_REQUEST)
+        }
         Err(e) => {
             tracing::error!(
                 "Failed to start follow-up execution for task attempt {}: {}",
@@ -1037,12 +1241,131 @@ pub async fn create_followup_attempt(
     }
 }
 
+/// Continue a Claude session started outside vibe-kanban, e.g. in the Claude desktop app,
+/// as a new run of the task's latest attempt. Returns the run's ID.
+///
+/// POST /api/tasks/:task_id/resume-session
+pub async fn resume_task_session(
+    Path(task_id): Path<Uuid>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<ResumeSession>,
+) -> Result<Response, StatusCode> {
+    let pool = task_pool(&app_state, task_id).await?;
+    let task = match Task::find_by_id(&pool, task_id).await {
+        Ok(Some(task)) => task,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch task {}: {}", task_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    if exceeded_budget(&app_state.db_pool, task.project_id)
+        .await?
+        .is_some()
+    {
+        return Err(StatusCode::PAYMENT_REQUIRED);
+    }
+
+    match TaskAttempt::resume_session(&pool, &app_state, task_id, task.project_id, &payload).await {
+        Ok(run_id) => Ok(ResponseJson(ApiResponse::success(run_id)).into_response()),
+        Err(e @ TaskAttemptError::ValidationError(_)) => Ok((
+            StatusCode::BAD_REQUEST,
+            ResponseJson(ApiResponse::<Uuid>::error(&e.to_string())),
+        )
+            .into_response()),
+        Err(TaskAttemptError::Executor(ExecutorError::PoolExhausted { .. })) => {
+            tracing::warn!(
+                "No free executor slot to resume a session on task {}",
+                task_id
+            );
+            Err(StatusCode::TOO_MANY_REQUESTS)
+        }
+        Err(e) => {
+            tracing::error!(
+                "Failed to resume session {} on task {}: {}",
+                payload.session_id,
+                task_id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Check, without spawning anything, that the task's executor command resolves, that the
+/// worktree exists and that the task has a prompt. Uses the latest attempt's executor and
+/// worktree, or the configured executor and the project repository when there is none.
+///
+/// POST /api/tasks/:task_id/validate-executor
+pub async fn validate_task_executor(
+    Path(task_id): Path<Uuid>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<ValidationReport>>, StatusCode> {
+    let pool = task_pool(&app_state, task_id).await?;
+    let task = match Task::find_by_id(&pool, task_id).await {
+        Ok(Some(task)) => task,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch task {}: {}", task_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let latest_attempt = match TaskAttempt::find_by_task_id(&pool, task_id).await {
+        Ok(attempts) => attempts.into_iter().next(),
+        Err(e) => {
+            tracing::error!("Failed to fetch attempts of task {}: {}", task_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let (executor_config, worktree_path) = match latest_attempt {
+        Some(attempt) => {
+            let executor_config = match attempt
+                .executor
+                .as_deref()
+                .and_then(|executor| executor.parse::<ExecutorConfig>().ok())
+            {
+                Some(executor_config) => executor_config,
+                None => app_state.get_config().read().await.executor.clone(),
+            };
+            (executor_config, attempt.worktree_path)
+        }
+        None => {
+            let project = match Project::find_by_id(&app_state.db_pool, task.project_id).await {
+                Ok(Some(project)) => project,
+                Ok(None) => return Err(StatusCode::NOT_FOUND),
+                Err(e) => {
+                    tracing::error!("Failed to fetch project {}: {}", task.project_id, e);
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+            };
+            let executor_config = app_state.get_config().read().await.executor.clone();
+            (executor_config, project.git_repo_path)
+        }
+    };
+
+    match executor_config
+        .create_executor()
+        .validate(&pool, task_id, &worktree_path)
+        .await
+    {
+        Ok(report) => Ok(ResponseJson(ApiResponse::success(report))),
+        Err(e) => {
+            tracing::error!("Failed to validate the executor of task {}: {}", task_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub async fn start_dev_server(
     Path((project_id, task_id, attempt_id)): Path<(Uuid, Uuid, Uuid)>,
     State(app_state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
     // Verify task attempt exists and belongs to the correct task
-    match TaskAttempt::exists_for_task(&app_state.db_pool, attempt_id, task_id, project_id).await {
+    match TaskAttempt::exists_for_task(&pool, attempt_id, task_id, project_id).await {
         Ok(false) => return Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!("Failed to check task attempt existence: {}", e);
@@ -1053,9 +1376,7 @@ pub async fn start_dev_server(
 
     // Stop any existing dev servers for this project
     let existing_dev_servers =
-        match ExecutionProcess::find_running_dev_servers_by_project(&app_state.db_pool, project_id)
-            .await
-        {
+        match ExecutionProcess::find_running_dev_servers_by_project(&pool, project_id).await {
             Ok(servers) => servers,
             Err(e) => {
                 tracing::error!(
@@ -1080,7 +1401,7 @@ pub async fn start_dev_server(
         } else {
             // Update the execution process status in the database
             if let Err(e) = ExecutionProcess::update_completion(
-                &app_state.db_pool,
+                &pool,
                 dev_server.id,
                 crate::models::execution_process::ExecutionProcessStatus::Killed,
                 None,
@@ -1097,15 +1418,7 @@ pub async fn start_dev_server(
     }
 
     // Start dev server execution
-    match TaskAttempt::start_dev_server(
-        &app_state.db_pool,
-        &app_state,
-        attempt_id,
-        task_id,
-        project_id,
-    )
-    .await
-    {
+    match TaskAttempt::start_dev_server(&pool, &app_state, attempt_id, task_id, project_id).await {
         Ok(_) => Ok(ResponseJson(ApiResponse {
             success: true,
             data: None,
@@ -1130,8 +1443,9 @@ pub async fn get_task_attempt_execution_state(
     Path((project_id, task_id, attempt_id)): Path<(Uuid, Uuid, Uuid)>,
     State(app_state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<TaskAttemptState>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
     // Verify task attempt exists and belongs to the correct task
-    match TaskAttempt::exists_for_task(&app_state.db_pool, attempt_id, task_id, project_id).await {
+    match TaskAttempt::exists_for_task(&pool, attempt_id, task_id, project_id).await {
         Ok(false) => return Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!("Failed to check task attempt existence: {}", e);
@@ -1141,9 +1455,7 @@ pub async fn get_task_attempt_execution_state(
     }
 
     // Get the execution state
-    match TaskAttempt::get_execution_state(&app_state.db_pool, attempt_id, task_id, project_id)
-        .await
-    {
+    match TaskAttempt::get_execution_state(&pool, attempt_id, task_id, project_id).await {
         Ok(state) => Ok(ResponseJson(ApiResponse {
             success: true,
             data: Some(state),
@@ -1240,8 +1552,9 @@ pub async fn approve_plan(
     Path((project_id, task_id, attempt_id)): Path<(Uuid, Uuid, Uuid)>,
     State(app_state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<FollowUpResponse>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
     // Verify task attempt exists and belongs to the correct task
-    match TaskAttempt::exists_for_task(&app_state.db_pool, attempt_id, task_id, project_id).await {
+    match TaskAttempt::exists_for_task(&pool, attempt_id, task_id, project_id).await {
         Ok(false) => return Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!("Failed to check task attempt existence: {}", e);
@@ -1249,7 +1562,7 @@ pub async fn approve_plan(
         }
         Ok(true) => {}
     }
-    let current_task = match Task::find_by_id(&app_state.db_pool, task_id).await {
+    let current_task = match Task::find_by_id(&pool, task_id).await {
         Ok(Some(task)) => task,
         Ok(None) => return Err(StatusCode::NOT_FOUND),
         Err(e) => {
@@ -1259,7 +1572,7 @@ pub async fn approve_plan(
     };
 
     // Find plan content with context across the task hierarchy
-    let plan_content = find_plan_content_with_context(&app_state.db_pool, attempt_id).await?;
+    let plan_content = find_plan_content_with_context(&pool, attempt_id).await?;
 
     use crate::models::task::CreateTask;
     let new_task_id = Uuid::new_v4();
@@ -1268,9 +1581,14 @@ pub async fn approve_plan(
         title: format!("Execute Plan: {}", current_task.title),
         description: Some(plan_content),
         parent_task_attempt: Some(attempt_id),
+        labels: None,
+        timeout_seconds: current_task.timeout_seconds,
+        executor_model: current_task.executor_model.clone(),
+        priority: Some(current_task.priority),
+        parent_task_id: current_task.parent_task_id,
     };
 
-    let new_task = match Task::create(&app_state.db_pool, &create_task_data, new_task_id).await {
+    let new_task = match Task::create(&pool, &create_task_data, new_task_id).await {
         Ok(task) => task,
         Err(e) => {
             tracing::error!("Failed to create new task: {}", e);
@@ -1279,9 +1597,7 @@ pub async fn approve_plan(
     };
 
     // Mark original task as completed since it now has children
-    if let Err(e) =
-        Task::update_status(&app_state.db_pool, task_id, project_id, TaskStatus::Done).await
-    {
+    if let Err(e) = Task::update_status(&pool, task_id, project_id, TaskStatus::Done).await {
         tracing::error!("Failed to update original task status to Done: {}", e);
         return Err(StatusCode::INTERNAL_SERVER_ERROR);
     } else {
@@ -1306,7 +1622,21 @@ pub async fn get_task_attempt_details(
     Path(attempt_id): Path<Uuid>,
     State(app_state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<TaskAttempt>>, StatusCode> {
-    match TaskAttempt::find_by_id(&app_state.db_pool, attempt_id).await {
+    let pool = app_state
+        .find_task_pool(|pool| async move {
+            Ok(TaskAttempt::find_by_id(&pool, attempt_id).await?.is_some())
+        })
+        .await
+        .map_err(|e| {
+            tracing::error!(
+                "Failed to find the database of attempt {}: {}",
+                attempt_id,
+                e
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    match TaskAttempt::find_by_id(&pool, attempt_id).await {
         Ok(Some(attempt)) => Ok(ResponseJson(ApiResponse {
             success: true,
             data: Some(attempt),
@@ -1324,8 +1654,9 @@ pub async fn get_task_attempt_children(
     Path((project_id, task_id, attempt_id)): Path<(Uuid, Uuid, Uuid)>,
     State(app_state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
     // Verify task exists in the specified project
-    match Task::find_by_id_and_project_id(&app_state.db_pool, task_id, project_id).await {
+    match Task::find_by_id_and_project_id(&pool, task_id, project_id).await {
         Ok(Some(_)) => {} // Task exists, proceed
         Ok(None) => return Err(StatusCode::NOT_FOUND),
         Err(e) => {
@@ -1334,7 +1665,7 @@ pub async fn get_task_attempt_children(
         }
     }
 
-    match Task::find_related_tasks_by_attempt_id(&app_state.db_pool, attempt_id, project_id).await {
+    match Task::find_related_tasks_by_attempt_id(&pool, attempt_id, project_id).await {
         Ok(related_tasks) => Ok(ResponseJson(ApiResponse {
             success: true,
             data: Some(related_tasks),
@@ -1413,6 +1744,11 @@ pub fn task_attempts_router() -> Router<AppState> {
             "/projects/:project_id/tasks/:task_id/attempts/:attempt_id/follow-up",
             post(create_followup_attempt),
         )
+        .route("/tasks/:task_id/resume-session", post(resume_task_session))
+        .route(
+            "/tasks/:task_id/validate-executor",
+            post(validate_task_executor),
+        )
         .route(
             "/projects/:project_id/tasks/:task_id/attempts/:attempt_id/start-dev-server",
             post(start_dev_server),
@@ -2,7 +2,7 @@ use axum::{
     extract::{Path, State},
     http::StatusCode,
     response::IntoResponse,
-    routing::get,
+    routing::{get, post},
     Json, Router,
 };
 use uuid::Uuid;
@@ -11,7 +11,11 @@ use crate::{
     app_state::AppState,
     models::{
         api_response::ApiResponse,
-        task_template::{CreateTaskTemplate, TaskTemplate, UpdateTaskTemplate},
+        project::Project,
+        task::{CreateTask, Task},
+        task_template::{
+            CreateTaskTemplate, InstantiateTaskTemplate, TaskTemplate, UpdateTaskTemplate,
+        },
     },
 };
 
@@ -108,6 +112,101 @@ pub async fn create_template(
     }
 }
 
+pub async fn create_project_template(
+    State(state): State<AppState>,
+    Path(project_id): Path<Uuid>,
+    Json(mut payload): Json<CreateTaskTemplate>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    match Project::exists(&state.db_pool, project_id).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::error("Project not found")),
+            ))
+        }
+        Err(e) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(&format!(
+                    "Failed to check project existence: {}",
+                    e
+                ))),
+            ))
+        }
+    }
+
+    payload.project_id = Some(project_id);
+    create_template(State(state), Json(payload)).await
+}
+
+/// Create a task of the project from one of its templates or a global template
+pub async fn instantiate_template(
+    State(state): State<AppState>,
+    Path((project_id, template_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<InstantiateTaskTemplate>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let template = match TaskTemplate::find_by_id(&state.db_pool, template_id).await {
+        Ok(Some(template))
+            if template.project_id.is_none() || template.project_id == Some(project_id) =>
+        {
+            template
+        }
+        Ok(_) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::error("Template not found")),
+            ))
+        }
+        Err(e) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(&format!(
+                    "Failed to fetch template: {}",
+                    e
+                ))),
+            ))
+        }
+    };
+
+    let (title, description) = template.render(&payload.variables).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(&e.to_string())),
+        )
+    })?;
+
+    let create_task = CreateTask {
+        project_id,
+        title,
+        description,
+        parent_task_attempt: None,
+        labels: None,
+        timeout_seconds: None,
+        executor_model: template.executor_model.clone(),
+        priority: None,
+        parent_task_id: None,
+    };
+    let created = async {
+        let pool = state.project_pool(project_id).await?;
+        let task = Task::create(&pool, &create_task, Uuid::new_v4()).await?;
+        Task::declare_env_vars(&pool, task.id, &template.env_var_keys).await?;
+        Ok::<_, sqlx::Error>(task)
+    }
+    .await;
+
+    match created {
+        Ok(task) => Ok((StatusCode::CREATED, Json(ApiResponse::success(task)))),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error(&format!(
+                "Failed to create task from template: {}",
+                e
+            ))),
+        )),
+    }
+}
+
 pub async fn update_template(
     State(state): State<AppState>,
     Path(template_id): Path<Uuid>,
@@ -173,6 +272,10 @@ pub fn templates_router() -> Router<AppState> {
         )
         .route(
             "/projects/:project_id/templates",
-            get(list_project_templates),
+            get(list_project_templates).post(create_project_template),
+        )
+        .route(
+            "/projects/:project_id/templates/:template_id/instantiate",
+            post(instantiate_template),
         )
 }
@@ -1,28 +1,119 @@
+use std::collections::BTreeMap;
+
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::Json as ResponseJson,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json as ResponseJson, Response},
     routing::get,
     Json, Router,
 };
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::SqlitePool;
 use uuid::Uuid;
 
 use crate::{
     app_state::AppState,
-    execution_monitor,
+    conversation_diff::{diff, ConversationDiff},
+    conversation_export::to_markdown,
+    diff_parser::FileDiff,
+    executor::NormalizedConversation,
     models::{
+        conversation_index::{ConversationIndex, ConversationSearchResult, MAX_SEARCH_RESULTS},
+        execution_process::ExecutionProcess,
+        notification::NotificationType,
         project::Project,
-        task::{CreateTask, CreateTaskAndStart, Task, TaskWithAttemptStatus, UpdateTask},
+        task::{
+            AddTaskDependency, CreateTask, CreateTaskAndStart, CreateTaskBatchItem, SetTaskEnvVar,
+            SimilarCompletion, Task, TaskBatchError, TaskDependency, TaskDependencyError,
+            TaskEnvVarError, TaskEstimate, TaskGraph, TaskStatus, TaskSubtasks, TaskTimeTracking,
+            TaskWithAttemptStatus, UpdateTask, TASK_PRIORITY_RANGE,
+        },
+        task_artifact::TaskArtifact,
         task_attempt::{CreateTaskAttempt, TaskAttempt},
+        task_run::TaskRun,
         ApiResponse,
     },
+    routes::task_attempts::normalize_process_logs,
+    services::{
+        audit_logger::{
+            TASK_ARCHIVED, TASK_CREATED, TASK_DEPENDENCY_ADDED, TASK_DEPENDENCY_REMOVED,
+            TASK_ENV_VAR_DELETED, TASK_ENV_VAR_SET, TASK_RESTORED, TASK_STATUS_CHANGED,
+            TASK_UPDATED,
+        },
+        git_service, AuditLogger,
+    },
+    utils::path::safe_join,
 };
 
+/// Largest worktree file served by the read-file endpoint
+const MAX_READ_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+pub struct ArchivedTasksQuery {
+    /// Also return tasks that were deleted but not purged yet
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectTasksQuery {
+    /// Also return tasks that were deleted but not purged yet
+    #[serde(default)]
+    pub include_archived: bool,
+    /// Only return tasks with the label of this name
+    pub label: Option<String>,
+}
+
+/// Pool of the database with the project's tasks, see [`AppState::project_pool`]
+pub(crate) async fn project_pool(
+    app_state: &AppState,
+    project_id: Uuid,
+) -> Result<SqlitePool, StatusCode> {
+    app_state
+        .project_pool(project_id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            e => {
+                tracing::error!("Failed to open database of project {}: {}", project_id, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })
+}
+
+/// Pool of the database holding the task, for routes that know the task only by ID
+pub(crate) async fn task_pool(
+    app_state: &AppState,
+    task_id: Uuid,
+) -> Result<SqlitePool, StatusCode> {
+    let pool = app_state
+        .find_task_pool(|pool| async move { Ok(Task::find_by_id(&pool, task_id).await?.is_some()) })
+        .await;
+    match pool {
+        Ok(Some(pool)) => Ok(pool),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to find the database of task {}: {}", task_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub async fn get_project_tasks(
     Path(project_id): Path<Uuid>,
+    Query(query): Query<ProjectTasksQuery>,
     State(app_state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<Vec<TaskWithAttemptStatus>>>, StatusCode> {
-    match Task::find_by_project_id_with_attempt_status(&app_state.db_pool, project_id).await {
+    let pool = project_pool(&app_state, project_id).await?;
+    match Task::find_by_project_id_with_attempt_status(
+        &pool,
+        project_id,
+        query.include_archived,
+        query.label.as_deref(),
+    )
+    .await
+    {
         Ok(tasks) => Ok(ResponseJson(ApiResponse {
             success: true,
             data: Some(tasks),
@@ -35,11 +126,39 @@ pub async fn get_project_tasks(
     }
 }
 
+/// List the project's tasks that are still to do, in the order their runs get executor slots
+pub async fn get_pending_tasks(
+    Path(project_id): Path<Uuid>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    match Task::list_pending(&pool, project_id).await {
+        Ok(tasks) => Ok(ResponseJson(ApiResponse::success(tasks))),
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch pending tasks for project {}: {}",
+                project_id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub async fn get_task(
     Path((project_id, task_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<ArchivedTasksQuery>,
     State(app_state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<Task>>, StatusCode> {
-    match Task::find_by_id_and_project_id(&app_state.db_pool, task_id, project_id).await {
+    let pool = project_pool(&app_state, project_id).await?;
+    let task = if query.include_archived {
+        Task::find_by_id(&pool, task_id)
+            .await
+            .map(|task| task.filter(|task| task.project_id == project_id))
+    } else {
+        Task::find_by_id_and_project_id(&pool, task_id, project_id).await
+    };
+    match task {
         Ok(Some(task)) => Ok(ResponseJson(ApiResponse {
             success: true,
             data: Some(task),
@@ -68,6 +187,20 @@ pub async fn create_task(
     // Ensure the project_id in the payload matches the path parameter
     payload.project_id = project_id;
 
+    if payload.timeout_seconds.is_some_and(|seconds| seconds <= 0) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if payload
+        .priority
+        .is_some_and(|priority| !TASK_PRIORITY_RANGE.contains(&priority))
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    payload.executor_model = payload
+        .executor_model
+        .map(|model| model.trim().to_string())
+        .filter(|model| !model.is_empty());
+
     // Verify project exists first
     match Project::exists(&app_state.db_pool, project_id).await {
         Ok(false) => return Err(StatusCode::NOT_FOUND),
@@ -77,6 +210,19 @@ pub async fn create_task(
         }
         Ok(true) => {}
     }
+    let pool = project_pool(&app_state, project_id).await?;
+
+    // Sub-tasks must live in the same project as their parent
+    if let Some(parent_task_id) = payload.parent_task_id {
+        match Task::exists(&pool, parent_task_id, project_id).await {
+            Ok(false) => return Err(StatusCode::BAD_REQUEST),
+            Err(e) => {
+                tracing::error!("Failed to check parent task existence: {}", e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+            Ok(true) => {}
+        }
+    }
 
     tracing::debug!(
         "Creating task '{}' in project {}",
@@ -84,7 +230,26 @@ pub async fn create_task(
         project_id
     );
 
-    match Task::create(&app_state.db_pool, &payload, id).await {
+    let audit = AuditLogger::new(pool.clone());
+    let created = async {
+        let mut tx = audit.begin().await?;
+        let task = Task::create_in_tx(&mut tx, &payload, id).await?;
+        audit
+            .log_change(
+                &mut tx,
+                TASK_CREATED,
+                task.id,
+                app_state.user_id(),
+                None,
+                Some(&task),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(task)
+    }
+    .await;
+
+    match created {
         Ok(task) => {
             // Track task creation event
             app_state
@@ -111,6 +276,130 @@ pub async fn create_task(
     }
 }
 
+pub async fn clone_task(
+    Path((project_id, task_id)): Path<(Uuid, Uuid)>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Task>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    let audit = AuditLogger::new(pool.clone());
+    let cloned = async {
+        let mut tx = audit.begin().await?;
+        let Some(task) = Task::duplicate(&mut tx, task_id, project_id, Uuid::new_v4()).await?
+        else {
+            return Ok(None);
+        };
+        audit
+            .log_change(
+                &mut tx,
+                TASK_CREATED,
+                task.id,
+                app_state.user_id(),
+                None,
+                Some(&task),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Some(task))
+    }
+    .await;
+
+    match cloned {
+        Ok(Some(task)) => {
+            app_state
+                .track_analytics_event(
+                    "task_created",
+                    Some(serde_json::json!({
+                        "task_id": task.id.to_string(),
+                        "project_id": project_id.to_string(),
+                        "has_description": task.description.is_some(),
+                        "cloned_from": task_id.to_string(),
+                    })),
+                )
+                .await;
+
+            Ok(ResponseJson(ApiResponse {
+                success: true,
+                data: Some(task),
+                message: Some("Task cloned successfully".to_string()),
+            }))
+        }
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to clone task {}: {}", task_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn create_task_batch(
+    Path(project_id): Path<Uuid>,
+    State(app_state): State<AppState>,
+    Json(mut payload): Json<Vec<CreateTaskBatchItem>>,
+) -> Result<Response, StatusCode> {
+    match Project::exists(&app_state.db_pool, project_id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check project existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
+    let pool = project_pool(&app_state, project_id).await?;
+
+    for item in &mut payload {
+        item.executor_model = item
+            .executor_model
+            .take()
+            .map(|model| model.trim().to_string())
+            .filter(|model| !model.is_empty());
+    }
+
+    let audit = AuditLogger::new(pool.clone());
+    let created = async {
+        let mut tx = audit.begin().await?;
+        let tasks = Task::create_batch(&mut tx, project_id, &payload).await?;
+        for task in &tasks {
+            audit
+                .log_change(
+                    &mut tx,
+                    TASK_CREATED,
+                    task.id,
+                    app_state.user_id(),
+                    None,
+                    Some(task),
+                )
+                .await?;
+        }
+        tx.commit().await?;
+        Ok::<_, TaskBatchError>(tasks)
+    }
+    .await;
+
+    match created {
+        Ok(tasks) => {
+            app_state
+                .track_analytics_event(
+                    "task_batch_created",
+                    Some(serde_json::json!({
+                        "project_id": project_id.to_string(),
+                        "task_count": tasks.len(),
+                    })),
+                )
+                .await;
+            Ok(ResponseJson(ApiResponse::success(tasks)).into_response())
+        }
+        Err(TaskBatchError::Database(e)) => {
+            tracing::error!("Failed to create task batch: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+        Err(e) => Ok((
+            StatusCode::BAD_REQUEST,
+            ResponseJson(ApiResponse::<Vec<Task>>::error(&e.to_string())),
+        )
+            .into_response()),
+    }
+}
+
 pub async fn create_task_and_start(
     Path(project_id): Path<Uuid>,
     State(app_state): State<AppState>,
@@ -121,6 +410,14 @@ pub async fn create_task_and_start(
     // Ensure the project_id in the payload matches the path parameter
     payload.project_id = project_id;
 
+    if payload.timeout_seconds.is_some_and(|seconds| seconds <= 0) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    payload.executor_model = payload
+        .executor_model
+        .map(|model| model.trim().to_string())
+        .filter(|model| !model.is_empty());
+
     // Verify project exists first
     match Project::exists(&app_state.db_pool, project_id).await {
         Ok(false) => return Err(StatusCode::NOT_FOUND),
@@ -130,6 +427,7 @@ pub async fn create_task_and_start(
         }
         Ok(true) => {}
     }
+    let pool = project_pool(&app_state, project_id).await?;
 
     tracing::debug!(
         "Creating and starting task '{}' in project {}",
@@ -143,8 +441,31 @@ pub async fn create_task_and_start(
         title: payload.title.clone(),
         description: payload.description.clone(),
         parent_task_attempt: payload.parent_task_attempt,
+        labels: payload.labels.clone(),
+        timeout_seconds: payload.timeout_seconds,
+        executor_model: payload.executor_model.clone(),
+        priority: None,
+        parent_task_id: None,
     };
-    let task = match Task::create(&app_state.db_pool, &create_task_payload, task_id).await {
+    let audit = AuditLogger::new(pool.clone());
+    let created = async {
+        let mut tx = audit.begin().await?;
+        let task = Task::create_in_tx(&mut tx, &create_task_payload, task_id).await?;
+        audit
+            .log_change(
+                &mut tx,
+                TASK_CREATED,
+                task.id,
+                app_state.user_id(),
+                None,
+                Some(&task),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(task)
+    }
+    .await;
+    let task = match created {
         Ok(task) => task,
         Err(e) => {
             tracing::error!("Failed to create task: {}", e);
@@ -159,7 +480,7 @@ pub async fn create_task_and_start(
         base_branch: None, // Not supported in task creation endpoint, only in task attempts
     };
 
-    match TaskAttempt::create(&app_state.db_pool, &attempt_payload, task_id).await {
+    match TaskAttempt::create(&pool, &attempt_payload, task_id).await {
         Ok(attempt) => {
             app_state
                 .track_analytics_event(
@@ -188,7 +509,7 @@ pub async fn create_task_and_start(
             let attempt_id = attempt.id;
             tokio::spawn(async move {
                 if let Err(e) = TaskAttempt::start_execution(
-                    &app_state_clone.db_pool,
+                    &pool,
                     &app_state_clone,
                     attempt_id,
                     task_id,
@@ -222,16 +543,40 @@ pub async fn update_task(
     State(app_state): State<AppState>,
     Json(payload): Json<UpdateTask>,
 ) -> Result<ResponseJson<ApiResponse<Task>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
     // Check if task exists in the specified project
-    let existing_task =
-        match Task::find_by_id_and_project_id(&app_state.db_pool, task_id, project_id).await {
-            Ok(Some(task)) => task,
-            Ok(None) => return Err(StatusCode::NOT_FOUND),
+    let existing_task = match Task::find_by_id_and_project_id(&pool, task_id, project_id).await {
+        Ok(Some(task)) => task,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check task existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let newly_cancelled = payload.status == Some(TaskStatus::Cancelled)
+        && existing_task.status != TaskStatus::Cancelled;
+
+    // A task is only complete once all of its sub-tasks are
+    if payload.status == Some(TaskStatus::Done) && existing_task.status != TaskStatus::Done {
+        match Task::incomplete_subtask_count(&pool, task_id).await {
+            Ok(0) => {}
+            Ok(count) => {
+                tracing::info!(
+                    "Not completing task {}: {} sub-tasks are not done",
+                    task_id,
+                    count
+                );
+                return Err(StatusCode::CONFLICT);
+            }
             Err(e) => {
-                tracing::error!("Failed to check task existence: {}", e);
+                tracing::error!("Failed to count sub-tasks of task {}: {}", task_id, e);
                 return Err(StatusCode::INTERNAL_SERVER_ERROR);
             }
-        };
+        }
+    }
+    let previous_status = existing_task.status.clone();
+    let before = existing_task.clone();
 
     // Use existing values if not provided in update
     let title = payload.title.unwrap_or(existing_task.title);
@@ -240,114 +585,1430 @@ pub async fn update_task(
     let parent_task_attempt = payload
         .parent_task_attempt
         .or(existing_task.parent_task_attempt);
+    let timeout_seconds = match payload.timeout_seconds {
+        Some(0) => None,
+        Some(seconds) if seconds < 0 => return Err(StatusCode::BAD_REQUEST),
+        Some(seconds) => Some(seconds),
+        None => existing_task.timeout_seconds,
+    };
+    let executor_model = match payload.executor_model {
+        Some(model) if model.trim().is_empty() => None,
+        Some(model) => Some(model.trim().to_string()),
+        None => existing_task.executor_model,
+    };
+    let priority = payload.priority;
+    if priority.is_some_and(|priority| !TASK_PRIORITY_RANGE.contains(&priority)) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
 
-    match Task::update(
-        &app_state.db_pool,
-        task_id,
-        project_id,
-        title,
-        description,
-        status,
-        parent_task_attempt,
-    )
-    .await
-    {
-        Ok(task) => Ok(ResponseJson(ApiResponse {
+    let audit = AuditLogger::new(pool.clone());
+    let updated = async {
+        let mut tx = audit.begin().await?;
+        if let Some(priority) = priority {
+            Task::set_priority(&mut *tx, task_id, project_id, priority).await?;
+        }
+        let task = Task::update(
+            &mut *tx,
+            task_id,
+            project_id,
+            title,
+            description,
+            status,
+            parent_task_attempt,
+            timeout_seconds,
+            executor_model,
+        )
+        .await?;
+        let action = if task.status != previous_status {
+            TASK_STATUS_CHANGED
+        } else {
+            TASK_UPDATED
+        };
+        audit
+            .log_change(
+                &mut tx,
+                action,
+                task.id,
+                app_state.user_id(),
+                Some(&before),
+                Some(&task),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(task)
+    }
+    .await;
+
+    match updated {
+        Ok(task) => {
+            if newly_cancelled {
+                cancel_blocked_dependents(&pool, task_id, app_state.user_id()).await;
+            }
+            if task.status != previous_status {
+                let message = format!(
+                    "'{}' moved from {:?} to {:?}",
+                    task.title, previous_status, task.status
+                );
+                app_state
+                    .send_notification(
+                        &pool,
+                        Some(task.id),
+                        NotificationType::StatusChanged,
+                        &message,
+                    )
+                    .await;
+            }
+
+            Ok(ResponseJson(ApiResponse {
+                success: true,
+                data: Some(task),
+                message: Some("Task updated successfully".to_string()),
+            }))
+        }
+        Err(e) => {
+            tracing::error!("Failed to update task: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Cancel every task that was (transitively) blocked on a task that has just been cancelled
+async fn cancel_blocked_dependents(pool: &SqlitePool, task_id: Uuid, actor_id: &str) {
+    let dependents = match Task::all_transitive_dependents(pool, task_id).await {
+        Ok(dependents) => dependents,
+        Err(e) => {
+            tracing::error!("Failed to fetch dependents of task {}: {}", task_id, e);
+            return;
+        }
+    };
+
+    let audit = AuditLogger::new(pool.clone());
+    for dependent in dependents {
+        if matches!(dependent.status, TaskStatus::Done | TaskStatus::Cancelled) {
+            continue;
+        }
+        let cancelled = async {
+            let mut tx = audit.begin().await?;
+            Task::update_status(
+                &mut *tx,
+                dependent.id,
+                dependent.project_id,
+                TaskStatus::Cancelled,
+            )
+            .await?;
+            let cancelled = Task {
+                status: TaskStatus::Cancelled,
+                ..dependent.clone()
+            };
+            audit
+                .log_change(
+                    &mut tx,
+                    TASK_STATUS_CHANGED,
+                    dependent.id,
+                    actor_id,
+                    Some(&dependent),
+                    Some(&cancelled),
+                )
+                .await?;
+            tx.commit().await
+        }
+        .await;
+        if let Err(e) = cancelled {
+            tracing::error!("Failed to cancel dependent task {}: {}", dependent.id, e);
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskSessionQuery {
+    pub session_id: String,
+}
+
+/// List every task that has had a run with the given executor session ID
+pub async fn get_tasks_by_session(
+    Query(query): Query<TaskSessionQuery>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, StatusCode> {
+    let tasks = async {
+        let mut tasks = Vec::new();
+        for pool in app_state.task_pools().await? {
+            tasks.extend(Task::find_by_run_session_id(&pool, &query.session_id).await?);
+        }
+        tasks.sort_by_key(|task| task.created_at);
+        Ok::<_, sqlx::Error>(tasks)
+    }
+    .await;
+    match tasks {
+        Ok(tasks) => Ok(ResponseJson(ApiResponse {
             success: true,
-            data: Some(task),
-            message: Some("Task updated successfully".to_string()),
+            data: Some(tasks),
+            message: None,
         })),
         Err(e) => {
-            tracing::error!("Failed to update task: {}", e);
+            tracing::error!(
+                "Failed to fetch tasks for session {}: {}",
+                query.session_id,
+                e
+            );
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
-pub async fn delete_task(
+#[derive(Debug, Deserialize)]
+pub struct ConversationSearchQuery {
+    pub q: String,
+    pub project_id: Option<Uuid>,
+}
+
+/// Search the conversations of task runs for the given text, best matches first
+pub async fn search_task_conversations(
+    Query(query): Query<ConversationSearchQuery>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<ConversationSearchResult>>>, StatusCode> {
+    if query.q.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let pools = match query.project_id {
+        Some(project_id) => vec![project_pool(&app_state, project_id).await?],
+        None => app_state.task_pools().await.map_err(|e| {
+            tracing::error!("Failed to open task databases: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?,
+    };
+    let results = async {
+        let mut results = Vec::new();
+        for pool in &pools {
+            results.extend(ConversationIndex::search(pool, &query.q, query.project_id).await?);
+        }
+        results.truncate(MAX_SEARCH_RESULTS as usize);
+        Ok::<_, sqlx::Error>(results)
+    }
+    .await;
+    match results {
+        Ok(results) => Ok(ResponseJson(ApiResponse::success(results))),
+        Err(e) => {
+            tracing::error!("Failed to search conversations for '{}': {}", query.q, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_task_labels(
     Path((project_id, task_id)): Path<(Uuid, Uuid)>,
     State(app_state): State<AppState>,
-) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
-    // Verify task exists in the specified project
-    match Task::find_by_id_and_project_id(&app_state.db_pool, task_id, project_id).await {
-        Ok(Some(_)) => {} // Task exists, proceed
-        Ok(None) => return Err(StatusCode::NOT_FOUND),
+) -> Result<ResponseJson<ApiResponse<Vec<String>>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    match Task::exists(&pool, task_id, project_id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!("Failed to check task existence: {}", e);
             return Err(StatusCode::INTERNAL_SERVER_ERROR);
         }
+        Ok(true) => {}
     }
 
-    // Clean up all worktrees for this task before deletion
-    if let Err(e) = execution_monitor::cleanup_task_worktrees(&app_state.db_pool, task_id).await {
-        tracing::error!("Failed to cleanup worktrees for task {}: {}", task_id, e);
-        // Continue with deletion even if cleanup fails
+    match Task::labels(&pool, task_id).await {
+        Ok(labels) => Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: Some(labels),
+            message: None,
+        })),
+        Err(e) => {
+            tracing::error!("Failed to fetch labels of task {}: {}", task_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
     }
+}
 
-    // Clean up all executor sessions for this task before deletion
-    match TaskAttempt::find_by_task_id(&app_state.db_pool, task_id).await {
-        Ok(task_attempts) => {
-            for attempt in task_attempts {
-                if let Err(e) =
-                    crate::models::executor_session::ExecutorSession::delete_by_task_attempt_id(
-                        &app_state.db_pool,
-                        attempt.id,
-                    )
-                    .await
-                {
-                    tracing::error!(
-                        "Failed to cleanup executor sessions for task attempt {}: {}",
-                        attempt.id,
-                        e
-                    );
-                    // Continue with deletion even if session cleanup fails
-                } else {
-                    tracing::debug!(
-                        "Cleaned up executor sessions for task attempt {}",
-                        attempt.id
-                    );
-                }
-            }
+pub async fn get_task_subtasks(
+    Path((project_id, task_id)): Path<(Uuid, Uuid)>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<TaskSubtasks>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    let task = match Task::find_by_id_and_project_id(&pool, task_id, project_id).await {
+        Ok(Some(task)) => task,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch task {}: {}", task_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
         }
+    };
+
+    let subtasks = async {
+        Ok::<_, sqlx::Error>(TaskSubtasks {
+            subtasks: Task::get_children(&pool, task_id).await?,
+            completion_percentage: task.completion_percentage(&pool).await?,
+        })
+    }
+    .await;
+    match subtasks {
+        Ok(subtasks) => Ok(ResponseJson(ApiResponse::success(subtasks))),
         Err(e) => {
-            tracing::error!("Failed to get task attempts for session cleanup: {}", e);
-            // Continue with deletion even if we can't get task attempts
+            tracing::error!("Failed to fetch sub-tasks of task {}: {}", task_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
+}
 
-    match Task::delete(&app_state.db_pool, task_id, project_id).await {
-        Ok(rows_affected) => {
-            if rows_affected == 0 {
-                Err(StatusCode::NOT_FOUND)
-            } else {
-                Ok(ResponseJson(ApiResponse {
-                    success: true,
-                    data: None,
-                    message: Some("Task deleted successfully".to_string()),
-                }))
-            }
+pub async fn get_task_artifacts(
+    Path((project_id, task_id)): Path<(Uuid, Uuid)>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskArtifact>>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    match Task::exists(&pool, task_id, project_id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check task existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
         }
+        Ok(true) => {}
+    }
+
+    match TaskArtifact::find_by_task_id(&pool, task_id).await {
+        Ok(artifacts) => Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: Some(artifacts),
+            message: None,
+        })),
         Err(e) => {
-            tracing::error!("Failed to delete task: {}", e);
+            tracing::error!("Failed to fetch artifacts of task {}: {}", task_id, e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
-pub fn tasks_router() -> Router<AppState> {
-    use axum::routing::post;
+/// Every coding agent run of the task, newest first, each with its own logs
+///
+/// GET /api/projects/:project_id/tasks/:task_id/runs
+pub async fn get_task_runs(
+    Path((project_id, task_id)): Path<(Uuid, Uuid)>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskRun>>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    match Task::exists(&pool, task_id, project_id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check task existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
 
-    Router::new()
-        .route(
-            "/projects/:project_id/tasks",
-            get(get_project_tasks).post(create_task),
-        )
-        .route(
-            "/projects/:project_id/tasks/create-and-start",
-            post(create_task_and_start),
-        )
-        .route(
-            "/projects/:project_id/tasks/:task_id",
-            get(get_task).put(update_task).delete(delete_task),
-        )
+    match TaskRun::find_by_task_id(&pool, task_id).await {
+        Ok(runs) => Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: Some(runs),
+            message: None,
+        })),
+        Err(e) => {
+            tracing::error!("Failed to fetch runs of task {}: {}", task_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// One coding agent run of the task with its logs and the commands it called
+///
+/// GET /api/projects/:project_id/tasks/:task_id/runs/:run_id
+pub async fn get_task_run(
+    Path((project_id, task_id, run_id)): Path<(Uuid, Uuid, Uuid)>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<TaskRun>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    match Task::exists(&pool, task_id, project_id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check task existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
+
+    match TaskRun::find_by_id_and_task_id(&pool, run_id, task_id).await {
+        Ok(Some(run)) => Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: Some(run),
+            message: None,
+        })),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch run {} of task {}: {}", run_id, task_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RunDiffQuery {
+    /// The older run
+    pub from: Uuid,
+    /// The newer run
+    pub to: Uuid,
+}
+
+/// Entries added and removed in the conversation of the `to` run compared to the `from` run
+///
+/// GET /api/projects/:project_id/tasks/:task_id/runs/diff?from=<run_id>&to=<run_id>
+pub async fn get_task_runs_diff(
+    Path((project_id, task_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<RunDiffQuery>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<ConversationDiff>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    match Task::exists(&pool, task_id, project_id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check task existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
+
+    let runs = match TaskRun::find_by_task_id(&pool, task_id).await {
+        Ok(runs) => runs,
+        Err(e) => {
+            tracing::error!("Failed to fetch runs of task {}: {}", task_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    // Both runs must belong to this task
+    for run_id in [query.from, query.to] {
+        if !runs.iter().any(|run| run.id == run_id) {
+            return Err(StatusCode::NOT_FOUND);
+        }
+    }
+
+    let from = run_conversation(&pool, query.from).await?;
+    let to = run_conversation(&pool, query.to).await?;
+    Ok(ResponseJson(ApiResponse::success(diff(&from, &to))))
+}
+
+async fn run_conversation(
+    pool: &SqlitePool,
+    run_id: Uuid,
+) -> Result<NormalizedConversation, StatusCode> {
+    match ExecutionProcess::find_by_id(pool, run_id).await {
+        Ok(Some(process)) => Ok(normalize_process_logs(pool, &process).await),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch execution process {}: {}", run_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// The latest run's conversation as a Markdown document
+///
+/// GET /api/projects/:project_id/tasks/:task_id/export.md
+pub async fn export_task_markdown(
+    Path((project_id, task_id)): Path<(Uuid, Uuid)>,
+    State(app_state): State<AppState>,
+) -> Result<Response, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    match Task::exists(&pool, task_id, project_id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check task existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
+
+    let latest_run = match TaskRun::find_by_task_id(&pool, task_id).await {
+        Ok(runs) => runs.into_iter().next().ok_or(StatusCode::NOT_FOUND)?,
+        Err(e) => {
+            tracing::error!("Failed to fetch runs of task {}: {}", task_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    let process = match ExecutionProcess::find_by_id(&pool, latest_run.id).await {
+        Ok(Some(process)) => process,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch execution process {}: {}", latest_run.id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let conversation = normalize_process_logs(&pool, &process).await;
+    Ok((
+        [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+        to_markdown(&conversation),
+    )
+        .into_response())
+}
+
+/// Done tasks similar to this one, showing how the agent handled comparable work
+///
+/// GET /api/projects/:project_id/tasks/:task_id/similar-completions
+pub async fn get_similar_completions(
+    Path((project_id, task_id)): Path<(Uuid, Uuid)>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<SimilarCompletion>>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    let task = match Task::find_by_id_and_project_id(&pool, task_id, project_id).await {
+        Ok(Some(task)) => task,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch task {}: {}", task_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    match task.find_similar_completions(&pool).await {
+        Ok(completions) => Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: Some(completions),
+            message: None,
+        })),
+        Err(e) => {
+            tracing::error!(
+                "Failed to find similar completions for task {}: {}",
+                task_id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// The task's word count, complexity score and expected completion time, estimated from
+/// the project's completed coding agent runs
+///
+/// GET /api/projects/:project_id/tasks/:task_id/estimate
+pub async fn get_task_estimate(
+    Path((project_id, task_id)): Path<(Uuid, Uuid)>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<TaskEstimate>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    let task = match Task::find_by_id_and_project_id(&pool, task_id, project_id).await {
+        Ok(Some(task)) => task,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch task {}: {}", task_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    match task.estimate(&pool).await {
+        Ok(estimate) => Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: Some(estimate),
+            message: None,
+        })),
+        Err(e) => {
+            tracing::error!("Failed to estimate task {}: {}", task_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// When the task's latest run started and ended, and how long it took
+///
+/// GET /api/projects/:project_id/tasks/:task_id/timing
+pub async fn get_task_timing(
+    Path((project_id, task_id)): Path<(Uuid, Uuid)>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<TaskTimeTracking>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    match Task::exists(&pool, task_id, project_id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check task existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
+
+    match Task::time_tracking(&pool, task_id).await {
+        Ok(Some(timing)) => Ok(ResponseJson(ApiResponse::success(timing))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to fetch timing of task {}: {}", task_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_task_dependents(
+    Path((project_id, task_id)): Path<(Uuid, Uuid)>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    match Task::exists(&pool, task_id, project_id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check task existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
+
+    match Task::dependent_tasks(&pool, task_id).await {
+        Ok(tasks) => Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: Some(tasks),
+            message: None,
+        })),
+        Err(e) => {
+            tracing::error!("Failed to fetch dependents of task {}: {}", task_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskGraphQuery {
+    /// `json` returns `{ nodes, edges }`; anything else returns Graphviz DOT
+    pub format: Option<String>,
+}
+
+pub async fn get_task_graph(
+    Path((project_id, task_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<TaskGraphQuery>,
+    State(app_state): State<AppState>,
+) -> Result<Response, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    match Task::exists(&pool, task_id, project_id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check task existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
+
+    let tasks = Task::dependency_graph_tasks(&pool, task_id).await;
+    let dependencies = Task::dependency_graph_edges(&pool, task_id).await;
+    let graph = match (tasks, dependencies) {
+        (Ok(tasks), Ok(dependencies)) => TaskGraph::new(tasks, dependencies),
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::error!(
+                "Failed to fetch dependency graph of task {}: {}",
+                task_id,
+                e
+            );
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    if query.format.as_deref() == Some("json") {
+        return Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: Some(graph),
+            message: None,
+        })
+        .into_response());
+    }
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/vnd.graphviz")],
+        graph.to_dot(),
+    )
+        .into_response())
+}
+
+/// The task's dependency graph as an adjacency list, mapping every task to the tasks it
+/// depends on
+pub async fn get_task_dependencies(
+    Path((project_id, task_id)): Path<(Uuid, Uuid)>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<BTreeMap<Uuid, Vec<Uuid>>>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    match Task::exists(&pool, task_id, project_id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check task existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
+
+    let tasks = Task::dependency_graph_tasks(&pool, task_id).await;
+    let dependencies = Task::dependency_graph_edges(&pool, task_id).await;
+    match (tasks, dependencies) {
+        (Ok(tasks), Ok(dependencies)) => Ok(ResponseJson(ApiResponse::success(
+            TaskGraph::new(tasks, dependencies).adjacency_list(),
+        ))),
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::error!("Failed to fetch dependencies of task {}: {}", task_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn add_task_dependency(
+    Path((project_id, task_id)): Path<(Uuid, Uuid)>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<AddTaskDependency>,
+) -> Result<Response, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    match Task::exists(&pool, task_id, project_id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check task existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
+
+    let audit = AuditLogger::new(pool.clone());
+    let added = async {
+        let mut tx = audit.begin().await?;
+        let dependency = Task::add_dependency(&mut tx, task_id, payload.depends_on_task_id).await?;
+        audit
+            .log_change(
+                &mut tx,
+                TASK_DEPENDENCY_ADDED,
+                task_id,
+                app_state.user_id(),
+                None,
+                Some(&dependency),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, TaskDependencyError>(dependency)
+    }
+    .await;
+
+    match added {
+        Ok(dependency) => Ok(ResponseJson(ApiResponse::success(dependency)).into_response()),
+        Err(TaskDependencyError::TaskNotFound) => Err(StatusCode::NOT_FOUND),
+        Err(e @ TaskDependencyError::Cycle(_)) => Ok((
+            StatusCode::CONFLICT,
+            ResponseJson(ApiResponse::<TaskDependency>::error(&e.to_string())),
+        )
+            .into_response()),
+        Err(TaskDependencyError::Database(e)) => {
+            tracing::error!("Failed to add dependency to task {}: {}", task_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn remove_task_dependency(
+    Path((project_id, task_id, depends_on_task_id)): Path<(Uuid, Uuid, Uuid)>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    match Task::exists(&pool, task_id, project_id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check task existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
+
+    let audit = AuditLogger::new(pool.clone());
+    let removed = async {
+        let mut tx = audit.begin().await?;
+        if Task::remove_dependency(&mut *tx, task_id, depends_on_task_id).await? == 0 {
+            return Ok(false);
+        }
+        let dependency = TaskDependency {
+            task_id,
+            depends_on_task_id,
+        };
+        audit
+            .log_change(
+                &mut tx,
+                TASK_DEPENDENCY_REMOVED,
+                task_id,
+                app_state.user_id(),
+                Some(&dependency),
+                None,
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(true)
+    }
+    .await;
+
+    match removed {
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Ok(true) => Ok(ResponseJson(ApiResponse::success(()))),
+        Err(e) => {
+            tracing::error!("Failed to remove dependency from task {}: {}", task_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Names of the task's environment variables; values are write-only
+pub async fn get_task_env_vars(
+    Path((project_id, task_id)): Path<(Uuid, Uuid)>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<String>>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    match Task::exists(&pool, task_id, project_id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check task existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
+
+    match Task::env_var_keys(&pool, task_id).await {
+        Ok(keys) => Ok(ResponseJson(ApiResponse::success(keys))),
+        Err(e) => {
+            tracing::error!("Failed to fetch environment of task {}: {}", task_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Whether `key` is a portable environment variable name
+fn is_valid_env_var_name(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+pub async fn set_task_env_var(
+    Path((project_id, task_id, key)): Path<(Uuid, Uuid, String)>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<SetTaskEnvVar>,
+) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    match Task::exists(&pool, task_id, project_id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check task existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
+
+    if !is_valid_env_var_name(&key) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let audit = AuditLogger::new(pool.clone());
+    let set = async {
+        let mut tx = audit.begin().await?;
+        Task::set_env_var(&mut *tx, task_id, &key, &payload.value).await?;
+        // Values can be secrets, so only the name is logged
+        audit
+            .log_change(
+                &mut tx,
+                TASK_ENV_VAR_SET,
+                task_id,
+                app_state.user_id(),
+                None,
+                Some(&serde_json::json!({ "key": key })),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, TaskEnvVarError>(())
+    }
+    .await;
+
+    match set {
+        Ok(()) => Ok(ResponseJson(ApiResponse::success(()))),
+        Err(TaskEnvVarError::Encryption(e)) => {
+            tracing::warn!(
+                "Cannot store environment variable of task {}: {}",
+                task_id,
+                e
+            );
+            Ok(ResponseJson(ApiResponse::error(&e.to_string())))
+        }
+        Err(TaskEnvVarError::Database(e)) => {
+            tracing::error!("Failed to set environment of task {}: {}", task_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn delete_task_env_var(
+    Path((project_id, task_id, key)): Path<(Uuid, Uuid, String)>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    match Task::exists(&pool, task_id, project_id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check task existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
+
+    let audit = AuditLogger::new(pool.clone());
+    let deleted = async {
+        let mut tx = audit.begin().await?;
+        if Task::delete_env_var(&mut *tx, task_id, &key).await? == 0 {
+            return Ok(false);
+        }
+        audit
+            .log_change(
+                &mut tx,
+                TASK_ENV_VAR_DELETED,
+                task_id,
+                app_state.user_id(),
+                Some(&serde_json::json!({ "key": key })),
+                None,
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(true)
+    }
+    .await;
+
+    match deleted {
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Ok(true) => Ok(ResponseJson(ApiResponse::success(()))),
+        Err(e) => {
+            tracing::error!("Failed to delete environment of task {}: {}", task_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReadFileQuery {
+    /// Path of the file relative to the worktree root
+    pub path: String,
+}
+
+/// Serve a single file from the worktree of the task's latest attempt
+pub async fn read_worktree_file(
+    Path((project_id, task_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<ReadFileQuery>,
+    headers: HeaderMap,
+    State(app_state): State<AppState>,
+) -> Result<Response, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    match Task::exists(&pool, task_id, project_id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check task existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
+
+    let attempt = match TaskAttempt::find_by_task_id(&pool, task_id).await {
+        Ok(attempts) => attempts.into_iter().next().ok_or(StatusCode::NOT_FOUND)?,
+        Err(e) => {
+            tracing::error!("Failed to fetch attempts of task {}: {}", task_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let worktree = std::path::Path::new(&attempt.worktree_path);
+    if attempt.worktree_deleted || !worktree.is_dir() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let file_path = safe_join(worktree, &query.path).ok_or(StatusCode::BAD_REQUEST)?;
+
+    // Resolve symlinks so a link inside the worktree cannot point outside of it
+    let (canonical_worktree, canonical_file) = match (
+        tokio::fs::canonicalize(worktree).await,
+        tokio::fs::canonicalize(&file_path).await,
+    ) {
+        (Ok(worktree), Ok(file)) => (worktree, file),
+        _ => return Err(StatusCode::NOT_FOUND),
+    };
+    if !canonical_file.starts_with(&canonical_worktree) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let metadata = tokio::fs::metadata(&canonical_file)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    if !metadata.is_file() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    if metadata.len() > MAX_READ_FILE_BYTES {
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    let modified: Option<DateTime<Utc>> = metadata.modified().ok().map(DateTime::from);
+    let etag = format!(
+        "W/\"{:x}-{:x}\"",
+        metadata.len(),
+        modified.map(|m| m.timestamp()).unwrap_or_default()
+    );
+    let last_modified = modified
+        .map(|m| m.format("%a, %d %b %Y %H:%M:%S GMT").to_string())
+        .unwrap_or_default();
+
+    if headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v == etag)
+    {
+        return Ok((
+            StatusCode::NOT_MODIFIED,
+            [(header::ETAG, etag), (header::LAST_MODIFIED, last_modified)],
+        )
+            .into_response());
+    }
+
+    let contents = match tokio::fs::read(&canonical_file).await {
+        Ok(contents) => contents,
+        Err(e) => {
+            tracing::error!(
+                "Failed to read '{}' from worktree of task {}: {}",
+                query.path,
+                task_id,
+                e
+            );
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let content_type = if !contents.contains(&0) && std::str::from_utf8(&contents).is_ok() {
+        "text/plain; charset=utf-8"
+    } else {
+        "application/octet-stream"
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::ETAG, etag),
+            (header::LAST_MODIFIED, last_modified),
+        ],
+        contents,
+    )
+        .into_response())
+}
+
+/// The changes of the task's latest run, i.e. of its commit, as a structured unified diff
+pub async fn get_task_diff(
+    Path((project_id, task_id)): Path<(Uuid, Uuid)>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<FileDiff>>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    match Task::exists(&pool, task_id, project_id).await {
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check task existence: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(true) => {}
+    }
+
+    let attempt = match TaskAttempt::find_by_task_id(&pool, task_id).await {
+        Ok(attempts) => attempts.into_iter().next().ok_or(StatusCode::NOT_FOUND)?,
+        Err(e) => {
+            tracing::error!("Failed to fetch attempts of task {}: {}", task_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let worktree = std::path::Path::new(&attempt.worktree_path);
+    if attempt.worktree_deleted || !worktree.is_dir() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    match git_service::latest_run_diff(worktree).await {
+        Ok(files) => Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: Some(files),
+            message: None,
+        })),
+        Err(e) => {
+            tracing::error!("Failed to diff worktree of task {}: {}", task_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Archive the task; it can be restored until it is purged 30 days later
+pub async fn delete_task(
+    Path((project_id, task_id)): Path<(Uuid, Uuid)>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    let audit = AuditLogger::new(pool.clone());
+    let archived = async {
+        let mut tx = audit.begin().await?;
+        let before = Task::find_by_id(&mut *tx, task_id).await?;
+        if Task::archive(&mut *tx, task_id, project_id).await? == 0 {
+            return Ok(false);
+        }
+        let after = Task::find_by_id(&mut *tx, task_id).await?;
+        audit
+            .log_change(
+                &mut tx,
+                TASK_ARCHIVED,
+                task_id,
+                app_state.user_id(),
+                before.as_ref(),
+                after.as_ref(),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(true)
+    }
+    .await;
+
+    match archived {
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Ok(true) => Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: None,
+            message: Some("Task archived successfully".to_string()),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to archive task {}: {}", task_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn restore_task(
+    Path((project_id, task_id)): Path<(Uuid, Uuid)>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Task>>, StatusCode> {
+    let pool = project_pool(&app_state, project_id).await?;
+    let audit = AuditLogger::new(pool.clone());
+    let restored = async {
+        let mut tx = audit.begin().await?;
+        let before = Task::find_by_id(&mut *tx, task_id).await?;
+        let Some(task) = Task::restore(&mut *tx, task_id, project_id).await? else {
+            return Ok(None);
+        };
+        audit
+            .log_change(
+                &mut tx,
+                TASK_RESTORED,
+                task_id,
+                app_state.user_id(),
+                before.as_ref(),
+                Some(&task),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Some(task))
+    }
+    .await;
+
+    match restored {
+        Ok(Some(task)) => Ok(ResponseJson(ApiResponse {
+            success: true,
+            data: Some(task),
+            message: Some("Task restored successfully".to_string()),
+        })),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to restore task {}: {}", task_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub fn tasks_router() -> Router<AppState> {
+    use axum::routing::{delete, post, put};
+
+    Router::new()
+        .route("/tasks", get(get_tasks_by_session))
+        .route("/tasks/search", get(search_task_conversations))
+        .route(
+            "/projects/:project_id/tasks",
+            get(get_project_tasks).post(create_task),
+        )
+        .route(
+            "/projects/:project_id/tasks/create-and-start",
+            post(create_task_and_start),
+        )
+        .route("/projects/:project_id/tasks/batch", post(create_task_batch))
+        .route(
+            "/projects/:project_id/tasks/pending",
+            get(get_pending_tasks),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id",
+            get(get_task).put(update_task).delete(delete_task),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/restore",
+            post(restore_task),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/clone",
+            post(clone_task),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/subtasks",
+            get(get_task_subtasks),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/labels",
+            get(get_task_labels),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/artifacts",
+            get(get_task_artifacts),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/runs",
+            get(get_task_runs),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/runs/diff",
+            get(get_task_runs_diff),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/runs/:run_id",
+            get(get_task_run),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/diff",
+            get(get_task_diff),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/export.md",
+            get(export_task_markdown),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/dependents",
+            get(get_task_dependents),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/dependencies",
+            get(get_task_dependencies).post(add_task_dependency),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/dependencies/:depends_on_task_id",
+            delete(remove_task_dependency),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/env-vars",
+            get(get_task_env_vars),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/env-vars/:key",
+            put(set_task_env_var).delete(delete_task_env_var),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/similar-completions",
+            get(get_similar_completions),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/timing",
+            get(get_task_timing),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/estimate",
+            get(get_task_estimate),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/graph",
+            get(get_task_graph),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/worktree/read-file",
+            get(read_worktree_file),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        models::config::Config,
+        test_fixtures::{create_attempt, create_project, create_task, memory_pool},
+    };
+
+    async fn setup() -> (AppState, Uuid, Uuid, Uuid) {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let task_id = create_task(&pool, project_id, "task").await;
+        let blocker_id = create_task(&pool, project_id, "blocker").await;
+
+        let app_state =
+            AppState::new(pool, Arc::new(tokio::sync::RwLock::new(Config::default()))).await;
+        (app_state, project_id, task_id, blocker_id)
+    }
+
+    #[tokio::test]
+    async fn test_dependency_changes_are_audited() {
+        let (app_state, project_id, task_id, blocker_id) = setup().await;
+
+        let response = add_task_dependency(
+            Path((project_id, task_id)),
+            State(app_state.clone()),
+            Json(AddTaskDependency {
+                depends_on_task_id: blocker_id,
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let ResponseJson(removed) = remove_task_dependency(
+            Path((project_id, task_id, blocker_id)),
+            State(app_state.clone()),
+        )
+        .await
+        .unwrap();
+        assert!(removed.success);
+
+        let entries = AuditLogger::new(app_state.db_pool.clone())
+            .entries(task_id, 50)
+            .await
+            .unwrap();
+        let actions: Vec<&str> = entries.iter().map(|entry| entry.action.as_str()).collect();
+        assert_eq!(
+            actions,
+            vec![TASK_DEPENDENCY_REMOVED, TASK_DEPENDENCY_ADDED]
+        );
+        let removed: TaskDependency =
+            serde_json::from_str(entries[0].before_json.as_deref().unwrap()).unwrap();
+        assert_eq!(removed.depends_on_task_id, blocker_id);
+        assert!(entries[0].after_json.is_none());
+        assert_eq!(entries[1].actor_id, app_state.user_id());
+    }
+
+    #[tokio::test]
+    async fn test_cancelling_a_blocker_audits_its_dependents() {
+        let (app_state, project_id, task_id, blocker_id) = setup().await;
+        let mut tx = app_state.db_pool.begin().await.unwrap();
+        Task::add_dependency(&mut tx, task_id, blocker_id)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        cancel_blocked_dependents(&app_state.db_pool, blocker_id, "user-1").await;
+
+        let entries = AuditLogger::new(app_state.db_pool.clone())
+            .entries(task_id, 50)
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].action, TASK_STATUS_CHANGED);
+        assert_eq!(entries[0].actor_id, "user-1");
+        let after: Task = serde_json::from_str(entries[0].after_json.as_deref().unwrap()).unwrap();
+        assert_eq!(after.status, TaskStatus::Cancelled);
+        let task = Task::find_by_id(&app_state.db_pool, task_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.status, TaskStatus::Cancelled);
+        assert_eq!(task.project_id, project_id);
+    }
+
+    #[tokio::test]
+    async fn test_estimate_waits_for_a_completed_run() {
+        let (app_state, project_id, task_id, _) = setup().await;
+
+        let ResponseJson(response) =
+            get_task_estimate(Path((project_id, task_id)), State(app_state.clone()))
+                .await
+                .unwrap();
+        let estimate = response.data.unwrap();
+        assert_eq!(estimate.word_count, 1);
+        assert_eq!(estimate.complexity_score, 0.01);
+        assert_eq!(estimate.estimated_completion_ms, None);
+
+        let missing = get_task_estimate(Path((project_id, Uuid::new_v4())), State(app_state)).await;
+        assert_eq!(missing.unwrap_err(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_subtasks_include_completion_percentage() {
+        let (app_state, project_id, task_id, subtask_id) = setup().await;
+        sqlx::query("UPDATE tasks SET parent_task_id = $1, status = 'done' WHERE id = $2")
+            .bind(task_id)
+            .bind(subtask_id)
+            .execute(&app_state.db_pool)
+            .await
+            .unwrap();
+
+        let ResponseJson(response) =
+            get_task_subtasks(Path((project_id, task_id)), State(app_state.clone()))
+                .await
+                .unwrap();
+        let subtasks = response.data.unwrap();
+        assert_eq!(subtasks.subtasks.len(), 1);
+        assert_eq!(subtasks.subtasks[0].id, subtask_id);
+        assert_eq!(subtasks.completion_percentage, 100.0);
+    }
+
+    #[tokio::test]
+    async fn test_timing_reports_the_latest_run() {
+        let (app_state, project_id, task_id, _) = setup().await;
+        Task::mark_started(&app_state.db_pool, task_id)
+            .await
+            .unwrap();
+
+        let ResponseJson(response) =
+            get_task_timing(Path((project_id, task_id)), State(app_state.clone()))
+                .await
+                .unwrap();
+        let timing = response.data.unwrap();
+        assert!(timing.started_at.is_some());
+        assert_eq!(timing.completed_at, None);
+        assert_eq!(timing.total_execution_ms, None);
+
+        let missing = get_task_timing(Path((Uuid::new_v4(), task_id)), State(app_state)).await;
+        assert_eq!(missing.unwrap_err(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_run_reports_its_recorded_command_counts() {
+        let (app_state, project_id, task_id, other_task_id) = setup().await;
+        let pool = &app_state.db_pool;
+        let attempt_id = create_attempt(pool, task_id, "/tmp/test-worktree").await;
+        let mut run_ids = Vec::new();
+        for counts in [[("cargo", 2), ("git", 1)], [("cargo", 1), ("rm", 3)]] {
+            let run_id = Uuid::new_v4();
+            sqlx::query(
+                "INSERT INTO execution_processes (id, task_attempt_id, process_type, status, command, working_directory)
+                 VALUES ($1, $2, 'codingagent', 'completed', 'claude', '/tmp/test-worktree')",
+            )
+            .bind(run_id)
+            .bind(attempt_id)
+            .execute(pool)
+            .await
+            .unwrap();
+            let counts = counts
+                .into_iter()
+                .map(|(command, count)| (command.to_string(), count))
+                .collect();
+            TaskRun::set_command_counts(pool, run_id, &counts)
+                .await
+                .unwrap();
+            run_ids.push(run_id);
+        }
+
+        let ResponseJson(response) = get_task_run(
+            Path((project_id, task_id, run_ids[0])),
+            State(app_state.clone()),
+        )
+        .await
+        .unwrap();
+        let run = response.data.unwrap();
+        assert_eq!(run.id, run_ids[0]);
+        let counts = run.command_counts.unwrap().0;
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts.get("cargo"), Some(&2));
+
+        let totals = TaskRun::command_counts_by_project_id(pool, project_id)
+            .await
+            .unwrap();
+        assert_eq!(totals.len(), 3);
+        assert_eq!(totals.get("cargo"), Some(&3));
+        assert_eq!(totals.get("rm"), Some(&3));
+
+        // The run belongs to another task
+        let missing = get_task_run(
+            Path((project_id, other_task_id, run_ids[0])),
+            State(app_state),
+        )
+        .await;
+        assert_eq!(missing.unwrap_err(), StatusCode::NOT_FOUND);
+    }
 }
@@ -0,0 +1,167 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{delete, get},
+    Json, Router,
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    models::{
+        api_response::ApiResponse,
+        project::Project,
+        webhook::{CreateWebhook, Webhook, WebhookDelivery},
+    },
+    services::webhook_service::WEBHOOK_EVENTS,
+};
+
+type ErrorResponse = (StatusCode, Json<ApiResponse<()>>);
+
+fn error(status: StatusCode, message: &str) -> ErrorResponse {
+    (status, Json(ApiResponse::error(message)))
+}
+
+/// Pool of the database holding the webhooks of the project, which must exist
+async fn project_pool(state: &AppState, project_id: Uuid) -> Result<SqlitePool, ErrorResponse> {
+    match Project::exists(&state.db_pool, project_id).await {
+        Ok(true) => state.project_pool(project_id).await.map_err(|e| {
+            error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &format!("Failed to open project database: {}", e),
+            )
+        }),
+        Ok(false) => Err(error(StatusCode::NOT_FOUND, "Project not found")),
+        Err(e) => Err(error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &format!("Failed to fetch project: {}", e),
+        )),
+    }
+}
+
+fn validate_webhook(payload: &CreateWebhook) -> Result<(), ErrorResponse> {
+    let url = payload.url.trim();
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Err(error(
+            StatusCode::BAD_REQUEST,
+            "Webhook URL must start with http:// or https://",
+        ));
+    }
+    if payload.secret.is_empty() {
+        return Err(error(StatusCode::BAD_REQUEST, "Webhook secret is required"));
+    }
+    if payload.events.is_empty() {
+        return Err(error(
+            StatusCode::BAD_REQUEST,
+            "Webhook must subscribe to at least one event",
+        ));
+    }
+    if let Some(unknown) = payload
+        .events
+        .iter()
+        .find(|event| !WEBHOOK_EVENTS.contains(&event.as_str()))
+    {
+        return Err(error(
+            StatusCode::BAD_REQUEST,
+            &format!(
+                "Unknown webhook event '{}', expected one of: {}",
+                unknown,
+                WEBHOOK_EVENTS.join(", ")
+            ),
+        ));
+    }
+    Ok(())
+}
+
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    Path(project_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ErrorResponse> {
+    let pool = project_pool(&state, project_id).await?;
+    match Webhook::find_by_project_id(&pool, project_id).await {
+        Ok(webhooks) => Ok(Json(ApiResponse::success(webhooks))),
+        Err(e) => Err(error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &format!("Failed to fetch webhooks: {}", e),
+        )),
+    }
+}
+
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    Path(project_id): Path<Uuid>,
+    Json(mut payload): Json<CreateWebhook>,
+) -> Result<impl IntoResponse, ErrorResponse> {
+    let pool = project_pool(&state, project_id).await?;
+    validate_webhook(&payload)?;
+    payload.url = payload.url.trim().to_string();
+    payload.events.sort();
+    payload.events.dedup();
+
+    match Webhook::create(&pool, project_id, &payload).await {
+        Ok(webhook) => Ok((StatusCode::CREATED, Json(ApiResponse::success(webhook)))),
+        Err(e) => Err(error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &format!("Failed to create webhook: {}", e),
+        )),
+    }
+}
+
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Path((project_id, webhook_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, ErrorResponse> {
+    let pool = project_pool(&state, project_id).await?;
+    match Webhook::delete(&pool, webhook_id, project_id).await {
+        Ok(0) => Err(error(StatusCode::NOT_FOUND, "Webhook not found")),
+        Ok(_) => Ok(Json(ApiResponse::success(()))),
+        Err(e) => Err(error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &format!("Failed to delete webhook: {}", e),
+        )),
+    }
+}
+
+pub async fn list_webhook_deliveries(
+    State(state): State<AppState>,
+    Path((project_id, webhook_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, ErrorResponse> {
+    let pool = project_pool(&state, project_id).await?;
+    let webhooks = Webhook::find_by_project_id(&pool, project_id)
+        .await
+        .map_err(|e| {
+            error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &format!("Failed to fetch webhooks: {}", e),
+            )
+        })?;
+    if !webhooks.iter().any(|webhook| webhook.id == webhook_id) {
+        return Err(error(StatusCode::NOT_FOUND, "Webhook not found"));
+    }
+
+    match WebhookDelivery::find_by_webhook_id(&pool, webhook_id).await {
+        Ok(deliveries) => Ok(Json(ApiResponse::success(deliveries))),
+        Err(e) => Err(error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &format!("Failed to fetch webhook deliveries: {}", e),
+        )),
+    }
+}
+
+pub fn webhooks_router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/projects/:project_id/webhooks",
+            get(list_webhooks).post(create_webhook),
+        )
+        .route(
+            "/projects/:project_id/webhooks/:webhook_id",
+            delete(delete_webhook),
+        )
+        .route(
+            "/projects/:project_id/webhooks/:webhook_id/deliveries",
+            get(list_webhook_deliveries),
+        )
+}
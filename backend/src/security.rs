@@ -0,0 +1,179 @@
+//! Checks on paths that executors are pointed at
+//!
+//! Worktree paths reach executors from API callers, so an executor could be started in any
+//! directory, e.g. `/etc`. [`ALLOWED_WORKTREE_BASE_DIRS_ENV`] restricts them to worktrees
+//! below a set of base directories.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use crate::executor::ExecutorError;
+
+/// Environment variable with the comma-separated absolute directories worktrees must be in,
+/// e.g. `$HOME/vibe-worktrees`. Unset, worktree paths are not checked; set without a single
+/// absolute directory, every worktree path is rejected.
+pub const ALLOWED_WORKTREE_BASE_DIRS_ENV: &str = "VIBE_ALLOWED_WORKTREE_BASE_DIRS";
+
+static ALLOWED_WORKTREE_BASES: OnceLock<Option<Vec<PathBuf>>> = OnceLock::new();
+
+/// Parse a comma-separated list of base directories; relative entries are skipped
+pub fn parse_allowed_bases(value: &str) -> Vec<PathBuf> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let path = PathBuf::from(entry);
+            if path.is_absolute() {
+                Some(path)
+            } else {
+                tracing::warn!(
+                    "Ignoring relative directory '{}' in {}",
+                    entry,
+                    ALLOWED_WORKTREE_BASE_DIRS_ENV
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+/// The base directories configured by `value`, a setting of
+/// [`ALLOWED_WORKTREE_BASE_DIRS_ENV`]; `None` when worktrees are not checked. A setting
+/// without a single usable directory yields no bases, so every worktree is rejected
+/// rather than the check being switched off by a typo.
+fn configured_bases(value: Option<&str>) -> Option<Vec<PathBuf>> {
+    let value = value.filter(|value| !value.trim().is_empty());
+    let Some(value) = value else {
+        tracing::warn!(
+            "{} is not set; executors may be started in any directory",
+            ALLOWED_WORKTREE_BASE_DIRS_ENV
+        );
+        return None;
+    };
+
+    let bases = parse_allowed_bases(value);
+    if bases.is_empty() {
+        tracing::error!(
+            "{} holds no absolute directory; every worktree will be rejected",
+            ALLOWED_WORKTREE_BASE_DIRS_ENV
+        );
+    }
+    Some(bases)
+}
+
+/// The base directories from [`ALLOWED_WORKTREE_BASE_DIRS_ENV`], read once; call at startup
+/// so a missing or unusable setting is reported right away. `None` when worktrees are not
+/// checked.
+pub fn allowed_worktree_bases() -> Option<&'static [PathBuf]> {
+    ALLOWED_WORKTREE_BASES
+        .get_or_init(|| {
+            configured_bases(
+                std::env::var(ALLOWED_WORKTREE_BASE_DIRS_ENV)
+                    .ok()
+                    .as_deref(),
+            )
+        })
+        .as_deref()
+}
+
+/// Check that the worktree, with symlinks and `..` resolved, lies within one of
+/// `allowed_bases`
+pub fn validate_worktree_path(
+    worktree_path: &str,
+    allowed_bases: &[PathBuf],
+) -> Result<(), ExecutorError> {
+    let path = Path::new(worktree_path).canonicalize().map_err(|_| {
+        ExecutorError::WorktreeNotAccessible {
+            path: worktree_path.to_string(),
+        }
+    })?;
+    let allowed = allowed_bases.iter().any(|base| {
+        // Bases may be symlinks themselves, like the temporary directory on macOS
+        let base = base.canonicalize().unwrap_or_else(|_| base.clone());
+        path.starts_with(base)
+    });
+    if allowed {
+        Ok(())
+    } else {
+        Err(ExecutorError::WorktreeNotAllowed {
+            path: worktree_path.to_string(),
+        })
+    }
+}
+
+/// [`validate_worktree_path`] against the configured bases; every path passes when
+/// [`ALLOWED_WORKTREE_BASE_DIRS_ENV`] is not set
+pub fn check_worktree_path(worktree_path: &str) -> Result<(), ExecutorError> {
+    match allowed_worktree_bases() {
+        Some(bases) => validate_worktree_path(worktree_path, bases),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_worktree_inside_allowed_base_is_accepted() {
+        let base = tempfile::tempdir().unwrap();
+        let worktree = base.path().join("task-1");
+        std::fs::create_dir(&worktree).unwrap();
+
+        let allowed = vec![base.path().to_path_buf()];
+        assert!(validate_worktree_path(worktree.to_str().unwrap(), &allowed).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_worktree_outside_allowed_bases_is_rejected() {
+        let base = tempfile::tempdir().unwrap();
+        let allowed = vec![base.path().to_path_buf()];
+
+        for path in [
+            "/etc".to_string(),
+            format!("{}/../../etc", base.path().display()),
+        ] {
+            let error = validate_worktree_path(&path, &allowed).unwrap_err();
+            assert_eq!(error.error_code(), "worktree_not_allowed", "{}", path);
+        }
+        let missing = base.path().join("missing");
+        assert_eq!(
+            validate_worktree_path(missing.to_str().unwrap(), &allowed)
+                .unwrap_err()
+                .error_code(),
+            "worktree_not_accessible"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parse_allowed_bases_skips_relative_entries() {
+        assert_eq!(
+            parse_allowed_bases("/srv/worktrees, relative/dir,,/home/dev/vibe-worktrees"),
+            vec![
+                PathBuf::from("/srv/worktrees"),
+                PathBuf::from("/home/dev/vibe-worktrees")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_setting_without_usable_bases_rejects_every_worktree() {
+        assert_eq!(configured_bases(None), None);
+        assert_eq!(configured_bases(Some(" ")), None);
+
+        let bases = configured_bases(Some("relative/dir, ,other")).unwrap();
+        assert!(bases.is_empty());
+        let worktree = tempfile::tempdir().unwrap();
+        assert_eq!(
+            validate_worktree_path(worktree.path().to_str().unwrap(), &bases)
+                .unwrap_err()
+                .error_code(),
+            "worktree_not_allowed"
+        );
+    }
+}
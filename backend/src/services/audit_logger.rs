@@ -0,0 +1,190 @@
+use serde::Serialize;
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use uuid::Uuid;
+
+use crate::models::audit_log::AuditLogEntry;
+
+pub const TASK_CREATED: &str = "task.created";
+pub const TASK_UPDATED: &str = "task.updated";
+pub const TASK_STATUS_CHANGED: &str = "task.status_changed";
+pub const TASK_ARCHIVED: &str = "task.archived";
+pub const TASK_RESTORED: &str = "task.restored";
+pub const TASK_DEPENDENCY_ADDED: &str = "task.dependency_added";
+pub const TASK_DEPENDENCY_REMOVED: &str = "task.dependency_removed";
+pub const TASK_ENV_VAR_SET: &str = "task.env_var_set";
+pub const TASK_ENV_VAR_DELETED: &str = "task.env_var_deleted";
+pub const PROJECT_CREATED: &str = "project.created";
+pub const PROJECT_UPDATED: &str = "project.updated";
+pub const PROJECT_DELETED: &str = "project.deleted";
+pub const PROJECT_SESSION_RESET: &str = "project.session_reset";
+pub const PROJECT_EXECUTOR_CONFIG_UPDATED: &str = "project.executor_config_updated";
+pub const PROJECT_PROMPTS_UPDATED: &str = "project.prompts_updated";
+pub const PROJECT_MCP_CONFIG_UPDATED: &str = "project.mcp_config_updated";
+pub const PROJECT_BUDGET_UPDATED: &str = "project.budget_updated";
+pub const RUN_STARTED: &str = "run.started";
+
+/// Largest number of entries returned for one entity
+pub const MAX_AUDIT_LOG_LIMIT: i64 = 500;
+
+/// Records who changed which task or project, and how, in the audit log
+#[derive(Debug, Clone)]
+pub struct AuditLogger {
+    pool: SqlitePool,
+}
+
+impl AuditLogger {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Start the transaction that holds a mutation together with its audit entry
+    pub async fn begin(&self) -> Result<Transaction<'static, Sqlite>, sqlx::Error> {
+        self.pool.begin().await
+    }
+
+    /// Record a change within `tx`, so the entry is only kept if the mutation commits. The
+    /// entity type is the part of `action` before the dot; `before` is `None` for creations
+    /// and `after` for deletions.
+    pub async fn log_change<T: Serialize>(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        action: &str,
+        entity_id: Uuid,
+        actor_id: &str,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) -> Result<(), sqlx::Error> {
+        let entity_type = action.split('.').next().unwrap_or(action);
+        let to_json = |value: Option<&T>| {
+            value
+                .map(serde_json::to_string)
+                .transpose()
+                .map_err(|e| sqlx::Error::Encode(Box::new(e)))
+        };
+
+        AuditLogEntry::create(
+            tx,
+            entity_type,
+            entity_id,
+            action,
+            actor_id,
+            to_json(before)?,
+            to_json(after)?,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Find the changes made to an entity, newest first
+    pub async fn entries(
+        &self,
+        entity_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<AuditLogEntry>, sqlx::Error> {
+        AuditLogEntry::find_by_entity_id(&self.pool, entity_id, limit.clamp(1, MAX_AUDIT_LOG_LIMIT))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{
+        models::task::{CreateTask, Task, TaskStatus},
+        test_fixtures::{create_project, memory_pool},
+    };
+
+    async fn setup() -> (SqlitePool, Task) {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let data = CreateTask {
+            project_id,
+            title: "Write docs".to_string(),
+            description: None,
+            parent_task_attempt: None,
+            labels: None,
+            timeout_seconds: None,
+            executor_model: None,
+            priority: None,
+            parent_task_id: None,
+        };
+        let task = Task::create(&pool, &data, Uuid::new_v4()).await.unwrap();
+        (pool, task)
+    }
+
+    async fn set_status(task: &Task, status: TaskStatus, tx: &mut Transaction<'_, Sqlite>) -> Task {
+        Task::update(
+            &mut **tx,
+            task.id,
+            task.project_id,
+            task.title.clone(),
+            task.description.clone(),
+            status,
+            task.parent_task_attempt,
+            task.timeout_seconds,
+            task.executor_model.clone(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_status_change_records_before_and_after() {
+        let (pool, task) = setup().await;
+        let audit = AuditLogger::new(pool.clone());
+
+        let mut tx = audit.begin().await.unwrap();
+        let updated = set_status(&task, TaskStatus::InProgress, &mut tx).await;
+        audit
+            .log_change(
+                &mut tx,
+                TASK_STATUS_CHANGED,
+                task.id,
+                "user-1",
+                Some(&task),
+                Some(&updated),
+            )
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let entries = audit.entries(task.id, 50).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.entity_type, "task");
+        assert_eq!(entry.action, TASK_STATUS_CHANGED);
+        assert_eq!(entry.actor_id, "user-1");
+        let before: Value = serde_json::from_str(entry.before_json.as_deref().unwrap()).unwrap();
+        let after: Value = serde_json::from_str(entry.after_json.as_deref().unwrap()).unwrap();
+        assert_eq!(before["status"], "todo");
+        assert_eq!(after["status"], "inprogress");
+        assert_eq!(before["id"], after["id"]);
+    }
+
+    #[tokio::test]
+    async fn test_rolled_back_change_is_not_logged() {
+        let (pool, task) = setup().await;
+        let audit = AuditLogger::new(pool.clone());
+
+        let mut tx = audit.begin().await.unwrap();
+        let updated = set_status(&task, TaskStatus::Done, &mut tx).await;
+        audit
+            .log_change(
+                &mut tx,
+                TASK_STATUS_CHANGED,
+                task.id,
+                "user-1",
+                Some(&task),
+                Some(&updated),
+            )
+            .await
+            .unwrap();
+        tx.rollback().await.unwrap();
+
+        assert!(audit.entries(task.id, 50).await.unwrap().is_empty());
+        let task = Task::find_by_id(&pool, task.id).await.unwrap().unwrap();
+        assert_eq!(task.status, TaskStatus::Todo);
+    }
+}
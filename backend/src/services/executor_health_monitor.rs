@@ -0,0 +1,67 @@
+use std::time::{Duration, Instant};
+
+use sqlx::SqlitePool;
+use tokio::time::interval;
+use tracing::{error, info, warn};
+
+use crate::{executor::ExecutorConfig, models::executor_health::HealthCheckResult};
+
+/// Service that periodically probes every coding agent executor and records the results
+pub struct ExecutorHealthMonitor {
+    pool: SqlitePool,
+    poll_interval: Duration,
+}
+
+impl ExecutorHealthMonitor {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool,
+            poll_interval: Duration::from_secs(5 * 60), // Check every 5 minutes
+        }
+    }
+
+    /// Start probing executors until the process exits
+    pub async fn start(&self) {
+        info!(
+            "Starting executor health monitor with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            self.check_all_executors().await;
+        }
+    }
+
+    /// Run one health probe for every executor and store the results
+    async fn check_all_executors(&self) {
+        for config in ExecutorConfig::coding_agents() {
+            let executor_name = config.to_string();
+            let executor = config.create_executor();
+
+            let started = Instant::now();
+            let outcome = executor.health_check().await;
+            let latency_ms = started.elapsed().as_millis() as i64;
+
+            if let Err(ref e) = outcome {
+                warn!("Executor {} failed its health check: {}", executor_name, e);
+            }
+
+            if let Err(e) = HealthCheckResult::record(
+                &self.pool,
+                &executor_name,
+                outcome.err().as_deref(),
+                latency_ms,
+            )
+            .await
+            {
+                error!(
+                    "Failed to record health check for executor {}: {}",
+                    executor_name, e
+                );
+            }
+        }
+    }
+}
@@ -1,4 +1,9 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
 
 use git2::{
     BranchType, Cred, DiffOptions, Error as GitError, FetchOptions, RebaseOptions, RemoteCallbacks,
@@ -6,10 +11,11 @@ use git2::{
 };
 use regex;
 use tracing::{debug, info};
+use uuid::Uuid;
 
 use crate::{
+    diff_parser,
     models::task_attempt::{DiffChunk, DiffChunkType, FileDiff, WorktreeDiff},
-    utils::worktree_manager::WorktreeManager,
 };
 
 #[derive(Debug)]
@@ -22,6 +28,8 @@ pub enum GitServiceError {
     MergeConflicts(String),
     InvalidPath(String),
     WorktreeDirty(String),
+    /// A `git` command exited with an error
+    CommandFailed(String),
 }
 
 impl std::fmt::Display for GitServiceError {
@@ -37,6 +45,7 @@ impl std::fmt::Display for GitServiceError {
             GitServiceError::WorktreeDirty(e) => {
                 write!(f, "Worktree has uncommitted changes: {}", e)
             }
+            GitServiceError::CommandFailed(e) => write!(f, "Git command failed: {}", e),
         }
     }
 }
@@ -55,6 +64,126 @@ impl From<std::io::Error> for GitServiceError {
     }
 }
 
+/// How long a repository validation result is reused before `git` is asked again
+const REPO_VALIDATION_TTL: Duration = Duration::from_secs(60);
+
+/// Cache of recent repository validation results, keyed by path
+static REPO_VALIDATION_CACHE: OnceLock<Mutex<HashMap<PathBuf, (bool, Instant)>>> = OnceLock::new();
+
+/// Check whether `path` is inside a git repository using `git rev-parse --git-dir`.
+///
+/// Results are cached per path for 60 seconds.
+pub async fn is_valid_repo(path: &Path) -> bool {
+    let cache = REPO_VALIDATION_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some((valid, checked_at)) = cache.lock().unwrap().get(path) {
+        if checked_at.elapsed() < REPO_VALIDATION_TTL {
+            return *valid;
+        }
+    }
+
+    let valid = tokio::process::Command::new("git")
+        .arg("rev-parse")
+        .arg("--git-dir")
+        .current_dir(path)
+        .output()
+        .await
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+
+    cache
+        .lock()
+        .unwrap()
+        .insert(path.to_path_buf(), (valid, Instant::now()));
+    valid
+}
+
+/// Commit message used by `use_commit_template` projects without a `git_commit_template`
+pub const DEFAULT_COMMIT_TEMPLATE: &str = "vibe-kanban: {title} [task:{task_id}]";
+
+/// Fill the `{title}` and `{task_id}` placeholders of a commit message template
+pub fn render_commit_message(template: &str, title: &str, task_id: Uuid) -> String {
+    template
+        .replace("{task_id}", &task_id.to_string())
+        .replace("{title}", title)
+}
+
+/// Run git in `worktree_path`, failing with its error output when it exits with another code
+/// than one of `ok_codes`
+async fn run_git(
+    worktree_path: &Path,
+    args: &[&str],
+    ok_codes: &[i32],
+) -> Result<std::process::Output, GitServiceError> {
+    let output = tokio::process::Command::new("git")
+        .args(args)
+        .current_dir(worktree_path)
+        .output()
+        .await?;
+    if !output
+        .status
+        .code()
+        .is_some_and(|code| ok_codes.contains(&code))
+    {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let detail = if stderr.trim().is_empty() {
+            stdout.trim()
+        } else {
+            stderr.trim()
+        };
+        return Err(GitServiceError::CommandFailed(format!(
+            "git {} failed: {}",
+            args[0], detail
+        )));
+    }
+    Ok(output)
+}
+
+/// Stage every change in `worktree_path` and commit it, like `git add -A && git commit -m`.
+/// Returns whether there was anything to commit.
+///
+/// Fails when git is missing or exits with an error.
+pub async fn commit_all(worktree_path: &Path, message: &str) -> Result<bool, GitServiceError> {
+    run_git(worktree_path, &["add", "-A"], &[0]).await?;
+    // Exits 1 when something is staged
+    let staged = run_git(worktree_path, &["diff", "--cached", "--quiet"], &[0, 1]).await?;
+    if staged.status.success() {
+        return Ok(false);
+    }
+    run_git(worktree_path, &["commit", "-m", message], &[0]).await?;
+    Ok(true)
+}
+
+/// Changes of the latest run in `worktree_path`, i.e. of the commit every run ends with
+/// (`HEAD~1..HEAD`). Worktrees without a commit to compare against have no changes.
+pub async fn latest_run_diff(
+    worktree_path: &Path,
+) -> Result<Vec<diff_parser::FileDiff>, GitServiceError> {
+    let has_base = tokio::process::Command::new("git")
+        .args(["rev-parse", "--verify", "--quiet", "HEAD~1"])
+        .current_dir(worktree_path)
+        .output()
+        .await?
+        .status
+        .success();
+    if !has_base {
+        return Ok(Vec::new());
+    }
+
+    let output = tokio::process::Command::new("git")
+        .args(["diff", "HEAD~1..HEAD"])
+        .current_dir(worktree_path)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(GitServiceError::CommandFailed(format!(
+            "git diff failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(diff_parser::parse(&String::from_utf8_lossy(&output.stdout)))
+}
+
 /// Service for managing Git operations in task execution workflows
 pub struct GitService {
     repo_path: PathBuf,
@@ -106,6 +235,16 @@ impl GitService {
 
         // Choose base reference
         let base_reference = if let Some(base_branch) = base_branch {
+            // A brand new repository has no commits, so give it the initial commit on main
+            // before looking up the base branch
+            if matches!(repo.head(), Err(e) if e.code() == git2::ErrorCode::UnbornBranch) {
+                self.create_initial_commit(&repo)?;
+            }
+
+            if !self.branch_exists(base_branch) {
+                return Err(GitServiceError::BranchNotFound(base_branch.to_string()));
+            }
+
             let branch = repo
                 .find_branch(base_branch, BranchType::Local)
                 .map_err(|_| GitServiceError::BranchNotFound(base_branch.to_string()))?;
@@ -145,6 +284,19 @@ impl GitService {
         Ok(())
     }
 
+    /// Check whether `branch` resolves in the repository using `git rev-parse --verify`
+    pub fn branch_exists(&self, branch: &str) -> bool {
+        std::process::Command::new("git")
+            .arg("rev-parse")
+            .arg("--verify")
+            .arg("--quiet")
+            .arg(format!("{}^{{commit}}", branch))
+            .current_dir(&self.repo_path)
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+
     /// Create an initial commit for empty repositories
     fn create_initial_commit(&self, repo: &Repository) -> Result<(), GitServiceError> {
         let signature = repo.signature().unwrap_or_else(|_| {
@@ -941,85 +1093,6 @@ impl GitService {
         result
     }
 
-    /// Recreate a worktree from an existing branch (for cold task support)
-    pub async fn recreate_worktree_from_branch(
-        &self,
-        branch_name: &str,
-        stored_worktree_path: &Path,
-    ) -> Result<PathBuf, GitServiceError> {
-        let repo = self.open_repo()?;
-
-        // Verify branch exists before proceeding
-        let _branch = repo
-            .find_branch(branch_name, BranchType::Local)
-            .map_err(|_| GitServiceError::BranchNotFound(branch_name.to_string()))?;
-        drop(_branch);
-
-        let stored_worktree_path_str = stored_worktree_path.to_string_lossy().to_string();
-
-        info!(
-            "Recreating worktree using stored path: {} (branch: {})",
-            stored_worktree_path_str, branch_name
-        );
-
-        // Clean up existing directory if it exists to avoid git sync issues
-        if stored_worktree_path.exists() {
-            debug!(
-                "Removing existing directory before worktree recreation: {}",
-                stored_worktree_path_str
-            );
-            std::fs::remove_dir_all(stored_worktree_path).map_err(|e| {
-                GitServiceError::IoError(std::io::Error::other(format!(
-                    "Failed to remove existing worktree directory {}: {}",
-                    stored_worktree_path_str, e
-                )))
-            })?;
-        }
-
-        // Ensure parent directory exists - critical for session continuity
-        if let Some(parent) = stored_worktree_path.parent() {
-            std::fs::create_dir_all(parent).map_err(|e| {
-                GitServiceError::IoError(std::io::Error::other(format!(
-                    "Failed to create parent directory for worktree path {}: {}",
-                    stored_worktree_path_str, e
-                )))
-            })?;
-        }
-
-        // Extract repository path for WorktreeManager
-        let repo_path = repo
-            .workdir()
-            .ok_or_else(|| {
-                GitServiceError::InvalidRepository(
-                    "Repository has no working directory".to_string(),
-                )
-            })?
-            .to_str()
-            .ok_or_else(|| {
-                GitServiceError::InvalidRepository("Repository path is not valid UTF-8".to_string())
-            })?
-            .to_string();
-
-        WorktreeManager::ensure_worktree_exists(
-            repo_path,
-            branch_name.to_string(),
-            stored_worktree_path.to_path_buf(),
-        )
-        .await
-        .map_err(|e| {
-            GitServiceError::IoError(std::io::Error::other(format!(
-                "WorktreeManager error: {}",
-                e
-            )))
-        })?;
-
-        info!(
-            "Successfully recreated worktree at original path: {} -> {}",
-            branch_name, stored_worktree_path_str
-        );
-        Ok(stored_worktree_path.to_path_buf())
-    }
-
     /// Extract GitHub owner and repo name from git repo path
     pub fn get_github_repo_info(&self) -> Result<(String, String), GitServiceError> {
         let repo = self.open_repo()?;
@@ -1180,4 +1253,80 @@ mod tests {
         let branch_name = git_service.get_default_branch_name().unwrap();
         assert_eq!(branch_name, "main");
     }
+
+    #[tokio::test]
+    async fn test_commit_all_commits_with_rendered_message() {
+        let (temp_dir, repo) = create_test_repo();
+        std::fs::write(temp_dir.path().join("notes.txt"), "hello").unwrap();
+        let task_id = Uuid::new_v4();
+        let message = render_commit_message(DEFAULT_COMMIT_TEMPLATE, "Add notes", task_id);
+
+        commit_all(temp_dir.path(), &message).await.unwrap();
+
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(
+            head.message().unwrap().trim_end(),
+            format!("vibe-kanban: Add notes [task:{}]", task_id)
+        );
+        assert!(head.tree().unwrap().get_name("notes.txt").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_commit_all_skips_when_nothing_changed() {
+        let (temp_dir, repo) = create_test_repo();
+        std::fs::write(temp_dir.path().join("notes.txt"), "hello").unwrap();
+        assert!(commit_all(temp_dir.path(), "first").await.unwrap());
+
+        assert!(!commit_all(temp_dir.path(), "empty").await.unwrap());
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.message().unwrap().trim_end(), "first");
+    }
+
+    #[tokio::test]
+    async fn test_latest_run_diff_without_history_is_empty() {
+        let (temp_dir, _repo) = create_test_repo();
+        std::fs::write(temp_dir.path().join("notes.txt"), "hello").unwrap();
+
+        commit_all(temp_dir.path(), "initial").await.unwrap();
+
+        assert!(latest_run_diff(temp_dir.path()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_latest_run_diff_of_run_commit() {
+        let (temp_dir, _repo) = create_test_repo();
+        std::fs::write(temp_dir.path().join("notes.txt"), "hello\n").unwrap();
+        commit_all(temp_dir.path(), "initial").await.unwrap();
+        std::fs::write(temp_dir.path().join("notes.txt"), "hello\nworld\n").unwrap();
+        commit_all(temp_dir.path(), "run").await.unwrap();
+
+        let files = latest_run_diff(temp_dir.path()).await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "notes.txt");
+        assert_eq!(
+            files[0].hunks[0].lines.last().unwrap(),
+            &diff_parser::DiffLine {
+                kind: diff_parser::DiffLineKind::Added,
+                content: "world".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_render_commit_message_fills_placeholders() {
+        let task_id = Uuid::nil();
+        assert_eq!(
+            render_commit_message("{title} ({task_id}) {title}", "Fix", task_id),
+            format!("Fix ({}) Fix", task_id)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_is_valid_repo() {
+        let (repo_dir, _repo) = create_test_repo();
+        assert!(is_valid_repo(repo_dir.path()).await);
+
+        let plain_dir = TempDir::new().unwrap();
+        assert!(!is_valid_repo(plain_dir.path()).await);
+    }
 }
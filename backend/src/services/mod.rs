@@ -1,13 +1,22 @@
 pub mod analytics;
+pub mod audit_logger;
+pub mod executor_health_monitor;
 pub mod git_service;
 pub mod github_service;
 pub mod notification_service;
 pub mod pr_monitor;
 pub mod process_service;
+pub mod system_health;
+pub mod webhook_service;
+pub mod worktree_cleanup;
 
 pub use analytics::{generate_user_id, AnalyticsConfig, AnalyticsService};
+pub use audit_logger::AuditLogger;
+pub use executor_health_monitor::ExecutorHealthMonitor;
 pub use git_service::{GitService, GitServiceError};
 pub use github_service::{CreatePrRequest, GitHubRepoInfo, GitHubService, GitHubServiceError};
 pub use notification_service::{NotificationConfig, NotificationService};
 pub use pr_monitor::PrMonitorService;
 pub use process_service::ProcessService;
+pub use webhook_service::WebhookService;
+pub use worktree_cleanup::WorktreeCleanup;
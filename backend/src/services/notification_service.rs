@@ -1,6 +1,9 @@
 use std::sync::OnceLock;
 
-use crate::models::config::SoundFile;
+use dashmap::DashMap;
+use tokio::sync::broadcast;
+
+use crate::models::{config::SoundFile, notification::Notification};
 
 /// Service for handling cross-platform notifications including sound alerts and push notifications
 #[derive(Debug, Clone)]
@@ -28,6 +31,17 @@ impl Default for NotificationConfig {
 /// Cache for WSL root path from PowerShell
 static WSL_ROOT_PATH_CACHE: OnceLock<Option<String>> = OnceLock::new();
 
+/// Number of in-app notifications a slow WebSocket client may fall behind by
+const IN_APP_CHANNEL_CAPACITY: usize = 64;
+
+/// Channels of users with at least one connected client, keyed by user ID
+static IN_APP_SUBSCRIBERS: OnceLock<DashMap<String, broadcast::Sender<Notification>>> =
+    OnceLock::new();
+
+fn in_app_subscribers() -> &'static DashMap<String, broadcast::Sender<Notification>> {
+    IN_APP_SUBSCRIBERS.get_or_init(DashMap::new)
+}
+
 impl NotificationService {
     /// Create a new NotificationService with the given configuration
     pub fn new(config: NotificationConfig) -> Self {
@@ -37,6 +51,28 @@ impl NotificationService {
         }
     }
 
+    /// Receive the in-app notifications sent to `user_id` from now on
+    pub fn subscribe(user_id: &str) -> broadcast::Receiver<Notification> {
+        in_app_subscribers()
+            .entry(user_id.to_string())
+            .or_insert_with(|| broadcast::channel(IN_APP_CHANNEL_CAPACITY).0)
+            .subscribe()
+    }
+
+    /// Push an in-app notification to every connected client of `user_id`
+    pub fn send(user_id: &str, notification: Notification) {
+        let subscribers = in_app_subscribers();
+        let delivered = subscribers
+            .get(user_id)
+            .map(|sender| sender.send(notification).is_ok())
+            .unwrap_or(false);
+
+        if !delivered {
+            // Nobody is listening any more; drop the channel until a client reconnects
+            subscribers.remove_if(user_id, |_, sender| sender.receiver_count() == 0);
+        }
+    }
+
     /// Send both sound and push notifications if enabled
     pub async fn notify(&self, title: &str, message: &str, sound_file: &SoundFile) {
         if self.sound_enabled {
@@ -6,6 +6,7 @@ use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 use crate::{
+    app_state::AppState,
     models::{
         config::Config,
         task::{Task, TaskStatus},
@@ -16,7 +17,7 @@ use crate::{
 
 /// Service to monitor GitHub PRs and update task status when they are merged
 pub struct PrMonitorService {
-    pool: SqlitePool,
+    app_state: AppState,
     poll_interval: Duration,
 }
 
@@ -32,9 +33,9 @@ pub struct PrInfo {
 }
 
 impl PrMonitorService {
-    pub fn new(pool: SqlitePool) -> Self {
+    pub fn new(app_state: AppState) -> Self {
         Self {
-            pool,
+            app_state,
             poll_interval: Duration::from_secs(60), // Check every minute
         }
     }
@@ -74,12 +75,25 @@ impl PrMonitorService {
         }
     }
 
-    /// Check all open PRs for updates with the provided GitHub token
+    /// Check all open PRs of every task database for updates with the provided GitHub token
     async fn check_all_open_prs_with_token(
         &self,
         github_token: &str,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let open_prs = self.get_open_prs_with_token(github_token).await?;
+        for pool in self.app_state.task_pools().await? {
+            self.check_open_prs_with_token(&pool, github_token).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Check the open PRs of one task database for updates
+    async fn check_open_prs_with_token(
+        &self,
+        pool: &SqlitePool,
+        github_token: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let open_prs = self.get_open_prs_with_token(pool, github_token).await?;
 
         if open_prs.is_empty() {
             debug!("No open PRs to check");
@@ -89,7 +103,7 @@ impl PrMonitorService {
         info!("Checking {} open PRs", open_prs.len());
 
         for pr_info in open_prs {
-            if let Err(e) = self.check_pr_status(&pr_info).await {
+            if let Err(e) = self.check_pr_status(pool, &pr_info).await {
                 error!(
                     "Error checking PR #{} for attempt {}: {}",
                     pr_info.pr_number, pr_info.attempt_id, e
@@ -103,6 +117,7 @@ impl PrMonitorService {
     /// Get all task attempts with open PRs using the provided GitHub token
     async fn get_open_prs_with_token(
         &self,
+        pool: &SqlitePool,
         github_token: &str,
     ) -> Result<Vec<PrInfo>, sqlx::Error> {
         let rows = sqlx::query!(
@@ -118,7 +133,7 @@ impl PrMonitorService {
                JOIN projects p ON t.project_id = p.id
                WHERE ta.pr_status = 'open' AND ta.pr_number IS NOT NULL"#
         )
-        .fetch_all(&self.pool)
+        .fetch_all(pool)
         .await?;
 
         let mut pr_infos = Vec::new();
@@ -160,6 +175,7 @@ impl PrMonitorService {
     /// Check the status of a specific PR
     async fn check_pr_status(
         &self,
+        pool: &SqlitePool,
         pr_info: &PrInfo,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let github_service = GitHubService::new(&pr_info.github_token)?;
@@ -184,7 +200,7 @@ impl PrMonitorService {
             let merge_commit_sha = pr_status.merge_commit_sha.as_deref();
 
             TaskAttempt::update_pr_status(
-                &self.pool,
+                pool,
                 pr_info.attempt_id,
                 &pr_status.status,
                 pr_status.merged_at,
@@ -192,20 +208,22 @@ impl PrMonitorService {
             )
             .await?;
 
-            // If the PR was merged, update the task status to done
-            if pr_status.merged {
+            // If the PR was merged, update the task status to done, unless it still has
+            // sub-tasks to finish
+            if pr_status.merged && Task::incomplete_subtask_count(pool, pr_info.task_id).await? > 0
+            {
+                info!(
+                    "PR #{} was merged, leaving task {} open until its sub-tasks are done",
+                    pr_info.pr_number, pr_info.task_id
+                );
+            } else if pr_status.merged {
                 info!(
                     "PR #{} was merged, updating task {} to done",
                     pr_info.pr_number, pr_info.task_id
                 );
 
-                Task::update_status(
-                    &self.pool,
-                    pr_info.task_id,
-                    pr_info.project_id,
-                    TaskStatus::Done,
-                )
-                .await?;
+                Task::update_status(pool, pr_info.task_id, pr_info.project_id, TaskStatus::Done)
+                    .await?;
             }
         }
 
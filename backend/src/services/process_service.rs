@@ -1,19 +1,33 @@
+use std::time::Duration;
+
+use futures_util::future::join_all;
 use sqlx::SqlitePool;
-use tracing::{debug, info};
+use tracing::{debug, info, Instrument};
 use uuid::Uuid;
 
 use crate::{
-    executor::Executor,
+    executor::{register_run_progress, Executor},
+    mcp::client_config,
     models::{
-        execution_process::{CreateExecutionProcess, ExecutionProcess, ExecutionProcessType},
+        execution_process::{
+            CreateExecutionProcess, ExecutionProcess, ExecutionProcessStatus, ExecutionProcessType,
+        },
         executor_session::{CreateExecutorSession, ExecutorSession},
         project::Project,
+        project_executor_config::ProjectExecutorConfig,
         task::Task,
-        task_attempt::{TaskAttempt, TaskAttemptError},
+        task_attempt::{validate_session_id, FollowupRequest, TaskAttempt, TaskAttemptError},
+        task_run::{CancelledRuns, RunningTaskRun, TaskRun},
     },
+    prompt_builder::{build_condensed_context, exceeds_context_turns},
+    services::{audit_logger::RUN_STARTED, AuditLogger},
     utils::shell::get_shell_command,
+    web_policy::WebPolicy,
 };
 
+/// How long cancelled runs get to exit after SIGTERM before they are sent SIGKILL
+const CANCEL_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
 /// Service responsible for managing process execution lifecycle
 pub struct ProcessService;
 
@@ -61,13 +75,10 @@ impl ProcessService {
                     Self::start_coding_agent(pool, app_state, attempt_id, task_id, project_id).await
                 }
                 "followup" => {
-                    let prompt = operation_params
-                        .as_ref()
-                        .and_then(|p| p.get("prompt"))
-                        .and_then(|p| p.as_str())
-                        .unwrap_or("");
+                    let request =
+                        FollowupRequest::from_operation_params(operation_params.as_ref())?;
                     Self::start_followup_execution_direct(
-                        pool, app_state, attempt_id, task_id, project_id, prompt,
+                        pool, app_state, attempt_id, task_id, project_id, request,
                     )
                     .await
                     .map(|_| ())
@@ -135,16 +146,19 @@ impl ProcessService {
             attempt_id,
             process_id,
             &task_attempt.worktree_path,
+            app_state.run_output_channels(),
         )
         .await?;
 
         // Register for monitoring
         Self::register_for_monitoring(
+            pool,
             app_state,
             process_id,
             attempt_id,
             &ExecutionProcessType::SetupScript,
             child,
+            None,
         )
         .await;
 
@@ -195,20 +209,38 @@ impl ProcessService {
         app_state: &crate::app_state::AppState,
         attempt_id: Uuid,
         task_id: Uuid,
-        _project_id: Uuid,
+        project_id: Uuid,
     ) -> Result<(), TaskAttemptError> {
         let task_attempt = TaskAttempt::find_by_id(pool, attempt_id)
             .await?
             .ok_or(TaskAttemptError::TaskNotFound)?;
 
         let executor_config = Self::resolve_executor_config(&task_attempt.executor);
+        let executor_type =
+            match Self::pinned_session_for_task(pool, task_id, project_id, &executor_config).await?
+            {
+                Some((session_id, prompt)) => {
+                    info!(
+                        "Continuing pinned session {} of project {} for task {}",
+                        session_id, project_id, task_id
+                    );
+                    crate::executor::ExecutorType::FollowUpCodingAgent {
+                        config: executor_config,
+                        session_id: Some(session_id),
+                        prompt,
+                        context_files: Vec::new(),
+                        tool_results: Vec::new(),
+                    }
+                }
+                None => crate::executor::ExecutorType::CodingAgent(executor_config),
+            };
 
         Self::start_process_execution(
             pool,
             app_state,
             attempt_id,
             task_id,
-            crate::executor::ExecutorType::CodingAgent(executor_config),
+            executor_type,
             "Starting executor".to_string(),
             ExecutionProcessType::CodingAgent,
             &task_attempt.worktree_path,
@@ -306,7 +338,7 @@ impl ProcessService {
         attempt_id: Uuid,
         task_id: Uuid,
         project_id: Uuid,
-        prompt: &str,
+        request: &FollowupRequest,
     ) -> Result<Uuid, TaskAttemptError> {
         use crate::models::task::{Task, TaskStatus};
 
@@ -334,14 +366,18 @@ impl ProcessService {
 
         // Ensure worktree exists (recreate if needed for cold task support)
         // This will resurrect the worktree at the exact same path for session continuity
-        let _worktree_path =
+        let worktree_path =
             TaskAttempt::ensure_worktree_exists(pool, actual_attempt_id, project_id, "followup")
                 .await?;
 
+        // Reject bad context files now rather than after the setup script has run
+        crate::executors::claude::validate_context_files(&request.context_files, &worktree_path)
+            .await?;
+
         // Use automatic setup logic with followup parameters
-        let operation_params = serde_json::json!({
-            "prompt": prompt
-        });
+        let operation_params = serde_json::to_value(request).map_err(|e| {
+            TaskAttemptError::ValidationError(format!("Invalid follow-up parameters: {}", e))
+        })?;
 
         Self::auto_setup_and_execute(
             pool,
@@ -364,7 +400,7 @@ impl ProcessService {
         attempt_id: Uuid,
         task_id: Uuid,
         project_id: Uuid,
-        prompt: &str,
+        request: FollowupRequest,
     ) -> Result<Uuid, TaskAttemptError> {
         // Ensure worktree exists (recreate if needed for cold task support)
         // This will resurrect the worktree at the exact same path for session continuity
@@ -427,8 +463,63 @@ impl ProcessService {
             }
         };
 
+        // Claude runs whose session never reached the executor session record can still be
+        // resumed from the session stored on the task
+        let session_id = match &executor_session.session_id {
+            Some(session_id) => Some(session_id.clone()),
+            None if matches!(
+                executor_config,
+                crate::executor::ExecutorConfig::Claude
+                    | crate::executor::ExecutorConfig::ClaudePlan
+                    | crate::executor::ExecutorConfig::ClaudePlanThenExecute
+            ) =>
+            {
+                Task::find_by_id(pool, task_id)
+                    .await?
+                    .and_then(|task| task.session_id)
+            }
+            None => None,
+        };
+
+        // Sessions whose history outgrew the project's turn limit are not resumed; a fresh
+        // session picks up from a summary of the previous run instead
+        if session_id.is_some() {
+            if let Some(prompt_suffix) = Self::condensed_followup_context(
+                pool,
+                project_id,
+                most_recent_coding_agent,
+                &executor_config,
+                &request.prompt,
+            )
+            .await?
+            {
+                info!(
+                    "Conversation of attempt {} exceeds the project's context turn limit, starting a fresh session",
+                    attempt_id
+                );
+                let process_id = Uuid::new_v4();
+                Self::start_process_execution_with_id(
+                    pool,
+                    app_state,
+                    process_id,
+                    attempt_id,
+                    task_id,
+                    crate::executor::ExecutorType::CondensedCodingAgent {
+                        config: executor_config,
+                        prompt_suffix,
+                    },
+                    "Starting new executor session (conversation history too long)".to_string(),
+                    ExecutionProcessType::CodingAgent,
+                    &worktree_path,
+                )
+                .await?;
+                TaskRun::set_context_truncated(pool, process_id).await?;
+                return Ok(attempt_id);
+            }
+        }
+
         // Try to use follow-up with session ID, but fall back to new session if it fails
-        let followup_executor = if let Some(session_id) = &executor_session.session_id {
+        let followup_executor = if let Some(session_id) = &session_id {
             // First try with session ID for continuation
             debug!(
                 "SESSION_FOLLOWUP: Attempting follow-up execution with session ID: {} (attempt: {}, worktree: {})",
@@ -436,8 +527,10 @@ impl ProcessService {
             );
             crate::executor::ExecutorType::FollowUpCodingAgent {
                 config: executor_config.clone(),
-                session_id: executor_session.session_id.clone(),
-                prompt: prompt.to_string(),
+                session_id: Some(session_id.clone()),
+                prompt: request.prompt,
+                context_files: request.context_files,
+                tool_results: request.tool_results,
             }
         } else {
             // No session ID available, start new session
@@ -462,11 +555,19 @@ impl ProcessService {
         .await;
 
         // If follow-up execution failed and we tried to use a session ID,
-        // fall back to a new session
-        if execution_result.is_err() && executor_session.session_id.is_some() {
+        // fall back to a new session. A full executor pool or a bad request is not the
+        // session's fault.
+        let pool_exhausted = matches!(
+            execution_result,
+            Err(TaskAttemptError::Executor(
+                crate::executor::ExecutorError::PoolExhausted { .. }
+                    | crate::executor::ExecutorError::InvalidInput(_)
+            ))
+        );
+        if execution_result.is_err() && !pool_exhausted && session_id.is_some() {
             tracing::warn!(
                 "SESSION_FOLLOWUP: Follow-up execution with session ID '{}' failed for attempt {}, falling back to new session. Error: {:?}",
-                executor_session.session_id.as_ref().unwrap(),
+                session_id.as_ref().unwrap(),
                 attempt_id,
                 execution_result.as_ref().err()
             );
@@ -493,6 +594,83 @@ impl ProcessService {
         Ok(attempt_id)
     }
 
+    /// Continue a Claude session that was started outside vibe-kanban, e.g. in the Claude
+    /// desktop app, in the task's latest attempt. Unlike follow-ups this never falls back to
+    /// a new session: a run that cannot start is marked failed, and one Claude rejects exits
+    /// non-zero and is marked failed by the execution monitor. Returns the new run's ID.
+    pub async fn start_session_resume(
+        pool: &SqlitePool,
+        app_state: &crate::app_state::AppState,
+        task_id: Uuid,
+        project_id: Uuid,
+        session_id: &str,
+        prompt: &str,
+    ) -> Result<Uuid, TaskAttemptError> {
+        use crate::models::task::TaskStatus;
+
+        let session_id = validate_session_id(session_id)?;
+
+        let attempt = TaskAttempt::find_by_task_id(pool, task_id)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                TaskAttemptError::ValidationError(
+                    "Task has no attempt to resume the session in".to_string(),
+                )
+            })?;
+
+        Task::update_status(pool, task_id, project_id, TaskStatus::InProgress).await?;
+        let worktree_path =
+            TaskAttempt::ensure_worktree_exists(pool, attempt.id, project_id, "session resume")
+                .await?;
+
+        info!(
+            "Resuming external session {} on attempt {} of task {}",
+            session_id, attempt.id, task_id
+        );
+        let process_id = Uuid::new_v4();
+        let result = Self::start_process_execution_with_id(
+            pool,
+            app_state,
+            process_id,
+            attempt.id,
+            task_id,
+            crate::executor::ExecutorType::FollowUpCodingAgent {
+                config: crate::executor::ExecutorConfig::Claude,
+                session_id: Some(session_id.to_string()),
+                prompt: prompt.to_string(),
+                context_files: Vec::new(),
+                tool_results: Vec::new(),
+            },
+            "Resuming external session".to_string(),
+            ExecutionProcessType::CodingAgent,
+            &worktree_path,
+        )
+        .await;
+
+        if let Err(e) = result {
+            // The run may already be recorded; it never started, so it failed
+            if let Err(update_error) = ExecutionProcess::update_completion(
+                pool,
+                process_id,
+                ExecutionProcessStatus::Failed,
+                None,
+            )
+            .await
+            {
+                tracing::error!(
+                    "Failed to mark execution process {} as failed: {}",
+                    process_id,
+                    update_error
+                );
+            }
+            return Err(e);
+        }
+
+        Ok(process_id)
+    }
+
     /// Unified function to start any type of process execution
     #[allow(clippy::too_many_arguments)]
     pub async fn start_process_execution(
@@ -505,11 +683,48 @@ impl ProcessService {
         process_type: ExecutionProcessType,
         worktree_path: &str,
     ) -> Result<(), TaskAttemptError> {
-        let process_id = Uuid::new_v4();
+        Self::start_process_execution_with_id(
+            pool,
+            app_state,
+            Uuid::new_v4(),
+            attempt_id,
+            task_id,
+            executor_type,
+            activity_note,
+            process_type,
+            worktree_path,
+        )
+        .await
+    }
+
+    /// Like [`ProcessService::start_process_execution`], recording the run as `process_id`
+    #[allow(clippy::too_many_arguments)]
+    async fn start_process_execution_with_id(
+        pool: &SqlitePool,
+        app_state: &crate::app_state::AppState,
+        process_id: Uuid,
+        attempt_id: Uuid,
+        task_id: Uuid,
+        executor_type: crate::executor::ExecutorType,
+        activity_note: String,
+        process_type: ExecutionProcessType,
+        worktree_path: &str,
+    ) -> Result<(), TaskAttemptError> {
+        let task = Task::find_by_id(pool, task_id)
+            .await?
+            .ok_or(TaskAttemptError::TaskNotFound)?;
+
+        // Coding agents wait for a slot in the project's pool before anything is recorded
+        let permit = if matches!(process_type, ExecutionProcessType::CodingAgent) {
+            Some(Self::acquire_executor_permit(pool, app_state, &task).await?)
+        } else {
+            None
+        };
 
         // Create execution process record
         let _execution_process = Self::create_execution_process_record(
             pool,
+            app_state.user_id(),
             attempt_id,
             process_id,
             &executor_type,
@@ -541,6 +756,16 @@ impl ProcessService {
 
         tracing::info!("Starting {} for task attempt {}", activity_note, attempt_id);
 
+        // Coding agent runs are traced from their spawn until they exit
+        let run_span = match &executor_type {
+            crate::executor::ExecutorType::CodingAgent(config)
+            | crate::executor::ExecutorType::CondensedCodingAgent { config, .. }
+            | crate::executor::ExecutorType::FollowUpCodingAgent { config, .. } => {
+                crate::telemetry::task_run_span(task_id, process_id, &config.to_string())
+            }
+            _ => tracing::Span::none(),
+        };
+
         // Execute the process
         let child = Self::execute_process(
             &executor_type,
@@ -549,12 +774,33 @@ impl ProcessService {
             attempt_id,
             process_id,
             worktree_path,
+            app_state.run_output_channels(),
+            app_state.run_progress_channels(),
         )
+        .instrument(run_span.clone())
         .await?;
 
         // Register for monitoring
-        Self::register_for_monitoring(app_state, process_id, attempt_id, &process_type, child)
-            .await;
+        Self::register_for_monitoring(
+            pool,
+            app_state,
+            process_id,
+            attempt_id,
+            &process_type,
+            child,
+            permit,
+        )
+        .instrument(run_span)
+        .await;
+
+        if matches!(process_type, ExecutionProcessType::CodingAgent) {
+            if let Err(e) = Task::mark_started(pool, task_id).await {
+                tracing::error!("Failed to record the start of task {}: {}", task_id, e);
+            }
+            if let Some(timeout_seconds) = task.timeout_seconds {
+                Self::schedule_timeout(pool, app_state, process_id, timeout_seconds as u64);
+            }
+        }
 
         tracing::info!(
             "Started execution {} for task attempt {}",
@@ -564,6 +810,313 @@ impl ProcessService {
         Ok(())
     }
 
+    /// Wait for a slot in the project's executor pool for a coding agent run of `task`;
+    /// more urgent tasks are served first
+    async fn acquire_executor_permit(
+        pool: &SqlitePool,
+        app_state: &crate::app_state::AppState,
+        task: &Task,
+    ) -> Result<crate::executor_pool::ExecutorPermit, TaskAttemptError> {
+        let boost_after = Project::find_by_id(pool, task.project_id)
+            .await?
+            .and_then(|project| project.priority_boost_after_seconds)
+            .filter(|seconds| *seconds > 0)
+            .map(|seconds| std::time::Duration::from_secs(seconds as u64));
+        Ok(app_state
+            .executor_pool(task.project_id)
+            .acquire(task.priority, boost_after)
+            .await?)
+    }
+
+    /// Resume the Claude session of crashed run `run_id` under the same run id. The executor
+    /// is built like any follow-up of the project and waits for a pool slot like any coding
+    /// agent run; the process is returned with its permit, ready to be monitored.
+    pub(crate) async fn spawn_crash_resume(
+        pool: &SqlitePool,
+        app_state: &crate::app_state::AppState,
+        run_id: Uuid,
+        attempt: &TaskAttempt,
+        config: &crate::executor::ExecutorConfig,
+        session_id: &str,
+    ) -> Result<
+        (
+            command_group::AsyncGroupChild,
+            crate::executor_pool::ExecutorPermit,
+        ),
+        TaskAttemptError,
+    > {
+        use crate::executors::claude::CRASH_RESUME_PROMPT;
+
+        let task = Task::find_by_id(pool, attempt.task_id)
+            .await?
+            .ok_or(TaskAttemptError::TaskNotFound)?;
+        let permit = Self::acquire_executor_permit(pool, app_state, &task).await?;
+        let executor = Self::followup_executor(
+            pool,
+            task.id,
+            attempt.id,
+            run_id,
+            config,
+            Some(session_id),
+            CRASH_RESUME_PROMPT,
+            &[],
+            &[],
+            app_state.run_progress_channels(),
+        )
+        .await?;
+        let child = executor
+            .execute_streaming(
+                pool,
+                task.id,
+                attempt.id,
+                run_id,
+                &attempt.worktree_path,
+                app_state.run_output_channels(),
+            )
+            .await
+            .map_err(TaskAttemptError::Executor)?;
+        Ok((child, permit))
+    }
+
+    /// Kill the execution's process group once it has run for `timeout_seconds`,
+    /// unless it finished first. The timed out run is recorded as killed and, when the
+    /// project enables it, its session is resumed in a new run.
+    ///
+    /// This is the one place a task's `timeout_seconds` is enforced: the deadline covers the
+    /// whole run, from the spawn until the process exits, and works the same for every
+    /// executor.
+    fn schedule_timeout(
+        pool: &SqlitePool,
+        app_state: &crate::app_state::AppState,
+        process_id: Uuid,
+        timeout_seconds: u64,
+    ) {
+        let pool = pool.clone();
+        let app_state = app_state.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_secs(timeout_seconds)).await;
+
+            match app_state.stop_running_execution_by_id(process_id).await {
+                Ok(true) => {
+                    let error = crate::executor::ExecutorError::Timeout {
+                        session_id: None,
+                        after_ms: timeout_seconds * 1000,
+                    };
+                    tracing::warn!(
+                        "Execution process {} killed after {}s: {}",
+                        process_id,
+                        timeout_seconds,
+                        error
+                    );
+                    let note = format!("[vibe-kanban] {} after {}s\n", error, timeout_seconds);
+                    if let Err(e) = ExecutionProcess::append_stderr(&pool, process_id, &note).await
+                    {
+                        tracing::error!(
+                            "Failed to record timeout for execution process {}: {}",
+                            process_id,
+                            e
+                        );
+                    }
+                    if let Err(e) = ExecutionProcess::update_completion(
+                        &pool,
+                        process_id,
+                        ExecutionProcessStatus::Killed,
+                        None,
+                    )
+                    .await
+                    {
+                        tracing::error!(
+                            "Failed to mark timed out execution process {} as killed: {}",
+                            process_id,
+                            e
+                        );
+                    }
+                    if let Err(e) = Self::resume_after_timeout(&pool, &app_state, process_id).await
+                    {
+                        tracing::error!(
+                            "Failed to resume timed out execution process {}: {}",
+                            process_id,
+                            e
+                        );
+                    }
+                }
+                // Already finished
+                Ok(false) => {}
+                Err(e) => tracing::error!(
+                    "Failed to kill timed out execution process {}: {}",
+                    process_id,
+                    e
+                ),
+            }
+        });
+    }
+
+    /// Continue the session of a Claude run that timed out in a new run of the same attempt,
+    /// when the project enables `auto_resume_on_timeout`. Each resume counts against
+    /// [`TimeoutResumePolicy`](crate::executor::TimeoutResumePolicy); returns whether a run
+    /// was started.
+    async fn resume_after_timeout(
+        pool: &SqlitePool,
+        app_state: &crate::app_state::AppState,
+        process_id: Uuid,
+    ) -> Result<bool, TaskAttemptError> {
+        use crate::executors::claude::TIMEOUT_RESUME_PROMPT;
+
+        let Some(run) = ExecutionProcess::find_by_id(pool, process_id).await? else {
+            return Ok(false);
+        };
+        let config = match run.executor_type.as_deref().map(str::parse) {
+            Some(Ok(
+                config @ (crate::executor::ExecutorConfig::Claude
+                | crate::executor::ExecutorConfig::ClaudePlan),
+            )) => config,
+            _ => return Ok(false),
+        };
+        let Some(attempt) = TaskAttempt::find_by_id(pool, run.task_attempt_id).await? else {
+            return Ok(false);
+        };
+        let Some(project_config) = Self::project_executor_config(pool, attempt.task_id, &config)
+            .await
+            .filter(|project_config| project_config.auto_resume_on_timeout)
+        else {
+            return Ok(false);
+        };
+
+        let Some(session_id) = ExecutorSession::find_by_execution_process_id(pool, process_id)
+            .await?
+            .and_then(|session| session.session_id)
+        else {
+            tracing::warn!(
+                "Run {} timed out before its session id was captured",
+                process_id
+            );
+            return Ok(false);
+        };
+
+        let policy = crate::executor::TimeoutResumePolicy::default();
+        let resumes = ExecutorSession::find_by_task_attempt_id(pool, attempt.id)
+            .await?
+            .into_iter()
+            .filter(|session| session.prompt.as_deref() == Some(TIMEOUT_RESUME_PROMPT))
+            .count();
+        if resumes >= policy.max_resumes as usize {
+            tracing::warn!(
+                "Run {} timed out again after {} resumes, giving up",
+                process_id,
+                resumes
+            );
+            return Ok(false);
+        }
+
+        let worktree_path = TaskAttempt::ensure_worktree_exists(
+            pool,
+            attempt.id,
+            project_config.project_id,
+            "timeout resume",
+        )
+        .await?;
+        info!(
+            "Resuming session {} of timed out run {} (retry {}/{})",
+            session_id,
+            process_id,
+            resumes + 1,
+            policy.max_resumes
+        );
+        Self::start_process_execution(
+            pool,
+            app_state,
+            attempt.id,
+            attempt.task_id,
+            crate::executor::ExecutorType::FollowUpCodingAgent {
+                config,
+                session_id: Some(session_id),
+                prompt: TIMEOUT_RESUME_PROMPT.to_string(),
+                context_files: Vec::new(),
+                tool_results: Vec::new(),
+            },
+            "Resuming session after timeout".to_string(),
+            ExecutionProcessType::CodingAgent,
+            &worktree_path,
+        )
+        .await?;
+        Ok(true)
+    }
+
+    /// Cancel every running coding agent run of a project. Each process group gets SIGTERM,
+    /// then SIGKILL when it has not exited within [`CANCEL_GRACE_PERIOD`]; cancelled runs
+    /// are recorded as killed. Runs whose process could not be signalled are left as they
+    /// are and reported in `failed_to_cancel`. Runs are terminated concurrently, so the
+    /// whole cancel takes at most one grace period.
+    pub async fn cancel_running_for_project(
+        app_state: &crate::app_state::AppState,
+        project_id: Uuid,
+    ) -> Result<CancelledRuns, sqlx::Error> {
+        let pool = &app_state.project_pool(project_id).await?;
+        let mut outcome = CancelledRuns::default();
+
+        let runs = TaskRun::find_running_by_project_id(pool, project_id).await?;
+        let terminated = join_all(runs.iter().map(|run| Self::terminate_run(app_state, run))).await;
+        for (run, result) in runs.iter().zip(terminated) {
+            match result {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::warn!(
+                        "Execution process {} has no process this server can signal",
+                        run.id
+                    );
+                    outcome.failed_to_cancel.push(run.id);
+                    continue;
+                }
+                Err(e) => {
+                    tracing::error!("Failed to cancel execution process {}: {}", run.id, e);
+                    outcome.failed_to_cancel.push(run.id);
+                    continue;
+                }
+            }
+
+            ExecutionProcess::update_completion(pool, run.id, ExecutionProcessStatus::Killed, None)
+                .await?;
+            Task::mark_completed(pool, run.task_id).await?;
+            TaskAttempt::release_worktree(pool, run.task_attempt_id, project_id).await?;
+            outcome.cancelled.push(run.id);
+        }
+
+        info!(
+            "Cancelled {} running runs of project {}",
+            outcome.cancelled.len(),
+            project_id
+        );
+        Ok(outcome)
+    }
+
+    /// Terminate the run's process group, returning whether it was signalled
+    async fn terminate_run(
+        app_state: &crate::app_state::AppState,
+        run: &RunningTaskRun,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        if app_state
+            .terminate_running_execution(run.id, CANCEL_GRACE_PERIOD)
+            .await?
+        {
+            return Ok(true);
+        }
+
+        // Not a child of this server, e.g. left running across a restart. Its process group
+        // is only signalled while the recorded PID still belongs to the run's process.
+        match (run.pid, run.pid_start_time) {
+            #[cfg(unix)]
+            (Some(pid), Some(start_time)) => {
+                Ok(
+                    terminate_process_group(pid as i32, start_time as u64, CANCEL_GRACE_PERIOD)
+                        .await?,
+                )
+            }
+            // Without a start time to check the PID against, signalling it could hit an
+            // unrelated process
+            _ => Ok(false),
+        }
+    }
+
     /// Load the execution context (task attempt and project) with validation
     async fn load_execution_context(
         pool: &SqlitePool,
@@ -614,23 +1167,74 @@ impl ProcessService {
         .await
     }
 
+    /// The project's pinned Claude session and the prompt to continue it with, when the
+    /// project's session affinity covers this task
+    async fn pinned_session_for_task(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        project_id: Uuid,
+        executor_config: &crate::executor::ExecutorConfig,
+    ) -> Result<Option<(String, String)>, TaskAttemptError> {
+        use crate::executor::ExecutorConfig;
+
+        if !matches!(
+            executor_config,
+            ExecutorConfig::Claude | ExecutorConfig::ClaudePlan
+        ) {
+            return Ok(None);
+        }
+
+        let project = Project::find_by_id(pool, project_id)
+            .await?
+            .ok_or(TaskAttemptError::ProjectNotFound)?;
+        let Some(session_id) = project.pinned_session_id else {
+            return Ok(None);
+        };
+        if !project
+            .session_affinity
+            .applies_to(&Task::labels(pool, task_id).await?)
+        {
+            return Ok(None);
+        }
+
+        let task = Task::find_by_id(pool, task_id)
+            .await?
+            .ok_or(TaskAttemptError::TaskNotFound)?;
+        let prompt = match task.description {
+            Some(description) => format!(
+                "Next task in this project.\n\nTask title: {}\nTask description: {}",
+                task.title, description
+            ),
+            None => format!("Next task in this project.\n\nTask title: {}", task.title),
+        };
+
+        Ok(Some((session_id, prompt)))
+    }
+
     /// Resolve executor configuration from string name
     fn resolve_executor_config(executor_name: &Option<String>) -> crate::executor::ExecutorConfig {
         match executor_name.as_ref().map(|s| s.as_str()) {
             Some("claude") => crate::executor::ExecutorConfig::Claude,
             Some("claude-plan") => crate::executor::ExecutorConfig::ClaudePlan,
+            Some("claude-plan-then-execute") => {
+                crate::executor::ExecutorConfig::ClaudePlanThenExecute
+            }
             Some("claude-code-router") => crate::executor::ExecutorConfig::ClaudeCodeRouter,
             Some("amp") => crate::executor::ExecutorConfig::Amp,
             Some("gemini") => crate::executor::ExecutorConfig::Gemini,
             Some("charm-opencode") => crate::executor::ExecutorConfig::CharmOpencode,
             Some("sst-opencode") => crate::executor::ExecutorConfig::SstOpencode,
+            Some("aider") => crate::executor::ExecutorConfig::Aider,
+            Some("mistral") => crate::executor::ExecutorConfig::Mistral,
             _ => crate::executor::ExecutorConfig::Echo, // Default for "echo" or None
         }
     }
 
-    /// Create execution process database record
+    /// Create execution process database record. Coding agent runs are recorded in the
+    /// audit log as started by `actor_id`.
     async fn create_execution_process_record(
         pool: &SqlitePool,
+        actor_id: &str,
         attempt_id: Uuid,
         process_id: Uuid,
         executor_type: &crate::executor::ExecutorType,
@@ -649,7 +1253,8 @@ impl ProcessService {
                 Some(serde_json::to_string(&[shell_arg, "dev_server"]).unwrap()),
                 None, // Dev servers don't have an executor type
             ),
-            crate::executor::ExecutorType::CodingAgent(config) => {
+            crate::executor::ExecutorType::CodingAgent(config)
+            | crate::executor::ExecutorType::CondensedCodingAgent { config, .. } => {
                 ("executor".to_string(), None, Some(format!("{}", config)))
             }
             crate::executor::ExecutorType::FollowUpCodingAgent { config, .. } => (
@@ -668,9 +1273,24 @@ impl ProcessService {
             working_directory: worktree_path.to_string(),
         };
 
-        ExecutionProcess::create(pool, &create_process, process_id)
-            .await
-            .map_err(TaskAttemptError::from)
+        let audit = AuditLogger::new(pool.clone());
+        let mut tx = audit.begin().await?;
+        let execution_process =
+            ExecutionProcess::create(&mut *tx, &create_process, process_id).await?;
+        if execution_process.process_type == ExecutionProcessType::CodingAgent {
+            audit
+                .log_change(
+                    &mut tx,
+                    RUN_STARTED,
+                    process_id,
+                    actor_id,
+                    None,
+                    Some(&execution_process),
+                )
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(execution_process)
     }
 
     /// Create executor session record for coding agents
@@ -704,7 +1324,9 @@ impl ProcessService {
             .map_err(TaskAttemptError::from)
     }
 
-    /// Execute the process based on type
+    /// Execute the process based on type. Claude follow-ups report their progress on a
+    /// channel registered in `progress_channels`.
+    #[allow(clippy::too_many_arguments)]
     async fn execute_process(
         executor_type: &crate::executor::ExecutorType,
         pool: &SqlitePool,
@@ -712,6 +1334,8 @@ impl ProcessService {
         attempt_id: Uuid,
         process_id: Uuid,
         worktree_path: &str,
+        output_channels: &crate::executor::RunOutputChannels,
+        progress_channels: &crate::executor::RunProgressChannels,
     ) -> Result<command_group::AsyncGroupChild, TaskAttemptError> {
         use crate::executors::{DevServerExecutor, SetupScriptExecutor};
 
@@ -721,7 +1345,14 @@ impl ProcessService {
                     script: script.clone(),
                 };
                 executor
-                    .execute_streaming(pool, task_id, attempt_id, process_id, worktree_path)
+                    .execute_streaming(
+                        pool,
+                        task_id,
+                        attempt_id,
+                        process_id,
+                        worktree_path,
+                        output_channels,
+                    )
                     .await
             }
             crate::executor::ExecutorType::DevServer(script) => {
@@ -729,113 +1360,506 @@ impl ProcessService {
                     script: script.clone(),
                 };
                 executor
-                    .execute_streaming(pool, task_id, attempt_id, process_id, worktree_path)
+                    .execute_streaming(
+                        pool,
+                        task_id,
+                        attempt_id,
+                        process_id,
+                        worktree_path,
+                        output_channels,
+                    )
                     .await
             }
-            crate::executor::ExecutorType::CodingAgent(config) => {
-                let executor = config.create_executor();
-                executor
-                    .execute_streaming(pool, task_id, attempt_id, process_id, worktree_path)
-                    .await
+            crate::executor::ExecutorType::CodingAgent(config)
+            | crate::executor::ExecutorType::CondensedCodingAgent { config, .. } => {
+                let model = Self::task_executor_model(pool, task_id).await;
+                let mcp_config_path =
+                    Self::write_mcp_config(pool, task_id, config, process_id, worktree_path)
+                        .await?;
+                let executor = match Self::project_executor_config(pool, task_id, config).await {
+                    Some(project_config) => {
+                        let mut executor = Self::project_claude_executor(
+                            config,
+                            &project_config,
+                            model.as_deref(),
+                        );
+                        if let Some(ref path) = mcp_config_path {
+                            executor = executor.with_mcp_config(path);
+                        }
+                        executor
+                            .validate_config()
+                            .map(|()| Box::new(executor) as Box<dyn Executor>)
+                    }
+                    None => match mcp_config_path {
+                        Some(ref path) => {
+                            let executor = match config {
+                                crate::executor::ExecutorConfig::ClaudePlan => {
+                                    crate::executors::ClaudeExecutor::new_plan_mode()
+                                }
+                                _ => crate::executors::ClaudeExecutor::new(),
+                            };
+                            let executor = match model {
+                                Some(ref model) => executor.with_model(model),
+                                None => executor,
+                            }
+                            .with_mcp_config(path);
+                            executor
+                                .validate_config()
+                                .map(|()| Box::new(executor) as Box<dyn Executor>)
+                        }
+                        None => config.try_create_executor_with_model(model.as_deref()),
+                    },
+                };
+                // A condensed session picks up from the summary after the task prompt
+                let executor = match executor_type {
+                    crate::executor::ExecutorType::CondensedCodingAgent {
+                        prompt_suffix, ..
+                    } => executor.map(|executor| {
+                        executor
+                            .with_prompt_suffix(prompt_suffix)
+                            .unwrap_or(executor)
+                    }),
+                    _ => executor,
+                };
+                let spawned = match executor {
+                    Ok(executor) => {
+                        executor
+                            .execute_streaming(
+                                pool,
+                                task_id,
+                                attempt_id,
+                                process_id,
+                                worktree_path,
+                                output_channels,
+                            )
+                            .await
+                    }
+                    Err(e) => Err(e),
+                };
+                // The monitor removes the config once the run exits, runs that never started
+                // are cleaned up here
+                if spawned.is_err() && mcp_config_path.is_some() {
+                    if let Err(e) = client_config::remove(worktree_path, process_id).await {
+                        tracing::warn!("Failed to remove MCP config of {}: {}", process_id, e);
+                    }
+                }
+                spawned
             }
             crate::executor::ExecutorType::FollowUpCodingAgent {
                 config,
                 session_id,
                 prompt,
+                context_files,
+                tool_results,
             } => {
-                use crate::executors::{
-                    AmpFollowupExecutor, CCRFollowupExecutor, CharmOpencodeFollowupExecutor,
-                    ClaudeFollowupExecutor, GeminiFollowupExecutor, SstOpencodeFollowupExecutor,
-                };
+                let executor = Self::followup_executor(
+                    pool,
+                    task_id,
+                    attempt_id,
+                    process_id,
+                    config,
+                    session_id.as_deref(),
+                    prompt,
+                    context_files,
+                    tool_results,
+                    progress_channels,
+                )
+                .await?;
 
-                let executor: Box<dyn crate::executor::Executor> = match config {
-                    crate::executor::ExecutorConfig::Claude => {
-                        if let Some(sid) = session_id {
-                            Box::new(ClaudeFollowupExecutor::new(sid.clone(), prompt.clone()))
-                        } else {
-                            return Err(TaskAttemptError::TaskNotFound); // No session ID for followup
-                        }
-                    }
-                    crate::executor::ExecutorConfig::ClaudePlan => {
-                        if let Some(sid) = session_id {
-                            Box::new(ClaudeFollowupExecutor::new_plan_mode(
-                                sid.clone(),
-                                prompt.clone(),
-                            ))
-                        } else {
-                            return Err(TaskAttemptError::TaskNotFound); // No session ID for followup
-                        }
-                    }
-                    crate::executor::ExecutorConfig::Amp => {
-                        if let Some(tid) = session_id {
-                            Box::new(AmpFollowupExecutor {
-                                thread_id: tid.clone(),
-                                prompt: prompt.clone(),
-                            })
-                        } else {
-                            return Err(TaskAttemptError::TaskNotFound); // No thread ID for followup
-                        }
-                    }
-                    crate::executor::ExecutorConfig::Gemini => {
-                        // For Gemini, we don't use real session IDs, we pass the context directly
-                        Box::new(GeminiFollowupExecutor {
-                            attempt_id,
-                            prompt: prompt.clone(),
-                        })
-                    }
-                    crate::executor::ExecutorConfig::Echo => {
-                        // Echo doesn't support followup, use regular echo
-                        config.create_executor()
-                    }
-                    crate::executor::ExecutorConfig::CharmOpencode => {
-                        if let Some(sid) = session_id {
-                            Box::new(CharmOpencodeFollowupExecutor {
-                                session_id: sid.clone(),
-                                prompt: prompt.clone(),
-                            })
-                        } else {
-                            return Err(TaskAttemptError::TaskNotFound); // No session ID for followup
-                        }
-                    }
-                    crate::executor::ExecutorConfig::ClaudeCodeRouter => {
-                        if let Some(sid) = session_id {
-                            Box::new(CCRFollowupExecutor::new(sid.clone(), prompt.clone()))
-                        } else {
-                            return Err(TaskAttemptError::TaskNotFound); // No session ID for followup
-                        }
-                    }
-                    crate::executor::ExecutorConfig::SstOpencode => {
-                        if let Some(sid) = session_id {
-                            Box::new(SstOpencodeFollowupExecutor::new(
-                                sid.clone(),
-                                prompt.clone(),
-                            ))
-                        } else {
-                            return Err(TaskAttemptError::TaskNotFound); // No session ID for followup
-                        }
+                executor
+                    .execute_streaming(
+                        pool,
+                        task_id,
+                        attempt_id,
+                        process_id,
+                        worktree_path,
+                        output_channels,
+                    )
+                    .await
+            }
+        };
+
+        // Rejected requests keep their type so routes can answer them with a 400
+        result.map_err(|e| match e {
+            crate::executor::ExecutorError::InvalidInput(_) => TaskAttemptError::Executor(e),
+            e => TaskAttemptError::Git(git2::Error::from_str(&e.to_string())),
+        })
+    }
+
+    /// Build the executor of a follow-up run. Claude sessions are resumed with the project's
+    /// executor configuration behind the default middleware.
+    #[allow(clippy::too_many_arguments)]
+    async fn followup_executor(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        process_id: Uuid,
+        config: &crate::executor::ExecutorConfig,
+        session_id: Option<&str>,
+        prompt: &str,
+        context_files: &[String],
+        tool_results: &[crate::executors::claude::ToolResult],
+        progress_channels: &crate::executor::RunProgressChannels,
+    ) -> Result<Box<dyn Executor>, TaskAttemptError> {
+        use crate::executors::{
+            AiderFollowupExecutor, AmpFollowupExecutor, CCRFollowupExecutor,
+            CharmOpencodeFollowupExecutor, GeminiFollowupExecutor, MistralFollowupExecutor,
+            SstOpencodeFollowupExecutor,
+        };
+
+        let executor: Box<dyn Executor> = match config {
+            // A follow-up continues the session the chain's execute run left off in
+            crate::executor::ExecutorConfig::Claude
+            | crate::executor::ExecutorConfig::ClaudePlanThenExecute => {
+                if let Some(sid) = session_id {
+                    let mut executor = Self::claude_followup_executor(
+                        pool,
+                        task_id,
+                        config,
+                        sid.to_string(),
+                        prompt.to_string(),
+                    )
+                    .await;
+                    if let Some(previous) =
+                        Self::load_previous_conversation(pool, attempt_id, process_id).await
+                    {
+                        executor = executor.with_previous_outcome(&previous);
                     }
-                    crate::executor::ExecutorConfig::SetupScript { .. } => {
-                        // Setup scripts don't support followup, use regular setup script
-                        config.create_executor()
+                    let progress_tx = register_run_progress(progress_channels, process_id).await;
+                    config.wrap_executor(Box::new(
+                        executor
+                            .with_context_files(context_files.to_vec())
+                            .with_tool_results(tool_results.to_vec())
+                            .with_progress(progress_tx),
+                    ))
+                } else {
+                    return Err(TaskAttemptError::TaskNotFound); // No session ID for followup
+                }
+            }
+            crate::executor::ExecutorConfig::ClaudePlan => {
+                if let Some(sid) = session_id {
+                    let mut executor = Self::claude_followup_executor(
+                        pool,
+                        task_id,
+                        config,
+                        sid.to_string(),
+                        prompt.to_string(),
+                    )
+                    .await;
+                    if let Some(previous) =
+                        Self::load_previous_conversation(pool, attempt_id, process_id).await
+                    {
+                        executor = executor.with_previous_outcome(&previous);
                     }
-                };
+                    let progress_tx = register_run_progress(progress_channels, process_id).await;
+                    config.wrap_executor(Box::new(
+                        executor
+                            .with_context_files(context_files.to_vec())
+                            .with_tool_results(tool_results.to_vec())
+                            .with_progress(progress_tx),
+                    ))
+                } else {
+                    return Err(TaskAttemptError::TaskNotFound); // No session ID for followup
+                }
+            }
+            crate::executor::ExecutorConfig::Amp => {
+                if let Some(tid) = session_id {
+                    Box::new(AmpFollowupExecutor {
+                        thread_id: tid.to_string(),
+                        prompt: prompt.to_string(),
+                    })
+                } else {
+                    return Err(TaskAttemptError::TaskNotFound); // No thread ID for followup
+                }
+            }
+            crate::executor::ExecutorConfig::Gemini => {
+                // For Gemini, we don't use real session IDs, we pass the context directly
+                Box::new(GeminiFollowupExecutor {
+                    attempt_id,
+                    prompt: prompt.to_string(),
+                })
+            }
+            crate::executor::ExecutorConfig::Echo => {
+                // Echo doesn't support followup, use regular echo
+                config.create_executor()
+            }
+            crate::executor::ExecutorConfig::CharmOpencode => {
+                if let Some(sid) = session_id {
+                    Box::new(CharmOpencodeFollowupExecutor {
+                        session_id: sid.to_string(),
+                        prompt: prompt.to_string(),
+                    })
+                } else {
+                    return Err(TaskAttemptError::TaskNotFound); // No session ID for followup
+                }
+            }
+            crate::executor::ExecutorConfig::ClaudeCodeRouter => {
+                if let Some(sid) = session_id {
+                    Box::new(CCRFollowupExecutor::new(
+                        sid.to_string(),
+                        prompt.to_string(),
+                    ))
+                } else {
+                    return Err(TaskAttemptError::TaskNotFound); // No session ID for followup
+                }
+            }
+            crate::executor::ExecutorConfig::SstOpencode => {
+                if let Some(sid) = session_id {
+                    Box::new(SstOpencodeFollowupExecutor::new(
+                        sid.to_string(),
+                        prompt.to_string(),
+                    ))
+                } else {
+                    return Err(TaskAttemptError::TaskNotFound); // No session ID for followup
+                }
+            }
+            crate::executor::ExecutorConfig::Aider => {
+                // aider resumes from the chat history it keeps in the worktree
+                Box::new(AiderFollowupExecutor {
+                    prompt: prompt.to_string(),
+                })
+            }
+            crate::executor::ExecutorConfig::Mistral => {
+                // codestral has no session to resume, so the prompt starts anew
+                Box::new(MistralFollowupExecutor {
+                    prompt: prompt.to_string(),
+                })
+            }
+            crate::executor::ExecutorConfig::SetupScript { .. } => {
+                // Setup scripts don't support followup, use regular setup script
+                config.create_executor()
+            }
+        };
+        Ok(executor)
+    }
 
-                executor
-                    .execute_streaming(pool, task_id, attempt_id, process_id, worktree_path)
-                    .await
+    /// The model the task's Claude runs are pinned to, if any
+    async fn task_executor_model(pool: &SqlitePool, task_id: Uuid) -> Option<String> {
+        Task::find_by_id(pool, task_id)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|task| task.executor_model)
+    }
+
+    /// The task's project executor configuration, when it applies to runs of `config`
+    async fn project_executor_config(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        config: &crate::executor::ExecutorConfig,
+    ) -> Option<ProjectExecutorConfig> {
+        let task = Task::find_by_id(pool, task_id).await.ok().flatten()?;
+        ProjectExecutorConfig::find_by_project_id(pool, task.project_id)
+            .await
+            .ok()
+            .flatten()
+            .filter(|project_config| project_config.executor_type == config.to_string())
+    }
+
+    /// The CLI command a project configuration runs `config` with, and whether it runs in
+    /// plan mode. The task's `model` takes precedence over the project's.
+    fn project_claude_command(
+        config: &crate::executor::ExecutorConfig,
+        project_config: &ProjectExecutorConfig,
+        model: Option<&str>,
+    ) -> (String, bool) {
+        let use_plan_mode = project_config.use_plan_mode
+            || matches!(config, crate::executor::ExecutorConfig::ClaudePlan);
+        let mut command = crate::executors::claude::build_claude_command(
+            &project_config.binary_path,
+            use_plan_mode,
+            model.or(project_config.model.as_deref()),
+        );
+        if let Some(extra_flags) = project_config.extra_flags.as_deref() {
+            if !extra_flags.trim().is_empty() {
+                command = format!("{} {}", command, extra_flags.trim());
             }
+        }
+        (command, use_plan_mode)
+    }
+
+    fn project_claude_executor(
+        config: &crate::executor::ExecutorConfig,
+        project_config: &ProjectExecutorConfig,
+        model: Option<&str>,
+    ) -> crate::executors::ClaudeExecutor {
+        let (command, use_plan_mode) = Self::project_claude_command(config, project_config, model);
+        let executor_type = if use_plan_mode {
+            "ClaudePlan"
+        } else {
+            "Claude"
         };
+        crate::executors::ClaudeExecutor::with_command(executor_type.to_string(), command)
+            .with_plan_mode(use_plan_mode)
+            .with_web_policy(WebPolicy::from_config(project_config))
+            .with_stdin_format(project_config.stdin_format)
+    }
 
-        result.map_err(|e| TaskAttemptError::Git(git2::Error::from_str(&e.to_string())))
+    /// Write the project's MCP config into the worktree for a Claude run of the task and
+    /// return its path, `None` for other executors and projects without one
+    async fn write_mcp_config(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        config: &crate::executor::ExecutorConfig,
+        process_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<Option<String>, TaskAttemptError> {
+        if !matches!(
+            config,
+            crate::executor::ExecutorConfig::Claude | crate::executor::ExecutorConfig::ClaudePlan
+        ) {
+            return Ok(None);
+        }
+        let Some(task) = Task::find_by_id(pool, task_id).await? else {
+            return Ok(None);
+        };
+        let mcp_config_json = Project::find_mcp_config(pool, task.project_id)
+            .await?
+            .and_then(|config| config.mcp_config_json);
+        let Some(json) = mcp_config_json else {
+            return Ok(None);
+        };
+
+        let path = client_config::write(worktree_path, process_id, &json)
+            .await
+            .map_err(|e| {
+                TaskAttemptError::Git(git2::Error::from_str(&format!(
+                    "Failed to write MCP config: {}",
+                    e
+                )))
+            })?;
+        Ok(Some(path.to_string_lossy().into_owned()))
+    }
+
+    /// Follow-up executor for a Claude session, using the project's executor configuration
+    /// when one applies and the detected CLI otherwise
+    async fn claude_followup_executor(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        config: &crate::executor::ExecutorConfig,
+        session_id: String,
+        prompt: String,
+    ) -> crate::executors::ClaudeFollowupExecutor {
+        use crate::executors::ClaudeFollowupExecutor;
+
+        let model = Self::task_executor_model(pool, task_id).await;
+        if let Some(project_config) = Self::project_executor_config(pool, task_id, config).await {
+            let (command, use_plan_mode) =
+                Self::project_claude_command(config, &project_config, model.as_deref());
+            let executor_type = if use_plan_mode {
+                "ClaudePlan"
+            } else {
+                "Claude"
+            };
+            return ClaudeFollowupExecutor::with_command(
+                session_id,
+                prompt,
+                executor_type.to_string(),
+                command,
+            )
+            .with_plan_mode(use_plan_mode)
+            .with_web_policy(WebPolicy::from_config(&project_config))
+            .with_stdin_format(project_config.stdin_format);
+        }
+
+        let executor = match config {
+            crate::executor::ExecutorConfig::ClaudePlan => {
+                ClaudeFollowupExecutor::new_plan_mode(session_id, prompt)
+            }
+            _ => ClaudeFollowupExecutor::new(session_id, prompt),
+        };
+        match model {
+            Some(model) => executor.with_model(&model),
+            None => executor,
+        }
+    }
+
+    /// The prompt suffix of a fresh session replacing the Claude session of `previous`, when
+    /// its conversation has more turns than the project's `max_context_turns`
+    async fn condensed_followup_context(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        previous: &ExecutionProcess,
+        config: &crate::executor::ExecutorConfig,
+        followup_prompt: &str,
+    ) -> Result<Option<String>, TaskAttemptError> {
+        if !matches!(
+            config,
+            crate::executor::ExecutorConfig::Claude | crate::executor::ExecutorConfig::ClaudePlan
+        ) {
+            return Ok(None);
+        }
+        let max_context_turns = Project::find_by_id(pool, project_id)
+            .await?
+            .and_then(|project| project.max_context_turns);
+        if max_context_turns.is_none() {
+            return Ok(None);
+        }
+
+        let stdout = previous.stdout.as_deref().unwrap_or_default();
+        let Ok(conversation) = config
+            .create_executor()
+            .normalize_logs(stdout, &previous.working_directory)
+        else {
+            return Ok(None);
+        };
+        if !exceeds_context_turns(conversation.entries.len(), max_context_turns) {
+            return Ok(None);
+        }
+
+        // The summarizer's summary when there is one, otherwise the CLI's final message
+        let summary = match TaskRun::find_summary(pool, previous.id).await? {
+            Some(summary) => Some(summary),
+            None => conversation.summary.clone(),
+        };
+        Ok(Some(build_condensed_context(
+            summary.as_deref(),
+            &conversation,
+            followup_prompt,
+        )))
+    }
+
+    /// Load the normalized conversation of the most recent coding agent run for an attempt,
+    /// excluding the process that is about to start
+    async fn load_previous_conversation(
+        pool: &SqlitePool,
+        attempt_id: Uuid,
+        current_process_id: Uuid,
+    ) -> Option<crate::executor::NormalizedConversation> {
+        let processes = ExecutionProcess::find_by_task_attempt_id(pool, attempt_id)
+            .await
+            .ok()?;
+        let previous = processes.iter().rev().find(|p| {
+            p.id != current_process_id
+                && matches!(p.process_type, ExecutionProcessType::CodingAgent)
+        })?;
+
+        let config: crate::executor::ExecutorConfig =
+            previous.executor_type.as_deref()?.parse().ok()?;
+        let stdout = previous.stdout.as_deref().unwrap_or_default();
+        let mut conversation = config
+            .create_executor()
+            .normalize_logs(stdout, &previous.working_directory)
+            .ok()?;
+
+        if let Ok(Some(session)) =
+            ExecutorSession::find_by_execution_process_id(pool, previous.id).await
+        {
+            conversation.summary = session.summary;
+        }
+
+        Some(conversation)
     }
 
     /// Register process for monitoring
-    async fn register_for_monitoring(
+    pub(crate) async fn register_for_monitoring(
+        pool: &SqlitePool,
         app_state: &crate::app_state::AppState,
         process_id: Uuid,
         attempt_id: Uuid,
         process_type: &ExecutionProcessType,
         child: command_group::AsyncGroupChild,
+        permit: Option<crate::executor_pool::ExecutorPermit>,
     ) {
         let execution_type = match process_type {
             ExecutionProcessType::SetupScript => crate::app_state::ExecutionType::SetupScript,
@@ -843,13 +1867,31 @@ impl ProcessService {
             ExecutionProcessType::DevServer => crate::app_state::ExecutionType::DevServer,
         };
 
+        if let Some(pid) = child.id() {
+            if let Err(e) =
+                ExecutionProcess::set_pid(pool, process_id, pid, process_start_time(pid)).await
+            {
+                tracing::error!(
+                    "Failed to record the PID of execution process {}: {}",
+                    process_id,
+                    e
+                );
+            }
+        }
+
+        if let Some(trace) = crate::telemetry::RunTrace::start_wait() {
+            app_state.add_run_trace(process_id, trace);
+        }
+
         app_state
             .add_running_execution(
                 process_id,
                 crate::app_state::RunningExecution {
                     task_attempt_id: attempt_id,
+                    pool: pool.clone(),
                     _execution_type: execution_type,
                     child,
+                    _permit: permit,
                 },
             )
             .await;
@@ -896,6 +1938,7 @@ impl ProcessService {
         attempt_id: Uuid,
         process_id: Uuid,
         worktree_path: &str,
+        output_channels: &crate::executor::RunOutputChannels,
     ) -> Result<command_group::AsyncGroupChild, TaskAttemptError> {
         use crate::executors::SetupScriptExecutor;
 
@@ -904,8 +1947,596 @@ impl ProcessService {
         };
 
         executor
-            .execute_streaming(pool, task_id, attempt_id, process_id, worktree_path)
+            .execute_streaming(
+                pool,
+                task_id,
+                attempt_id,
+                process_id,
+                worktree_path,
+                output_channels,
+            )
             .await
             .map_err(|e| TaskAttemptError::Git(git2::Error::from_str(&e.to_string())))
     }
 }
+
+/// Start time of process `pid` in clock ticks since boot, which tells it apart from a later
+/// process reusing the PID. Only Linux exposes it; elsewhere this is `None`.
+fn process_start_time(pid: u32) -> Option<u64> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name may contain spaces and parentheses, the fields after it do not.
+    // starttime is field 22, the 20th after the name.
+    stat.rsplit_once(')')?
+        .1
+        .split_whitespace()
+        .nth(19)?
+        .parse()
+        .ok()
+}
+
+/// Send SIGTERM to the process group led by `pid`, then SIGKILL if it is still there after
+/// `grace`. Nothing is signalled unless `pid` is still the process that started at
+/// `start_time` and leads its own group, since the PID may have been reused since.
+/// Returns whether the group was signalled.
+#[cfg(unix)]
+async fn terminate_process_group(
+    pid: i32,
+    start_time: u64,
+    grace: Duration,
+) -> Result<bool, nix::Error> {
+    use nix::{
+        errno::Errno,
+        sys::signal::{killpg, Signal},
+        unistd::{getpgid, Pid},
+    };
+
+    let pgid = Pid::from_raw(pid);
+    if process_start_time(pid as u32) != Some(start_time) || getpgid(Some(pgid)) != Ok(pgid) {
+        tracing::warn!(
+            "Process {} no longer belongs to the run, leaving it alone",
+            pid
+        );
+        return Ok(false);
+    }
+    match killpg(pgid, Signal::SIGTERM) {
+        // Already gone
+        Err(Errno::ESRCH) => return Ok(false),
+        result => result?,
+    }
+
+    let exited = tokio::time::timeout(grace, async {
+        while killpg(pgid, None).is_ok() {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .is_ok();
+    // The PID cannot be reused while the group still exists, so a different leader only
+    // shows up once the whole group exited right after the last check
+    let leader_replaced = process_start_time(pid as u32).is_some_and(|time| time != start_time);
+    if !exited && !leader_replaced {
+        match killpg(pgid, Signal::SIGKILL) {
+            Err(Errno::ESRCH) => {}
+            result => result?,
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        app_state::{AppState, ExecutionType, RunningExecution},
+        executor::{ExecutorConfig, ExecutorType},
+        executors::claude::StdinFormat,
+        models::{config::Config, project_executor_config::UpdateProjectExecutorConfig},
+        test_fixtures::{create_attempt, create_project, create_task, memory_pool},
+    };
+
+    /// A project with one running coding agent run; returns the state, project and run IDs
+    /// and the run's attempt ID
+    async fn setup_running_run() -> (AppState, Uuid, Uuid, Uuid) {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        let task_id = create_task(&pool, project_id, "t").await;
+        let attempt_id = create_attempt(&pool, task_id, "/tmp/test-worktree").await;
+        let run_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO execution_processes (id, task_attempt_id, process_type, executor_type, status, command, working_directory)
+             VALUES ($1, $2, 'codingagent', 'claude', 'running', 'claude', '/tmp/test-worktree')",
+        )
+        .bind(run_id)
+        .bind(attempt_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let app_state =
+            AppState::new(pool, Arc::new(tokio::sync::RwLock::new(Config::default()))).await;
+        (app_state, project_id, run_id, attempt_id)
+    }
+
+    #[tokio::test]
+    async fn test_claude_followups_use_the_project_stdin_format() {
+        let (app_state, project_id, _, attempt_id) = setup_running_run().await;
+        let pool = &app_state.db_pool;
+        let task_id: Uuid = sqlx::query_scalar("SELECT task_id FROM task_attempts WHERE id = $1")
+            .bind(attempt_id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        ProjectExecutorConfig::upsert(
+            pool,
+            project_id,
+            &UpdateProjectExecutorConfig {
+                executor_type: ExecutorConfig::Claude.to_string(),
+                binary_path: "claude".to_string(),
+                model: None,
+                extra_flags: None,
+                use_plan_mode: false,
+                web_allowlist: None,
+                web_denylist: None,
+                stdin_format: Some(StdinFormat::StructuredJson),
+                auto_resume_on_timeout: false,
+            },
+        )
+        .await
+        .unwrap();
+
+        let executor = ProcessService::claude_followup_executor(
+            pool,
+            task_id,
+            &ExecutorConfig::Claude,
+            "session".to_string(),
+            "Continue".to_string(),
+        )
+        .await;
+        assert_eq!(executor.stdin_format, StdinFormat::StructuredJson);
+    }
+
+    #[test]
+    fn test_followup_request_round_trips_through_operation_params() {
+        use crate::executors::claude::{ToolResult, ToolResultKind};
+
+        let request = FollowupRequest {
+            prompt: "Continue".to_string(),
+            context_files: vec!["src/lib.rs".to_string()],
+            tool_results: vec![ToolResult {
+                kind: ToolResultKind::ToolResult,
+                tool_use_id: "toolu_01".to_string(),
+                content: "42 tests passed".to_string(),
+                is_error: false,
+            }],
+        };
+        let operation_params = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(
+            FollowupRequest::from_operation_params(Some(&operation_params)).unwrap(),
+            request
+        );
+    }
+
+    #[test]
+    fn test_malformed_followup_params_are_rejected() {
+        let operation_params = serde_json::json!({
+            "prompt": "Continue",
+            "tool_results": [{ "tool_use_id": 1 }]
+        });
+
+        assert!(matches!(
+            FollowupRequest::from_operation_params(Some(&operation_params)),
+            Err(TaskAttemptError::ValidationError(_))
+        ));
+        assert!(matches!(
+            FollowupRequest::from_operation_params(None),
+            Err(TaskAttemptError::ValidationError(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_timed_out_runs_resume_their_session_once() {
+        use command_group::AsyncCommandGroup;
+
+        use crate::executors::claude::TIMEOUT_RESUME_PROMPT;
+
+        // A repository with one commit and a linked worktree of the `run` branch
+        let repo_dir = tempfile::TempDir::new().unwrap();
+        let worktrees_dir = tempfile::TempDir::new().unwrap();
+        let worktree = worktrees_dir.path().join("run");
+        let repo = git2::Repository::init(repo_dir.path()).unwrap();
+        std::fs::write(repo_dir.path().join("README.md"), "hello").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(std::path::Path::new("README.md")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Test User", "test@example.com").unwrap();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "Initial commit",
+            &tree,
+            &[],
+        )
+        .unwrap();
+        repo.worktree("run", &worktree, None).unwrap();
+
+        let (app_state, project_id, run_id, attempt_id) = setup_running_run().await;
+        let pool = app_state.db_pool.clone();
+        sqlx::query("UPDATE projects SET git_repo_path = $2 WHERE id = $1")
+            .bind(project_id)
+            .bind(repo_dir.path().to_string_lossy().to_string())
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE task_attempts SET worktree_path = $2, branch = 'run' WHERE id = $1")
+            .bind(attempt_id)
+            .bind(worktree.to_string_lossy().to_string())
+            .execute(&pool)
+            .await
+            .unwrap();
+        ExecutorSession::create(
+            &pool,
+            &CreateExecutorSession {
+                task_attempt_id: attempt_id,
+                execution_process_id: run_id,
+                prompt: Some("Do the task".to_string()),
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        ExecutorSession::update_session_id(&pool, run_id, "session-1")
+            .await
+            .unwrap();
+        ProjectExecutorConfig::upsert(
+            &pool,
+            project_id,
+            &UpdateProjectExecutorConfig {
+                executor_type: ExecutorConfig::Claude.to_string(),
+                binary_path: "true".to_string(),
+                model: None,
+                extra_flags: None,
+                use_plan_mode: false,
+                web_allowlist: None,
+                web_denylist: None,
+                stdin_format: None,
+                auto_resume_on_timeout: true,
+            },
+        )
+        .await
+        .unwrap();
+
+        let child = tokio::process::Command::new("sleep")
+            .arg("30")
+            .group_spawn()
+            .unwrap();
+        app_state
+            .add_running_execution(
+                run_id,
+                RunningExecution {
+                    task_attempt_id: attempt_id,
+                    pool: pool.clone(),
+                    _execution_type: ExecutionType::CodingAgent,
+                    child,
+                    _permit: None,
+                },
+            )
+            .await;
+
+        ProcessService::schedule_timeout(&pool, &app_state, run_id, 0);
+        let resumed = tokio::time::timeout(Duration::from_secs(20), async {
+            loop {
+                let sessions = ExecutorSession::find_by_task_attempt_id(&pool, attempt_id)
+                    .await
+                    .unwrap();
+                if let Some(session) = sessions
+                    .into_iter()
+                    .find(|session| session.execution_process_id != run_id)
+                {
+                    break session;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await
+        .expect("the timed out run was not resumed");
+
+        assert_eq!(resumed.prompt.as_deref(), Some(TIMEOUT_RESUME_PROMPT));
+        let run = ExecutionProcess::find_by_id(&pool, run_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(run.status, ExecutionProcessStatus::Killed);
+
+        // The retry budget is spent, so a second timeout is not resumed
+        ExecutorSession::update_session_id(&pool, resumed.execution_process_id, "session-1")
+            .await
+            .unwrap();
+        assert!(!ProcessService::resume_after_timeout(
+            &pool,
+            &app_state,
+            resumed.execution_process_id
+        )
+        .await
+        .unwrap());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_timed_out_runs_are_not_resumed_unless_enabled() {
+        use command_group::AsyncCommandGroup;
+
+        let (app_state, _, run_id, attempt_id) = setup_running_run().await;
+        let pool = app_state.db_pool.clone();
+        ExecutorSession::create(
+            &pool,
+            &CreateExecutorSession {
+                task_attempt_id: attempt_id,
+                execution_process_id: run_id,
+                prompt: Some("Do the task".to_string()),
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        ExecutorSession::update_session_id(&pool, run_id, "session-1")
+            .await
+            .unwrap();
+
+        let child = tokio::process::Command::new("sleep")
+            .arg("30")
+            .group_spawn()
+            .unwrap();
+        app_state
+            .add_running_execution(
+                run_id,
+                RunningExecution {
+                    task_attempt_id: attempt_id,
+                    pool: pool.clone(),
+                    _execution_type: ExecutionType::CodingAgent,
+                    child,
+                    _permit: None,
+                },
+            )
+            .await;
+
+        ProcessService::schedule_timeout(&pool, &app_state, run_id, 0);
+        tokio::time::timeout(Duration::from_secs(20), async {
+            loop {
+                let run = ExecutionProcess::find_by_id(&pool, run_id)
+                    .await
+                    .unwrap()
+                    .unwrap();
+                if run.status == ExecutionProcessStatus::Killed {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await
+        .expect("the timed out run was not killed");
+
+        // Give a resume, if any, the chance to record its session
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let sessions = ExecutorSession::find_by_task_attempt_id(&pool, attempt_id)
+            .await
+            .unwrap();
+        assert_eq!(sessions.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_only_coding_agent_runs_are_audited_as_started() {
+        let (app_state, _, _, attempt_id) = setup_running_run().await;
+        let pool = &app_state.db_pool;
+        let (run_id, setup_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+        for (process_id, executor_type, process_type) in [
+            (
+                run_id,
+                ExecutorType::CodingAgent(ExecutorConfig::Echo),
+                ExecutionProcessType::CodingAgent,
+            ),
+            (
+                setup_id,
+                ExecutorType::SetupScript("npm install".to_string()),
+                ExecutionProcessType::SetupScript,
+            ),
+        ] {
+            ProcessService::create_execution_process_record(
+                pool,
+                "user-1",
+                attempt_id,
+                process_id,
+                &executor_type,
+                process_type,
+                "/tmp/test-worktree",
+            )
+            .await
+            .unwrap();
+        }
+
+        let audit = AuditLogger::new(pool.clone());
+        let entries = audit.entries(run_id, 50).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].entity_type, "run");
+        assert_eq!(entries[0].action, RUN_STARTED);
+        assert_eq!(entries[0].actor_id, "user-1");
+        assert!(entries[0].before_json.is_none());
+        assert!(audit.entries(setup_id, 50).await.unwrap().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_cancel_running_for_project_kills_the_run() {
+        use command_group::AsyncCommandGroup;
+        use nix::{sys::signal::kill, unistd::Pid};
+
+        let (app_state, project_id, run_id, attempt_id) = setup_running_run().await;
+        let child = tokio::process::Command::new("sleep")
+            .arg("30")
+            .group_spawn()
+            .unwrap();
+        let pid = child.id().unwrap();
+        ExecutionProcess::set_pid(&app_state.db_pool, run_id, pid, process_start_time(pid))
+            .await
+            .unwrap();
+        app_state
+            .add_running_execution(
+                run_id,
+                RunningExecution {
+                    task_attempt_id: attempt_id,
+                    pool: app_state.db_pool.clone(),
+                    _execution_type: ExecutionType::CodingAgent,
+                    child,
+                    _permit: None,
+                },
+            )
+            .await;
+
+        let outcome = ProcessService::cancel_running_for_project(&app_state, project_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            outcome,
+            CancelledRuns {
+                cancelled: vec![run_id],
+                failed_to_cancel: vec![],
+            }
+        );
+
+        let run = ExecutionProcess::find_by_id(&app_state.db_pool, run_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(run.status, ExecutionProcessStatus::Killed);
+        assert!(kill(Pid::from_raw(pid as i32), None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_reports_untracked_runs_without_a_pid_as_failed() {
+        // Not registered with this server, e.g. left running across a restart
+        let (app_state, project_id, run_id, _) = setup_running_run().await;
+
+        let outcome = ProcessService::cancel_running_for_project(&app_state, project_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            outcome,
+            CancelledRuns {
+                cancelled: vec![],
+                failed_to_cancel: vec![run_id],
+            }
+        );
+
+        let run = ExecutionProcess::find_by_id(&app_state.db_pool, run_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(run.status, ExecutionProcessStatus::Running);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_cancel_signals_the_recorded_process_group_of_untracked_runs() {
+        use command_group::AsyncCommandGroup;
+
+        let (app_state, project_id, run_id, _) = setup_running_run().await;
+        // Left running by an earlier server
+        let mut orphan = tokio::process::Command::new("sleep")
+            .arg("30")
+            .group_spawn()
+            .unwrap();
+        let pid = orphan.id().unwrap();
+        ExecutionProcess::set_pid(&app_state.db_pool, run_id, pid, process_start_time(pid))
+            .await
+            .unwrap();
+
+        let outcome = ProcessService::cancel_running_for_project(&app_state, project_id)
+            .await
+            .unwrap();
+        assert_eq!(outcome.cancelled, vec![run_id]);
+
+        let status = tokio::time::timeout(Duration::from_secs(1), orphan.wait())
+            .await
+            .expect("the orphaned run was not signalled")
+            .unwrap();
+        assert!(!status.success());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_cancel_does_not_signal_reused_pids() {
+        use nix::{sys::signal::kill, unistd::Pid};
+
+        let (app_state, project_id, run_id, _) = setup_running_run().await;
+        // A process the recorded PID now belongs to, started after the run's process
+        let mut unrelated = tokio::process::Command::new("sleep")
+            .arg("30")
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        let pid = unrelated.id().unwrap();
+        let run_start_time = process_start_time(pid).map_or(1, |time| time - 1);
+        ExecutionProcess::set_pid(&app_state.db_pool, run_id, pid, Some(run_start_time))
+            .await
+            .unwrap();
+
+        let outcome = ProcessService::cancel_running_for_project(&app_state, project_id)
+            .await
+            .unwrap();
+        assert_eq!(outcome.failed_to_cancel, vec![run_id]);
+        assert!(kill(Pid::from_raw(pid as i32), None).is_ok());
+        unrelated.kill().await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_cancel_terminates_runs_concurrently() {
+        use command_group::AsyncCommandGroup;
+
+        let (app_state, project_id, first_run_id, attempt_id) = setup_running_run().await;
+        let second_run_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO execution_processes (id, task_attempt_id, process_type, executor_type, status, command, working_directory)
+             VALUES ($1, $2, 'codingagent', 'claude', 'running', 'claude', '/tmp/test-worktree')",
+        )
+        .bind(second_run_id)
+        .bind(attempt_id)
+        .execute(&app_state.db_pool)
+        .await
+        .unwrap();
+        for run_id in [first_run_id, second_run_id] {
+            // Ignores SIGTERM, so each run takes the whole grace period
+            let child = tokio::process::Command::new("sh")
+                .args(["-c", "trap '' TERM; sleep 30"])
+                .group_spawn()
+                .unwrap();
+            app_state
+                .add_running_execution(
+                    run_id,
+                    RunningExecution {
+                        task_attempt_id: attempt_id,
+                        pool: app_state.db_pool.clone(),
+                        _execution_type: ExecutionType::CodingAgent,
+                        child,
+                        _permit: None,
+                    },
+                )
+                .await;
+        }
+
+        let started = std::time::Instant::now();
+        let outcome = ProcessService::cancel_running_for_project(&app_state, project_id)
+            .await
+            .unwrap();
+        assert_eq!(outcome.cancelled.len(), 2);
+        assert!(started.elapsed() < 2 * CANCEL_GRACE_PERIOD);
+    }
+}
@@ -0,0 +1,291 @@
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+
+use crate::models::ApiResponse;
+
+/// Environment variable overriding how much free space, in MiB, the worktree directory needs
+pub const MIN_FREE_DISK_ENV: &str = "VIBE_MIN_FREE_DISK_MB";
+const DEFAULT_MIN_FREE_DISK_MB: u64 = 1024;
+/// Each check gets this long, so the whole report stays under two seconds
+const CHECK_TIMEOUT: Duration = Duration::from_millis(1500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum SystemCheckStatus {
+    Ok,
+    Degraded,
+    Fail,
+}
+
+/// Outcome of one check of the server's dependencies
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SystemCheck {
+    pub name: String,
+    pub status: SystemCheckStatus,
+    pub latency_ms: i64,
+    pub detail: Option<String>,
+}
+
+/// Every check, with the worst of their statuses
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SystemHealthReport {
+    pub status: SystemCheckStatus,
+    pub checks: Vec<SystemCheck>,
+}
+
+impl SystemHealthReport {
+    pub fn new(checks: Vec<SystemCheck>) -> Self {
+        let status = checks
+            .iter()
+            .map(|check| check.status)
+            .max()
+            .unwrap_or(SystemCheckStatus::Ok);
+        Self { status, checks }
+    }
+
+    /// 200 when every check is ok, 207 when some are degraded and 503 when any failed
+    pub fn http_status(&self) -> StatusCode {
+        match self.status {
+            SystemCheckStatus::Ok => StatusCode::OK,
+            SystemCheckStatus::Degraded => StatusCode::MULTI_STATUS,
+            SystemCheckStatus::Fail => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+impl IntoResponse for SystemHealthReport {
+    fn into_response(self) -> Response {
+        let status = self.http_status();
+        let message = match self.status {
+            SystemCheckStatus::Ok => "Service is healthy",
+            SystemCheckStatus::Degraded => "Service is degraded",
+            SystemCheckStatus::Fail => "Service is unhealthy",
+        };
+        let body = ApiResponse {
+            success: self.status != SystemCheckStatus::Fail,
+            data: Some(self),
+            message: Some(message.to_string()),
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+/// Run every check concurrently
+pub async fn check_system_health(pool: &SqlitePool, worktree_dir: &Path) -> SystemHealthReport {
+    let min_free_bytes = min_free_disk_mb_from_env() * 1024 * 1024;
+    let (database, claude, disk) = tokio::join!(
+        run_check("database", CHECK_TIMEOUT, check_database(pool)),
+        run_check("claude_cli", CHECK_TIMEOUT, check_claude_cli()),
+        run_check(
+            "disk_space",
+            CHECK_TIMEOUT,
+            check_disk_space(worktree_dir.to_path_buf(), min_free_bytes)
+        ),
+    );
+    SystemHealthReport::new(vec![database, claude, disk])
+}
+
+/// Time a check; one that does not finish within `timeout` is reported as degraded
+pub async fn run_check<F>(name: &str, timeout: Duration, check: F) -> SystemCheck
+where
+    F: Future<Output = (SystemCheckStatus, Option<String>)>,
+{
+    let started = Instant::now();
+    let (status, detail) = match tokio::time::timeout(timeout, check).await {
+        Ok(outcome) => outcome,
+        Err(_) => (
+            SystemCheckStatus::Degraded,
+            Some(format!("Timed out after {} ms", timeout.as_millis())),
+        ),
+    };
+    SystemCheck {
+        name: name.to_string(),
+        status,
+        latency_ms: started.elapsed().as_millis() as i64,
+        detail,
+    }
+}
+
+pub async fn check_database(pool: &SqlitePool) -> (SystemCheckStatus, Option<String>) {
+    match sqlx::query("SELECT 1").execute(pool).await {
+        Ok(_) => (SystemCheckStatus::Ok, None),
+        Err(e) => (SystemCheckStatus::Fail, Some(e.to_string())),
+    }
+}
+
+/// Other executors still work without Claude, so a missing CLI only degrades the service
+async fn check_claude_cli() -> (SystemCheckStatus, Option<String>) {
+    match crate::executors::claude::detect_local_claude_code().await {
+        Some(path) => (SystemCheckStatus::Ok, Some(path)),
+        None => (
+            SystemCheckStatus::Degraded,
+            Some("claude-code was not found".to_string()),
+        ),
+    }
+}
+
+/// Fails when the file system holding `dir` has less than `min_free_bytes` available.
+/// `dir` may not exist yet, in which case its closest existing ancestor is checked.
+pub async fn check_disk_space(
+    dir: PathBuf,
+    min_free_bytes: u64,
+) -> (SystemCheckStatus, Option<String>) {
+    let available = tokio::task::spawn_blocking(move || {
+        let existing = dir.ancestors().find(|path| path.exists()).unwrap_or(&dir);
+        available_bytes(existing)
+    })
+    .await;
+
+    match available {
+        Ok(Ok(available)) => {
+            let detail = Some(format!("{} MiB available", available / (1024 * 1024)));
+            if available < min_free_bytes {
+                (SystemCheckStatus::Fail, detail)
+            } else {
+                (SystemCheckStatus::Ok, detail)
+            }
+        }
+        Ok(Err(e)) => (SystemCheckStatus::Degraded, Some(e.to_string())),
+        Err(e) => (SystemCheckStatus::Degraded, Some(e.to_string())),
+    }
+}
+
+#[cfg(unix)]
+#[allow(clippy::useless_conversion)]
+fn available_bytes(path: &Path) -> std::io::Result<u64> {
+    let stats = nix::sys::statvfs::statvfs(path).map_err(std::io::Error::from)?;
+    Ok(u64::from(stats.blocks_available()) * u64::from(stats.fragment_size()))
+}
+
+#[cfg(not(unix))]
+fn available_bytes(_path: &Path) -> std::io::Result<u64> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Free disk space is only checked on Unix",
+    ))
+}
+
+/// Read the threshold from `VIBE_MIN_FREE_DISK_MB`, falling back to 1 GiB when it is unset
+/// or not a number
+fn min_free_disk_mb_from_env() -> u64 {
+    match std::env::var(MIN_FREE_DISK_ENV) {
+        Ok(value) => value.trim().parse().unwrap_or_else(|_| {
+            tracing::warn!(
+                "Ignoring invalid {}={:?}, using {}",
+                MIN_FREE_DISK_ENV,
+                value,
+                DEFAULT_MIN_FREE_DISK_MB
+            );
+            DEFAULT_MIN_FREE_DISK_MB
+        }),
+        Err(_) => DEFAULT_MIN_FREE_DISK_MB,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn check(name: &str, status: SystemCheckStatus) -> SystemCheck {
+        SystemCheck {
+            name: name.to_string(),
+            status,
+            latency_ms: 0,
+            detail: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_healthy_checks_respond_ok() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let dir = TempDir::new().unwrap();
+
+        let database = run_check("database", CHECK_TIMEOUT, check_database(&pool)).await;
+        let disk = run_check(
+            "disk_space",
+            CHECK_TIMEOUT,
+            check_disk_space(dir.path().join("not-created-yet"), 0),
+        )
+        .await;
+        assert_eq!(database.status, SystemCheckStatus::Ok);
+        assert_eq!(disk.status, SystemCheckStatus::Ok);
+
+        let response = SystemHealthReport::new(vec![database, disk]).into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_database_responds_unavailable() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        pool.close().await;
+
+        let database = run_check("database", CHECK_TIMEOUT, check_database(&pool)).await;
+        assert_eq!(database.status, SystemCheckStatus::Fail);
+
+        let report =
+            SystemHealthReport::new(vec![check("disk_space", SystemCheckStatus::Ok), database]);
+        assert_eq!(
+            report.into_response().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+
+    #[tokio::test]
+    async fn test_low_disk_space_responds_unavailable() {
+        let dir = TempDir::new().unwrap();
+
+        let disk = run_check(
+            "disk_space",
+            CHECK_TIMEOUT,
+            check_disk_space(dir.path().to_path_buf(), u64::MAX),
+        )
+        .await;
+        assert_eq!(disk.status, SystemCheckStatus::Fail);
+
+        let report = SystemHealthReport::new(vec![check("database", SystemCheckStatus::Ok), disk]);
+        assert_eq!(
+            report.into_response().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_check_responds_multi_status() {
+        let slow = run_check("claude_cli", Duration::from_millis(10), async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            (SystemCheckStatus::Ok, None)
+        })
+        .await;
+        assert_eq!(slow.status, SystemCheckStatus::Degraded);
+        assert!(slow.latency_ms < 1000);
+
+        let report = SystemHealthReport::new(vec![check("database", SystemCheckStatus::Ok), slow]);
+        assert_eq!(report.status, SystemCheckStatus::Degraded);
+        assert_eq!(report.into_response().status(), StatusCode::MULTI_STATUS);
+    }
+}
@@ -0,0 +1,172 @@
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+pub use crate::executor::COST_LIMIT_EXCEEDED_EVENT;
+use crate::models::webhook::{Webhook, WebhookDelivery, WebhookDeliveryStatus};
+
+pub const TASK_COMPLETED_EVENT: &str = "task.completed";
+pub const TASK_FAILED_EVENT: &str = "task.failed";
+
+/// Events a webhook can subscribe to
+pub const WEBHOOK_EVENTS: &[&str] = &[
+    TASK_COMPLETED_EVENT,
+    TASK_FAILED_EVENT,
+    COST_LIMIT_EXCEEDED_EVENT,
+];
+
+pub const SIGNATURE_HEADER: &str = "X-Vibe-Signature";
+pub const EVENT_HEADER: &str = "X-Vibe-Event";
+
+/// Retries after the first failed attempt, each waiting twice as long as the previous one
+const MAX_RETRIES: u32 = 3;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Posts signed task events to the webhooks subscribed to them
+pub struct WebhookService;
+
+impl WebhookService {
+    /// Queue `event` for every webhook of the project subscribed to it and deliver it in
+    /// the background. Failures are logged; they never affect the caller.
+    pub async fn dispatch(pool: &SqlitePool, project_id: Uuid, event: &str, data: Value) {
+        let webhooks = match Webhook::find_subscribed(pool, project_id, event).await {
+            Ok(webhooks) => webhooks,
+            Err(e) => {
+                tracing::error!("Failed to load webhooks for project {}: {}", project_id, e);
+                return;
+            }
+        };
+        if webhooks.is_empty() {
+            return;
+        }
+
+        let payload = json!({
+            "event": event,
+            "project_id": project_id,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "data": data,
+        })
+        .to_string();
+
+        for webhook in webhooks {
+            let delivery = match WebhookDelivery::create(pool, webhook.id, event, &payload).await {
+                Ok(delivery) => delivery,
+                Err(e) => {
+                    tracing::error!("Failed to queue webhook delivery for {}: {}", webhook.id, e);
+                    continue;
+                }
+            };
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                Self::deliver(&pool, &webhook, &delivery).await;
+            });
+        }
+    }
+
+    /// Post the delivery, retrying with exponential backoff until it succeeds or the
+    /// retries run out
+    async fn deliver(pool: &SqlitePool, webhook: &Webhook, delivery: &WebhookDelivery) {
+        let client = match reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build() {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::error!("Failed to build webhook client: {}", e);
+                return;
+            }
+        };
+        let signature = format!("sha256={}", sign(&webhook.secret, &delivery.payload));
+        let mut backoff = INITIAL_BACKOFF;
+
+        for attempt in 0..=MAX_RETRIES {
+            let result = client
+                .post(&webhook.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(SIGNATURE_HEADER, &signature)
+                .header(EVENT_HEADER, &delivery.event)
+                .body(delivery.payload.clone())
+                .send()
+                .await;
+
+            let (response_status, error) = match result {
+                Ok(response) if response.status().is_success() => {
+                    (Some(response.status().as_u16() as i64), None)
+                }
+                Ok(response) => (
+                    Some(response.status().as_u16() as i64),
+                    Some(format!("Endpoint responded with {}", response.status())),
+                ),
+                Err(e) => (None, Some(e.to_string())),
+            };
+
+            let is_last = attempt == MAX_RETRIES;
+            let status = match (&error, is_last) {
+                (None, _) => WebhookDeliveryStatus::Delivered,
+                (Some(_), true) => WebhookDeliveryStatus::Failed,
+                (Some(_), false) => WebhookDeliveryStatus::Pending,
+            };
+            if let Err(e) = WebhookDelivery::record_attempt(
+                pool,
+                delivery.id,
+                status,
+                response_status,
+                error.as_deref(),
+            )
+            .await
+            {
+                tracing::error!("Failed to record webhook delivery {}: {}", delivery.id, e);
+            }
+
+            match error {
+                None => return,
+                Some(error) if is_last => {
+                    tracing::warn!(
+                        "Giving up on webhook delivery {} to {}: {}",
+                        delivery.id,
+                        webhook.url,
+                        error
+                    );
+                }
+                Some(error) => {
+                    tracing::debug!(
+                        "Webhook delivery {} failed, retrying in {:?}: {}",
+                        delivery.id,
+                        backoff,
+                        error
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+            }
+        }
+    }
+}
+
+/// Hex-encoded HMAC-SHA256 of `body` keyed with the webhook secret
+pub fn sign(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body.as_bytes());
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_matches_rfc_4231_vector() {
+        assert_eq!(
+            sign("Jefe", "what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_sign_depends_on_secret() {
+        assert_ne!(sign("a", "{}"), sign("b", "{}"));
+    }
+}
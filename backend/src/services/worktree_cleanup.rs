@@ -0,0 +1,271 @@
+use std::{path::Path, time::Duration};
+
+use sqlx::SqlitePool;
+use tokio::{process::Command, time::interval};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    app_state::AppState,
+    models::{
+        task_attempt::TaskAttempt,
+        task_run::{TaskRun, WorktreeCleanupCandidate, WorktreeCleanupStatus},
+    },
+};
+
+/// Environment variable overriding how many hours a finished run's worktree is kept
+pub const WORKTREE_RETENTION_HOURS_ENV: &str = "VIBE_WORKTREE_RETENTION_HOURS";
+const DEFAULT_WORKTREE_RETENTION_HOURS: u64 = 24;
+const POLL_INTERVAL: Duration = Duration::from_secs(10 * 60); // Check every 10 minutes
+
+/// Service that removes the worktrees of finished task runs once they are past retention
+pub struct WorktreeCleanup {
+    pool: SqlitePool,
+    retention: Duration,
+}
+
+impl WorktreeCleanup {
+    pub fn new(pool: SqlitePool, retention: Duration) -> Self {
+        Self { pool, retention }
+    }
+
+    /// Read the retention period from `VIBE_WORKTREE_RETENTION_HOURS`, falling back to 24
+    /// hours when it is unset or not a number
+    pub fn retention_from_env() -> Duration {
+        let hours = match std::env::var(WORKTREE_RETENTION_HOURS_ENV) {
+            Ok(value) => value.trim().parse().unwrap_or_else(|_| {
+                warn!(
+                    "Ignoring invalid {}={:?}, using {}",
+                    WORKTREE_RETENTION_HOURS_ENV, value, DEFAULT_WORKTREE_RETENTION_HOURS
+                );
+                DEFAULT_WORKTREE_RETENTION_HOURS
+            }),
+            Err(_) => DEFAULT_WORKTREE_RETENTION_HOURS,
+        };
+        Duration::from_secs(hours * 60 * 60)
+    }
+
+    /// Clean up the worktrees of every task database until the process exits
+    pub async fn start(app_state: AppState, retention: Duration) {
+        info!("Starting worktree cleanup with retention {:?}", retention);
+
+        let mut interval = interval(POLL_INTERVAL);
+
+        loop {
+            interval.tick().await;
+            match app_state.task_pools().await {
+                Ok(pools) => {
+                    for pool in pools {
+                        Self::new(pool, retention).run_once().await;
+                    }
+                }
+                Err(e) => error!("Failed to open task databases: {}", e),
+            }
+        }
+    }
+
+    /// Remove the worktree of every run that is due and record the outcome. Returns the
+    /// number of runs handled; runs already handled are never picked up again.
+    pub async fn run_once(&self) -> usize {
+        let candidates = match TaskRun::find_due_for_worktree_cleanup(
+            &self.pool,
+            self.retention.as_secs() as i64,
+        )
+        .await
+        {
+            Ok(candidates) => candidates,
+            Err(e) => {
+                error!("Failed to find runs due for worktree cleanup: {}", e);
+                return 0;
+            }
+        };
+
+        for candidate in &candidates {
+            let status = self.cleanup(candidate).await;
+            if let Err(e) = TaskRun::set_cleanup_status(&self.pool, candidate.id, status).await {
+                error!(
+                    "Failed to record worktree cleanup of run {}: {}",
+                    candidate.id, e
+                );
+            }
+        }
+        candidates.len()
+    }
+
+    async fn cleanup(&self, candidate: &WorktreeCleanupCandidate) -> WorktreeCleanupStatus {
+        let status = remove_worktree(
+            &candidate.git_repo_path,
+            Path::new(&candidate.worktree_path),
+        )
+        .await;
+        if status != WorktreeCleanupStatus::Error {
+            // Follow-ups recreate the worktree on demand
+            if let Err(e) =
+                TaskAttempt::mark_worktree_deleted(&self.pool, candidate.task_attempt_id).await
+            {
+                error!(
+                    "Failed to mark worktree as deleted for attempt {}: {}",
+                    candidate.task_attempt_id, e
+                );
+            }
+        }
+        status
+    }
+}
+
+/// Run `git worktree remove --force` for the worktree if it still exists
+pub async fn remove_worktree(repo_path: &str, worktree_path: &Path) -> WorktreeCleanupStatus {
+    if !worktree_path.exists() {
+        debug!("Worktree {} is already gone", worktree_path.display());
+        return WorktreeCleanupStatus::NotFound;
+    }
+
+    let output = Command::new("git")
+        .args(["worktree", "remove", "--force"])
+        .arg(worktree_path)
+        .current_dir(repo_path)
+        .output()
+        .await;
+
+    match output {
+        Ok(output) if output.status.success() => {
+            info!("Removed worktree {}", worktree_path.display());
+            WorktreeCleanupStatus::Success
+        }
+        Ok(output) => {
+            error!(
+                "git worktree remove failed for {}: {}",
+                worktree_path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            WorktreeCleanupStatus::Error
+        }
+        Err(e) => {
+            error!(
+                "Failed to run git worktree remove for {}: {}",
+                worktree_path.display(),
+                e
+            );
+            WorktreeCleanupStatus::Error
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use git2::Repository;
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::test_fixtures::{create_attempt, create_project, create_task, memory_pool};
+
+    /// A repository with one commit and a linked worktree at `worktree`
+    fn create_repo_with_worktree(repo_dir: &Path, worktree: &Path) {
+        let repo = Repository::init(repo_dir).unwrap();
+        std::fs::write(repo_dir.join("README.md"), "hello").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("README.md")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Test User", "test@example.com").unwrap();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "Initial commit",
+            &tree,
+            &[],
+        )
+        .unwrap();
+        repo.worktree("run", worktree, None).unwrap();
+    }
+
+    /// A database holding one attempt with a coding agent run that finished two days ago
+    async fn setup(repo_path: &Path, worktree_path: &Path) -> (SqlitePool, Uuid, Uuid) {
+        let pool = memory_pool().await;
+        let project_id = create_project(&pool).await;
+        sqlx::query("UPDATE projects SET git_repo_path = $1 WHERE id = $2")
+            .bind(repo_path.to_string_lossy().to_string())
+            .bind(project_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let task_id = create_task(&pool, project_id, "task").await;
+        let attempt_id = create_attempt(&pool, task_id, &worktree_path.to_string_lossy()).await;
+        let run_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO execution_processes (id, task_attempt_id, process_type, status, command, working_directory, completed_at)
+             VALUES ($1, $2, 'codingagent', 'completed', 'claude', $3, $4)",
+        )
+        .bind(run_id)
+        .bind(attempt_id)
+        .bind(worktree_path.to_string_lossy().to_string())
+        .bind(chrono::Utc::now() - chrono::Duration::days(2))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        (pool, attempt_id, run_id)
+    }
+
+    async fn cleanup_status(pool: &SqlitePool, run_id: Uuid) -> Option<WorktreeCleanupStatus> {
+        sqlx::query_scalar("SELECT cleanup_status FROM execution_processes WHERE id = $1")
+            .bind(run_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_removes_worktree_once() {
+        let repo_dir = TempDir::new().unwrap();
+        let worktrees_dir = TempDir::new().unwrap();
+        let worktree = worktrees_dir.path().join("run");
+        create_repo_with_worktree(repo_dir.path(), &worktree);
+        let (pool, attempt_id, run_id) = setup(repo_dir.path(), &worktree).await;
+        let cleanup = WorktreeCleanup::new(pool.clone(), Duration::from_secs(24 * 60 * 60));
+
+        assert_eq!(cleanup.run_once().await, 1);
+        assert!(!worktree.exists());
+        assert_eq!(
+            cleanup_status(&pool, run_id).await,
+            Some(WorktreeCleanupStatus::Success)
+        );
+        let deleted: bool =
+            sqlx::query_scalar("SELECT worktree_deleted FROM task_attempts WHERE id = $1")
+                .bind(attempt_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert!(deleted);
+
+        // Runs already cleaned up are left alone
+        assert_eq!(cleanup.run_once().await, 0);
+        assert_eq!(
+            cleanup_status(&pool, run_id).await,
+            Some(WorktreeCleanupStatus::Success)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_worktree_is_recorded_as_not_found() {
+        let repo_dir = TempDir::new().unwrap();
+        let worktrees_dir = TempDir::new().unwrap();
+        let worktree = worktrees_dir.path().join("run");
+        create_repo_with_worktree(repo_dir.path(), &worktree);
+        std::fs::remove_dir_all(&worktree).unwrap();
+        let (pool, _, run_id) = setup(repo_dir.path(), &worktree).await;
+
+        // Nothing is due while the run is inside the retention period
+        let long_retention =
+            WorktreeCleanup::new(pool.clone(), Duration::from_secs(7 * 24 * 60 * 60));
+        assert_eq!(long_retention.run_once().await, 0);
+        assert_eq!(cleanup_status(&pool, run_id).await, None);
+
+        let cleanup = WorktreeCleanup::new(pool.clone(), Duration::from_secs(24 * 60 * 60));
+        assert_eq!(cleanup.run_once().await, 1);
+        assert_eq!(
+            cleanup_status(&pool, run_id).await,
+            Some(WorktreeCleanupStatus::NotFound)
+        );
+    }
+}
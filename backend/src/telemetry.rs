@@ -0,0 +1,276 @@
+//! OpenTelemetry traces of task runs
+//!
+//! A coding agent run is traced as a `task_run` span with the children `executor.spawn`,
+//! `executor.wait` and `normalize_logs`. Every span carries the run's `task.id`, `run.id`,
+//! `executor.type` and `executor.command_hash`. Spans are exported over OTLP when
+//! [`OTLP_ENDPOINT_ENV`] is set.
+
+use opentelemetry_otlp::WithExportConfig;
+use tracing::{field::Empty, Span, Subscriber};
+use tracing_subscriber::{
+    filter::LevelFilter,
+    registry::{LookupSpan, Registry},
+    Layer,
+};
+use uuid::Uuid;
+
+use crate::executor_events::command_hash;
+
+/// Environment variable with the address of the OTLP collector spans are exported to
+pub const OTLP_ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+
+/// Service name spans are reported under
+const SERVICE_NAME: &str = "vibe-kanban";
+
+const COMMAND_HASH_FIELD: &str = "executor.command_hash";
+
+/// A layer exporting spans to the collector at [`OTLP_ENDPOINT_ENV`], or `None` when the
+/// variable is not set or the exporter cannot be created
+pub fn otlp_layer<S>() -> Option<impl Layer<S>>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let endpoint = std::env::var(OTLP_ENDPOINT_ENV)
+        .ok()
+        .filter(|endpoint| !endpoint.trim().is_empty())?;
+
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint.clone()),
+        )
+        .with_trace_config(opentelemetry_sdk::trace::config().with_resource(
+            opentelemetry_sdk::Resource::new(vec![opentelemetry::KeyValue::new(
+                "service.name",
+                SERVICE_NAME,
+            )]),
+        ))
+        .install_batch(opentelemetry_sdk::runtime::Tokio);
+    match tracer {
+        Ok(tracer) => Some(
+            tracing_opentelemetry::layer()
+                .with_tracer(tracer)
+                .with_filter(LevelFilter::INFO),
+        ),
+        Err(e) => {
+            // Logging is not set up yet, the layer is part of it
+            eprintln!("Failed to export traces to {}: {}", endpoint, e);
+            None
+        }
+    }
+}
+
+/// Export the spans that are still buffered
+pub fn shutdown() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
+
+/// Attributes shared by the spans of a run, kept on its `task_run` span
+#[derive(Debug, Clone)]
+struct RunAttributes {
+    task_id: Uuid,
+    run_id: Uuid,
+    executor_type: String,
+    /// Set once the executor has built its command
+    command_hash: Option<String>,
+}
+
+/// A child span of `$parent` carrying the attributes of its run
+macro_rules! run_span {
+    ($parent:expr, $attributes:expr, $name:literal $(, $($fields:tt)*)?) => {
+        tracing::info_span!(
+            parent: $parent,
+            $name,
+            task.id = %$attributes.task_id,
+            run.id = %$attributes.run_id,
+            executor.type = $attributes.executor_type.as_str(),
+            executor.command_hash = $attributes.command_hash.as_deref(),
+            $($($fields)*)?
+        )
+    };
+}
+
+/// The root span of a coding agent run. Spawning and registering the run for monitoring
+/// should happen inside it.
+pub fn task_run_span(task_id: Uuid, run_id: Uuid, executor_type: &str) -> Span {
+    let span = tracing::info_span!(
+        "task_run",
+        task.id = %task_id,
+        run.id = %run_id,
+        executor.type = executor_type,
+        executor.command_hash = Empty,
+    );
+    span.with_subscriber(|(id, dispatch)| {
+        if let Some(span) = dispatch
+            .downcast_ref::<Registry>()
+            .and_then(|registry| registry.span(id))
+        {
+            span.extensions_mut().insert(RunAttributes {
+                task_id,
+                run_id,
+                executor_type: executor_type.to_string(),
+                command_hash: None,
+            });
+        }
+    });
+    span
+}
+
+/// Attributes of the run `span` belongs to
+fn run_attributes(span: &Span) -> Option<RunAttributes> {
+    span.with_subscriber(|(id, dispatch)| {
+        dispatch
+            .downcast_ref::<Registry>()?
+            .span(id)?
+            .scope()
+            .find_map(|span| span.extensions().get::<RunAttributes>().cloned())
+    })
+    .flatten()
+}
+
+/// Span from building the executor's command until its process started; disabled outside
+/// of runs
+pub fn spawn_span() -> Span {
+    let parent = Span::current();
+    match run_attributes(&parent) {
+        Some(attributes) => run_span!(&parent, attributes, "executor.spawn"),
+        None => Span::none(),
+    }
+}
+
+/// Record the hash of the command an executor is about to start on the current span and the
+/// run it belongs to
+pub fn record_spawned_command(command: &str) {
+    let hash = command_hash(command);
+    Span::current().with_subscriber(|(id, dispatch)| {
+        let Some(span) = dispatch
+            .downcast_ref::<Registry>()
+            .and_then(|registry| registry.span(id))
+        else {
+            return;
+        };
+        for span in span.scope() {
+            if let Some(attributes) = span.extensions_mut().get_mut::<RunAttributes>() {
+                attributes.command_hash = Some(hash.clone());
+            }
+            let fields = span.metadata().fields();
+            if let Some(field) = fields.field(COMMAND_HASH_FIELD) {
+                let value = hash.as_str();
+                let values = [(&field, Some(&value as &dyn tracing::Value))];
+                dispatch.record(
+                    &span.id(),
+                    &tracing::span::Record::new(&fields.value_set(&values)),
+                );
+            }
+        }
+    });
+}
+
+/// Spans of a run that outlive its spawn: the root, until the run is done with, and the wait
+/// for its process to exit
+#[derive(Debug)]
+pub struct RunTrace {
+    root: Span,
+    wait: Option<Span>,
+}
+
+impl RunTrace {
+    /// Start waiting for the run the current span belongs to; `None` outside of runs
+    pub fn start_wait() -> Option<Self> {
+        let root = Span::current();
+        let attributes = run_attributes(&root)?;
+        let wait = run_span!(&root, attributes, "executor.wait", exit_code = Empty);
+        Some(Self {
+            root,
+            wait: Some(wait),
+        })
+    }
+
+    /// End the wait once the process exited; signals leave `exit_code` unset
+    pub fn record_exit(&mut self, exit_code: Option<i64>) {
+        if let Some(wait) = self.wait.take() {
+            wait.record("exit_code", exit_code);
+        }
+    }
+
+    /// Span to enter while the run's output is normalized
+    pub fn normalize_logs_span(&self) -> Span {
+        match run_attributes(&self.root) {
+            Some(attributes) => run_span!(&self.root, attributes, "normalize_logs"),
+            None => Span::none(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        sync::{Arc, Mutex},
+    };
+
+    use opentelemetry::trace::TracerProvider as _;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    /// Collects what the stdout exporter writes
+    #[derive(Debug, Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_run_spans_are_exported_with_their_names() {
+        let buffer = SharedBuffer::default();
+        let exporter = opentelemetry_stdout::SpanExporter::builder()
+            .with_writer(buffer.clone())
+            .build();
+        let provider = opentelemetry_sdk::trace::TracerProvider::builder()
+            .with_simple_exporter(exporter)
+            .build();
+        let subscriber = Registry::default()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+        let command = "npx -y @anthropic-ai/claude-code@latest -p";
+        tracing::subscriber::with_default(subscriber, || {
+            let root = task_run_span(Uuid::new_v4(), Uuid::new_v4(), "claude");
+            let _entered = root.enter();
+            spawn_span().in_scope(|| record_spawned_command(command));
+
+            let mut trace = RunTrace::start_wait().expect("inside a run");
+            trace.record_exit(Some(0));
+            trace.normalize_logs_span().in_scope(|| {});
+        });
+        // Spans outside of runs are not traced
+        assert!(spawn_span().is_disabled());
+
+        provider.force_flush();
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        for name in [
+            "task_run",
+            "executor.spawn",
+            "executor.wait",
+            "normalize_logs",
+        ] {
+            assert!(
+                output.contains(&format!("\"name\":\"{}\"", name)),
+                "span {} missing from {}",
+                name,
+                output
+            );
+        }
+        assert!(output.contains(&command_hash(command)));
+        assert!(output.contains("exit_code"));
+    }
+}
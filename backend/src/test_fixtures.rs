@@ -0,0 +1,58 @@
+//! Database rows the test modules build their scenarios on
+//!
+//! Each helper inserts the bare minimum its table requires; tests set any other column they
+//! depend on themselves.
+
+use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+use uuid::Uuid;
+
+/// An in-memory database with every migration applied. A single connection keeps every
+/// query on the same database, as each connection to `sqlite::memory:` opens its own.
+pub(crate) async fn memory_pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+    pool
+}
+
+/// Insert a project named "project" with a repository path of its own
+pub(crate) async fn create_project(pool: &SqlitePool) -> Uuid {
+    let project_id = Uuid::new_v4();
+    sqlx::query("INSERT INTO projects (id, name, git_repo_path) VALUES ($1, $2, $3)")
+        .bind(project_id)
+        .bind("project")
+        .bind(format!("/repos/{}", project_id))
+        .execute(pool)
+        .await
+        .unwrap();
+    project_id
+}
+
+/// Insert a task of the project
+pub(crate) async fn create_task(pool: &SqlitePool, project_id: Uuid, title: &str) -> Uuid {
+    let task_id = Uuid::new_v4();
+    sqlx::query("INSERT INTO tasks (id, project_id, title) VALUES ($1, $2, $3)")
+        .bind(task_id)
+        .bind(project_id)
+        .bind(title)
+        .execute(pool)
+        .await
+        .unwrap();
+    task_id
+}
+
+/// Insert an attempt at the task working in `worktree_path`
+pub(crate) async fn create_attempt(pool: &SqlitePool, task_id: Uuid, worktree_path: &str) -> Uuid {
+    let attempt_id = Uuid::new_v4();
+    sqlx::query("INSERT INTO task_attempts (id, task_id, worktree_path) VALUES ($1, $2, $3)")
+        .bind(attempt_id)
+        .bind(task_id)
+        .bind(worktree_path)
+        .execute(pool)
+        .await
+        .unwrap();
+    attempt_id
+}
@@ -2,6 +2,9 @@ use std::{env, sync::OnceLock};
 
 use directories::ProjectDirs;
 
+pub mod database;
+pub mod encryption;
+pub mod log_compression;
 pub mod path;
 pub mod shell;
 pub mod text;
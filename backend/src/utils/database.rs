@@ -0,0 +1,96 @@
+//! Connection pool for the application database
+//!
+//! The database runs in WAL mode so API requests can read while an executor writes logs.
+
+use std::time::Duration;
+
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions, SqliteSynchronous},
+    SqlitePool,
+};
+
+const MAX_CONNECTIONS: u32 = 8;
+const MIN_CONNECTIONS: u32 = 2;
+const BUSY_TIMEOUT: Duration = Duration::from_millis(5000);
+/// Negative sizes are in KiB, so this is 64 MiB per connection
+const CACHE_SIZE: &str = "-65536";
+
+/// Open the pool, applying the connection-level pragmas to every connection it opens, then
+/// switch the database to WAL mode
+pub async fn connect_pool(options: SqliteConnectOptions) -> Result<SqlitePool, sqlx::Error> {
+    connect_pool_with(options, SqlitePoolOptions::new()).await
+}
+
+/// Like [`connect_pool`], building the pool from `pool_options`, e.g. to set up each
+/// connection as it is opened
+pub async fn connect_pool_with(
+    options: SqliteConnectOptions,
+    pool_options: SqlitePoolOptions,
+) -> Result<SqlitePool, sqlx::Error> {
+    let options = options
+        .synchronous(SqliteSynchronous::Normal)
+        .busy_timeout(BUSY_TIMEOUT)
+        .pragma("cache_size", CACHE_SIZE);
+    let pool = pool_options
+        .max_connections(MAX_CONNECTIONS)
+        .min_connections(MIN_CONNECTIONS)
+        .connect_with(options)
+        .await?;
+    configure_sqlite_pool(&pool).await?;
+    Ok(pool)
+}
+
+/// Put the database in WAL mode and tune the connection this runs on. WAL mode is stored in
+/// the database file, so it holds for every connection once set.
+pub async fn configure_sqlite_pool(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL; PRAGMA cache_size=-65536; PRAGMA busy_timeout=5000;",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_configure_enables_wal_mode() {
+        let dir = TempDir::new().unwrap();
+        let url = format!(
+            "sqlite://{}",
+            dir.path().join("db.sqlite").to_string_lossy()
+        );
+        let options = SqliteConnectOptions::from_str(&url)
+            .unwrap()
+            .create_if_missing(true);
+        let pool = SqlitePool::connect_with(options.clone()).await.unwrap();
+
+        configure_sqlite_pool(&pool).await.unwrap();
+
+        let mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(mode, "wal");
+        pool.close().await;
+
+        // Connections opened later see the same mode
+        let pool = connect_pool(options).await.unwrap();
+        let mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(mode, "wal");
+        let synchronous: i64 = sqlx::query_scalar("PRAGMA synchronous")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(synchronous, 1);
+    }
+}
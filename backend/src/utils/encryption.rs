@@ -0,0 +1,138 @@
+//! Encryption of secrets stored in the database
+//!
+//! Values are sealed with AES-256-GCM under a key derived from the `VIBE_ENCRYPTION_KEY`
+//! server secret. A sealed value is the random 12-byte nonce followed by the ciphertext and
+//! its authentication tag. Values sealed with associated data, such as the row they belong
+//! to, only open with the same associated data.
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    Aes256Gcm, Key, Nonce,
+};
+use sha2::{Digest, Sha256};
+
+/// Environment variable holding the server secret the encryption key is derived from
+pub const ENCRYPTION_KEY_ENV: &str = "VIBE_ENCRYPTION_KEY";
+
+const NONCE_LEN: usize = 12;
+
+#[derive(Debug)]
+pub enum EncryptionError {
+    /// `VIBE_ENCRYPTION_KEY` is not set
+    MissingKey,
+    /// The value was sealed under another key or has been tampered with
+    Decrypt,
+}
+
+impl std::fmt::Display for EncryptionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncryptionError::MissingKey => write!(f, "{} is not set", ENCRYPTION_KEY_ENV),
+            EncryptionError::Decrypt => write!(f, "Failed to decrypt value"),
+        }
+    }
+}
+
+impl std::error::Error for EncryptionError {}
+
+/// Key derived from a server secret of any length
+pub struct SecretKey(Key<Aes256Gcm>);
+
+impl SecretKey {
+    pub fn derive(secret: &str) -> Self {
+        let digest = Sha256::digest(secret.as_bytes());
+        Self(*Key::<Aes256Gcm>::from_slice(&digest))
+    }
+
+    /// Derive the key from `VIBE_ENCRYPTION_KEY`
+    pub fn from_env() -> Result<Self, EncryptionError> {
+        match std::env::var(ENCRYPTION_KEY_ENV) {
+            Ok(secret) if !secret.is_empty() => Ok(Self::derive(&secret)),
+            _ => Err(EncryptionError::MissingKey),
+        }
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        self.encrypt_with_aad(plaintext, &[])
+    }
+
+    /// Seal `plaintext` so that it only opens with the same `aad`
+    pub fn encrypt_with_aad(&self, plaintext: &[u8], aad: &[u8]) -> Vec<u8> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = Aes256Gcm::new(&self.0)
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .expect("AES-GCM encryption of an in-memory buffer cannot fail");
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend(ciphertext);
+        sealed
+    }
+
+    pub fn decrypt(&self, sealed: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        self.decrypt_with_aad(sealed, &[])
+    }
+
+    /// Open a value sealed by [`SecretKey::encrypt_with_aad`] with the same `aad`
+    pub fn decrypt_with_aad(&self, sealed: &[u8], aad: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        if sealed.len() < NONCE_LEN {
+            return Err(EncryptionError::Decrypt);
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        Aes256Gcm::new(&self.0)
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .map_err(|_| EncryptionError::Decrypt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let key = SecretKey::derive("server secret");
+        let sealed = key.encrypt(b"sk-test-123");
+
+        assert!(!sealed
+            .windows(b"sk-test-123".len())
+            .any(|window| window == b"sk-test-123"));
+        assert_eq!(key.decrypt(&sealed).unwrap(), b"sk-test-123");
+        // A fresh nonce is used for every value
+        assert_ne!(key.encrypt(b"sk-test-123"), sealed);
+    }
+
+    #[test]
+    fn test_decrypt_rejects_other_keys_and_tampering() {
+        let sealed = SecretKey::derive("server secret").encrypt(b"value");
+
+        assert!(SecretKey::derive("other secret").decrypt(&sealed).is_err());
+
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        let key = SecretKey::derive("server secret");
+        assert!(key.decrypt(&tampered).is_err());
+        assert!(key.decrypt(&sealed[..4]).is_err());
+    }
+
+    #[test]
+    fn test_decrypt_requires_the_same_associated_data() {
+        let key = SecretKey::derive("server secret");
+        let sealed = key.encrypt_with_aad(b"value", b"row-1");
+
+        assert_eq!(key.decrypt_with_aad(&sealed, b"row-1").unwrap(), b"value");
+        assert!(key.decrypt_with_aad(&sealed, b"row-2").is_err());
+        assert!(key.decrypt(&sealed).is_err());
+    }
+}
@@ -0,0 +1,104 @@
+//! Compression of executor logs stored in the database
+//!
+//! Finished processes keep their raw output as a zstd frame instead of plain text; the
+//! NDJSON most executors emit compresses to a small fraction of its size.
+
+use std::io;
+
+/// zstd level used for stored logs, a good trade-off between speed and size
+const LOG_COMPRESSION_LEVEL: i32 = 3;
+
+/// How much a log shrank when it was compressed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompressionStats {
+    pub original_bytes: usize,
+    pub compressed_bytes: usize,
+    /// `original_bytes / compressed_bytes`, so higher is better
+    pub ratio: f64,
+}
+
+/// Compress a log for storage
+pub fn compress_log(log: &str) -> io::Result<(Vec<u8>, CompressionStats)> {
+    let compressed = zstd::encode_all(log.as_bytes(), LOG_COMPRESSION_LEVEL)?;
+    let stats = CompressionStats {
+        original_bytes: log.len(),
+        compressed_bytes: compressed.len(),
+        ratio: log.len() as f64 / compressed.len().max(1) as f64,
+    };
+    Ok((compressed, stats))
+}
+
+/// Decompress a log written by `compress_log`
+pub fn decompress_log(compressed: &[u8]) -> io::Result<String> {
+    let bytes = zstd::decode_all(compressed)?;
+    String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Check that `compressed` is exactly one complete zstd frame, reading only its frame and
+/// block headers
+pub fn validate_log_frame(compressed: &[u8]) -> io::Result<()> {
+    match zstd::zstd_safe::find_frame_compressed_size(compressed) {
+        Ok(size) if size == compressed.len() => Ok(()),
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Data after the end of the zstd frame",
+        )),
+        Err(code) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            zstd::zstd_safe::get_error_name(code),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Claude stream-json output of roughly 50 KB, with multi-byte characters
+    fn sample_log() -> String {
+        let mut log = String::new();
+        let mut turn = 0;
+        while log.len() < 50 * 1024 {
+            log.push_str(&format!(
+                r#"{{"type":"assistant","message":{{"role":"assistant","content":[{{"type":"tool_use","id":"toolu_{turn:04}","name":"Read","input":{{"file_path":"/repo/src/module_{turn}.rs"}}}}]}},"session_id":"3f1c2a"}}"#
+            ));
+            log.push('\n');
+            log.push_str(&format!(
+                r#"{{"type":"user","message":{{"role":"user","content":[{{"type":"tool_result","tool_use_id":"toolu_{turn:04}","content":"fn handler_{turn}() -> Résultat<(), Error> {{ todo!(\"✓ step {turn}\") }}"}}]}}}}"#
+            ));
+            log.push('\n');
+            turn += 1;
+        }
+        log
+    }
+
+    #[test]
+    fn test_compressed_log_round_trips() {
+        let log = sample_log();
+
+        let (compressed, stats) = compress_log(&log).unwrap();
+        assert_eq!(stats.original_bytes, log.len());
+        assert_eq!(stats.compressed_bytes, compressed.len());
+        assert!(stats.ratio > 1.0);
+
+        let restored = decompress_log(&compressed).unwrap();
+        assert_eq!(restored.as_bytes(), log.as_bytes());
+    }
+
+    #[test]
+    fn test_decompress_rejects_garbage() {
+        assert!(decompress_log(b"not a zstd frame").is_err());
+    }
+
+    #[test]
+    fn test_validate_checks_the_frame_without_decompressing() {
+        let (compressed, _) = compress_log(&sample_log()).unwrap();
+        assert!(validate_log_frame(&compressed).is_ok());
+
+        assert!(validate_log_frame(b"not a zstd frame").is_err());
+        assert!(validate_log_frame(&compressed[..compressed.len() / 2]).is_err());
+        let mut trailing = compressed.clone();
+        trailing.extend_from_slice(b"junk");
+        assert!(validate_log_frame(&trailing).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 
 /// Convert absolute paths to relative paths based on worktree path
 /// This is a robust implementation that handles symlinks and edge cases
@@ -69,6 +69,27 @@ pub fn make_path_relative(path: &str, worktree_path: &str) -> String {
     }
 }
 
+/// Join a user-supplied relative path onto `base`, refusing anything that could escape it.
+/// Absolute paths and `..` components are rejected; returns `None` in that case.
+pub fn safe_join(base: &Path, rel_path: &str) -> Option<PathBuf> {
+    let rel = Path::new(rel_path);
+    let mut joined = base.to_path_buf();
+    let mut has_file = false;
+
+    for component in rel.components() {
+        match component {
+            Component::Normal(part) => {
+                joined.push(part);
+                has_file = true;
+            }
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+
+    has_file.then_some(joined)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -93,4 +114,24 @@ mod tests {
             "/other/path/file.js"
         );
     }
+
+    #[test]
+    fn test_safe_join() {
+        let base = Path::new("/tmp/test-worktree");
+
+        assert_eq!(
+            safe_join(base, "src/main.rs"),
+            Some(PathBuf::from("/tmp/test-worktree/src/main.rs"))
+        );
+        assert_eq!(
+            safe_join(base, "./README.md"),
+            Some(PathBuf::from("/tmp/test-worktree/README.md"))
+        );
+
+        assert_eq!(safe_join(base, "../secret"), None);
+        assert_eq!(safe_join(base, "src/../../secret"), None);
+        assert_eq!(safe_join(base, "/etc/passwd"), None);
+        assert_eq!(safe_join(base, ""), None);
+        assert_eq!(safe_join(base, "."), None);
+    }
 }
@@ -1,5 +1,11 @@
 //! Cross-platform shell command utilities
 
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    sync::OnceLock,
+};
+
 /// Returns the appropriate shell command and argument for the current platform.
 ///
 /// Returns (shell_program, shell_arg) where:
@@ -17,3 +23,140 @@ pub fn get_shell_command() -> (&'static str, &'static str) {
         }
     }
 }
+
+/// A shell found on the current system
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShellInfo {
+    pub path: PathBuf,
+    pub name: String,
+    pub version: Option<String>,
+    /// Whether the shell exposes bash's `PIPESTATUS` array
+    pub supports_pipestatus: bool,
+}
+
+/// Shells probed by [`detect_available_shells`], in order of preference
+const SHELL_CANDIDATES: &[&str] = &["bash", "zsh", "sh", "dash", "pwsh", "powershell"];
+
+/// Cache for shell detection, the set of installed shells does not change at runtime
+static AVAILABLE_SHELLS: OnceLock<Vec<ShellInfo>> = OnceLock::new();
+
+/// Returns every usable shell on the system, ordered by preference (bash first).
+///
+/// Detection runs once per process and the result is cached.
+pub fn detect_available_shells() -> Vec<ShellInfo> {
+    AVAILABLE_SHELLS
+        .get_or_init(|| {
+            SHELL_CANDIDATES
+                .iter()
+                .filter_map(|name| {
+                    let path = find_in_path(name)?;
+                    Some(ShellInfo {
+                        version: probe_shell_version(name, &path),
+                        supports_pipestatus: *name == "bash",
+                        name: name.to_string(),
+                        path,
+                    })
+                })
+                .collect()
+        })
+        .clone()
+}
+
+/// Whether the shell returned by [`get_shell_command`] supports `PIPESTATUS`
+pub fn current_shell_supports_pipestatus() -> bool {
+    let (shell, _) = get_shell_command();
+    detect_available_shells()
+        .iter()
+        .any(|info| info.name == shell && info.supports_pipestatus)
+}
+
+/// Locate an executable by name in the directories listed in `PATH`
+pub fn find_in_path(name: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths).find_map(|dir| {
+        let candidate = dir.join(name);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        if cfg!(windows) {
+            let exe = dir.join(format!("{}.exe", name));
+            if exe.is_file() {
+                return Some(exe);
+            }
+        }
+        None
+    })
+}
+
+/// Quote `arg` for a POSIX shell command line. Arguments made only of characters the
+/// shell does not interpret are left as they are.
+pub fn shell_quote(arg: &str) -> String {
+    let is_plain = !arg.is_empty()
+        && arg.chars().all(|c| {
+            c.is_ascii_alphanumeric()
+                || matches!(c, '_' | '-' | '.' | '/' | ',' | ':' | '@' | '+' | '=')
+        });
+    if is_plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// Read the first line of a shell's version output. Shells without a version flag
+/// (`sh`, `dash`) report `None`.
+fn probe_shell_version(name: &str, path: &Path) -> Option<String> {
+    let mut command = Command::new(path);
+    match name {
+        "bash" | "zsh" => {
+            command.arg("--version");
+        }
+        "pwsh" | "powershell" => {
+            command.args([
+                "-NoProfile",
+                "-Command",
+                "$PSVersionTable.PSVersion.ToString()",
+            ]);
+        }
+        _ => return None,
+    }
+
+    let output = command.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_available_shells_only_bash_supports_pipestatus() {
+        let shells = detect_available_shells();
+        for shell in &shells {
+            assert!(shell.path.is_file());
+            assert_eq!(shell.supports_pipestatus, shell.name == "bash");
+        }
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("docs/requirements.md"), "docs/requirements.md");
+        assert_eq!(shell_quote("test output.txt"), "'test output.txt'");
+        assert_eq!(shell_quote("it's; rm -rf ~"), "'it'\\''s; rm -rf ~'");
+        assert_eq!(shell_quote(""), "''");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_detect_available_shells_finds_sh() {
+        let shells = detect_available_shells();
+        assert!(shells.iter().any(|shell| shell.name == "sh"));
+    }
+}
@@ -2,15 +2,32 @@ use std::{
     collections::HashMap,
     path::{Path, PathBuf},
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
 use git2::{Error as GitError, Repository, WorktreeAddOptions};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// Environment variable overriding how long a released branch worktree is kept, in seconds
+pub const WORKTREE_RETENTION_ENV: &str = "VIBE_WORKTREE_RETENTION_SECS";
+const DEFAULT_WORKTREE_RETENTION: Duration = Duration::from_secs(60 * 60);
 
 // Global synchronization for worktree creation to prevent race conditions
 lazy_static::lazy_static! {
     static ref WORKTREE_CREATION_LOCKS: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>> =
         Arc::new(Mutex::new(HashMap::new()));
+    static ref BRANCH_WORKTREES: tokio::sync::Mutex<HashMap<(Uuid, String), BranchWorktree>> =
+        tokio::sync::Mutex::new(HashMap::new());
+}
+
+/// A worktree handed out for a project branch, kept so later runs on the branch reuse it
+struct BranchWorktree {
+    repo_path: String,
+    path: PathBuf,
+    /// Bumped on every acquire and release so a pending removal can tell it went stale
+    generation: u64,
+    released: bool,
 }
 
 pub struct WorktreeManager;
@@ -48,6 +65,150 @@ impl WorktreeManager {
         Self::recreate_worktree_internal(repo_path, branch_name, worktree_path).await
     }
 
+    /// Get the worktree for a project branch, creating it at `worktree_path` on first use.
+    /// Later calls for the same branch get the cached path back, whatever location they
+    /// suggest, and cancel a removal still pending from `release`.
+    pub async fn acquire(
+        project_id: Uuid,
+        repo_path: &str,
+        branch_name: &str,
+        worktree_path: PathBuf,
+    ) -> Result<PathBuf, GitError> {
+        let key = (project_id, branch_name.to_string());
+        let mut worktrees = BRANCH_WORKTREES.lock().await;
+
+        if let Some(entry) = worktrees.get_mut(&key) {
+            if entry.path.exists() {
+                entry.generation += 1;
+                entry.released = false;
+                debug!(
+                    "Reusing worktree for branch {} at {}",
+                    branch_name,
+                    entry.path.display()
+                );
+                return Ok(entry.path.clone());
+            }
+        }
+
+        // Not created yet, or removed from disk behind our back: (re)create it where it
+        // was before so the cached path stays stable
+        let (path, generation) = match worktrees.get(&key) {
+            Some(entry) => (entry.path.clone(), entry.generation + 1),
+            None => (worktree_path, 0),
+        };
+        Self::ensure_worktree_exists(repo_path.to_string(), branch_name.to_string(), path.clone())
+            .await?;
+
+        worktrees.insert(
+            key,
+            BranchWorktree {
+                repo_path: repo_path.to_string(),
+                path: path.clone(),
+                generation,
+                released: false,
+            },
+        );
+        Ok(path)
+    }
+
+    /// Mark a project branch's worktree as no longer in use. It is removed with
+    /// `git worktree remove --force` once the retention period from
+    /// `VIBE_WORKTREE_RETENTION_SECS` passes, unless the branch is acquired again first.
+    pub async fn release(
+        project_id: Uuid,
+        branch_name: &str,
+    ) -> Option<tokio::task::JoinHandle<()>> {
+        Self::release_after(project_id, branch_name, Self::retention_from_env()).await
+    }
+
+    /// Like `release` with an explicit retention period. Returns the handle of the pending
+    /// removal, or `None` when the branch has no cached worktree.
+    pub async fn release_after(
+        project_id: Uuid,
+        branch_name: &str,
+        retention: Duration,
+    ) -> Option<tokio::task::JoinHandle<()>> {
+        let key = (project_id, branch_name.to_string());
+        let generation = {
+            let mut worktrees = BRANCH_WORKTREES.lock().await;
+            let entry = worktrees.get_mut(&key)?;
+            entry.generation += 1;
+            entry.released = true;
+            entry.generation
+        };
+
+        Some(tokio::spawn(async move {
+            tokio::time::sleep(retention).await;
+
+            let mut worktrees = BRANCH_WORKTREES.lock().await;
+            let still_released = worktrees
+                .get(&key)
+                .is_some_and(|entry| entry.released && entry.generation == generation);
+            if !still_released {
+                return;
+            }
+            if let Some(entry) = worktrees.remove(&key) {
+                match Self::force_remove_worktree(&entry.repo_path, &entry.path).await {
+                    Ok(()) => info!(
+                        "Removed released worktree for branch {} at {}",
+                        key.1,
+                        entry.path.display()
+                    ),
+                    Err(e) => warn!(
+                        "Failed to remove released worktree {}: {}",
+                        entry.path.display(),
+                        e
+                    ),
+                }
+            }
+        }))
+    }
+
+    /// Read the retention period from `VIBE_WORKTREE_RETENTION_SECS`, falling back to one
+    /// hour when it is unset or not a number
+    pub fn retention_from_env() -> Duration {
+        match std::env::var(WORKTREE_RETENTION_ENV) {
+            Ok(value) => match value.trim().parse::<u64>() {
+                Ok(secs) => Duration::from_secs(secs),
+                Err(_) => {
+                    warn!(
+                        "Ignoring invalid {}={:?}, using {:?}",
+                        WORKTREE_RETENTION_ENV, value, DEFAULT_WORKTREE_RETENTION
+                    );
+                    DEFAULT_WORKTREE_RETENTION
+                }
+            },
+            Err(_) => DEFAULT_WORKTREE_RETENTION,
+        }
+    }
+
+    /// Run `git worktree remove --force` for a worktree of the given repository
+    async fn force_remove_worktree(repo_path: &str, worktree_path: &Path) -> Result<(), GitError> {
+        let repo_path_owned = repo_path.to_string();
+        let worktree_path_owned = worktree_path.to_path_buf();
+
+        tokio::task::spawn_blocking(move || {
+            let output = std::process::Command::new("git")
+                .args(["worktree", "remove", "--force"])
+                .arg(&worktree_path_owned)
+                .current_dir(&repo_path_owned)
+                .output()
+                .map_err(|e| {
+                    GitError::from_str(&format!("Failed to run git worktree remove: {}", e))
+                })?;
+
+            if !output.status.success() {
+                return Err(GitError::from_str(&format!(
+                    "git worktree remove failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )));
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| GitError::from_str(&format!("Task join error: {}", e)))?
+    }
+
     /// Internal worktree recreation function (always recreates)
     async fn recreate_worktree_internal(
         repo_path: String,
@@ -416,6 +577,35 @@ impl WorktreeManager {
         Ok(())
     }
 
+    /// List the linked worktrees of a repository, i.e. every worktree except the main one
+    pub async fn list_linked_worktrees(repo_path: &str) -> Result<Vec<PathBuf>, GitError> {
+        let repo_path_owned = repo_path.to_string();
+
+        tokio::task::spawn_blocking(move || {
+            let output = std::process::Command::new("git")
+                .args(["worktree", "list", "--porcelain"])
+                .current_dir(&repo_path_owned)
+                .output()
+                .map_err(|e| {
+                    GitError::from_str(&format!("Failed to run git worktree list: {}", e))
+                })?;
+
+            if !output.status.success() {
+                return Err(GitError::from_str(&format!(
+                    "git worktree list failed in {}: {}",
+                    repo_path_owned,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )));
+            }
+
+            Ok(parse_linked_worktrees(&String::from_utf8_lossy(
+                &output.stdout,
+            )))
+        })
+        .await
+        .map_err(|e| GitError::from_str(&format!("Task join error: {}", e)))?
+    }
+
     /// Try to infer the git repository path from a worktree
     async fn infer_git_repo_path(worktree_path: &Path) -> Option<String> {
         // Try using git rev-parse --git-common-dir from within the worktree
@@ -476,3 +666,159 @@ impl WorktreeManager {
         .map_err(|e| GitError::from_str(&format!("Task join error: {}", e)))?
     }
 }
+
+/// Parse `git worktree list --porcelain` output into the paths of the linked worktrees.
+/// The first record always describes the main worktree and is skipped.
+fn parse_linked_worktrees(porcelain: &str) -> Vec<PathBuf> {
+    porcelain
+        .split("\n\n")
+        .skip(1)
+        .filter_map(|record| {
+            record
+                .lines()
+                .find_map(|line| line.strip_prefix("worktree "))
+                .map(PathBuf::from)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// A repository with one commit on `main` and a `feature` branch pointing at it
+    fn create_test_repo() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        std::fs::write(temp_dir.path().join("README.md"), "hello").unwrap();
+
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("README.md")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let commit_id = repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "Initial commit",
+                &tree,
+                &[],
+            )
+            .unwrap();
+        repo.branch("feature", &repo.find_commit(commit_id).unwrap(), false)
+            .unwrap();
+
+        temp_dir
+    }
+
+    #[tokio::test]
+    async fn test_acquire_reuses_worktree_for_same_branch() {
+        let repo_dir = create_test_repo();
+        let worktrees_dir = TempDir::new().unwrap();
+        let repo_path = repo_dir.path().to_string_lossy().to_string();
+        let project_id = Uuid::new_v4();
+
+        let first = WorktreeManager::acquire(
+            project_id,
+            &repo_path,
+            "feature",
+            worktrees_dir.path().join("first"),
+        )
+        .await
+        .unwrap();
+        let second = WorktreeManager::acquire(
+            project_id,
+            &repo_path,
+            "feature",
+            worktrees_dir.path().join("second"),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(first, second);
+        assert!(first.join("README.md").exists());
+        assert!(!worktrees_dir.path().join("second").exists());
+        let linked = WorktreeManager::list_linked_worktrees(&repo_path)
+            .await
+            .unwrap();
+        assert_eq!(linked.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_release_removes_worktree_after_retention() {
+        let repo_dir = create_test_repo();
+        let worktrees_dir = TempDir::new().unwrap();
+        let repo_path = repo_dir.path().to_string_lossy().to_string();
+        let project_id = Uuid::new_v4();
+
+        let path = WorktreeManager::acquire(
+            project_id,
+            &repo_path,
+            "feature",
+            worktrees_dir.path().join("feature"),
+        )
+        .await
+        .unwrap();
+
+        WorktreeManager::release_after(project_id, "feature", Duration::ZERO)
+            .await
+            .unwrap()
+            .await
+            .unwrap();
+
+        assert!(!path.exists());
+        assert!(WorktreeManager::list_linked_worktrees(&repo_path)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(
+            WorktreeManager::release_after(project_id, "feature", Duration::ZERO)
+                .await
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_acquire_cancels_pending_removal() {
+        let repo_dir = create_test_repo();
+        let worktrees_dir = TempDir::new().unwrap();
+        let repo_path = repo_dir.path().to_string_lossy().to_string();
+        let project_id = Uuid::new_v4();
+        let worktree_path = worktrees_dir.path().join("feature");
+
+        WorktreeManager::acquire(project_id, &repo_path, "feature", worktree_path.clone())
+            .await
+            .unwrap();
+        let removal =
+            WorktreeManager::release_after(project_id, "feature", Duration::from_millis(50))
+                .await
+                .unwrap();
+        let reused =
+            WorktreeManager::acquire(project_id, &repo_path, "feature", worktree_path.clone())
+                .await
+                .unwrap();
+        removal.await.unwrap();
+
+        assert_eq!(reused, worktree_path);
+        assert!(worktree_path.exists());
+    }
+
+    #[test]
+    fn test_parse_linked_worktrees_skips_main_worktree() {
+        let porcelain = "worktree /repo\nHEAD 1111111111111111111111111111111111111111\nbranch refs/heads/main\n\n\
+                         worktree /tmp/vibe-kanban/task one\nHEAD 2222222222222222222222222222222222222222\nbranch refs/heads/vk-1\n\n\
+                         worktree /tmp/vibe-kanban/task-2\nHEAD 3333333333333333333333333333333333333333\ndetached\nprunable gitdir file points to non-existent location\n\n";
+
+        assert_eq!(
+            parse_linked_worktrees(porcelain),
+            vec![
+                PathBuf::from("/tmp/vibe-kanban/task one"),
+                PathBuf::from("/tmp/vibe-kanban/task-2"),
+            ]
+        );
+        assert!(parse_linked_worktrees("worktree /repo\nbare\n\n").is_empty());
+    }
+}
@@ -0,0 +1,208 @@
+//! Which domains a project's agents may fetch from
+//!
+//! A run that fetches a blocked domain is terminated as soon as the tool use is printed,
+//! and the fetch is shown as a policy error in its normalized logs.
+
+use url::Url;
+
+use crate::{
+    executor::{ActionType, NormalizedEntry, NormalizedEntryType},
+    models::project_executor_config::ProjectExecutorConfig,
+};
+
+/// `tool_use_id` of the errors that replace blocked fetches
+pub const POLICY_TOOL_USE_ID: &str = "policy";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WebPolicy {
+    /// Domains that may be fetched, subdomains included; `None` allows every domain that is
+    /// not denied
+    pub allowlist: Option<Vec<String>>,
+    /// Domains that may not be fetched, subdomains included
+    pub denylist: Vec<String>,
+}
+
+impl WebPolicy {
+    pub fn from_config(config: &ProjectExecutorConfig) -> Self {
+        Self {
+            allowlist: config.web_allowlist.as_ref().map(|list| list.0.clone()),
+            denylist: config.web_denylist.0.clone(),
+        }
+    }
+
+    /// Whether every URL may be fetched
+    pub fn is_unrestricted(&self) -> bool {
+        self.allowlist.is_none() && self.denylist.is_empty()
+    }
+
+    /// Check a URL, returning the domain it is blocked for. URLs without a host cannot be
+    /// checked, so a restrictive policy blocks them whole.
+    pub fn check(&self, url: &str) -> Result<(), String> {
+        if self.is_unrestricted() {
+            return Ok(());
+        }
+        let Some(host) = Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(|host| host.to_ascii_lowercase()))
+        else {
+            return Err(url.to_string());
+        };
+
+        let denied = self
+            .denylist
+            .iter()
+            .any(|domain| matches_domain(&host, domain));
+        let allowed = match &self.allowlist {
+            Some(allowlist) => allowlist.iter().any(|domain| matches_domain(&host, domain)),
+            None => true,
+        };
+        if denied || !allowed {
+            Err(host)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The domain a web fetch entry is blocked for; `None` for allowed fetches and other
+    /// entries
+    pub fn blocked_domain(&self, entry: &NormalizedEntry) -> Option<String> {
+        match &entry.entry_type {
+            NormalizedEntryType::ToolUse {
+                action_type: ActionType::WebFetch { url },
+                ..
+            } => self.check(url).err(),
+            _ => None,
+        }
+    }
+
+    /// Replace the blocked web fetches among `entries` with policy errors
+    pub fn apply(&self, entries: &mut [NormalizedEntry]) {
+        for entry in entries {
+            if let Some(domain) = self.blocked_domain(entry) {
+                *entry = NormalizedEntry {
+                    timestamp: entry.timestamp.take(),
+                    entry_type: NormalizedEntryType::ToolError {
+                        tool_use_id: POLICY_TOOL_USE_ID.to_string(),
+                        exit_code: None,
+                    },
+                    content: format!("Blocked by project policy: {}", domain),
+                    metadata: None,
+                    repeat_count: entry.repeat_count,
+                };
+            }
+        }
+    }
+}
+
+/// Whether `host` is `domain` or one of its subdomains; a leading `*.` on the domain is
+/// ignored
+fn matches_domain(host: &str, domain: &str) -> bool {
+    let domain = domain.trim().trim_start_matches("*.").to_ascii_lowercase();
+    !domain.is_empty()
+        && (host == domain
+            || host
+                .strip_suffix(domain.as_str())
+                .is_some_and(|subdomain| subdomain.ends_with('.')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(allowlist: Option<&[&str]>, denylist: &[&str]) -> WebPolicy {
+        let list = |domains: &[&str]| domains.iter().map(|d| d.to_string()).collect();
+        WebPolicy {
+            allowlist: allowlist.map(list),
+            denylist: list(denylist),
+        }
+    }
+
+    fn fetch(url: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: Some("2025-08-26T10:00:00Z".to_string()),
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: "WebFetch".to_string(),
+                action_type: ActionType::WebFetch {
+                    url: url.to_string(),
+                },
+            },
+            content: format!("`{}`", url),
+            metadata: None,
+            repeat_count: 1,
+        }
+    }
+
+    #[test]
+    fn test_allowlist_only() {
+        let policy = policy(Some(&["docs.rs", "github.com"]), &[]);
+        assert!(policy.check("https://docs.rs/tokio").is_ok());
+        assert!(policy.check("https://api.github.com/repos").is_ok());
+        assert_eq!(
+            policy.check("https://evilgithub.com/x"),
+            Err("evilgithub.com".to_string())
+        );
+        assert_eq!(
+            policy.check("https://Example.com/"),
+            Err("example.com".to_string())
+        );
+    }
+
+    #[test]
+    fn test_denylist() {
+        let policy = policy(None, &["pastebin.com", "*.internal.corp"]);
+        assert!(policy.check("https://docs.rs/tokio").is_ok());
+        assert_eq!(
+            policy.check("https://pastebin.com/raw/abc"),
+            Err("pastebin.com".to_string())
+        );
+        assert_eq!(
+            policy.check("http://wiki.internal.corp/page"),
+            Err("wiki.internal.corp".to_string())
+        );
+
+        // The denylist wins over the allowlist
+        let policy = WebPolicy {
+            allowlist: Some(vec!["github.com".to_string()]),
+            ..policy
+        };
+        assert!(policy.check("https://pastebin.com/raw/abc").is_err());
+        assert!(policy.check("https://github.com/").is_ok());
+    }
+
+    #[test]
+    fn test_malformed_url() {
+        let restricted = policy(None, &["pastebin.com"]);
+        assert_eq!(restricted.check("not a url"), Err("not a url".to_string()));
+        assert!(restricted.check("file:///etc/passwd").is_err());
+        // Without a policy nothing is checked
+        assert!(WebPolicy::default().check("not a url").is_ok());
+    }
+
+    #[test]
+    fn test_apply_replaces_blocked_fetches() {
+        let policy = policy(None, &["pastebin.com"]);
+        let mut entries = vec![
+            fetch("https://docs.rs/tokio"),
+            fetch("https://pastebin.com/raw/abc"),
+        ];
+
+        policy.apply(&mut entries);
+
+        assert_eq!(entries[0], fetch("https://docs.rs/tokio"));
+        assert_eq!(
+            entries[1].entry_type,
+            NormalizedEntryType::ToolError {
+                tool_use_id: POLICY_TOOL_USE_ID.to_string(),
+                exit_code: None,
+            }
+        );
+        assert_eq!(
+            entries[1].content,
+            "Blocked by project policy: pastebin.com"
+        );
+        assert_eq!(
+            entries[1].timestamp.as_deref(),
+            Some("2025-08-26T10:00:00Z")
+        );
+    }
+}
@@ -11,10 +11,13 @@ import {
   Edit,
   Eye,
   Globe,
+  Monitor,
+  MoveRight,
   Plus,
   Search,
   Settings,
   Terminal,
+  Trash2,
   User,
 } from 'lucide-react';
 import {
@@ -66,6 +69,12 @@ const getEntryIcon = (entryType: NormalizedEntryType) => {
     if (action_type.action === 'file_write') {
       return <Edit className="h-4 w-4 text-red-600" />;
     }
+    if (action_type.action === 'file_delete') {
+      return <Trash2 className="h-4 w-4 text-red-600" />;
+    }
+    if (action_type.action === 'file_move') {
+      return <MoveRight className="h-4 w-4 text-orange-600" />;
+    }
     if (action_type.action === 'command_run') {
       return <Terminal className="h-4 w-4 text-yellow-600" />;
     }
@@ -81,6 +90,9 @@ const getEntryIcon = (entryType: NormalizedEntryType) => {
     if (action_type.action === 'plan_presentation') {
       return <CheckSquare className="h-4 w-4 text-blue-600" />;
     }
+    if (action_type.action === 'computer_use') {
+      return <Monitor className="h-4 w-4 text-pink-600" />;
+    }
     return <Settings className="h-4 w-4 text-gray-600" />;
   }
   return <Settings className="h-4 w-4 text-gray-400" />;
@@ -100,6 +112,10 @@ const getContentClassName = (entryType: NormalizedEntryType) => {
     return `${baseClasses} text-red-600 font-mono bg-red-50 dark:bg-red-950/20 px-2 py-1 rounded`;
   }
 
+  if (entryType.type === 'debug_trace' || entryType.type === 'timing') {
+    return `${baseClasses} font-mono text-xs text-muted-foreground`;
+  }
+
   // Special styling for TODO lists
   if (
     entryType.type === 'tool_use' &&
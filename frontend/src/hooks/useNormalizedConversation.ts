@@ -142,6 +142,8 @@ const useNormalizedConversation = ({
                   executor_type: 'unknown',
                   prompt: null,
                   summary: null,
+                  stats: null,
+                  metadata: { debug_entry_count: 0 },
                 };
 
                 try {
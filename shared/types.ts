@@ -4,7 +4,20 @@
 
 export type ApiResponse<T> = { success: boolean, data: T | null, message: string | null, };
 
-export type Config = { theme: ThemeMode, executor: ExecutorConfig, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, github_login_acknowledged: boolean, telemetry_acknowledged: boolean, sound_alerts: boolean, sound_file: SoundFile, push_notifications: boolean, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean | null, };
+export type Config = { theme: ThemeMode, executor: ExecutorConfig, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, github_login_acknowledged: boolean, telemetry_acknowledged: boolean, sound_alerts: boolean, sound_file: SoundFile, push_notifications: boolean, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean | null, 
+/**
+ * Check the claude-code binary against `binary_manifest` at startup and refuse to run
+ * Claude if it does not match
+ */
+verify_binary: boolean, 
+/**
+ * Trusted SHA-256 hashes of the claude-code binary, keyed by version
+ */
+binary_manifest: { [key: string]: string }, 
+/**
+ * Which crashed coding agent runs are resumed automatically
+ */
+restart_policy: RestartPolicy, };
 
 export type ThemeMode = "light" | "dark" | "system" | "purple" | "green" | "blue" | "orange" | "red";
 
@@ -22,17 +35,104 @@ export type SoundConstants = { sound_files: Array<SoundFile>, sound_labels: Arra
 
 export type ConfigConstants = { editor: EditorConstants, sound: SoundConstants, };
 
-export type ExecutorConfig = { "type": "echo" } | { "type": "claude" } | { "type": "claude-plan" } | { "type": "amp" } | { "type": "gemini" } | { "type": "setup-script", script: string, } | { "type": "claude-code-router" } | { "type": "charm-opencode" } | { "type": "sst-opencode" };
+export type ExecutorConfig = { "type": "echo" } | { "type": "claude" } | { "type": "claude-plan" } | { "type": "claude-plan-then-execute" } | { "type": "amp" } | { "type": "gemini" } | { "type": "setup-script", script: string, } | { "type": "claude-code-router" } | { "type": "charm-opencode" } | { "type": "sst-opencode" } | { "type": "aider" } | { "type": "mistral" };
 
 export type ExecutorConstants = { executor_types: Array<ExecutorConfig>, executor_labels: Array<string>, };
 
-export type CreateProject = { name: string, git_repo_path: string, use_existing_repo: boolean, setup_script: string | null, dev_script: string | null, };
-
-export type Project = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, created_at: Date, updated_at: Date, };
+export type ValidationReport = { 
+/**
+ * Command the run would execute; empty when the executor does not resolve one up front
+ */
+command_resolved: string, binary_exists: boolean, worktree_accessible: boolean, 
+/**
+ * Whether the task has a title to build the prompt from
+ */
+prompt_non_empty: boolean, all_ok: boolean, };
+
+export type ClaudeBinary = { path: string, version: string, 
+/**
+ * Hex SHA-256 of the binary's contents
+ */
+sha256: string, };
+
+export type StdinFormat = "plain_text" | "structured_json";
+
+export type ToolResult = { type: "tool_result", 
+/**
+ * Id of the `tool_use` block this result answers
+ */
+tool_use_id: string, content: string, is_error: boolean, };
+
+export type RestartPolicy = { 
+/**
+ * Exit codes of transient crashes, by default 137 (SIGKILL), 139 (SIGSEGV) and 1
+ */
+restartable_exit_codes: Array<bigint>, 
+/**
+ * How often one run is restarted before it is left failed
+ */
+max_restarts: number, };
 
-export type ProjectWithBranch = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, current_branch: string | null, created_at: Date, updated_at: Date, };
+export type CreateProject = { name: string, git_repo_path: string, use_existing_repo: boolean, setup_script: string | null, dev_script: string | null, };
 
-export type UpdateProject = { name: string | null, git_repo_path: string | null, setup_script: string | null, dev_script: string | null, };
+export type Project = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, 
+/**
+ * Branch that new task attempt worktrees are created from
+ */
+base_branch: string, 
+/**
+ * Labels given to new tasks that are created without any
+ */
+default_labels: Array<string>, 
+/**
+ * Which tasks continue the project's pinned Claude session
+ */
+session_affinity: SessionAffinity, 
+/**
+ * Session continued by tasks covered by `session_affinity`
+ */
+pinned_session_id: string | null, 
+/**
+ * Every finished coding agent run is committed in its worktree. With this on, successful
+ * runs are committed with `git_commit_template` instead of the run's summary.
+ */
+use_commit_template: boolean, 
+/**
+ * Message of those commits, with `{title}` and `{task_id}` placeholders; `None` uses
+ * the default template
+ */
+git_commit_template: string | null, 
+/**
+ * Seconds a waiting task needs to gain one priority point; `None` never boosts
+ */
+priority_boost_after_seconds: bigint | null, 
+/**
+ * Summarize each finished run with a cheaper model, see `ConversationSummarizer`
+ */
+auto_summarize: boolean, 
+/**
+ * Turns, one user and one assistant entry each, after which a follow-up starts a fresh
+ * Claude session from a summary instead of resuming; `None` always resumes
+ */
+max_context_turns: bigint | null, created_at: Date, updated_at: Date, };
+
+export type ProjectWithBranch = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, base_branch: string, default_labels: Array<string>, session_affinity: SessionAffinity, pinned_session_id: string | null, use_commit_template: boolean, git_commit_template: string | null, priority_boost_after_seconds: bigint | null, auto_summarize: boolean, max_context_turns: bigint | null, current_branch: string | null, created_at: Date, updated_at: Date, };
+
+export type UpdateProject = { name: string | null, git_repo_path: string | null, setup_script: string | null, dev_script: string | null, default_labels?: Array<string>, session_affinity?: SessionAffinity, use_commit_template?: boolean, 
+/**
+ * An empty template restores the default
+ */
+git_commit_template?: string, 
+/**
+ * 0 turns the boost off
+ */
+priority_boost_after_seconds?: bigint, auto_summarize?: boolean, 
+/**
+ * 0 removes the limit
+ */
+max_context_turns?: bigint, };
+
+export type SessionAffinity = "none" | "shared_per_project" | { "shared_per_task_group": string };
 
 export type SearchResult = { path: string, is_file: boolean, match_type: SearchMatchType, };
 
@@ -40,25 +140,502 @@ export type SearchMatchType = "FileName" | "DirectoryName" | "FullPath";
 
 export type GitBranch = { name: string, is_current: boolean, is_remote: boolean, last_commit_date: Date, };
 
-export type CreateBranch = { name: string, base_branch: string | null, };
+export type ProjectGitStatus = { 
+/**
+ * Checked out branch, `None` when HEAD is detached
+ */
+branch: string | null, has_uncommitted_changes: boolean, has_untracked_files: boolean, 
+/**
+ * Linked worktrees, i.e. not counting the main checkout
+ */
+active_worktrees: number, };
 
-export type CreateTask = { project_id: string, title: string, description: string | null, parent_task_attempt: string | null, };
+export type CreateBranch = { name: string, base_branch: string | null, };
 
-export type CreateTaskAndStart = { project_id: string, title: string, description: string | null, parent_task_attempt: string | null, executor: ExecutorConfig | null, };
+export type SetProjectBaseBranch = { base_branch: string, };
+
+export type SetProjectSprint = { sprint_name: string, };
+
+export type ProjectPrompts = { 
+/**
+ * Standing instructions, e.g. "never modify files outside `src/`"
+ */
+system_prompt: string | null, 
+/**
+ * Text placed after the system prompt
+ */
+prompt_prefix: string | null, };
+
+export type UpdateProjectPrompts = { system_prompt?: string, prompt_prefix?: string, };
+
+export type ProjectMcpConfig = { 
+/**
+ * `{"mcpServers": {"<name>": {"command": "...", "args": [...]}}}`; `null` clears it
+ */
+mcp_config_json: string | null, };
+
+export type ProjectBudget = { 
+/**
+ * `None` when the project has no limit
+ */
+monthly_cost_limit_usd: number | null, monthly_cost_usd_used: number, };
+
+export type SetProjectBudget = { 
+/**
+ * `None` removes the limit
+ */
+monthly_cost_limit_usd: number | null, };
+
+export type ProjectExecutorConfig = { project_id: string, 
+/**
+ * Executor the settings apply to, e.g. `claude` or `claude-plan`
+ */
+executor_type: string, 
+/**
+ * Path or command that starts the CLI, e.g. `/opt/claude-1.0/bin/claude`
+ */
+binary_path: string, 
+/**
+ * Passed as `--model`; a task's own model takes precedence
+ */
+model: string | null, 
+/**
+ * Appended verbatim to the command line
+ */
+extra_flags: string | null, use_plan_mode: boolean, 
+/**
+ * Domains web fetches are limited to, subdomains included; `None` allows every domain
+ * that is not denied
+ */
+web_allowlist: Array<string> | null, 
+/**
+ * Domains web fetches may not reach, even when allowlisted
+ */
+web_denylist: Array<string>, 
+/**
+ * How the prompt is written to the CLI's stdin
+ */
+stdin_format: StdinFormat, 
+/**
+ * Resume the session of a run that timed out instead of failing the task
+ */
+auto_resume_on_timeout: boolean, created_at: string, updated_at: string, };
+
+export type UpdateProjectExecutorConfig = { executor_type: string, binary_path: string, model: string | null, extra_flags: string | null, use_plan_mode: boolean, web_allowlist?: Array<string>, web_denylist?: Array<string>, stdin_format?: StdinFormat, auto_resume_on_timeout: boolean, };
+
+export type ProjectApiKey = { id: string, project_id: string, 
+/**
+ * Executor the key is given to, e.g. `claude`
+ */
+executor_type: string, 
+/**
+ * Last characters of the key, e.g. `…a1b2`, or `…****` for keys too short to show any
+ */
+key_hint: string, created_at: string, updated_at: string, };
+
+export type SetProjectApiKey = { api_key: string, };
+
+export type WorktreeGcReason = "no_record" | "task_not_in_progress";
+
+export type WorktreeGcEntry = { path: string, attempt_ids: Array<string>, reason: WorktreeGcReason, 
+/**
+ * False for dry runs and for worktrees that failed to be removed
+ */
+removed: boolean, error: string | null, };
+
+export type ProjectArchive = { schema_version: bigint, project: ArchivedProject, tasks: Array<ArchivedTask>, task_attempts: Array<ArchivedTaskAttempt>, 
+/**
+ * Coding agent runs of the tasks
+ */
+task_runs: Array<ArchivedTaskRun>, labels: Array<ArchivedLabel>, task_labels: Array<ArchivedTaskLabel>, 
+/**
+ * Missing from archives written before dependencies were exported
+ */
+task_dependencies: Array<ArchivedTaskDependency>, 
+/**
+ * Names only: values are encrypted with the exporting instance's key, so imported
+ * tasks declare the variables for the user to set again
+ */
+task_env_vars: Array<ArchivedTaskEnvVar>, executor_config: ArchivedExecutorConfig | null, 
+/**
+ * For reference only: webhooks are not imported since their URLs are redacted and
+ * their secrets left out
+ */
+webhooks: Array<ArchivedWebhook>, };
+
+export type ArchivedProject = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, base_branch: string, default_labels: Array<string>, 
+/**
+ * Archives exported before the rename name it `auto_commit`
+ */
+use_commit_template: boolean, git_commit_template: string | null, priority_boost_after_seconds: bigint | null, system_prompt: string | null, prompt_prefix: string | null, monthly_cost_limit_usd: number | null, };
+
+export type ArchivedTask = { id: string, title: string, description: string | null, status: TaskStatus, priority: bigint, complexity_score: number, timeout_seconds: bigint | null, executor_model: string | null, 
+/**
+ * Task this one is a sub-task of
+ */
+parent_task_id: string | null, 
+/**
+ * Attempt this task was created from
+ */
+parent_task_attempt: string | null, created_at: string, archived_at: string | null, started_at: string | null, completed_at: string | null, total_execution_ms: bigint | null, };
+
+export type ArchivedTaskAttempt = { id: string, task_id: string, 
+/**
+ * Worktree on the exporting machine; imported attempts are marked as having none
+ */
+worktree_path: string, branch: string, base_branch: string, executor: string | null, merge_commit: string | null, pr_url: string | null, pr_number: bigint | null, pr_status: string | null, created_at: string, };
+
+export type ArchivedTaskRun = { id: string, task_attempt_id: string, executor_type: string | null, 
+/**
+ * Runs still going when the project was exported are imported as killed
+ */
+status: ExecutionProcessStatus, command: string, args: string | null, working_directory: string, exit_code: bigint | null, had_tool_errors: boolean, 
+/**
+ * Normalized conversation as stored for the run
+ */
+normalized_json: string | null, 
+/**
+ * Raw executor output as a base64-encoded zstd frame
+ */
+logs_zstd_base64: string | null, started_at: string, completed_at: string | null, };
+
+export type ArchivedLabel = { id: string, name: string, color_hex: string, };
+
+export type ArchivedTaskLabel = { task_id: string, label_id: string, };
+
+export type ArchivedTaskDependency = { task_id: string, depends_on_task_id: string, };
+
+export type ArchivedTaskEnvVar = { task_id: string, key: string, };
+
+export type ArchivedExecutorConfig = { executor_type: string, binary_path: string, model: string | null, extra_flags: string | null, use_plan_mode: boolean, };
+
+export type ArchivedWebhook = { 
+/**
+ * Scheme and host only, e.g. `https://hooks.example.com/[redacted]`
+ */
+url: string, events: Array<string>, };
+
+export type ImportedProject = { project_id: string, 
+/**
+ * The archive had been imported before and no new project was created
+ */
+already_imported: boolean, };
+
+export type CreateTask = { project_id: string, title: string, description: string | null, parent_task_attempt: string | null, 
+/**
+ * Falls back to the project's default labels when empty
+ */
+labels?: Array<string>, timeout_seconds?: bigint, executor_model?: string, 
+/**
+ * Defaults to 50
+ */
+priority?: bigint, 
+/**
+ * Makes the new task a sub-task of another task of the same project
+ */
+parent_task_id?: string, };
+
+export type CreateTaskAndStart = { project_id: string, title: string, description: string | null, parent_task_attempt: string | null, executor: ExecutorConfig | null, labels?: Array<string>, timeout_seconds?: bigint, executor_model?: string, };
+
+export type CreateTaskBatchItem = { 
+/**
+ * Lets other tasks of the batch depend on this one; generated when omitted
+ */
+id?: string, title: string, description: string | null, 
+/**
+ * Board column the task starts in, `todo` when omitted
+ */
+status?: TaskStatus, executor_model?: string, 
+/**
+ * Tasks of the batch or of the project this task waits for
+ */
+dependencies: Array<string>, };
 
 export type TaskStatus = "todo" | "inprogress" | "inreview" | "done" | "cancelled";
 
-export type Task = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, created_at: string, updated_at: string, };
-
-export type TaskWithAttemptStatus = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, created_at: string, updated_at: string, has_in_progress_attempt: boolean, has_merged_attempt: boolean, last_attempt_failed: boolean, latest_attempt_executor: string | null, };
-
-export type UpdateTask = { title: string | null, description: string | null, status: TaskStatus | null, parent_task_attempt: string | null, };
-
-export type TaskTemplate = { id: string, project_id: string | null, title: string, description: string | null, template_name: string, created_at: string, updated_at: string, };
-
-export type CreateTaskTemplate = { project_id: string | null, title: string, description: string | null, template_name: string, };
-
-export type UpdateTaskTemplate = { title: string | null, description: string | null, template_name: string | null, };
+export type Task = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, created_at: string, updated_at: string, 
+/**
+ * Heuristic estimate of the work involved, from 0 to 10
+ */
+complexity_score: number, 
+/**
+ * Seconds an execution may run before it is killed, unconstrained when `None`
+ */
+timeout_seconds: bigint | null, 
+/**
+ * Executor session of the latest coding agent run, used to resume it in follow-ups
+ */
+session_id: string | null, 
+/**
+ * Model Claude runs for this task use, e.g. `claude-haiku-4`; the CLI default when `None`
+ */
+executor_model: string | null, 
+/**
+ * From 1 to 100; runs of higher-priority tasks take free executor slots first
+ */
+priority: bigint, 
+/**
+ * Task this one is a sub-task of
+ */
+parent_task_id: string | null, 
+/**
+ * When the task was deleted; archived tasks are purged for good after 30 days
+ */
+archived_at: string | null, };
+
+export type TaskWithAttemptStatus = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, created_at: string, updated_at: string, complexity_score: number, timeout_seconds: bigint | null, has_in_progress_attempt: boolean, has_merged_attempt: boolean, last_attempt_failed: boolean, latest_attempt_executor: string | null, parent_task_id: string | null, 
+/**
+ * Direct sub-tasks of the task
+ */
+subtask_count: bigint, completed_subtask_count: bigint, archived_at: string | null, 
+/**
+ * The task's labels, by name
+ */
+labels: Array<TaskLabel>, };
+
+export type UpdateTask = { title: string | null, description: string | null, status: TaskStatus | null, parent_task_attempt: string | null, 
+/**
+ * Leaves the current timeout unchanged when omitted; 0 removes it
+ */
+timeout_seconds?: bigint, 
+/**
+ * Leaves the current model unchanged when omitted; an empty string removes it
+ */
+executor_model?: string, 
+/**
+ * Leaves the current priority unchanged when omitted
+ */
+priority?: bigint, };
+
+export type TaskDependency = { task_id: string, depends_on_task_id: string, };
+
+export type AddTaskDependency = { depends_on_task_id: string, };
+
+export type SetTaskEnvVar = { value: string, };
+
+export type TaskGraphNode = { id: string, title: string, status: TaskStatus, };
+
+export type TaskGraphEdge = { from: string, to: string, 
+/**
+ * True once the blocking task is done
+ */
+satisfied: boolean, };
+
+export type TaskGraph = { nodes: Array<TaskGraphNode>, edges: Array<TaskGraphEdge>, };
+
+export type SimilarCompletion = { task_id: string, title: string, 
+/**
+ * When the task was last updated, i.e. moved to done
+ */
+completed_at: string, 
+/**
+ * First sentence of the summary of the task's latest coding agent run
+ */
+summary: string | null, };
+
+export type TaskEstimate = { word_count: number, 
+/**
+ * Heuristic from 0 to 10, see [`Task::complexity_score`]
+ */
+complexity_score: number, 
+/**
+ * `None` until the project has a completed coding agent run
+ */
+estimated_completion_ms: bigint | null, };
+
+export type TaskSubtasks = { subtasks: Array<Task>, 
+/**
+ * Share of the sub-tasks that are done, from 0 to 100
+ */
+completion_percentage: number, };
+
+export type TaskTimeTracking = { 
+/**
+ * `None` before the task's first run
+ */
+started_at: string | null, 
+/**
+ * `None` while the run is going
+ */
+completed_at: string | null, 
+/**
+ * Milliseconds from `started_at` to `completed_at`
+ */
+total_execution_ms: bigint | null, };
+
+export type TaskTimingStats = { 
+/**
+ * Tasks whose latest run completed within the time window
+ */
+completed_tasks: number, avg_task_execution_ms: number | null, p95_task_execution_ms: bigint | null, };
+
+export type Label = { id: string, project_id: string, 
+/**
+ * Unique within the project
+ */
+name: string, 
+/**
+ * `#rrggbb`
+ */
+color_hex: string, created_at: string, };
+
+export type TaskLabel = { id: string, name: string, color_hex: string, };
+
+export type CreateLabel = { project_id: string, name: string, 
+/**
+ * `#rrggbb`, gray when omitted
+ */
+color_hex?: string, };
+
+export type AttachLabel = { label_id: string, };
+
+export type TaskArtifact = { id: string, execution_process_id: string, filename: string, size_bytes: bigint, mime_type: string, stored_path: string, created_at: string, };
+
+export type TaskRun = { 
+/**
+ * Id of the execution process
+ */
+id: string, task_id: string, task_attempt_id: string, started_at: string, 
+/**
+ * `None` while the run is still going
+ */
+finished_at: string | null, exit_code: bigint | null, 
+/**
+ * Raw executor output, normalized with the executor named by `executor_type`
+ */
+logs: string | null, session_id: string | null, executor_type: string | null, 
+/**
+ * Worktree the run executed in
+ */
+worktree_path: string, 
+/**
+ * `None` until the worktree has been cleaned up
+ */
+cleanup_status: WorktreeCleanupStatus | null, 
+/**
+ * Whether a tool use of the run failed, e.g. a shell command exiting non-zero
+ */
+had_tool_errors: boolean, 
+/**
+ * Written by `ConversationSummarizer` for projects with `auto_summarize`
+ */
+summary: string | null, 
+/**
+ * Whether the run started a fresh session because the one it followed up on had more
+ * turns than the project's `max_context_turns`
+ */
+was_context_truncated: boolean, 
+/**
+ * How often the run called each of its most used base commands; `None` until the run
+ * finished
+ */
+command_counts: Record<string, number> | null, 
+/**
+ * How many output lines of the run were cut off for exceeding the line size limit
+ */
+truncated_line_count: bigint, };
+
+export type WorktreeCleanupStatus = "success" | "not_found" | "error";
+
+export type CancelledRuns = { cancelled: Array<string>, 
+/**
+ * Runs whose process could not be signalled; they are left running
+ */
+failed_to_cancel: Array<string>, };
+
+export type ConversationSearchResult = { task_id: string, run_id: string, 
+/**
+ * Excerpt of the entry with the matched terms wrapped in `**`
+ */
+snippet: string, 
+/**
+ * `type` tag of the entry, e.g. `tool_use` or `assistant_message`
+ */
+entry_type: string, };
+
+export type ToolUseStat = { tool_name: string, 
+/**
+ * `action` tag of the tool use, e.g. `command_run` or `file_read`
+ */
+action_type: string, count: bigint, 
+/**
+ * Average length of the entry content shown for each use
+ */
+avg_content_length: number, };
+
+export type HealthCheckResult = { id: string, timestamp: string, executor_name: string, healthy: boolean, error: string | null, latency_ms: bigint, };
+
+export type SystemCheckStatus = "ok" | "degraded" | "fail";
+
+export type SystemCheck = { name: string, status: SystemCheckStatus, latency_ms: bigint, detail: string | null, };
+
+export type SystemHealthReport = { status: SystemCheckStatus, checks: Array<SystemCheck>, };
+
+export type NotificationType = "task_completed" | "task_failed" | "status_changed";
+
+export type Notification = { id: string, user_id: string, task_id: string | null, type: NotificationType, message: string, 
+/**
+ * When the user marked the notification as read, `None` while unread
+ */
+read_at: string | null, created_at: string, };
+
+export type AuditLogEntry = { id: string, 
+/**
+ * `task` or `project`
+ */
+entity_type: string, entity_id: string, 
+/**
+ * What happened, e.g. `task.created` or `task.status_changed`
+ */
+action: string, actor_id: string, 
+/**
+ * The entity as JSON before the change, `None` for creations
+ */
+before_json: string | null, 
+/**
+ * The entity as JSON after the change, `None` for deletions
+ */
+after_json: string | null, created_at: string, };
+
+export type TaskTemplate = { id: string, project_id: string | null, title: string, description: string | null, template_name: string, 
+/**
+ * Model tasks created from the template run with; the CLI default when `None`
+ */
+executor_model: string | null, 
+/**
+ * Environment variables tasks created from the template get, without a value
+ */
+env_var_keys: Array<string>, created_at: string, updated_at: string, };
+
+export type CreateTaskTemplate = { project_id: string | null, title: string, description: string | null, template_name: string, executor_model?: string, env_var_keys?: Array<string>, };
+
+export type UpdateTaskTemplate = { title: string | null, description: string | null, template_name: string | null, 
+/**
+ * Leaves the current model unchanged when omitted; an empty string removes it
+ */
+executor_model?: string, env_var_keys?: Array<string>, };
+
+export type InstantiateTaskTemplate = { variables: { [key: string]: string }, };
+
+export type Webhook = { id: string, project_id: string, url: string, 
+/**
+ * Event names the webhook is subscribed to, e.g. `task.completed`
+ */
+events: Array<string>, created_at: string, };
+
+export type CreateWebhook = { url: string, events: Array<string>, secret: string, };
+
+export type WebhookDeliveryStatus = "pending" | "delivered" | "failed";
+
+export type WebhookDelivery = { id: string, webhook_id: string, event: string, 
+/**
+ * JSON body that is posted
+ */
+payload: string, status: WebhookDeliveryStatus, attempts: bigint, 
+/**
+ * HTTP status of the last attempt, `None` when no response arrived
+ */
+response_status: bigint | null, last_error: string | null, created_at: string, updated_at: string, };
 
 export type TaskAttemptStatus = "setuprunning" | "setupcomplete" | "setupfailed" | "executorrunning" | "executorcomplete" | "executorfailed";
 
@@ -68,7 +645,21 @@ export type CreateTaskAttempt = { executor: string | null, base_branch: string |
 
 export type UpdateTaskAttempt = Record<string, never>;
 
-export type CreateFollowUpAttempt = { prompt: string, };
+export type CreateFollowUpAttempt = { prompt: string, 
+/**
+ * Files handed to Claude with the prompt, relative to the worktree
+ */
+context_files?: Array<string>, 
+/**
+ * Pre-computed tool outputs injected into Claude's session ahead of the prompt
+ */
+tool_results?: Array<ToolResult>, };
+
+export type ResumeSession = { 
+/**
+ * Claude session ID, a UUID
+ */
+session_id: string, prompt: string, };
 
 export type DirectoryEntry = { name: string, path: string, is_directory: boolean, is_git_repo: boolean, };
 
@@ -76,7 +667,11 @@ export type DirectoryListResponse = { entries: Array<DirectoryEntry>, current_pa
 
 export type DeviceStartResponse = { device_code: string, user_code: string, verification_uri: string, expires_in: number, interval: number, };
 
-export type ProcessLogsResponse = { id: string, process_type: ExecutionProcessType, command: string, executor_type: string | null, status: ExecutionProcessStatus, normalized_conversation: NormalizedConversation, };
+export type ProcessLogsResponse = { id: string, process_type: ExecutionProcessType, command: string, executor_type: string | null, status: ExecutionProcessStatus, normalized_conversation: NormalizedConversation, bash_command_counts: { [key: string]: number }, 
+/**
+ * Wall-clock span of the conversation's timestamped entries; `None` without timestamps
+ */
+duration_ms: bigint | null, };
 
 export type DiffChunkType = "Equal" | "Insert" | "Delete";
 
@@ -110,24 +705,73 @@ export type CreateExecutorSession = { task_attempt_id: string, execution_process
 
 export type UpdateExecutorSession = { session_id: string | null, prompt: string | null, summary: string | null, };
 
-export type NormalizedConversation = { entries: Array<NormalizedEntry>, session_id: string | null, executor_type: string, prompt: string | null, summary: string | null, };
-
-export type NormalizedEntry = { timestamp: string | null, entry_type: NormalizedEntryType, content: string, };
-
-export type NormalizedEntryType = { "type": "user_message" } | { "type": "assistant_message" } | { "type": "tool_use", tool_name: string, action_type: ActionType, } | { "type": "system_message" } | { "type": "error_message" } | { "type": "thinking" };
-
-export type ActionType = { "action": "file_read", path: string, } | { "action": "file_write", path: string, } | { "action": "command_run", command: string, } | { "action": "search", query: string, } | { "action": "web_fetch", url: string, } | { "action": "task_create", description: string, } | { "action": "plan_presentation", plan: string, } | { "action": "other", description: string, };
+export type NormalizedConversation = { entries: Array<NormalizedEntry>, session_id: string | null, executor_type: string, prompt: string | null, summary: string | null, 
+/**
+ * Token usage and cost reported by the executor at the end of the run
+ */
+stats: ExecutionStats | null, metadata: ConversationMetadata, };
+
+export type ExecutionStats = { num_turns: number | null, duration_ms: bigint | null, total_cost_usd: number | null, input_tokens: bigint | null, output_tokens: bigint | null, };
+
+export type ConversationMetadata = { 
+/**
+ * Debug entries left out of `entries` because the executor does not include them
+ */
+debug_entry_count: number, };
+
+export type NormalizedEntry = { timestamp: string | null, entry_type: NormalizedEntryType, content: string, 
+/**
+ * How many identical consecutive entries this one stands for
+ */
+repeat_count: number, };
+
+export type NormalizedEntryType = { "type": "user_message" } | { "type": "assistant_message" } | { "type": "tool_use", tool_name: string, action_type: ActionType, } | { "type": "tool_result", tool_use_id: string, } | { "type": "tool_error", tool_use_id: string, 
+/**
+ * Exit code of a failed shell command, when the tool reported one
+ */
+exit_code: number | null, } | { "type": "system_message" } | { "type": "error_message" } | { "type": "thinking" } | { "type": "debug_trace" } | { "type": "timing" };
+
+export type ActionType = { "action": "file_read", path: string, } | { "action": "file_write", path: string, } | { "action": "file_delete", path: string, } | { "action": "file_move", from: string, to: string, } | { "action": "command_run", command: string, } | { "action": "search", query: string, } | { "action": "web_fetch", url: string, } | { "action": "task_create", description: string, } | { "action": "plan_presentation", plan: string, } | { "action": "computer_use", computer_action: string, coordinate: [number, number] | null, } | { "action": "other", description: string, };
+
+export type ConversationDiff = { 
+/**
+ * Entries only in the newer conversation, in its order
+ */
+added: Array<NormalizedEntry>, 
+/**
+ * Entries only in the older conversation, in its order
+ */
+removed: Array<NormalizedEntry>, 
+/**
+ * Entries found in both
+ */
+unchanged_count: number, };
+
+export type UnifiedFileDiff = { 
+/**
+ * Path after the change; the old path for deleted files
+ */
+path: string, hunks: Array<DiffHunk>, };
+
+export type DiffHunk = { old_start: number, new_start: number, old_count: number, new_count: number, lines: Array<DiffLine>, is_binary: boolean, };
+
+export type DiffLineKind = "context" | "added" | "removed";
+
+export type DiffLine = { kind: DiffLineKind, content: string, };
 
 // Generated constants
 export const EXECUTOR_TYPES: string[] = [
     "echo",
     "claude",
     "claude-plan",
+    "claude-plan-then-execute",
     "amp",
     "gemini",
     "charm-opencode",
     "claude-code-router",
-    "sst-opencode"
+    "sst-opencode",
+    "aider",
+    "mistral"
 ];
 
 export const EDITOR_TYPES: EditorType[] = [
@@ -143,11 +787,14 @@ export const EXECUTOR_LABELS: Record<string, string> = {
     "echo": "Echo (Test Mode)",
     "claude": "Claude",
     "claude-plan": "Claude Plan",
+    "claude-plan-then-execute": "Claude Plan then Execute",
     "amp": "Amp",
     "gemini": "Gemini",
     "charm-opencode": "Charm Opencode",
     "claude-code-router": "Claude Code Router",
-    "sst-opencode": "SST Opencode"
+    "sst-opencode": "SST Opencode",
+    "aider": "Aider",
+    "mistral": "Mistral Codestral"
 };
 
 export const EDITOR_LABELS: Record<string, string> = {